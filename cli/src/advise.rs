@@ -0,0 +1,228 @@
+//! Privacy advice for a pool
+//!
+//! A claim is only as private as the set of unclaimed deposits it could have
+//! come from, and claiming right after depositing into a quiet pool links the
+//! two by timing. This module turns an indexer deposits export into the
+//! pool's anonymity set, its recent claim rate and a suggested claim delay.
+
+use crate::types::IndexedDeposit;
+
+/// Below this many unclaimed deposits a claim offers little privacy
+pub const MIN_ANONYMITY_SET: usize = 10;
+
+/// Anonymity set size at which no size warning is given
+pub const RECOMMENDED_ANONYMITY_SET: usize = 50;
+
+/// Other pool events (deposits or claims) to wait out before claiming
+pub const TARGET_INTERVENING_EVENTS: u64 = 10;
+
+/// Shortest delay ever recommended (1 hour)
+pub const MIN_CLAIM_DELAY_SECS: u64 = 60 * 60;
+
+/// Longest delay ever recommended (30 days)
+pub const MAX_CLAIM_DELAY_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Something the user should know before depositing into or claiming from a pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// No deposits for the pool in the export
+    EmptyPool,
+    /// Anonymity set below [`MIN_ANONYMITY_SET`]
+    TooSmall { anonymity_set: usize },
+    /// Anonymity set below [`RECOMMENDED_ANONYMITY_SET`]
+    Small { anonymity_set: usize },
+    /// Nothing was deposited or claimed during the window
+    NoRecentActivity,
+    /// The export carries no timestamps, so rates could not be measured
+    NoTimestamps,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::EmptyPool => write!(f, "pool has no indexed deposits"),
+            Warning::TooSmall { anonymity_set } => write!(
+                f,
+                "only {} unclaimed deposit(s): too few for meaningful privacy (need {})",
+                anonymity_set, MIN_ANONYMITY_SET
+            ),
+            Warning::Small { anonymity_set } => write!(
+                f,
+                "{} unclaimed deposits: privacy is weak below {}",
+                anonymity_set, RECOMMENDED_ANONYMITY_SET
+            ),
+            Warning::NoRecentActivity => write!(
+                f,
+                "no deposits or claims in the window: a claim now stands out by timing"
+            ),
+            Warning::NoTimestamps => write!(
+                f,
+                "deposits export has no timestamps: re-export from the indexer to measure activity"
+            ),
+        }
+    }
+}
+
+/// Activity summary and recommendation for one pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolAdvice {
+    pub total_deposits: usize,
+    /// Unclaimed deposits a claim could have come from
+    pub anonymity_set: usize,
+    pub window_secs: u64,
+    pub deposits_in_window: usize,
+    pub claims_in_window: usize,
+    /// Claims per day over the window, if timestamps were available
+    pub claim_rate_per_day: Option<f64>,
+    pub recommended_delay_secs: u64,
+    pub warnings: Vec<Warning>,
+}
+
+impl PoolAdvice {
+    /// True when the pool is too small to use privately at all
+    pub fn is_unsafe(&self) -> bool {
+        self.warnings
+            .iter()
+            .any(|w| matches!(w, Warning::EmptyPool | Warning::TooSmall { .. }))
+    }
+}
+
+/// Summarise `pool` from an indexer export as of unix time `now`.
+///
+/// The recommended delay is the time the pool needs, at its recent rate of
+/// deposits and claims, to see [`TARGET_INTERVENING_EVENTS`] other events,
+/// clamped to [`MIN_CLAIM_DELAY_SECS`]..=[`MAX_CLAIM_DELAY_SECS`].
+pub fn advise(deposits: &[IndexedDeposit], pool: &str, now: u64, window_secs: u64) -> PoolAdvice {
+    let pool_deposits: Vec<_> = deposits.iter().filter(|d| d.pool == pool).collect();
+    let total_deposits = pool_deposits.len();
+    let anonymity_set = pool_deposits.iter().filter(|d| !d.claimed).count();
+
+    let since = now.saturating_sub(window_secs);
+    let recent = |at: Option<u64>| at.is_some_and(|t| t >= since && t <= now);
+    let deposits_in_window = pool_deposits.iter().filter(|d| recent(d.deposited_at)).count();
+    let claims_in_window = pool_deposits.iter().filter(|d| recent(d.claimed_at)).count();
+    let has_timestamps = pool_deposits
+        .iter()
+        .any(|d| d.deposited_at.is_some() || d.claimed_at.is_some());
+
+    let mut warnings = Vec::new();
+    if total_deposits == 0 {
+        warnings.push(Warning::EmptyPool);
+    } else if anonymity_set < MIN_ANONYMITY_SET {
+        warnings.push(Warning::TooSmall { anonymity_set });
+    } else if anonymity_set < RECOMMENDED_ANONYMITY_SET {
+        warnings.push(Warning::Small { anonymity_set });
+    }
+
+    let window_days = window_secs.max(1) as f64 / 86_400.0;
+    let (claim_rate_per_day, recommended_delay_secs) = if !has_timestamps {
+        if total_deposits > 0 {
+            warnings.push(Warning::NoTimestamps);
+        }
+        (None, MAX_CLAIM_DELAY_SECS)
+    } else {
+        let events = (deposits_in_window + claims_in_window) as u64;
+        let delay = match (TARGET_INTERVENING_EVENTS * window_secs).checked_div(events) {
+            Some(delay) => delay.clamp(MIN_CLAIM_DELAY_SECS, MAX_CLAIM_DELAY_SECS),
+            None => {
+                warnings.push(Warning::NoRecentActivity);
+                MAX_CLAIM_DELAY_SECS
+            }
+        };
+        (Some(claims_in_window as f64 / window_days), delay)
+    };
+
+    PoolAdvice {
+        total_deposits,
+        anonymity_set,
+        window_secs,
+        deposits_in_window,
+        claims_in_window,
+        claim_rate_per_day,
+        recommended_delay_secs,
+        warnings,
+    }
+}
+
+/// Human-readable duration, e.g. `3d 4h` or `45m`
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+    const NOW: u64 = 1_700_000_000;
+
+    fn deposit(pool: &str, deposited_at: Option<u64>, claimed_at: Option<u64>) -> IndexedDeposit {
+        IndexedDeposit {
+            pool: pool.to_string(),
+            leaf_index: 0,
+            commitment: String::new(),
+            amount: 1_000,
+            claimed: claimed_at.is_some(),
+            deposited_at,
+            claimed_at,
+        }
+    }
+
+    #[test]
+    fn test_small_pool_warns() {
+        let mut deposits: Vec<_> = (0..5).map(|_| deposit("poolA", Some(NOW - DAY), None)).collect();
+        deposits.push(deposit("poolA", Some(NOW - 2 * DAY), Some(NOW - DAY)));
+        deposits.push(deposit("poolB", Some(NOW - DAY), None));
+
+        let advice = advise(&deposits, "poolA", NOW, 7 * DAY);
+        assert_eq!(advice.total_deposits, 6);
+        assert_eq!(advice.anonymity_set, 5);
+        assert_eq!(advice.claims_in_window, 1);
+        assert!(advice.is_unsafe());
+        assert!(advice.warnings.contains(&Warning::TooSmall { anonymity_set: 5 }));
+
+        let empty = advise(&deposits, "poolC", NOW, 7 * DAY);
+        assert_eq!(empty.warnings, vec![Warning::EmptyPool]);
+    }
+
+    #[test]
+    fn test_delay_tracks_recent_activity() {
+        // Hundreds of events per day: ten pass well within the minimum delay
+        let busy: Vec<_> = (0..2000)
+            .map(|i| deposit("poolA", Some(NOW - i * 300), (i % 2 == 0).then_some(NOW - i * 150)))
+            .collect();
+        let advice = advise(&busy, "poolA", NOW, 7 * DAY);
+        assert_eq!(advice.recommended_delay_secs, MIN_CLAIM_DELAY_SECS);
+        assert!(advice.warnings.is_empty());
+        assert!(advice.claim_rate_per_day.unwrap() > 0.0);
+
+        // One deposit per day, nothing claimed: ten days
+        let slow: Vec<_> = (0..60).map(|i| deposit("poolA", Some(NOW - i * DAY), None)).collect();
+        let advice = advise(&slow, "poolA", NOW, 7 * DAY);
+        assert_eq!(advice.deposits_in_window, 8);
+        assert_eq!(advice.recommended_delay_secs, 10 * 7 * DAY / 8);
+        assert_eq!(advice.claim_rate_per_day, Some(0.0));
+
+        // Old deposits only
+        let quiet: Vec<_> = (0..60).map(|_| deposit("poolA", Some(NOW - 90 * DAY), None)).collect();
+        let advice = advise(&quiet, "poolA", NOW, 7 * DAY);
+        assert_eq!(advice.recommended_delay_secs, MAX_CLAIM_DELAY_SECS);
+        assert_eq!(advice.warnings, vec![Warning::NoRecentActivity]);
+    }
+
+    #[test]
+    fn test_export_without_timestamps() {
+        let deposits: Vec<_> = (0..60).map(|_| deposit("poolA", None, None)).collect();
+        let advice = advise(&deposits, "poolA", NOW, 7 * DAY);
+        assert_eq!(advice.claim_rate_per_day, None);
+        assert_eq!(advice.recommended_delay_secs, MAX_CLAIM_DELAY_SECS);
+        assert_eq!(advice.warnings, vec![Warning::NoTimestamps]);
+        assert_eq!(format_duration(MAX_CLAIM_DELAY_SECS), "30d 0h");
+        assert_eq!(format_duration(5_400), "1h 30m");
+    }
+}
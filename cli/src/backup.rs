@@ -0,0 +1,275 @@
+//! Encrypted backups of claim material
+//!
+//! Deposit notes and proof bundles are all a recipient needs to claim, so
+//! they must not sit in cloud storage in the clear. A backup packs the files
+//! into one blob sealed with XChaCha20-Poly1305 under a key derived from a
+//! passphrase with Argon2id. The Poly1305 tag covers the header (KDF
+//! parameters, salt, nonce) as associated data and every packed file, so a
+//! wrong passphrase and a tampered or truncated backup are both rejected
+//! before anything is written.
+//!
+//! Layout: `MAGIC || version (u8) || m_cost, t_cost, p_cost (u32 LE) ||
+//! salt (16) || nonce (24) || ciphertext || tag (16)`.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+/// Leading bytes of every backup
+pub const MAGIC: &[u8; 8] = b"MURKLBAK";
+
+/// Current backup format version
+pub const BACKUP_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+const TAG_LEN: usize = 16;
+
+/// Argon2id cost parameters, stored in the header so restores follow them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub m_cost: u32,
+    /// Passes over memory
+    pub t_cost: u32,
+    /// Lanes
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB, 3 passes, 1 lane
+    fn default() -> Self {
+        Self { m_cost: 64 * 1024, t_cost: 3, p_cost: 1 }
+    }
+}
+
+impl KdfParams {
+    /// Highest costs a backup may ask for: 1 GiB, 16 passes, 16 lanes. The
+    /// header is only authenticated after the key is derived, so a crafted
+    /// one must not get to pick an arbitrary amount of memory or time.
+    pub const MAX: Self = Self { m_cost: 1024 * 1024, t_cost: 16, p_cost: 16 };
+
+    /// Every cost is within [`KdfParams::MAX`]
+    pub fn within_limits(&self) -> bool {
+        self.m_cost <= Self::MAX.m_cost && self.t_cost <= Self::MAX.t_cost && self.p_cost <= Self::MAX.p_cost
+    }
+}
+
+/// A file carried in a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteFile {
+    /// File name, without directories
+    pub name: String,
+    /// Hex-encoded contents
+    pub contents: String,
+}
+
+impl NoteFile {
+    pub fn new(name: impl Into<String>, contents: &[u8]) -> Self {
+        Self { name: name.into(), contents: hex::encode(contents) }
+    }
+
+    /// Raw contents
+    pub fn bytes(&self) -> Result<Vec<u8>, BackupError> {
+        hex::decode(&self.contents).map_err(|_| BackupError::Corrupt("file contents are not hex"))
+    }
+}
+
+/// Why a backup could not be made or opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupError {
+    /// Not a backup, one written by a newer CLI, or one whose KDF costs
+    /// exceed [`KdfParams::MAX`]
+    UnsupportedFormat,
+    /// Wrong passphrase, or the backup was modified
+    AuthenticationFailed,
+    /// Decrypted fine but the contents are malformed
+    Corrupt(&'static str),
+    /// A file name that could escape the restore directory
+    UnsafeName(String),
+    /// KDF parameters rejected by Argon2
+    Kdf(String),
+}
+
+impl std::fmt::Display for BackupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupError::UnsupportedFormat => write!(f, "not a murkl backup (or from a newer version)"),
+            BackupError::AuthenticationFailed => {
+                write!(f, "wrong passphrase, or the backup has been modified")
+            }
+            BackupError::Corrupt(what) => write!(f, "corrupt backup: {}", what),
+            BackupError::UnsafeName(name) => write!(f, "refusing file name {:?}", name),
+            BackupError::Kdf(reason) => write!(f, "key derivation failed: {}", reason),
+        }
+    }
+}
+
+/// Seal `files` under `passphrase`
+pub fn seal(files: &[NoteFile], passphrase: &str, params: KdfParams) -> Result<Vec<u8>, BackupError> {
+    if !params.within_limits() {
+        return Err(BackupError::Kdf("costs exceed KdfParams::MAX, so the backup could not be restored".into()));
+    }
+    for file in files {
+        check_name(&file.name)?;
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut out = Vec::with_capacity(HEADER_LEN);
+    out.extend_from_slice(MAGIC);
+    out.push(BACKUP_VERSION);
+    for cost in [params.m_cost, params.t_cost, params.p_cost] {
+        out.extend_from_slice(&cost.to_le_bytes());
+    }
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+
+    let plaintext = serde_json::to_vec(files).expect("note files serialize");
+    let cipher = cipher(passphrase, &salt, params)?;
+    let sealed = cipher
+        .encrypt(&nonce, Payload { msg: &plaintext, aad: &out })
+        .map_err(|_| BackupError::Corrupt("encryption failed"))?;
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Open a backup made by [`seal`]
+pub fn open(backup: &[u8], passphrase: &str) -> Result<Vec<NoteFile>, BackupError> {
+    if backup.len() < HEADER_LEN + TAG_LEN || &backup[..MAGIC.len()] != MAGIC {
+        return Err(BackupError::UnsupportedFormat);
+    }
+    if backup[MAGIC.len()] != BACKUP_VERSION {
+        return Err(BackupError::UnsupportedFormat);
+    }
+    let (header, sealed) = backup.split_at(HEADER_LEN);
+    let cost = |i: usize| {
+        let at = MAGIC.len() + 1 + 4 * i;
+        u32::from_le_bytes(header[at..at + 4].try_into().unwrap())
+    };
+    let params = KdfParams { m_cost: cost(0), t_cost: cost(1), p_cost: cost(2) };
+    if !params.within_limits() {
+        return Err(BackupError::UnsupportedFormat);
+    }
+    let salt_at = MAGIC.len() + 1 + 12;
+    let salt = &header[salt_at..salt_at + SALT_LEN];
+    let nonce = XNonce::from_slice(&header[salt_at + SALT_LEN..]);
+
+    let plaintext = cipher(passphrase, salt, params)?
+        .decrypt(nonce, Payload { msg: sealed, aad: header })
+        .map_err(|_| BackupError::AuthenticationFailed)?;
+    let files: Vec<NoteFile> =
+        serde_json::from_slice(&plaintext).map_err(|_| BackupError::Corrupt("file list is not JSON"))?;
+    for file in &files {
+        check_name(&file.name)?;
+    }
+    Ok(files)
+}
+
+fn cipher(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<XChaCha20Poly1305, BackupError> {
+    let params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| BackupError::Kdf(e.to_string()))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| BackupError::Kdf(e.to_string()))?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    key.fill(0);
+    Ok(cipher)
+}
+
+/// Only bare file names are restored, so a crafted backup cannot write
+/// outside the target directory
+fn check_name(name: &str) -> Result<(), BackupError> {
+    let bare = std::path::Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name);
+    if name.is_empty() || !bare || name.contains(['/', '\\']) {
+        return Err(BackupError::UnsafeName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Whether a file in the working directory is claim material the CLI wrote
+/// (`deposit*.json`, `proof*.json`, `proof*.bin`)
+pub fn is_note_file(name: &str) -> bool {
+    (name.starts_with("deposit") && name.ends_with(".json"))
+        || (name.starts_with("proof") && (name.ends_with(".json") || name.ends_with(".bin")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters so tests stay fast
+    const TEST_PARAMS: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    fn notes() -> Vec<NoteFile> {
+        vec![
+            NoteFile::new("deposit.json", br#"{"identifier":"@alice"}"#),
+            NoteFile::new("proof.bin", &[0, 1, 2, 255]),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let backup = seal(&notes(), "correct horse", TEST_PARAMS).unwrap();
+        assert_eq!(&backup[..8], MAGIC);
+        let files = open(&backup, "correct horse").unwrap();
+        assert_eq!(files, notes());
+        assert_eq!(files[1].bytes().unwrap(), vec![0, 1, 2, 255]);
+
+        // Fresh salt and nonce each time
+        assert_ne!(seal(&notes(), "correct horse", TEST_PARAMS).unwrap(), backup);
+    }
+
+    #[test]
+    fn test_rejects_wrong_passphrase_and_tampering() {
+        let backup = seal(&notes(), "correct horse", TEST_PARAMS).unwrap();
+        assert_eq!(open(&backup, "battery staple"), Err(BackupError::AuthenticationFailed));
+
+        // Header bytes are authenticated too: here the salt
+        for at in [HEADER_LEN - NONCE_LEN - 1, HEADER_LEN + 3, backup.len() - 1] {
+            let mut tampered = backup.clone();
+            tampered[at] ^= 1;
+            assert_eq!(open(&tampered, "correct horse"), Err(BackupError::AuthenticationFailed));
+        }
+        assert_eq!(open(&backup[..backup.len() - 1], "correct horse"), Err(BackupError::AuthenticationFailed));
+
+        let mut other_version = backup.clone();
+        other_version[MAGIC.len()] = BACKUP_VERSION + 1;
+        assert_eq!(open(&other_version, "correct horse"), Err(BackupError::UnsupportedFormat));
+        assert_eq!(open(b"{}", "correct horse"), Err(BackupError::UnsupportedFormat));
+    }
+
+    #[test]
+    fn test_rejects_excessive_kdf_costs() {
+        let backup = seal(&notes(), "correct horse", TEST_PARAMS).unwrap();
+        // Refused from the header alone, before any memory is allocated
+        for cost in 0..3 {
+            let mut crafted = backup.clone();
+            let at = MAGIC.len() + 1 + 4 * cost;
+            crafted[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert_eq!(open(&crafted, "correct horse"), Err(BackupError::UnsupportedFormat));
+        }
+
+        let params = KdfParams { m_cost: KdfParams::MAX.m_cost + 1, ..TEST_PARAMS };
+        assert!(matches!(seal(&notes(), "correct horse", params), Err(BackupError::Kdf(_))));
+    }
+
+    #[test]
+    fn test_file_names() {
+        for name in ["../deposit.json", "/etc/passwd", "dir/proof.bin", "..", ""] {
+            let files = vec![NoteFile::new(name, b"x")];
+            assert_eq!(seal(&files, "pw", TEST_PARAMS), Err(BackupError::UnsafeName(name.to_string())));
+        }
+        assert!(is_note_file("deposit.json"));
+        assert!(is_note_file("deposit-bob.json"));
+        assert!(is_note_file("proof.bin"));
+        assert!(is_note_file("proof.json"));
+        assert!(!is_note_file("merkle.json"));
+        assert!(!is_note_file("deposits.json.bak"));
+    }
+}
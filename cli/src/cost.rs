@@ -0,0 +1,245 @@
+//! Lamport budget for submitting a claim
+//!
+//! A claim goes out as several transactions: create the proof buffer, one
+//! upload per chunk, finalize, then claim. A payer that runs dry partway is
+//! left with a half-written buffer holding its rent, so this module adds up
+//! everything the payer spends before the first transaction is sent.
+//!
+//! The relayer fee is not part of that sum: the program takes it out of the
+//! claimed amount, in pool tokens, and pays it to whoever submits the claim.
+
+use serde_json::{json, Value};
+
+/// Base fee per signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Proof bytes per `upload_chunk` transaction, as the relayer and client send them
+pub const DEFAULT_CHUNK_SIZE: usize = 896;
+
+/// Bytes ahead of the proof in a stark-verifier proof buffer
+pub const PROOF_BUFFER_HEADER: usize = 449;
+
+/// `NullifierRecord`: discriminator, pool, nullifier, claimed_at, bump
+pub const NULLIFIER_RECORD_SPACE: usize = 8 + 32 + 32 + 8 + 1;
+
+/// `ClaimReceipt`: discriminator, pool, recipient, two slots, claim count, bump
+pub const CLAIM_RECEIPT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 4 + 1;
+
+/// Compute a transaction requests per instruction when it sets no limit;
+/// the priority fee is charged on the request, not on what is used
+pub const COMPUTE_UNITS_PER_INSTRUCTION: u64 = 200_000;
+
+/// What is being submitted and how
+#[derive(Debug, Clone)]
+pub struct ClaimPlan {
+    pub proof_len: usize,
+    pub chunk_size: usize,
+    /// Compute unit price, in micro-lamports
+    pub priority_fee: u64,
+    /// The claim also creates the recipient's `ClaimReceipt`
+    pub with_receipt: bool,
+    /// Relayer fee in pool token base units
+    pub relayer_fee: u64,
+}
+
+/// One transaction of a claim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub signatures: u64,
+    /// Instructions besides compute budget ones
+    pub instructions: u64,
+}
+
+impl Transaction {
+    /// Base fee plus priority fee at `price` micro-lamports per unit
+    pub fn fee(&self, price: u64) -> u64 {
+        let units = self.instructions * COMPUTE_UNITS_PER_INSTRUCTION;
+        self.signatures * LAMPORTS_PER_SIGNATURE + (price * units).div_ceil(1_000_000)
+    }
+}
+
+/// Everything a claim costs its payer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimCost {
+    pub chunks: usize,
+    pub transactions: Vec<Transaction>,
+    /// Refunded when the buffer is closed after the claim
+    pub buffer_rent: u64,
+    pub nullifier_rent: u64,
+    pub receipt_rent: u64,
+    pub signature_fees: u64,
+    pub priority_fees: u64,
+    /// Taken from the claimed amount in pool tokens, not from the payer
+    pub relayer_fee: u64,
+}
+
+impl ClaimCost {
+    /// Lamports the payer must hold before the first transaction
+    pub fn total_lamports(&self) -> u64 {
+        self.buffer_rent + self.nullifier_rent + self.receipt_rent + self.signature_fees + self.priority_fees
+    }
+}
+
+/// Cost of `plan`, with `rent` giving the rent-exempt minimum for an
+/// account of that many bytes
+pub fn estimate(plan: &ClaimPlan, rent: impl Fn(usize) -> u64) -> ClaimCost {
+    let chunks = plan.proof_len.div_ceil(plan.chunk_size.max(1));
+    // Create buffer (the buffer keypair co-signs), chunks, finalize, claim
+    let mut transactions = vec![Transaction { signatures: 2, instructions: 2 }];
+    transactions.extend((0..chunks).map(|_| Transaction { signatures: 1, instructions: 1 }));
+    transactions.push(Transaction { signatures: 1, instructions: 1 });
+    transactions.push(Transaction { signatures: 1, instructions: 1 });
+
+    let signature_fees = transactions.iter().map(|tx| tx.fee(0)).sum::<u64>();
+    let priority_fees = transactions.iter().map(|tx| tx.fee(plan.priority_fee)).sum::<u64>() - signature_fees;
+    ClaimCost {
+        chunks,
+        buffer_rent: rent(PROOF_BUFFER_HEADER + plan.proof_len),
+        nullifier_rent: rent(NULLIFIER_RECORD_SPACE),
+        receipt_rent: if plan.with_receipt { rent(CLAIM_RECEIPT_SPACE) } else { 0 },
+        transactions,
+        signature_fees,
+        priority_fees,
+        relayer_fee: plan.relayer_fee,
+    }
+}
+
+/// Rent-exempt minimum under the default rent parameters (3480 lamports per
+/// byte-year, two years, 128 bytes of account overhead)
+pub fn default_rent(space: usize) -> u64 {
+    (128 + space as u64) * 3_480 * 2
+}
+
+/// Format lamports as SOL
+pub fn format_sol(lamports: u64) -> String {
+    format!("{}.{:09} SOL", lamports / 1_000_000_000, lamports % 1_000_000_000)
+}
+
+/// Why an RPC request failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// The node could not be reached or answered with an HTTP error
+    Transport(String),
+    /// The node returned a JSON-RPC error
+    Node { code: i64, message: String },
+    /// The response did not have the expected shape
+    Malformed(&'static str),
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Transport(e) => write!(f, "RPC request failed: {}", e),
+            RpcError::Node { code, message } => write!(f, "RPC error {}: {}", code, message),
+            RpcError::Malformed(method) => write!(f, "unexpected {} response", method),
+        }
+    }
+}
+
+/// Minimal Solana JSON-RPC client for the values the budget needs
+pub struct Rpc {
+    url: String,
+}
+
+impl Rpc {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string() }
+    }
+
+    fn call(&self, method: &'static str, params: Value) -> Result<Value, RpcError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = ureq::post(&self.url)
+            .send_json(body)
+            .map_err(|e| RpcError::Transport(e.to_string()))?
+            .into_json()
+            .map_err(|e| RpcError::Transport(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::Node {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        response.get("result").cloned().ok_or(RpcError::Malformed(method))
+    }
+
+    /// Lamports held by `address`
+    pub fn balance(&self, address: &str) -> Result<u64, RpcError> {
+        let result = self.call("getBalance", json!([address]))?;
+        result["value"].as_u64().ok_or(RpcError::Malformed("getBalance"))
+    }
+
+    /// Rent-exempt minimum for an account of `space` bytes
+    pub fn rent_exempt_minimum(&self, space: usize) -> Result<u64, RpcError> {
+        let result = self.call("getMinimumBalanceForRentExemption", json!([space]))?;
+        result.as_u64().ok_or(RpcError::Malformed("getMinimumBalanceForRentExemption"))
+    }
+
+    /// Median compute unit price over recent slots, in micro-lamports
+    pub fn recent_priority_fee(&self) -> Result<u64, RpcError> {
+        let result = self.call("getRecentPrioritizationFees", json!([]))?;
+        let fees = result
+            .as_array()
+            .ok_or(RpcError::Malformed("getRecentPrioritizationFees"))?
+            .iter()
+            .map(|entry| entry["prioritizationFee"].as_u64().ok_or(RpcError::Malformed("getRecentPrioritizationFees")))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(median(fees))
+    }
+}
+
+fn median(mut values: Vec<u64>) -> u64 {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(proof_len: usize) -> ClaimPlan {
+        ClaimPlan { proof_len, chunk_size: DEFAULT_CHUNK_SIZE, priority_fee: 0, with_receipt: false, relayer_fee: 0 }
+    }
+
+    #[test]
+    fn test_transactions_follow_upload() {
+        let cost = estimate(&plan(2_000), default_rent);
+        assert_eq!(cost.chunks, 3);
+        // create, 3 chunks, finalize, claim
+        assert_eq!(cost.transactions.len(), 6);
+        assert_eq!(cost.signature_fees, 7 * LAMPORTS_PER_SIGNATURE);
+        assert_eq!(cost.priority_fees, 0);
+        assert_eq!(estimate(&plan(1_792), default_rent).chunks, 2);
+    }
+
+    #[test]
+    fn test_rent_and_priority_fees() {
+        let cost = estimate(&plan(1_000), default_rent);
+        assert_eq!(cost.buffer_rent, default_rent(1_449));
+        assert_eq!(cost.nullifier_rent, 1_454_640);
+        assert_eq!(cost.receipt_rent, 0);
+        assert_eq!(
+            cost.total_lamports(),
+            cost.buffer_rent + cost.nullifier_rent + 6 * LAMPORTS_PER_SIGNATURE
+        );
+
+        let busy = estimate(&ClaimPlan { priority_fee: 1_001, with_receipt: true, relayer_fee: 50, ..plan(1_000) }, default_rent);
+        // 200k units requested per instruction, rounded up per transaction
+        let create = 2 * 200_000 * 1_001 / 1_000_000 + 1;
+        let others = 200_000 * 1_001 / 1_000_000 + 1;
+        assert_eq!(busy.priority_fees, create + 4 * others);
+        assert_eq!(busy.receipt_rent, default_rent(CLAIM_RECEIPT_SPACE));
+        // The relayer fee comes out of the claim, not the payer's balance
+        assert_eq!(busy.relayer_fee, 50);
+        assert_eq!(
+            busy.total_lamports(),
+            cost.total_lamports() + busy.priority_fees + busy.receipt_rent
+        );
+    }
+
+    #[test]
+    fn test_median_fee() {
+        assert_eq!(median(vec![]), 0);
+        assert_eq!(median(vec![5, 0, 100]), 5);
+        assert_eq!(format_sol(1_454_640), "0.001454640 SOL");
+    }
+}
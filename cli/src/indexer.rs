@@ -0,0 +1,486 @@
+//! Reorg-safe pool indexing
+//!
+//! An indexer following a pool over RPC sees deposits at `processed` or
+//! `confirmed` before they are final, and a fork can drop those slots after
+//! their leaves were appended. [`PoolIndexer`] keeps the leaves up to the
+//! last finalized slot as a checkpoint that is never rolled back, tracks
+//! later deposits with their slot and confirmation level, and on a rollback
+//! discards every deposit from the fork slot on. Roots are always recomputed
+//! from the checkpoint plus the surviving deposits, so a dropped leaf cannot
+//! linger in an export.
+//!
+//! Exports also list the root after every leaf whose slot the indexer saw,
+//! so a claimer can rebuild the tree at an older root the pool still
+//! accepts ([`MerkleData::at_root`]).
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{parse_hash, MerkleData, MerkleLeaf, RootCheckpoint, MERKLE_DATA_VERSION};
+
+/// Commitment level of a slot, as reported by the RPC node
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confirmation {
+    #[default]
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+/// One entry of an indexer feed (JSON lines, tagged by `event`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IndexerEvent {
+    /// A deposit appended `commitment` at `leaf_index` in `slot`
+    Deposit {
+        slot: u64,
+        leaf_index: u32,
+        /// Hex-encoded commitment (`0x` prefix optional)
+        commitment: String,
+        #[serde(default)]
+        confirmation: Confirmation,
+    },
+    /// `slot` and its ancestors reached `confirmation`
+    Confirmed { slot: u64, confirmation: Confirmation },
+    /// A fork dropped `slot` and every later slot
+    Rollback { slot: u64 },
+}
+
+/// Why an event could not be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexerError {
+    /// A deposit or rollback at or before the finalized checkpoint
+    BeforeCheckpoint { slot: u64, finalized_slot: u64 },
+    /// A finalized leaf reported with a different commitment
+    FinalizedConflict { leaf_index: u32 },
+    /// Leaf indices must follow the tree without holes
+    LeafGap { expected: u32, got: u32 },
+    /// A deposit in an earlier slot than the leaf before it
+    SlotOutOfOrder { slot: u64, previous_slot: u64 },
+    /// Commitment is not 32 hex-encoded bytes
+    InvalidCommitment(String),
+    /// Checkpoint export fails validation
+    InvalidCheckpoint(String),
+}
+
+impl std::fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexerError::BeforeCheckpoint { slot, finalized_slot } => write!(
+                f,
+                "slot {} is at or before the finalized checkpoint (slot {})",
+                slot, finalized_slot
+            ),
+            IndexerError::FinalizedConflict { leaf_index } => {
+                write!(f, "leaf {} is finalized with a different commitment", leaf_index)
+            }
+            IndexerError::LeafGap { expected, got } => {
+                write!(f, "expected leaf {}, got leaf {}", expected, got)
+            }
+            IndexerError::SlotOutOfOrder { slot, previous_slot } => write!(
+                f,
+                "deposit in slot {} follows a leaf from slot {}",
+                slot, previous_slot
+            ),
+            IndexerError::InvalidCommitment(reason) => write!(f, "invalid commitment: {}", reason),
+            IndexerError::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint: {}", reason),
+        }
+    }
+}
+
+/// A deposit past the finalized checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingDeposit {
+    slot: u64,
+    commitment: [u8; 32],
+    confirmation: Confirmation,
+}
+
+/// Tree state of one pool, rolled back by slot on reorgs
+#[derive(Debug, Clone)]
+pub struct PoolIndexer {
+    pool: String,
+    depth: usize,
+    /// Leaves up to `finalized_slot`; never rolled back
+    finalized: Vec<[u8; 32]>,
+    /// Deposit slots of the last `finalized_slots.len()` finalized leaves;
+    /// shorter than `finalized` after resuming from an export without roots
+    finalized_slots: Vec<u64>,
+    finalized_slot: u64,
+    /// Deposits after the checkpoint, in leaf order
+    pending: Vec<PendingDeposit>,
+}
+
+impl PoolIndexer {
+    /// Start indexing `pool` from an empty tree
+    pub fn new(pool: impl Into<String>) -> Self {
+        Self {
+            pool: pool.into(),
+            depth: murkl_prover::TREE_DEPTH,
+            finalized: Vec::new(),
+            finalized_slots: Vec::new(),
+            finalized_slot: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Resume from a finalized export (see [`PoolIndexer::snapshot`])
+    pub fn from_checkpoint(data: &MerkleData) -> Result<Self, IndexerError> {
+        let invalid = |e: murkl_prover::ProofError| IndexerError::InvalidCheckpoint(e.to_string());
+        data.validate().map_err(invalid)?;
+        let finalized = data
+            .leaves
+            .iter()
+            .map(|leaf| parse_hash(&leaf.commitment, "leaf"))
+            .collect::<Result<_, _>>()
+            .map_err(invalid)?;
+
+        // Keep the slots of the unbroken run of checkpoints ending at the last leaf
+        let mut finalized_slots = Vec::new();
+        let mut leaf_count = data.leaves.len() as u32;
+        for checkpoint in data.roots.iter().rev() {
+            if checkpoint.leaf_count != leaf_count || leaf_count == 0 {
+                break;
+            }
+            finalized_slots.push(checkpoint.slot);
+            leaf_count -= 1;
+        }
+        finalized_slots.reverse();
+
+        Ok(Self {
+            pool: data.pool.clone(),
+            depth: data.depth as usize,
+            finalized,
+            finalized_slots,
+            finalized_slot: data.last_indexed_slot,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Slot of the finalized checkpoint
+    pub fn finalized_slot(&self) -> u64 {
+        self.finalized_slot
+    }
+
+    /// Leaves indexed so far, finalized or not
+    pub fn leaf_count(&self) -> usize {
+        self.finalized.len() + self.pending.len()
+    }
+
+    /// Apply one feed entry; returns how many deposits a rollback dropped
+    pub fn apply(&mut self, event: &IndexerEvent) -> Result<usize, IndexerError> {
+        match event {
+            IndexerEvent::Deposit { slot, leaf_index, commitment, confirmation } => {
+                let commitment = parse_hash(commitment, "commitment")
+                    .map_err(|e| IndexerError::InvalidCommitment(e.to_string()))?;
+                self.deposit(*slot, *leaf_index, commitment, *confirmation)
+            }
+            IndexerEvent::Confirmed { slot, confirmation } => {
+                self.confirm(*slot, *confirmation);
+                Ok(0)
+            }
+            IndexerEvent::Rollback { slot } => self.rollback(*slot),
+        }
+    }
+
+    /// Record a deposit
+    ///
+    /// Replays of a known deposit only raise its confirmation. A different
+    /// commitment at an unfinalized leaf means the earlier one was on a
+    /// dropped fork: everything from its slot on is rolled back first, and
+    /// the number of dropped deposits is returned.
+    pub fn deposit(
+        &mut self,
+        slot: u64,
+        leaf_index: u32,
+        commitment: [u8; 32],
+        confirmation: Confirmation,
+    ) -> Result<usize, IndexerError> {
+        let index = leaf_index as usize;
+        if let Some(finalized) = self.finalized.get(index) {
+            return if *finalized == commitment {
+                Ok(0)
+            } else {
+                Err(IndexerError::FinalizedConflict { leaf_index })
+            };
+        }
+        if slot <= self.finalized_slot {
+            return Err(IndexerError::BeforeCheckpoint { slot, finalized_slot: self.finalized_slot });
+        }
+
+        let mut dropped = 0;
+        if let Some(known) = self.pending.get_mut(index - self.finalized.len()) {
+            if known.slot == slot && known.commitment == commitment {
+                known.confirmation = known.confirmation.max(confirmation);
+                return Ok(0);
+            }
+            let fork_slot = known.slot.min(slot);
+            tracing::warn!(leaf_index, slot, fork_slot, "conflicting deposit; rolling back fork");
+            dropped = self.rollback(fork_slot)?;
+        }
+
+        let expected = self.leaf_count() as u32;
+        if leaf_index != expected {
+            return Err(IndexerError::LeafGap { expected, got: leaf_index });
+        }
+        if let Some(previous) = self.pending.last() {
+            if slot < previous.slot {
+                return Err(IndexerError::SlotOutOfOrder { slot, previous_slot: previous.slot });
+            }
+        }
+        tracing::debug!(
+            slot,
+            leaf_index,
+            commitment = %crate::logging::hash_prefix(&commitment),
+            ?confirmation,
+            "deposit"
+        );
+        self.pending.push(PendingDeposit { slot, commitment, confirmation });
+        if confirmation == Confirmation::Finalized {
+            self.confirm(slot, confirmation);
+        }
+        Ok(dropped)
+    }
+
+    /// Raise every deposit up to `slot` to at least `confirmation`; at
+    /// `finalized` they move into the checkpoint
+    pub fn confirm(&mut self, slot: u64, confirmation: Confirmation) {
+        for deposit in self.pending.iter_mut().take_while(|d| d.slot <= slot) {
+            deposit.confirmation = deposit.confirmation.max(confirmation);
+        }
+        if confirmation == Confirmation::Finalized && slot > self.finalized_slot {
+            let count = self.pending.iter().take_while(|d| d.slot <= slot).count();
+            for deposit in self.pending.drain(..count) {
+                self.finalized.push(deposit.commitment);
+                self.finalized_slots.push(deposit.slot);
+            }
+            self.finalized_slot = slot;
+            tracing::debug!(slot, finalized = count, "checkpoint advanced");
+        }
+    }
+
+    /// Drop every deposit in `slot` or later; returns how many were dropped
+    pub fn rollback(&mut self, slot: u64) -> Result<usize, IndexerError> {
+        if slot <= self.finalized_slot {
+            return Err(IndexerError::BeforeCheckpoint { slot, finalized_slot: self.finalized_slot });
+        }
+        let kept = self.pending.iter().take_while(|d| d.slot < slot).count();
+        let dropped = self.pending.len() - kept;
+        self.pending.truncate(kept);
+        if dropped > 0 {
+            tracing::info!(slot, dropped, "rolled back");
+        }
+        Ok(dropped)
+    }
+
+    /// Tree export holding the leaves at `confirmation` or above
+    ///
+    /// Leaves stop at the first one below the level, since a later leaf
+    /// cannot outlive an earlier one on its fork. Root checkpoints cover
+    /// every exported leaf with a known slot.
+    pub fn snapshot(&self, confirmation: Confirmation) -> MerkleData {
+        let included: Vec<&PendingDeposit> =
+            self.pending.iter().take_while(|d| d.confirmation >= confirmation).collect();
+        let mut leaves = self.finalized.clone();
+        leaves.extend(included.iter().map(|d| d.commitment));
+        let root = murkl_prover::merkle::pool_root(&leaves, self.depth);
+
+        let first_known = self.finalized.len() - self.finalized_slots.len();
+        let slots = self.finalized_slots.iter().copied().chain(included.iter().map(|d| d.slot));
+        let roots = murkl_prover::merkle::pool_roots(&leaves, self.depth)
+            .into_iter()
+            .enumerate()
+            .skip(first_known)
+            .zip(slots)
+            .map(|((index, root), slot)| RootCheckpoint {
+                leaf_count: index as u32 + 1,
+                slot,
+                root: hex::encode(root),
+            })
+            .collect();
+
+        MerkleData {
+            version: MERKLE_DATA_VERSION,
+            pool: self.pool.clone(),
+            root: hex::encode(root),
+            depth: self.depth as u32,
+            last_indexed_slot: included.last().map_or(self.finalized_slot, |d| d.slot),
+            leaves: leaves
+                .iter()
+                .enumerate()
+                .map(|(index, leaf)| MerkleLeaf { index: index as u32, commitment: hex::encode(leaf) })
+                .collect(),
+            roots,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use murkl_prover::merkle::pool_root;
+
+    use Confirmation::*;
+
+    fn leaf(n: u8) -> [u8; 32] {
+        [n; 32]
+    }
+
+    fn root_of(leaves: &[[u8; 32]]) -> String {
+        hex::encode(pool_root(leaves, murkl_prover::TREE_DEPTH))
+    }
+
+    #[test]
+    fn test_snapshot_by_confirmation() {
+        let mut indexer = PoolIndexer::new("poolA");
+        indexer.deposit(10, 0, leaf(1), Finalized).unwrap();
+        indexer.deposit(11, 1, leaf(2), Confirmed).unwrap();
+        indexer.deposit(12, 2, leaf(3), Processed).unwrap();
+        assert_eq!(indexer.finalized_slot(), 10);
+
+        let finalized = indexer.snapshot(Finalized);
+        assert_eq!(finalized.leaves.len(), 1);
+        assert_eq!(finalized.last_indexed_slot, 10);
+        assert_eq!(finalized.root, root_of(&[leaf(1)]));
+
+        let confirmed = indexer.snapshot(Confirmed);
+        assert_eq!(confirmed.root, root_of(&[leaf(1), leaf(2)]));
+        assert_eq!(confirmed.last_indexed_slot, 11);
+        assert!(confirmed.validate().is_ok());
+
+        assert_eq!(indexer.snapshot(Processed).leaves.len(), 3);
+
+        // Replays only raise the level
+        indexer.deposit(12, 2, leaf(3), Confirmed).unwrap();
+        assert_eq!(indexer.snapshot(Confirmed).leaves.len(), 3);
+        indexer.confirm(12, Finalized);
+        assert_eq!(indexer.finalized_slot(), 12);
+        assert_eq!(indexer.snapshot(Finalized).root, root_of(&[leaf(1), leaf(2), leaf(3)]));
+    }
+
+    #[test]
+    fn test_reorg_rebuilds_from_checkpoint() {
+        let mut indexer = PoolIndexer::new("poolA");
+        indexer.deposit(10, 0, leaf(1), Finalized).unwrap();
+        indexer.deposit(11, 1, leaf(2), Confirmed).unwrap();
+        indexer.deposit(12, 2, leaf(3), Processed).unwrap();
+        indexer.deposit(13, 3, leaf(4), Processed).unwrap();
+
+        // Slots 12 and 13 were on a fork; the canonical chain put leaf 5 in slot 14
+        assert_eq!(indexer.rollback(12), Ok(2));
+        indexer.deposit(14, 2, leaf(5), Processed).unwrap();
+
+        let mut canonical = PoolIndexer::new("poolA");
+        canonical.deposit(10, 0, leaf(1), Finalized).unwrap();
+        canonical.deposit(11, 1, leaf(2), Confirmed).unwrap();
+        canonical.deposit(14, 2, leaf(5), Processed).unwrap();
+
+        let snapshot = indexer.snapshot(Processed);
+        assert_eq!(snapshot.root, canonical.snapshot(Processed).root);
+        assert_eq!(snapshot.root, root_of(&[leaf(1), leaf(2), leaf(5)]));
+        assert!(snapshot.validate().is_ok());
+
+        // The finalized checkpoint survives any rollback
+        assert_eq!(
+            indexer.rollback(10),
+            Err(IndexerError::BeforeCheckpoint { slot: 10, finalized_slot: 10 })
+        );
+        assert_eq!(indexer.rollback(11), Ok(2));
+        assert_eq!(indexer.snapshot(Processed).root, root_of(&[leaf(1)]));
+    }
+
+    #[test]
+    fn test_conflicting_leaf_rolls_back_fork() {
+        let mut indexer = PoolIndexer::new("poolA");
+        indexer.deposit(10, 0, leaf(1), Finalized).unwrap();
+        indexer.deposit(11, 1, leaf(2), Processed).unwrap();
+        indexer.deposit(12, 2, leaf(3), Processed).unwrap();
+
+        // Leaf 1 shows up with another commitment: slot 11 onwards was dropped
+        assert_eq!(indexer.deposit(13, 1, leaf(9), Confirmed), Ok(2));
+        assert_eq!(indexer.leaf_count(), 2);
+        assert_eq!(indexer.snapshot(Confirmed).root, root_of(&[leaf(1), leaf(9)]));
+
+        assert_eq!(
+            indexer.deposit(14, 0, leaf(7), Processed),
+            Err(IndexerError::FinalizedConflict { leaf_index: 0 })
+        );
+        assert_eq!(indexer.deposit(10, 0, leaf(1), Finalized), Ok(0));
+        assert_eq!(
+            indexer.deposit(15, 5, leaf(7), Processed),
+            Err(IndexerError::LeafGap { expected: 2, got: 5 })
+        );
+        assert_eq!(
+            indexer.deposit(12, 2, leaf(7), Processed),
+            Err(IndexerError::SlotOutOfOrder { slot: 12, previous_slot: 13 })
+        );
+    }
+
+    #[test]
+    fn test_feed_and_checkpoint_resume() {
+        let feed = [
+            r#"{"event":"deposit","slot":5,"leaf_index":0,"commitment":"0x0101010101010101010101010101010101010101010101010101010101010101"}"#,
+            r#"{"event":"deposit","slot":6,"leaf_index":1,"commitment":"0202020202020202020202020202020202020202020202020202020202020202"}"#,
+            r#"{"event":"confirmed","slot":5,"confirmation":"finalized"}"#,
+            r#"{"event":"rollback","slot":6}"#,
+        ];
+        let mut indexer = PoolIndexer::new("poolA");
+        let dropped: Vec<usize> = feed
+            .iter()
+            .map(|line| indexer.apply(&serde_json::from_str(line).unwrap()).unwrap())
+            .collect();
+        assert_eq!(dropped, vec![0, 0, 0, 1]);
+
+        let checkpoint = indexer.snapshot(Finalized);
+        assert_eq!(checkpoint.root, root_of(&[leaf(1)]));
+
+        let mut resumed = PoolIndexer::from_checkpoint(&checkpoint).unwrap();
+        assert_eq!(resumed.finalized_slot(), 5);
+        resumed.deposit(7, 1, leaf(3), Processed).unwrap();
+        assert_eq!(resumed.snapshot(Processed).root, root_of(&[leaf(1), leaf(3)]));
+    }
+
+    #[test]
+    fn test_root_checkpoints_follow_deposits() {
+        let mut indexer = PoolIndexer::new("poolA");
+        indexer.deposit(10, 0, leaf(1), Finalized).unwrap();
+        indexer.deposit(11, 1, leaf(2), Confirmed).unwrap();
+        indexer.deposit(11, 2, leaf(3), Confirmed).unwrap();
+        indexer.deposit(12, 3, leaf(4), Processed).unwrap();
+        indexer.rollback(12).unwrap();
+
+        let snapshot = indexer.snapshot(Processed);
+        let checkpoints: Vec<(u32, u64, String)> =
+            snapshot.roots.iter().map(|c| (c.leaf_count, c.slot, c.root.clone())).collect();
+        assert_eq!(
+            checkpoints,
+            vec![
+                (1, 10, root_of(&[leaf(1)])),
+                (2, 11, root_of(&[leaf(1), leaf(2)])),
+                (3, 11, root_of(&[leaf(1), leaf(2), leaf(3)])),
+            ]
+        );
+        assert!(snapshot.validate().is_ok());
+
+        // History survives a resume, and an export without it only loses the old slots
+        indexer.confirm(11, Finalized);
+        let mut resumed = PoolIndexer::from_checkpoint(&indexer.snapshot(Finalized)).unwrap();
+        resumed.deposit(13, 3, leaf(5), Processed).unwrap();
+        let snapshot = resumed.snapshot(Processed);
+        assert_eq!(snapshot.roots.len(), 4);
+        assert_eq!(snapshot.roots[3].slot, 13);
+
+        let mut bare = indexer.snapshot(Finalized);
+        bare.roots.clear();
+        let mut resumed = PoolIndexer::from_checkpoint(&bare).unwrap();
+        resumed.deposit(13, 3, leaf(5), Processed).unwrap();
+        let snapshot = resumed.snapshot(Processed);
+        assert_eq!(snapshot.roots.len(), 1);
+        assert_eq!(snapshot.roots[0].leaf_count, 4);
+        assert!(snapshot.validate().is_ok());
+
+        assert!(snapshot.at_root(&pool_root(&[leaf(1), leaf(2)], murkl_prover::TREE_DEPTH)).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! Diagnostic logging
+//!
+//! Command output stays on stdout. Diagnostics go to stderr through
+//! `tracing`, filtered by `MURKL_LOG` (env-filter directives such as
+//! `debug` or `murkl=debug`; `warn` when unset) and written as text or as
+//! JSON lines. JSON entries carry the current span (`prove`, `index`, ...)
+//! with its pool, leaf index and nullifier prefix, matching the relayer's
+//! `LOG_FORMAT=json` entries.
+
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding the log filter
+pub const LOG_ENV: &str = "MURKL_LOG";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Install the global subscriber
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_env(LOG_ENV).unwrap_or_else(|_| EnvFilter::new("warn"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().flatten_event(true).init(),
+    }
+}
+
+/// First bytes of a nullifier or commitment, enough to correlate entries
+pub fn hash_prefix(hash: &[u8; 32]) -> String {
+    hex::encode(&hash[..4])
+}
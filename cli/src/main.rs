@@ -4,14 +4,25 @@
 //! - deposit: Generate commitment from identifier + secret
 //! - prove: Generate STARK proof for claiming
 //! - claim: Submit claim transaction
+//! - recover: Find a lost leaf index from identifier + password
+//! - advise: Check a pool's anonymity set and get a recommended claim delay
+//! - cost: Add up the lamports a claim needs and check the payer's balance
+//! - note: Back up / restore deposit notes and proofs under a passphrase
 
 use clap::{Parser, Subcommand};
 use std::fs;
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+mod advise;
+mod backup;
+mod cost;
+mod indexer;
+mod logging;
 mod prover;
 mod types;
 
+use murkl_prover::{SecurityLevel, Verifier};
 use prover::MurklProver;
 use types::*;
 
@@ -19,6 +30,10 @@ use types::*;
 #[command(name = "murkl")]
 #[command(about = "Anonymous social transfers on Solana", long_about = None)]
 struct Cli {
+    /// Diagnostics format on stderr (filter with MURKL_LOG, e.g. MURKL_LOG=debug)
+    #[arg(long, global = true, value_enum, default_value_t = logging::LogFormat::Text)]
+    log_format: logging::LogFormat,
+    
     #[command(subcommand)]
     command: Commands,
 }
@@ -50,17 +65,55 @@ enum Commands {
         #[arg(short, long)]
         password: String,
         
-        /// Leaf index in Merkle tree
+        /// Leaf index in Merkle tree (looked up from the merkle data if omitted)
         #[arg(short, long)]
-        leaf_index: u32,
+        leaf_index: Option<u32>,
         
         /// Merkle tree data file (from pool)
         #[arg(short, long)]
         merkle: PathBuf,
         
+        /// Pool address (must match the merkle data; recorded in the proof bundle)
+        #[arg(long)]
+        pool: Option<String>,
+        
+        /// Current on-chain pool root (hex); refuses stale merkle data
+        #[arg(long)]
+        root: Option<String>,
+        
+        /// Prove against this earlier root (hex) from the merkle data's root history
+        #[arg(long)]
+        at_root: Option<String>,
+        
         /// Output proof file
         #[arg(short, long, default_value = "proof.bin")]
         output: PathBuf,
+        
+        /// Print time, hash count and size per prover stage
+        #[arg(long)]
+        timings: bool,
+        
+        /// Queries, blowup and proof of work: demo, standard96 or high128
+        #[arg(long, default_value = "standard96", value_parser = parse_security_level)]
+        security: SecurityLevel,
+        
+        /// Prove even if --security is below the CLI's minimum (standard96)
+        #[arg(long)]
+        insecure_override: bool,
+        
+        /// Grind this many proof-of-work bits before drawing queries
+        /// (defaults to the security level's)
+        #[arg(long)]
+        pow_bits: Option<u32>,
+        
+        /// Threads for the proof-of-work search (defaults to every core)
+        #[arg(long)]
+        threads: Option<usize>,
+        
+        /// Checkpoint file: resumes from it if present, saved to after each
+        /// costly stage (the proof-of-work nonce)
+        #[arg(long)]
+        resume: Option<PathBuf>,
     },
     
     /// Verify a proof locally
@@ -72,6 +125,19 @@ enum Commands {
         /// Public commitment
         #[arg(short, long)]
         commitment: String,
+        
+        /// Nullifier (hex); with --root and --recipient, runs the on-chain
+        /// verifier's exact check
+        #[arg(long, requires_all = ["root", "recipient"])]
+        nullifier: Option<String>,
+        
+        /// Pool root the proof targets (hex)
+        #[arg(long, requires_all = ["nullifier", "recipient"])]
+        root: Option<String>,
+        
+        /// Recipient token account the proof binds (hex)
+        #[arg(long, requires_all = ["nullifier", "root"])]
+        recipient: Option<String>,
     },
     
 /// Show deposit info
@@ -81,6 +147,104 @@ enum Commands {
         input: PathBuf,
     },
     
+    /// Recover leaf index and amount for a deposit from identifier + password
+    Recover {
+        /// Social identifier
+        #[arg(short, long)]
+        identifier: String,
+        
+        /// Password
+        #[arg(short, long)]
+        password: String,
+        
+        /// Pool address the deposit was made to
+        #[arg(long)]
+        pool: String,
+        
+        /// Indexed deposits export (JSON array of {pool, leaf_index, commitment, amount, claimed})
+        #[arg(short, long, default_value = "deposits.json")]
+        deposits: PathBuf,
+        
+        /// Used nullifiers for the pool (JSON array of hex strings)
+        #[arg(short, long)]
+        nullifiers: Option<PathBuf>,
+    },
+    
+    /// Report a pool's anonymity set and claim rate, and recommend a claim delay
+    Advise {
+        /// Pool address
+        #[arg(long)]
+        pool: String,
+        
+        /// Indexed deposits export (with deposited_at / claimed_at timestamps)
+        #[arg(short, long, default_value = "deposits.json")]
+        deposits: PathBuf,
+        
+        /// Window for measuring recent activity, in hours
+        #[arg(short, long, default_value_t = 168)]
+        window_hours: u64,
+    },
+    
+    /// Add up the lamports a claim needs (rent, fees) and check the payer can cover them
+    Cost {
+        /// Proof bundle (from `prove`)
+        #[arg(short, long, default_value = "proof.json")]
+        proof: PathBuf,
+        
+        /// Solana RPC endpoint for rent, recent priority fees and the payer balance
+        #[arg(long)]
+        rpc: Option<String>,
+        
+        /// Wallet paying for the buffer, nullifier record and fees (needs --rpc)
+        #[arg(long)]
+        payer: Option<String>,
+        
+        /// Compute unit price in micro-lamports (default: recent median from --rpc)
+        #[arg(long)]
+        priority_fee: Option<u64>,
+        
+        /// Relayer fee in pool token base units (paid out of the claim)
+        #[arg(long, default_value_t = 0)]
+        relayer_fee: u64,
+        
+        /// Proof bytes per upload transaction
+        #[arg(long, default_value_t = cost::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+        
+        /// The claim also creates the recipient's claim receipt
+        #[arg(long)]
+        receipt: bool,
+    },
+    
+    /// Replay an indexer feed into a merkle data export, undoing forked slots
+    Index {
+        /// Pool address
+        #[arg(long)]
+        pool: String,
+        
+        /// Indexer feed: one JSON event per line (deposit / confirmed / rollback)
+        #[arg(short, long, default_value = "events.jsonl")]
+        events: PathBuf,
+        
+        /// Finalized merkle data export to resume from
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        
+        /// Lowest confirmation level a leaf needs to be exported
+        #[arg(short, long, value_enum, default_value_t = indexer::Confirmation::Confirmed)]
+        confirmation: indexer::Confirmation,
+        
+        /// Output merkle data file (input for `prove`)
+        #[arg(short, long, default_value = "merkle.json")]
+        output: PathBuf,
+    },
+    
+    /// Encrypted backups of deposit notes and proofs
+    Note {
+        #[command(subcommand)]
+        action: NoteAction,
+    },
+    
     /// Compute commitment from identifier + password (for verification)
     Hash {
         /// Social identifier
@@ -93,22 +257,84 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum NoteAction {
+    /// Encrypt deposit notes and proof bundles into one backup file
+    Backup {
+        /// Files to back up (default: deposit*.json, proof*.json, proof*.bin in --dir)
+        files: Vec<PathBuf>,
+        
+        /// Directory scanned when no files are given
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+        
+        /// Passphrase the backup key is derived from
+        #[arg(short, long)]
+        passphrase: String,
+        
+        /// Output backup file
+        #[arg(short, long, default_value = "backup.enc")]
+        out: PathBuf,
+    },
+    
+    /// Decrypt a backup and write its files back out
+    Restore {
+        /// Backup file
+        #[arg(short, long, default_value = "backup.enc")]
+        input: PathBuf,
+        
+        /// Passphrase the backup was made with
+        #[arg(short, long)]
+        passphrase: String,
+        
+        /// Directory to restore into
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+        
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+}
+
 fn main() {
     let cli = Cli::parse();
+    logging::init(cli.log_format);
     
     match cli.command {
         Commands::Commit { identifier, password, output } => {
             cmd_commit(&identifier, &password, &output);
         }
-        Commands::Prove { identifier, password, leaf_index, merkle, output } => {
-            cmd_prove(&identifier, &password, leaf_index, &merkle, &output);
+        Commands::Prove { identifier, password, leaf_index, merkle, pool, root, at_root, output, timings, security, insecure_override, pow_bits, threads, resume } => {
+            let level = ProveLevel { security, insecure_override, pow_bits };
+            cmd_prove(&identifier, &password, leaf_index, &merkle, pool, root, at_root, &output, timings, level, threads, resume);
         }
-        Commands::Verify { proof, commitment } => {
-            cmd_verify(&proof, &commitment);
+        Commands::Verify { proof, commitment, nullifier, root, recipient } => {
+            let statement = nullifier.zip(root).zip(recipient).map(|((n, r), p)| [n, r, p]);
+            cmd_verify(&proof, &commitment, statement);
         }
         Commands::Info { input } => {
             cmd_info(&input);
         }
+        Commands::Recover { identifier, password, pool, deposits, nullifiers } => {
+            cmd_recover(&identifier, &password, &pool, &deposits, nullifiers.as_ref());
+        }
+        Commands::Advise { pool, deposits, window_hours } => {
+            cmd_advise(&pool, &deposits, window_hours);
+        }
+        Commands::Cost { proof, rpc, payer, priority_fee, relayer_fee, chunk_size, receipt } => {
+            let plan = cost::ClaimPlan { proof_len: 0, chunk_size, priority_fee: 0, with_receipt: receipt, relayer_fee };
+            cmd_cost(&proof, rpc.as_deref(), payer.as_deref(), priority_fee, plan);
+        }
+        Commands::Index { pool, events, checkpoint, confirmation, output } => {
+            cmd_index(&pool, &events, checkpoint.as_ref(), confirmation, &output);
+        }
+        Commands::Note { action: NoteAction::Backup { files, dir, passphrase, out } } => {
+            cmd_note_backup(&files, &dir, &passphrase, &out);
+        }
+        Commands::Note { action: NoteAction::Restore { input, passphrase, dir, force } } => {
+            cmd_note_restore(&input, &passphrase, &dir, force);
+        }
         Commands::Hash { identifier, password } => {
             cmd_hash(&identifier, &password);
         }
@@ -150,8 +376,57 @@ fn cmd_commit(identifier: &str, password: &str, output: &PathBuf) {
     println!("   3. Recipient claims with: murkl prove -i {} -p {}", identifier, password);
 }
 
-fn cmd_prove(identifier: &str, password: &str, leaf_index: u32, merkle: &PathBuf, output: &PathBuf) {
+/// Weakest level `prove` emits without --insecure-override
+const MIN_SECURITY_LEVEL: SecurityLevel = SecurityLevel::Standard96;
+
+fn parse_security_level(name: &str) -> Result<SecurityLevel, String> {
+    SecurityLevel::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = SecurityLevel::ALL.iter().map(|level| level.name()).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+/// How strong a proof `prove` emits
+struct ProveLevel {
+    security: SecurityLevel,
+    insecure_override: bool,
+    /// Overrides the level's proof-of-work bits
+    pow_bits: Option<u32>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_prove(
+    identifier: &str,
+    password: &str,
+    requested_index: Option<u32>,
+    merkle: &PathBuf,
+    pool: Option<String>,
+    expected_root: Option<String>,
+    target_root: Option<String>,
+    output: &PathBuf,
+    timings: bool,
+    level: ProveLevel,
+    threads: Option<usize>,
+    resume: Option<PathBuf>,
+) {
     println!("🐈‍⬛ Murkl - Generating STARK proof\n");
+    if level.security < MIN_SECURITY_LEVEL {
+        if !level.insecure_override {
+            eprintln!(
+                "   ❌ {} is below the minimum of {}; pass --insecure-override to prove anyway",
+                level.security, MIN_SECURITY_LEVEL
+            );
+            std::process::exit(1);
+        }
+        println!("   ⚠️  Proving at {}, below {} (--insecure-override)", level.security, MIN_SECURITY_LEVEL);
+    }
+    let span = tracing::info_span!(
+        "prove",
+        pool = tracing::field::Empty,
+        leaf_index = tracing::field::Empty,
+        nullifier = tracing::field::Empty,
+    )
+    .entered();
     
     // Derive values from identifier + password
     let id_hash = hash_identifier(identifier);
@@ -160,40 +435,129 @@ fn cmd_prove(identifier: &str, password: &str, leaf_index: u32, merkle: &PathBuf
     
     println!("   Identifier: {}", identifier);
     println!("   Commitment: 0x{}", hex::encode(&commitment[..8]));
-    println!("   Leaf index: {}", leaf_index);
     
     // Load merkle tree
     let merkle_json = fs::read_to_string(merkle).expect("Failed to read merkle data");
-    let merkle_data: MerkleData = serde_json::from_str(&merkle_json).expect("Invalid merkle data");
+    let parse_root = |hex_root: String, flag: &str| {
+        hex::decode(hex_root.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .unwrap_or_else(|| panic!("Invalid {} (expected 32-byte hex)", flag))
+    };
+    let expected_root = expected_root.map(|hex_root| parse_root(hex_root, "--root"));
+    let target_root = target_root.map(|hex_root| parse_root(hex_root, "--at-root"));
+    let merkle_data = match MerkleData::from_json(&merkle_json)
+        .and_then(|data| data.check_current(pool.as_deref(), expected_root.as_ref()).map(|_| data))
+        .and_then(|data| match &target_root {
+            Some(root) => data.at_root(root),
+            None => Ok(data),
+        })
+    {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("   ❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("   Merkle data: {} leaves, slot {}", merkle_data.leaves.len(), merkle_data.last_indexed_slot);
+    if let Some(root) = &target_root {
+        println!("   Target root: 0x{}", hex::encode(&root[..8]));
+    }
+    span.record("pool", merkle_data.pool.as_str());
+    tracing::debug!(leaves = merkle_data.leaves.len(), slot = merkle_data.last_indexed_slot, "merkle data loaded");
     
-    // Verify commitment is in tree
-    if let Some(found_idx) = merkle_data.find_leaf(&commitment.to_vec()) {
-        if found_idx != leaf_index {
-            println!("   ⚠️  Warning: commitment found at index {} but you specified {}", found_idx, leaf_index);
+    // Locate commitment in tree (validated against the stored root)
+    let witness = match merkle_data.witness_for(&commitment) {
+        Ok(witness) => witness,
+        Err(e) => {
+            eprintln!("   ❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let leaf_index = witness.leaf_index as u32;
+    if let Some(requested) = requested_index {
+        if merkle_data.leaf(requested) != Some(commitment) {
+            eprintln!("   ❌ Commitment is not at index {} (found at {})", requested, leaf_index);
+            std::process::exit(1);
         }
     }
+    let leaf_index = requested_index.unwrap_or(leaf_index);
+    println!("   Leaf index: {}", leaf_index);
     
     // Compute nullifier = hash(secret, leaf_index)
-    let nullifier = m31_hash2(secret, leaf_index);
+    let nullifier = pq_nullifier(secret, leaf_index);
     println!("   Nullifier: 0x{}", hex::encode(&nullifier[..8]));
+    span.record("leaf_index", leaf_index);
+    span.record("nullifier", logging::hash_prefix(&nullifier).as_str());
     
     // Generate STARK proof
     println!("\n   Generating STARK proof...");
-    let prover = MurklProver::new();
-    let proof = prover.generate_proof(
-        id_hash,
-        secret,
-        leaf_index,
-        &merkle_data,
-    );
+    let started = std::time::Instant::now();
+    let pow_bits = level.pow_bits.unwrap_or(level.security.pow_bits());
+    let mut prover = MurklProver::new().with_security_level(level.security).with_pow(pow_bits, 1);
+    if let Some(security) = prover.security_level() {
+        println!("   Security: {}", security);
+    }
+    if pow_bits > 0 {
+        let threads = threads
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1);
+        println!("   Grinding {} proof-of-work bits on {} threads...", pow_bits, threads);
+        prover = prover.with_pow(pow_bits, threads).with_grind_progress(|progress| {
+            eprint!("\r   {} of ~{} nonces tried", progress.attempts, progress.expected);
+        });
+    }
+    if let Some(path) = resume {
+        let checkpoint = match fs::read(&path) {
+            Ok(bytes) => match murkl_prover::Checkpoint::from_bytes(&bytes) {
+                Ok(checkpoint) => {
+                    println!("   Resuming from {:?}", path);
+                    Some(checkpoint)
+                }
+                Err(e) => {
+                    eprintln!("   ❌ {:?} is not a usable checkpoint: {}", path, e);
+                    std::process::exit(1);
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                eprintln!("   ❌ Failed to read {:?}: {}", path, e);
+                std::process::exit(1);
+            }
+        };
+        prover = prover.with_checkpoint(checkpoint, move |checkpoint| {
+            if let Err(e) = fs::write(&path, checkpoint.to_bytes()) {
+                eprintln!("\n   ⚠️  Failed to save checkpoint to {:?}: {}", path, e);
+            }
+        });
+    }
+    let proved = if timings {
+        let recorder = murkl_prover::StageRecorder::new(murkl_prover::metrics::system_clock);
+        prover
+            .generate_proof_with_metrics(id_hash, secret, leaf_index, &merkle_data, recorder)
+            .map(|(proof, metrics)| (proof, Some(metrics)))
+    } else {
+        prover.generate_proof(id_hash, secret, leaf_index, &merkle_data).map(|proof| (proof, None))
+    };
+    let (proof, metrics) = match proved {
+        Ok(proved) => proved,
+        Err(e) => {
+            eprintln!("   ❌ {}", e);
+            std::process::exit(1);
+        }
+    };
     
     // Save proof bundle (proof + public inputs)
-    let proof_bundle = ProofBundle {
-        proof: proof.serialize(),
-        commitment: commitment.to_vec(),
-        nullifier: nullifier.to_vec(),
-        leaf_index,
-    };
+    let mut proof_bundle = ProofBundle::new(proof.serialize(), commitment, nullifier, leaf_index)
+        .with_pool(merkle_data.pool.clone());
+    if let Some(nonce) = proof.pow_nonce {
+        eprintln!();
+        println!("   Proof-of-work nonce: {}", nonce);
+        proof_bundle = proof_bundle.with_pow_nonce(nonce);
+    }
+    if let Some(root) = &target_root {
+        proof_bundle = proof_bundle.with_merkle_root(root);
+    }
     
     let bundle_json = serde_json::to_string_pretty(&proof_bundle).unwrap();
     fs::write(output.with_extension("json"), &bundle_json).expect("Failed to write proof bundle");
@@ -201,21 +565,52 @@ fn cmd_prove(identifier: &str, password: &str, leaf_index: u32, merkle: &PathBuf
     // Also save raw proof
     fs::write(output, &proof_bundle.proof).expect("Failed to write proof");
     
+    tracing::info!(
+        proof_bytes = proof_bundle.proof.len(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "proof generated"
+    );
     println!("   Proof size: {} bytes", proof_bundle.proof.len());
+    if let Some(metrics) = metrics {
+        println!("\n   Prover stages:");
+        for line in metrics.to_string().lines() {
+            println!("   {}", line);
+        }
+    }
     println!("\n✅ Proof saved to {:?}", output);
     println!("✅ Proof bundle saved to {:?}", output.with_extension("json"));
     println!("\n📋 NEXT STEP: Submit to relayer with your wallet address");
 }
 
-fn cmd_verify(proof_path: &PathBuf, commitment: &str) {
+fn cmd_verify(proof_path: &PathBuf, commitment: &str, statement: Option<[String; 3]>) {
     println!("🐈‍⬛ Murkl - Verifying proof\n");
     
     let proof_bytes = fs::read(proof_path).expect("Failed to read proof");
     let commitment_bytes = hex::decode(commitment.trim_start_matches("0x"))
         .expect("Invalid commitment hex");
     
+    // With the whole statement, run what stark-verifier runs
+    if let Some([nullifier, root, recipient]) = statement {
+        let parse = |hex_value: &str, flag: &str| -> [u8; 32] {
+            hex::decode(hex_value.trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .unwrap_or_else(|| panic!("Invalid {} (expected 32-byte hex)", flag))
+        };
+        let commitment = parse(commitment, "--commitment");
+        let (nullifier, root, recipient) = (parse(&nullifier, "--nullifier"), parse(&root, "--root"), parse(&recipient, "--recipient"));
+        match Verifier::verify_bytes(&proof_bytes, &commitment, &nullifier, &root, &recipient) {
+            Ok(()) => println!("✅ Proof is VALID! The on-chain verifier will accept it."),
+            Err(e) => {
+                println!("❌ Proof is INVALID: {} (error {})", e, e.code());
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    
     let prover = MurklProver::new();
-    let proof = MurklProof::deserialize(&proof_bytes);
+    let proof = MurklProof::deserialize(&proof_bytes).expect("Invalid proof");
     
     let valid = prover.verify_proof(&proof, &commitment_bytes);
     
@@ -224,6 +619,7 @@ fn cmd_verify(proof_path: &PathBuf, commitment: &str) {
     } else {
         println!("❌ Proof is INVALID!");
     }
+    println!("   Structure only; pass --nullifier, --root and --recipient for the on-chain check");
 }
 
 fn cmd_info(input: &PathBuf) {
@@ -241,9 +637,17 @@ fn cmd_info(input: &PathBuf) {
     
     // Try ProofBundle
     if let Ok(bundle) = serde_json::from_str::<ProofBundle>(&json) {
-        println!("   Commitment: 0x{}", hex::encode(&bundle.commitment[..8.min(bundle.commitment.len())]));
-        println!("   Nullifier: 0x{}", hex::encode(&bundle.nullifier[..8.min(bundle.nullifier.len())]));
+        if let Err(e) = bundle.check_version() {
+            println!("   ❌ {}", e);
+            return;
+        }
+        println!("   Version: {}", bundle.version);
+        println!("   Commitment: 0x{}", hex::encode(&bundle.commitment[..8]));
+        println!("   Nullifier: 0x{}", hex::encode(&bundle.nullifier[..8]));
         println!("   Leaf index: {}", bundle.leaf_index);
+        if let Some(pool) = &bundle.pool {
+            println!("   Pool: {}", pool);
+        }
         println!("   Proof size: {} bytes", bundle.proof.len());
         return;
     }
@@ -251,13 +655,311 @@ fn cmd_info(input: &PathBuf) {
     println!("   ❌ Unknown file format");
 }
 
+fn cmd_recover(
+    identifier: &str,
+    password: &str,
+    pool: &str,
+    deposits_path: &PathBuf,
+    nullifiers_path: Option<&PathBuf>,
+) {
+    println!("🐈‍⬛ Murkl - Recovering deposit\n");
+    let _span = tracing::info_span!("recover", pool).entered();
+    
+    let id_hash = hash_identifier(identifier);
+    let secret = hash_password(password);
+    let commitment = m31_hash2(id_hash, secret);
+    
+    println!("   Identifier: {}", identifier);
+    println!("   Pool: {}", pool);
+    println!("   Commitment: 0x{}", hex::encode(&commitment[..8]));
+    
+    let deposits_json = fs::read_to_string(deposits_path).expect("Failed to read deposits");
+    let deposits: Vec<IndexedDeposit> = serde_json::from_str(&deposits_json).expect("Invalid deposits data");
+    
+    let used: Vec<Vec<u8>> = match nullifiers_path {
+        Some(path) => {
+            let json = fs::read_to_string(path).expect("Failed to read nullifiers");
+            let hexes: Vec<String> = serde_json::from_str(&json).expect("Invalid nullifiers data");
+            hexes
+                .iter()
+                .map(|h| hex::decode(h.trim_start_matches("0x")).expect("Invalid nullifier hex"))
+                .collect()
+        }
+        None => vec![],
+    };
+    
+    let matches = find_deposits(&deposits, pool, &commitment);
+    if matches.is_empty() {
+        eprintln!("\n   ❌ No deposit for this identifier + password in {} indexed deposits", deposits.len());
+        eprintln!("      Check the identifier spelling, password and pool address.");
+        std::process::exit(1);
+    }
+    
+    println!("\n   Found {} deposit(s):", matches.len());
+    let mut claimable = 0;
+    for deposit in matches {
+        // Nullifier = hash(secret, leaf_index), unique per deposit
+        let nullifier = pq_nullifier(secret, deposit.leaf_index);
+        let spent = deposit.claimed || used.iter().any(|n| n.as_slice() == nullifier.as_slice());
+        if !spent {
+            claimable += 1;
+        }
+        println!("\n   Leaf index: {}", deposit.leaf_index);
+        println!("   Amount: {}", deposit.amount);
+        println!("   Nullifier: 0x{}", hex::encode(nullifier));
+        println!("   Status: {}", if spent { "already claimed" } else { "unclaimed" });
+    }
+    
+    if nullifiers_path.is_none() {
+        println!("\n   ⚠️  No --nullifiers given: status reflects the index only.");
+        println!("      Confirm on-chain via the relayer: GET /nullifier/{}/<nullifier>", pool);
+    }
+    if claimable > 0 {
+        println!("\n📋 NEXT STEP: murkl prove -i {} -p <password> -l <leaf index> -m <merkle.json>", identifier);
+    }
+}
+
+fn cmd_advise(pool: &str, deposits_path: &PathBuf, window_hours: u64) {
+    println!("🐈‍⬛ Murkl - Pool privacy check\n");
+    let _span = tracing::info_span!("advise", pool).entered();
+    
+    let deposits_json = fs::read_to_string(deposits_path).expect("Failed to read deposits");
+    let deposits: Vec<IndexedDeposit> = serde_json::from_str(&deposits_json).expect("Invalid deposits data");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Clock before 1970").as_secs();
+    let advice = advise::advise(&deposits, pool, now, window_hours * 3600);
+    
+    println!("   Pool: {}", pool);
+    println!("   Deposits: {}", advice.total_deposits);
+    println!("   Anonymity set: {} unclaimed", advice.anonymity_set);
+    match advice.claim_rate_per_day {
+        Some(rate) => println!(
+            "   Last {}h: {} deposit(s), {} claim(s) ({:.1} claims/day)",
+            window_hours, advice.deposits_in_window, advice.claims_in_window, rate
+        ),
+        None => println!("   Recent activity: unknown"),
+    }
+    println!("   Recommended claim delay: {}", advise::format_duration(advice.recommended_delay_secs));
+    
+    for warning in &advice.warnings {
+        println!("\n   ⚠️  {}", warning);
+    }
+    if advice.is_unsafe() {
+        println!("\n   ❌ Not recommended: deposits and claims in this pool are easy to link.");
+        println!("      Use a larger pool, or wait for it to grow before depositing or claiming.");
+        std::process::exit(2);
+    }
+    println!("\n📋 Wait at least the recommended delay after the deposit before claiming.");
+}
+
+fn cmd_cost(bundle_path: &PathBuf, rpc_url: Option<&str>, payer: Option<&str>, priority_fee: Option<u64>, mut plan: cost::ClaimPlan) {
+    println!("🐈‍⬛ Murkl - Claim cost\n");
+    let _span = tracing::info_span!("cost").entered();
+    
+    let json = fs::read_to_string(bundle_path).expect("Failed to read proof bundle");
+    let bundle: ProofBundle = serde_json::from_str(&json).expect("Invalid proof bundle");
+    if let Err(e) = bundle.check_version() {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+    if payer.is_some() && rpc_url.is_none() {
+        eprintln!("❌ Checking the payer balance needs --rpc");
+        std::process::exit(1);
+    }
+    
+    let rpc = rpc_url.map(cost::Rpc::new);
+    let rpc_failed = |e: cost::RpcError| -> ! {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    };
+    plan.proof_len = bundle.proof.len();
+    plan.priority_fee = match (priority_fee, &rpc) {
+        (Some(fee), _) => fee,
+        (None, Some(rpc)) => rpc.recent_priority_fee().unwrap_or_else(|e| rpc_failed(e)),
+        (None, None) => 0,
+    };
+    let estimate = cost::estimate(&plan, |space| match &rpc {
+        Some(rpc) => rpc.rent_exempt_minimum(space).unwrap_or_else(|e| rpc_failed(e)),
+        None => cost::default_rent(space),
+    });
+    
+    println!("   Proof: {} bytes in {} chunk(s)", plan.proof_len, estimate.chunks);
+    println!("   Transactions: {}", estimate.transactions.len());
+    println!("   Buffer rent: {} (refunded when the buffer is closed)", cost::format_sol(estimate.buffer_rent));
+    println!("   Nullifier record rent: {}", cost::format_sol(estimate.nullifier_rent));
+    if plan.with_receipt {
+        println!("   Claim receipt rent: {}", cost::format_sol(estimate.receipt_rent));
+    }
+    println!("   Signature fees: {}", cost::format_sol(estimate.signature_fees));
+    println!(
+        "   Priority fees: {} ({} micro-lamports/CU)",
+        cost::format_sol(estimate.priority_fees),
+        plan.priority_fee
+    );
+    println!("   Total: {}", cost::format_sol(estimate.total_lamports()));
+    if plan.relayer_fee > 0 {
+        println!("   Relayer fee: {} token base units, deducted from the claimed amount", plan.relayer_fee);
+    }
+    if rpc.is_none() {
+        println!("\n   ⚠️  No --rpc given: rent assumes the default rent parameters");
+        if priority_fee.is_none() {
+            println!("      and priority fees are left out (set --priority-fee)");
+        }
+    }
+    
+    let (Some(rpc), Some(payer)) = (&rpc, payer) else { return };
+    let balance = rpc.balance(payer).unwrap_or_else(|e| rpc_failed(e));
+    println!("\n   Payer balance: {}", cost::format_sol(balance));
+    if balance < estimate.total_lamports() {
+        println!(
+            "   ❌ Short by {}: fund the payer before uploading, or the claim stops after the buffer is paid for",
+            cost::format_sol(estimate.total_lamports() - balance)
+        );
+        std::process::exit(1);
+    }
+    println!("   ✅ Payer can cover the claim");
+}
+
+fn cmd_index(
+    pool: &str,
+    events_path: &PathBuf,
+    checkpoint: Option<&PathBuf>,
+    confirmation: indexer::Confirmation,
+    output: &PathBuf,
+) {
+    println!("🐈‍⬛ Murkl - Indexing pool\n");
+    let _span = tracing::info_span!("index", pool).entered();
+    
+    let mut pool_indexer = match checkpoint {
+        Some(path) => {
+            let json = fs::read_to_string(path).expect("Failed to read checkpoint");
+            let data = MerkleData::from_json(&json).expect("Invalid checkpoint");
+            if data.pool != pool {
+                eprintln!("❌ Checkpoint is for pool {}, not {}", data.pool, pool);
+                std::process::exit(1);
+            }
+            indexer::PoolIndexer::from_checkpoint(&data).expect("Invalid checkpoint")
+        }
+        None => indexer::PoolIndexer::new(pool),
+    };
+    
+    let feed = fs::read_to_string(events_path).expect("Failed to read events");
+    let mut dropped = 0;
+    for (line_no, line) in feed.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let event: indexer::IndexerEvent = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("Invalid event on line {}: {}", line_no + 1, e));
+        match pool_indexer.apply(&event) {
+            Ok(n) => dropped += n,
+            Err(e) => {
+                tracing::error!(line = line_no + 1, error = %e, "feed rejected");
+                eprintln!("❌ Line {}: {}", line_no + 1, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    let snapshot = pool_indexer.snapshot(confirmation);
+    tracing::info!(
+        leaves = snapshot.leaves.len(),
+        slot = snapshot.last_indexed_slot,
+        finalized_slot = pool_indexer.finalized_slot(),
+        rolled_back = dropped,
+        "snapshot exported"
+    );
+    fs::write(output, serde_json::to_string_pretty(&snapshot).unwrap()).expect("Failed to write merkle data");
+    
+    println!("   Pool: {}", pool);
+    println!("   Leaves indexed: {}", pool_indexer.leaf_count());
+    println!("   Finalized through slot {}", pool_indexer.finalized_slot());
+    println!("   Rolled back: {} deposit(s)", dropped);
+    println!("   Exported: {} leaves at {:?} (slot {})", snapshot.leaves.len(), confirmation, snapshot.last_indexed_slot);
+    println!("   Root history: {} checkpoint(s)", snapshot.roots.len());
+    println!("   Root: 0x{}", snapshot.root);
+    println!("\n💾 Saved to: {}", output.display());
+}
+
+fn cmd_note_backup(files: &[PathBuf], dir: &PathBuf, passphrase: &str, out: &PathBuf) {
+    println!("🐈‍⬛ Murkl - Backing up notes\n");
+    
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        let mut found: Vec<PathBuf> = fs::read_dir(dir)
+            .expect("Failed to read directory")
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(backup::is_note_file))
+            .collect();
+        found.sort();
+        found
+    } else {
+        files.to_vec()
+    };
+    if paths.is_empty() {
+        eprintln!("❌ No deposit notes or proofs found in {}", dir.display());
+        std::process::exit(1);
+    }
+    
+    let mut notes = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let name = path.file_name().and_then(|n| n.to_str()).expect("File name is not UTF-8");
+        if notes.iter().any(|n: &backup::NoteFile| n.name == name) {
+            eprintln!("❌ Two files named {} (backups keep base names only)", name);
+            std::process::exit(1);
+        }
+        let contents = fs::read(path).expect("Failed to read file");
+        println!("   + {} ({} bytes)", name, contents.len());
+        notes.push(backup::NoteFile::new(name, &contents));
+    }
+    
+    let sealed = backup::seal(&notes, passphrase, backup::KdfParams::default()).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    });
+    fs::write(out, &sealed).expect("Failed to write backup");
+    
+    println!("\n   Files: {}", notes.len());
+    println!("   Size: {} bytes (argon2id + XChaCha20-Poly1305)", sealed.len());
+    println!("\n💾 Saved to: {}", out.display());
+    println!("   Without the passphrase this backup cannot be opened - store them separately.");
+}
+
+fn cmd_note_restore(input: &PathBuf, passphrase: &str, dir: &PathBuf, force: bool) {
+    println!("🐈‍⬛ Murkl - Restoring notes\n");
+    
+    let sealed = fs::read(input).expect("Failed to read backup");
+    let notes = backup::open(&sealed, passphrase).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    });
+    
+    // Decode everything and check for clobbering before writing anything
+    let mut files = Vec::with_capacity(notes.len());
+    for note in &notes {
+        let path = dir.join(&note.name);
+        if path.exists() && !force {
+            eprintln!("❌ {} already exists (use --force to overwrite)", path.display());
+            std::process::exit(1);
+        }
+        let contents = note.bytes().unwrap_or_else(|e| {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        });
+        files.push((path, contents));
+    }
+    
+    fs::create_dir_all(dir).expect("Failed to create directory");
+    for (path, contents) in &files {
+        fs::write(path, contents).expect("Failed to write file");
+        println!("   ✓ {} ({} bytes)", path.display(), contents.len());
+    }
+    println!("\n✅ Restored {} file(s)", files.len());
+}
+
 fn cmd_hash(identifier: &str, password: &str) {
     println!("🐈‍⬛ Murkl - Compute Hash\n");
     
     let id_hash = hash_identifier(identifier);
     let secret = hash_password(password);
     let commitment = m31_hash2(id_hash, secret);
-    let nullifier_example = m31_hash2(secret, 0); // Example with leaf_index=0
+    let nullifier_example = pq_nullifier(secret, 0); // Example with leaf_index=0
     
     println!("   Identifier: {}", identifier);
     println!("   ID hash: {}", id_hash);
@@ -271,7 +973,7 @@ fn cmd_hash(identifier: &str, password: &str) {
 // Post-quantum secure: relies only on hash collision resistance
 // ============================================================================
 
-use murkl_prover::{M31, M31_PRIME, keccak_hash};
+use murkl_prover::{M31, M31_PRIME, ProofBundle, keccak_hash};
 
 /// Derive secret from password using SDK
 fn hash_password(password: &str) -> u32 {
@@ -317,10 +1019,3 @@ struct DepositData {
     commitment: Vec<u8>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct ProofBundle {
-    proof: Vec<u8>,
-    commitment: Vec<u8>,
-    nullifier: Vec<u8>,
-    leaf_index: u32,
-}
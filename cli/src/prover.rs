@@ -9,7 +9,10 @@ use crate::types::*;
 
 // Import the murkl-prover SDK
 use murkl_prover::prelude::*;
-use murkl_prover::{M31_PRIME, keccak_hash, QM31};
+use murkl_prover::air::poseidon2::{MurklHashAir, WITNESS_COLUMNS};
+use murkl_prover::onchain::{evaluate_trace_at_oods, hash_air_composition, Channel};
+use murkl_prover::{M31_PRIME, keccak_hash, Checkpoint, HashKind, ProofError, ProveMetrics, ProveStage, QM31, SecurityLevel, StageRecorder, TREE_DEPTH};
+use murkl_prover::prover::{grind_resumable, GrindProgress};
 
 // ============================================================================
 // Prover Configuration
@@ -20,15 +23,23 @@ pub struct MurklProverConfig {
     pub log_blowup_factor: u32,
     pub n_queries: usize,
     pub n_fri_layers: usize,
+    pub n_trace_columns: usize,
+    /// Proof-of-work bits ground before query indices are drawn (0 = none)
+    pub pow_bits: u32,
+    /// Threads the nonce search runs on
+    pub grind_threads: usize,
 }
 
 impl Default for MurklProverConfig {
     fn default() -> Self {
         Self {
-            log_trace_size: 6,       // 64 rows
+            log_trace_size: 10,      // 1024 rows: both permutations, a block per tree level, then mask rows
             log_blowup_factor: 2,    // 4x blowup
             n_queries: 4,            // 4 queries (demo)
             n_fri_layers: 3,         // 3 FRI folding rounds
+            n_trace_columns: WITNESS_COLUMNS, // Poseidon2 state, secret and path bit
+            pow_bits: 0,             // no grinding until the verifier checks it
+            grind_threads: 1,
         }
     }
 }
@@ -37,30 +48,121 @@ impl Default for MurklProverConfig {
 // Murkl STARK Prover
 // ============================================================================
 
+/// Called with the nonce search's progress while grinding
+type GrindHook = Box<dyn Fn(GrindProgress)>;
+/// Called with the checkpoint taken once the nonce is ground
+type CheckpointHook = Box<dyn Fn(&Checkpoint)>;
+
 pub struct MurklProver {
     config: MurklProverConfig,
+    on_grind: Option<GrindHook>,
+    resume: Option<Checkpoint>,
+    on_checkpoint: Option<CheckpointHook>,
 }
 
 impl MurklProver {
     pub fn new() -> Self {
         Self {
             config: MurklProverConfig::default(),
+            on_grind: None,
+            resume: None,
+            on_checkpoint: None,
         }
     }
 
+    /// Queries, blowup and proof of work of `level`
+    pub fn with_security_level(mut self, level: SecurityLevel) -> Self {
+        self.config.n_queries = level.num_queries();
+        self.config.log_blowup_factor = level.log_blowup();
+        self.config.pow_bits = level.pow_bits();
+        self
+    }
+
+    /// Highest level this prover's proofs meet, `None` below
+    /// [`SecurityLevel::Demo`]
+    pub fn security_level(&self) -> Option<SecurityLevel> {
+        SecurityLevel::of(self.config.n_queries, self.config.log_blowup_factor, HashKind::Keccak as u8)
+    }
+
+    /// Grind `pow_bits` of proof of work on `threads` threads before
+    /// drawing query indices
+    pub fn with_pow(mut self, pow_bits: u32, threads: usize) -> Self {
+        self.config.pow_bits = pow_bits;
+        self.config.grind_threads = threads.max(1);
+        self
+    }
+
+    /// Report nonce search progress to `on_grind` while grinding
+    pub fn with_grind_progress(mut self, on_grind: impl Fn(GrindProgress) + 'static) -> Self {
+        self.on_grind = Some(Box::new(on_grind));
+        self
+    }
+
+    /// Hand a [`Checkpoint`] to `on_checkpoint` once the nonce is ground,
+    /// and take the nonce from `resume` when it was ground for this proof
+    ///
+    /// Every other stage is a handful of hashes and is simply rerun, so
+    /// the checkpoint holds the nonce and no witness values. One from
+    /// another proof is ignored.
+    pub fn with_checkpoint(mut self, resume: Option<Checkpoint>, on_checkpoint: impl Fn(&Checkpoint) + 'static) -> Self {
+        self.resume = resume;
+        self.on_checkpoint = Some(Box::new(on_checkpoint));
+        self
+    }
+
     /// Generate a STARK proof in format matching on-chain verifier
+    ///
+    /// Fails if `merkle_data` has no pool-depth path for `leaf_index`.
     pub fn generate_proof(
         &self,
         identifier: u32,
         secret: u32,
         leaf_index: u32,
-        _merkle_data: &MerkleData,
-    ) -> MurklProof {
+        merkle_data: &MerkleData,
+    ) -> Result<MurklProof, ProofError> {
+        let mut recorder = StageRecorder::new(murkl_prover::metrics::no_clock);
+        self.generate_proof_recorded(identifier, secret, leaf_index, merkle_data, &mut recorder)
+    }
+
+    /// [`MurklProver::generate_proof`], also reporting time and hashes per stage
+    pub fn generate_proof_with_metrics(
+        &self,
+        identifier: u32,
+        secret: u32,
+        leaf_index: u32,
+        merkle_data: &MerkleData,
+        mut recorder: StageRecorder<'_>,
+    ) -> Result<(MurklProof, ProveMetrics), ProofError> {
+        let proof = self.generate_proof_recorded(identifier, secret, leaf_index, merkle_data, &mut recorder)?;
+        let proof_bytes = proof.serialize().len();
+        Ok((proof, recorder.into_metrics(proof_bytes)))
+    }
+
+    fn generate_proof_recorded(
+        &self,
+        identifier: u32,
+        secret: u32,
+        leaf_index: u32,
+        merkle_data: &MerkleData,
+        recorder: &mut StageRecorder<'_>,
+    ) -> Result<MurklProof, ProofError> {
         // Compute M31 values
         let id_m31 = identifier % M31_PRIME;
         let secret_m31 = secret % M31_PRIME;
         let commitment_m31 = compute_m31_commitment(id_m31, secret_m31);
-        let nullifier_m31 = compute_m31_nullifier(secret_m31, leaf_index);
+
+        // The hash AIR's statement and witness columns, the commitment's
+        // path up to the snapshot root included
+        let commitment = murkl_prover::pq_commitment(M31::new(id_m31), M31::new(secret_m31));
+        let nullifier = murkl_prover::pq_nullifier(M31::new(secret_m31), leaf_index);
+        let merkle_root = merkle_data.root_hash()?;
+        let air = MurklHashAir::new(&commitment, &nullifier)
+            .with_membership(&merkle_root, TREE_DEPTH)
+            .with_log_rows(self.config.log_trace_size);
+        let trace =
+            air.generate_trace(M31::new(id_m31), M31::new(secret_m31), leaf_index, &merkle_data.get_proof(leaf_index))?;
+        let witness_columns: Vec<Vec<M31>> =
+            trace.columns[..WITNESS_COLUMNS].iter().map(|column| column.values.clone()).collect();
 
         // Generate deterministic commitments
         let trace_commitment = keccak_hash(&[
@@ -68,21 +170,38 @@ impl MurklProver {
             &id_m31.to_le_bytes(),
             &secret_m31.to_le_bytes(),
         ]);
+        recorder.finish(ProveStage::CommitTrace, 1);
 
         let composition_commitment = keccak_hash(&[
             b"murkl_composition_v3",
             &trace_commitment,
         ]);
-
-        // OODS values (evaluations at out-of-domain point)
-        let trace_oods = QM31::from_u32(commitment_m31, nullifier_m31, id_m31, secret_m31);
-
-        let composition_oods = QM31::from_u32(
-            (commitment_m31.wrapping_mul(7)) % M31_PRIME,
-            (nullifier_m31.wrapping_mul(11)) % M31_PRIME,
-            0,
-            0,
-        );
+        recorder.finish(ProveStage::Composition, 1);
+
+        // OODS values: the witness columns at a point drawn from the
+        // transcript, never the witness itself. No recipient is bound here;
+        // the program mixes it after the root.
+        let mut channel = Channel::new(HashKind::Keccak);
+        for input in [&commitment, &nullifier, &merkle_root, &trace_commitment] {
+            channel.mix_digest(input);
+        }
+        let alpha = channel.squeeze_qm31();
+        channel.mix_digest(&composition_commitment);
+        let oods_point = channel.squeeze_circle_point();
+        let (trace_oods, trace_oods_next) =
+            evaluate_trace_at_oods(&witness_columns, self.config.log_trace_size, &oods_point);
+        let composition_oods = hash_air_composition(
+            &trace_oods,
+            &trace_oods_next,
+            &commitment,
+            &nullifier,
+            &merkle_root,
+            alpha,
+            &oods_point,
+            self.config.log_trace_size,
+        )
+        .map_err(|e| ProofError::InvalidWitness(e.to_string()))?;
+        recorder.finish(ProveStage::Oods, channel.hashes());
 
         // FRI layer commitments
         let mut fri_layer_commitments = Vec::with_capacity(self.config.n_fri_layers);
@@ -99,10 +218,43 @@ impl MurklProver {
             QM31::from_u32(1, 0, 0, 0),
             QM31::from_u32(commitment_m31 % 1000, 0, 0, 0),
         ];
+        recorder.finish(ProveStage::Fri, fri_layer_commitments.len() as u64);
+
+        // Proof of work over everything committed so far; the nonce then
+        // feeds every query seed
+        let pow_nonce = (self.config.pow_bits > 0).then(|| {
+            let mut seed_parts: Vec<&[u8]> = vec![b"murkl_pow_seed_v1", &trace_commitment, &composition_commitment];
+            seed_parts.extend(fri_layer_commitments.iter().map(|c| c.as_slice()));
+            let pow_seed = keccak_hash(&seed_parts);
+            let mut report = |progress: GrindProgress| {
+                if let Some(on_grind) = &self.on_grind {
+                    on_grind(progress);
+                }
+            };
+            // Only a search that ran saves; a resumed nonce cost nothing
+            let mut searched = false;
+            let mut save = |checkpoint: &Checkpoint| {
+                searched = true;
+                if let Some(on_checkpoint) = &self.on_checkpoint {
+                    on_checkpoint(checkpoint);
+                }
+            };
+            let nonce = grind_resumable(
+                &pow_seed,
+                self.config.pow_bits,
+                self.config.grind_threads,
+                &mut report,
+                self.resume.as_ref(),
+                &mut save,
+            );
+            recorder.finish(ProveStage::Grind, if searched { nonce + 2 } else { 0 });
+            nonce
+        });
+        let nonce_bytes = pow_nonce.map(u64::to_le_bytes);
+        let nonce_seed: &[u8] = nonce_bytes.as_ref().map_or(&[], |bytes| bytes.as_slice());
 
         // Generate queries
         let mut queries = Vec::with_capacity(self.config.n_queries);
-        let domain_size = 1 << (self.config.log_trace_size + self.config.log_blowup_factor);
         let tree_depth = self.config.log_trace_size + self.config.log_blowup_factor;
 
         for q in 0..self.config.n_queries {
@@ -112,15 +264,24 @@ impl MurklProver {
                 &(q as u32).to_le_bytes(),
                 &trace_commitment,
                 &composition_commitment,
+                nonce_seed,
             ]);
-            let index = u32::from_le_bytes([query_seed[0], query_seed[1], query_seed[2], query_seed[3]]) % domain_size;
+            // Low bits of the word, so every position is equally likely
+            let index = u32::from_le_bytes([query_seed[0], query_seed[1], query_seed[2], query_seed[3]])
+                & ((1 << tree_depth) - 1);
 
-            // Trace value at query point
-            let trace_value = keccak_hash(&[
-                b"trace_eval",
-                &index.to_le_bytes(),
-                &trace_commitment,
-            ]);
+            // Trace row at query point, one value per column
+            let trace_values = (0..self.config.n_trace_columns as u32)
+                .map(|column| {
+                    let val = keccak_hash(&[
+                        b"trace_eval",
+                        &index.to_le_bytes(),
+                        &column.to_le_bytes(),
+                        &trace_commitment,
+                    ]);
+                    u32::from_le_bytes([val[0], val[1], val[2], val[3]]) % M31_PRIME
+                })
+                .collect();
 
             // Trace Merkle path
             let trace_path = generate_merkle_path(tree_depth as usize, index, &trace_commitment);
@@ -172,23 +333,31 @@ impl MurklProver {
 
             queries.push(QueryProof {
                 index,
-                trace_value,
+                trace_values,
                 trace_path,
                 composition_value,
                 composition_path,
                 fri_layer_data,
             });
         }
+        recorder.finish(ProveStage::Queries, queries.iter().map(query_hashes).sum());
 
-        MurklProof::from_parts(
+        let mut proof = MurklProof::from_parts(ProofParts {
             trace_commitment,
             composition_commitment,
+            num_trace_columns: self.config.n_trace_columns as u8,
+            log_trace_size: self.config.log_trace_size as u8,
+            log_blowup: self.config.log_blowup_factor as u8,
+            log_folding_factor: 2, // fold by 4
             trace_oods,
+            trace_oods_next,
             composition_oods,
             fri_layer_commitments,
             fri_final_poly,
             queries,
-        )
+        });
+        proof.pow_nonce = pow_nonce;
+        Ok(proof)
     }
 
     /// Verify a proof locally
@@ -211,9 +380,11 @@ fn compute_m31_commitment(id: u32, secret: u32) -> u32 {
     murkl_prover::m31_commitment(M31::new(id), M31::new(secret)).value()
 }
 
-/// Compute M31 nullifier using SDK
-fn compute_m31_nullifier(secret: u32, leaf_index: u32) -> u32 {
-    murkl_prover::m31_nullifier(M31::new(secret), leaf_index).value()
+/// Keccaks behind one query: its index, the trace row, the composition
+/// value and every sibling and path node
+fn query_hashes(query: &QueryProof) -> u64 {
+    let fri: usize = query.fri_layer_data.iter().map(|(siblings, path)| siblings.len() + path.len()).sum();
+    (1 + query.trace_values.len() + query.trace_path.len() + 1 + query.composition_path.len() + fri) as u64
 }
 
 fn generate_merkle_path(depth: usize, index: u32, seed: &[u8; 32]) -> Vec<[u8; 32]> {
@@ -252,17 +423,29 @@ pub fn compute_keccak_nullifier(secret_hash: u32, leaf_index: u32) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use murkl_prover::air::poseidon2::SECRET;
+    use murkl_prover::spec;
+
+    /// A pool snapshot holding the test claim's commitment at leaf 0
+    fn merkle_data() -> MerkleData {
+        let commitment = murkl_prover::pq_commitment(M31::new(12345), M31::new(67890));
+        MerkleData {
+            version: MERKLE_DATA_VERSION,
+            pool: String::new(),
+            root: hex::encode(murkl_prover::merkle::pool_root(&[commitment], TREE_DEPTH)),
+            depth: TREE_DEPTH as u32,
+            last_indexed_slot: 0,
+            leaves: vec![MerkleLeaf { index: 0, commitment: hex::encode(commitment) }],
+            roots: Vec::new(),
+        }
+    }
 
     #[test]
     fn test_proof_generation() {
         let prover = MurklProver::new();
-        let merkle_data = MerkleData {
-            root: vec![0u8; 32],
-            leaves: vec![vec![0u8; 32]],
-            depth: 1,
-        };
+        let merkle_data = merkle_data();
 
-        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data);
+        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap();
         
         // Check structure
         assert!(!proof.queries.is_empty());
@@ -273,38 +456,169 @@ mod tests {
     #[test]
     fn test_proof_serialization() {
         let prover = MurklProver::new();
-        let merkle_data = MerkleData {
-            root: vec![0u8; 32],
-            leaves: vec![vec![0u8; 32]],
-            depth: 1,
-        };
+        let merkle_data = merkle_data();
 
-        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data);
+        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap();
         let serialized = proof.serialize();
 
         // Verify minimum size
         assert!(serialized.len() >= 128);
 
         // Check header
-        assert_eq!(&serialized[0..32], &proof.trace_commitment);
-        assert_eq!(&serialized[32..64], &proof.composition_commitment);
+        assert_eq!(serialized[spec::HASH_KIND.start], murkl_prover::HashKind::Keccak as u8);
+        assert_eq!(&serialized[spec::TRACE_COMMITMENT], &proof.trace_commitment);
+        assert_eq!(&serialized[spec::COMPOSITION_COMMITMENT], &proof.composition_commitment);
+        assert_eq!(serialized[spec::TRACE_COLUMNS.start] as usize, WITNESS_COLUMNS);
+        assert_eq!(&serialized[spec::MAGIC], &spec::PROOF_MAGIC);
+        assert_eq!(serialized[spec::LOG_TRACE_SIZE.start] + serialized[spec::LOG_BLOWUP.start], 12);
+        assert_eq!(serialized[spec::QUERY_COUNT.start] as usize, proof.queries.len());
+
+        // Same wire layout the on-chain parser reads
+        spec::layout(&serialized).unwrap();
     }
 
     #[test]
     fn test_proof_roundtrip() {
         let prover = MurklProver::new();
-        let merkle_data = MerkleData {
-            root: vec![0u8; 32],
-            leaves: vec![vec![0u8; 32]],
-            depth: 1,
-        };
+        let merkle_data = merkle_data();
 
-        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data);
+        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap();
         let serialized = proof.serialize();
-        let deserialized = MurklProof::deserialize(&serialized);
+        let deserialized = MurklProof::deserialize(&serialized).unwrap();
 
         assert_eq!(proof.trace_commitment, deserialized.trace_commitment);
         assert_eq!(proof.composition_commitment, deserialized.composition_commitment);
         assert_eq!(proof.queries.len(), deserialized.queries.len());
+        assert_eq!(deserialized.num_trace_columns as usize, WITNESS_COLUMNS);
+        assert_eq!(deserialized.log_trace_size, proof.log_trace_size);
+        assert_eq!(deserialized.log_blowup, proof.log_blowup);
+        for (original, parsed) in proof.queries.iter().zip(&deserialized.queries) {
+            assert_eq!(parsed.trace_values.len(), WITNESS_COLUMNS);
+            assert_eq!(original.trace_values, parsed.trace_values);
+        }
+    }
+
+    #[test]
+    fn test_oods_is_an_evaluation_not_the_witness() {
+        let merkle_data = merkle_data();
+        let prover = MurklProver::new();
+        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap();
+        let secret = QM31::from_u32(67890, 0, 0, 0);
+        assert!(proof.trace_oods.iter().chain(&proof.trace_oods_next).all(|value| *value != secret));
+
+        // The sent frame satisfies the hash AIR at the drawn point
+        let commitment = murkl_prover::pq_commitment(M31::new(12345), M31::new(67890));
+        let nullifier = murkl_prover::pq_nullifier(M31::new(67890), 0);
+        let mut channel = Channel::new(HashKind::Keccak);
+        let root = merkle_data.root_hash().unwrap();
+        for input in [&commitment, &nullifier, &root, &proof.trace_commitment] {
+            channel.mix_digest(input);
+        }
+        let alpha = channel.squeeze_qm31();
+        channel.mix_digest(&proof.composition_commitment);
+        let point = channel.squeeze_circle_point();
+        let (current, next) = (&proof.trace_oods, &proof.trace_oods_next);
+        let log_rows = prover.config.log_trace_size;
+        let composition =
+            hash_air_composition(current, next, &commitment, &nullifier, &root, alpha, &point, log_rows).unwrap();
+        assert_eq!(proof.composition_oods, composition);
+
+        // A wrong secret breaks the nullifier's permutation
+        let mut forged = proof.trace_oods.clone();
+        forged[SECRET] = secret;
+        let composition =
+            hash_air_composition(&forged, next, &commitment, &nullifier, &root, alpha, &point, log_rows).unwrap();
+        assert_ne!(proof.composition_oods, composition);
+
+        // As does a root the path does not reach
+        let composition =
+            hash_air_composition(current, next, &commitment, &nullifier, &[1; 32], alpha, &point, log_rows).unwrap();
+        assert_ne!(proof.composition_oods, composition);
+    }
+
+    #[test]
+    fn test_proof_metrics() {
+        let prover = MurklProver::new();
+        let merkle_data = merkle_data();
+
+        let recorder = StageRecorder::new(murkl_prover::metrics::system_clock);
+        let (proof, metrics) = prover.generate_proof_with_metrics(12345, 67890, 0, &merkle_data, recorder).unwrap();
+        assert_eq!(proof.serialize(), prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap().serialize());
+        assert_eq!(metrics.proof_bytes, proof.serialize().len());
+
+        let stages: Vec<ProveStage> = metrics.stages.iter().map(|s| s.stage).collect();
+        use ProveStage::*;
+        assert_eq!(stages, [CommitTrace, Composition, Oods, Fri, Queries]);
+        assert_eq!(metrics.stage(Fri).unwrap().hashes, prover.config.n_fri_layers as u64);
+
+        // 4 queries over a depth-12 domain with 3 FRI layers of depth 10, 8, 6
+        let per_query = 1 + 18 + 12 + 1 + 12 + (4 + 10) + (4 + 8) + (4 + 6);
+        assert_eq!(metrics.stage(Queries).unwrap().hashes, 4 * per_query);
+    }
+
+    #[test]
+    fn test_security_level() {
+        let merkle_data = merkle_data();
+        // 4x blowup is below every level
+        assert_eq!(MurklProver::new().security_level(), None);
+
+        let prover = MurklProver::new().with_security_level(SecurityLevel::Standard96);
+        assert_eq!(prover.security_level(), Some(SecurityLevel::Standard96));
+        let proof = prover.generate_proof(12345, 67890, 0, &merkle_data).unwrap();
+        assert_eq!(proof.queries.len(), 24);
+        assert_eq!(proof.log_blowup, 4);
+        assert!(proof.pow_nonce.is_some());
+    }
+
+    #[test]
+    fn test_proof_of_work_moves_queries() {
+        let merkle_data = merkle_data();
+        let plain = MurklProver::new().generate_proof(12345, 67890, 0, &merkle_data).unwrap();
+        assert_eq!(plain.pow_nonce, None);
+
+        let prover = MurklProver::new().with_pow(10, 3).with_grind_progress(|_| {});
+        let recorder = StageRecorder::new(murkl_prover::metrics::no_clock);
+        let (proof, metrics) = prover.generate_proof_with_metrics(12345, 67890, 0, &merkle_data, recorder).unwrap();
+        let nonce = proof.pow_nonce.unwrap();
+        assert_eq!(metrics.stage(ProveStage::Grind).unwrap().hashes, nonce + 2);
+
+        // Same nonce on one thread; only the query openings change
+        let serial = MurklProver::new().with_pow(10, 1).generate_proof(12345, 67890, 0, &merkle_data).unwrap();
+        assert_eq!(serial.pow_nonce, Some(nonce));
+        assert_eq!(proof.fri_layer_commitments, plain.fri_layer_commitments);
+        assert_ne!(proof.serialize(), plain.serialize());
+    }
+
+    #[test]
+    fn test_resume_skips_grinding() {
+        use std::sync::{Arc, Mutex};
+
+        let merkle_data = merkle_data();
+        let saved = Arc::new(Mutex::new(None));
+        let sink = saved.clone();
+        let fresh = MurklProver::new()
+            .with_pow(10, 1)
+            .with_checkpoint(None, move |c| *sink.lock().unwrap() = Some(c.clone()))
+            .generate_proof(12345, 67890, 0, &merkle_data)
+            .unwrap();
+        let checkpoint = saved.lock().unwrap().take().expect("checkpoint after grinding");
+
+        let prover = MurklProver::new()
+            .with_pow(10, 1)
+            .with_checkpoint(Some(checkpoint.clone()), |_| panic!("resumed proof ground again"));
+        let recorder = StageRecorder::new(murkl_prover::metrics::no_clock);
+        let (resumed, metrics) = prover.generate_proof_with_metrics(12345, 67890, 0, &merkle_data, recorder).unwrap();
+        assert_eq!(resumed.serialize(), fresh.serialize());
+        assert_eq!(metrics.stage(ProveStage::Grind).unwrap().hashes, 0);
+
+        // Another secret grinds afresh
+        let ground = Arc::new(Mutex::new(false));
+        let flag = ground.clone();
+        MurklProver::new()
+            .with_pow(10, 1)
+            .with_checkpoint(Some(checkpoint), move |_| *flag.lock().unwrap() = true)
+            .generate_proof(12345, 67891, 0, &merkle_data)
+            .unwrap();
+        assert!(*ground.lock().unwrap());
     }
 }
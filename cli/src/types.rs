@@ -6,31 +6,302 @@ use serde::{Deserialize, Serialize};
 
 // Re-export QM31 from SDK
 pub use murkl_prover::QM31;
+pub use murkl_prover::MerkleWitness;
 
-/// Merkle tree data
+use murkl_prover::{ProofError, M31};
+use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof};
+
+/// Current [`MerkleData`] schema version
+pub const MERKLE_DATA_VERSION: u32 = 1;
+
+/// Merkle tree snapshot of a pool, as exported by an indexer for `prove`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MerkleData {
-    pub root: Vec<u8>,
-    pub leaves: Vec<Vec<u8>>,
+    /// Schema version ([`MERKLE_DATA_VERSION`])
+    pub version: u32,
+    /// Pool address (base58)
+    pub pool: String,
+    /// Hex-encoded root (`0x` prefix optional)
+    pub root: String,
     pub depth: u32,
+    /// Slot at which the indexer last saw the pool
+    pub last_indexed_slot: u64,
+    /// Leaves in index order
+    pub leaves: Vec<MerkleLeaf>,
+    /// Roots the tree passed through, oldest first (see [`MerkleData::at_root`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<RootCheckpoint>,
+}
+
+/// Root of the tree once it held `leaf_count` leaves
+///
+/// The pool pushes a root per deposit and accepts claims against its recent
+/// ones, so an indexer records one checkpoint per leaf it saw land.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootCheckpoint {
+    pub leaf_count: u32,
+    /// Slot of the deposit that produced this root
+    pub slot: u64,
+    /// Hex-encoded root (`0x` prefix optional)
+    pub root: String,
+}
+
+/// A tree leaf (deposit commitment) at its index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleLeaf {
+    pub index: u32,
+    /// Hex-encoded commitment (`0x` prefix optional)
+    pub commitment: String,
 }
 
 impl MerkleData {
-    /// Find the index of a leaf
-    pub fn find_leaf(&self, commitment: &[u8]) -> Option<u32> {
-        self.leaves.iter()
-            .position(|leaf| leaf == commitment)
-            .map(|i| i as u32)
+    /// Parse and validate a snapshot
+    pub fn from_json(json: &str) -> Result<Self, ProofError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: Option<u32>,
+        }
+        let versioned: Versioned = serde_json::from_str(json)
+            .map_err(|e| ProofError::SerializationError(format!("invalid merkle data: {}", e)))?;
+        match versioned.version {
+            Some(MERKLE_DATA_VERSION) => {}
+            Some(v) => {
+                return Err(ProofError::SerializationError(format!(
+                    "unsupported merkle data version {} (expected {})",
+                    v, MERKLE_DATA_VERSION
+                )))
+            }
+            None => {
+                return Err(ProofError::SerializationError(
+                    "unversioned merkle data; re-export it from the indexer".to_string(),
+                ))
+            }
+        }
+
+        let data: Self = serde_json::from_str(json)
+            .map_err(|e| ProofError::SerializationError(format!("invalid merkle data: {}", e)))?;
+        data.validate()?;
+        Ok(data)
     }
-    
+
+    /// Check internal consistency: depth, leaf numbering and root
+    pub fn validate(&self) -> Result<(), ProofError> {
+        if self.depth as usize != murkl_prover::TREE_DEPTH {
+            return Err(ProofError::MerkleError(format!(
+                "tree depth {} does not match the pool depth {}",
+                self.depth,
+                murkl_prover::TREE_DEPTH
+            )));
+        }
+        if self.leaves.len() > 1usize << self.depth {
+            return Err(ProofError::MerkleError(format!(
+                "{} leaves exceed tree depth {}",
+                self.leaves.len(),
+                self.depth
+            )));
+        }
+        if let Some((position, leaf)) = self
+            .leaves
+            .iter()
+            .enumerate()
+            .find(|(position, leaf)| leaf.index as usize != *position)
+        {
+            return Err(ProofError::MerkleError(format!(
+                "leaf {} listed at position {}; leaves must be contiguous from 0",
+                leaf.index, position
+            )));
+        }
+
+        let leaves = self.leaf_hashes()?;
+        let root = self.root_hash()?;
+        let computed = murkl_prover::merkle::pool_root(&leaves, self.depth as usize);
+        if computed != root {
+            return Err(ProofError::MerkleError(format!(
+                "leaves hash to 0x{} but the snapshot root is 0x{}",
+                hex::encode(computed),
+                hex::encode(root)
+            )));
+        }
+
+        // Checkpoints are only spot-checked here; `at_root` recomputes the one it uses
+        let mut previous = 0;
+        for checkpoint in &self.roots {
+            if checkpoint.leaf_count <= previous || checkpoint.leaf_count as usize > self.leaves.len() {
+                return Err(ProofError::MerkleError(format!(
+                    "root checkpoint at {} leaves is out of order or past the {} leaves",
+                    checkpoint.leaf_count,
+                    self.leaves.len()
+                )));
+            }
+            let checkpoint_root = parse_hash(&checkpoint.root, "checkpoint root")?;
+            if checkpoint.leaf_count as usize == self.leaves.len() && checkpoint_root != root {
+                return Err(ProofError::MerkleError(format!(
+                    "latest root checkpoint 0x{} does not match the snapshot root",
+                    hex::encode(checkpoint_root)
+                )));
+            }
+            previous = checkpoint.leaf_count;
+        }
+        Ok(())
+    }
+
+    /// The tree as it was when `root` was the pool root
+    ///
+    /// Lets a claimer prove against a root the pool still keeps in its
+    /// history rather than the latest one. Leaves are cut back to the
+    /// checkpoint's count and the checkpoint is recomputed from them, so a
+    /// corrupt history entry fails here instead of on-chain.
+    pub fn at_root(&self, root: &[u8; 32]) -> Result<MerkleData, ProofError> {
+        if self.root_hash()? == *root {
+            return Ok(self.clone());
+        }
+        let position = self
+            .roots
+            .iter()
+            .position(|checkpoint| parse_hash(&checkpoint.root, "checkpoint root").ok().as_ref() == Some(root))
+            .ok_or_else(|| {
+                let span = match (self.roots.first(), self.roots.last()) {
+                    (Some(first), Some(last)) => {
+                        format!("checkpoints cover {} to {} leaves", first.leaf_count, last.leaf_count)
+                    }
+                    _ => "the snapshot has no root history".to_string(),
+                };
+                ProofError::MerkleError(format!("root 0x{} is not in the snapshot ({})", hex::encode(root), span))
+            })?;
+        let checkpoint = &self.roots[position];
+
+        let leaves = self.leaves[..checkpoint.leaf_count as usize].to_vec();
+        let data = MerkleData {
+            version: self.version,
+            pool: self.pool.clone(),
+            root: hex::encode(root),
+            depth: self.depth,
+            last_indexed_slot: checkpoint.slot,
+            leaves,
+            roots: self.roots[..=position].to_vec(),
+        };
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Refuse a snapshot for another pool or an older tree state
+    ///
+    /// `expected_root` is the pool's current on-chain root; a mismatch means
+    /// deposits landed after `last_indexed_slot` (or the export is corrupt).
+    pub fn check_current(&self, pool: Option<&str>, expected_root: Option<&[u8; 32]>) -> Result<(), ProofError> {
+        if let Some(pool) = pool {
+            if pool != self.pool {
+                return Err(ProofError::MerkleError(format!(
+                    "snapshot is for pool {}, not {}",
+                    self.pool, pool
+                )));
+            }
+        }
+        if let Some(expected) = expected_root {
+            let root = self.root_hash()?;
+            if root != *expected {
+                return Err(ProofError::MerkleError(format!(
+                    "snapshot root 0x{} ({} leaves, slot {}) is stale; pool root is 0x{}",
+                    hex::encode(root),
+                    self.leaves.len(),
+                    self.last_indexed_slot,
+                    hex::encode(expected)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Commitment at `index`, if present and well-formed
+    pub fn leaf(&self, index: u32) -> Option<[u8; 32]> {
+        self.leaves.get(index as usize).and_then(|leaf| parse_hash(&leaf.commitment, "leaf").ok())
+    }
+
     /// Get Merkle proof for a leaf
-    pub fn get_proof(&self, _index: u32) -> Vec<[u8; 32]> {
-        // For now, return empty proof (simplified tree)
-        // In production, compute actual sibling path
-        vec![]
+    pub fn get_proof(&self, index: u32) -> Vec<[u8; 32]> {
+        self.leaf_hashes()
+            .ok()
+            .and_then(|leaves| MerkleWitness::from_leaves(&leaves, self.depth as usize, index as usize))
+            .map(|witness| witness.siblings)
+            .unwrap_or_default()
+    }
+
+    /// Build the membership witness for a commitment
+    ///
+    /// Fails if the commitment is not in the tree or if the leaves do not
+    /// reproduce `root` (stale or truncated indexer data), instead of letting
+    /// the prover silently work against the wrong index.
+    pub fn witness_for(&self, commitment: &[u8]) -> Result<MerkleWitness, ProofError> {
+        let leaves = self.leaf_hashes()?;
+        let root = self.root_hash()?;
+        let commitment = to_hash(commitment, "commitment")?;
+        murkl_prover::merkle::witness_for(&leaves, self.depth as usize, &root, &commitment)
+    }
+
+    pub fn root_hash(&self) -> Result<[u8; 32], ProofError> {
+        parse_hash(&self.root, "root")
+    }
+
+    fn leaf_hashes(&self) -> Result<Vec<[u8; 32]>, ProofError> {
+        self.leaves.iter().map(|leaf| parse_hash(&leaf.commitment, "leaf")).collect()
     }
 }
 
+/// Deposit as exported by an indexer (one entry per on-chain `DepositRecord`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDeposit {
+    pub pool: String,
+    pub leaf_index: u32,
+    /// Hex-encoded commitment (`0x` prefix optional)
+    pub commitment: String,
+    pub amount: u64,
+    #[serde(default)]
+    pub claimed: bool,
+    /// Unix time of the deposit, when the indexer recorded it
+    #[serde(default)]
+    pub deposited_at: Option<u64>,
+    /// Unix time of the claim, when the indexer recorded it
+    #[serde(default)]
+    pub claimed_at: Option<u64>,
+}
+
+impl IndexedDeposit {
+    fn commitment_bytes(&self) -> Option<[u8; 32]> {
+        hex::decode(self.commitment.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+    }
+}
+
+/// All deposits in `pool` carrying `commitment`, in leaf order.
+///
+/// The same identifier + password can be deposited to more than once, so
+/// this returns every match rather than the first.
+pub fn find_deposits<'a>(
+    deposits: &'a [IndexedDeposit],
+    pool: &str,
+    commitment: &[u8; 32],
+) -> Vec<&'a IndexedDeposit> {
+    let mut found: Vec<_> = deposits
+        .iter()
+        .filter(|d| d.pool == pool && d.commitment_bytes().as_ref() == Some(commitment))
+        .collect();
+    found.sort_by_key(|d| d.leaf_index);
+    found
+}
+
+pub(crate) fn parse_hash(hex_str: &str, what: &str) -> Result<[u8; 32], ProofError> {
+    let bytes = hex::decode(hex_str.trim_start_matches("0x"))
+        .map_err(|e| ProofError::MerkleError(format!("{} is not hex: {}", what, e)))?;
+    to_hash(&bytes, what)
+}
+
+fn to_hash(bytes: &[u8], what: &str) -> Result<[u8; 32], ProofError> {
+    bytes.try_into().map_err(|_| {
+        ProofError::MerkleError(format!("{} must be 32 bytes, got {}", what, bytes.len()))
+    })
+}
+
 /// FRI layer proof
 #[derive(Debug, Clone)]
 pub struct FriLayerProof {
@@ -43,7 +314,8 @@ pub struct FriLayerProof {
 #[derive(Debug, Clone)]
 pub struct QueryProof {
     pub index: u32,
-    pub trace_value: [u8; 32],
+    /// One M31 value per trace column at the queried row
+    pub trace_values: Vec<u32>,
     pub trace_path: Vec<[u8; 32]>,
     pub composition_value: [u8; 32],
     pub composition_path: Vec<[u8; 32]>,
@@ -51,16 +323,47 @@ pub struct QueryProof {
     pub fri_layer_data: Vec<(Vec<QM31>, Vec<[u8; 32]>)>,
 }
 
+/// Everything [`MurklProof::from_parts`] builds a proof from
+#[derive(Debug, Clone)]
+pub struct ProofParts {
+    pub trace_commitment: [u8; 32],
+    pub composition_commitment: [u8; 32],
+    pub num_trace_columns: u8,
+    pub log_trace_size: u8,
+    pub log_blowup: u8,
+    pub log_folding_factor: u8,
+    /// Each trace column at the OODS point
+    pub trace_oods: Vec<QM31>,
+    /// Each trace column one trace row past the OODS point
+    pub trace_oods_next: Vec<QM31>,
+    pub composition_oods: QM31,
+    pub fri_layer_commitments: Vec<[u8; 32]>,
+    pub fri_final_poly: Vec<QM31>,
+    pub queries: Vec<QueryProof>,
+}
+
 /// STARK proof - format matches on-chain verifier
 #[derive(Debug, Clone)]
 pub struct MurklProof {
     pub trace_commitment: [u8; 32],
     pub composition_commitment: [u8; 32],
-    pub trace_oods: QM31,
+    /// Trace columns opened by every query
+    pub num_trace_columns: u8,
+    /// log2 of the trace length
+    pub log_trace_size: u8,
+    /// log2 of the blowup; queries index a 2^(log_trace_size + log_blowup) domain
+    pub log_blowup: u8,
+    pub log_folding_factor: u8,
+    /// Each trace column at the OODS point
+    pub trace_oods: Vec<QM31>,
+    /// Each trace column one trace row past the OODS point
+    pub trace_oods_next: Vec<QM31>,
     pub composition_oods: QM31,
     pub fri_layer_commitments: Vec<[u8; 32]>,
     pub fri_final_poly: Vec<QM31>,
     pub queries: Vec<QueryProof>,
+    /// Proof-of-work nonce mixed into the query seeds; not part of the wire format
+    pub pow_nonce: Option<u64>,
     
     // Legacy fields for compatibility (ignored in new format)
     pub oods_values: Vec<QM31>,
@@ -73,230 +376,80 @@ pub struct MurklProof {
 impl MurklProof {
     /// Serialize to format expected by on-chain verifier
     pub fn serialize(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        
-        // 1. Trace commitment (32 bytes)
-        bytes.extend_from_slice(&self.trace_commitment);
-        
-        // 2. Composition commitment (32 bytes)
-        bytes.extend_from_slice(&self.composition_commitment);
-        
-        // 3. Trace OODS (16 bytes QM31)
-        bytes.extend_from_slice(&self.trace_oods.to_bytes());
-        
-        // 4. Composition OODS (16 bytes QM31)
-        bytes.extend_from_slice(&self.composition_oods.to_bytes());
-        
-        // 5. FRI layer count (1 byte)
-        bytes.push(self.fri_layer_commitments.len() as u8);
-        
-        // 6. FRI layer commitments (32 bytes each)
-        for commitment in &self.fri_layer_commitments {
-            bytes.extend_from_slice(commitment);
-        }
-        
-        // 7. Final polynomial count (2 bytes u16)
-        bytes.extend_from_slice(&(self.fri_final_poly.len() as u16).to_le_bytes());
-        
-        // 8. Final polynomial coefficients (16 bytes QM31 each)
-        for coeff in &self.fri_final_poly {
-            bytes.extend_from_slice(&coeff.to_bytes());
-        }
-        
-        // 9. Query count (1 byte)
-        bytes.push(self.queries.len() as u8);
-        
-        // 10. Queries
-        for query in &self.queries {
-            // Index (4 bytes)
-            bytes.extend_from_slice(&query.index.to_le_bytes());
-            
-            // Trace value (32 bytes)
-            bytes.extend_from_slice(&query.trace_value);
-            
-            // Trace path length (1 byte)
-            bytes.push(query.trace_path.len() as u8);
-            
-            // Trace path (32 bytes each)
-            for node in &query.trace_path {
-                bytes.extend_from_slice(node);
-            }
-            
-            // Composition value (32 bytes)
-            bytes.extend_from_slice(&query.composition_value);
-            
-            // Composition path length (1 byte)
-            bytes.push(query.composition_path.len() as u8);
-            
-            // Composition path (32 bytes each)
-            for node in &query.composition_path {
-                bytes.extend_from_slice(node);
-            }
-            
-            // FRI layer data (per layer: 4 siblings + path)
-            for (siblings, path) in &query.fri_layer_data {
-                // 4 sibling values (16 bytes QM31 each = 64 bytes)
-                for i in 0..4 {
-                    let sibling = siblings.get(i).copied().unwrap_or_default();
-                    bytes.extend_from_slice(&sibling.to_bytes());
-                }
-                
-                // Path length (1 byte)
-                bytes.push(path.len() as u8);
-                
-                // Path (32 bytes each)
-                for node in path {
-                    bytes.extend_from_slice(node);
-                }
-            }
+        self.to_wire().encode()
+    }
+
+    /// The canonical proof this one encodes as. This prover only commits
+    /// with keccak; missing FRI siblings are sent as zero.
+    pub fn to_wire(&self) -> SerializedProof {
+        let queries = self
+            .queries
+            .iter()
+            .map(|query| QueryOpening {
+                index: query.index,
+                trace_row: query.trace_values.clone(),
+                trace_path: query.trace_path.clone(),
+                composition_leaf: query.composition_value,
+                composition_path: query.composition_path.clone(),
+                fri: query
+                    .fri_layer_data
+                    .iter()
+                    .map(|(siblings, path)| FriOpening {
+                        siblings: core::array::from_fn(|i| qm31_to_wire(&siblings.get(i).copied().unwrap_or_default())),
+                        path: path.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        SerializedProof {
+            hash_kind: murkl_prover::HashKind::Keccak as u8,
+            log_trace_size: self.log_trace_size,
+            log_blowup: self.log_blowup,
+            log_folding_factor: self.log_folding_factor,
+            trace_commitment: self.trace_commitment,
+            composition_commitment: self.composition_commitment,
+            trace_columns: self.num_trace_columns,
+            trace_oods: self.trace_oods.iter().map(qm31_to_wire).collect(),
+            trace_oods_next: self.trace_oods_next.iter().map(qm31_to_wire).collect(),
+            composition_oods: qm31_to_wire(&self.composition_oods),
+            fri_layer_commitments: self.fri_layer_commitments.clone(),
+            final_layer: FinalLayer::Coefficients(self.fri_final_poly.iter().map(qm31_to_wire).collect()),
+            queries,
         }
-        
-        bytes
     }
     
     /// Create a new proof from builder parts
-    pub fn from_parts(
-        trace_commitment: [u8; 32],
-        composition_commitment: [u8; 32],
-        trace_oods: QM31,
-        composition_oods: QM31,
-        fri_layer_commitments: Vec<[u8; 32]>,
-        fri_final_poly: Vec<QM31>,
-        queries: Vec<QueryProof>,
-    ) -> Self {
-        Self {
+    pub fn from_parts(parts: ProofParts) -> Self {
+        let ProofParts {
             trace_commitment,
             composition_commitment,
+            num_trace_columns,
+            log_trace_size,
+            log_blowup,
+            log_folding_factor,
             trace_oods,
+            trace_oods_next,
             composition_oods,
             fri_layer_commitments,
             fri_final_poly,
             queries,
-            // Legacy (unused)
-            oods_values: vec![],
-            fri_layers: vec![],
-            last_layer_poly: vec![],
-            query_positions: vec![],
-            trace_decommitments: vec![],
-        }
-    }
-    
-    /// Deserialize (for local verification)
-    pub fn deserialize(bytes: &[u8]) -> Self {
-        let mut offset = 0;
-        
-        let mut trace_commitment = [0u8; 32];
-        trace_commitment.copy_from_slice(&bytes[offset..offset+32]);
-        offset += 32;
-        
-        let mut composition_commitment = [0u8; 32];
-        composition_commitment.copy_from_slice(&bytes[offset..offset+32]);
-        offset += 32;
-        
-        let trace_oods = Self::parse_qm31(&bytes[offset..offset+16]);
-        offset += 16;
-        
-        let composition_oods = Self::parse_qm31(&bytes[offset..offset+16]);
-        offset += 16;
-        
-        let num_fri_layers = bytes[offset] as usize;
-        offset += 1;
-        
-        let mut fri_layer_commitments = Vec::with_capacity(num_fri_layers);
-        for _ in 0..num_fri_layers {
-            let mut commitment = [0u8; 32];
-            commitment.copy_from_slice(&bytes[offset..offset+32]);
-            fri_layer_commitments.push(commitment);
-            offset += 32;
-        }
-        
-        let final_poly_count = u16::from_le_bytes([bytes[offset], bytes[offset+1]]) as usize;
-        offset += 2;
-        
-        let mut fri_final_poly = Vec::with_capacity(final_poly_count);
-        for _ in 0..final_poly_count {
-            fri_final_poly.push(Self::parse_qm31(&bytes[offset..offset+16]));
-            offset += 16;
-        }
-        
-        let num_queries = bytes[offset] as usize;
-        offset += 1;
-        
-        let mut queries = Vec::with_capacity(num_queries);
-        for _ in 0..num_queries {
-            let index = u32::from_le_bytes(bytes[offset..offset+4].try_into().unwrap());
-            offset += 4;
-            
-            let mut trace_value = [0u8; 32];
-            trace_value.copy_from_slice(&bytes[offset..offset+32]);
-            offset += 32;
-            
-            let trace_path_len = bytes[offset] as usize;
-            offset += 1;
-            
-            let mut trace_path = Vec::with_capacity(trace_path_len);
-            for _ in 0..trace_path_len {
-                let mut node = [0u8; 32];
-                node.copy_from_slice(&bytes[offset..offset+32]);
-                trace_path.push(node);
-                offset += 32;
-            }
-            
-            let mut composition_value = [0u8; 32];
-            composition_value.copy_from_slice(&bytes[offset..offset+32]);
-            offset += 32;
-            
-            let comp_path_len = bytes[offset] as usize;
-            offset += 1;
-            
-            let mut composition_path = Vec::with_capacity(comp_path_len);
-            for _ in 0..comp_path_len {
-                let mut node = [0u8; 32];
-                node.copy_from_slice(&bytes[offset..offset+32]);
-                composition_path.push(node);
-                offset += 32;
-            }
-            
-            let mut fri_layer_data = Vec::with_capacity(num_fri_layers);
-            for _ in 0..num_fri_layers {
-                let mut siblings = Vec::with_capacity(4);
-                for _ in 0..4 {
-                    siblings.push(Self::parse_qm31(&bytes[offset..offset+16]));
-                    offset += 16;
-                }
-                
-                let path_len = bytes[offset] as usize;
-                offset += 1;
-                
-                let mut path = Vec::with_capacity(path_len);
-                for _ in 0..path_len {
-                    let mut node = [0u8; 32];
-                    node.copy_from_slice(&bytes[offset..offset+32]);
-                    path.push(node);
-                    offset += 32;
-                }
-                
-                fri_layer_data.push((siblings, path));
-            }
-            
-            queries.push(QueryProof {
-                index,
-                trace_value,
-                trace_path,
-                composition_value,
-                composition_path,
-                fri_layer_data,
-            });
-        }
-        
+        } = parts;
         Self {
             trace_commitment,
             composition_commitment,
+            num_trace_columns,
+            log_trace_size,
+            log_blowup,
+            log_folding_factor,
             trace_oods,
+            trace_oods_next,
             composition_oods,
             fri_layer_commitments,
             fri_final_poly,
             queries,
+            pow_nonce: None,
+            // Legacy (unused)
             oods_values: vec![],
             fri_layers: vec![],
             last_layer_poly: vec![],
@@ -305,8 +458,177 @@ impl MurklProof {
         }
     }
     
-    fn parse_qm31(bytes: &[u8]) -> QM31 {
-        // Use SDK's from_bytes method
-        QM31::from_bytes(bytes)
+    /// Deserialize (for local verification)
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, ProofError> {
+        let wire = SerializedProof::decode(bytes)
+            .map_err(|e| ProofError::SerializationError(e.to_string()))?;
+        let fri_final_poly = match &wire.final_layer {
+            FinalLayer::Coefficients(coeffs) => coeffs.iter().map(qm31_from_wire).collect(),
+            FinalLayer::Evaluations(_) => {
+                return Err(ProofError::SerializationError(
+                    "final layer sent as evaluations; this prover only reads coefficients".into(),
+                ))
+            }
+        };
+        let queries = wire
+            .queries
+            .into_iter()
+            .map(|query| QueryProof {
+                index: query.index,
+                trace_values: query.trace_row,
+                trace_path: query.trace_path,
+                composition_value: query.composition_leaf,
+                composition_path: query.composition_path,
+                fri_layer_data: query
+                    .fri
+                    .into_iter()
+                    .map(|layer| (layer.siblings.iter().map(qm31_from_wire).collect(), layer.path))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self::from_parts(ProofParts {
+            trace_commitment: wire.trace_commitment,
+            composition_commitment: wire.composition_commitment,
+            num_trace_columns: wire.trace_columns,
+            log_trace_size: wire.log_trace_size,
+            log_blowup: wire.log_blowup,
+            log_folding_factor: wire.log_folding_factor,
+            trace_oods: wire.trace_oods.iter().map(qm31_from_wire).collect(),
+            trace_oods_next: wire.trace_oods_next.iter().map(qm31_from_wire).collect(),
+            composition_oods: qm31_from_wire(&wire.composition_oods),
+            fri_layer_commitments: wire.fri_layer_commitments,
+            fri_final_poly,
+            queries,
+        }))
+    }
+}
+
+fn qm31_to_wire(value: &QM31) -> murkl_proof_format::Qm31 {
+    [value.a.value(), value.b.value(), value.c.value(), value.d.value()]
+}
+
+fn qm31_from_wire(value: &murkl_proof_format::Qm31) -> QM31 {
+    QM31 { a: M31::new(value[0]), b: M31::new(value[1]), c: M31::new(value[2]), d: M31::new(value[3]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit(pool: &str, leaf_index: u32, commitment: &[u8; 32]) -> IndexedDeposit {
+        IndexedDeposit {
+            pool: pool.to_string(),
+            leaf_index,
+            commitment: format!("0x{}", hex::encode(commitment)),
+            amount: 1_000,
+            claimed: false,
+            deposited_at: None,
+            claimed_at: None,
+        }
+    }
+
+    fn snapshot(leaves: &[[u8; 32]]) -> MerkleData {
+        let depth = murkl_prover::TREE_DEPTH;
+        MerkleData {
+            version: MERKLE_DATA_VERSION,
+            pool: "poolA".to_string(),
+            root: hex::encode(murkl_prover::merkle::pool_root(leaves, depth)),
+            depth: depth as u32,
+            last_indexed_slot: 42,
+            leaves: leaves
+                .iter()
+                .enumerate()
+                .map(|(i, leaf)| MerkleLeaf { index: i as u32, commitment: hex::encode(leaf) })
+                .collect(),
+            roots: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merkle_data_round_trip_and_validation() {
+        let data = snapshot(&[[1u8; 32], [2u8; 32], [3u8; 32]]);
+        let json = serde_json::to_string(&data).unwrap();
+        let loaded = MerkleData::from_json(&json).unwrap();
+        assert_eq!(loaded.leaf(1), Some([2u8; 32]));
+        assert_eq!(loaded.witness_for(&[3u8; 32]).unwrap().leaf_index, 2);
+
+        let mut gap = data.clone();
+        gap.leaves.remove(1);
+        assert!(gap.validate().unwrap_err().to_string().contains("contiguous"));
+
+        let mut wrong_root = data.clone();
+        wrong_root.root = hex::encode([9u8; 32]);
+        assert!(wrong_root.validate().is_err());
+
+        let unversioned = json.replace("\"version\":1,", "");
+        assert!(MerkleData::from_json(&unversioned).is_err());
+        let future = json.replace("\"version\":1", "\"version\":2");
+        assert!(MerkleData::from_json(&future).is_err());
+    }
+
+    #[test]
+    fn test_merkle_data_refuses_stale_or_foreign_snapshot() {
+        let data = snapshot(&[[1u8; 32]]);
+        let current = murkl_prover::merkle::pool_root(&[[1u8; 32]], murkl_prover::TREE_DEPTH);
+        assert!(data.check_current(Some("poolA"), Some(&current)).is_ok());
+        assert!(data.check_current(Some("poolB"), None).is_err());
+
+        let newer = murkl_prover::merkle::pool_root(&[[1u8; 32], [2u8; 32]], murkl_prover::TREE_DEPTH);
+        let err = data.check_current(None, Some(&newer)).unwrap_err().to_string();
+        assert!(err.contains("stale") && err.contains("slot 42"));
+    }
+
+    #[test]
+    fn test_merkle_data_at_historical_root() {
+        let leaves = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let mut data = snapshot(&leaves);
+        data.roots = (1..=leaves.len())
+            .map(|n| RootCheckpoint {
+                leaf_count: n as u32,
+                slot: 40 + n as u64,
+                root: hex::encode(murkl_prover::merkle::pool_root(&leaves[..n], murkl_prover::TREE_DEPTH)),
+            })
+            .collect();
+        let json = serde_json::to_string(&data).unwrap();
+        let data = MerkleData::from_json(&json).unwrap();
+
+        let older = murkl_prover::merkle::pool_root(&leaves[..2], murkl_prover::TREE_DEPTH);
+        let past = data.at_root(&older).unwrap();
+        assert_eq!(past.leaves.len(), 2);
+        assert_eq!(past.last_indexed_slot, 42);
+        assert_eq!(past.roots.len(), 2);
+        assert_eq!(past.witness_for(&[2u8; 32]).unwrap().root, older);
+        assert!(past.witness_for(&[3u8; 32]).is_err());
+
+        let current = murkl_prover::merkle::pool_root(&leaves, murkl_prover::TREE_DEPTH);
+        assert_eq!(data.at_root(&current).unwrap().leaves.len(), 3);
+        assert!(data.at_root(&[9u8; 32]).unwrap_err().to_string().contains("cover 1 to 3 leaves"));
+
+        // A forged checkpoint is caught when it is used
+        let mut forged = data.clone();
+        forged.roots[0].root = hex::encode([9u8; 32]);
+        assert!(forged.at_root(&[9u8; 32]).is_err());
+
+        let mut unordered = data.clone();
+        unordered.roots.swap(0, 1);
+        assert!(unordered.validate().is_err());
+    }
+
+    #[test]
+    fn test_find_deposits_filters_pool_and_returns_all_matches() {
+        let mine = [7u8; 32];
+        let other = [9u8; 32];
+        let deposits = vec![
+            deposit("poolA", 5, &mine),
+            deposit("poolA", 1, &other),
+            deposit("poolB", 2, &mine),
+            deposit("poolA", 3, &mine),
+        ];
+
+        let found = find_deposits(&deposits, "poolA", &mine);
+        let indices: Vec<u32> = found.iter().map(|d| d.leaf_index).collect();
+        assert_eq!(indices, vec![3, 5]);
+        assert!(find_deposits(&deposits, "poolC", &mine).is_empty());
     }
 }
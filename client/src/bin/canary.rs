@@ -0,0 +1,355 @@
+//! Murkl devnet canary
+//!
+//! Runs the whole deposit → prove → claim loop against a live cluster with a
+//! test mint, the way a wallet and relayer would: deposit from the canary's
+//! token account, prove with the same prover the browser runs
+//! (`murkl_wasm::prove_claim`), upload and verify through a proof buffer,
+//! then claim back into the same account with no relayer fee.
+//!
+//! Every run appends one JSON line to `--history` (proof size, prover time,
+//! verification and claim CU) so trends show up before limits are hit. A
+//! failed step posts to `--webhook`; with `--interval 0` the canary runs once
+//! and exits 1 on failure, for cron or CI.
+//!
+//! ```text
+//! canary --keypair canary.json --mint <TEST_MINT> \
+//!     --interval 900 --history canary.jsonl --webhook https://hooks.example/...
+//! ```
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use clap::Parser;
+use murkl_client::{murkl, pda, verifier, OnChainError, DEFAULT_CHUNK_SIZE};
+use murkl_program::{DepositRecord, Pool, PoolMerkle};
+use murkl_prover::metrics::{system_clock, StageRecorder};
+use murkl_prover::merkle::pool_frontier_path;
+use murkl_prover::{hash_identifier, hash_password, pq_commitment, HashKind};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::UiTransactionEncoding;
+
+/// CU limit for `finalize_and_verify` (the runtime maximum)
+const VERIFY_CU_LIMIT: u32 = 1_400_000;
+
+#[derive(Parser)]
+#[command(name = "canary")]
+#[command(about = "Continuous deposit → prove → claim check against a live cluster")]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, default_value = "https://api.devnet.solana.com")]
+    rpc_url: String,
+
+    /// Fee payer, depositor and relayer; must hold SOL and the test mint
+    #[arg(long)]
+    keypair: PathBuf,
+
+    /// Test token mint with an initialized pool
+    #[arg(long)]
+    mint: Pubkey,
+
+    /// Amount deposited and claimed back each run (base units)
+    #[arg(long, default_value = "1000")]
+    amount: u64,
+
+    /// Seconds between runs; 0 runs once and exits non-zero on failure
+    #[arg(long, default_value = "0")]
+    interval: u64,
+
+    /// Append one JSON line per run to this file
+    #[arg(long)]
+    history: Option<PathBuf>,
+
+    /// POST `{"text": ...}` here when a run fails
+    #[arg(long)]
+    webhook: Option<String>,
+}
+
+/// One canary run, as written to the history file
+#[derive(Serialize, Default)]
+struct RunRecord {
+    /// Unix seconds
+    started_at: u64,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed_step: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    leaf_index: Option<u64>,
+    proof_bytes: Option<usize>,
+    prove_ms: Option<u64>,
+    verify_cu: Option<u64>,
+    claim_cu: Option<u64>,
+    elapsed_ms: u64,
+}
+
+/// A failed step of the loop
+struct StepError {
+    step: &'static str,
+    error: String,
+}
+
+trait Step<T> {
+    fn step(self, step: &'static str) -> Result<T, StepError>;
+}
+
+impl<T, E: std::fmt::Display> Step<T> for Result<T, E> {
+    fn step(self, step: &'static str) -> Result<T, StepError> {
+        self.map_err(|e| StepError { step, error: e.to_string() })
+    }
+}
+
+struct Canary {
+    rpc: RpcClient,
+    payer: Keypair,
+    mint: Pubkey,
+    token: Pubkey,
+    amount: u64,
+}
+
+impl Canary {
+    fn send(&self, instructions: &[Instruction], extra: &[&Keypair]) -> Result<Signature, String> {
+        let blockhash = self.rpc.get_latest_blockhash().map_err(|e| e.to_string())?;
+        let mut signers = vec![&self.payer];
+        signers.extend_from_slice(extra);
+        let tx = Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &signers, blockhash);
+        self.rpc.send_and_confirm_transaction(&tx).map_err(|e| {
+            // Name program errors (and what to do) instead of "custom program error: 0x..."
+            e.get_transaction_error()
+                .and_then(|err| OnChainError::from_transaction_error(instructions, &err))
+                .map_or_else(|| e.to_string(), |err| err.to_string())
+        })
+    }
+
+    fn fetch<T: AccountDeserialize>(&self, key: &Pubkey) -> Result<T, String> {
+        let data = self.rpc.get_account_data(key).map_err(|e| e.to_string())?;
+        T::try_deserialize(&mut data.as_slice()).map_err(|e| e.to_string())
+    }
+
+    /// Compute units a confirmed transaction consumed
+    fn compute_units(&self, signature: &Signature) -> Result<u64, String> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = self.rpc.get_transaction_with_config(signature, config).map_err(|e| e.to_string())?;
+        let meta = tx.transaction.meta.ok_or("transaction has no meta")?;
+        Option::<u64>::from(meta.compute_units_consumed).ok_or_else(|| "RPC did not report compute units".into())
+    }
+
+    /// Deposit, prove, verify and claim once, filling `record` as steps finish
+    fn run(&self, record: &mut RunRecord) -> Result<(), StepError> {
+        let pool_key = pda::pool(&self.mint);
+        let pool: Pool = self.fetch(&pool_key).step("fetch_pool")?;
+        let leaf_index = pool.leaf_count;
+        record.leaf_index = Some(leaf_index);
+
+        // Fresh note per run; nothing needs to survive the process
+        let identifier = format!("canary-{}-{}", record.started_at, leaf_index);
+        let password = Keypair::new().pubkey().to_string();
+        let commitment = pq_commitment(hash_identifier(&identifier), hash_password(&password));
+
+        let deposit = murkl::deposit(
+            &self.payer.pubkey(),
+            &self.token,
+            &self.mint,
+            leaf_index,
+            self.amount,
+            commitment,
+            pool.stats_enabled,
+        );
+        self.send(&[deposit], &[]).step("deposit")?;
+
+        let merkle_root = self.fetch::<Pool>(&pool_key).step("fetch_root")?.merkle_root;
+        // The canary's deposit is the newest leaf, so the frontier alone
+        // gives its path
+        let frontier = self.fetch::<PoolMerkle>(&pda::pool_merkle(&pool_key)).step("fetch_path")?.branch;
+        let merkle_path = pool_frontier_path(&frontier, leaf_index as usize);
+        let mut recorder = StageRecorder::new(system_clock);
+        let bundle = murkl_wasm::prove_claim(
+            &identifier,
+            &password,
+            leaf_index as u32,
+            &merkle_root,
+            &merkle_path,
+            &self.token.to_bytes(),
+            HashKind::Keccak,
+            &mut recorder,
+        )
+        .step("prove")?;
+        let metrics = recorder.into_metrics(bundle.proof.len());
+        record.proof_bytes = Some(metrics.proof_bytes);
+        record.prove_ms = Some(metrics.total_elapsed().as_millis() as u64);
+
+        let buffer = Keypair::new();
+        let owner = self.payer.pubkey();
+        let len = bundle.proof.len();
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(verifier::buffer_space(len))
+            .step("buffer_rent")?;
+        self.send(
+            &[
+                verifier::create_proof_buffer(&owner, &buffer.pubkey(), len, lamports),
+                verifier::init_proof_buffer(
+                    &owner,
+                    &buffer.pubkey(),
+                    &bundle.proof,
+                    verifier::DEFAULT_BUFFER_EXPIRY_SLOTS,
+                ),
+            ],
+            &[&buffer],
+        )
+        .step("create_buffer")?;
+
+        let verified = self.verify_and_claim(&buffer.pubkey(), &bundle, merkle_root, leaf_index, record, &pool);
+        // Refund the buffer either way; a failure here only costs rent
+        if let Err(e) = self.send(&[verifier::close_proof_buffer(&owner, &buffer.pubkey())], &[]) {
+            eprintln!("warning: close_proof_buffer failed: {}", e);
+        }
+        verified?;
+
+        let claimed: DepositRecord = self.fetch(&pda::deposit(&pool_key, leaf_index)).step("fetch_deposit")?;
+        if !claimed.claimed {
+            return Err(StepError { step: "fetch_deposit", error: "deposit not marked claimed".into() });
+        }
+        Ok(())
+    }
+
+    fn verify_and_claim(
+        &self,
+        buffer: &Pubkey,
+        bundle: &murkl_prover::ProofBundle,
+        merkle_root: [u8; 32],
+        leaf_index: u64,
+        record: &mut RunRecord,
+        pool: &Pool,
+    ) -> Result<(), StepError> {
+        let owner = self.payer.pubkey();
+        for chunk in verifier::upload_chunks(&owner, buffer, &bundle.proof, DEFAULT_CHUNK_SIZE) {
+            self.send(&[chunk], &[]).step("upload")?;
+        }
+
+        let finalize = verifier::finalize_and_verify(
+            &owner,
+            buffer,
+            bundle.commitment,
+            bundle.nullifier,
+            merkle_root,
+            self.token.to_bytes(),
+            false,
+        );
+        let signature = self
+            .send(&[ComputeBudgetInstruction::set_compute_unit_limit(VERIFY_CU_LIMIT), finalize], &[])
+            .step("finalize_and_verify")?;
+        record.verify_cu = Some(self.compute_units(&signature).step("verify_cu")?);
+
+        // Canary is its own relayer and recipient, so the tokens come back
+        let claim = murkl::claim(
+            &owner,
+            &self.token,
+            &self.token,
+            buffer,
+            &self.mint,
+            leaf_index,
+            bundle.nullifier,
+            0,
+            pool.stats_enabled,
+            pool.nullifier_bloom_enabled,
+            None,
+            false,
+        );
+        let signature = self.send(&[claim], &[]).step("claim")?;
+        record.claim_cu = Some(self.compute_units(&signature).step("claim_cu")?);
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn append_history(path: &PathBuf, record: &RunRecord) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+fn alert(webhook: &str, text: &str) {
+    let body = serde_json::json!({ "text": text });
+    match reqwest::blocking::Client::new().post(webhook).json(&body).send() {
+        Ok(resp) if !resp.status().is_success() => eprintln!("warning: webhook returned {}", resp.status()),
+        Err(e) => eprintln!("warning: webhook failed: {}", e),
+        Ok(_) => {}
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let payer = match read_keypair_file(&args.keypair) {
+        Ok(k) => k,
+        Err(e) => {
+            eprintln!("error: cannot read keypair {}: {}", args.keypair.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let canary = Canary {
+        rpc: RpcClient::new_with_commitment(args.rpc_url.clone(), CommitmentConfig::confirmed()),
+        token: get_associated_token_address(&payer.pubkey(), &args.mint),
+        payer,
+        mint: args.mint,
+        amount: args.amount,
+    };
+
+    loop {
+        let start = Instant::now();
+        let mut record = RunRecord { started_at: unix_now(), ..Default::default() };
+        let result = canary.run(&mut record);
+        record.elapsed_ms = start.elapsed().as_millis() as u64;
+        record.ok = result.is_ok();
+
+        match &result {
+            Ok(()) => println!(
+                "ok leaf={} proof={}B prove={}ms verify={}CU claim={}CU",
+                record.leaf_index.unwrap_or_default(),
+                record.proof_bytes.unwrap_or_default(),
+                record.prove_ms.unwrap_or_default(),
+                record.verify_cu.unwrap_or_default(),
+                record.claim_cu.unwrap_or_default(),
+            ),
+            Err(e) => {
+                record.failed_step = Some(e.step);
+                record.error = Some(e.error.clone());
+                let text = format!("murkl canary ({}): {} failed: {}", args.rpc_url, e.step, e.error);
+                eprintln!("{}", text);
+                if let Some(webhook) = &args.webhook {
+                    alert(webhook, &text);
+                }
+            }
+        }
+
+        if let Some(path) = &args.history {
+            if let Err(e) = append_history(path, &record) {
+                eprintln!("warning: cannot write {}: {}", path.display(), e);
+            }
+        }
+
+        if args.interval == 0 {
+            return if result.is_ok() { ExitCode::SUCCESS } else { ExitCode::FAILURE };
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
@@ -0,0 +1,10 @@
+//! Print the on-chain error table as JSON, for the relayer:
+//!
+//! ```text
+//! cargo run --bin error-table > ../relayer/src/program-errors.json
+//! ```
+
+fn main() {
+    let table = murkl_client::errors::error_table();
+    println!("{}", serde_json::to_string_pretty(&table).expect("table serializes"));
+}
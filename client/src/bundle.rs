@@ -0,0 +1,320 @@
+//! Claim transactions, planned from the proof
+//!
+//! Once the proof is uploaded, a claim still has to verify it and pay out.
+//! [`plan_claim`] reads the proof's query count and lays both out in as few
+//! v0 transactions as fit the packet size and compute limit:
+//!
+//! - Up to about 8 queries, `finalize_and_verify` fits one transaction, and
+//!   below that the claim usually rides along with it.
+//! - Beyond that the proof goes through staged verification. Stages share
+//!   transactions where they fit, and the claim goes in with
+//!   `finalize_staged_verification`.
+//!
+//! Each planned transaction starts with a compute unit limit for the
+//! estimated work. The estimates are rough, so simulate before sending. A
+//! query batch that stops short logs how many queries it verified; send
+//! [`verifier::verify_step`] until it completes, then the rest of the plan.
+//!
+//! The upload before it is planned by [`plan_upload`].
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_sdk::address_lookup_table::instruction::{create_lookup_table, extend_lookup_table};
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::hash::Hash;
+use solana_sdk::message::{v0, CompileError, VersionedMessage};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use stark_verifier::{chunk_received, upload_chunk_count, ProofHeader, STAGED_QUERY_CU_RESERVE, UPLOAD_CHUNK_ALIGN};
+
+use crate::verifier::{self, BatchEntry};
+use crate::{pda, MURKL_PROGRAM_ID, STARK_VERIFIER_ID};
+
+/// Compute units one transaction may request
+pub const MAX_TRANSACTION_CU: u32 = 1_400_000;
+/// Parsing, transcript and OODS check; once per verification
+pub const VERIFY_BASE_CU: u32 = 60_000;
+/// One query opening's Merkle paths and FRI folds. At this rate a direct
+/// verification stops fitting a transaction past 8 queries.
+pub const QUERY_CU: u32 = 160_000;
+/// `begin_staged_verification` or `finalize_staged_verification`
+pub const STAGE_CU: u32 = 20_000;
+/// The claim together with its setup (token accounts, nullifier shard)
+pub const CLAIM_CU: u32 = 100_000;
+
+/// How a planned claim verifies its proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// One `finalize_and_verify`
+    Direct,
+    /// `begin_staged_verification` through `finalize_staged_verification`
+    Staged,
+}
+
+/// One transaction of a [`ClaimPlan`]. Its size leaves room for a compute
+/// unit price instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedTransaction {
+    /// Led by the compute unit limit
+    pub instructions: Vec<Instruction>,
+    /// Estimated compute units, which the limit requests
+    pub compute_units: u32,
+}
+
+/// Transactions of a claim, to be sent in order
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimPlan {
+    pub verification: Verification,
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    /// The proof header doesn't parse
+    Proof(anchor_lang::error::Error),
+    /// `step` does not fit a transaction on its own
+    TooLarge { step: &'static str },
+    /// The instructions don't compile against the lookup tables
+    Compile(CompileError),
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Proof(e) => write!(f, "invalid proof: {}", e),
+            Self::TooLarge { step } => write!(f, "{} does not fit a transaction", step),
+            Self::Compile(e) => write!(f, "cannot compile transaction: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Plan the verification of `proof`, uploaded to `owner`'s `buffer`, and
+/// the `claim` instructions after it (e.g. [`crate::murkl::claim`], led by
+/// any token account or shard setup).
+///
+/// `owner` pays for every transaction. `lookup_tables` are the active tables
+/// the transactions will be compiled against. Without a
+/// [`claim_lookup_table`], a claim that also creates token accounts or
+/// passes compliance accounts tends to need a transaction of its own.
+pub fn plan_claim(
+    owner: &Pubkey,
+    buffer: &Pubkey,
+    proof: &[u8],
+    inputs: &BatchEntry,
+    claim: Vec<Instruction>,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<ClaimPlan, PlanError> {
+    let num_queries = ProofHeader::parse(proof).map_err(PlanError::Proof)?.num_queries as u32;
+    let BatchEntry { commitment, nullifier, merkle_root, recipient } = inputs.clone();
+    let mut packer = Packer::new(owner, lookup_tables);
+
+    let direct_cu = VERIFY_BASE_CU + num_queries * QUERY_CU;
+    let verification = if direct_cu <= MAX_TRANSACTION_CU {
+        let finalize = verifier::finalize_and_verify(owner, buffer, commitment, nullifier, merkle_root, recipient, false);
+        packer.push("finalize_and_verify", vec![finalize], direct_cu)?;
+        Verification::Direct
+    } else {
+        let begin = verifier::begin_staged_verification(owner, buffer, commitment, nullifier, merkle_root, recipient);
+        packer.push("begin_staged_verification", vec![begin], STAGE_CU)?;
+        packer.push("verify_staged_oods", vec![verifier::verify_staged_oods(owner, buffer)], VERIFY_BASE_CU)?;
+
+        let mut left = num_queries;
+        while left > 0 {
+            let count = packer.room_for_queries().min(left).min(u8::MAX as u32);
+            if count == 0 {
+                packer.flush();
+                continue;
+            }
+            let batch = verifier::verify_staged_queries(owner, buffer, count as u8);
+            packer.push("verify_staged_queries", vec![batch], count * QUERY_CU)?;
+            left -= count;
+        }
+
+        let finalize = verifier::finalize_staged_verification(owner, buffer);
+        packer.push("finalize_staged_verification", vec![finalize], STAGE_CU)?;
+        Verification::Staged
+    };
+    packer.push("claim", claim, CLAIM_CU)?;
+    packer.flush();
+
+    Ok(ClaimPlan { verification, transactions: packer.done })
+}
+
+/// Upload the chunks of `proof` missing from `received`, one
+/// [`verifier::upload_chunks_multi`] per transaction.
+///
+/// `received` is the buffer's upload bitmap ([`stark_verifier::upload_bitmap`]
+/// of its data), or empty for a fresh buffer. Each instruction takes as
+/// many missing chunks as fit a transaction `uploader` pays for, compiled
+/// against `lookup_tables` and with room for a compute unit price; a buffer
+/// in a lookup table leaves room for more. The instructions are independent
+/// and may land in any order. Once they have, plan again with the new
+/// bitmap to resend whatever was dropped: an empty plan means the upload is
+/// complete.
+pub fn plan_upload(
+    uploader: &Pubkey,
+    buffer: &Pubkey,
+    proof: &[u8],
+    received: &[u8],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Instruction>, PlanError> {
+    let missing: Vec<usize> =
+        (0..upload_chunk_count(proof.len())).filter(|&i| !chunk_received(received, i)).collect();
+    let upload = |chunks: &[usize]| {
+        // Runs of consecutive chunks become one segment each
+        let mut segments: Vec<(u32, &[u8])> = Vec::new();
+        for run in chunks.chunk_by(|a, b| a + 1 == *b) {
+            let start = run[0] * UPLOAD_CHUNK_ALIGN;
+            let end = ((run[run.len() - 1] + 1) * UPLOAD_CHUNK_ALIGN).min(proof.len());
+            segments.push((start as u32, &proof[start..end]));
+        }
+        verifier::upload_chunks_multi(uploader, buffer, &segments)
+    };
+    let fits = |ix: Instruction| -> Result<bool, PlanError> {
+        let instructions = [ComputeBudgetInstruction::set_compute_unit_price(0), ix];
+        Ok(transaction_size(uploader, &instructions, lookup_tables)? <= PACKET_DATA_SIZE)
+    };
+
+    let mut uploads = Vec::new();
+    let mut start = 0;
+    while start < missing.len() {
+        if !fits(upload(&missing[start..start + 1]))? {
+            return Err(PlanError::TooLarge { step: "upload_chunks_multi" });
+        }
+        let mut end = start + 1;
+        while end < missing.len() && fits(upload(&missing[start..end + 1]))? {
+            end += 1;
+        }
+        uploads.push(upload(&missing[start..end]));
+        start = end;
+    }
+    Ok(uploads)
+}
+
+/// Fills transactions in order, starting the next one when a step would
+/// overflow the packet or the compute limit
+struct Packer<'a> {
+    payer: &'a Pubkey,
+    lookup_tables: &'a [AddressLookupTableAccount],
+    done: Vec<PlannedTransaction>,
+    current: Vec<Instruction>,
+    compute_units: u32,
+}
+
+impl<'a> Packer<'a> {
+    fn new(payer: &'a Pubkey, lookup_tables: &'a [AddressLookupTableAccount]) -> Self {
+        Self { payer, lookup_tables, done: Vec::new(), current: Vec::new(), compute_units: 0 }
+    }
+
+    fn with_limit(compute_units: u32, instructions: &[Instruction]) -> Vec<Instruction> {
+        let mut all = Vec::with_capacity(instructions.len() + 1);
+        all.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_units));
+        all.extend_from_slice(instructions);
+        all
+    }
+
+    fn fits(&self, step: &[Instruction], compute_units: u32) -> Result<bool, PlanError> {
+        let total = self.compute_units + compute_units;
+        if total > MAX_TRANSACTION_CU {
+            return Ok(false);
+        }
+        let mut instructions = Self::with_limit(total, &self.current);
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(0));
+        instructions.extend_from_slice(step);
+        Ok(transaction_size(self.payer, &instructions, self.lookup_tables)? <= PACKET_DATA_SIZE)
+    }
+
+    fn push(
+        &mut self,
+        name: &'static str,
+        step: Vec<Instruction>,
+        compute_units: u32,
+    ) -> Result<(), PlanError> {
+        if !self.fits(&step, compute_units)? {
+            self.flush();
+            if !self.fits(&step, compute_units)? {
+                return Err(PlanError::TooLarge { step: name });
+            }
+        }
+        self.current.extend(step);
+        self.compute_units += compute_units;
+        Ok(())
+    }
+
+    /// Queries the current transaction has compute left for, keeping the
+    /// reserve `verify_staged_queries` stops at
+    fn room_for_queries(&self) -> u32 {
+        MAX_TRANSACTION_CU.saturating_sub(self.compute_units + STAGED_QUERY_CU_RESERVE as u32) / QUERY_CU
+    }
+
+    fn flush(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        let instructions = Self::with_limit(self.compute_units, &std::mem::take(&mut self.current));
+        self.done.push(PlannedTransaction { instructions, compute_units: self.compute_units });
+        self.compute_units = 0;
+    }
+}
+
+/// Wire size of a signed v0 transaction of `instructions`
+pub fn transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<usize, PlanError> {
+    let message =
+        v0::Message::try_compile(payer, instructions, lookup_tables, Hash::default()).map_err(PlanError::Compile)?;
+    let signatures = message.header.num_required_signatures as usize;
+    // Signature count as a one-byte compact-u16, then the signatures
+    Ok(1 + signatures * 64 + VersionedMessage::V0(message).serialize().len())
+}
+
+/// Accounts every claim from the pool of `token_mint` shares, for a
+/// relayer paying out through `relayer_token`: the programs, the pool's
+/// PDAs and the mint. Per-claim accounts (deposit, nullifier, buffer,
+/// recipient) and signers stay in the transaction.
+pub fn claim_lookup_addresses(token_mint: &Pubkey, relayer_token: &Pubkey) -> Vec<Pubkey> {
+    let pool = pda::pool(token_mint);
+    vec![
+        MURKL_PROGRAM_ID,
+        STARK_VERIFIER_ID,
+        anchor_spl::token::ID,
+        anchor_spl::associated_token::ID,
+        anchor_lang::system_program::ID,
+        compute_budget::ID,
+        pool,
+        pda::vault(&pool),
+        pda::pool_stats(&pool),
+        pda::nullifier_bloom(&pool),
+        pda::verifier_params(),
+        pda::verifier_config(),
+        *token_mint,
+        *relayer_token,
+    ]
+}
+
+/// Create a lookup table of [`claim_lookup_addresses`] owned by
+/// `authority`, returning its address and the instructions.
+///
+/// `recent_slot` has to be a recent finalized slot. The table resolves from
+/// the slot after these instructions land, so a relayer creates it once per
+/// pool, not per claim.
+pub fn claim_lookup_table(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+    token_mint: &Pubkey,
+    relayer_token: &Pubkey,
+) -> (Pubkey, Vec<Instruction>) {
+    let (create, table) = create_lookup_table(*authority, *payer, recent_slot);
+    let extend = extend_lookup_table(
+        table,
+        *authority,
+        Some(*payer),
+        claim_lookup_addresses(token_mint, relayer_token),
+    );
+    (table, vec![create, extend])
+}
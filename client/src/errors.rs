@@ -0,0 +1,389 @@
+//! On-chain error codes, decoded
+//!
+//! Both programs number their errors from 6000, so a bare code means nothing
+//! without the program that raised it. [`OnChainError::from_transaction_error`]
+//! takes the failing instruction's program from the transaction and returns
+//! the typed error, whose [`OnChainError::hint`] says what to do next.
+//!
+//! The relayer can't link this crate, so it reads the same table from
+//! `relayer/src/program-errors.json`; `tests/errors.rs` fails when the two
+//! drift. Regenerate the file with `cargo run --bin error-table`.
+
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use murkl_program::MurklError;
+use serde::Serialize;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::transaction::TransactionError;
+use stark_verifier::{first_missing_range, upload_bitmap, VerifierError};
+
+use crate::{MURKL_PROGRAM_ID, STARK_VERIFIER_ID};
+
+/// First custom error code Anchor assigns
+const ERROR_CODE_OFFSET: u32 = 6000;
+
+/// `MurklError` variants in declaration order, so index + 6000 is the code.
+/// A new variant goes at the end here too; its missing hint below stops the
+/// build until it has one.
+const MURKL_ERRORS: &[MurklError] = &[
+    MurklError::PoolPaused,
+    MurklError::DepositTooSmall,
+    MurklError::AlreadyClaimed,
+    MurklError::CommitmentMismatch,
+    MurklError::MerkleRootMismatch,
+    MurklError::FeeTooHigh,
+    MurklError::Unauthorized,
+    MurklError::ProofNotVerified,
+    MurklError::InvalidVerifierBuffer,
+    MurklError::InvalidVault,
+    MurklError::InvalidTokenMint,
+    MurklError::NullifierMismatch,
+    MurklError::NullifierReplay,
+    MurklError::InvalidDepositPool,
+    MurklError::InvalidPoolConfig,
+    MurklError::MathOverflow,
+    MurklError::InvalidPoolAccount,
+    MurklError::ComplianceHookMissing,
+    MurklError::ComplianceRejected,
+    MurklError::PathClaimsDisabled,
+    MurklError::InvalidMerklePath,
+    MurklError::ProofExpired,
+    MurklError::PoolStatsMissing,
+    MurklError::SubscriptionsDisabled,
+    MurklError::InvalidEpochCount,
+    MurklError::InvalidEpochDuration,
+    MurklError::EpochOutOfRange,
+    MurklError::EpochNotOpen,
+    MurklError::MerkleTreeFull,
+    MurklError::InvalidRootHistoryWindow,
+    MurklError::VerifierParamsMissing,
+    MurklError::VerifierParamsMismatch,
+    MurklError::WrongPoolKind,
+    MurklError::NullifierRegistryDisabled,
+    MurklError::NullifierRegistryLocked,
+    MurklError::InvalidNullifierShard,
+    MurklError::NullifierAlreadyUsed,
+    MurklError::DepositRevealRequired,
+    MurklError::DepositRevealDisabled,
+    MurklError::InvalidRevealWindow,
+    MurklError::RevealTooEarly,
+    MurklError::RegistrationExpired,
+    MurklError::RegistrationMismatch,
+    MurklError::RegistrationNotExpired,
+    MurklError::MintAlreadyAccepted,
+    MurklError::TooManyAcceptedMints,
+    MurklError::MintDecimalsMismatch,
+    MurklError::InvalidSecurityLevel,
+    MurklError::VerifierBelowSecurityLevel,
+    MurklError::NullifierBloomMissing,
+    MurklError::VaultRotationLocked,
+];
+
+/// `VerifierError` variants in declaration order
+const VERIFIER_ERRORS: &[VerifierError] = &[
+    VerifierError::InvalidProofFormat,
+    VerifierError::ProofTooLarge,
+    VerifierError::BufferTooSmall,
+    VerifierError::BufferCorrupt,
+    VerifierError::Unauthorized,
+    VerifierError::LamportOverflow,
+    VerifierError::BufferAlreadyFinalized,
+    VerifierError::ProofNotFinalized,
+    VerifierError::IncompleteProof,
+    VerifierError::ConstraintMismatch,
+    VerifierError::FinalPolyDegreeTooHigh,
+    VerifierError::TraceMerklePathFailed,
+    VerifierError::CompositionMerklePathFailed,
+    VerifierError::FriFoldingFailed,
+    VerifierError::QueryIndexMismatch,
+    VerifierError::FinalPolyMismatch,
+    VerifierError::FriLayerCountTooLow,
+    VerifierError::TooFewQueries,
+    VerifierError::TraceColumnCountMismatch,
+    VerifierError::VerificationPhaseMismatch,
+    VerifierError::ProofChangedDuringVerification,
+    VerifierError::UnsupportedHashKind,
+    VerifierError::InvalidBatch,
+    VerifierError::DomainSizeMismatch,
+    VerifierError::UnsupportedProofVersion,
+    VerifierError::FoldingFactorMismatch,
+    VerifierError::InvalidPublicInputs,
+    VerifierError::PublicInputsMismatch,
+    VerifierError::InvalidBufferExpiry,
+    VerifierError::BufferNotExpired,
+    VerifierError::UnalignedUpload,
+    VerifierError::InvalidUploadSegments,
+    VerifierError::ProofHashMismatch,
+    VerifierError::NullifierMismatch,
+    VerifierError::NonCanonicalPublicInput,
+    VerifierError::InvalidSecurityLevel,
+    VerifierError::SecurityLevelNotAllowed,
+];
+
+/// A custom error from one of the Murkl programs
+#[derive(Clone, Copy, Debug)]
+pub enum OnChainError {
+    Murkl(MurklError),
+    Verifier(VerifierError),
+}
+
+impl PartialEq for OnChainError {
+    fn eq(&self, other: &Self) -> bool {
+        self.program_id() == other.program_id() && self.code() == other.code()
+    }
+}
+
+impl Eq for OnChainError {}
+
+impl OnChainError {
+    /// Every error of both programs, murkl first
+    pub fn all() -> impl Iterator<Item = OnChainError> {
+        MURKL_ERRORS
+            .iter()
+            .map(|&e| OnChainError::Murkl(e))
+            .chain(VERIFIER_ERRORS.iter().map(|&e| OnChainError::Verifier(e)))
+    }
+
+    /// The error `program_id` reports as custom error `code`, if it is one
+    /// of the two programs and the code is one of its errors
+    pub fn decode(program_id: &Pubkey, code: u32) -> Option<Self> {
+        let index = code.checked_sub(ERROR_CODE_OFFSET)? as usize;
+        if *program_id == MURKL_PROGRAM_ID {
+            MURKL_ERRORS.get(index).map(|&e| OnChainError::Murkl(e))
+        } else if *program_id == STARK_VERIFIER_ID {
+            VERIFIER_ERRORS.get(index).map(|&e| OnChainError::Verifier(e))
+        } else {
+            None
+        }
+    }
+
+    /// The error behind a failed transaction of `instructions`, when one
+    /// of them failed with a Murkl program's custom error
+    pub fn from_transaction_error(instructions: &[Instruction], err: &TransactionError) -> Option<Self> {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        let ix = instructions.get(*index as usize)?;
+        Self::decode(&ix.program_id, *code)
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            OnChainError::Murkl(_) => MURKL_PROGRAM_ID,
+            OnChainError::Verifier(_) => STARK_VERIFIER_ID,
+        }
+    }
+
+    /// Custom error code as the runtime reports it
+    pub fn code(&self) -> u32 {
+        match *self {
+            OnChainError::Murkl(e) => e.into(),
+            OnChainError::Verifier(e) => e.into(),
+        }
+    }
+
+    /// Variant name, e.g. `IncompleteProof`
+    pub fn name(&self) -> String {
+        match self {
+            OnChainError::Murkl(e) => e.name(),
+            OnChainError::Verifier(e) => e.name(),
+        }
+    }
+
+    /// The program's own `#[msg]`
+    pub fn message(&self) -> String {
+        match self {
+            OnChainError::Murkl(e) => e.to_string(),
+            OnChainError::Verifier(e) => e.to_string(),
+        }
+    }
+
+    /// What the caller can do about it
+    pub fn hint(&self) -> &'static str {
+        match self {
+            OnChainError::Murkl(e) => murkl_hint(e),
+            OnChainError::Verifier(e) => verifier_hint(e),
+        }
+    }
+
+    /// [`hint`](Self::hint), made specific with the proof buffer's data when
+    /// the error is about it: an incomplete upload names the first byte
+    /// missing
+    pub fn remediation(&self, buffer_data: Option<&[u8]>) -> String {
+        match (self, buffer_data.and_then(first_missing_byte)) {
+            (OnChainError::Verifier(VerifierError::IncompleteProof), Some((missing, expected))) => {
+                format!("Resume the upload from byte {} of {}", missing, expected)
+            }
+            _ => self.hint().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for OnChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}. {}", self.name(), self.code(), self.message(), self.hint())
+    }
+}
+
+/// First byte the upload bitmap of a stark-verifier buffer lacks, and the
+/// bytes expected
+fn first_missing_byte(buffer_data: &[u8]) -> Option<(usize, usize)> {
+    let expected = u32::from_le_bytes(buffer_data.get(36..40)?.try_into().ok()?) as usize;
+    let missing = first_missing_range(upload_bitmap(buffer_data).ok()?, expected)?;
+    Some((missing.start, expected))
+}
+
+fn murkl_hint(e: &MurklError) -> &'static str {
+    match e {
+        MurklError::PoolPaused => "The pool admin paused the pool; retry after it is unpaused",
+        MurklError::DepositTooSmall => "Deposit at least the pool's min_deposit",
+        MurklError::AlreadyClaimed => "This deposit or epoch was already paid out; there is nothing to retry",
+        MurklError::CommitmentMismatch => "The proof is for another deposit; prove again with this leaf's note",
+        MurklError::MerkleRootMismatch => {
+            "The proof's root fell out of the pool's root history; prove again against the current root"
+        }
+        MurklError::FeeTooHigh => "Lower the relayer fee to at most max_relayer_fee_bps of the deposit",
+        MurklError::Unauthorized => "Sign with the pool admin, or with the relayer that owns the verifier buffer",
+        MurklError::ProofNotVerified => "Run finalize_and_verify on the buffer before claiming",
+        MurklError::InvalidVerifierBuffer => {
+            "Pass a stark-verifier buffer whose proof names this claim's recipient account"
+        }
+        MurklError::InvalidVault => "Pass the pool's vault PDA",
+        MurklError::InvalidTokenMint => "Use token accounts of the pool's mint",
+        MurklError::NullifierMismatch => "Pass the nullifier the buffer was verified with",
+        MurklError::NullifierReplay | MurklError::NullifierAlreadyUsed => {
+            "The nullifier is already spent; there is nothing to retry"
+        }
+        MurklError::InvalidDepositPool => "Pass this pool's deposit record for the leaf",
+        MurklError::InvalidPoolConfig => "Use a min_deposit above 0 and a fee cap within MAX_RELAYER_FEE_BPS",
+        MurklError::MathOverflow => "An amount overflowed; check the deposit amount and relayer fee",
+        MurklError::InvalidPoolAccount => "Pass a murkl Pool account",
+        MurklError::ComplianceHookMissing => {
+            "Append the pool's compliance hook program, then its accounts, as remaining accounts"
+        }
+        MurklError::ComplianceRejected => "The pool's compliance hook refused this payout; ask the pool operator",
+        MurklError::PathClaimsDisabled => "Claim with a STARK proof; this pool doesn't allow path claims",
+        MurklError::InvalidMerklePath => "Rebuild the leaf's Merkle path from the pool's deposit events",
+        MurklError::ProofExpired => "Restamp the buffer with reverify_proof_buffer, then claim again",
+        MurklError::PoolStatsMissing => "Pass the pool's PoolStats account (with_stats = true)",
+        MurklError::SubscriptionsDisabled => "The pool admin has to set an epoch duration first",
+        MurklError::InvalidEpochCount => "Use between 1 and MAX_SUBSCRIPTION_EPOCHS epochs",
+        MurklError::InvalidEpochDuration => "Use an epoch duration of 0 or more seconds",
+        MurklError::EpochOutOfRange => "Prove for an epoch the subscription has",
+        MurklError::EpochNotOpen => "Wait for the epoch to open, then claim it",
+        MurklError::MerkleTreeFull => "This pool takes no more deposits; use another pool",
+        MurklError::InvalidRootHistoryWindow => "Use a window of at most ROOT_HISTORY_SIZE roots",
+        MurklError::VerifierParamsMissing => {
+            "Pass stark-verifier's VerifierParams PDA, publishing it with publish_verifier_params if needed"
+        }
+        MurklError::VerifierParamsMismatch => {
+            "The verifier's parameters differ from the pool's pin; the pool admin has to re-pin"
+        }
+        MurklError::WrongPoolKind => "Use the _sol instructions for the SOL pool and the others for token pools",
+        MurklError::NullifierRegistryDisabled => "Claim with claim; this pool has no nullifier registry",
+        MurklError::NullifierRegistryLocked => "The pool's nullifier shard count can't change once set",
+        MurklError::InvalidNullifierShard => "Use 1 to 16 shard bits and a shard index below 2^bits",
+        MurklError::DepositRevealRequired => {
+            "Register the deposit with register_deposit, then reveal it in a later slot"
+        }
+        MurklError::DepositRevealDisabled => "Deposit directly; this pool has no reveal window",
+        MurklError::InvalidRevealWindow => "Use a window of at most MAX_DEPOSIT_REVEAL_SLOTS slots",
+        MurklError::RevealTooEarly => "Wait for the next slot, then send the reveal again",
+        MurklError::RegistrationExpired => "Close it with expire_deposit_registration and register again",
+        MurklError::RegistrationMismatch => "Reveal with the commitment and salt the registration hash was made from",
+        MurklError::RegistrationNotExpired => "Wait until the registration's expires_slot has passed",
+        MurklError::MintAlreadyAccepted => "Deposit the mint with deposit_accepted_mint; it is accepted already",
+        MurklError::TooManyAcceptedMints => "A pool accepts at most MAX_ACCEPTED_MINTS mints besides its own; open another pool",
+        MurklError::MintDecimalsMismatch => "Only mints with the pool mint's decimals can be taken at par",
+        MurklError::InvalidSecurityLevel => "Pass 0 (demo), 1 (standard96) or 2 (high128), or None to clear",
+        MurklError::VerifierBelowSecurityLevel => {
+            "The verifier accepts proofs below the pool's minimum level; wait for it to publish stronger params"
+        }
+        MurklError::NullifierBloomMissing => "Pass the pool's NullifierBloom account (with_bloom = true)",
+        MurklError::VaultRotationLocked => "Wait until the rotation's executable_at before calling rotate_vault",
+    }
+}
+
+fn verifier_hint(e: &VerifierError) -> &'static str {
+    match e {
+        VerifierError::InvalidProofFormat => "Prove again with a prover that matches this verifier's wire format",
+        VerifierError::ProofTooLarge => "Keep chunks within the buffer's expected size, or size a new buffer for the proof",
+        VerifierError::BufferTooSmall => "Create the buffer with verifier::buffer_space(proof_len) bytes",
+        VerifierError::BufferCorrupt => "Close the buffer and upload the proof to a new one",
+        VerifierError::Unauthorized => "Sign with the buffer owner, or its upload delegate for chunks",
+        VerifierError::LamportOverflow => "Close the buffer to a different account",
+        VerifierError::BufferAlreadyFinalized => "The buffer is already verified; claim with it or close it",
+        VerifierError::ProofNotFinalized => "Run finalize_and_verify on the buffer first",
+        VerifierError::IncompleteProof => {
+            "Upload the missing chunks; bundle::plan_upload with the buffer's upload bitmap resends just those"
+        }
+        VerifierError::ConstraintMismatch
+        | VerifierError::FinalPolyDegreeTooHigh
+        | VerifierError::TraceMerklePathFailed
+        | VerifierError::CompositionMerklePathFailed
+        | VerifierError::FriFoldingFailed
+        | VerifierError::QueryIndexMismatch
+        | VerifierError::FinalPolyMismatch => {
+            "The proof doesn't verify for these public inputs; check them and prove again"
+        }
+        VerifierError::FriLayerCountTooLow
+        | VerifierError::TooFewQueries
+        | VerifierError::TraceColumnCountMismatch
+        | VerifierError::DomainSizeMismatch
+        | VerifierError::FoldingFactorMismatch => {
+            "The proof's parameters are below what the verifier requires; prove with the default config"
+        }
+        VerifierError::VerificationPhaseMismatch => "Run the staged verification steps in order",
+        VerifierError::ProofChangedDuringVerification => {
+            "The buffer changed mid-verification; cancel and start staged verification again"
+        }
+        VerifierError::UnsupportedHashKind => "Prove with a hash kind the verifier accepts",
+        VerifierError::UnsupportedProofVersion => "Regenerate the proof with a prover that writes the current proof format",
+        VerifierError::InvalidBatch => "Pass 1 to MAX_BATCH_PROOFS entries with one buffer account each",
+        VerifierError::InvalidPublicInputs => {
+            "Upload the public inputs block (public_inputs_block) right after the proof"
+        }
+        VerifierError::PublicInputsMismatch => {
+            "Finalize with public_inputs_hash of the inputs uploaded to this buffer"
+        }
+        VerifierError::InvalidBufferExpiry => "Initialize with 1 to MAX_BUFFER_EXPIRY_SLOTS expiry slots",
+        VerifierError::BufferNotExpired => "Wait until buffer_expires_at, or ask the owner to close it",
+        VerifierError::UnalignedUpload => "Split the proof at multiples of UPLOAD_CHUNK_ALIGN bytes",
+        VerifierError::InvalidUploadSegments => "Pass at least one segment and the data of every segment, in order",
+        VerifierError::ProofHashMismatch => {
+            "Re-upload the proof the buffer was opened for, or close it and open a new one with this proof's keccak"
+        }
+        VerifierError::NullifierMismatch => "Pass the nullifier the proof buffer was finalized with",
+        VerifierError::NonCanonicalPublicInput => {
+            "Pass the Poseidon2 commitment and nullifier exactly as the prover computed them"
+        }
+        VerifierError::InvalidSecurityLevel => "Pass 0 (demo), 1 (standard96) or 2 (high128)",
+        VerifierError::SecurityLevelNotAllowed => {
+            "Pick standard96 or high128; the demo profile needs a verifier built with the demo feature"
+        }
+    }
+}
+
+/// One row of the table the relayer reads
+#[derive(Serialize)]
+pub struct ErrorEntry {
+    pub program: String,
+    pub code: u32,
+    pub name: String,
+    pub message: String,
+    pub hint: &'static str,
+}
+
+/// Every error as rows for `relayer/src/program-errors.json`
+pub fn error_table() -> Vec<ErrorEntry> {
+    OnChainError::all()
+        .map(|e| ErrorEntry {
+            program: e.program_id().to_string(),
+            code: e.code(),
+            name: e.name(),
+            message: e.message(),
+            hint: e.hint(),
+        })
+        .collect()
+}
@@ -0,0 +1,1200 @@
+//! Murkl Client - typed instruction builders for the Murkl programs
+//!
+//! Wraps the Anchor-generated `accounts` / `instruction` structs of
+//! `murkl-program` and `stark-verifier` with PDA derivation, so a caller
+//! names the pool by its token mint and passes only the keys it owns.
+//!
+//! `tests/instructions.rs` runs every builder here against both programs
+//! under `solana-program-test`. Each test is a complete, copyable example of
+//! one step of the flow:
+//!
+//! 1. [`murkl::initialize_config`], [`murkl::initialize_registry`]
+//! 2. [`murkl::initialize_pool`], [`murkl::initialize_pool_merkle`],
+//!    [`murkl::initialize_pool_stats`], [`murkl::initialize_admin_log`],
+//!    [`murkl::register_pool`]
+//! 3. [`murkl::deposit`], or on a pool with a reveal window
+//!    ([`murkl::set_deposit_reveal_window`]) [`murkl::register_deposit`]
+//!    and, in a later slot, [`murkl::reveal_deposit`]
+//! 4. [`verifier::create_proof_buffer`], [`verifier::init_proof_buffer`],
+//!    [`verifier::upload_chunks`], [`verifier::finalize_and_verify`]
+//!    (or [`verifier::finalize_and_verify_batch`] for several buffers),
+//!    [`verifier::close_proof_buffer`]
+//! 5. [`murkl::claim`]
+//! 6. Admin: pause, unpause, path claims, proof age, root history window,
+//!    compliance hook, verifier params pin ([`verifier::publish_verifier_params`]
+//!    first), epochs, grow
+//!
+//! Proofs past about 8 queries verify in stages instead
+//! ([`verifier::begin_staged_verification`] through
+//! [`verifier::finalize_staged_verification`]). After the upload,
+//! [`bundle::plan_claim`] picks the route from the proof and packs it and
+//! the claim into as few transactions as fit, compiled against a
+//! [`bundle::claim_lookup_table`].
+//!
+//! [`bundle::plan_upload`] packs the upload the same way, as
+//! [`verifier::upload_chunks_multi`] instructions of as many chunks as fit a
+//! transaction. Planned again with the buffer's upload bitmap, it covers
+//! only the chunks that did not land.
+//!
+//! A pool with a nullifier registry ([`murkl::set_nullifier_shard_bits`])
+//! claims with [`murkl::claim_sharded`] once the nullifier's shard exists
+//! ([`murkl::initialize_nullifier_shard`]); [`murkl::check_nullifier`] asks
+//! whether a nullifier is spent.
+//!
+//! Once a pool has a nullifier bloom filter
+//! ([`murkl::initialize_nullifier_bloom`]), claims pass `with_bloom` and a
+//! wallet checks all its deposits against one fetch of
+//! [`pda::nullifier_bloom`] with [`bloom_may_be_spent`], looking up only
+//! the maybe-spent ones.
+//!
+//! The native-SOL pool swaps in [`murkl::initialize_sol_pool`],
+//! [`murkl::deposit_sol`] and [`murkl::claim_sol`], and is named by
+//! [`SOL_POOL_MINT`] everywhere else.
+//!
+//! A multi-mint pool takes further mints at par once the admin adds them
+//! ([`murkl::add_accepted_mint`]); their deposits and claims go through
+//! [`murkl::deposit_accepted_mint`] and [`murkl::claim_accepted_mint`],
+//! which still name the pool by its own `token_mint`.
+//!
+//! A token pool's admin moves its tokens to a fresh vault with
+//! [`murkl::schedule_vault_rotation`] and, once the timelock is up,
+//! [`murkl::rotate_vault`]. The builders here derive the original
+//! [`pda::vault`], so for a rotated pool swap in `Pool::vault` before
+//! sending.
+//!
+//! [`OnChainError`] turns a failed transaction's custom error code back into
+//! the program's error, with a hint on what to do about it.
+//!
+//! `src/bin/canary.rs` strings steps 3–5 together against a live cluster
+//! (`cargo run --bin canary -- --help`) and logs proof size and CU per run.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{system_program, InstructionData};
+
+pub use murkl_program::{
+    deposit_registration_hash, nullifier_bloom_contains, nullifier_shard_index, PoolConfig, ID as MURKL_PROGRAM_ID,
+    NULLIFIER_BLOOM_BYTES, NULLIFIER_BLOOM_HEADER, NULLIFIER_SHARD_HEADER, SOL_POOL_MINT, STARK_VERIFIER_ID,
+};
+pub use stark_verifier::SecurityLevel;
+
+pub mod bundle;
+pub mod errors;
+pub use errors::OnChainError;
+
+/// Proof bytes per `upload_chunk` transaction: fits the 1232-byte packet
+/// and is a multiple of [`verifier::UPLOAD_CHUNK_ALIGN`]
+pub const DEFAULT_CHUNK_SIZE: usize = 896;
+
+/// Bytes the stark-verifier reserves ahead of the proof in a buffer account
+pub const BUFFER_HEADER_SIZE: usize = 449;
+
+/// Whether the deposit at `leaf_index` may have claimed `nullifier`, given
+/// the data of the pool's [`pda::nullifier_bloom`] account. `false` is
+/// definite; on `true`, confirm with the nullifier's PDA or shard
+/// ([`murkl::check_nullifier`]). Deposits older than the filter are always
+/// maybe-spent, since it holds no claims from before it existed.
+pub fn bloom_may_be_spent(bloom_account: &[u8], leaf_index: u64, nullifier: &[u8; 32]) -> Result<bool> {
+    let bloom = murkl_program::NullifierBloom::try_deserialize(&mut &bloom_account[..])?;
+    let bits = bloom_account
+        .get(NULLIFIER_BLOOM_HEADER..NULLIFIER_BLOOM_HEADER + NULLIFIER_BLOOM_BYTES)
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    Ok(leaf_index < bloom.tracked_from_leaf || nullifier_bloom_contains(bits, nullifier))
+}
+
+/// Program-derived addresses used by the Murkl program
+pub mod pda {
+    use super::*;
+    use murkl_program::{
+        ADMIN_LOG_SEED, CLAIM_RECEIPT_SEED, CONFIG_SEED, DEPOSIT_REGISTRATION_SEED, MEMBERSHIP_SEED,
+        NULLIFIER_BLOOM_SEED, NULLIFIER_SHARD_SEED, REGISTRY_ENTRY_SEED, REGISTRY_SEED, VAULT_ROTATION_SEED,
+    };
+
+    fn find(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &MURKL_PROGRAM_ID).0
+    }
+
+    pub fn config() -> Pubkey {
+        find(&[CONFIG_SEED])
+    }
+
+    pub fn registry() -> Pubkey {
+        find(&[REGISTRY_SEED])
+    }
+
+    pub fn registry_entry(pool: &Pubkey) -> Pubkey {
+        find(&[REGISTRY_ENTRY_SEED, pool.as_ref()])
+    }
+
+    /// One pool per token mint
+    pub fn pool(token_mint: &Pubkey) -> Pubkey {
+        find(&[b"pool", token_mint.as_ref()])
+    }
+
+    /// The pool's first vault; after [`murkl::rotate_vault`] it is
+    /// [`vault_at`] the pool's `vault_generation`
+    pub fn vault(pool: &Pubkey) -> Pubkey {
+        find(&[b"vault", pool.as_ref()])
+    }
+
+    /// Vault of a pool after `generation` rotations (from 1)
+    pub fn vault_at(pool: &Pubkey, generation: u16) -> Pubkey {
+        find(&[b"vault", pool.as_ref(), &generation.to_le_bytes()])
+    }
+
+    /// Pending vault rotation of the pool
+    pub fn vault_rotation(pool: &Pubkey) -> Pubkey {
+        find(&[VAULT_ROTATION_SEED, pool.as_ref()])
+    }
+
+    /// Vault of a mint the pool accepts besides its `token_mint`
+    pub fn accepted_vault(pool: &Pubkey, mint: &Pubkey) -> Pubkey {
+        find(&[b"vault", pool.as_ref(), mint.as_ref()])
+    }
+
+    pub fn pool_merkle(pool: &Pubkey) -> Pubkey {
+        find(&[b"pool-merkle", pool.as_ref()])
+    }
+
+    pub fn pool_stats(pool: &Pubkey) -> Pubkey {
+        find(&[b"pool-stats", pool.as_ref()])
+    }
+
+    /// Hash chain of the pool's admin actions
+    pub fn admin_log(pool: &Pubkey) -> Pubkey {
+        find(&[ADMIN_LOG_SEED, pool.as_ref()])
+    }
+
+    /// Deposit record of the leaf at `leaf_index`
+    pub fn deposit(pool: &Pubkey, leaf_index: u64) -> Pubkey {
+        find(&[b"deposit", pool.as_ref(), &leaf_index.to_le_bytes()])
+    }
+
+    /// Pending deposit registered under `registration_hash`
+    /// (see [`deposit_registration_hash`])
+    pub fn deposit_registration(pool: &Pubkey, registration_hash: &[u8; 32]) -> Pubkey {
+        find(&[DEPOSIT_REGISTRATION_SEED, pool.as_ref(), registration_hash.as_ref()])
+    }
+
+    /// Exists once `nullifier` has been claimed from `pool`
+    pub fn nullifier(pool: &Pubkey, nullifier: &[u8; 32]) -> Pubkey {
+        find(&[b"nullifier", pool.as_ref(), nullifier.as_ref()])
+    }
+
+    /// Shard `shard` of the pool's nullifier registry (see [`nullifier_shard_index`])
+    pub fn nullifier_shard(pool: &Pubkey, shard: u16) -> Pubkey {
+        find(&[NULLIFIER_SHARD_SEED, pool.as_ref(), &shard.to_le_bytes()])
+    }
+
+    /// Bloom filter of the pool's claimed nullifiers; exists once created
+    pub fn nullifier_bloom(pool: &Pubkey) -> Pubkey {
+        find(&[NULLIFIER_BLOOM_SEED, pool.as_ref()])
+    }
+
+    /// `recipient`'s claim receipt for `pool`; exists once a claim asked for one
+    pub fn claim_receipt(pool: &Pubkey, recipient: &Pubkey) -> Pubkey {
+        find(&[CLAIM_RECEIPT_SEED, pool.as_ref(), recipient.as_ref()])
+    }
+
+    /// Exists once `nullifier`'s deposit has proven membership under `scope`;
+    /// separate from [`nullifier`], so the deposit stays claimable
+    pub fn membership(pool: &Pubkey, scope: &[u8; 32], nullifier: &[u8; 32]) -> Pubkey {
+        find(&[MEMBERSHIP_SEED, pool.as_ref(), scope.as_ref(), nullifier.as_ref()])
+    }
+
+    /// stark-verifier's published parameters (owned by the verifier, not murkl)
+    pub fn verifier_params() -> Pubkey {
+        Pubkey::find_program_address(&[stark_verifier::VERIFIER_PARAMS_SEED], &STARK_VERIFIER_ID).0
+    }
+
+    /// Profile stark-verifier checks proofs at (owned by the verifier)
+    pub fn verifier_config() -> Pubkey {
+        Pubkey::find_program_address(&[stark_verifier::VERIFIER_CONFIG_SEED], &STARK_VERIFIER_ID).0
+    }
+
+    /// Checkpoint of a staged verification of `buffer` (owned by the verifier)
+    pub fn verification_state(buffer: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"verification", buffer.as_ref()], &STARK_VERIFIER_ID).0
+    }
+}
+
+/// Instructions of the Murkl pool program
+pub mod murkl {
+    use super::*;
+    use murkl_program::{accounts, instruction};
+
+    fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: MURKL_PROGRAM_ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// `pool_stats` once the pool tracks them, `None` otherwise
+    fn stats(pool: &Pubkey, with_stats: bool) -> Option<Pubkey> {
+        with_stats.then(|| pda::pool_stats(pool))
+    }
+
+    /// `nullifier_bloom` once the pool keeps one, `None` otherwise
+    fn bloom(pool: &Pubkey, with_bloom: bool) -> Option<Pubkey> {
+        with_bloom.then(|| pda::nullifier_bloom(pool))
+    }
+
+    /// Create the global config; `admin` becomes the config admin
+    pub fn initialize_config(admin: &Pubkey) -> Instruction {
+        ix(
+            accounts::InitializeConfig {
+                config: pda::config(),
+                admin: *admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeConfig {},
+        )
+    }
+
+    pub fn initialize_registry(admin: &Pubkey) -> Instruction {
+        ix(
+            accounts::InitializeRegistry {
+                config: pda::config(),
+                registry: pda::registry(),
+                admin: *admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeRegistry {},
+        )
+    }
+
+    pub fn register_pool(admin: &Pubkey, token_mint: &Pubkey, verifier_config_hash: [u8; 32]) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::RegisterPool {
+                config: pda::config(),
+                registry: pda::registry(),
+                pool,
+                entry: pda::registry_entry(&pool),
+                admin: *admin,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterPool { verifier_config_hash },
+        )
+    }
+
+    /// Create the pool and its vault for `token_mint` (config admin)
+    pub fn initialize_pool(admin: &Pubkey, token_mint: &Pubkey, config: PoolConfig) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializePool {
+                config: pda::config(),
+                pool,
+                token_mint: *token_mint,
+                vault: pda::vault(&pool),
+                admin: *admin,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::InitializePool { config },
+        )
+    }
+
+    /// Create the native-SOL pool and fund its vault's rent reserve (config
+    /// admin). Other builders name it by [`SOL_POOL_MINT`].
+    pub fn initialize_sol_pool(admin: &Pubkey, config: PoolConfig) -> Instruction {
+        let pool = pda::pool(&SOL_POOL_MINT);
+        ix(
+            accounts::InitializeSolPool {
+                config: pda::config(),
+                pool,
+                vault: pda::vault(&pool),
+                admin: *admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeSolPool { config },
+        )
+    }
+
+    /// Create the pool's incremental Merkle state; deposits need it
+    pub fn initialize_pool_merkle(payer: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializePoolMerkle {
+                pool,
+                pool_merkle: pda::pool_merkle(&pool),
+                payer: *payer,
+                system_program: system_program::ID,
+                rent: anchor_lang::solana_program::sysvar::rent::ID,
+            },
+            instruction::InitializePoolMerkle {},
+        )
+    }
+
+    /// Start tracking pool stats; later deposits and claims must pass `with_stats`
+    pub fn initialize_pool_stats(payer: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializePoolStats {
+                pool,
+                pool_stats: pda::pool_stats(&pool),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializePoolStats {},
+        )
+    }
+
+    /// Create the pool's admin log; every admin instruction needs it
+    pub fn initialize_admin_log(payer: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializeAdminLog {
+                pool,
+                admin_log: pda::admin_log(&pool),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAdminLog {},
+        )
+    }
+
+    /// Deposit `amount` under `commitment`.
+    ///
+    /// `leaf_index` is the pool's current `leaf_count`; the deposit record
+    /// is derived from it, so read the pool just before sending.
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit(
+        depositor: &Pubkey,
+        depositor_token: &Pubkey,
+        token_mint: &Pubkey,
+        leaf_index: u64,
+        amount: u64,
+        commitment: [u8; 32],
+        with_stats: bool,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::Deposit {
+                pool,
+                pool_merkle: pda::pool_merkle(&pool),
+                deposit: pda::deposit(&pool, leaf_index),
+                vault: pda::vault(&pool),
+                depositor: *depositor,
+                depositor_token: *depositor_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+            },
+            instruction::Deposit { amount, commitment },
+        )
+    }
+
+    /// Deposit `amount` lamports into the SOL pool under `commitment`;
+    /// `leaf_index` as for [`deposit`]
+    pub fn deposit_sol(
+        depositor: &Pubkey,
+        leaf_index: u64,
+        amount: u64,
+        commitment: [u8; 32],
+        with_stats: bool,
+    ) -> Instruction {
+        let pool = pda::pool(&SOL_POOL_MINT);
+        ix(
+            accounts::DepositSol {
+                pool,
+                pool_merkle: pda::pool_merkle(&pool),
+                deposit: pda::deposit(&pool, leaf_index),
+                vault: pda::vault(&pool),
+                depositor: *depositor,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+            },
+            instruction::DepositSol { amount, commitment },
+        )
+    }
+
+    /// Deposit `amount` of `mint`, one the pool named by `token_mint`
+    /// accepts (see [`add_accepted_mint`]); `leaf_index` as for [`deposit`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_accepted_mint(
+        depositor: &Pubkey,
+        depositor_token: &Pubkey,
+        token_mint: &Pubkey,
+        mint: &Pubkey,
+        leaf_index: u64,
+        amount: u64,
+        commitment: [u8; 32],
+        with_stats: bool,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::DepositAcceptedMint {
+                pool,
+                pool_merkle: pda::pool_merkle(&pool),
+                deposit: pda::deposit(&pool, leaf_index),
+                vault: pda::accepted_vault(&pool, mint),
+                depositor: *depositor,
+                depositor_token: *depositor_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+            },
+            instruction::DepositAcceptedMint { amount, commitment },
+        )
+    }
+
+    /// Register a deposit on a pool with a reveal window; reveal it in a
+    /// later slot with the same commitment and salt
+    pub fn register_deposit(depositor: &Pubkey, token_mint: &Pubkey, commitment: &[u8; 32], salt: &[u8; 32]) -> Instruction {
+        let pool = pda::pool(token_mint);
+        let registration_hash = deposit_registration_hash(commitment, salt);
+        ix(
+            accounts::RegisterDeposit {
+                pool,
+                registration: pda::deposit_registration(&pool, &registration_hash),
+                depositor: *depositor,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterDeposit { registration_hash },
+        )
+    }
+
+    /// Fund a deposit registered with [`register_deposit`]; otherwise as
+    /// [`deposit`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn reveal_deposit(
+        depositor: &Pubkey,
+        depositor_token: &Pubkey,
+        token_mint: &Pubkey,
+        leaf_index: u64,
+        amount: u64,
+        commitment: [u8; 32],
+        salt: [u8; 32],
+        with_stats: bool,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::RevealDeposit {
+                pool,
+                registration: pda::deposit_registration(&pool, &deposit_registration_hash(&commitment, &salt)),
+                pool_merkle: pda::pool_merkle(&pool),
+                deposit: pda::deposit(&pool, leaf_index),
+                vault: pda::vault(&pool),
+                depositor: *depositor,
+                depositor_token: *depositor_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+            },
+            instruction::RevealDeposit { amount, commitment, salt },
+        )
+    }
+
+    /// [`reveal_deposit`] for the SOL pool
+    pub fn reveal_deposit_sol(
+        depositor: &Pubkey,
+        leaf_index: u64,
+        amount: u64,
+        commitment: [u8; 32],
+        salt: [u8; 32],
+        with_stats: bool,
+    ) -> Instruction {
+        let pool = pda::pool(&SOL_POOL_MINT);
+        ix(
+            accounts::RevealDepositSol {
+                pool,
+                registration: pda::deposit_registration(&pool, &deposit_registration_hash(&commitment, &salt)),
+                pool_merkle: pda::pool_merkle(&pool),
+                deposit: pda::deposit(&pool, leaf_index),
+                vault: pda::vault(&pool),
+                depositor: *depositor,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+            },
+            instruction::RevealDepositSol { amount, commitment, salt },
+        )
+    }
+
+    /// Close an unrevealed registration past its window; its rent goes back
+    /// to `depositor`
+    pub fn expire_deposit_registration(token_mint: &Pubkey, registration_hash: &[u8; 32], depositor: &Pubkey) -> Instruction {
+        ix(
+            accounts::ExpireDepositRegistration {
+                registration: pda::deposit_registration(&pda::pool(token_mint), registration_hash),
+                depositor: *depositor,
+            },
+            instruction::ExpireDepositRegistration {},
+        )
+    }
+
+    /// Claim the deposit at `leaf_index` against the relayer's finalized `verifier_buffer`
+    ///
+    /// `receipt_owner`, the wallet owning `recipient_token`, records the
+    /// claim on that wallet's [`murkl_program::ClaimReceipt`]; `None` skips it.
+    /// With `close_buffer` the claim also closes `verifier_buffer`, refunding
+    /// its rent to the relayer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim(
+        relayer: &Pubkey,
+        relayer_token: &Pubkey,
+        recipient_token: &Pubkey,
+        verifier_buffer: &Pubkey,
+        token_mint: &Pubkey,
+        leaf_index: u64,
+        nullifier: [u8; 32],
+        relayer_fee: u64,
+        with_stats: bool,
+        with_bloom: bool,
+        receipt_owner: Option<&Pubkey>,
+        close_buffer: bool,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        let mut claim = ix(
+            accounts::Claim {
+                pool,
+                deposit: pda::deposit(&pool, leaf_index),
+                verifier_buffer: *verifier_buffer,
+                nullifier_record: pda::nullifier(&pool, &nullifier),
+                vault: pda::vault(&pool),
+                recipient_token: *recipient_token,
+                relayer: *relayer,
+                relayer_token: *relayer_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+                // Always passed; only read when the pool pins verifier params
+                verifier_params: Some(pda::verifier_params()),
+                claim_receipt: receipt_owner.map(|owner| pda::claim_receipt(&pool, owner)),
+                nullifier_bloom: bloom(&pool, with_bloom),
+                verifier_program: close_buffer.then_some(STARK_VERIFIER_ID),
+            },
+            instruction::Claim { relayer_fee, nullifier },
+        );
+        if close_buffer {
+            // `Claim` only reads the buffer unless it closes it
+            if let Some(buffer) = claim.accounts.iter_mut().find(|meta| meta.pubkey == *verifier_buffer) {
+                buffer.is_writable = true;
+            }
+        }
+        claim
+    }
+
+    /// Claim a [`deposit_accepted_mint`] deposit of `mint`; the token
+    /// accounts are `mint`'s, otherwise as for [`claim`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_accepted_mint(
+        relayer: &Pubkey,
+        relayer_token: &Pubkey,
+        recipient_token: &Pubkey,
+        verifier_buffer: &Pubkey,
+        token_mint: &Pubkey,
+        mint: &Pubkey,
+        leaf_index: u64,
+        nullifier: [u8; 32],
+        relayer_fee: u64,
+        with_stats: bool,
+        with_bloom: bool,
+        receipt_owner: Option<&Pubkey>,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::ClaimAcceptedMint {
+                pool,
+                deposit: pda::deposit(&pool, leaf_index),
+                verifier_buffer: *verifier_buffer,
+                nullifier_record: pda::nullifier(&pool, &nullifier),
+                vault: pda::accepted_vault(&pool, mint),
+                recipient_token: *recipient_token,
+                relayer: *relayer,
+                relayer_token: *relayer_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+                verifier_params: Some(pda::verifier_params()),
+                claim_receipt: receipt_owner.map(|owner| pda::claim_receipt(&pool, owner)),
+                nullifier_bloom: bloom(&pool, with_bloom),
+            },
+            instruction::ClaimAcceptedMint { relayer_fee, nullifier },
+        )
+    }
+
+    /// Claim like [`claim`] on a pool with a nullifier registry of
+    /// `2^shard_bits` shards; the nullifier's shard must exist
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_sharded(
+        relayer: &Pubkey,
+        relayer_token: &Pubkey,
+        recipient_token: &Pubkey,
+        verifier_buffer: &Pubkey,
+        token_mint: &Pubkey,
+        leaf_index: u64,
+        nullifier: [u8; 32],
+        relayer_fee: u64,
+        shard_bits: u8,
+        with_stats: bool,
+        with_bloom: bool,
+        receipt_owner: Option<&Pubkey>,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::ClaimSharded {
+                pool,
+                deposit: pda::deposit(&pool, leaf_index),
+                verifier_buffer: *verifier_buffer,
+                nullifier_record: pda::nullifier(&pool, &nullifier),
+                nullifier_shard: pda::nullifier_shard(&pool, nullifier_shard_index(&nullifier, shard_bits)),
+                vault: pda::vault(&pool),
+                recipient_token: *recipient_token,
+                relayer: *relayer,
+                relayer_token: *relayer_token,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+                verifier_params: Some(pda::verifier_params()),
+                claim_receipt: receipt_owner.map(|owner| pda::claim_receipt(&pool, owner)),
+                nullifier_bloom: bloom(&pool, with_bloom),
+            },
+            instruction::ClaimSharded { relayer_fee, nullifier },
+        )
+    }
+
+    /// Start the pool's nullifier bloom filter; `payer` funds it, and later
+    /// claims must pass `with_bloom`
+    pub fn initialize_nullifier_bloom(payer: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializeNullifierBloom {
+                pool,
+                nullifier_bloom: pda::nullifier_bloom(&pool),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeNullifierBloom {},
+        )
+    }
+
+    /// Create shard `shard` of the pool's nullifier registry; `payer` funds it
+    pub fn initialize_nullifier_shard(payer: &Pubkey, token_mint: &Pubkey, shard: u16) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::InitializeNullifierShard {
+                pool,
+                nullifier_shard: pda::nullifier_shard(&pool, shard),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeNullifierShard { shard },
+        )
+    }
+
+    /// Whether `nullifier` was claimed, as one byte of return data (1 = used);
+    /// simulate it rather than send it. `shard_bits` is the pool's registry
+    /// size once the nullifier's shard exists, `None` otherwise.
+    pub fn check_nullifier(token_mint: &Pubkey, nullifier: [u8; 32], shard_bits: Option<u8>) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::CheckNullifier {
+                pool,
+                nullifier_record: pda::nullifier(&pool, &nullifier),
+                nullifier_shard: shard_bits
+                    .map(|bits| pda::nullifier_shard(&pool, nullifier_shard_index(&nullifier, bits))),
+            },
+            instruction::CheckNullifier { nullifier },
+        )
+    }
+
+    /// Claim a SOL pool deposit to the `recipient` wallet the proof names;
+    /// the fee goes to `relayer`. `with_receipt` records it on the
+    /// recipient's claim receipt
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_sol(
+        relayer: &Pubkey,
+        recipient: &Pubkey,
+        verifier_buffer: &Pubkey,
+        leaf_index: u64,
+        nullifier: [u8; 32],
+        relayer_fee: u64,
+        with_stats: bool,
+        with_bloom: bool,
+        with_receipt: bool,
+    ) -> Instruction {
+        let pool = pda::pool(&SOL_POOL_MINT);
+        ix(
+            accounts::ClaimSol {
+                pool,
+                deposit: pda::deposit(&pool, leaf_index),
+                verifier_buffer: *verifier_buffer,
+                nullifier_record: pda::nullifier(&pool, &nullifier),
+                vault: pda::vault(&pool),
+                recipient: *recipient,
+                relayer: *relayer,
+                system_program: system_program::ID,
+                pool_stats: stats(&pool, with_stats),
+                verifier_params: Some(pda::verifier_params()),
+                claim_receipt: with_receipt.then(|| pda::claim_receipt(&pool, recipient)),
+                nullifier_bloom: bloom(&pool, with_bloom),
+            },
+            instruction::ClaimSol { relayer_fee, nullifier },
+        )
+    }
+
+    /// Record that the deposit at `leaf_index` belongs to `member` under
+    /// `scope`, without moving any tokens; `payer` funds the record
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_membership_only(
+        payer: &Pubkey,
+        member: &Pubkey,
+        verifier_buffer: &Pubkey,
+        token_mint: &Pubkey,
+        leaf_index: u64,
+        nullifier: [u8; 32],
+        scope: [u8; 32],
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::ProveMembershipOnly {
+                pool,
+                deposit: pda::deposit(&pool, leaf_index),
+                verifier_buffer: *verifier_buffer,
+                membership_record: pda::membership(&pool, &scope, &nullifier),
+                member: *member,
+                payer: *payer,
+                system_program: system_program::ID,
+                verifier_params: Some(pda::verifier_params()),
+            },
+            instruction::ProveMembershipOnly { nullifier, scope },
+        )
+    }
+
+    fn admin_action(admin: &Pubkey, token_mint: &Pubkey, data: impl InstructionData) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::AdminAction {
+                pool,
+                admin: *admin,
+                admin_log: pda::admin_log(&pool),
+            },
+            data,
+        )
+    }
+
+    pub fn pause_pool(admin: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        admin_action(admin, token_mint, instruction::PausePool {})
+    }
+
+    pub fn unpause_pool(admin: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        admin_action(admin, token_mint, instruction::UnpausePool {})
+    }
+
+    pub fn set_path_claims(admin: &Pubkey, token_mint: &Pubkey, enabled: bool) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetPathClaims { enabled })
+    }
+
+    /// Emit only `event_seq` and the pool in claim events
+    pub fn set_minimal_events(admin: &Pubkey, token_mint: &Pubkey, enabled: bool) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetMinimalEvents { enabled })
+    }
+
+    /// Refuse proofs finalized more than `max_age_slots` ago (0 = no limit)
+    pub fn set_max_proof_age(admin: &Pubkey, token_mint: &Pubkey, max_age_slots: u64) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetMaxProofAge { max_age_slots })
+    }
+
+    /// Accept claims against the current root and the `window` roots before it
+    pub fn set_root_history_window(admin: &Pubkey, token_mint: &Pubkey, window: u8) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetRootHistoryWindow { window })
+    }
+
+    pub fn set_compliance_hook(admin: &Pubkey, token_mint: &Pubkey, hook: Option<Pubkey>) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetComplianceHook { hook })
+    }
+
+    /// Require claims to be verified under the stark-verifier params hashing
+    /// to `params_hash` (see [`verifier::publish_verifier_params`]); `None` unpins
+    pub fn set_required_verifier_params(
+        admin: &Pubkey,
+        token_mint: &Pubkey,
+        params_hash: Option<[u8; 32]>,
+    ) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetRequiredVerifierParams { params_hash })
+    }
+
+    /// Require the published verifier params to guarantee `level` before
+    /// claims pay out; `None` drops the requirement
+    pub fn set_min_security_level(admin: &Pubkey, token_mint: &Pubkey, level: Option<SecurityLevel>) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetMinSecurityLevel { level: level.map(|level| level as u8) })
+    }
+
+    /// Enable subscription deposits with epochs of `duration_secs` (0 disables)
+    /// Turn on the pool's nullifier registry with `2^bits` shards; set once
+    pub fn set_nullifier_shard_bits(admin: &Pubkey, token_mint: &Pubkey, bits: u8) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetNullifierShardBits { bits })
+    }
+
+    pub fn set_epoch_duration(admin: &Pubkey, token_mint: &Pubkey, duration_secs: i64) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetEpochDuration { duration_secs })
+    }
+
+    /// Require deposits to be registered and revealed within `window_slots`
+    /// (0 = direct deposits)
+    pub fn set_deposit_reveal_window(admin: &Pubkey, token_mint: &Pubkey, window_slots: u64) -> Instruction {
+        admin_action(admin, token_mint, instruction::SetDepositRevealWindow { window_slots })
+    }
+
+    /// Accept deposits of `mint` at par with `token_mint`, creating its vault
+    pub fn add_accepted_mint(admin: &Pubkey, token_mint: &Pubkey, mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::AddAcceptedMint {
+                pool,
+                token_mint: *token_mint,
+                mint: *mint,
+                vault: pda::accepted_vault(&pool, mint),
+                admin: *admin,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                admin_log: pda::admin_log(&pool),
+            },
+            instruction::AddAcceptedMint {},
+        )
+    }
+
+    /// Start the timelock on moving the pool's tokens to a new vault
+    pub fn schedule_vault_rotation(admin: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::ScheduleVaultRotation {
+                pool,
+                vault_rotation: pda::vault_rotation(&pool),
+                admin: *admin,
+                system_program: system_program::ID,
+                admin_log: pda::admin_log(&pool),
+            },
+            instruction::ScheduleVaultRotation {},
+        )
+    }
+
+    pub fn cancel_vault_rotation(admin: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::CancelVaultRotation {
+                pool,
+                vault_rotation: pda::vault_rotation(&pool),
+                admin: *admin,
+                admin_log: pda::admin_log(&pool),
+            },
+            instruction::CancelVaultRotation {},
+        )
+    }
+
+    /// Move the pool's tokens from `vault` (its current `Pool::vault`) to
+    /// the vault of generation `vault_generation + 1`, once the scheduled
+    /// rotation's timelock is up. `registered` passes the pool's registry
+    /// entry so it follows the move.
+    pub fn rotate_vault(
+        admin: &Pubkey,
+        token_mint: &Pubkey,
+        vault: &Pubkey,
+        vault_generation: u16,
+        registered: bool,
+    ) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::RotateVault {
+                pool,
+                token_mint: *token_mint,
+                vault: *vault,
+                new_vault: pda::vault_at(&pool, vault_generation.wrapping_add(1)),
+                vault_rotation: pda::vault_rotation(&pool),
+                registry_entry: registered.then(|| pda::registry_entry(&pool)),
+                admin: *admin,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                admin_log: pda::admin_log(&pool),
+            },
+            instruction::RotateVault {},
+        )
+    }
+
+    /// Resize a pool written under an older layout; no-op when current
+    pub fn grow_pool(admin: &Pubkey, token_mint: &Pubkey) -> Instruction {
+        let pool = pda::pool(token_mint);
+        ix(
+            accounts::GrowPool {
+                pool,
+                admin: *admin,
+                system_program: system_program::ID,
+                admin_log: pda::admin_log(&pool),
+            },
+            instruction::GrowPool {},
+        )
+    }
+}
+
+/// Instructions of the stark-verifier proof buffer lifecycle
+pub mod verifier {
+    use super::*;
+    use stark_verifier::{accounts, instruction};
+
+    pub use stark_verifier::{BatchEntry, UploadSegment, DEFAULT_BUFFER_EXPIRY_SLOTS, UPLOAD_CHUNK_ALIGN};
+
+    fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: STARK_VERIFIER_ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// Space a buffer account needs for a proof of `proof_len` bytes
+    pub fn buffer_space(proof_len: usize) -> usize {
+        BUFFER_HEADER_SIZE + proof_len
+    }
+
+    /// System `create_account` for a buffer owned by the stark-verifier.
+    ///
+    /// `buffer` is a fresh keypair that signs this transaction only;
+    /// `lamports` is the rent exemption for [`buffer_space`].
+    pub fn create_proof_buffer(payer: &Pubkey, buffer: &Pubkey, proof_len: usize, lamports: u64) -> Instruction {
+        system_instruction::create_account(
+            payer,
+            buffer,
+            lamports,
+            buffer_space(proof_len) as u64,
+            &STARK_VERIFIER_ID,
+        )
+    }
+
+    /// Claim the buffer for `owner` and fix the proof it will hold: its
+    /// size and keccak, which the upload must match before it is verified.
+    /// After `expiry_slots` (e.g. [`DEFAULT_BUFFER_EXPIRY_SLOTS`]) anyone
+    /// may [`reap_expired_buffer`] it.
+    pub fn init_proof_buffer(owner: &Pubkey, buffer: &Pubkey, proof: &[u8], expiry_slots: u64) -> Instruction {
+        ix(
+            accounts::InitProofBuffer {
+                proof_buffer: *buffer,
+                owner: *owner,
+                system_program: system_program::ID,
+            },
+            instruction::InitProofBuffer {
+                expected_size: proof.len() as u32,
+                expiry_slots,
+                expected_keccak: keccak::hash(proof).0,
+            },
+        )
+    }
+
+    pub fn upload_chunk(uploader: &Pubkey, buffer: &Pubkey, offset: u32, chunk_data: Vec<u8>) -> Instruction {
+        ix(
+            accounts::UploadChunk {
+                proof_buffer: *buffer,
+                uploader: *uploader,
+            },
+            instruction::UploadChunk { offset, chunk_data },
+        )
+    }
+
+    /// Write each `(offset, bytes)` segment of the proof, in one instruction
+    pub fn upload_chunks_multi(uploader: &Pubkey, buffer: &Pubkey, segments: &[(u32, &[u8])]) -> Instruction {
+        ix(
+            accounts::UploadChunk {
+                proof_buffer: *buffer,
+                uploader: *uploader,
+            },
+            instruction::UploadChunksMulti {
+                segments: segments
+                    .iter()
+                    .map(|(offset, bytes)| UploadSegment { offset: *offset, len: bytes.len() as u16 })
+                    .collect(),
+                data: segments.iter().flat_map(|(_, bytes)| bytes.iter().copied()).collect(),
+            },
+        )
+    }
+
+    /// One `upload_chunk` per `chunk_size` bytes of `proof`, in order.
+    /// `chunk_size` must be a multiple of [`UPLOAD_CHUNK_ALIGN`].
+    pub fn upload_chunks(uploader: &Pubkey, buffer: &Pubkey, proof: &[u8], chunk_size: usize) -> Vec<Instruction> {
+        proof
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| upload_chunk(uploader, buffer, (i * chunk_size) as u32, chunk.to_vec()))
+            .collect()
+    }
+
+    pub fn delegate_uploader(owner: &Pubkey, buffer: &Pubkey, delegate: Pubkey) -> Instruction {
+        ix(
+            accounts::DelegateUploader {
+                proof_buffer: *buffer,
+                owner: *owner,
+            },
+            instruction::DelegateUploader { delegate },
+        )
+    }
+
+    /// Verify the uploaded proof against its public inputs and mark the buffer
+    /// finalized, or with `auto_close` close it and refund the owner
+    pub fn finalize_and_verify(
+        owner: &Pubkey,
+        buffer: &Pubkey,
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
+        auto_close: bool,
+    ) -> Instruction {
+        let mut finalize = ix(
+            accounts::FinalizeAndVerify {
+                proof_buffer: *buffer,
+                owner: *owner,
+                verifier_config: pda::verifier_config(),
+            },
+            instruction::FinalizeAndVerify { commitment, nullifier, merkle_root, recipient, auto_close },
+        );
+        // The refund needs the owner writable
+        finalize.accounts[1].is_writable |= auto_close;
+        finalize
+    }
+
+    /// Finalize several of `owner`'s buffers at once, each against its
+    /// entry in `proofs`. Unless `atomic`, a failed proof leaves its buffer
+    /// open without failing the rest; `BatchProofResult` events report each.
+    pub fn finalize_and_verify_batch(
+        owner: &Pubkey,
+        buffers: &[Pubkey],
+        proofs: Vec<BatchEntry>,
+        atomic: bool,
+    ) -> Instruction {
+        let mut ix = ix(
+            accounts::FinalizeAndVerifyBatch { owner: *owner, verifier_config: pda::verifier_config() },
+            instruction::FinalizeAndVerifyBatch { proofs, atomic },
+        );
+        ix.accounts.extend(buffers.iter().map(|buffer| AccountMeta::new(*buffer, false)));
+        ix
+    }
+
+    /// Stage 1 of a staged verification: pin the buffer and public inputs
+    /// in [`pda::verification_state`], paid for by `owner`
+    pub fn begin_staged_verification(
+        owner: &Pubkey,
+        buffer: &Pubkey,
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
+    ) -> Instruction {
+        ix(
+            accounts::BeginStagedVerification {
+                proof_buffer: *buffer,
+                verification_state: pda::verification_state(buffer),
+                owner: *owner,
+                system_program: system_program::ID,
+                verifier_config: pda::verifier_config(),
+            },
+            instruction::BeginStagedVerification { commitment, nullifier, merkle_root, recipient },
+        )
+    }
+
+    fn staged(owner: &Pubkey, buffer: &Pubkey) -> accounts::StagedVerification {
+        accounts::StagedVerification {
+            proof_buffer: *buffer,
+            verification_state: pda::verification_state(buffer),
+            owner: *owner,
+        }
+    }
+
+    fn close_staged(owner: &Pubkey, buffer: &Pubkey) -> accounts::CloseStagedVerification {
+        accounts::CloseStagedVerification {
+            proof_buffer: *buffer,
+            verification_state: pda::verification_state(buffer),
+            owner: *owner,
+        }
+    }
+
+    /// Stage 2: commitments, the OODS check and the FRI alphas
+    pub fn verify_staged_oods(owner: &Pubkey, buffer: &Pubkey) -> Instruction {
+        ix(staged(owner, buffer), instruction::VerifyStagedOods {})
+    }
+
+    /// Stage 3: the next `count` query openings, or as many as the
+    /// transaction's compute budget allows for 0
+    pub fn verify_staged_queries(owner: &Pubkey, buffer: &Pubkey, count: u8) -> Instruction {
+        ix(staged(owner, buffer), instruction::VerifyStagedQueries { count })
+    }
+
+    /// Whichever of stages 2 and 3 the checkpoint allows next
+    pub fn verify_step(owner: &Pubkey, buffer: &Pubkey) -> Instruction {
+        ix(staged(owner, buffer), instruction::VerifyStep {})
+    }
+
+    /// Stage 4: mark the buffer finalized and refund the checkpoint's rent
+    pub fn finalize_staged_verification(owner: &Pubkey, buffer: &Pubkey) -> Instruction {
+        ix(close_staged(owner, buffer), instruction::FinalizeStagedVerification {})
+    }
+
+    /// Abandon a staged verification, refunding the checkpoint's rent
+    pub fn cancel_staged_verification(owner: &Pubkey, buffer: &Pubkey) -> Instruction {
+        ix(close_staged(owner, buffer), instruction::CancelStagedVerification {})
+    }
+
+    /// Create [`pda::verifier_config`] at `security_level` (deployer, once);
+    /// `authority` can change the profile afterwards
+    pub fn initialize_verifier_config(authority: &Pubkey, security_level: SecurityLevel) -> Instruction {
+        ix(
+            accounts::InitializeVerifierConfig {
+                verifier_config: pda::verifier_config(),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeVerifierConfig { security_level: security_level as u8 },
+        )
+    }
+
+    /// Switch the profile proofs are checked at (config authority)
+    pub fn set_verifier_profile(authority: &Pubkey, security_level: SecurityLevel) -> Instruction {
+        ix(
+            accounts::SetVerifierProfile {
+                verifier_config: pda::verifier_config(),
+                authority: *authority,
+            },
+            instruction::SetVerifierProfile { security_level: security_level as u8 },
+        )
+    }
+
+    /// Create or refresh the VerifierParams PDA from the deployed program
+    /// and its profile
+    pub fn publish_verifier_params(payer: &Pubkey) -> Instruction {
+        ix(
+            accounts::PublishVerifierParams {
+                verifier_config: pda::verifier_config(),
+                verifier_params: pda::verifier_params(),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::PublishVerifierParams {},
+        )
+    }
+
+    /// Zero the buffer and refund its rent to `owner`
+    pub fn close_proof_buffer(owner: &Pubkey, buffer: &Pubkey) -> Instruction {
+        ix(
+            accounts::CloseProofBuffer {
+                proof_buffer: *buffer,
+                owner: *owner,
+            },
+            instruction::CloseProofBuffer {},
+        )
+    }
+
+    /// Close `owner`'s buffer once past its expiry; `reaper` keeps
+    /// [`stark_verifier::REAP_REWARD_BPS`] of the rent and `owner` gets the rest
+    pub fn reap_expired_buffer(reaper: &Pubkey, buffer: &Pubkey, owner: &Pubkey) -> Instruction {
+        ix(
+            accounts::ReapExpiredBuffer {
+                proof_buffer: *buffer,
+                owner: *owner,
+                reaper: *reaper,
+            },
+            instruction::ReapExpiredBuffer {},
+        )
+    }
+}
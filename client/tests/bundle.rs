@@ -0,0 +1,257 @@
+//! Claim transaction planning; pure, no programs needed
+
+use murkl_client::bundle::{
+    claim_lookup_addresses, plan_claim, plan_upload, transaction_size, ClaimPlan, PlanError, Verification,
+    MAX_TRANSACTION_CU,
+};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account;
+use anchor_spl::token;
+use anchor_lang::AnchorDeserialize;
+use murkl_client::{murkl, pda, verifier, DEFAULT_CHUNK_SIZE};
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use stark_verifier::{
+    instruction::UploadChunksMulti, BatchEntry, PROOF_MAGIC, PROOF_VERSION, UPLOAD_BITMAP_LEN, UPLOAD_CHUNK_ALIGN,
+};
+
+struct Claim {
+    relayer: Pubkey,
+    recipient: Pubkey,
+    relayer_token: Pubkey,
+    buffer: Pubkey,
+    mint: Pubkey,
+    inputs: BatchEntry,
+}
+
+impl Claim {
+    fn new() -> Self {
+        let (recipient, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let recipient_token = get_associated_token_address(&recipient, &mint);
+        Self {
+            relayer: Pubkey::new_unique(),
+            recipient,
+            relayer_token: Pubkey::new_unique(),
+            buffer: Pubkey::new_unique(),
+            mint,
+            inputs: BatchEntry {
+                commitment: [1; 32],
+                nullifier: [2; 32],
+                merkle_root: [3; 32],
+                recipient: recipient_token.to_bytes(),
+            },
+        }
+    }
+
+    fn claim_ix(&self) -> Instruction {
+        let recipient_token = Pubkey::new_from_array(self.inputs.recipient);
+        murkl::claim(
+            &self.relayer,
+            &self.relayer_token,
+            &recipient_token,
+            &self.buffer,
+            &self.mint,
+            7,
+            self.inputs.nullifier,
+            10,
+            true,
+            true,
+            None,
+            true,
+        )
+    }
+
+    fn lookup_table(&self) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: claim_lookup_addresses(&self.mint, &self.relayer_token),
+        }
+    }
+
+    fn plan(&self, num_queries: u8, tables: &[AddressLookupTableAccount]) -> ClaimPlan {
+        plan_claim(&self.relayer, &self.buffer, &proof(num_queries), &self.inputs, vec![self.claim_ix()], tables)
+            .unwrap()
+    }
+}
+
+/// Header of a proof with `num_queries` openings; the planner reads no further
+fn proof(num_queries: u8) -> Vec<u8> {
+    let mut data = PROOF_MAGIC.to_vec();
+    data.extend_from_slice(&[PROOF_VERSION, 0, 10, 4, 2, num_queries]);
+    data.resize(4096, 0);
+    data
+}
+
+/// Every planned instruction past the compute unit limits, in order
+fn instructions(plan: &ClaimPlan) -> Vec<&Instruction> {
+    plan.transactions
+        .iter()
+        .flat_map(|tx| {
+            assert_eq!(tx.instructions[0].program_id, compute_budget::ID);
+            &tx.instructions[1..]
+        })
+        .collect()
+}
+
+fn check_limits(plan: &ClaimPlan, payer: &Pubkey, tables: &[AddressLookupTableAccount]) {
+    for tx in &plan.transactions {
+        assert!(tx.compute_units <= MAX_TRANSACTION_CU);
+        assert!(transaction_size(payer, &tx.instructions, tables).unwrap() <= PACKET_DATA_SIZE);
+    }
+}
+
+#[test]
+fn small_proof_claims_in_one_transaction() {
+    let claim = Claim::new();
+    let tables = [claim.lookup_table()];
+    let plan = claim.plan(4, &tables);
+    check_limits(&plan, &claim.relayer, &tables);
+
+    assert_eq!(plan.verification, Verification::Direct);
+    assert_eq!(plan.transactions.len(), 1);
+    let finalize = verifier::finalize_and_verify(
+        &claim.relayer,
+        &claim.buffer,
+        claim.inputs.commitment,
+        claim.inputs.nullifier,
+        claim.inputs.merkle_root,
+        claim.inputs.recipient,
+        false,
+    );
+    assert_eq!(instructions(&plan), [&finalize, &claim.claim_ix()]);
+}
+
+#[test]
+fn lookup_table_makes_room_for_claim_setup() {
+    let claim = Claim::new();
+    let create_token_account =
+        create_associated_token_account(&claim.relayer, &claim.recipient, &claim.mint, &token::ID);
+    let mut claim_ix = claim.claim_ix();
+    // A compliance hook and its state accounts ride along as remaining accounts
+    claim_ix.accounts.extend((0..9).map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false)));
+    let setup = vec![create_token_account, claim_ix];
+
+    let plan_with = |tables: &[AddressLookupTableAccount]| {
+        let plan =
+            plan_claim(&claim.relayer, &claim.buffer, &proof(4), &claim.inputs, setup.clone(), tables).unwrap();
+        check_limits(&plan, &claim.relayer, tables);
+        plan.transactions.len()
+    };
+    assert_eq!(plan_with(&[]), 2);
+    assert_eq!(plan_with(&[claim.lookup_table()]), 1);
+}
+
+#[test]
+fn direct_verification_leaves_the_claim_to_the_next_transaction_when_out_of_compute() {
+    let claim = Claim::new();
+    let tables = [claim.lookup_table()];
+    let plan = claim.plan(8, &tables);
+    check_limits(&plan, &claim.relayer, &tables);
+
+    assert_eq!(plan.verification, Verification::Direct);
+    assert_eq!(plan.transactions.len(), 2);
+    assert_eq!(plan.transactions[1].instructions[1], claim.claim_ix());
+}
+
+#[test]
+fn large_proof_verifies_in_stages() {
+    let claim = Claim::new();
+    let tables = [claim.lookup_table()];
+    let plan = claim.plan(24, &tables);
+    check_limits(&plan, &claim.relayer, &tables);
+    assert_eq!(plan.verification, Verification::Staged);
+
+    let (owner, buffer) = (&claim.relayer, &claim.buffer);
+    let queries = verifier::verify_staged_queries(owner, buffer, 0);
+    let all = instructions(&plan);
+    let (batches, rest): (Vec<&Instruction>, Vec<&Instruction>) =
+        all.iter().partition(|ix| ix.data[..8] == queries.data[..8]);
+
+    // Batches cover every query, in as few transactions as the budget allows
+    assert_eq!(batches.iter().map(|ix| ix.data[8] as u32).sum::<u32>(), 24);
+    assert_eq!(plan.transactions.len(), 3);
+
+    let BatchEntry { commitment, nullifier, merkle_root, recipient } = claim.inputs.clone();
+    let expected = [
+        verifier::begin_staged_verification(owner, buffer, commitment, nullifier, merkle_root, recipient),
+        verifier::verify_staged_oods(owner, buffer),
+        verifier::finalize_staged_verification(owner, buffer),
+        claim.claim_ix(),
+    ];
+    assert_eq!(rest, expected.iter().collect::<Vec<_>>());
+    // The claim lands with the finalization
+    let last = plan.transactions.last().unwrap();
+    assert_eq!(last.instructions[last.instructions.len() - 2], expected[2]);
+    assert_eq!(
+        pda::verification_state(buffer),
+        expected[2].accounts[1].pubkey,
+        "stages share the buffer's checkpoint"
+    );
+}
+
+#[test]
+fn oversized_claim_is_reported() {
+    let claim = Claim::new();
+    let mut huge = claim.claim_ix();
+    huge.data.resize(PACKET_DATA_SIZE, 0);
+    let err = plan_claim(&claim.relayer, &claim.buffer, &proof(4), &claim.inputs, vec![huge], &[]).unwrap_err();
+    assert_eq!(err, PlanError::TooLarge { step: "claim" });
+
+    let err = plan_claim(&claim.relayer, &claim.buffer, b"not a proof", &claim.inputs, vec![], &[]).unwrap_err();
+    assert!(matches!(err, PlanError::Proof(_)));
+}
+
+/// Write planned uploads into `data` and its upload `bitmap` as the program would
+fn apply_uploads(uploads: &[Instruction], data: &mut [u8], bitmap: &mut [u8]) {
+    for ix in uploads {
+        let UploadChunksMulti { segments, data: bytes } = UploadChunksMulti::try_from_slice(&ix.data[8..]).unwrap();
+        let mut rest = &bytes[..];
+        for segment in segments {
+            let (offset, len) = (segment.offset as usize, segment.len as usize);
+            assert_eq!(offset % UPLOAD_CHUNK_ALIGN, 0);
+            data[offset..offset + len].copy_from_slice(&rest[..len]);
+            rest = &rest[len..];
+            for i in offset / UPLOAD_CHUNK_ALIGN..(offset + len).div_ceil(UPLOAD_CHUNK_ALIGN) {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+    }
+}
+
+#[test]
+fn upload_plan_fills_transactions_and_resends_only_missing_chunks() {
+    let (uploader, buffer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let proof: Vec<u8> = (0..16_000u32).map(|i| (i % 251) as u8).collect();
+    let price = ComputeBudgetInstruction::set_compute_unit_price(0);
+
+    let uploads = plan_upload(&uploader, &buffer, &proof, &[], &[]).unwrap();
+    for ix in &uploads {
+        assert!(transaction_size(&uploader, &[price.clone(), ix.clone()], &[]).unwrap() <= PACKET_DATA_SIZE);
+    }
+    assert!(uploads.len() < verifier::upload_chunks(&uploader, &buffer, &proof, DEFAULT_CHUNK_SIZE).len());
+
+    // The second transaction is dropped: the replan covers exactly its chunks
+    let (mut data, mut bitmap) = (vec![0u8; proof.len()], vec![0u8; UPLOAD_BITMAP_LEN]);
+    apply_uploads(&uploads[..1], &mut data, &mut bitmap);
+    apply_uploads(&uploads[2..], &mut data, &mut bitmap);
+    assert_ne!(data, proof);
+    let resend = plan_upload(&uploader, &buffer, &proof, &bitmap, &[]).unwrap();
+    assert_eq!(resend, uploads[1..2]);
+
+    apply_uploads(&resend, &mut data, &mut bitmap);
+    assert_eq!(data, proof);
+    assert!(plan_upload(&uploader, &buffer, &proof, &bitmap, &[]).unwrap().is_empty());
+
+    // Scattered gaps share one transaction, a segment each
+    let mut gaps = bitmap.clone();
+    gaps[0] &= !0b101;
+    gaps[10] &= !0b1000_0000;
+    let resend = plan_upload(&uploader, &buffer, &proof, &gaps, &[]).unwrap();
+    assert_eq!(resend.len(), 1);
+    let UploadChunksMulti { segments, .. } = UploadChunksMulti::try_from_slice(&resend[0].data[8..]).unwrap();
+    let offsets: Vec<u32> = segments.iter().map(|segment| segment.offset).collect();
+    assert_eq!(offsets, [0, 2 * UPLOAD_CHUNK_ALIGN as u32, 87 * UPLOAD_CHUNK_ALIGN as u32]);
+}
@@ -0,0 +1,65 @@
+//! Error code decoding, and the relayer's copy of the table
+
+use murkl_client::errors::error_table;
+use murkl_client::{OnChainError, BUFFER_HEADER_SIZE, MURKL_PROGRAM_ID, STARK_VERIFIER_ID};
+use murkl_program::MurklError;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::transaction::TransactionError;
+use stark_verifier::VerifierError;
+
+#[test]
+fn every_code_decodes_to_its_error() {
+    let mut next = [(MURKL_PROGRAM_ID, 6000), (STARK_VERIFIER_ID, 6000)];
+    for error in OnChainError::all() {
+        let (_, code) = next.iter_mut().find(|(id, _)| *id == error.program_id()).unwrap();
+        // Tables follow declaration order, so codes count up without gaps
+        assert_eq!(error.code(), *code, "{}", error.name());
+        *code += 1;
+        assert_eq!(OnChainError::decode(&error.program_id(), error.code()), Some(error));
+    }
+    assert_eq!(OnChainError::decode(&MURKL_PROGRAM_ID, next[0].1), None);
+    assert_eq!(OnChainError::decode(&solana_sdk::system_program::ID, 6000), None);
+}
+
+#[test]
+fn transaction_error_names_the_failing_program() {
+    let ix = |program_id| Instruction { program_id, accounts: vec![], data: vec![] };
+    let instructions = [ix(STARK_VERIFIER_ID), ix(MURKL_PROGRAM_ID)];
+    let failed = |index, code| TransactionError::InstructionError(index, InstructionError::Custom(code));
+
+    // 6002 is a different error in each program
+    assert_eq!(
+        OnChainError::from_transaction_error(&instructions, &failed(1, 6002)),
+        Some(OnChainError::Murkl(MurklError::AlreadyClaimed))
+    );
+    assert_eq!(
+        OnChainError::from_transaction_error(&instructions, &failed(0, 6002)),
+        Some(OnChainError::Verifier(VerifierError::BufferTooSmall))
+    );
+    assert_eq!(OnChainError::from_transaction_error(&instructions, &failed(2, 6002)), None);
+    assert_eq!(OnChainError::from_transaction_error(&instructions, &TransactionError::AccountInUse), None);
+}
+
+#[test]
+fn incomplete_upload_names_resume_offset() {
+    let error = OnChainError::Verifier(VerifierError::IncompleteProof);
+    let mut header = vec![0u8; BUFFER_HEADER_SIZE];
+    // The tail landed, so the furthest byte written is the last one
+    header[32..36].copy_from_slice(&2600u32.to_le_bytes());
+    header[36..40].copy_from_slice(&2600u32.to_le_bytes());
+    // Of the 41 chunks only chunk 28, from byte 1792, is missing
+    let bitmap = &mut header[257..];
+    bitmap[..3].fill(0xff);
+    bitmap[3] = 0b1110_1111;
+    bitmap[4] = 0xff;
+    bitmap[5] = 0b0000_0111;
+    assert_eq!(error.remediation(Some(&header)), "Resume the upload from byte 1792 of 2600");
+    assert_eq!(error.remediation(None), error.hint());
+}
+
+#[test]
+fn relayer_table_matches() {
+    let committed: serde_json::Value = serde_json::from_str(include_str!("../../relayer/src/program-errors.json")).unwrap();
+    let current = serde_json::to_value(error_table()).unwrap();
+    assert!(committed == current, "relayer/src/program-errors.json is stale; regenerate it with `cargo run --bin error-table`");
+}
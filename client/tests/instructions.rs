@@ -0,0 +1,668 @@
+//! Executable examples for every murkl-client builder
+//!
+//! Each test walks one step of the pool flow the way an integrator would,
+//! against the real programs. Build them first; the tests load the `.so`
+//! files rather than running the programs natively:
+//!
+//! ```text
+//! anchor build
+//! cd client && BPF_OUT_DIR=../target/deploy cargo test
+//! ```
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use murkl_client::{
+    bloom_may_be_spent, bundle, deposit_registration_hash, murkl, nullifier_shard_index, pda, verifier, PoolConfig,
+    SecurityLevel, BUFFER_HEADER_SIZE, DEFAULT_CHUNK_SIZE, MURKL_PROGRAM_ID, NULLIFIER_SHARD_HEADER, SOL_POOL_MINT, STARK_VERIFIER_ID,
+};
+use murkl_program::{
+    AdminLog, ClaimReceipt, DepositRecord, DepositRegistration, GlobalConfig, MintDepositRecord, NullifierBloom,
+    NullifierRecord, Pool, PoolRegistry, RegistryEntry, VaultRotation, VAULT_ROTATION_DELAY_SECS,
+};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Clock,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+
+const DEPOSIT: u64 = 1_000_000;
+
+// ============================================================================
+// Harness
+// ============================================================================
+
+async fn start() -> ProgramTestContext {
+    let mut test = ProgramTest::default();
+    test.add_program("murkl_program", MURKL_PROGRAM_ID, None);
+    test.add_program("stark_verifier", STARK_VERIFIER_ID, None);
+    let mut ctx = test.start_with_context().await;
+    // What the deployer does first: verification needs a profile
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[verifier::initialize_verifier_config(&payer, SecurityLevel::Standard96)], &[]).await.unwrap();
+    ctx
+}
+
+/// Send `ixs` in one transaction paid by the context payer
+async fn send(ctx: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let mut all = vec![&ctx.payer];
+    all.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&ctx.payer.pubkey()), all.as_slice(), blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, key: &Pubkey) -> T {
+    let account = ctx.banks_client.get_account(*key).await.unwrap().expect("account exists");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+async fn create_mint(ctx: &mut ProgramTestContext) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let len = spl_token::state::Mint::LEN;
+    let ixs = [
+        system_instruction::create_account(&payer, &mint.pubkey(), rent.minimum_balance(len), len as u64, &spl_token::ID),
+        spl_token::instruction::initialize_mint(&spl_token::ID, &mint.pubkey(), &payer, None, 6).unwrap(),
+    ];
+    send(ctx, &ixs, &[&mint]).await.unwrap();
+    mint.pubkey()
+}
+
+/// Token account for `owner`, funded with `amount` freshly minted tokens
+async fn token_account(ctx: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+    let account = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let len = spl_token::state::Account::LEN;
+    let mut ixs = vec![
+        system_instruction::create_account(&payer, &account.pubkey(), rent.minimum_balance(len), len as u64, &spl_token::ID),
+        spl_token::instruction::initialize_account(&spl_token::ID, &account.pubkey(), mint, owner).unwrap(),
+    ];
+    if amount > 0 {
+        ixs.push(spl_token::instruction::mint_to(&spl_token::ID, mint, &account.pubkey(), &payer, &[], amount).unwrap());
+    }
+    send(ctx, &ixs, &[&account]).await.unwrap();
+    account.pubkey()
+}
+
+async fn balance(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(*token_account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+/// Config, pool, vault, Merkle state and admin log for a new mint; the payer is admin
+async fn pool(ctx: &mut ProgramTestContext, with_stats: bool) -> Pubkey {
+    let admin = ctx.payer.pubkey();
+    let mint = create_mint(ctx).await;
+    let mut ixs = vec![
+        murkl::initialize_config(&admin),
+        murkl::initialize_pool(&admin, &mint, PoolConfig::default()),
+        murkl::initialize_pool_merkle(&admin, &mint),
+        murkl::initialize_admin_log(&admin, &mint),
+    ];
+    if with_stats {
+        ixs.push(murkl::initialize_pool_stats(&admin, &mint));
+    }
+    send(ctx, &ixs, &[]).await.unwrap();
+    mint
+}
+
+/// Deposit `DEPOSIT` under `commitment`, returning its leaf index
+async fn deposit(ctx: &mut ProgramTestContext, mint: &Pubkey, commitment: [u8; 32], with_stats: bool) -> u64 {
+    let depositor = ctx.payer.pubkey();
+    let depositor_token = token_account(ctx, mint, &depositor, DEPOSIT).await;
+    let leaf_index = fetch::<Pool>(ctx, &pda::pool(mint)).await.leaf_count;
+
+    let ix = murkl::deposit(&depositor, &depositor_token, mint, leaf_index, DEPOSIT, commitment, with_stats);
+    send(ctx, &[ix], &[]).await.unwrap();
+    leaf_index
+}
+
+/// Write a stark-verifier buffer finalized by `relayer` for these public
+/// inputs, standing in for a real proof from `proof_buffer_lifecycle`
+fn finalized_buffer(
+    ctx: &mut ProgramTestContext,
+    relayer: &Pubkey,
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    root: &[u8; 32],
+    recipient: &Pubkey,
+) -> Pubkey {
+    let buffer = Pubkey::new_unique();
+    let mut data = vec![0u8; BUFFER_HEADER_SIZE];
+    data[0..32].copy_from_slice(relayer.as_ref());
+    data[40] = 1;
+    data[41..73].copy_from_slice(commitment);
+    data[73..105].copy_from_slice(nullifier);
+    data[105..137].copy_from_slice(root);
+    data[137..169].copy_from_slice(recipient.as_ref());
+    let lamports = Rent::default().minimum_balance(data.len());
+    ctx.set_account(
+        &buffer,
+        &AccountSharedData::from(Account { lamports, data, owner: STARK_VERIFIER_ID, executable: false, rent_epoch: 0 }),
+    );
+    buffer
+}
+
+// ============================================================================
+// Examples
+// ============================================================================
+
+#[tokio::test]
+async fn initialize_config_and_registry() {
+    let mut ctx = start().await;
+    let admin = ctx.payer.pubkey();
+
+    send(&mut ctx, &[murkl::initialize_config(&admin)], &[]).await.unwrap();
+    let config: GlobalConfig = fetch(&mut ctx, &pda::config()).await;
+    assert_eq!(config.admin, admin);
+
+    send(&mut ctx, &[murkl::initialize_registry(&admin)], &[]).await.unwrap();
+    let registry: PoolRegistry = fetch(&mut ctx, &pda::registry()).await;
+    assert_eq!(registry.pool_count, 0);
+}
+
+#[tokio::test]
+async fn initialize_and_register_pool() {
+    let mut ctx = start().await;
+    let admin = ctx.payer.pubkey();
+    let mint = pool(&mut ctx, true).await;
+    let pool_key = pda::pool(&mint);
+
+    let state: Pool = fetch(&mut ctx, &pool_key).await;
+    assert_eq!(state.token_mint, mint);
+    assert_eq!(state.vault, pda::vault(&pool_key));
+    assert_eq!(state.leaf_count, 0);
+    assert!(state.stats_enabled);
+
+    let ixs = [murkl::initialize_registry(&admin), murkl::register_pool(&admin, &mint, [7; 32])];
+    send(&mut ctx, &ixs, &[]).await.unwrap();
+    let entry: RegistryEntry = fetch(&mut ctx, &pda::registry_entry(&pool_key)).await;
+    assert_eq!((entry.index, entry.pool, entry.verifier_config_hash), (0, pool_key, [7; 32]));
+}
+
+#[tokio::test]
+async fn deposit_into_pool() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, true).await;
+    let pool_key = pda::pool(&mint);
+    let empty_root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+
+    let leaf_index = deposit(&mut ctx, &mint, [0xc0; 32], true).await;
+
+    let record: DepositRecord = fetch(&mut ctx, &pda::deposit(&pool_key, leaf_index)).await;
+    assert_eq!((record.amount, record.commitment, record.claimed), (DEPOSIT, [0xc0; 32], false));
+    let state: Pool = fetch(&mut ctx, &pool_key).await;
+    assert_eq!(state.leaf_count, 1);
+    assert_ne!(state.merkle_root, empty_root);
+    assert_eq!(balance(&mut ctx, &pda::vault(&pool_key)).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn commit_reveal_deposit() {
+    let mut ctx = start().await;
+    let admin = ctx.payer.pubkey();
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    send(&mut ctx, &[murkl::set_deposit_reveal_window(&admin, &mint, 50)], &[]).await.unwrap();
+
+    // Direct deposits are refused once the pool has a reveal window
+    let depositor_token = token_account(&mut ctx, &mint, &admin, DEPOSIT).await;
+    let direct = murkl::deposit(&admin, &depositor_token, &mint, 0, DEPOSIT, [0xc1; 32], false);
+    assert!(send(&mut ctx, &[direct], &[]).await.is_err());
+
+    let (commitment, salt) = ([0xc2; 32], [0x5a; 32]);
+    send(&mut ctx, &[murkl::register_deposit(&admin, &mint, &commitment, &salt)], &[]).await.unwrap();
+    let registration = pda::deposit_registration(&pool_key, &deposit_registration_hash(&commitment, &salt));
+    let registered_slot = fetch::<DepositRegistration>(&mut ctx, &registration).await.registered_slot;
+
+    ctx.warp_to_slot(registered_slot + 1).unwrap();
+    let reveal = murkl::reveal_deposit(&admin, &depositor_token, &mint, 0, DEPOSIT, commitment, salt, false);
+    send(&mut ctx, &[reveal], &[]).await.unwrap();
+
+    let record: DepositRecord = fetch(&mut ctx, &pda::deposit(&pool_key, 0)).await;
+    assert_eq!((record.amount, record.commitment), (DEPOSIT, commitment));
+    assert!(ctx.banks_client.get_account(registration).await.unwrap().is_none());
+
+    // An unrevealed registration can be closed by anyone once it expires
+    let stale_hash = deposit_registration_hash(&[0xc3; 32], &salt);
+    send(&mut ctx, &[murkl::register_deposit(&admin, &mint, &[0xc3; 32], &salt)], &[]).await.unwrap();
+    let expire = murkl::expire_deposit_registration(&mint, &stale_hash, &admin);
+    assert!(send(&mut ctx, &[expire.clone()], &[]).await.is_err());
+    ctx.warp_to_slot(registered_slot + 60).unwrap();
+    send(&mut ctx, &[expire], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn proof_buffer_lifecycle() {
+    let mut ctx = start().await;
+    let owner = ctx.payer.pubkey();
+    let buffer = Keypair::new();
+    let proof: Vec<u8> = (0..2000u32).map(|i| i as u8).collect();
+
+    // Create and initialize in one transaction so nobody else can init it
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let lamports = rent.minimum_balance(verifier::buffer_space(proof.len()));
+    let ixs = [
+        verifier::create_proof_buffer(&owner, &buffer.pubkey(), proof.len(), lamports),
+        verifier::init_proof_buffer(&owner, &buffer.pubkey(), &proof, verifier::DEFAULT_BUFFER_EXPIRY_SLOTS),
+    ];
+    send(&mut ctx, &ixs, &[&buffer]).await.unwrap();
+
+    for ix in verifier::upload_chunks(&owner, &buffer.pubkey(), &proof, DEFAULT_CHUNK_SIZE) {
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    // A delegate may write chunks too, e.g. to spread uploads over worker keys
+    let worker = Keypair::new();
+    send(&mut ctx, &[verifier::delegate_uploader(&owner, &buffer.pubkey(), worker.pubkey())], &[]).await.unwrap();
+    let rewrite = verifier::upload_chunk(&worker.pubkey(), &buffer.pubkey(), 0, proof[..DEFAULT_CHUNK_SIZE].to_vec());
+    send(&mut ctx, &[rewrite], &[&worker]).await.unwrap();
+
+    let data = ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap().data;
+    assert_eq!(u32::from_le_bytes(data[32..36].try_into().unwrap()) as usize, proof.len());
+    assert_eq!(&data[BUFFER_HEADER_SIZE..], &proof[..]);
+
+    // These bytes are not a proof, so verification fails and the buffer stays open
+    let finalize = verifier::finalize_and_verify(&owner, &buffer.pubkey(), [1; 32], [2; 32], [3; 32], [4; 32], false);
+    assert!(send(&mut ctx, &[finalize], &[]).await.is_err());
+
+    // Batched, a failing proof fails the transaction only when atomic
+    let entry = verifier::BatchEntry { commitment: [1; 32], nullifier: [2; 32], merkle_root: [3; 32], recipient: [4; 32] };
+    let batch = |atomic| verifier::finalize_and_verify_batch(&owner, &[buffer.pubkey()], vec![entry.clone()], atomic);
+    assert!(send(&mut ctx, &[batch(true)], &[]).await.is_err());
+    send(&mut ctx, &[batch(false)], &[]).await.unwrap();
+    let data = ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap().data;
+    assert_eq!(data[40], 0, "failed batch entry stays unfinalized");
+
+    // Staged, the run fails where it begins and leaves no checkpoint behind
+    let begin = verifier::begin_staged_verification(&owner, &buffer.pubkey(), [1; 32], [2; 32], [3; 32], [4; 32]);
+    assert!(send(&mut ctx, &[begin], &[]).await.is_err());
+    let state = pda::verification_state(&buffer.pubkey());
+    assert!(ctx.banks_client.get_account(state).await.unwrap().is_none());
+
+    send(&mut ctx, &[verifier::close_proof_buffer(&owner, &buffer.pubkey())], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn verifier_profile_is_set_by_its_authority() {
+    let mut ctx = start().await;
+    let authority = ctx.payer.pubkey();
+
+    // Once only, and only the authority that created it may change it
+    let again = verifier::initialize_verifier_config(&authority, SecurityLevel::High128);
+    assert!(send(&mut ctx, &[again], &[]).await.is_err());
+    let stranger = Keypair::new();
+    let hijack = verifier::set_verifier_profile(&stranger.pubkey(), SecurityLevel::High128);
+    assert!(send(&mut ctx, &[hijack], &[&stranger]).await.is_err());
+
+    // Raising it changes what gets published for pools to pin
+    send(&mut ctx, &[verifier::set_verifier_profile(&authority, SecurityLevel::High128)], &[]).await.unwrap();
+    send(&mut ctx, &[verifier::publish_verifier_params(&authority)], &[]).await.unwrap();
+    let params: stark_verifier::VerifierParams = fetch(&mut ctx, &pda::verifier_params()).await;
+    assert_eq!(params.config().security_level(), Some(SecurityLevel::High128));
+
+    // `anchor build` without the demo feature never drops to 4 queries
+    let demo = verifier::set_verifier_profile(&authority, SecurityLevel::Demo);
+    assert!(send(&mut ctx, &[demo], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn dropped_upload_is_resent_from_the_bitmap() {
+    let mut ctx = start().await;
+    let owner = ctx.payer.pubkey();
+    let buffer = Keypair::new();
+    let proof: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+
+    let lamports = ctx.banks_client.get_rent().await.unwrap().minimum_balance(verifier::buffer_space(proof.len()));
+    let ixs = [
+        verifier::create_proof_buffer(&owner, &buffer.pubkey(), proof.len(), lamports),
+        verifier::init_proof_buffer(&owner, &buffer.pubkey(), &proof, verifier::DEFAULT_BUFFER_EXPIRY_SLOTS),
+    ];
+    send(&mut ctx, &ixs, &[&buffer]).await.unwrap();
+
+    // Everything but the first transaction lands, which writes past the gap
+    let uploads = bundle::plan_upload(&owner, &buffer.pubkey(), &proof, &[], &[]).unwrap();
+    for ix in &uploads[1..] {
+        send(&mut ctx, &[ix.clone()], &[]).await.unwrap();
+    }
+    let data = ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap().data;
+    assert_eq!(u32::from_le_bytes(data[32..36].try_into().unwrap()) as usize, proof.len());
+    let finalize = verifier::finalize_and_verify(&owner, &buffer.pubkey(), [1; 32], [2; 32], [3; 32], [4; 32], false);
+    assert!(send(&mut ctx, &[finalize], &[]).await.is_err());
+
+    let bitmap = stark_verifier::upload_bitmap(&data).unwrap();
+    let resend = bundle::plan_upload(&owner, &buffer.pubkey(), &proof, bitmap, &[]).unwrap();
+    assert_eq!(resend, uploads[..1]);
+    send(&mut ctx, &resend, &[]).await.unwrap();
+    let data = ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap().data;
+    assert_eq!(&data[BUFFER_HEADER_SIZE..], &proof[..]);
+    let bitmap = stark_verifier::upload_bitmap(&data).unwrap();
+    assert!(bundle::plan_upload(&owner, &buffer.pubkey(), &proof, bitmap, &[]).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn abandoned_buffer_is_reaped() {
+    let mut ctx = start().await;
+    let payer = ctx.payer.pubkey();
+    let owner = Keypair::new();
+    let buffer = Keypair::new();
+    let fund = system_instruction::transfer(&payer, &owner.pubkey(), 1_000_000_000);
+    send(&mut ctx, &[fund], &[]).await.unwrap();
+
+    let lamports = ctx.banks_client.get_rent().await.unwrap().minimum_balance(verifier::buffer_space(100));
+    let ixs = [
+        verifier::create_proof_buffer(&owner.pubkey(), &buffer.pubkey(), 100, lamports),
+        verifier::init_proof_buffer(&owner.pubkey(), &buffer.pubkey(), &[0; 100], 50),
+    ];
+    send(&mut ctx, &ixs, &[&owner, &buffer]).await.unwrap();
+    let data = ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap().data;
+    let expires_at = stark_verifier::buffer_expires_at(&data).unwrap();
+    let refund_before = ctx.banks_client.get_balance(owner.pubkey()).await.unwrap();
+
+    // Anyone may reap it, but only once it expires and only for its owner
+    let reaper = ctx.payer.pubkey();
+    let reap = verifier::reap_expired_buffer(&reaper, &buffer.pubkey(), &owner.pubkey());
+    assert!(send(&mut ctx, &[reap.clone()], &[]).await.is_err());
+    ctx.warp_to_slot(expires_at).unwrap();
+    let misdirected = verifier::reap_expired_buffer(&reaper, &buffer.pubkey(), &reaper);
+    assert!(send(&mut ctx, &[misdirected], &[]).await.is_err());
+    send(&mut ctx, &[reap], &[]).await.unwrap();
+
+    assert!(ctx.banks_client.get_account(buffer.pubkey()).await.unwrap().is_none());
+    let refund = lamports - stark_verifier::reap_reward(lamports);
+    assert_eq!(ctx.banks_client.get_balance(owner.pubkey()).await.unwrap(), refund_before + refund);
+}
+
+#[tokio::test]
+async fn claim_with_finalized_buffer() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, true).await;
+    let pool_key = pda::pool(&mint);
+    let commitment = [0xc0; 32];
+    let nullifier = [0x4e; 32];
+    let leaf_index = deposit(&mut ctx, &mint, commitment, true).await;
+
+    let relayer = ctx.payer.pubkey();
+    let relayer_token = token_account(&mut ctx, &mint, &relayer, 0).await;
+    let recipient = Keypair::new().pubkey();
+    let recipient_token = token_account(&mut ctx, &mint, &recipient, 0).await;
+
+    // A relayer gets here through `proof_buffer_lifecycle` with a real proof
+    // from the prover; this example writes the finalized header directly so
+    // it does not depend on one.
+    let root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+    let buffer = finalized_buffer(&mut ctx, &relayer, &commitment, &nullifier, &root, &recipient_token);
+
+    // Pool default caps the fee at 1%
+    let fee = DEPOSIT / 100;
+    let claim = |fee| {
+        let receipt = Some(&recipient);
+        murkl::claim(&relayer, &relayer_token, &recipient_token, &buffer, &mint, leaf_index, nullifier, fee, true, false, receipt, true)
+    };
+
+    // A pool pinning verifier params refuses claims until the verifier
+    // publishes exactly those
+    let params_hash = stark_verifier::VerifierConfig::for_level(SecurityLevel::Standard96).params_hash();
+    let pin = murkl::set_required_verifier_params(&relayer, &mint, Some(params_hash));
+    send(&mut ctx, &[pin], &[]).await.unwrap();
+    assert!(send(&mut ctx, &[claim(fee)], &[]).await.is_err());
+    send(&mut ctx, &[verifier::publish_verifier_params(&relayer)], &[]).await.unwrap();
+    let params: stark_verifier::VerifierParams = fetch(&mut ctx, &pda::verifier_params()).await;
+    assert_eq!(params.params_hash, params_hash);
+
+    send(&mut ctx, &[claim(fee)], &[]).await.unwrap();
+
+    assert_eq!(balance(&mut ctx, &recipient_token).await, DEPOSIT - fee);
+    assert_eq!(balance(&mut ctx, &relayer_token).await, fee);
+    assert!(fetch::<DepositRecord>(&mut ctx, &pda::deposit(&pool_key, leaf_index)).await.claimed);
+    let record: NullifierRecord = fetch(&mut ctx, &pda::nullifier(&pool_key, &nullifier)).await;
+    assert_eq!(record.nullifier, nullifier);
+    let receipt: ClaimReceipt = fetch(&mut ctx, &pda::claim_receipt(&pool_key, &recipient)).await;
+    assert_eq!((receipt.recipient, receipt.claims), (recipient, 1));
+    // The claim closed the buffer, so there's no separate close to send
+    assert!(ctx.banks_client.get_account(buffer).await.unwrap().is_none());
+
+    // The nullifier record already exists, so a second claim fails
+    assert!(send(&mut ctx, &[claim(fee - 1)], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn sol_pool_deposit_and_claim() {
+    let mut ctx = start().await;
+    let admin = ctx.payer.pubkey();
+    let pool_key = pda::pool(&SOL_POOL_MINT);
+    let ixs = [
+        murkl::initialize_config(&admin),
+        murkl::initialize_sol_pool(&admin, PoolConfig::default()),
+        murkl::initialize_pool_merkle(&admin, &SOL_POOL_MINT),
+    ];
+    send(&mut ctx, &ixs, &[]).await.unwrap();
+    let vault = pda::vault(&pool_key);
+    let reserve = ctx.banks_client.get_balance(vault).await.unwrap();
+    assert!(reserve > 0, "vault holds its rent reserve");
+
+    let commitment = [0x50; 32];
+    let nullifier = [0x51; 32];
+    send(&mut ctx, &[murkl::deposit_sol(&admin, 0, DEPOSIT, commitment, false)], &[]).await.unwrap();
+    assert_eq!(ctx.banks_client.get_balance(vault).await.unwrap(), reserve + DEPOSIT);
+    // Token instructions refuse the SOL pool's vault
+    let token = token_account(&mut ctx, &create_mint(&mut ctx).await, &admin, DEPOSIT).await;
+    let wrong = murkl::deposit(&admin, &token, &SOL_POOL_MINT, 1, DEPOSIT, commitment, false);
+    assert!(send(&mut ctx, &[wrong], &[]).await.is_err());
+
+    let recipient = Keypair::new().pubkey();
+    let root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+    let buffer = finalized_buffer(&mut ctx, &admin, &commitment, &nullifier, &root, &recipient);
+
+    let fee = DEPOSIT / 100;
+    let claim = murkl::claim_sol(&admin, &recipient, &buffer, 0, nullifier, fee, false, false, false);
+    send(&mut ctx, &[claim], &[]).await.unwrap();
+
+    assert_eq!(ctx.banks_client.get_balance(recipient).await.unwrap(), DEPOSIT - fee);
+    assert_eq!(ctx.banks_client.get_balance(vault).await.unwrap(), reserve);
+    assert!(fetch::<DepositRecord>(&mut ctx, &pda::deposit(&pool_key, 0)).await.claimed);
+}
+
+#[tokio::test]
+async fn multi_mint_deposit_and_claim() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    let admin = ctx.payer.pubkey();
+    let other = create_mint(&mut ctx).await;
+    send(&mut ctx, &[murkl::add_accepted_mint(&admin, &mint, &other)], &[]).await.unwrap();
+    assert_eq!(fetch::<Pool>(&mut ctx, &pool_key).await.accepted_vault(&other), Some(pda::accepted_vault(&pool_key, &other)));
+
+    // One tree: a pool-mint deposit then an accepted-mint one take leaves 0 and 1
+    deposit(&mut ctx, &mint, [0x10; 32], false).await;
+    let commitment = [0x11; 32];
+    let nullifier = [0x12; 32];
+    let depositor_token = token_account(&mut ctx, &other, &admin, DEPOSIT).await;
+    let ix = murkl::deposit_accepted_mint(&admin, &depositor_token, &mint, &other, 1, DEPOSIT, commitment, false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(balance(&mut ctx, &pda::accepted_vault(&pool_key, &other)).await, DEPOSIT);
+    let record: MintDepositRecord = fetch(&mut ctx, &pda::deposit(&pool_key, 1)).await;
+    assert_eq!((record.mint, record.leaf_index), (other, 1));
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_token = token_account(&mut ctx, &other, &recipient, 0).await;
+    let root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+    let buffer = finalized_buffer(&mut ctx, &admin, &commitment, &nullifier, &root, &recipient_token);
+
+    // A plain claim can't pay the deposit out of the pool mint's vault
+    let pool_token = token_account(&mut ctx, &mint, &recipient, 0).await;
+    let plain = murkl::claim(&admin, &pool_token, &pool_token, &buffer, &mint, 1, nullifier, 0, false, false, None, false);
+    assert!(send(&mut ctx, &[plain], &[]).await.is_err());
+
+    let claim = murkl::claim_accepted_mint(
+        &admin, &recipient_token, &recipient_token, &buffer, &mint, &other, 1, nullifier, 0, false, false, None,
+    );
+    send(&mut ctx, &[claim], &[]).await.unwrap();
+    assert_eq!(balance(&mut ctx, &recipient_token).await, DEPOSIT);
+    assert_eq!(balance(&mut ctx, &pda::vault(&pool_key)).await, DEPOSIT);
+    assert!(fetch::<MintDepositRecord>(&mut ctx, &pda::deposit(&pool_key, 1)).await.claimed);
+}
+
+#[tokio::test]
+async fn nullifier_registry_claim() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    let admin = ctx.payer.pubkey();
+    let commitment = [0x5c; 32];
+    let nullifier = [0xa7; 32];
+    let leaf_index = deposit(&mut ctx, &mint, commitment, false).await;
+
+    // Four leading bits: 16 shards, created as claims first need them
+    let bits = 4;
+    let shard = nullifier_shard_index(&nullifier, bits);
+    let setup = [
+        murkl::set_nullifier_shard_bits(&admin, &mint, bits),
+        murkl::initialize_nullifier_shard(&admin, &mint, shard),
+    ];
+    send(&mut ctx, &setup, &[]).await.unwrap();
+    // The shard count is fixed once set
+    assert!(send(&mut ctx, &[murkl::set_nullifier_shard_bits(&admin, &mint, 8)], &[]).await.is_err());
+
+    let admin_token = token_account(&mut ctx, &mint, &admin, 0).await;
+    let root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+    let buffer = finalized_buffer(&mut ctx, &admin, &commitment, &nullifier, &root, &admin_token);
+    let claim =
+        murkl::claim_sharded(&admin, &admin_token, &admin_token, &buffer, &mint, leaf_index, nullifier, 0, bits, false, false, None);
+    send(&mut ctx, &[claim.clone()], &[]).await.unwrap();
+
+    // The nullifier is the shard's only entry, and no NullifierRecord was made
+    let shard_account = ctx.banks_client.get_account(pda::nullifier_shard(&pool_key, shard)).await.unwrap().unwrap();
+    assert_eq!(shard_account.data[NULLIFIER_SHARD_HEADER..], nullifier);
+    assert!(ctx.banks_client.get_account(pda::nullifier(&pool_key, &nullifier)).await.unwrap().is_none());
+
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let check = Transaction::new_signed_with_payer(
+        &[murkl::check_nullifier(&mint, nullifier, Some(bits))],
+        Some(&admin),
+        &[&ctx.payer],
+        blockhash,
+    );
+    let simulated = ctx.banks_client.simulate_transaction(check).await.unwrap();
+    assert_eq!(simulated.simulation_details.unwrap().return_data.unwrap().data, [1]);
+
+    assert!(send(&mut ctx, &[claim], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn nullifier_bloom_claim() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    let admin = ctx.payer.pubkey();
+    let old_leaf = deposit(&mut ctx, &mint, [0x01; 32], false).await;
+    send(&mut ctx, &[murkl::initialize_nullifier_bloom(&admin, &mint)], &[]).await.unwrap();
+    let commitment = [0xb1; 32];
+    let nullifier = [0x3d; 32];
+    let leaf_index = deposit(&mut ctx, &mint, commitment, false).await;
+
+    let bloom_key = pda::nullifier_bloom(&pool_key);
+    let data = ctx.banks_client.get_account(bloom_key).await.unwrap().unwrap().data;
+    assert!(!bloom_may_be_spent(&data, leaf_index, &nullifier).unwrap());
+    // Deposits from before the filter could have been claimed before it
+    assert!(bloom_may_be_spent(&data, old_leaf, &[0x77; 32]).unwrap());
+
+    let admin_token = token_account(&mut ctx, &mint, &admin, 0).await;
+    let root = fetch::<Pool>(&mut ctx, &pool_key).await.merkle_root;
+    let buffer = finalized_buffer(&mut ctx, &admin, &commitment, &nullifier, &root, &admin_token);
+    let claim = |with_bloom| {
+        murkl::claim(&admin, &admin_token, &admin_token, &buffer, &mint, leaf_index, nullifier, 0, false, with_bloom, None, false)
+    };
+    // Once the pool keeps a filter, claims must update it
+    assert!(send(&mut ctx, &[claim(false)], &[]).await.is_err());
+    send(&mut ctx, &[claim(true)], &[]).await.unwrap();
+
+    let data = ctx.banks_client.get_account(bloom_key).await.unwrap().unwrap().data;
+    assert!(bloom_may_be_spent(&data, leaf_index, &nullifier).unwrap());
+    assert_eq!(fetch::<NullifierBloom>(&mut ctx, &bloom_key).await.inserted, 1);
+}
+
+#[tokio::test]
+async fn vault_rotation() {
+    let mut ctx = start().await;
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    let admin = ctx.payer.pubkey();
+    deposit(&mut ctx, &mint, [0x21; 32], false).await;
+    let old_vault = pda::vault(&pool_key);
+    let rotate = murkl::rotate_vault(&admin, &mint, &old_vault, 0, false);
+
+    send(&mut ctx, &[murkl::schedule_vault_rotation(&admin, &mint)], &[]).await.unwrap();
+    let rotation: VaultRotation = fetch(&mut ctx, &pda::vault_rotation(&pool_key)).await;
+    assert_eq!(rotation.executable_at - rotation.scheduled_at, VAULT_ROTATION_DELAY_SECS);
+    // Not before the timelock is up
+    assert!(send(&mut ctx, &[rotate.clone()], &[]).await.is_err());
+
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = rotation.executable_at;
+    ctx.set_sysvar(&clock);
+    send(&mut ctx, &[rotate], &[]).await.unwrap();
+
+    let state: Pool = fetch(&mut ctx, &pool_key).await;
+    assert_eq!((state.vault, state.vault_generation), (pda::vault_at(&pool_key, 1), 1));
+    assert_eq!(balance(&mut ctx, &state.vault).await, DEPOSIT);
+    assert!(ctx.banks_client.get_account(old_vault).await.unwrap().is_none());
+    assert!(ctx.banks_client.get_account(pda::vault_rotation(&pool_key)).await.unwrap().is_none());
+
+    // A fresh schedule can be withdrawn
+    send(&mut ctx, &[murkl::schedule_vault_rotation(&admin, &mint)], &[]).await.unwrap();
+    send(&mut ctx, &[murkl::cancel_vault_rotation(&admin, &mint)], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(pda::vault_rotation(&pool_key)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn admin_operations() {
+    let mut ctx = start().await;
+    let admin = ctx.payer.pubkey();
+    let mint = pool(&mut ctx, false).await;
+    let pool_key = pda::pool(&mint);
+    let hook = Pubkey::new_unique();
+
+    let ixs = [
+        murkl::pause_pool(&admin, &mint),
+        murkl::set_path_claims(&admin, &mint, true),
+        murkl::set_max_proof_age(&admin, &mint, 150),
+        murkl::set_root_history_window(&admin, &mint, 5),
+        murkl::set_compliance_hook(&admin, &mint, Some(hook)),
+        murkl::set_epoch_duration(&admin, &mint, 86_400),
+        murkl::grow_pool(&admin, &mint),
+    ];
+    send(&mut ctx, &ixs, &[]).await.unwrap();
+
+    let state: Pool = fetch(&mut ctx, &pool_key).await;
+    assert!(state.paused);
+    assert!(state.path_claims_enabled);
+    assert_eq!(state.max_proof_age_slots, 150);
+    assert_eq!(state.root_history_window, 5);
+    assert_eq!(state.config.compliance_hook, Some(hook));
+    assert_eq!(state.epoch_duration_secs, 86_400);
+
+    send(&mut ctx, &[murkl::unpause_pool(&admin, &mint)], &[]).await.unwrap();
+    assert!(!fetch::<Pool>(&mut ctx, &pool_key).await.paused);
+
+    // Every action above was chained into the pool's admin log
+    let log: AdminLog = fetch(&mut ctx, &pda::admin_log(&pool_key)).await;
+    assert_eq!(log.pool, pool_key);
+    assert_eq!(log.entries, ixs.len() as u64 + 1);
+    assert_ne!(log.head, [0; 32]);
+
+    // Only the pool admin may run admin instructions
+    let stranger = Keypair::new();
+    assert!(send(&mut ctx, &[murkl::pause_pool(&stranger.pubkey(), &mint)], &[&stranger]).await.is_err());
+    assert_eq!(fetch::<AdminLog>(&mut ctx, &pda::admin_log(&pool_key)).await.entries, log.entries);
+}
@@ -0,0 +1,560 @@
+//! Canonical Murkl proof
+//!
+//! [`SerializedProof`] holds every field of the one proof encoding
+//! stark-verifier accepts. The WASM prover and the CLI build one and
+//! [`encode`](SerializedProof::encode) it; [`decode`](SerializedProof::decode)
+//! reads the bytes back and rejects whatever stark-verifier's parser would.
+//!
+//! ```text
+//! params   magic "MRKL" | version u8 | hash_kind u8 | log_trace_size u8
+//!          | log_blowup u8 | log_folding_factor u8 | query_count u8
+//! header   trace_commitment hash | composition_commitment hash
+//!          | trace_columns u8 | trace_oods qm31 × columns
+//!          | trace_oods_next qm31 × columns | composition_oods qm31
+//!          | fri_layer_count u8 | fri_layer_commitment hash × layers
+//!          | final_poly_len u16 (top bit: evaluations) | final_poly qm31 × len
+//! query    index u32 | trace_row m31 × columns | trace_path
+//!          | composition_leaf hash | composition_path
+//!          | (fri_siblings qm31 × 4 | fri_path) × layers
+//! path     depth u8 | hash × depth
+//! ```
+//!
+//! Integers are little-endian; a QM31 is its four M31 coordinates in order.
+//! `trace_oods` holds every trace column at the OODS point and
+//! `trace_oods_next` every column one trace row on, for constraints that
+//! span two rows.
+//! murkl-prover's `spec` module lists the same layout field by field for
+//! test vectors. stark-verifier keeps a zero-copy parser of its own, since a
+//! decoded proof would not fit the on-chain heap; its tests hold it to
+//! [`decode`](SerializedProof::decode).
+//!
+//! [`SecurityLevel`] names the header parameters (queries, blowup, hash)
+//! provers emit and verifiers or pools require.
+//!
+//! Proofs from upstream stwo tooling share the magic and carry
+//! [`STWO_PROOF_VERSION`] in the version byte; [`StwoProof`] reads them and
+//! [`AnyProof::decode`] picks the encoding by that byte.
+
+#![no_std]
+
+extern crate alloc;
+
+mod security;
+mod stwo;
+
+pub use security::SecurityLevel;
+pub use stwo::{
+    StwoDecommitment, StwoFriLayer, StwoFriProof, StwoPcsConfig, StwoProof, STWO_HASHER_BLAKE2S, STWO_PROOF_VERSION,
+};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// First four bytes of every proof
+pub const PROOF_MAGIC: [u8; 4] = *b"MRKL";
+/// Format version [`SerializedProof`] encodes
+pub const PROOF_VERSION: u8 = 2;
+/// Highest hash kind byte: 0 keccak, 1 Poseidon2 over M31, 2 Poseidon over BN254
+pub const MAX_HASH_KIND: u8 = 2;
+/// Set in `final_poly_len` when the last FRI layer is sent as evaluations
+pub const FINAL_EVALUATIONS_FLAG: u16 = 0x8000;
+/// Prefix of a trace tree leaf: every column of a row is hashed into one
+/// leaf as `H(prefix || values as u32 LE)`, in the order `trace_row` sends them
+pub const TRACE_ROW_DOMAIN: &[u8] = b"murkl_trace_row_v1";
+/// Prefix of a FRI layer leaf: each fold-by-4 group is hashed into one leaf
+/// as `H(prefix || siblings as QM31 LE)`, so every value the fold reads is
+/// committed
+pub const FRI_GROUP_DOMAIN: &[u8] = b"murkl_fri_group_v1";
+
+// Bounds stark-verifier enforces while parsing
+/// Most trace columns per row
+pub const MAX_TRACE_COLUMNS: usize = 32;
+/// Most FRI layers
+pub const MAX_FRI_LAYERS: usize = 7;
+/// Deepest Merkle path, and the largest log2 evaluation domain
+pub const MAX_MERKLE_DEPTH: usize = 14;
+/// Most final polynomial coefficients
+pub const MAX_FINAL_POLY_LEN: usize = 16;
+/// Most last-layer evaluations
+pub const MAX_FINAL_LAYER_EVALS: usize = 64;
+/// Most query openings
+pub const MAX_QUERIES: usize = 32;
+/// Largest proof a verifier buffer accepts
+pub const MAX_PROOF_SIZE: usize = 88 * 1024;
+
+/// 2^31 - 1; trace values must be below it
+const M31_MODULUS: u32 = (1 << 31) - 1;
+
+/// 32-byte Merkle root, leaf or path node
+pub type Digest = [u8; 32];
+/// A QM31 as its four M31 coordinates, in wire order
+pub type Qm31 = [u32; 4];
+
+/// Every field of a proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct SerializedProof {
+    /// Hash behind the Merkle trees and the channel (see [`MAX_HASH_KIND`])
+    pub hash_kind: u8,
+    pub log_trace_size: u8,
+    /// Queries index a domain of 2^(log_trace_size + log_blowup)
+    pub log_blowup: u8,
+    pub log_folding_factor: u8,
+    pub trace_commitment: Digest,
+    pub composition_commitment: Digest,
+    /// Width of every opened trace row
+    pub trace_columns: u8,
+    /// Each column at the OODS point
+    pub trace_oods: Vec<Qm31>,
+    /// Each column at the point one trace row on
+    pub trace_oods_next: Vec<Qm31>,
+    pub composition_oods: Qm31,
+    pub fri_layer_commitments: Vec<Digest>,
+    pub final_layer: FinalLayer,
+    pub queries: Vec<QueryOpening>,
+}
+
+/// How the last FRI layer is sent
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub enum FinalLayer {
+    /// Final polynomial coefficients, lowest degree first
+    Coefficients(Vec<Qm31>),
+    /// The whole last layer's values, in layer order
+    Evaluations(Vec<Qm31>),
+}
+
+/// One query's openings
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct QueryOpening {
+    /// Position in the evaluation domain
+    pub index: u32,
+    /// One canonical M31 per trace column
+    pub trace_row: Vec<u32>,
+    pub trace_path: Vec<Digest>,
+    pub composition_leaf: Digest,
+    pub composition_path: Vec<Digest>,
+    /// One per FRI layer
+    pub fri: Vec<FriOpening>,
+}
+
+/// A query's coset in one FRI layer
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct FriOpening {
+    pub siblings: [Qm31; 4],
+    pub path: Vec<Digest>,
+}
+
+/// Why bytes are not a proof, or a proof can't be encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// The bytes end inside this field
+    Truncated(&'static str),
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownHashKind(u8),
+    /// A count or size outside what stark-verifier parses
+    OutOfBounds { field: &'static str, value: usize },
+    /// A query's trace row holds a value that is not below the M31 modulus
+    NonCanonical { query: usize },
+    /// A query whose row width or FRI openings disagree with the header
+    Inconsistent { query: usize, field: &'static str },
+    /// Bytes left after the last query
+    TrailingBytes(usize),
+    /// A field element of this kind in a stwo proof is not canonical
+    NonCanonicalField(&'static str),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(field) => write!(f, "proof ends inside {}", field),
+            Self::BadMagic => write!(f, "proof does not start with MRKL"),
+            Self::UnsupportedVersion(v) => write!(f, "format version {}, expected {}", v, PROOF_VERSION),
+            Self::UnknownHashKind(k) => write!(f, "unknown hash kind {}", k),
+            Self::OutOfBounds { field, value } => write!(f, "{} of {} is outside the verifier's bounds", field, value),
+            Self::NonCanonical { query } => write!(f, "query {} opens a non-canonical M31", query),
+            Self::Inconsistent { query, field } => write!(f, "query {}: {} does not match the header", query, field),
+            Self::TrailingBytes(n) => write!(f, "{} bytes after the last query", n),
+            Self::NonCanonicalField(kind) => write!(f, "stwo proof holds a non-canonical {}", kind),
+        }
+    }
+}
+
+fn bound(field: &'static str, value: usize, min: usize, max: usize) -> Result<(), FormatError> {
+    if value < min || value > max {
+        return Err(FormatError::OutOfBounds { field, value });
+    }
+    Ok(())
+}
+
+impl SerializedProof {
+    /// log2 of the evaluation domain
+    pub fn log_domain_size(&self) -> u32 {
+        self.log_trace_size as u32 + self.log_blowup as u32
+    }
+
+    /// Check the bounds and consistency [`decode`](Self::decode) requires
+    pub fn check(&self) -> Result<(), FormatError> {
+        if self.hash_kind > MAX_HASH_KIND {
+            return Err(FormatError::UnknownHashKind(self.hash_kind));
+        }
+        let log_domain = self.log_domain_size() as usize;
+        bound("log_domain_size", log_domain, 1, MAX_MERKLE_DEPTH)?;
+        bound("log_folding_factor", self.log_folding_factor as usize, 1, log_domain)?;
+        bound("trace_columns", self.trace_columns as usize, 1, MAX_TRACE_COLUMNS)?;
+        let columns = self.trace_columns as usize;
+        bound("trace_oods", self.trace_oods.len(), columns, columns)?;
+        bound("trace_oods_next", self.trace_oods_next.len(), columns, columns)?;
+        bound("fri_layer_count", self.fri_layer_commitments.len(), 0, MAX_FRI_LAYERS)?;
+        match &self.final_layer {
+            FinalLayer::Coefficients(c) => bound("final_poly_len", c.len(), 0, MAX_FINAL_POLY_LEN)?,
+            FinalLayer::Evaluations(e) => bound("final_layer_evals", e.len(), 1, MAX_FINAL_LAYER_EVALS)?,
+        }
+        bound("query_count", self.queries.len(), 0, MAX_QUERIES)?;
+
+        for (i, query) in self.queries.iter().enumerate() {
+            if query.trace_row.len() != self.trace_columns as usize {
+                return Err(FormatError::Inconsistent { query: i, field: "trace row width" });
+            }
+            if query.fri.len() != self.fri_layer_commitments.len() {
+                return Err(FormatError::Inconsistent { query: i, field: "FRI openings" });
+            }
+            if query.trace_row.iter().any(|&v| v >= M31_MODULUS) {
+                return Err(FormatError::NonCanonical { query: i });
+            }
+            let paths = [&query.trace_path, &query.composition_path];
+            for path in paths.into_iter().chain(query.fri.iter().map(|layer| &layer.path)) {
+                bound("path depth", path.len(), 0, MAX_MERKLE_DEPTH)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wire bytes
+    ///
+    /// Counts are written at their wire width without checking; run
+    /// [`check`](Self::check) first on a proof that may be out of bounds.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        out.extend_from_slice(&PROOF_MAGIC);
+        out.extend_from_slice(&[
+            PROOF_VERSION,
+            self.hash_kind,
+            self.log_trace_size,
+            self.log_blowup,
+            self.log_folding_factor,
+            self.queries.len() as u8,
+        ]);
+        out.extend_from_slice(&self.trace_commitment);
+        out.extend_from_slice(&self.composition_commitment);
+        out.push(self.trace_columns);
+        for value in self.trace_oods.iter().chain(&self.trace_oods_next) {
+            put_qm31(&mut out, value);
+        }
+        put_qm31(&mut out, &self.composition_oods);
+
+        out.push(self.fri_layer_commitments.len() as u8);
+        for root in &self.fri_layer_commitments {
+            out.extend_from_slice(root);
+        }
+        let (flag, values) = match &self.final_layer {
+            FinalLayer::Coefficients(c) => (0, c),
+            FinalLayer::Evaluations(e) => (FINAL_EVALUATIONS_FLAG, e),
+        };
+        out.extend_from_slice(&(flag | values.len() as u16).to_le_bytes());
+        for value in values {
+            put_qm31(&mut out, value);
+        }
+
+        for query in &self.queries {
+            out.extend_from_slice(&query.index.to_le_bytes());
+            for value in &query.trace_row {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            put_path(&mut out, &query.trace_path);
+            out.extend_from_slice(&query.composition_leaf);
+            put_path(&mut out, &query.composition_path);
+            for layer in &query.fri {
+                for sibling in &layer.siblings {
+                    put_qm31(&mut out, sibling);
+                }
+                put_path(&mut out, &layer.path);
+            }
+        }
+        out
+    }
+
+    /// Size of [`encode`](Self::encode)'s output
+    pub fn encoded_len(&self) -> usize {
+        let path = |p: &Vec<Digest>| 1 + 32 * p.len();
+        let final_values = match &self.final_layer {
+            FinalLayer::Coefficients(v) | FinalLayer::Evaluations(v) => v.len(),
+        };
+        let queries: usize = self
+            .queries
+            .iter()
+            .map(|q| {
+                let fri: usize = q.fri.iter().map(|layer| 64 + path(&layer.path)).sum();
+                4 + 4 * q.trace_row.len() + path(&q.trace_path) + 32 + path(&q.composition_path) + fri
+            })
+            .sum();
+        let oods = 16 * (self.trace_oods.len() + self.trace_oods_next.len() + 1);
+        10 + 2 * 32 + 1 + oods + 1 + 32 * self.fri_layer_commitments.len() + 2 + 16 * final_values + queries
+    }
+
+    /// Read a proof, rejecting anything stark-verifier's parser rejects
+    /// (bad magic or version, counts over the bounds, non-canonical trace
+    /// values, truncation) and bytes after the last query
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        bound("proof size", bytes.len(), 0, MAX_PROOF_SIZE)?;
+        let mut r = Reader { bytes, at: 0 };
+
+        if r.take(4, "magic")? != PROOF_MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+        let version = r.u8("version")?;
+        if version != PROOF_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+        let hash_kind = r.u8("hash_kind")?;
+        let log_trace_size = r.u8("log_trace_size")?;
+        let log_blowup = r.u8("log_blowup")?;
+        let log_folding_factor = r.u8("log_folding_factor")?;
+        let query_count = r.u8("query_count")? as usize;
+        let trace_commitment = r.digest("trace_commitment")?;
+        let composition_commitment = r.digest("composition_commitment")?;
+        let trace_columns = r.u8("trace_columns")?;
+        let trace_oods = (0..trace_columns)
+            .map(|_| r.qm31("trace_oods"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let trace_oods_next = (0..trace_columns)
+            .map(|_| r.qm31("trace_oods_next"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let composition_oods = r.qm31("composition_oods")?;
+
+        let layers = r.u8("fri_layer_count")? as usize;
+        let fri_layer_commitments = (0..layers)
+            .map(|_| r.digest("fri_layer_commitment"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let final_len = u16::from_le_bytes([r.u8("final_poly_len")?, r.u8("final_poly_len")?]);
+        let values = (0..(final_len & !FINAL_EVALUATIONS_FLAG) as usize)
+            .map(|_| r.qm31("final_poly"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let final_layer = if final_len & FINAL_EVALUATIONS_FLAG != 0 {
+            FinalLayer::Evaluations(values)
+        } else {
+            FinalLayer::Coefficients(values)
+        };
+
+        let mut queries = Vec::with_capacity(query_count);
+        for _ in 0..query_count {
+            let index = r.u32("query index")?;
+            let trace_row = (0..trace_columns)
+                .map(|_| r.u32("trace_row"))
+                .collect::<Result<Vec<_>, _>>()?;
+            let trace_path = r.path("trace_path")?;
+            let composition_leaf = r.digest("composition_leaf")?;
+            let composition_path = r.path("composition_path")?;
+            let mut fri = Vec::with_capacity(layers);
+            for _ in 0..layers {
+                let siblings = [r.qm31("fri_siblings")?, r.qm31("fri_siblings")?, r.qm31("fri_siblings")?, r.qm31("fri_siblings")?];
+                fri.push(FriOpening { siblings, path: r.path("fri_path")? });
+            }
+            queries.push(QueryOpening { index, trace_row, trace_path, composition_leaf, composition_path, fri });
+        }
+        if r.at != bytes.len() {
+            return Err(FormatError::TrailingBytes(bytes.len() - r.at));
+        }
+
+        let proof = Self {
+            hash_kind,
+            log_trace_size,
+            log_blowup,
+            log_folding_factor,
+            trace_commitment,
+            composition_commitment,
+            trace_columns,
+            trace_oods,
+            trace_oods_next,
+            composition_oods,
+            fri_layer_commitments,
+            final_layer,
+            queries,
+        };
+        proof.check()?;
+        Ok(proof)
+    }
+}
+
+/// A proof in either encoding, told apart by its version byte
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnyProof {
+    /// [`PROOF_VERSION`], the encoding stark-verifier verifies
+    Murkl(SerializedProof),
+    /// [`STWO_PROOF_VERSION`]
+    Stwo(StwoProof),
+}
+
+impl AnyProof {
+    /// Decode by the version byte after the magic; any other version is
+    /// [`FormatError::UnsupportedVersion`]
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        match bytes.get(PROOF_MAGIC.len()) {
+            Some(&STWO_PROOF_VERSION) => StwoProof::decode(bytes).map(Self::Stwo),
+            _ => SerializedProof::decode(bytes).map(Self::Murkl),
+        }
+    }
+}
+
+fn put_qm31(out: &mut Vec<u8>, value: &Qm31) {
+    for limb in value {
+        out.extend_from_slice(&limb.to_le_bytes());
+    }
+}
+
+fn put_path(out: &mut Vec<u8>, path: &[Digest]) {
+    out.push(path.len() as u8);
+    for node in path {
+        out.extend_from_slice(node);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, field: &'static str) -> Result<&'a [u8], FormatError> {
+        let bytes = self.bytes.get(self.at..self.at + len).ok_or(FormatError::Truncated(field))?;
+        self.at += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self, field: &'static str) -> Result<u8, FormatError> {
+        Ok(self.take(1, field)?[0])
+    }
+
+    fn u32(&mut self, field: &'static str) -> Result<u32, FormatError> {
+        let b = self.take(4, field)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn digest(&mut self, field: &'static str) -> Result<Digest, FormatError> {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(self.take(32, field)?);
+        Ok(digest)
+    }
+
+    fn qm31(&mut self, field: &'static str) -> Result<Qm31, FormatError> {
+        Ok([self.u32(field)?, self.u32(field)?, self.u32(field)?, self.u32(field)?])
+    }
+
+    fn path(&mut self, field: &'static str) -> Result<Vec<Digest>, FormatError> {
+        let depth = self.u8(field)? as usize;
+        bound(field, depth, 0, MAX_MERKLE_DEPTH)?;
+        (0..depth).map(|_| self.digest(field)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// 2 columns, 1 FRI layer, 2 queries
+    pub(crate) fn sample() -> SerializedProof {
+        let query = |index: u32| QueryOpening {
+            index,
+            trace_row: vec![index, M31_MODULUS - 1],
+            trace_path: vec![[7; 32], [8; 32]],
+            composition_leaf: [9; 32],
+            composition_path: vec![],
+            fri: vec![FriOpening { siblings: [[1, 2, 3, 4]; 4], path: vec![[10; 32]] }],
+        };
+        SerializedProof {
+            hash_kind: 1,
+            log_trace_size: 10,
+            log_blowup: 4,
+            log_folding_factor: 2,
+            trace_commitment: [1; 32],
+            composition_commitment: [2; 32],
+            trace_columns: 2,
+            trace_oods: vec![[3, 3, 3, 3]; 2],
+            trace_oods_next: vec![[3, 3, 3, 4]; 2],
+            composition_oods: [4, 4, 4, 4],
+            fri_layer_commitments: vec![[5; 32]],
+            final_layer: FinalLayer::Coefficients(vec![[6, 0, 0, 0]]),
+            queries: vec![query(0), query(16383)],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let proof = sample();
+        let bytes = proof.encode();
+        assert_eq!(bytes.len(), proof.encoded_len());
+        assert_eq!(&bytes[..4], b"MRKL");
+        assert_eq!(SerializedProof::decode(&bytes).unwrap(), proof);
+
+        let evaluations = SerializedProof { final_layer: FinalLayer::Evaluations(vec![[1, 2, 3, 4]; 16]), ..proof };
+        let bytes = evaluations.encode();
+        assert_eq!(bytes.len(), evaluations.encoded_len());
+        assert_eq!(SerializedProof::decode(&bytes).unwrap(), evaluations);
+    }
+
+    #[test]
+    fn test_decode_rejects() {
+        let bytes = sample().encode();
+        let decode = |edit: &dyn Fn(&mut Vec<u8>)| {
+            let mut b = bytes.clone();
+            edit(&mut b);
+            SerializedProof::decode(&b).unwrap_err()
+        };
+        assert_eq!(decode(&|b| b[0] = b'X'), FormatError::BadMagic);
+        assert_eq!(decode(&|b| b[4] = 1), FormatError::UnsupportedVersion(1));
+        assert_eq!(decode(&|b| b[5] = 3), FormatError::UnknownHashKind(3));
+        assert_eq!(decode(&|b| b[7] = 5), FormatError::OutOfBounds { field: "log_domain_size", value: 15 });
+        assert_eq!(decode(&|b| b[8] = 0), FormatError::OutOfBounds { field: "log_folding_factor", value: 0 });
+        assert_eq!(decode(&|b| b.push(0)), FormatError::TrailingBytes(1));
+        assert_eq!(decode(&|b| { b.pop(); }), FormatError::Truncated("fri_path"));
+        // Second trace value of query 0 raised to the modulus
+        let row = 10 + 64 + 1 + 4 * 16 + 16 + 1 + 32 + 2 + 16 + 4 + 4;
+        assert_eq!(
+            decode(&|b| b[row..row + 4].copy_from_slice(&M31_MODULUS.to_le_bytes())),
+            FormatError::NonCanonical { query: 0 }
+        );
+    }
+
+    #[test]
+    fn test_check_matches_header() {
+        let mut proof = sample();
+        proof.queries[1].trace_row.pop();
+        assert_eq!(proof.check(), Err(FormatError::Inconsistent { query: 1, field: "trace row width" }));
+
+        let mut proof = sample();
+        proof.queries[0].fri.clear();
+        assert_eq!(proof.check(), Err(FormatError::Inconsistent { query: 0, field: "FRI openings" }));
+
+        let mut proof = sample();
+        proof.trace_oods_next.pop();
+        assert_eq!(proof.check(), Err(FormatError::OutOfBounds { field: "trace_oods_next", value: 1 }));
+
+        let empty = SerializedProof { final_layer: FinalLayer::Evaluations(vec![]), ..sample() };
+        assert_eq!(empty.check(), Err(FormatError::OutOfBounds { field: "final_layer_evals", value: 0 }));
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_round_trip() {
+        let proof = sample();
+        let bytes = borsh::to_vec(&proof).unwrap();
+        assert_eq!(SerializedProof::try_from_slice(&bytes).unwrap(), proof);
+    }
+}
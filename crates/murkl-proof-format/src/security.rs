@@ -0,0 +1,176 @@
+//! Named security levels
+//!
+//! A [`SecurityLevel`] names the parameters a proof is generated and
+//! checked under. Its nominal soundness is `num_queries × log_blowup` bits:
+//! each query catches a codeword far from low degree with probability about
+//! 1 - 2^-log_blowup. Everything that counts is in the proof header, so a
+//! verifier can tell which level a proof meets. Proof of work comes on top
+//! as margin and is not part of the level: the on-chain verifier does not
+//! see the nonce.
+//!
+//! The hash counts as well, since a collision forges an opening. Poseidon2
+//! digests are eight M31 limbs (248 bits) and Poseidon BN254 digests one
+//! field element, both short of 128-bit collision resistance, so
+//! [`SecurityLevel::High128`] takes keccak proofs only.
+
+use core::fmt;
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Preset proof parameters, weakest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[repr(u8)]
+pub enum SecurityLevel {
+    /// 4 queries: 16 bits, for tests and demos
+    Demo = 0,
+    /// 24 queries and 8 bits of grinding: 96 bits
+    Standard96 = 1,
+    /// 32 queries and 16 bits of grinding over keccak: 128 bits
+    High128 = 2,
+}
+
+impl SecurityLevel {
+    /// Every level, weakest first
+    pub const ALL: [Self; 3] = [Self::Demo, Self::Standard96, Self::High128];
+
+    /// Parse a level byte
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Demo),
+            1 => Some(Self::Standard96),
+            2 => Some(Self::High128),
+            _ => None,
+        }
+    }
+
+    /// Parse a [`name`](Self::name)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Lowercase name, as the CLI takes it
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Demo => "demo",
+            Self::Standard96 => "standard96",
+            Self::High128 => "high128",
+        }
+    }
+
+    /// Query openings a proof carries
+    pub const fn num_queries(self) -> usize {
+        match self {
+            Self::Demo => 4,
+            Self::Standard96 => 24,
+            Self::High128 => 32,
+        }
+    }
+
+    /// log2 of the blowup factor
+    pub const fn log_blowup(self) -> u32 {
+        4
+    }
+
+    /// Proof-of-work bits provers grind before drawing queries
+    pub const fn pow_bits(self) -> u32 {
+        match self {
+            Self::Demo => 0,
+            Self::Standard96 => 8,
+            Self::High128 => 16,
+        }
+    }
+
+    /// Hash kind byte provers use
+    pub const fn hash_kind(self) -> u8 {
+        0
+    }
+
+    /// Hash kinds a proof may use, bit `k` for hash kind byte `k`
+    pub const fn hash_kinds(self) -> u8 {
+        match self {
+            Self::Demo | Self::Standard96 => 0b111,
+            Self::High128 => 0b001,
+        }
+    }
+
+    /// Nominal soundness in bits
+    pub const fn bits(self) -> u32 {
+        self.num_queries() as u32 * self.log_blowup()
+    }
+
+    /// Whether a proof with these header parameters meets this level
+    pub const fn admits(self, num_queries: usize, log_blowup: u32, hash_kind: u8) -> bool {
+        num_queries >= self.num_queries()
+            && log_blowup >= self.log_blowup()
+            && hash_kind < 8
+            && self.hash_kinds() & (1 << hash_kind) != 0
+    }
+
+    /// Highest level a proof with these header parameters meets, `None`
+    /// if below [`SecurityLevel::Demo`]
+    pub fn of(num_queries: usize, log_blowup: u32, hash_kind: u8) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|level| level.admits(num_queries, log_blowup, hash_kind))
+    }
+
+    /// Level every proof meets that a verifier requiring `min_queries`
+    /// queries at blowup 2^`log_blowup` over any of `hash_kinds` accepts:
+    /// the lowest over its hash kinds, `None` if it accepts none
+    pub fn guaranteed(min_queries: usize, log_blowup: u32, hash_kinds: u8) -> Option<Self> {
+        (0..8)
+            .filter(|kind| hash_kinds & (1 << kind) != 0)
+            .map(|kind| Self::of(min_queries, log_blowup, kind))
+            .min()
+            .flatten()
+    }
+}
+
+impl fmt::Display for SecurityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} bits)", self.name(), self.bits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_QUERIES;
+
+    #[test]
+    fn test_presets_meet_their_level() {
+        for level in SecurityLevel::ALL {
+            assert_eq!(SecurityLevel::from_byte(level as u8), Some(level));
+            assert_eq!(SecurityLevel::from_name(level.name()), Some(level));
+            assert!(level.num_queries() <= MAX_QUERIES);
+            assert_eq!(
+                SecurityLevel::of(level.num_queries(), level.log_blowup(), level.hash_kind()),
+                Some(level)
+            );
+        }
+        assert_eq!(SecurityLevel::Standard96.bits(), 96);
+        assert_eq!(SecurityLevel::High128.bits(), 128);
+        assert_eq!(SecurityLevel::from_name("High128"), Some(SecurityLevel::High128));
+        assert_eq!(SecurityLevel::from_name("insecure"), None);
+    }
+
+    #[test]
+    fn test_weaker_parameters_drop_a_level() {
+        let high = SecurityLevel::High128;
+        assert_eq!(SecurityLevel::of(31, 4, 0), Some(SecurityLevel::Standard96));
+        assert_eq!(SecurityLevel::of(32, 3, 0), None);
+        // Poseidon2 digests are too short for 128 bits
+        assert_eq!(SecurityLevel::of(high.num_queries(), 4, 1), Some(SecurityLevel::Standard96));
+        assert_eq!(SecurityLevel::of(3, 4, 0), None);
+        assert_eq!(SecurityLevel::of(32, 4, 9), None);
+
+        // A verifier is as strong as the weakest proof it takes
+        assert_eq!(SecurityLevel::guaranteed(32, 4, 0b001), Some(SecurityLevel::High128));
+        assert_eq!(SecurityLevel::guaranteed(32, 4, 0b011), Some(SecurityLevel::Standard96));
+        assert_eq!(SecurityLevel::guaranteed(4, 4, 0b011), Some(SecurityLevel::Demo));
+        assert_eq!(SecurityLevel::guaranteed(32, 4, 0), None);
+    }
+}
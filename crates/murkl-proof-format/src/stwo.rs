@@ -0,0 +1,360 @@
+//! STWO proof encoding
+//!
+//! Proofs from upstream stwo tooling are its `CommitmentSchemeProof` over
+//! Blake2s Merkle trees, serialized with bincode 1 defaults (little-endian,
+//! fixed-width integers, u64 lengths), behind a murkl prefix whose version
+//! byte is [`STWO_PROOF_VERSION`]:
+//!
+//! ```text
+//! prefix          magic "MRKL" | version u8 | hasher u8 (0 = Blake2s)
+//! proof           config | commitments | sampled_values | decommitments
+//!                 | queried_values | proof_of_work u64 | fri_proof
+//! config          pow_bits u32 | log_blowup_factor u32
+//!                 | log_last_layer_degree_bound u32 | n_queries u64
+//! commitments     vec<hash>                 one root per tree
+//! sampled_values  vec<vec<vec<qm31>>>       tree, column, sample
+//! decommitments   vec<decommitment>         one per tree
+//! queried_values  vec<vec<vec<m31>>>        tree, column, query
+//! decommitment    hash_witness vec<hash> | column_witness vec<m31>
+//! fri_proof       first_layer fri_layer | inner_layers vec<fri_layer>
+//!                 | last_layer_poly vec<qm31> | last_layer_log_size u32
+//! fri_layer       fri_witness vec<qm31> | decommitment | commitment hash
+//! vec<T>          len u64 | T × len
+//! ```
+//!
+//! stark-verifier does not verify these: its channel, FRI folding and AIR
+//! are murkl's, so it rejects the version with `UnsupportedProofVersion`.
+//! [`StwoProof::decode`] is for tooling that routes or inspects proofs of
+//! either kind, via [`AnyProof`](crate::AnyProof).
+
+use alloc::vec::Vec;
+
+use crate::{bound, put_qm31, Digest, FormatError, Qm31, Reader, M31_MODULUS, PROOF_MAGIC};
+
+/// Format version byte of a [`StwoProof`]. The high bit keeps it apart from
+/// murkl's own versions.
+pub const STWO_PROOF_VERSION: u8 = 0x80;
+/// The only hasher byte: stwo's Blake2s Merkle channel
+pub const STWO_HASHER_BLAKE2S: u8 = 0;
+/// Largest `last_layer_log_size` read, so the coefficient count stays sane
+const MAX_LAST_LAYER_LOG_SIZE: u32 = 20;
+
+/// stwo's `CommitmentSchemeProof`, field for field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StwoProof {
+    /// Merkle hasher, [`STWO_HASHER_BLAKE2S`]
+    pub hasher: u8,
+    pub config: StwoPcsConfig,
+    /// One root per committed tree
+    pub commitments: Vec<Digest>,
+    /// Per tree, per column, each sample at the OODS point(s)
+    pub sampled_values: Vec<Vec<Vec<Qm31>>>,
+    /// One per tree
+    pub decommitments: Vec<StwoDecommitment>,
+    /// Per tree, per column, the value at each query
+    pub queried_values: Vec<Vec<Vec<u32>>>,
+    pub proof_of_work: u64,
+    pub fri_proof: StwoFriProof,
+}
+
+/// stwo's `PcsConfig` with its `FriConfig` inlined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StwoPcsConfig {
+    pub pow_bits: u32,
+    pub log_blowup_factor: u32,
+    pub log_last_layer_degree_bound: u32,
+    pub n_queries: u64,
+}
+
+/// stwo's `MerkleDecommitment`: the hashes and values a batch of queries
+/// needs beyond the opened leaves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StwoDecommitment {
+    pub hash_witness: Vec<Digest>,
+    pub column_witness: Vec<u32>,
+}
+
+/// stwo's `FriLayerProof`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StwoFriLayer {
+    pub fri_witness: Vec<Qm31>,
+    pub decommitment: StwoDecommitment,
+    pub commitment: Digest,
+}
+
+/// stwo's `FriProof`; `last_layer_poly` is its `LinePoly`, coefficients
+/// lowest degree first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StwoFriProof {
+    pub first_layer: StwoFriLayer,
+    pub inner_layers: Vec<StwoFriLayer>,
+    pub last_layer_poly: Vec<Qm31>,
+    pub last_layer_log_size: u32,
+}
+
+impl StwoProof {
+    /// Check what [`decode`](Self::decode) requires beyond the layout: a
+    /// known hasher, one sample set, decommitment and query set per tree, a
+    /// last layer of 2^`last_layer_log_size` coefficients and canonical
+    /// field elements throughout
+    pub fn check(&self) -> Result<(), FormatError> {
+        if self.hasher != STWO_HASHER_BLAKE2S {
+            return Err(FormatError::UnknownHashKind(self.hasher));
+        }
+        let trees = self.commitments.len();
+        bound("sampled_values", self.sampled_values.len(), trees, trees)?;
+        bound("decommitments", self.decommitments.len(), trees, trees)?;
+        bound("queried_values", self.queried_values.len(), trees, trees)?;
+        let fri = &self.fri_proof;
+        bound("last_layer_log_size", fri.last_layer_log_size as usize, 0, MAX_LAST_LAYER_LOG_SIZE as usize)?;
+        let coefficients = 1 << fri.last_layer_log_size;
+        bound("last_layer_poly", fri.last_layer_poly.len(), coefficients, coefficients)?;
+
+        let m31s = self.queried_values.iter().flatten().flatten();
+        let witnesses = self.decommitments.iter().chain(self.fri_layers().map(|layer| &layer.decommitment));
+        if m31s.chain(witnesses.flat_map(|d| &d.column_witness)).any(|&v| v >= M31_MODULUS) {
+            return Err(FormatError::NonCanonicalField("m31"));
+        }
+        let qm31s = self.sampled_values.iter().flatten().flatten();
+        let qm31s = qm31s.chain(self.fri_layers().flat_map(|layer| &layer.fri_witness)).chain(&fri.last_layer_poly);
+        if qm31s.flatten().any(|&v| v >= M31_MODULUS) {
+            return Err(FormatError::NonCanonicalField("qm31"));
+        }
+        Ok(())
+    }
+
+    /// First FRI layer, then the inner ones
+    fn fri_layers(&self) -> impl Iterator<Item = &StwoFriLayer> {
+        core::iter::once(&self.fri_proof.first_layer).chain(&self.fri_proof.inner_layers)
+    }
+
+    /// Prefix and bincode payload
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = PROOF_MAGIC.to_vec();
+        out.extend_from_slice(&[STWO_PROOF_VERSION, self.hasher]);
+        let config = &self.config;
+        for word in [config.pow_bits, config.log_blowup_factor, config.log_last_layer_degree_bound] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&config.n_queries.to_le_bytes());
+        put_vec(&mut out, &self.commitments, |out, root| out.extend_from_slice(root));
+        put_vec(&mut out, &self.sampled_values, |out, tree| {
+            put_vec(out, tree, |out, column| put_vec(out, column, put_qm31))
+        });
+        put_vec(&mut out, &self.decommitments, put_decommitment);
+        put_vec(&mut out, &self.queried_values, |out, tree| {
+            put_vec(out, tree, |out, column| put_vec(out, column, put_m31))
+        });
+        out.extend_from_slice(&self.proof_of_work.to_le_bytes());
+        let fri = &self.fri_proof;
+        put_fri_layer(&mut out, &fri.first_layer);
+        put_vec(&mut out, &fri.inner_layers, put_fri_layer);
+        put_vec(&mut out, &fri.last_layer_poly, put_qm31);
+        out.extend_from_slice(&fri.last_layer_log_size.to_le_bytes());
+        out
+    }
+
+    /// Read a prefixed stwo proof, rejecting a wrong magic or version,
+    /// truncation, lengths the remaining bytes cannot hold, bytes after the
+    /// proof and whatever [`check`](Self::check) rejects
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        let mut r = Reader { bytes, at: 0 };
+        if r.take(4, "magic")? != PROOF_MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+        let version = r.u8("version")?;
+        if version != STWO_PROOF_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+        let hasher = r.u8("hasher")?;
+        let config = StwoPcsConfig {
+            pow_bits: r.u32("pow_bits")?,
+            log_blowup_factor: r.u32("log_blowup_factor")?,
+            log_last_layer_degree_bound: r.u32("log_last_layer_degree_bound")?,
+            n_queries: r.u64("n_queries")?,
+        };
+        let commitments = r.vec("commitments", 32, |r| r.digest("commitments"))?;
+        let sampled_values = r.vec("sampled_values", 8, |r| {
+            r.vec("sampled_values", 8, |r| r.vec("sampled_values", 16, |r| r.qm31("sampled_values")))
+        })?;
+        let decommitments = r.vec("decommitments", 16, read_decommitment)?;
+        let queried_values = r.vec("queried_values", 8, |r| {
+            r.vec("queried_values", 8, |r| r.vec("queried_values", 4, |r| r.u32("queried_values")))
+        })?;
+        let proof_of_work = r.u64("proof_of_work")?;
+        let first_layer = read_fri_layer(&mut r)?;
+        let inner_layers = r.vec("inner_layers", 56, read_fri_layer)?;
+        let last_layer_poly = r.vec("last_layer_poly", 16, |r| r.qm31("last_layer_poly"))?;
+        let last_layer_log_size = r.u32("last_layer_log_size")?;
+        if r.at != bytes.len() {
+            return Err(FormatError::TrailingBytes(bytes.len() - r.at));
+        }
+
+        let proof = Self {
+            hasher,
+            config,
+            commitments,
+            sampled_values,
+            decommitments,
+            queried_values,
+            proof_of_work,
+            fri_proof: StwoFriProof { first_layer, inner_layers, last_layer_poly, last_layer_log_size },
+        };
+        proof.check()?;
+        Ok(proof)
+    }
+}
+
+fn put_vec<T>(out: &mut Vec<u8>, items: &[T], mut put: impl FnMut(&mut Vec<u8>, &T)) {
+    out.extend_from_slice(&(items.len() as u64).to_le_bytes());
+    for item in items {
+        put(out, item);
+    }
+}
+
+fn put_m31(out: &mut Vec<u8>, value: &u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_decommitment(out: &mut Vec<u8>, decommitment: &StwoDecommitment) {
+    put_vec(out, &decommitment.hash_witness, |out, hash| out.extend_from_slice(hash));
+    put_vec(out, &decommitment.column_witness, put_m31);
+}
+
+fn put_fri_layer(out: &mut Vec<u8>, layer: &StwoFriLayer) {
+    put_vec(out, &layer.fri_witness, put_qm31);
+    put_decommitment(out, &layer.decommitment);
+    out.extend_from_slice(&layer.commitment);
+}
+
+fn read_decommitment(r: &mut Reader) -> Result<StwoDecommitment, FormatError> {
+    Ok(StwoDecommitment {
+        hash_witness: r.vec("hash_witness", 32, |r| r.digest("hash_witness"))?,
+        column_witness: r.vec("column_witness", 4, |r| r.u32("column_witness"))?,
+    })
+}
+
+fn read_fri_layer(r: &mut Reader) -> Result<StwoFriLayer, FormatError> {
+    Ok(StwoFriLayer {
+        fri_witness: r.vec("fri_witness", 16, |r| r.qm31("fri_witness"))?,
+        decommitment: read_decommitment(r)?,
+        commitment: r.digest("fri_commitment")?,
+    })
+}
+
+impl<'a> Reader<'a> {
+    fn u64(&mut self, field: &'static str) -> Result<u64, FormatError> {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(self.take(8, field)?);
+        Ok(u64::from_le_bytes(word))
+    }
+
+    /// A u64 length, then that many items of at least `min_size` bytes each.
+    /// A length the remaining bytes cannot hold is truncation, caught before
+    /// allocating for it.
+    fn vec<T>(
+        &mut self,
+        field: &'static str,
+        min_size: usize,
+        mut item: impl FnMut(&mut Self) -> Result<T, FormatError>,
+    ) -> Result<Vec<T>, FormatError> {
+        let len = self.u64(field)?;
+        let remaining = (self.bytes.len() - self.at) as u64;
+        if len > remaining / min_size as u64 {
+            return Err(FormatError::Truncated(field));
+        }
+        (0..len).map(|_| item(self)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyProof, SerializedProof};
+    use alloc::vec;
+
+    /// Two trees, two queries, one inner FRI layer
+    fn sample() -> StwoProof {
+        let layer = |seed: u8| StwoFriLayer {
+            fri_witness: vec![[seed as u32, 1, 2, 3]],
+            decommitment: StwoDecommitment { hash_witness: vec![[seed; 32]], column_witness: vec![] },
+            commitment: [seed + 1; 32],
+        };
+        StwoProof {
+            hasher: STWO_HASHER_BLAKE2S,
+            config: StwoPcsConfig { pow_bits: 10, log_blowup_factor: 1, log_last_layer_degree_bound: 0, n_queries: 2 },
+            commitments: vec![[1; 32], [2; 32]],
+            sampled_values: vec![vec![vec![[3, 3, 3, 3], [4, 4, 4, 4]]], vec![vec![[5, 5, 5, 5]]; 2]],
+            decommitments: vec![
+                StwoDecommitment { hash_witness: vec![[6; 32]; 3], column_witness: vec![7] },
+                StwoDecommitment { hash_witness: vec![], column_witness: vec![M31_MODULUS - 1] },
+            ],
+            queried_values: vec![vec![vec![8, 9]], vec![vec![10, 11], vec![12, 13]]],
+            proof_of_work: 0xdead_beef,
+            fri_proof: StwoFriProof {
+                first_layer: layer(20),
+                inner_layers: vec![layer(30)],
+                last_layer_poly: vec![[14, 0, 0, 0]],
+                last_layer_log_size: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let proof = sample();
+        let bytes = proof.encode();
+        assert_eq!(&bytes[..6], &[b'M', b'R', b'K', b'L', STWO_PROOF_VERSION, STWO_HASHER_BLAKE2S]);
+        // bincode lays the config out as three u32 and a u64
+        assert_eq!(&bytes[6..10], &10u32.to_le_bytes());
+        assert_eq!(&bytes[18..26], &2u64.to_le_bytes());
+        assert_eq!(StwoProof::decode(&bytes).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_decode_rejects() {
+        let bytes = sample().encode();
+        let decode = |edit: &dyn Fn(&mut Vec<u8>)| {
+            let mut b = bytes.clone();
+            edit(&mut b);
+            StwoProof::decode(&b).unwrap_err()
+        };
+        assert_eq!(decode(&|b| b[0] = b'X'), FormatError::BadMagic);
+        assert_eq!(decode(&|b| b[4] = 2), FormatError::UnsupportedVersion(2));
+        assert_eq!(decode(&|b| b[5] = 1), FormatError::UnknownHashKind(1));
+        assert_eq!(decode(&|b| b.push(0)), FormatError::TrailingBytes(1));
+        assert_eq!(decode(&|b| { b.pop(); }), FormatError::Truncated("last_layer_log_size"));
+        // A commitment count no proof this size could hold
+        let huge = u64::MAX.to_le_bytes();
+        assert_eq!(decode(&|b| b[26..34].copy_from_slice(&huge)), FormatError::Truncated("commitments"));
+        // One commitment fewer than trees everywhere else
+        let short = StwoProof { commitments: vec![[1; 32]], ..sample() };
+        assert_eq!(
+            StwoProof::decode(&short.encode()).unwrap_err(),
+            FormatError::OutOfBounds { field: "sampled_values", value: 2 }
+        );
+
+        let mut wide = sample();
+        wide.fri_proof.last_layer_log_size = 1;
+        assert_eq!(wide.check(), Err(FormatError::OutOfBounds { field: "last_layer_poly", value: 1 }));
+        let mut high = sample();
+        high.queried_values[1][0][1] = M31_MODULUS;
+        assert_eq!(high.check(), Err(FormatError::NonCanonicalField("m31")));
+        let mut high = sample();
+        high.fri_proof.inner_layers[0].fri_witness[0][3] = M31_MODULUS;
+        assert_eq!(high.check(), Err(FormatError::NonCanonicalField("qm31")));
+    }
+
+    #[test]
+    fn test_any_proof_dispatches_on_version() {
+        let stwo = sample();
+        assert_eq!(AnyProof::decode(&stwo.encode()).unwrap(), AnyProof::Stwo(stwo));
+
+        let murkl = SerializedProof::decode(&crate::tests::sample().encode()).unwrap();
+        assert_eq!(AnyProof::decode(&murkl.encode()).unwrap(), AnyProof::Murkl(murkl));
+
+        let mut unknown = sample().encode();
+        unknown[4] = 0x81;
+        assert_eq!(AnyProof::decode(&unknown).unwrap_err(), FormatError::UnsupportedVersion(0x81));
+    }
+}
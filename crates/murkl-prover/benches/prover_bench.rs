@@ -0,0 +1,87 @@
+//! Benchmarks for work shared across proving phases
+//!
+//! Compares the phases with and without sharing work (the trace coset's
+//! interpolation data through `EvaluationCache`) on 2^14-point traces, and
+//! times committing the composition: its own tree, then FRI's layer trees
+//! with a leaf per folding group.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use murkl_prover::cache::EvaluationCache;
+use murkl_prover::circle::{barycentric_weights, Coset, SecureCirclePoint};
+use murkl_prover::fri::{FriConfig, FriProver};
+use murkl_prover::m31::M31;
+use murkl_prover::merkle::MerkleCommitment;
+
+const LOG_SIZE: u32 = 14;
+
+fn composition() -> Vec<M31> {
+    (0..1u32 << LOG_SIZE).map(|i| M31::new(i.wrapping_mul(2654435761))).collect()
+}
+
+fn folded(commit: impl FnOnce(&mut FriProver)) -> FriProver {
+    let config = FriConfig::default();
+    let rounds = config.num_rounds(LOG_SIZE);
+    let mut fri = FriProver::new(config);
+    commit(&mut fri);
+    for round in 0..rounds {
+        fri.fold(M31::new(round as u32 + 3));
+    }
+    fri
+}
+
+fn bench_composition_commit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("composition_commit");
+    let values = composition();
+
+    group.bench_with_input(BenchmarkId::new("with_fri", 1 << LOG_SIZE), &values, |b, values| {
+        b.iter(|| {
+            let commitment = MerkleCommitment::commit(black_box(values));
+            let fri = folded(|fri| fri.commit(values.clone(), LOG_SIZE));
+            (commitment.root(), fri.get_roots())
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_trace_extension(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trace_extension");
+    group.sample_size(10);
+
+    // A handful of commitment-coset points stands in for the full extension
+    let coset = Coset::canonic(LOG_SIZE);
+    let points: Vec<SecureCirclePoint> = Coset::commitment(LOG_SIZE + 2)
+        .iter()
+        .take(4)
+        .map(SecureCirclePoint::from_base)
+        .collect();
+
+    group.bench_function(BenchmarkId::new("uncached", 1 << LOG_SIZE), |b| {
+        b.iter(|| {
+            points
+                .iter()
+                .map(|p| barycentric_weights(black_box(&coset), p))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function(BenchmarkId::new("cached", 1 << LOG_SIZE), |b| {
+        b.iter(|| {
+            let mut cache = EvaluationCache::new();
+            points
+                .iter()
+                .map(|p| cache.weights(black_box(LOG_SIZE), p))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_composition_commit,
+    bench_trace_extension,
+);
+
+criterion_main!(benches);
@@ -1,11 +1,24 @@
 //! AIR (Algebraic Intermediate Representation) constraints
 //!
 //! Defines the constraint system for Circle STARKs.
+//!
+//! [`poseidon2`] holds the AIR proving a commitment and nullifier hash.
 
 #[cfg(not(feature = "std"))]
 use alloc::{vec, vec::Vec, string::String};
 
+use core::ops::{Add, Mul, Sub};
+
 use crate::m31::M31;
+use crate::qm31::QM31;
+
+pub mod poseidon2;
+
+/// A field constraints can be evaluated over: M31 on trace rows, QM31 at
+/// the out-of-domain point
+pub trait AirField: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + From<M31> {}
+
+impl<F> AirField for F where F: Copy + Add<Output = F> + Sub<Output = F> + Mul<Output = F> + From<M31> {}
 
 /// Configuration for AIR constraints
 #[derive(Clone, Debug)]
@@ -150,6 +163,25 @@ pub trait ConstraintEvaluator {
     fn max_degree(&self) -> usize {
         self.constraints().iter().map(|c| c.degree).max().unwrap_or(0)
     }
+
+    /// Evaluate all constraints off the trace domain, from every column's
+    /// value at the point (`current`) and one trace step further (`next`)
+    ///
+    /// This is what lets a verifier check the composition at the OODS
+    /// point. `None` for evaluators whose constraints reach further than
+    /// the next row, whose proofs the verifier rejects.
+    fn evaluate_at_point(&self, _current: &[QM31], _next: &[QM31]) -> Option<Vec<QM31>> {
+        None
+    }
+
+    /// Columns the AIR itself fixes (selectors, round constants), as
+    /// (column, value per trace row)
+    ///
+    /// A verifier evaluates these at the OODS point itself instead of
+    /// taking the prover's values.
+    fn fixed_columns(&self) -> Vec<(usize, Vec<M31>)> {
+        Vec::new()
+    }
 }
 
 /// Fibonacci constraint system (example)
@@ -195,13 +227,21 @@ impl ConstraintEvaluator for FibonacciAir {
     }
 }
 
-/// Murkl-specific AIR for Merkle membership proofs
+/// One-row layout of a claim's columns: commitment preimage, a group of
+/// columns per tree level, root and nullifier inputs
+///
+/// Only the path bits, the shared secret and the epoch are constrained.
+/// Claims are proven with [`poseidon2::MurklHashAir`], which also proves
+/// the hashes and the pool tree path.
 #[derive(Clone, Debug)]
 pub struct MurklAir {
     /// Tree depth
     pub tree_depth: usize,
     /// Number of columns per Merkle level
     pub cols_per_level: usize,
+    /// Epoch claimed from a subscription deposit; adds an epoch column
+    /// after the nullifier columns, pinned to this value
+    pub epoch: Option<M31>,
 }
 
 impl MurklAir {
@@ -211,67 +251,96 @@ impl MurklAir {
             tree_depth,
             // Each level: current, sibling, path_bit, output
             cols_per_level: 4,
+            epoch: None,
+        }
+    }
+
+    /// AIR for claiming `epoch` of a subscription deposit
+    pub fn with_epoch(tree_depth: usize, epoch: u32) -> Self {
+        Self {
+            epoch: Some(M31::new(epoch)),
+            ..Self::new(tree_depth)
         }
     }
 
     /// Total number of columns needed
     pub fn num_columns(&self) -> usize {
-        // Commitment columns + Merkle levels + nullifier columns
-        3 + self.tree_depth * self.cols_per_level + 3
+        // Commitment columns + Merkle levels + root + nullifier columns (+ epoch)
+        3 + self.tree_depth * self.cols_per_level + 1 + 3 + self.epoch.is_some() as usize
     }
 }
 
-impl ConstraintEvaluator for MurklAir {
-    fn evaluate(&self, trace: &Trace, row: usize) -> Vec<M31> {
+impl MurklAir {
+    /// The constraints over one row, read through `cell(column)`
+    fn evaluate_row<F: AirField>(&self, cell: impl Fn(usize) -> F) -> Vec<F> {
         let mut constraints = Vec::new();
 
-        // Skip if not enough columns
-        if trace.num_columns() < self.num_columns() {
-            return vec![M31::ZERO; self.tree_depth + 2];
-        }
-
         let mut col = 0;
 
         // === Commitment verification ===
-        let _identifier = trace.get(row, col);
+        let _identifier = cell(col);
         col += 1;
-        let secret = trace.get(row, col);
+        let secret = cell(col);
         col += 1;
-        let _leaf = trace.get(row, col);
+        let _leaf = cell(col);
         col += 1;
 
         // === Merkle path verification ===
         for _level in 0..self.tree_depth {
-            let _current = trace.get(row, col);
+            let _current = cell(col);
             col += 1;
-            let _sibling = trace.get(row, col);
+            let _sibling = cell(col);
             col += 1;
-            let path_bit = trace.get(row, col);
+            let path_bit = cell(col);
             col += 1;
-            let _next = trace.get(row, col);
+            let _next = cell(col);
             col += 1;
 
             // Constraint: path_bit must be boolean (0 or 1)
             // path_bit * (1 - path_bit) = 0
-            constraints.push(path_bit * (M31::ONE - path_bit));
+            constraints.push(path_bit * (F::from(M31::ONE) - path_bit));
         }
 
         // === Root verification ===
-        let _merkle_root = trace.get(row, col);
+        let _merkle_root = cell(col);
         col += 1;
 
         // === Nullifier verification ===
-        let null_secret = trace.get(row, col);
+        let null_secret = cell(col);
+        col += 1;
+        let _leaf_index = cell(col);
         col += 1;
-        let _leaf_index = trace.get(row, col);
+        let _nullifier = cell(col);
         col += 1;
-        let _nullifier = trace.get(row, col);
 
         // Constraint: nullifier secret must match commitment secret
         constraints.push(null_secret - secret);
 
+        // Constraint: the nullifier's epoch is the one being claimed
+        if let Some(epoch) = self.epoch {
+            constraints.push(cell(col) - F::from(epoch));
+        }
+
         constraints
     }
+}
+
+impl ConstraintEvaluator for MurklAir {
+    fn evaluate(&self, trace: &Trace, row: usize) -> Vec<M31> {
+        // Skip if not enough columns
+        if trace.num_columns() < self.num_columns() {
+            return vec![M31::ZERO; self.tree_depth + 2];
+        }
+
+        self.evaluate_row(|col| trace.get(row, col))
+    }
+
+    fn evaluate_at_point(&self, current: &[QM31], _next: &[QM31]) -> Option<Vec<QM31>> {
+        if current.len() < self.num_columns() {
+            return None;
+        }
+        Some(self.evaluate_row(|col| current[col]))
+    }
 
     fn constraints(&self) -> Vec<Constraint> {
         let mut constraints = Vec::new();
@@ -292,6 +361,14 @@ impl ConstraintEvaluator for MurklAir {
             vec![1, 3 + self.tree_depth * self.cols_per_level + 1],
         ));
 
+        if self.epoch.is_some() {
+            constraints.push(Constraint::new(
+                "epoch_binding",
+                1,
+                vec![3 + self.tree_depth * self.cols_per_level + 4],
+            ));
+        }
+
         constraints
     }
 }
@@ -424,8 +501,25 @@ mod tests {
     fn test_murkl_air_columns() {
         let air = MurklAir::new(16);
 
-        // 3 commitment + 16*4 merkle + 3 nullifier = 70
-        assert_eq!(air.num_columns(), 70);
+        // 3 commitment + 16*4 merkle + 1 root + 3 nullifier = 71
+        assert_eq!(air.num_columns(), 71);
+    }
+
+    #[test]
+    fn test_murkl_air_epoch_binding() {
+        let air = MurklAir::with_epoch(2, 5);
+        assert_eq!(air.num_columns(), 3 + 2 * 4 + 1 + 3 + 1);
+        assert_eq!(air.constraints().last().unwrap().name, "epoch_binding");
+
+        let row = |epoch: u32| {
+            let mut row = vec![M31::ZERO; air.num_columns()];
+            row[1] = M31::new(42);
+            row[3 + 2 * 4 + 1] = M31::new(42);
+            row[air.num_columns() - 1] = M31::new(epoch);
+            row
+        };
+        assert!(verify_constraints(&air, &Trace::from_rows(vec![row(5); 4])).is_ok());
+        assert!(verify_constraints(&air, &Trace::from_rows(vec![row(6); 4])).is_err());
     }
 
     #[test]
@@ -0,0 +1,622 @@
+//! AIR for the Poseidon2 commitment and nullifier
+//!
+//! Proves, for a private identifier hash, secret and leaf index, that
+//! `commitment = pq_commitment(id_hash, secret)` and
+//! `nullifier = pq_epoch_nullifier(secret, leaf_index, epoch)`, with the
+//! commitment and nullifier bytes public and the epoch read from the
+//! nullifier's tag. Each hash is one permutation
+//! ([`hash_tagged`](crate::poseidon2::hash_tagged)), laid out as a block of
+//! [`BLOCK_ROWS`] rows: row 0 holds the input state, every following row the
+//! state after one more round, and row [`OUTPUT_ROW`] the output, whose
+//! first lanes are the digest limbs. The commitment block comes first, then
+//! the nullifier block; a longer trace idles after them. Nothing constrains
+//! the witness columns on idle rows, so the prover fills them from a key
+//! only it knows: an opening of a column, out of domain or at a query,
+//! gives away none of the witness.
+//!
+//! The leaf index is not pinned: the pool keeps a deposit, or a deposit's
+//! epoch, from being claimed twice whatever nullifier the claim brings.
+//!
+//! With a Merkle root set ([`MurklHashAir::with_membership`]), a block per
+//! pool tree level follows the nullifier block. Its input row holds the
+//! node and its sibling, left child first, as
+//! [`pool_node`](crate::merkle::pool_node) compresses them, and a path bit
+//! column says which side the node is on. The first level's node is the
+//! commitment, each later level's the previous output, held through the
+//! padding rows; the last output is the root. The path bits are not tied
+//! to the nullifier's leaf index either.
+//!
+//! Which round a row runs is not read from the row index (constraints are
+//! also evaluated off the trace domain) but from fixed columns: selectors
+//! and round constants, evaluated by the verifier itself
+//! ([`ConstraintEvaluator::fixed_columns`]). A secret column, carried from
+//! row to row through both blocks, feeds lane 2 of both inputs, so both
+//! hashes use one secret.
+//!
+//! A round row constrains the next state by a selector times an S-box, so
+//! the constraints have degree 6 and the quotient only fits the commitment
+//! domain with a blowup of 8 or more (`log_blowup_factor >= 3`).
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec, vec::Vec};
+
+use core::array;
+
+use super::{AirField, Constraint, ConstraintEvaluator, Trace, TraceColumn};
+use crate::hash::{keccak_hash, nullifier_epoch, Hash32, EPOCH_NULLIFIER_TAG};
+use crate::types::ProofError;
+use crate::m31::M31;
+use crate::poseidon2::{
+    COMMITMENT_TAG, DIGEST_LIMBS, EXTERNAL_CONSTANTS, HALF_FULL_ROUNDS, INTERNAL_CONSTANTS, INTERNAL_DIAG,
+    NULLIFIER_TAG, WIDTH,
+};
+use crate::qm31::QM31;
+
+/// Rows per permutation: input, 22 rounds after the initial linear layer,
+/// padded to a power of two
+pub const BLOCK_ROWS: usize = 32;
+
+/// Row of a block holding the permutation output
+pub const OUTPUT_ROW: usize = 1 + 2 * HALF_FULL_ROUNDS + INTERNAL_CONSTANTS.len();
+
+/// Commitment block, then nullifier block: the shortest trace
+pub const TRACE_ROWS: usize = 2 * BLOCK_ROWS;
+
+/// Digest limbs a nullifier keeps; its tag replaces the last one
+pub const NULLIFIER_LIMBS: usize = EPOCH_NULLIFIER_TAG.start / 4;
+
+/// First of the [`WIDTH`] permutation state columns
+pub const STATE: usize = 0;
+/// The secret, equal on every row of the two blocks
+pub const SECRET: usize = STATE + WIDTH;
+/// 1 on a level block whose node is the right child
+pub const PATH_BIT: usize = SECRET + 1;
+/// First of the [`WIDTH`] round constant columns (fixed)
+pub const ROUND_CONSTANTS: usize = PATH_BIT + 1;
+/// 1 where the next row is the initial linear layer (fixed)
+pub const LINEAR: usize = ROUND_CONSTANTS + WIDTH;
+/// 1 where the next row is a full round (fixed)
+pub const FULL: usize = LINEAR + 1;
+/// 1 where the next row is a partial round (fixed)
+pub const PARTIAL: usize = FULL + 1;
+/// 1 on the input row of both blocks (fixed)
+pub const INPUT: usize = PARTIAL + 1;
+/// The block's tag on its input row, else 0 (fixed)
+pub const TAG: usize = INPUT + 1;
+/// 1 on the nullifier input row, whose lane 3 is the epoch (fixed)
+pub const NULLIFIER_INPUT: usize = TAG + 1;
+/// 1 on the commitment output row (fixed)
+pub const COMMITMENT_OUTPUT: usize = NULLIFIER_INPUT + 1;
+/// 1 on the nullifier output row (fixed)
+pub const NULLIFIER_OUTPUT: usize = COMMITMENT_OUTPUT + 1;
+/// 1 where the next row is still in the two blocks and keeps the secret
+/// (fixed)
+pub const CARRY: usize = NULLIFIER_OUTPUT + 1;
+/// 1 on the input row of every level block (fixed)
+pub const LEVEL_INPUT: usize = CARRY + 1;
+/// 1 on the first level's input row, whose node is the commitment (fixed)
+pub const LEAF_INPUT: usize = LEVEL_INPUT + 1;
+/// 1 past a level's output row where the next row keeps its node (fixed)
+pub const HOLD: usize = LEAF_INPUT + 1;
+/// 1 on a level's last row, whose node enters the next level (fixed)
+pub const LINK: usize = HOLD + 1;
+/// 1 on the last level's output row, the root (fixed)
+pub const ROOT_OUTPUT: usize = LINK + 1;
+/// Total columns
+pub const NUM_COLUMNS: usize = ROOT_OUTPUT + 1;
+/// Columns the prover commits to; the rest are fixed
+pub const WITNESS_COLUMNS: usize = ROUND_CONSTANTS;
+
+/// What the row after `row` (within a block) is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Linear,
+    /// Full round with these external constants
+    Full(usize),
+    /// Partial round with this internal constant
+    Partial(usize),
+}
+
+/// What the row after `row` is in a trace of `blocks` blocks
+fn step(row: usize, blocks: usize) -> Option<Step> {
+    if row >= blocks * BLOCK_ROWS {
+        return None;
+    }
+    let partial_start = 1 + HALF_FULL_ROUNDS;
+    let partial_end = partial_start + INTERNAL_CONSTANTS.len();
+    match row % BLOCK_ROWS {
+        0 => Some(Step::Linear),
+        r if r < partial_start => Some(Step::Full(r - 1)),
+        r if r < partial_end => Some(Step::Partial(r - partial_start)),
+        r if r < OUTPUT_ROW => Some(Step::Full(r - partial_end + HALF_FULL_ROUNDS)),
+        _ => None,
+    }
+}
+
+/// Limb `lane` of a digest, as an M31
+fn limb(digest: &Hash32, lane: usize) -> M31 {
+    let bytes = &digest[4 * lane..4 * lane + 4];
+    M31::new(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Proves a commitment and nullifier share one secret, and optionally that
+/// the commitment is a pool tree leaf
+#[derive(Clone, Debug)]
+pub struct MurklHashAir {
+    pub commitment: [M31; DIGEST_LIMBS],
+    pub nullifier: [M31; NULLIFIER_LIMBS],
+    pub epoch: M31,
+    /// Root the path levels end at; zero without membership
+    pub merkle_root: [M31; DIGEST_LIMBS],
+    /// Pool tree levels proven, 0 for the hashes alone
+    pub depth: usize,
+    /// Trace length, a power of two holding every block
+    pub rows: usize,
+}
+
+impl MurklHashAir {
+    /// AIR for the given public commitment and nullifier
+    pub fn new(commitment: &Hash32, nullifier: &Hash32) -> Self {
+        Self {
+            commitment: array::from_fn(|lane| limb(commitment, lane)),
+            nullifier: array::from_fn(|lane| limb(nullifier, lane)),
+            epoch: M31::new(nullifier_epoch(nullifier)),
+            merkle_root: [M31::ZERO; DIGEST_LIMBS],
+            depth: 0,
+            rows: TRACE_ROWS,
+        }
+    }
+
+    /// Also prove the commitment is a leaf of a pool tree of `depth` levels
+    /// under `merkle_root`
+    pub fn with_membership(mut self, merkle_root: &Hash32, depth: usize) -> Self {
+        self.merkle_root = array::from_fn(|lane| limb(merkle_root, lane));
+        self.depth = depth;
+        self.rows = self.rows.max(self.block_rows().next_power_of_two());
+        self
+    }
+
+    /// The same AIR over a trace of `2^log_rows` rows, idle past the
+    /// blocks, to match a verifier's fixed trace size
+    pub fn with_log_rows(mut self, log_rows: u32) -> Self {
+        self.rows = (1 << log_rows).max(self.block_rows().next_power_of_two());
+        self
+    }
+
+    /// Permutation blocks: commitment, nullifier, then one per tree level
+    pub fn blocks(&self) -> usize {
+        2 + self.depth
+    }
+
+    /// Rows the blocks fill; the trace idles after them
+    pub fn block_rows(&self) -> usize {
+        self.blocks() * BLOCK_ROWS
+    }
+
+    /// Trace proving the hashes of `id_hash`, `secret` and `leaf_index`,
+    /// and the path up from the commitment through `siblings`, leaf level
+    /// first
+    ///
+    /// The trace only satisfies the constraints if the public values are
+    /// the hashes of these inputs and the path ends at the root. Fails if
+    /// `siblings` is not [`depth`](Self::depth) long.
+    pub fn generate_trace(
+        &self,
+        id_hash: M31,
+        secret: M31,
+        leaf_index: u32,
+        siblings: &[Hash32],
+    ) -> Result<Trace, ProofError> {
+        if siblings.len() != self.depth {
+            return Err(ProofError::MerkleError(format!(
+                "witness path has {} levels, the AIR {}",
+                siblings.len(),
+                self.depth
+            )));
+        }
+
+        let mut columns = vec![Vec::new(); NUM_COLUMNS];
+        let tagged = |tag: u32, lanes: [M31; 3]| {
+            let mut state = [M31::ZERO; WIDTH];
+            state[0] = M31::new(tag);
+            state[1..4].copy_from_slice(&lanes);
+            state
+        };
+        self.push_block(&mut columns, 0, tagged(COMMITMENT_TAG, [id_hash, secret, M31::ZERO]), M31::ZERO);
+        self.push_block(&mut columns, 1, tagged(NULLIFIER_TAG, [M31::new(leaf_index), secret, self.epoch]), M31::ZERO);
+        let mut node = self.commitment;
+        for (level, sibling) in siblings.iter().enumerate() {
+            let sibling: [M31; DIGEST_LIMBS] = array::from_fn(|lane| limb(sibling, lane));
+            let right = leaf_index.checked_shr(level as u32).unwrap_or(0) & 1 == 1;
+            let (left, right_child) = if right { (sibling, node) } else { (node, sibling) };
+            let state = array::from_fn(|lane| if lane < DIGEST_LIMBS { left[lane] } else { right_child[lane - DIGEST_LIMBS] });
+            let output = self.push_block(&mut columns, 2 + level, state, M31::new(right as u32));
+            node = array::from_fn(|lane| output[lane]);
+        }
+        for column in &mut columns[STATE..=PATH_BIT] {
+            column.resize(self.rows, M31::ZERO);
+        }
+        columns[SECRET] = vec![secret; self.rows];
+        let key = keccak_hash(&[
+            b"murkl_trace_mask_v1",
+            &id_hash.value().to_le_bytes(),
+            &secret.value().to_le_bytes(),
+            &leaf_index.to_le_bytes(),
+        ]);
+        for (column, values) in columns[STATE..=PATH_BIT].iter_mut().enumerate() {
+            for (row, value) in values.iter_mut().enumerate().skip(self.block_rows()) {
+                let word = keccak_hash(&[&key, &(column as u32).to_le_bytes(), &(row as u32).to_le_bytes()]);
+                *value = M31::new(u32::from_le_bytes([word[0], word[1], word[2], word[3]]));
+            }
+        }
+        for (column, values) in self.fixed_columns() {
+            columns[column] = values;
+        }
+
+        Ok(Trace::new(
+            columns
+                .into_iter()
+                .enumerate()
+                .map(|(index, values)| TraceColumn::new(index, values))
+                .collect(),
+        ))
+    }
+
+    /// Append the rows of `block`, from its input `state`, with `bit` in
+    /// the path bit column; returns the output, held to the block's end
+    fn push_block(&self, columns: &mut [Vec<M31>], block: usize, mut state: [M31; WIDTH], bit: M31) -> [M31; WIDTH] {
+        for row in block * BLOCK_ROWS..(block + 1) * BLOCK_ROWS {
+            for (lane, &value) in state.iter().enumerate() {
+                columns[STATE + lane].push(value);
+            }
+            columns[PATH_BIT].push(bit);
+            if let Some(step) = step(row, self.blocks()) {
+                state = apply(step, &state);
+            }
+        }
+        state
+    }
+
+    /// The constraints from one row's values and the next row's
+    fn evaluate_frame<F: AirField>(&self, current: &[F], next: &[F]) -> Vec<F> {
+        let state: [F; WIDTH] = array::from_fn(|lane| current[STATE + lane]);
+        let constants: [F; WIDTH] = array::from_fn(|lane| current[ROUND_CONSTANTS + lane]);
+
+        let linear = external_linear(&state);
+        let full = external_linear(&array::from_fn(|lane| sbox(state[lane] + constants[lane])));
+        let mut partial = state;
+        partial[0] = sbox(state[0] + constants[0]);
+        let partial = internal_linear(&partial);
+
+        let mut constraints = Vec::with_capacity(2 * WIDTH + 4 * DIGEST_LIMBS + NULLIFIER_LIMBS + 1);
+        for lane in 0..WIDTH {
+            let next_lane = next[STATE + lane];
+            constraints.push(
+                current[LINEAR] * (next_lane - linear[lane])
+                    + current[FULL] * (next_lane - full[lane])
+                    + current[PARTIAL] * (next_lane - partial[lane]),
+            );
+        }
+
+        constraints.push(current[CARRY] * (next[SECRET] - current[SECRET]));
+        let input = current[INPUT];
+        constraints.push(input * state[0] - current[TAG]);
+        constraints.push(input * (state[2] - current[SECRET]));
+        constraints.push(input * state[3] - current[NULLIFIER_INPUT] * F::from(self.epoch));
+        constraints.extend(state[4..].iter().map(|&lane| input * lane));
+        for (lane, &limb) in self.commitment.iter().enumerate() {
+            constraints.push(current[COMMITMENT_OUTPUT] * (state[lane] - F::from(limb)));
+        }
+        for (lane, &limb) in self.nullifier.iter().enumerate() {
+            constraints.push(current[NULLIFIER_OUTPUT] * (state[lane] - F::from(limb)));
+        }
+
+        let one = F::from(M31::ONE);
+        let bit = current[PATH_BIT];
+        constraints.push(current[LEVEL_INPUT] * bit * (one - bit));
+        for (lane, &limb) in self.commitment.iter().enumerate() {
+            let leaf = F::from(limb);
+            let placed = (one - bit) * (state[lane] - leaf) + bit * (state[DIGEST_LIMBS + lane] - leaf);
+            constraints.push(current[LEAF_INPUT] * placed);
+        }
+        let next_bit = next[PATH_BIT];
+        for lane in 0..DIGEST_LIMBS {
+            let held = next[STATE + lane] - state[lane];
+            let entered = (one - next_bit) * held + next_bit * (next[STATE + DIGEST_LIMBS + lane] - state[lane]);
+            constraints.push(current[HOLD] * held + current[LINK] * entered);
+        }
+        for (lane, &limb) in self.merkle_root.iter().enumerate() {
+            constraints.push(current[ROOT_OUTPUT] * (state[lane] - F::from(limb)));
+        }
+        constraints
+    }
+}
+
+impl ConstraintEvaluator for MurklHashAir {
+    fn evaluate(&self, trace: &Trace, row: usize) -> Vec<M31> {
+        if trace.num_columns() < NUM_COLUMNS {
+            return vec![M31::ZERO; self.constraints().len()];
+        }
+        let current: Vec<M31> = trace.columns.iter().map(|c| c.at(row)).collect();
+        let next: Vec<M31> = trace.columns.iter().map(|c| c.at_offset(row, 1)).collect();
+        self.evaluate_frame(&current, &next)
+    }
+
+    fn constraints(&self) -> Vec<Constraint> {
+        let round_columns: Vec<usize> = (STATE..STATE + WIDTH)
+            .chain(ROUND_CONSTANTS..ROUND_CONSTANTS + WIDTH)
+            .chain([LINEAR, FULL, PARTIAL])
+            .collect();
+        let mut constraints: Vec<Constraint> = (0..WIDTH)
+            .map(|lane| Constraint::new(format!("round_lane_{}", lane), 6, round_columns.clone()))
+            .collect();
+        constraints.push(Constraint::new("secret_carried", 2, vec![CARRY, SECRET]));
+        constraints.push(Constraint::new("input_tag", 2, vec![INPUT, STATE, TAG]));
+        constraints.push(Constraint::new("input_secret", 2, vec![INPUT, STATE + 2, SECRET]));
+        constraints.push(Constraint::new("input_epoch", 2, vec![INPUT, NULLIFIER_INPUT, STATE + 3]));
+        for lane in 4..WIDTH {
+            constraints.push(Constraint::new(format!("input_padding_{}", lane), 2, vec![INPUT, STATE + lane]));
+        }
+        for lane in 0..DIGEST_LIMBS {
+            constraints.push(Constraint::new(format!("commitment_{}", lane), 2, vec![COMMITMENT_OUTPUT, STATE + lane]));
+        }
+        for lane in 0..NULLIFIER_LIMBS {
+            constraints.push(Constraint::new(format!("nullifier_{}", lane), 2, vec![NULLIFIER_OUTPUT, STATE + lane]));
+        }
+        constraints.push(Constraint::new("path_bit", 3, vec![LEVEL_INPUT, PATH_BIT]));
+        for lane in 0..DIGEST_LIMBS {
+            let columns = vec![LEAF_INPUT, PATH_BIT, STATE + lane, STATE + DIGEST_LIMBS + lane];
+            constraints.push(Constraint::new(format!("leaf_{}", lane), 3, columns));
+        }
+        for lane in 0..DIGEST_LIMBS {
+            let columns = vec![HOLD, LINK, PATH_BIT, STATE + lane, STATE + DIGEST_LIMBS + lane];
+            constraints.push(Constraint::new(format!("node_{}", lane), 3, columns));
+        }
+        for lane in 0..DIGEST_LIMBS {
+            constraints.push(Constraint::new(format!("root_{}", lane), 2, vec![ROOT_OUTPUT, STATE + lane]));
+        }
+        constraints
+    }
+
+    fn evaluate_at_point(&self, current: &[QM31], next: &[QM31]) -> Option<Vec<QM31>> {
+        if current.len() < NUM_COLUMNS || next.len() < NUM_COLUMNS {
+            return None;
+        }
+        Some(self.evaluate_frame(current, next))
+    }
+
+    fn fixed_columns(&self) -> Vec<(usize, Vec<M31>)> {
+        let mut columns = vec![vec![M31::ZERO; self.rows]; NUM_COLUMNS - ROUND_CONSTANTS];
+        let mut set = |column: usize, row: usize, value: M31| columns[column - ROUND_CONSTANTS][row] = value;
+        for row in 0..self.block_rows() {
+            if row + 1 < TRACE_ROWS {
+                set(CARRY, row, M31::ONE);
+            }
+            match step(row, self.blocks()) {
+                Some(Step::Linear) => set(LINEAR, row, M31::ONE),
+                Some(Step::Full(round)) => {
+                    set(FULL, row, M31::ONE);
+                    for (lane, &constant) in EXTERNAL_CONSTANTS[round].iter().enumerate() {
+                        set(ROUND_CONSTANTS + lane, row, M31::new(constant));
+                    }
+                }
+                Some(Step::Partial(round)) => {
+                    set(PARTIAL, row, M31::ONE);
+                    set(ROUND_CONSTANTS, row, M31::new(INTERNAL_CONSTANTS[round]));
+                }
+                None => {}
+            }
+        }
+        for (block, tag) in [COMMITMENT_TAG, NULLIFIER_TAG].into_iter().enumerate() {
+            set(INPUT, block * BLOCK_ROWS, M31::ONE);
+            set(TAG, block * BLOCK_ROWS, M31::new(tag));
+        }
+        set(NULLIFIER_INPUT, BLOCK_ROWS, M31::ONE);
+        set(COMMITMENT_OUTPUT, OUTPUT_ROW, M31::ONE);
+        set(NULLIFIER_OUTPUT, BLOCK_ROWS + OUTPUT_ROW, M31::ONE);
+        for level in 0..self.depth {
+            let start = (2 + level) * BLOCK_ROWS;
+            set(LEVEL_INPUT, start, M31::ONE);
+            if level == 0 {
+                set(LEAF_INPUT, start, M31::ONE);
+            }
+            if level + 1 < self.depth {
+                for row in start + OUTPUT_ROW..start + BLOCK_ROWS - 1 {
+                    set(HOLD, row, M31::ONE);
+                }
+                set(LINK, start + BLOCK_ROWS - 1, M31::ONE);
+            } else {
+                set(ROOT_OUTPUT, start + OUTPUT_ROW, M31::ONE);
+            }
+        }
+
+        (ROUND_CONSTANTS..NUM_COLUMNS).zip(columns).collect()
+    }
+}
+
+/// The state after `step`, as the permutation computes it
+fn apply(step: Step, state: &[M31; WIDTH]) -> [M31; WIDTH] {
+    match step {
+        Step::Linear => external_linear(state),
+        Step::Full(round) => {
+            external_linear(&array::from_fn(|lane| sbox(state[lane] + M31::new(EXTERNAL_CONSTANTS[round][lane]))))
+        }
+        Step::Partial(round) => {
+            let mut state = *state;
+            state[0] = sbox(state[0] + M31::new(INTERNAL_CONSTANTS[round]));
+            internal_linear(&state)
+        }
+    }
+}
+
+fn sbox<F: AirField>(x: F) -> F {
+    let x2 = x * x;
+    x2 * x2 * x
+}
+
+/// The permutation's external layer over any [`AirField`]
+fn external_linear<F: AirField>(state: &[F; WIDTH]) -> [F; WIDTH] {
+    let mut out = *state;
+    for block in out.chunks_exact_mut(4) {
+        let t01 = block[0] + block[1];
+        let t23 = block[2] + block[3];
+        let t0123 = t01 + t23;
+        let t01123 = t0123 + block[1];
+        let t01233 = t0123 + block[3];
+        block[3] = t01233 + block[0] + block[0];
+        block[1] = t01123 + block[2] + block[2];
+        block[0] = t01123 + t01;
+        block[2] = t01233 + t23;
+    }
+    let mut sums = [F::from(M31::ZERO); 4];
+    for (i, &lane) in out.iter().enumerate() {
+        sums[i % 4] = sums[i % 4] + lane;
+    }
+    for (i, lane) in out.iter_mut().enumerate() {
+        *lane = *lane + sums[i % 4];
+    }
+    out
+}
+
+/// The permutation's internal layer over any [`AirField`]
+fn internal_linear<F: AirField>(state: &[F; WIDTH]) -> [F; WIDTH] {
+    let sum = state.iter().fold(F::from(M31::ZERO), |acc, &lane| acc + lane);
+    array::from_fn(|lane| sum + F::from(M31::new(INTERNAL_DIAG[lane])) * state[lane])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::air::verify_constraints;
+    use crate::hash::{pq_commitment, pq_epoch_nullifier};
+    use crate::merkle::MerkleWitness;
+    use crate::poseidon2::permute;
+
+    fn claim() -> (M31, M31, MurklHashAir) {
+        let (id_hash, secret) = (M31::new(1234), M31::new(987_654));
+        let air = MurklHashAir::new(&pq_commitment(id_hash, secret), &pq_epoch_nullifier(secret, 5, 2));
+        (id_hash, secret, air)
+    }
+
+    #[test]
+    fn test_trace_follows_permutation() {
+        let (id_hash, secret, air) = claim();
+        let trace = air.generate_trace(id_hash, secret, 5, &[]).unwrap();
+        assert_eq!(trace.num_rows, TRACE_ROWS);
+        assert_eq!(trace.num_columns(), NUM_COLUMNS);
+        assert_eq!(air.epoch, M31::new(2));
+
+        let mut expected = [M31::ZERO; WIDTH];
+        expected[..3].copy_from_slice(&[M31::new(COMMITMENT_TAG), id_hash, secret]);
+        permute(&mut expected);
+        for (lane, &value) in expected.iter().enumerate() {
+            assert_eq!(trace.get(OUTPUT_ROW, STATE + lane), value, "lane {lane}");
+        }
+        assert_eq!(trace.get(BLOCK_ROWS + OUTPUT_ROW, STATE), air.nullifier[0]);
+        // Every row, wrap included, satisfies the constraints
+        assert!(verify_constraints(&air, &trace).is_ok());
+        for row in TRACE_ROWS - 2..TRACE_ROWS {
+            assert!(air.evaluate(&trace, row).iter().all(|c| c.is_zero()));
+        }
+    }
+
+    #[test]
+    fn test_longer_trace_idles() {
+        let (id_hash, secret, air) = claim();
+        let air = air.with_log_rows(8);
+        let trace = air.generate_trace(id_hash, secret, 5, &[]).unwrap();
+        assert_eq!(trace.num_rows, 256);
+        assert!(verify_constraints(&air, &trace).is_ok());
+        assert!(air.fixed_columns().iter().all(|(_, values)| values[TRACE_ROWS..].iter().all(|v| v.is_zero())));
+
+        // Idle rows mask every witness column, the secret's included
+        for column in &trace.columns[STATE..=SECRET] {
+            assert!(column.values[TRACE_ROWS..].iter().any(|v| !v.is_zero()));
+        }
+        assert!(trace.columns[SECRET].values[TRACE_ROWS..].iter().all(|&v| v != secret));
+        assert_eq!(trace.columns[SECRET].values[..TRACE_ROWS], [secret; TRACE_ROWS]);
+    }
+
+    #[test]
+    fn test_wrong_secret_or_public_value_fails() {
+        let (id_hash, secret, air) = claim();
+        let failing = |air: &MurklHashAir, trace: &Trace| {
+            let constraints = air.constraints();
+            let mut names: Vec<_> = verify_constraints(air, trace)
+                .unwrap_err()
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect();
+            names.dedup();
+            assert!(names.iter().all(|name| constraints.iter().any(|c| &c.name == name)));
+            names
+        };
+        let outputs = |names: &[String]| {
+            let mut outputs: Vec<_> = names.iter().filter_map(|name| name.split('_').next()).collect();
+            outputs.dedup();
+            outputs.join(",")
+        };
+
+        // Another secret reproduces neither public hash
+        let trace = air.generate_trace(id_hash, M31::new(1), 5, &[]).unwrap();
+        assert_eq!(outputs(&failing(&air, &trace)), "commitment,nullifier");
+
+        // Another leaf index or epoch is another nullifier
+        assert_eq!(outputs(&failing(&air, &air.generate_trace(id_hash, secret, 6, &[]).unwrap())), "nullifier");
+        let other_epoch = MurklHashAir { epoch: M31::new(3), ..air.clone() };
+        let names = failing(&other_epoch, &air.generate_trace(id_hash, secret, 5, &[]).unwrap());
+        assert_eq!(names, ["input_epoch"]);
+
+        // Splicing in another secret for the nullifier breaks the shared secret
+        let mut trace = air.generate_trace(id_hash, secret, 5, &[]).unwrap();
+        trace.columns[SECRET].values[BLOCK_ROWS..].fill(M31::new(1));
+        assert!(failing(&air, &trace).contains(&"secret_carried".into()));
+
+        // Skipping a round is caught by the round constraints
+        let mut trace = air.generate_trace(id_hash, secret, 5, &[]).unwrap();
+        trace.columns[STATE + 3].values[7] += M31::ONE;
+        assert!(failing(&air, &trace).iter().any(|name| name.starts_with("round_lane_")));
+    }
+
+    #[test]
+    fn test_membership_path() {
+        let (id_hash, secret, air) = claim();
+        let commitment = pq_commitment(id_hash, secret);
+        let leaves = [[7u8; 32], [9u8; 32], [3u8; 32], [5u8; 32], [1u8; 32], commitment];
+        let witness = MerkleWitness::from_leaves(&leaves, 4, 5).unwrap();
+        let air = air.with_membership(&witness.root, 4);
+        assert_eq!(air.rows, 256);
+        let trace = air.generate_trace(id_hash, secret, 5, &witness.siblings).unwrap();
+        assert!(verify_constraints(&air, &trace).is_ok());
+        assert!(air.generate_trace(id_hash, secret, 5, &witness.siblings[1..]).is_err());
+
+        // A wrong sibling ends the path at another root
+        let mut siblings = witness.siblings.clone();
+        siblings[2][0] ^= 1;
+        let names: Vec<_> = verify_constraints(&air, &air.generate_trace(id_hash, secret, 5, &siblings).unwrap())
+            .unwrap_err()
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert!(!names.is_empty() && names.iter().all(|name| name.starts_with("root_")));
+
+        // So does another position, and a tree without the commitment
+        assert!(verify_constraints(&air, &air.generate_trace(id_hash, secret, 4, &witness.siblings).unwrap()).is_err());
+        let other = MerkleWitness::from_leaves(&leaves[..5], 4, 4).unwrap();
+        let air = MurklHashAir::new(&commitment, &pq_epoch_nullifier(secret, 4, 2)).with_membership(&other.root, 4);
+        assert!(verify_constraints(&air, &air.generate_trace(id_hash, secret, 4, &other.siblings).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_at_point_matches_rows() {
+        let (id_hash, secret, air) = claim();
+        let trace = air.generate_trace(id_hash, secret, 5, &[]).unwrap();
+        let row = 9;
+        let lift = |offset: i32| trace.columns.iter().map(|c| QM31::from(c.at_offset(row, offset))).collect::<Vec<_>>();
+        let at_point = air.evaluate_at_point(&lift(0), &lift(1)).unwrap();
+        let at_row: Vec<QM31> = air.evaluate(&trace, row).into_iter().map(QM31::from).collect();
+        assert_eq!(at_point, at_row);
+        assert_eq!(at_point.len(), air.constraints().len());
+        assert!(air.evaluate_at_point(&lift(0)[..NUM_COLUMNS - 1], &lift(1)).is_none());
+    }
+}
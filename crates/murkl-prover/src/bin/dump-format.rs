@@ -0,0 +1,10 @@
+//! Print the proof wire format as a Markdown table
+//!
+//! Usage: cargo run -p murkl-prover --bin dump-format > FORMAT.md
+//!
+//! The table is generated from `murkl_prover::spec`, the same description
+//! the layout checker and test vectors use, so it tracks the Rust source.
+
+fn main() {
+    print!("{}", murkl_prover::spec::format_table());
+}
@@ -11,6 +11,7 @@ use murkl_prover::{
 const M31_PRIME: u32 = 0x7FFFFFFF;
 const N_FRI_LAYERS: usize = 3;
 const N_QUERIES: usize = 4;
+const N_TRACE_COLUMNS: usize = 4;
 const LOG_TRACE_SIZE: u32 = 6;
 const LOG_BLOWUP: u32 = 2;
 
@@ -97,6 +98,9 @@ fn generate_proof(id_hash: u32, secret: u32, leaf_index: u32) -> Vec<u8> {
     ]);
     proof.extend_from_slice(&composition_commitment);
     
+    // Trace column count (1 byte)
+    proof.push(N_TRACE_COLUMNS as u8);
+    
     // 3. Trace OODS (16 bytes - QM31)
     for val in &[commitment_m31, nullifier_m31, id_hash, secret] {
         proof.extend_from_slice(&val.to_le_bytes());
@@ -162,13 +166,17 @@ fn generate_proof(id_hash: u32, secret: u32, leaf_index: u32) -> Vec<u8> {
         // Index (4 bytes)
         proof.extend_from_slice(&index.to_le_bytes());
         
-        // Trace value (32 bytes)
-        let trace_value = keccak_hash(&[
-            b"trace_eval",
-            &index.to_le_bytes(),
-            &trace_commitment,
-        ]);
-        proof.extend_from_slice(&trace_value);
+        // Trace row (4 bytes M31 per column)
+        for column in 0..N_TRACE_COLUMNS as u32 {
+            let value = keccak_hash(&[
+                b"trace_eval",
+                &index.to_le_bytes(),
+                &column.to_le_bytes(),
+                &trace_commitment,
+            ]);
+            let value = u32::from_le_bytes([value[0], value[1], value[2], value[3]]) % M31_PRIME;
+            proof.extend_from_slice(&value.to_le_bytes());
+        }
         
         // Trace path length (1 byte)
         proof.push(tree_depth as u8);
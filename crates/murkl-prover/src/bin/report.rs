@@ -0,0 +1,272 @@
+//! Benchmark and parameter report
+//!
+//! Usage: cargo run --release -p murkl-prover --bin report -- [--format markdown|json] [--runs N]
+//!
+//! Proves and verifies a few representative workloads at every
+//! [`SecurityLevel`] and prints, per run: proof size, median prove time
+//! on this host, the on-chain compute estimate and the soundness the
+//! parameters give. Generate it on release hardware and
+//! commit the output next to the release (e.g. `docs/report.md`), so
+//! parameters can be picked from numbers.
+//!
+//! Compute units are estimated, not measured: a fixed cost per
+//! verification plus one per query, the same rates the client's claim
+//! planner (`murkl_client::bundle`) budgets transactions with.
+
+use std::env;
+use std::process::exit;
+use std::time::Duration;
+
+use murkl_prover::air::poseidon2::MurklHashAir;
+use murkl_prover::air::{ConstraintEvaluator, FibonacciAir, Trace};
+use murkl_prover::hash::{pq_commitment, pq_nullifier};
+use murkl_prover::merkle::{MerkleWitness, TREE_DEPTH};
+use murkl_prover::metrics::{system_clock, StageRecorder};
+use murkl_prover::{Prover, ProverConfig, PublicInputs, SecurityLevel, Verifier, M31};
+
+/// Parsing, transcript and OODS check; once per verification
+const VERIFY_BASE_CU: u64 = 60_000;
+/// One query opening's Merkle paths and FRI folds
+const QUERY_CU: u64 = 160_000;
+/// Compute units one transaction may request
+const MAX_TRANSACTION_CU: u64 = 1_400_000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Markdown,
+    Json,
+}
+
+/// A proof to generate: its AIR, trace and public inputs
+struct Workload {
+    name: &'static str,
+    description: &'static str,
+    prove: fn(&ProverConfig) -> Measured,
+}
+
+/// One proving run
+struct Measured {
+    proof_bytes: usize,
+    prove: Duration,
+}
+
+/// A workload at one security level, over several runs
+struct Row {
+    workload: &'static str,
+    level: SecurityLevel,
+    queries: usize,
+    log_blowup: u32,
+    pow_bits: u32,
+    proof_bytes: usize,
+    prove: Duration,
+}
+
+impl Row {
+    /// Nominal soundness of the queries, as [`SecurityLevel::bits`] counts it
+    fn query_bits(&self) -> u32 {
+        self.queries as u32 * self.log_blowup
+    }
+
+    fn estimated_cu(&self) -> u64 {
+        VERIFY_BASE_CU + self.queries as u64 * QUERY_CU
+    }
+
+    /// Whether `finalize_and_verify` fits one transaction, or the proof
+    /// needs staged verification
+    fn verification(&self) -> &'static str {
+        if self.estimated_cu() <= MAX_TRANSACTION_CU {
+            "direct"
+        } else {
+            "staged"
+        }
+    }
+}
+
+const WORKLOADS: [Workload; 3] = [
+    Workload {
+        name: "fibonacci-256",
+        description: "Fibonacci AIR, 2^8 rows: a small trace, mostly fixed costs",
+        prove: |config| {
+            let air = FibonacciAir::new(256);
+            let trace = air.generate_trace(M31::ONE, M31::ONE);
+            let inputs = PublicInputs::new(vec![M31::ONE, M31::ONE], vec![trace.get(255, 0)]);
+            measure(config, &air, &trace, inputs)
+        },
+    },
+    Workload {
+        name: "fibonacci-4096",
+        description: "Fibonacci AIR, 2^12 rows: how proving scales with the trace",
+        prove: |config| {
+            let air = FibonacciAir::new(4096);
+            let trace = air.generate_trace(M31::ONE, M31::ONE);
+            let inputs = PublicInputs::new(vec![M31::ONE, M31::ONE], vec![trace.get(4095, 0)]);
+            measure(config, &air, &trace, inputs)
+        },
+    },
+    Workload {
+        name: "claim-hash",
+        description: "Poseidon2 commitment, nullifier and pool path of one claim (MurklHashAir)",
+        prove: |config| {
+            let (id_hash, secret, leaf_index) = (M31::new(0x1d), M31::new(0x5ec), 7);
+            let commitment = pq_commitment(id_hash, secret);
+            let mut leaves = vec![[0x11; 32]; leaf_index as usize];
+            leaves.push(commitment);
+            let witness = MerkleWitness::from_leaves(&leaves, TREE_DEPTH, leaf_index as usize).unwrap();
+            let air = MurklHashAir::new(&commitment, &pq_nullifier(secret, leaf_index))
+                .with_membership(&witness.root, TREE_DEPTH);
+            let trace = air.generate_trace(id_hash, secret, leaf_index, &witness.siblings).unwrap();
+            let inputs = PublicInputs::new(air.commitment.iter().chain(&air.nullifier).copied().collect(), vec![]);
+            measure(config, &air, &trace, inputs)
+        },
+    },
+];
+
+fn measure<E: ConstraintEvaluator>(config: &ProverConfig, air: &E, trace: &Trace, inputs: PublicInputs) -> Measured {
+    let (proof, metrics) = Prover::new(config.clone())
+        .prove_with_metrics(air, trace, inputs, StageRecorder::new(system_clock))
+        .unwrap_or_else(|e| {
+            eprintln!("error: proving failed: {}", e);
+            exit(1);
+        });
+    // Sizes of a malformed proof would be meaningless
+    if let Err(e) = Verifier::new(config.clone()).quick_verify(&proof) {
+        eprintln!("error: generated proof is malformed: {}", e);
+        exit(1);
+    }
+    Measured { proof_bytes: metrics.proof_bytes, prove: metrics.total_elapsed() }
+}
+
+fn run(workload: &Workload, level: SecurityLevel, runs: usize) -> Row {
+    let config = ProverConfig::for_level(level);
+    let measured: Vec<Measured> = (0..runs).map(|_| (workload.prove)(&config)).collect();
+    let mut times: Vec<Duration> = measured.iter().map(|m| m.prove).collect();
+    times.sort();
+    Row {
+        workload: workload.name,
+        level,
+        queries: config.num_queries,
+        log_blowup: config.log_blowup_factor,
+        pow_bits: config.pow_bits,
+        proof_bytes: measured[0].proof_bytes,
+        prove: times[times.len() / 2],
+    }
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.1}", d.as_secs_f64() * 1000.0)
+}
+
+fn markdown(rows: &[Row], runs: usize) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# murkl-prover {} report\n\n", env!("CARGO_PKG_VERSION")));
+    out.push_str(&format!(
+        "Host: {}-{}, {} run(s) per row, median prove time. Compute units are estimates \
+         ({} per verification + {} per query; {} fit one transaction).\n\n",
+        env::consts::ARCH,
+        env::consts::OS,
+        runs,
+        VERIFY_BASE_CU,
+        QUERY_CU,
+        MAX_TRANSACTION_CU
+    ));
+    for workload in &WORKLOADS {
+        out.push_str(&format!("## {}\n\n{}\n\n", workload.name, workload.description));
+        out.push_str("| Level | Queries | Blowup | PoW bits | Soundness bits ");
+        out.push_str("| Proof bytes | Prove ms | Est. CU | Verification |\n");
+        out.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---|\n");
+        for row in rows.iter().filter(|row| row.workload == workload.name) {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} + {} | {} | {} | {} | {} |\n",
+                row.level.name(),
+                row.queries,
+                1u32 << row.log_blowup,
+                row.pow_bits,
+                row.query_bits(),
+                row.pow_bits,
+                row.proof_bytes,
+                millis(row.prove),
+                row.estimated_cu(),
+                row.verification()
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn json(rows: &[Row], runs: usize) -> String {
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            format!(
+                concat!(
+                    "    {{\"workload\": \"{}\", \"level\": \"{}\", \"queries\": {}, \"log_blowup\": {}, ",
+                    "\"pow_bits\": {}, \"query_bits\": {}, \"proof_bytes\": {}, \"prove_ms\": {}, ",
+                    "\"estimated_cu\": {}, \"verification\": \"{}\"}}"
+                ),
+                row.workload,
+                row.level.name(),
+                row.queries,
+                row.log_blowup,
+                row.pow_bits,
+                row.query_bits(),
+                row.proof_bytes,
+                millis(row.prove),
+                row.estimated_cu(),
+                row.verification()
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"version\": \"{}\",\n  \"host\": \"{}-{}\",\n  \"runs\": {},\n  \"rows\": [\n{}\n  ]\n}}\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::ARCH,
+        env::consts::OS,
+        runs,
+        rows.join(",\n")
+    )
+}
+
+fn usage() -> ! {
+    eprintln!("usage: report [--format markdown|json] [--runs N]");
+    exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut format = Format::Markdown;
+    let mut runs = 3;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("markdown") => Format::Markdown,
+                    Some("json") => Format::Json,
+                    _ => usage(),
+                }
+            }
+            "--runs" => {
+                runs = match args.next().and_then(|n| n.parse().ok()) {
+                    Some(n) if n > 0 => n,
+                    _ => usage(),
+                }
+            }
+            _ => usage(),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for workload in &WORKLOADS {
+        for level in SecurityLevel::ALL {
+            eprintln!("{} at {}...", workload.name, level);
+            rows.push(run(workload, level, runs));
+        }
+    }
+
+    match format {
+        Format::Markdown => print!("{}", markdown(&rows, runs)),
+        Format::Json => print!("{}", json(&rows, runs)),
+    }
+}
@@ -0,0 +1,81 @@
+//! Evaluation cache carried through a single `prove()` call
+//!
+//! Evaluating the trace at the out-of-domain point and a step on both
+//! interpolate over the same canonic trace coset.
+//! The coset's interpolation data ([`BarycentricDomain`]) does not depend on
+//! the evaluation point and costs O(n²) to build, so [`EvaluationCache`]
+//! builds it once per coset and hands it to every phase that needs it.
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::circle::{BarycentricDomain, Coset, SecureCirclePoint};
+use crate::qm31::QM31;
+
+/// Hit/miss counters for an [`EvaluationCache`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups served from the cache
+    pub hits: usize,
+    /// Lookups that had to build the domain
+    pub misses: usize,
+}
+
+/// Interpolation data for canonic cosets, keyed by log size
+#[derive(Clone, Debug, Default)]
+pub struct EvaluationCache {
+    domains: BTreeMap<u32, BarycentricDomain>,
+    stats: CacheStats,
+}
+
+impl EvaluationCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interpolation data for the canonic coset of size 2^log_size
+    pub fn domain(&mut self, log_size: u32) -> &BarycentricDomain {
+        if self.domains.contains_key(&log_size) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        self.domains
+            .entry(log_size)
+            .or_insert_with(|| BarycentricDomain::new(&Coset::canonic(log_size)))
+    }
+
+    /// Barycentric weights for the canonic coset of size 2^log_size at `point`
+    pub fn weights(&mut self, log_size: u32, point: &SecureCirclePoint) -> Vec<QM31> {
+        self.domain(log_size).weights(point)
+    }
+
+    /// Hit/miss counters since creation
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circle::barycentric_weights;
+
+    #[test]
+    fn test_cached_weights_match_uncached() {
+        let mut cache = EvaluationCache::new();
+        let point = SecureCirclePoint::from_t(QM31::from_u32(3, 1, 4, 1));
+
+        for _ in 0..3 {
+            assert_eq!(cache.weights(4, &point), barycentric_weights(&Coset::canonic(4), &point));
+        }
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 1 });
+
+        // A different coset size is a separate entry
+        cache.weights(3, &point);
+        assert_eq!(cache.stats().misses, 2);
+    }
+}
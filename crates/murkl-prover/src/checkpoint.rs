@@ -0,0 +1,243 @@
+//! Checkpoints for resuming an interrupted proof
+//!
+//! A [`Checkpoint`] holds the output of every [`ProveStage`] a prover has
+//! finished, tied by a fingerprint to the statement being proven. The
+//! prover hands it to a caller-provided sink after each stage (to write to
+//! disk, IndexedDB, ...); given it back, the prover restores those stages
+//! instead of running them again and carries on from the first missing one.
+//!
+//! What a stage stores is up to the prover.
+//! [`Prover::prove_resumable`](crate::prover::Prover::prove_resumable)
+//! keeps the extended trace, the Merkle trees, the transcript and the FRI
+//! layers, so its checkpoints hold the witness and are as secret as it is.
+//! The CLI and browser provers only grind for long, so theirs hold the
+//! nonce ([`grind_resumable`](crate::prover::grind_resumable)).
+//!
+//! # Format
+//!
+//! `CHECKPOINT_MAGIC || version (u8) || fingerprint (32) || count (u8)`,
+//! then per stage `stage (u8) || length (u32 LE) || state`, stages in
+//! proving order. Stage bytes are [`ProveStage::ALL`] positions.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+#[cfg(feature = "prove")]
+use crate::m31::{M31, M31_PRIME};
+use crate::merkle::Hash;
+use crate::metrics::ProveStage;
+use crate::types::ProofError;
+
+/// First bytes of a serialized [`Checkpoint`]
+pub const CHECKPOINT_MAGIC: [u8; 8] = *b"MRKLCKPT";
+
+/// Current [`Checkpoint`] format version
+pub const CHECKPOINT_VERSION: u8 = 2;
+
+/// Finished stages of one proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    fingerprint: Hash,
+    stages: Vec<(ProveStage, Vec<u8>)>,
+}
+
+impl Checkpoint {
+    /// Empty checkpoint for the statement `fingerprint` identifies
+    pub fn new(fingerprint: Hash) -> Self {
+        Self { fingerprint, stages: Vec::new() }
+    }
+
+    /// Identifies the statement, configuration and prover the stages
+    /// belong to
+    pub fn fingerprint(&self) -> &Hash {
+        &self.fingerprint
+    }
+
+    /// State `stage` left behind, if it finished
+    pub fn stage(&self, stage: ProveStage) -> Option<&[u8]> {
+        self.stages.iter().find(|(s, _)| *s == stage).map(|(_, state)| state.as_slice())
+    }
+
+    /// The latest stage that finished
+    pub fn last_stage(&self) -> Option<ProveStage> {
+        self.stages.last().map(|(stage, _)| *stage)
+    }
+
+    /// Record the state `stage` left behind, replacing any earlier record
+    pub fn record(&mut self, stage: ProveStage, state: Vec<u8>) {
+        self.stages.retain(|(s, _)| *s != stage);
+        self.stages.push((stage, state));
+        self.stages.sort_by_key(|(s, _)| stage_byte(*s));
+    }
+
+    /// Serialize for storage
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let state_bytes: usize = self.stages.iter().map(|(_, state)| 5 + state.len()).sum();
+        let mut bytes = Vec::with_capacity(CHECKPOINT_MAGIC.len() + 34 + state_bytes);
+        bytes.extend_from_slice(&CHECKPOINT_MAGIC);
+        bytes.push(CHECKPOINT_VERSION);
+        bytes.extend_from_slice(&self.fingerprint);
+        bytes.push(self.stages.len() as u8);
+        for (stage, state) in &self.stages {
+            bytes.push(stage_byte(*stage));
+            bytes.extend_from_slice(&(state.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(state);
+        }
+        bytes
+    }
+
+    /// Parse what [`Checkpoint::to_bytes`] wrote
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = StateReader::new(bytes);
+        if reader.take(CHECKPOINT_MAGIC.len())? != CHECKPOINT_MAGIC {
+            return Err(checkpoint_error("not a prover checkpoint"));
+        }
+        let version = reader.u8()?;
+        if version != CHECKPOINT_VERSION {
+            return Err(checkpoint_error(format!("unsupported version {}", version)));
+        }
+        let mut checkpoint = Self::new(reader.hash()?);
+        for _ in 0..reader.u8()? {
+            let stage = *ProveStage::ALL
+                .get(reader.u8()? as usize)
+                .ok_or_else(|| checkpoint_error("unknown stage"))?;
+            if checkpoint.stage(stage).is_some() {
+                return Err(checkpoint_error(format!("{} recorded twice", stage)));
+            }
+            let len = reader.u32()? as usize;
+            checkpoint.record(stage, reader.take(len)?.to_vec());
+        }
+        reader.finish()?;
+        Ok(checkpoint)
+    }
+}
+
+fn stage_byte(stage: ProveStage) -> u8 {
+    ProveStage::ALL.iter().position(|&s| s == stage).unwrap_or(0) as u8
+}
+
+pub(crate) fn checkpoint_error(msg: impl core::fmt::Display) -> ProofError {
+    ProofError::SerializationError(format!("checkpoint: {}", msg))
+}
+
+/// Length-prefixed M31 values, as [`StateReader::m31s`] reads them
+#[cfg(feature = "prove")]
+pub(crate) fn put_m31s(out: &mut Vec<u8>, values: &[M31]) {
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Cursor over stage state
+pub(crate) struct StateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], ProofError> {
+        if self.bytes.len() < len {
+            return Err(checkpoint_error("truncated"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ProofError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, ProofError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    #[cfg(feature = "prove")]
+    pub(crate) fn u64(&mut self) -> Result<u64, ProofError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn hash(&mut self) -> Result<Hash, ProofError> {
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    #[cfg(feature = "prove")]
+    pub(crate) fn m31(&mut self) -> Result<M31, ProofError> {
+        let value = self.u32()?;
+        if value >= M31_PRIME {
+            return Err(checkpoint_error(format!("{} is not a field element", value)));
+        }
+        Ok(M31::new(value))
+    }
+
+    /// Count, bounded by the bytes left at `item_size` bytes per item, so
+    /// a corrupt length cannot trigger a huge allocation
+    #[cfg(feature = "prove")]
+    pub(crate) fn count(&mut self, item_size: usize) -> Result<usize, ProofError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(item_size) > self.bytes.len() {
+            return Err(checkpoint_error("truncated"));
+        }
+        Ok(count)
+    }
+
+    #[cfg(feature = "prove")]
+    pub(crate) fn m31s(&mut self) -> Result<Vec<M31>, ProofError> {
+        let count = self.count(4)?;
+        (0..count).map(|_| self.m31()).collect()
+    }
+
+    /// Fails if anything is left over
+    pub(crate) fn finish(self) -> Result<(), ProofError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(checkpoint_error(format!("{} trailing bytes", self.bytes.len())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let mut checkpoint = Checkpoint::new([7; 32]);
+        checkpoint.record(ProveStage::Grind, 42u64.to_le_bytes().to_vec());
+        checkpoint.record(ProveStage::Extend, vec![1, 2, 3]);
+        checkpoint.record(ProveStage::Extend, vec![4]);
+        assert_eq!(checkpoint.last_stage(), Some(ProveStage::Grind));
+        assert_eq!(checkpoint.stage(ProveStage::Extend), Some(&[4][..]));
+        assert_eq!(checkpoint.stage(ProveStage::Fri), None);
+
+        let bytes = checkpoint.to_bytes();
+        assert_eq!(Checkpoint::from_bytes(&bytes).unwrap(), checkpoint);
+        assert!(Checkpoint::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Checkpoint::from_bytes(&trailing).is_err());
+        let mut version = bytes;
+        version[CHECKPOINT_MAGIC.len()] = CHECKPOINT_VERSION + 1;
+        assert!(Checkpoint::from_bytes(&version).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "prove")]
+    fn test_state_reader_bounds_counts() {
+        let mut state = Vec::new();
+        put_m31s(&mut state, &[M31::new(5), M31::new(6)]);
+        let mut reader = StateReader::new(&state);
+        assert_eq!(reader.m31s().unwrap(), vec![M31::new(5), M31::new(6)]);
+        assert!(reader.finish().is_ok());
+
+        let huge = u32::MAX.to_le_bytes();
+        assert!(StateReader::new(&huge).m31s().is_err());
+        let unreduced = [1, 0, 0, 0, 0xff, 0xff, 0xff, 0x7f];
+        assert!(StateReader::new(&unreduced).m31s().is_err());
+    }
+}
@@ -4,11 +4,17 @@
 //!
 //! Points on this curve form a cyclic group of order p + 1 = 2^31
 //! This power-of-two order enables efficient FFT operations.
+//!
+//! [`cfft`] and [`icfft`] move an M31 column between values over a canonic
+//! coset and coefficients in the circle-FFT basis of [`crate::poly`], and
+//! [`evaluate_on_domain`] evaluates those coefficients over any coset at
+//! least as large, which is the low-degree extension.
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 
-use crate::m31::M31;
+use crate::m31::{batch::batch_inverse, M31};
+use crate::qm31::QM31;
 use core::fmt;
 
 /// A point on the circle x² + y² = 1 over M31
@@ -174,6 +180,18 @@ pub fn subgroup_generator(log_size: u32) -> CirclePoint {
     CIRCLE_GENERATOR.repeated_double(LOG_CIRCLE_ORDER - log_size)
 }
 
+/// Reverse the low `log_size` bits of `index`
+///
+/// Position i of a bit-reversed evaluation holds the coset point at
+/// `bit_reverse_index(i, log_size)`, which puts the points a fold combines
+/// next to each other.
+pub fn bit_reverse_index(index: usize, log_size: u32) -> usize {
+    if log_size == 0 {
+        return 0;
+    }
+    index.reverse_bits() >> (usize::BITS - log_size)
+}
+
 /// Compute all powers of a generator (domain for polynomial evaluation)
 ///
 /// Returns [G^0, G^1, G^2, ..., G^(2^log_size - 1)]
@@ -218,6 +236,16 @@ impl Coset {
         }
     }
 
+    /// Create the canonic coset of size 2^log_size
+    ///
+    /// This is G_{2n} + <G_n>: the subgroup shifted by half a step, so it
+    /// contains no point with y = 0 and splits into conjugate pairs
+    /// (x, y), (x, -y). Trace columns are interpreted as evaluations over
+    /// this coset.
+    pub fn canonic(log_size: u32) -> Self {
+        Self::shifted(log_size, subgroup_generator(log_size + 1))
+    }
+
     /// Create a coset starting at a different point
     pub fn shifted(log_size: u32, shift: CirclePoint) -> Self {
         Self {
@@ -227,6 +255,16 @@ impl Coset {
         }
     }
 
+    /// Create the commitment coset of size 2^log_size
+    ///
+    /// <G_n> shifted by the group generator, so every point is an odd power
+    /// of G. Canonic trace cosets below 2^30 only hold even powers, so the
+    /// two never meet: trace values are never committed at trace-domain
+    /// points and trace vanishing polynomials are nonzero on the whole coset.
+    pub fn commitment(log_size: u32) -> Self {
+        Self::shifted(log_size, CIRCLE_GENERATOR)
+    }
+
     /// Size of the coset
     pub fn size(&self) -> usize {
         1 << self.log_size
@@ -321,6 +359,401 @@ impl CircleDomain {
     }
 }
 
+/// A point on the circle over the secure field QM31
+///
+/// Out-of-domain sampling happens here: the OODS point is drawn from the
+/// channel as a field element t and mapped onto the circle
+/// ([`Transcript::challenge_circle_point`](crate::prover::Transcript)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecureCirclePoint {
+    pub x: QM31,
+    pub y: QM31,
+}
+
+impl SecureCirclePoint {
+    /// Map t ∈ QM31 to the circle via the stereographic parametrization
+    /// x = (1 - t²) / (1 + t²), y = 2t / (1 + t²)
+    ///
+    /// Undefined for t = ±i (where 1 + t² = 0); see [`Self::try_from_t`].
+    pub fn from_t(t: QM31) -> Self {
+        let t_sq = t.square();
+        let denom_inv = (QM31::ONE + t_sq).inv();
+        Self {
+            x: (QM31::ONE - t_sq) * denom_inv,
+            y: (t + t) * denom_inv,
+        }
+    }
+
+    /// [`Self::from_t`] for a channel draw, or `None` if it must be drawn
+    /// again
+    ///
+    /// Besides t = ±i, this refuses t in M31: those map onto the base
+    /// circle, where every trace and evaluation domain lies, so the point
+    /// would not be out of domain and a vanishing polynomial could be zero
+    /// there. Every other t gives a point off the base circle.
+    pub fn try_from_t(t: QM31) -> Option<Self> {
+        let in_base_field = t == QM31::from_m31(t.a);
+        if in_base_field || (QM31::ONE + t.square()).is_zero() {
+            return None;
+        }
+        Some(Self::from_t(t))
+    }
+
+    /// Embed an M31 circle point
+    pub fn from_base(p: CirclePoint) -> Self {
+        Self {
+            x: QM31::from(p.x),
+            y: QM31::from(p.y),
+        }
+    }
+
+    /// Check if point is on the circle x² + y² = 1
+    pub fn is_on_circle(&self) -> bool {
+        self.x.square() + self.y.square() == QM31::ONE
+    }
+
+    /// Group operation with an M31 point, e.g. one trace step on
+    pub fn add_base(&self, p: CirclePoint) -> Self {
+        let (px, py) = (QM31::from(p.x), QM31::from(p.y));
+        Self {
+            x: self.x * px - self.y * py,
+            y: self.x * py + self.y * px,
+        }
+    }
+}
+
+/// Barycentric weights for evaluating a column over `coset` at `point`
+///
+/// A column of size n over a canonic coset is the restriction of a unique
+/// f(x, y) = f₀(x) + y·f₁(x) with deg f₀, deg f₁ < n/2. Splitting each
+/// conjugate pair and applying univariate barycentric interpolation in x
+/// gives, for the domain point (xᵢ, yᵢ) in x-group j,
+///
+///   wᵢ = ℓⱼ(p.x) · (yᵢ + p.y) / (2yᵢ)
+///
+/// so that f(p) = Σ wᵢ·f(xᵢ, yᵢ). Weights only depend on the domain and the
+/// point, so they are computed once and shared by every column.
+///
+/// # Panics
+/// Panics if the coset does not split into conjugate pairs (e.g. the
+/// standard subgroup, which contains (±1, 0)); use [`Coset::canonic`].
+pub fn barycentric_weights(coset: &Coset, point: &SecureCirclePoint) -> Vec<QM31> {
+    BarycentricDomain::new(coset).weights(point)
+}
+
+/// Point-independent part of [`barycentric_weights`] for one coset
+///
+/// Grouping the domain into conjugate pairs and the Lagrange denominators
+/// cost O(n²); holding them lets every further point cost O(n).
+#[derive(Clone, Debug)]
+pub struct BarycentricDomain {
+    points: Vec<CirclePoint>,
+    /// x-group of each domain point
+    group: Vec<usize>,
+    /// Distinct x-coordinates
+    xs: Vec<M31>,
+    /// 1 / Π_{k≠j} (xⱼ - xₖ)
+    lagrange_inv: Vec<M31>,
+    /// 1 / (2yᵢ)
+    y_factors: Vec<M31>,
+}
+
+impl BarycentricDomain {
+    /// Precompute interpolation data for `coset`
+    ///
+    /// # Panics
+    /// Panics if the coset does not split into conjugate pairs.
+    pub fn new(coset: &Coset) -> Self {
+        let n = coset.size();
+        let points: Vec<CirclePoint> = coset.iter().collect();
+
+        // Group domain points by x-coordinate
+        let mut xs: Vec<M31> = Vec::with_capacity(n / 2);
+        let mut group = Vec::with_capacity(n);
+        if n > 1 {
+            for p in &points {
+                assert!(!p.y.is_zero(), "barycentric domain must not contain y = 0");
+                match xs.iter().position(|x| *x == p.x) {
+                    Some(j) => group.push(j),
+                    None => {
+                        xs.push(p.x);
+                        group.push(xs.len() - 1);
+                    }
+                }
+            }
+            assert_eq!(xs.len() * 2, n, "barycentric domain must split into conjugate pairs");
+        }
+
+        // Lagrange denominators in x: 1 / Π_{k≠j} (xⱼ - xₖ)
+        let lagrange_inv: Vec<M31> = xs
+            .iter()
+            .enumerate()
+            .map(|(j, &xj)| {
+                xs.iter()
+                    .enumerate()
+                    .filter(|&(k, _)| k != j)
+                    .fold(M31::ONE, |acc, (_, &xk)| acc * (xj - xk))
+                    .inv()
+            })
+            .collect();
+
+        let y_factors = if n > 1 { points.iter().map(|p| p.y.double().inv()).collect() } else { Vec::new() };
+
+        Self { points, group, xs, lagrange_inv, y_factors }
+    }
+
+    /// Number of domain points
+    pub fn size(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Weights for evaluating a column over this domain at `point`
+    pub fn weights(&self, point: &SecureCirclePoint) -> Vec<QM31> {
+        let n = self.points.len();
+        if n == 1 {
+            return vec![QM31::ONE];
+        }
+
+        // Point inside the domain: the interpolant is just the column value
+        if let Some(i) = self.points.iter().position(|p| SecureCirclePoint::from_base(*p) == *point) {
+            let mut weights = vec![QM31::ZERO; n];
+            weights[i] = QM31::ONE;
+            return weights;
+        }
+
+        // ℓⱼ(X) = Z(X) / ((X - xⱼ) · Π_{k≠j}(xⱼ - xₖ)) with Z(X) = Π (X - xₖ).
+        // If p.x collides with some xⱼ (but p.y does not), only that group survives.
+        let diffs: Vec<QM31> = self.xs.iter().map(|&xj| point.x - QM31::from(xj)).collect();
+        let lagrange: Vec<QM31> = match diffs.iter().position(|d| d.is_zero()) {
+            Some(hit) => (0..self.xs.len())
+                .map(|j| if j == hit { QM31::ONE } else { QM31::ZERO })
+                .collect(),
+            None => {
+                let z = diffs.iter().fold(QM31::ONE, |acc, d| acc * *d);
+                diffs
+                    .iter()
+                    .zip(self.lagrange_inv.iter())
+                    .map(|(d, &li)| z * d.inv() * QM31::from(li))
+                    .collect()
+            }
+        };
+
+        let two_inv = M31::new(2).inv();
+        self.group
+            .iter()
+            .zip(self.y_factors.iter())
+            .map(|(&j, &y_inv)| {
+                // (yᵢ + p.y) / (2yᵢ) = 1/2 + p.y / (2yᵢ)
+                let y_factor = QM31::from(two_inv) + point.y * QM31::from(y_inv);
+                lagrange[j] * y_factor
+            })
+            .collect()
+    }
+}
+
+/// Evaluate a column at the point its weights were computed for
+pub fn barycentric_eval(evals: &[M31], weights: &[QM31]) -> QM31 {
+    assert_eq!(evals.len(), weights.len(), "column/weights length mismatch");
+    evals
+        .iter()
+        .zip(weights.iter())
+        .fold(QM31::ZERO, |acc, (&v, &w)| acc + w * QM31::from(v))
+}
+
+/// Vanishing polynomial of the canonic coset of size 2^log_size, at x
+///
+/// Doubling maps the canonic coset of size n onto the one of size n/2, and
+/// the canonic coset of size 2 is {(0, ±1)}. Applying the x-doubling map
+/// x ↦ 2x² - 1 (log_size - 1) times therefore sends exactly the coset's
+/// points to zero.
+pub fn canonic_vanishing(log_size: u32, x: M31) -> M31 {
+    assert!(log_size >= 1, "canonic coset needs at least 2 points");
+    (1..log_size).fold(x, |x, _| x.square().double() - M31::ONE)
+}
+
+/// [`canonic_vanishing`] at a secure-field point (e.g. the OODS point)
+pub fn canonic_vanishing_secure(log_size: u32, point: &SecureCirclePoint) -> QM31 {
+    assert!(log_size >= 1, "canonic coset needs at least 2 points");
+    (1..log_size).fold(point.x, |x, _| {
+        let sq = x.square();
+        sq + sq - QM31::ONE
+    })
+}
+
+/// Twiddles for [`cfft`] and [`icfft`] over the canonic coset of size
+/// 2^log_size
+///
+/// Layer 0 splits conjugate points by their y-coordinate and every later
+/// layer splits x ↦ -x pairs, on x-coordinates doubled by π(x) = 2x² - 1
+/// once per layer. The transforms keep values in butterfly order, where
+/// each layer pairs position j with j + h inside blocks of 2h: coefficient
+/// i sits at position `bit_reverse_index(i)`, and the value at coset point
+/// `order[j]` at position j.
+#[derive(Clone, Debug)]
+pub struct FftTwiddles {
+    log_size: u32,
+    /// Coset index held at each butterfly position
+    order: Vec<usize>,
+    /// Twiddle per position of a half block, one layer per level, largest
+    /// first
+    layers: Vec<Vec<M31>>,
+    /// Inverses of `layers`
+    inverse: Vec<Vec<M31>>,
+}
+
+impl FftTwiddles {
+    /// Precompute the twiddles for the canonic coset of size 2^log_size
+    pub fn new(log_size: u32) -> Self {
+        assert!(log_size < LOG_CIRCLE_ORDER, "canonic coset exceeds the circle group");
+        let n = 1usize << log_size;
+
+        // Position j + m/2 pairs with j: the point mirrored in a block of m
+        let mut order = Vec::with_capacity(n);
+        order.push(0);
+        while order.len() < n {
+            let m = 2 * order.len();
+            let mirrored: Vec<usize> = order.iter().map(|&i| m - 1 - i).collect();
+            order.extend(mirrored);
+        }
+
+        let half: Vec<CirclePoint> = Coset::canonic(log_size).iter().take(n / 2).collect();
+        let mut layers: Vec<Vec<M31>> = Vec::with_capacity(log_size as usize);
+        if log_size > 0 {
+            layers.push(order[..n / 2].iter().map(|&i| half[i].y).collect());
+            let mut xs: Vec<M31> = half.iter().map(|p| p.x).collect();
+            for layer in 1..log_size {
+                let h = n >> (layer + 1);
+                layers.push(order[..h].iter().map(|&i| xs[i]).collect());
+                xs = double_xs(&xs[..h]);
+            }
+        }
+        let inverse = layers.iter().map(|layer| batch_inverse(layer)).collect();
+
+        Self { log_size, order, layers, inverse }
+    }
+
+    /// Log2 of the coset size
+    pub fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    /// Size of the coset
+    pub fn size(&self) -> usize {
+        1 << self.log_size
+    }
+}
+
+/// Circle FFT: coefficients to values over the canonic coset `twiddles`
+/// was built for, in natural coset order
+///
+/// # Panics
+/// Panics unless there is one coefficient per coset point.
+pub fn cfft(coeffs: &[M31], twiddles: &FftTwiddles) -> Vec<M31> {
+    let n = twiddles.size();
+    assert_eq!(coeffs.len(), n, "circle FFT needs one coefficient per coset point");
+    let mut values: Vec<M31> = (0..n)
+        .map(|j| coeffs[bit_reverse_index(j, twiddles.log_size)])
+        .collect();
+    butterflies(&mut values, twiddles.layers.iter().rev());
+
+    let mut out = vec![M31::ZERO; n];
+    for (value, &i) in values.into_iter().zip(&twiddles.order) {
+        out[i] = value;
+    }
+    out
+}
+
+/// Inverse circle FFT: values over the canonic coset `twiddles` was built
+/// for, in natural order, to coefficients
+///
+/// # Panics
+/// Panics unless there is one value per coset point.
+pub fn icfft(evals: &[M31], twiddles: &FftTwiddles) -> Vec<M31> {
+    let n = twiddles.size();
+    assert_eq!(evals.len(), n, "inverse circle FFT needs one value per coset point");
+    let mut values: Vec<M31> = twiddles.order.iter().map(|&i| evals[i]).collect();
+    for layer in &twiddles.inverse {
+        let h = layer.len();
+        for block in values.chunks_exact_mut(2 * h) {
+            let (lo, hi) = block.split_at_mut(h);
+            for ((a, b), &t) in lo.iter_mut().zip(hi.iter_mut()).zip(layer) {
+                let (u, v) = (*a, *b);
+                *a = u + v;
+                *b = (u - v) * t;
+            }
+        }
+    }
+
+    // Every layer doubled the values
+    let inv_n = M31::new(n as u32).inv();
+    (0..n)
+        .map(|i| values[bit_reverse_index(i, twiddles.log_size)] * inv_n)
+        .collect()
+}
+
+/// Evaluate circle-FFT coefficients at every point of `coset`, in coset
+/// order
+///
+/// The coset need not be canonic: the prover's commitment coset is not
+/// closed under conjugation. Points half a coset apart are antipodes
+/// instead, so f = f₀(x) + y·f₁(x) is evaluated as two x-FFTs over the
+/// whole coset, twice the work of [`cfft`].
+///
+/// # Panics
+/// Panics unless the coefficient count is a power of two no larger than
+/// the coset.
+pub fn evaluate_on_domain(coeffs: &[M31], coset: &Coset) -> Vec<M31> {
+    assert!(
+        coeffs.len().is_power_of_two() && coeffs.len() <= coset.size(),
+        "coset too small for the coefficients"
+    );
+    let n = coset.size();
+    let points: Vec<CirclePoint> = coset.iter().collect();
+
+    let mut layers: Vec<Vec<M31>> = Vec::with_capacity(coset.log_size as usize);
+    let mut xs: Vec<M31> = points[..n / 2].iter().map(|p| p.x).collect();
+    while !xs.is_empty() {
+        let next = double_xs(&xs[..xs.len() / 2]);
+        layers.push(xs);
+        xs = next;
+    }
+
+    let mut f0 = vec![M31::ZERO; n];
+    let mut f1 = vec![M31::ZERO; n];
+    for (i, &c) in coeffs.iter().enumerate() {
+        let part = if i % 2 == 0 { &mut f0 } else { &mut f1 };
+        part[bit_reverse_index(i / 2, coset.log_size)] = c;
+    }
+    butterflies(&mut f0, layers.iter().rev());
+    butterflies(&mut f1, layers.iter().rev());
+
+    points
+        .iter()
+        .zip(f0.into_iter().zip(f1))
+        .map(|(p, (a, b))| a + p.y * b)
+        .collect()
+}
+
+/// Forward butterflies, smallest blocks first: (a, b) ↦ (a + t·b, a - t·b)
+fn butterflies<'a>(values: &mut [M31], layers: impl Iterator<Item = &'a Vec<M31>>) {
+    for layer in layers {
+        let h = layer.len();
+        for block in values.chunks_exact_mut(2 * h) {
+            let (lo, hi) = block.split_at_mut(h);
+            for ((a, b), &t) in lo.iter_mut().zip(hi.iter_mut()).zip(layer) {
+                let tb = t * *b;
+                let u = *a;
+                *a = u + tb;
+                *b = u - tb;
+            }
+        }
+    }
+}
+
+fn double_xs(xs: &[M31]) -> Vec<M31> {
+    xs.iter().map(|x| x.square().double() - M31::ONE).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,4 +960,118 @@ mod tests {
         assert_eq!(ab.x.value(), ba.x.value());
         assert_eq!(ab.y.value(), ba.y.value());
     }
+
+    /// f(x, y) = f₀(x) + y·f₁(x) with deg f₀, f₁ < 4 (fits a size-8 domain)
+    fn sample_poly(x: QM31, y: QM31) -> QM31 {
+        let c = |v: u32| QM31::from(M31::new(v));
+        let f0 = c(3) + c(5) * x + c(7) * x.square() + c(11) * x.pow(3);
+        let f1 = c(2) + c(13) * x + x.pow(3);
+        f0 + y * f1
+    }
+
+    #[test]
+    fn test_commitment_coset_disjoint_from_trace_domain() {
+        for log_trace in 1..6 {
+            let trace: Vec<_> = Coset::canonic(log_trace).iter().collect();
+            for p in Coset::commitment(log_trace + 4).iter() {
+                assert!(p.is_on_circle());
+                assert!(!trace.contains(&p));
+                assert!(!canonic_vanishing(log_trace, p.x).is_zero());
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonic_vanishing_zero_on_coset() {
+        for log_size in 1..8 {
+            for p in Coset::canonic(log_size).iter() {
+                assert!(canonic_vanishing(log_size, p.x).is_zero());
+                let secure = SecureCirclePoint::from_base(p);
+                assert!(canonic_vanishing_secure(log_size, &secure).is_zero());
+            }
+        }
+        let oods = SecureCirclePoint::from_t(QM31::from_u32(5, 6, 7, 8));
+        assert!(!canonic_vanishing_secure(10, &oods).is_zero());
+    }
+
+    #[test]
+    fn test_canonic_coset_conjugate_pairs() {
+        let coset = Coset::canonic(3);
+        let points: Vec<_> = coset.iter().collect();
+        for p in &points {
+            assert!(p.is_on_circle());
+            assert!(!p.y.is_zero());
+            assert!(points.contains(&p.neg()));
+        }
+    }
+
+    #[test]
+    fn test_secure_point_from_t_on_circle() {
+        let p = SecureCirclePoint::from_t(QM31::from_u32(123, 456, 789, 10));
+        assert!(p.is_on_circle());
+    }
+
+    #[test]
+    fn test_try_from_t_stays_off_base_circle() {
+        // i² = -1, so the parametrization has no point for t = ±i
+        let i = QM31::from_u32(0, 1, 0, 0);
+        assert_eq!(SecureCirclePoint::try_from_t(i), None);
+        assert_eq!(SecureCirclePoint::try_from_t(-i), None);
+        // An M31 t gives a base point, possibly one of the trace domain's
+        assert_eq!(SecureCirclePoint::try_from_t(QM31::from_u32(5, 0, 0, 0)), None);
+
+        let t = QM31::from_u32(123, 456, 789, 10);
+        let p = SecureCirclePoint::try_from_t(t).unwrap();
+        assert_eq!(p, SecureCirclePoint::from_t(t));
+        assert!(p.is_on_circle());
+        for log_size in 1..=10 {
+            assert!(!canonic_vanishing_secure(log_size, &p).is_zero());
+        }
+    }
+
+    #[test]
+    fn test_barycentric_matches_direct_evaluation() {
+        let coset = Coset::canonic(3);
+        let evals: Vec<M31> = coset
+            .iter()
+            .map(|p| sample_poly(QM31::from(p.x), QM31::from(p.y)).a)
+            .collect();
+
+        let point = SecureCirclePoint::from_t(QM31::from_u32(98765, 4321, 1, 77));
+        let weights = barycentric_weights(&coset, &point);
+        let result = barycentric_eval(&evals, &weights);
+
+        assert_eq!(result, sample_poly(point.x, point.y));
+    }
+
+    #[test]
+    fn test_barycentric_at_domain_point() {
+        let coset = Coset::canonic(4);
+        let evals: Vec<M31> = (0..16).map(|i| M31::new(i * 31 + 7)).collect();
+
+        for i in [0usize, 5, 15] {
+            let point = SecureCirclePoint::from_base(coset.at(i));
+            let weights = barycentric_weights(&coset, &point);
+            assert_eq!(barycentric_eval(&evals, &weights), QM31::from(evals[i]));
+        }
+    }
+
+    #[test]
+    fn test_cfft_matches_circle_poly() {
+        use crate::poly::CirclePoly;
+
+        for log_size in 0..6 {
+            let twiddles = FftTwiddles::new(log_size);
+            let evals: Vec<M31> = (0..1u32 << log_size).map(|i| M31::new(i * i * 97 + 5)).collect();
+            let coeffs = icfft(&evals, &twiddles);
+            let poly = CirclePoly::interpolate_base(&evals);
+            assert_eq!(poly.coeffs(), coeffs.iter().map(|&c| QM31::from(c)).collect::<Vec<_>>());
+            assert_eq!(cfft(&coeffs, &twiddles), evals);
+
+            for coset in [Coset::canonic(log_size + 2), Coset::commitment(log_size + 1)] {
+                let lifted: Vec<M31> = poly.evaluate_on(&coset).into_iter().map(|v| v.a).collect();
+                assert_eq!(evaluate_on_domain(&coeffs, &coset), lifted);
+            }
+        }
+    }
 }
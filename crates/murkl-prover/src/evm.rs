@@ -0,0 +1,223 @@
+//! EVM export for cross-chain claims
+//!
+//! Encodes a [`ProofBundle`] plus the claim context (Merkle root, recipient)
+//! as Solidity ABI call data, and generates a reference verifier contract
+//! that tracks spent nullifiers. The STARK check itself is left to an
+//! `_verifyStark` hook in the generated contract.
+//!
+//! Enabled with the `evm` feature.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+use crate::hash::{keccak_hash, Hash32};
+use crate::m31::M31_PRIME;
+use crate::types::{ProofBundle, ProofError};
+
+/// Solidity signature of the generated contract's claim entry point
+pub const CLAIM_SIGNATURE: &str = "claim(bytes,bytes32,bytes32,bytes32,uint32,address)";
+
+/// A claim as consumed by an EVM verifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvmClaim {
+    /// Serialized proof (on-chain verifier format)
+    pub proof: Vec<u8>,
+    pub commitment: Hash32,
+    pub nullifier: Hash32,
+    /// Pool root the proof was generated against
+    pub merkle_root: Hash32,
+    pub leaf_index: u32,
+    /// 20-byte EVM recipient address
+    pub recipient: [u8; 20],
+}
+
+impl EvmClaim {
+    /// Build a claim from a proof bundle and the EVM-side claim context
+    pub fn from_bundle(
+        bundle: &ProofBundle,
+        merkle_root: Hash32,
+        recipient: [u8; 20],
+    ) -> Result<Self, ProofError> {
+        bundle.check_version()?;
+        Ok(Self {
+            proof: bundle.proof.clone(),
+            commitment: bundle.commitment,
+            nullifier: bundle.nullifier,
+            merkle_root,
+            leaf_index: bundle.leaf_index,
+            recipient,
+        })
+    }
+
+    /// ABI-encoded arguments `(bytes, bytes32, bytes32, bytes32, uint32, address)`
+    pub fn abi_encode(&self) -> Vec<u8> {
+        const HEAD_WORDS: usize = 6;
+        let mut out = Vec::with_capacity(32 * (HEAD_WORDS + 1) + padded_len(self.proof.len()));
+
+        // Head: dynamic `bytes` is an offset to its tail
+        out.extend_from_slice(&uint_word((32 * HEAD_WORDS) as u64));
+        out.extend_from_slice(&self.commitment);
+        out.extend_from_slice(&self.nullifier);
+        out.extend_from_slice(&self.merkle_root);
+        out.extend_from_slice(&uint_word(self.leaf_index as u64));
+        let mut address = [0u8; 32];
+        address[12..].copy_from_slice(&self.recipient);
+        out.extend_from_slice(&address);
+
+        // Tail: length, then data right-padded to a word boundary
+        out.extend_from_slice(&uint_word(self.proof.len() as u64));
+        out.extend_from_slice(&self.proof);
+        out.resize(out.len() + padded_len(self.proof.len()) - self.proof.len(), 0);
+        out
+    }
+
+    /// Call data for [`CLAIM_SIGNATURE`]: selector followed by the encoded arguments
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut out = function_selector(CLAIM_SIGNATURE).to_vec();
+        out.extend_from_slice(&self.abi_encode());
+        out
+    }
+}
+
+/// First four bytes of keccak256 of a function signature
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = keccak_hash(&[signature.as_bytes()]);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn uint_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(32) * 32
+}
+
+/// Reference Solidity verifier for [`EvmClaim`] call data.
+///
+/// The contract is abstract: deployers implement `_verifyStark` (or delegate
+/// it to a STARK verifier contract), `_isKnownRoot` and `_payout`.
+/// Commitments and nullifiers are the Poseidon2 digests of
+/// [`crate::hash::pq_commitment`] and [`crate::hash::pq_nullifier`], which
+/// the contract never recomputes: the proof binds them.
+pub fn solidity_verifier_stub(contract_name: &str) -> String {
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by murkl-prover (evm feature). Claim selector: 0x{selector}
+pragma solidity ^0.8.20;
+
+abstract contract {name} {{
+    uint32 public constant M31_PRIME = {prime};
+
+    mapping(bytes32 => bool) public nullifierUsed;
+
+    event Claimed(bytes32 indexed nullifier, bytes32 merkleRoot, uint32 leafIndex, address recipient);
+
+    error NullifierUsed();
+    error UnknownRoot();
+    error InvalidProof();
+
+    function {signature_name}(
+        bytes calldata proof,
+        bytes32 commitment,
+        bytes32 nullifier,
+        bytes32 merkleRoot,
+        uint32 leafIndex,
+        address recipient
+    ) external {{
+        if (nullifierUsed[nullifier]) revert NullifierUsed();
+        if (!_isKnownRoot(merkleRoot)) revert UnknownRoot();
+        if (!_verifyStark(proof, commitment, nullifier, merkleRoot, recipient)) revert InvalidProof();
+        nullifierUsed[nullifier] = true;
+        emit Claimed(nullifier, merkleRoot, leafIndex, recipient);
+        _payout(commitment, leafIndex, recipient);
+    }}
+
+    /// Circle STARK verification of `proof` bound to the public inputs.
+    function _verifyStark(
+        bytes calldata proof,
+        bytes32 commitment,
+        bytes32 nullifier,
+        bytes32 merkleRoot,
+        address recipient
+    ) internal view virtual returns (bool);
+
+    /// Whether `root` is a current (or recent) root of the deposit tree.
+    function _isKnownRoot(bytes32 root) internal view virtual returns (bool);
+
+    /// Release the deposit at `leafIndex` to `recipient`.
+    function _payout(bytes32 commitment, uint32 leafIndex, address recipient) internal virtual;
+}}
+"#,
+        selector = hex::encode(function_selector(CLAIM_SIGNATURE)),
+        name = contract_name,
+        prime = M31_PRIME,
+        signature_name = &CLAIM_SIGNATURE[..CLAIM_SIGNATURE.find('(').unwrap_or(0)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{pq_commitment, pq_nullifier};
+    use crate::m31::M31;
+
+    fn claim(proof_len: usize) -> EvmClaim {
+        let bundle = ProofBundle::new(
+            (0..proof_len).map(|i| i as u8).collect(),
+            pq_commitment(M31::new(7), M31::new(11)),
+            pq_nullifier(M31::new(11), 3),
+            3,
+        );
+        EvmClaim::from_bundle(&bundle, [0xaa; 32], [0x11; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_function_selector() {
+        assert_eq!(function_selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+    }
+
+    #[test]
+    fn test_abi_layout() {
+        let c = claim(40);
+        let encoded = c.abi_encode();
+        let word = |i: usize| &encoded[32 * i..32 * (i + 1)];
+
+        assert_eq!(encoded.len(), 32 * 7 + 64);
+        assert_eq!(word(0), &uint_word(192)[..]);
+        assert_eq!(word(1), &c.commitment[..]);
+        assert_eq!(word(2), &c.nullifier[..]);
+        assert_eq!(word(3), &[0xaa; 32][..]);
+        assert_eq!(word(4), &uint_word(3)[..]);
+        assert_eq!(&word(5)[..12], &[0u8; 12][..]);
+        assert_eq!(&word(5)[12..], &[0x11; 20][..]);
+        assert_eq!(word(6), &uint_word(40)[..]);
+        assert_eq!(&encoded[224..264], &c.proof[..]);
+        assert!(encoded[264..].iter().all(|&b| b == 0));
+
+        // Word-aligned proofs get no padding
+        assert_eq!(claim(64).abi_encode().len(), 32 * 7 + 64);
+
+        let calldata = c.calldata();
+        assert_eq!(calldata[..4], function_selector(CLAIM_SIGNATURE));
+        assert_eq!(calldata[4..], encoded[..]);
+    }
+
+    #[test]
+    fn test_stub_matches_claim_encoding() {
+        let stub = solidity_verifier_stub("MurklClaims");
+        assert!(stub.contains("abstract contract MurklClaims {"));
+        assert!(stub.contains(&format!("Claim selector: 0x{}", hex::encode(function_selector(CLAIM_SIGNATURE)))));
+        assert!(stub.contains("function claim(\n        bytes calldata proof,"));
+        assert!(stub.contains(&format!("M31_PRIME = {};", M31_PRIME)));
+    }
+
+    #[test]
+    fn test_rejects_unknown_bundle_version() {
+        let mut bundle = ProofBundle::new(vec![1, 2, 3], [0; 32], [0; 32], 0);
+        bundle.version = 0;
+        assert!(EvmClaim::from_bundle(&bundle, [0; 32], [0; 20]).is_err());
+    }
+}
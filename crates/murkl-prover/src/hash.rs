@@ -1,16 +1,25 @@
 //! Hash functions for Murkl protocol
 //!
-//! Provides keccak256-based hash functions for:
+//! Provides hash functions for:
 //! - Commitments (identifier + secret → commitment)
 //! - Nullifiers (secret + leaf_index → nullifier)
-//! - M31 field element derivation
+//! - M31 field element derivation (keccak)
 //!
 //! All hashing uses domain separation for security.
+//!
+//! [`HashKind`] selects keccak, Poseidon2 or (with `poseidon-bn254`) BN254
+//! Poseidon for proof commitments.
+//!
+//! The 32-byte commitments and nullifiers the pool stores ([`pq_commitment`],
+//! [`pq_nullifier`], [`pq_epoch_nullifier`]) are Poseidon2 digests: a STARK
+//! can constrain them row by row (`air::poseidon2`), which keccak would need
+//! thousands of bit columns for.
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{format, string::String, vec::Vec};
 
 use crate::m31::{M31, M31_PRIME};
+use crate::poseidon2::{hash_tagged, COMMITMENT_TAG, NULLIFIER_TAG};
 use sha3::{Digest, Keccak256};
 
 /// A 32-byte hash output
@@ -37,6 +46,57 @@ pub fn keccak_hash(inputs: &[&[u8]]) -> Hash32 {
     hash
 }
 
+/// Hash behind a proof's Merkle commitments and Fiat-Shamir channel
+///
+/// Written as the first byte of the on-chain proof format. Poseidon2 keeps
+/// every digest a tuple of M31 elements, so an AIR can recompute Merkle
+/// paths; keccak stays the default because Solana runs it as a syscall.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HashKind {
+    #[default]
+    Keccak = 0,
+    /// [`crate::poseidon2`] sponge, with its compression for Merkle nodes
+    Poseidon2 = 1,
+    /// `crate::poseidon_bn254`; cheap on-chain where the verifier was built
+    /// with the `sol_poseidon` syscall, and rejected everywhere else
+    #[cfg(feature = "poseidon-bn254")]
+    PoseidonBn254 = 2,
+}
+
+impl HashKind {
+    /// Parse a proof header byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Keccak),
+            1 => Some(Self::Poseidon2),
+            #[cfg(feature = "poseidon-bn254")]
+            2 => Some(Self::PoseidonBn254),
+            _ => None,
+        }
+    }
+
+    /// Hash the concatenation of `inputs`
+    pub fn hash(self, inputs: &[&[u8]]) -> Hash32 {
+        match self {
+            Self::Keccak => keccak_hash(inputs),
+            Self::Poseidon2 => crate::poseidon2::hash(inputs),
+            #[cfg(feature = "poseidon-bn254")]
+            Self::PoseidonBn254 => crate::poseidon_bn254::hash(inputs),
+        }
+    }
+
+    /// Parent of two Merkle nodes
+    pub fn hash_node(self, left: &Hash32, right: &Hash32) -> Hash32 {
+        match self {
+            Self::Keccak => keccak_hash(&[left, right]),
+            Self::Poseidon2 => crate::poseidon2::compress(left, right),
+            #[cfg(feature = "poseidon-bn254")]
+            Self::PoseidonBn254 => crate::poseidon_bn254::compress(left, right),
+        }
+    }
+}
+
 /// Convert first 4 bytes of hash to M31 element
 #[inline]
 pub fn hash_to_m31(hash: &Hash32) -> M31 {
@@ -52,6 +112,65 @@ pub fn hash_password(password: &str) -> M31 {
     hash_to_m31(&hash)
 }
 
+/// Password stretching that runs in bounded steps
+///
+/// Starts from the [`hash_password`] digest and applies `rounds` keccak
+/// rounds (domain `murkl_password_stretch_v1`), so with zero rounds the
+/// secret equals [`hash_password`]. Callers drive it with [`step`] so a UI
+/// thread can report progress and abandon the work between chunks.
+///
+/// [`step`]: PasswordStretch::step
+#[derive(Clone, Debug)]
+pub struct PasswordStretch {
+    state: Hash32,
+    rounds: u32,
+    done: u32,
+}
+
+impl PasswordStretch {
+    pub fn new(password: &str, rounds: u32) -> Self {
+        Self {
+            state: keccak_hash(&[b"murkl_password_v1", password.as_bytes()]),
+            rounds,
+            done: 0,
+        }
+    }
+
+    /// Run up to `max_rounds` more rounds; returns true once all are done
+    pub fn step(&mut self, max_rounds: u32) -> bool {
+        let end = self.done.saturating_add(max_rounds).min(self.rounds);
+        for round in self.done..end {
+            self.state = keccak_hash(&[b"murkl_password_stretch_v1", &round.to_le_bytes(), &self.state]);
+        }
+        self.done = end;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done == self.rounds
+    }
+
+    pub fn rounds_done(&self) -> u32 {
+        self.done
+    }
+
+    pub fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    /// The derived secret, once every round has run
+    pub fn secret(&self) -> Option<M31> {
+        self.is_finished().then(|| hash_to_m31(&self.state))
+    }
+}
+
+/// Stretch a password in one go (see [`PasswordStretch`])
+pub fn stretch_password(password: &str, rounds: u32) -> M31 {
+    let mut stretch = PasswordStretch::new(password, rounds);
+    stretch.step(rounds);
+    hash_to_m31(&stretch.state)
+}
+
 /// Hash an identifier to M31 (case-insensitive)
 ///
 /// Domain: `murkl_identifier_v1`
@@ -61,6 +180,53 @@ pub fn hash_identifier(identifier: &str) -> M31 {
     hash_to_m31(&hash)
 }
 
+/// Identifier for a phone number or email address that does not reveal it
+///
+/// Domain: `murkl_blind_identifier_v1`. `channel` is `phone:<number>` or
+/// `email:<address>`; phone numbers keep only `+` and digits, emails are
+/// trimmed and lowercased, like [`hash_identifier`]. `otp_session_token` is
+/// the salt the relayer's verification service hands out once the address
+/// passes an OTP check, and the service attests to the result: without the
+/// token, a list of phone numbers cannot be hashed to find the recipient.
+///
+/// Returns `blind:<hex>`, used in place of the raw identifier everywhere
+/// (commitment, proof); `None` for an empty token or a malformed channel.
+pub fn blind_identifier(otp_session_token: &[u8], channel: &str) -> Option<String> {
+    if otp_session_token.is_empty() {
+        return None;
+    }
+    let normalized = normalize_channel(channel)?;
+    let hash = keccak_hash(&[
+        b"murkl_blind_identifier_v1",
+        &(otp_session_token.len() as u32).to_le_bytes(),
+        otp_session_token,
+        normalized.as_bytes(),
+    ]);
+    Some(format!("blind:{}", hex::encode(hash)))
+}
+
+/// `channel` as [`blind_identifier`] hashes it
+fn normalize_channel(channel: &str) -> Option<String> {
+    let (kind, address) = channel.split_once(':')?;
+    match kind.trim().to_lowercase().as_str() {
+        "phone" => {
+            let digits: String = address.chars().filter(char::is_ascii_digit).collect();
+            let plus = address.trim_start().starts_with('+');
+            let valid = plus
+                && (8..=15).contains(&digits.len())
+                && address.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c));
+            valid.then(|| format!("phone:+{}", digits))
+        }
+        "email" => {
+            let address = address.trim().to_lowercase();
+            let (user, domain) = address.split_once('@')?;
+            let valid = !user.is_empty() && domain.contains('.') && !domain.contains('@');
+            valid.then(|| format!("email:{}", address))
+        }
+        _ => None,
+    }
+}
+
 /// Compute M31 commitment from id_hash and secret
 ///
 /// Domain: `murkl_m31_commitment`
@@ -85,25 +251,56 @@ pub fn m31_nullifier(secret: M31, leaf_index: u32) -> M31 {
     hash_to_m31(&hash)
 }
 
-/// Compute full 32-byte commitment (for on-chain storage)
+/// 32-byte commitment a deposit stores
 ///
-/// Domain: `murkl_commitment_v1`
+/// The Poseidon2 digest of [`COMMITMENT_TAG`] with the identifier hash in
+/// lane 1 and the secret in lane 2 ([`hash_tagged`]).
 pub fn pq_commitment(id_hash: M31, secret: M31) -> Hash32 {
-    keccak_hash(&[
-        b"murkl_m31_hash_v1",
-        &id_hash.to_le_bytes(),
-        &secret.to_le_bytes(),
-    ])
+    hash_tagged(COMMITMENT_TAG, [id_hash, secret, M31::ZERO])
 }
 
-/// Compute full 32-byte nullifier (for on-chain double-spend prevention)
+/// 32-byte nullifier of a one-off deposit (for on-chain double-spend
+/// prevention)
 ///
-/// Domain: `murkl_nullifier_v1`  
+/// The [`pq_epoch_nullifier`] of epoch 0: a deposit paid out at once is
+/// claimed in its first epoch.
 pub fn pq_nullifier(secret: M31, leaf_index: u32) -> Hash32 {
-    let mut data = [0u8; 8];
-    data[0..4].copy_from_slice(&secret.to_le_bytes());
-    data[4..8].copy_from_slice(&leaf_index.to_le_bytes());
-    keccak_hash(&[&data])
+    pq_epoch_nullifier(secret, leaf_index, 0)
+}
+
+/// Bytes of a subscription nullifier that carry its epoch (u32 LE)
+pub const EPOCH_NULLIFIER_TAG: core::ops::Range<usize> = 28..32;
+
+/// M31 nullifier for one epoch of a subscription deposit
+///
+/// Domain: `murkl_m31_epoch_nullifier`
+pub fn m31_epoch_nullifier(secret: M31, leaf_index: u32, epoch: u32) -> M31 {
+    let hash = keccak_hash(&[
+        b"murkl_m31_epoch_nullifier",
+        &secret.to_le_bytes(),
+        &leaf_index.to_le_bytes(),
+        &epoch.to_le_bytes(),
+    ]);
+    hash_to_m31(&hash)
+}
+
+/// 32-byte nullifier for one epoch of a subscription deposit
+///
+/// The Poseidon2 digest of [`NULLIFIER_TAG`] with the leaf index, the secret
+/// and the epoch in lanes 1 to 3, as for [`pq_commitment`]. The last four
+/// bytes are replaced by the epoch in the clear, so the pool can validate
+/// which epoch a proven nullifier claims without learning the secret.
+pub fn pq_epoch_nullifier(secret: M31, leaf_index: u32, epoch: u32) -> Hash32 {
+    let mut nullifier = hash_tagged(NULLIFIER_TAG, [M31::new(leaf_index), secret, M31::new(epoch)]);
+    nullifier[EPOCH_NULLIFIER_TAG].copy_from_slice(&epoch.to_le_bytes());
+    nullifier
+}
+
+/// Epoch carried by a [`pq_epoch_nullifier`]
+pub fn nullifier_epoch(nullifier: &Hash32) -> u32 {
+    let mut epoch = [0u8; 4];
+    epoch.copy_from_slice(&nullifier[EPOCH_NULLIFIER_TAG]);
+    u32::from_le_bytes(epoch)
 }
 
 /// Hash two M31 values together (generic)
@@ -147,6 +344,21 @@ mod tests {
         assert!(secret1.value() < M31_PRIME);
     }
 
+    #[test]
+    fn test_password_stretch_chunked() {
+        assert_eq!(stretch_password("password123", 0), hash_password("password123"));
+
+        let mut stretch = PasswordStretch::new("password123", 1000);
+        assert_eq!(stretch.secret(), None);
+        assert!(!stretch.step(300));
+        assert_eq!(stretch.rounds_done(), 300);
+        while !stretch.step(300) {}
+        assert_eq!(stretch.rounds_done(), 1000);
+        assert_eq!(stretch.secret(), Some(stretch_password("password123", 1000)));
+        assert_ne!(stretch.secret(), Some(stretch_password("password123", 999)));
+        assert_ne!(stretch.secret(), Some(hash_password("password123")));
+    }
+
     #[test]
     fn test_hash_identifier_case_insensitive() {
         let id1 = hash_identifier("@Alice");
@@ -157,6 +369,27 @@ mod tests {
         assert_eq!(id2, id3);
     }
 
+    #[test]
+    fn test_blind_identifier() {
+        let token = b"otp-session-1";
+        let phone = blind_identifier(token, "phone:+1 (555) 010-2030").unwrap();
+        assert_eq!(blind_identifier(token, "Phone:+15550102030"), Some(phone.clone()));
+        assert!(phone.starts_with("blind:") && !phone.contains("5550102030"));
+        assert_ne!(blind_identifier(b"otp-session-2", "phone:+15550102030"), Some(phone.clone()));
+
+        let email = blind_identifier(token, "email: Alice@Example.com ").unwrap();
+        assert_eq!(blind_identifier(token, "email:alice@example.com"), Some(email.clone()));
+        assert_ne!(email, phone);
+        // Already lowercase, so hash_identifier takes it as is
+        assert_eq!(email, email.to_lowercase());
+
+        assert_eq!(blind_identifier(b"", "email:alice@example.com"), None);
+        assert_eq!(blind_identifier(token, "phone:15550102030"), None);
+        assert_eq!(blind_identifier(token, "phone:+1555#0102030"), None);
+        assert_eq!(blind_identifier(token, "email:alice"), None);
+        assert_eq!(blind_identifier(token, "@alice"), None);
+    }
+
     #[test]
     fn test_m31_commitment() {
         let id = M31::new(12345);
@@ -178,6 +411,18 @@ mod tests {
         assert_ne!(nullifier1, nullifier2);
     }
 
+    #[test]
+    fn test_commitment_and_nullifier_are_separated() {
+        let secret = M31::new(12345);
+        assert_ne!(pq_nullifier(secret, 0), pq_nullifier(secret, 1));
+        // Same lanes, different tag
+        assert_ne!(pq_commitment(M31::new(7), secret), hash_tagged(NULLIFIER_TAG, [M31::new(7), secret, M31::ZERO]));
+        // Every limb is a canonical M31
+        for limb in pq_commitment(M31::new(7), secret).chunks_exact(4) {
+            assert!(u32::from_le_bytes(limb.try_into().unwrap()) < M31_PRIME);
+        }
+    }
+
     #[test]
     fn test_pq_commitment() {
         let id = M31::new(100);
@@ -196,6 +441,19 @@ mod tests {
 
         assert_eq!(nullifier.len(), 32);
     }
+
+    #[test]
+    fn test_epoch_nullifier() {
+        let secret = M31::new(100);
+        let epoch3 = pq_epoch_nullifier(secret, 5, 3);
+
+        assert_eq!(nullifier_epoch(&epoch3), 3);
+        assert_ne!(epoch3, pq_epoch_nullifier(secret, 5, 4));
+        assert_ne!(epoch3, pq_epoch_nullifier(secret, 6, 3));
+        assert_ne!(epoch3, pq_nullifier(secret, 5));
+        assert_eq!(pq_nullifier(secret, 5), pq_epoch_nullifier(secret, 5, 0));
+        assert_ne!(m31_epoch_nullifier(secret, 5, 3), m31_epoch_nullifier(secret, 5, 4));
+    }
 }
 
 #[test]
@@ -23,21 +23,43 @@
 //! # Features
 //!
 //! - `std` - Enable standard library features (default)
+//! - `prove` - Proof generation; implies `verify` (default)
+//! - `verify` - Proof verification without the prover; implies `hash-only`
+//! - `hash-only` - Field, hash and Merkle primitives only
 //! - `simd` - Enable SIMD optimizations for M31 field operations
 //! - `wasm` - Enable WebAssembly support
+//! - `evm` - ABI-encoded claim export and a reference Solidity verifier
+//! - `poseidon-bn254` - The BN254 Poseidon proof hash, for verifiers with the
+//!   `sol_poseidon` syscall (requires `std`)
+//! - `testing` - Labeled corruptions of on-chain proofs for negative tests
 //!
 //! # Components
 //!
 //! - [`m31`] - Mersenne-31 field implementation with optional SIMD
 //! - [`qm31`] - QM31 quartic extension field (M31[i][j])
 //! - [`hash`] - Keccak256-based hash functions (commitments, nullifiers)
+//! - [`poseidon2`] - Poseidon2 over M31, the field-native proof hash option
+//! - `poseidon_bn254` - Poseidon over BN254 (`poseidon-bn254` feature)
 //! - [`circle`] - Circle group operations for Circle STARKs
+//! - [`poly`] - Circle polynomials: CFFT interpolation and vanishing division
 //! - [`merkle`] - Keccak256-based Merkle tree
 //! - [`fri`] - FRI (Fast Reed-Solomon IOPP) protocol
+//! - [`cache`] - Interpolation data shared across proving phases
 //! - [`air`] - Algebraic Intermediate Representation constraints
 //! - [`prover`] - Proof generation
+//! - [`metrics`] - Per-stage prover timings and hash counts
+//! - [`spec`] - On-chain proof byte layout and annotated test vectors
 //! - [`verifier`] - Proof verification (for testing)
+//! - [`onchain`] - The stark-verifier program's check on on-chain proof bytes
 //! - [`types`] - Common types (Proof, PublicInputs, etc.)
+//! - `evm` - EVM call data and Solidity verifier stub (`evm` feature)
+//! - `testing` - `ProofMutator`, corrupted proofs for soundness tests
+//!   (`testing` feature)
+//!
+//! With `hash-only`, only [`m31`], [`hash`], [`poseidon2`], [`merkle`],
+//! [`metrics`], [`spec`] and [`types`] are built. `verify` adds everything but
+//! [`cache`], the prover itself and FRI layer construction, which come with
+//! `prove`.
 //!
 //! # Security
 //!
@@ -56,36 +78,81 @@ extern crate alloc;
 use alloc::{vec, vec::Vec};
 
 pub mod m31;
+#[cfg(feature = "verify")]
 pub mod qm31;
+#[cfg(feature = "verify")]
 pub mod circle;
+#[cfg(feature = "verify")]
+pub mod poly;
 pub mod merkle;
 pub mod hash;
+pub mod poseidon2;
+#[cfg(feature = "poseidon-bn254")]
+pub mod poseidon_bn254;
+#[cfg(feature = "verify")]
 pub mod fri;
+#[cfg(feature = "prove")]
+pub mod cache;
+#[cfg(feature = "verify")]
 pub mod air;
+#[cfg(feature = "verify")]
 pub mod prover;
+#[cfg(feature = "verify")]
 pub mod verifier;
+#[cfg(feature = "verify")]
+pub mod onchain;
 pub mod types;
+pub mod metrics;
+pub mod checkpoint;
+pub mod spec;
+#[cfg(feature = "evm")]
+pub mod evm;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 // Re-exports for convenience
 pub use m31::{M31, M31_PRIME};
+pub use merkle::{MerkleTree, MerklePath, MerkleWitness, TREE_DEPTH};
+pub use hash::{HashKind, keccak_hash, hash_password, stretch_password, PasswordStretch, hash_identifier, blind_identifier, m31_commitment, m31_nullifier, m31_epoch_nullifier, pq_commitment, pq_nullifier, pq_epoch_nullifier, nullifier_epoch, Hash32};
+pub use types::{ProofBundle, PublicInputs, Witness, ProofError, PROOF_BUNDLE_VERSION};
+pub use metrics::{ProveMetrics, ProveStage, StageMetrics, StageRecorder};
+pub use checkpoint::Checkpoint;
+pub use murkl_proof_format::SecurityLevel;
+#[cfg(feature = "verify")]
 pub use qm31::QM31;
-pub use circle::{CirclePoint, CIRCLE_GENERATOR};
-pub use merkle::{MerkleTree, MerklePath, TREE_DEPTH};
-pub use hash::{keccak_hash, hash_password, hash_identifier, m31_commitment, m31_nullifier, pq_commitment, pq_nullifier, Hash32};
-pub use fri::{FriConfig, FriProof};
+#[cfg(feature = "verify")]
+pub use circle::{CirclePoint, SecureCirclePoint, CIRCLE_GENERATOR};
+#[cfg(feature = "verify")]
+pub use poly::CirclePoly;
+#[cfg(feature = "verify")]
+pub use fri::{FinalLayerEncoding, FriConfig, FriProof};
+#[cfg(feature = "verify")]
 pub use air::{AirConfig, TraceColumn};
-pub use prover::{Prover, ProverConfig};
+#[cfg(feature = "verify")]
+pub use prover::ProverConfig;
+#[cfg(feature = "verify")]
 pub use verifier::Verifier;
-pub use types::{Proof, PublicInputs, Witness, ProofError};
+#[cfg(feature = "verify")]
+pub use types::Proof;
+#[cfg(feature = "prove")]
+pub use prover::Prover;
+#[cfg(feature = "prove")]
+pub use cache::EvaluationCache;
 
 /// Prelude module for convenient imports
 pub mod prelude {
     pub use crate::m31::{M31, M31_PRIME};
-    pub use crate::qm31::QM31;
-    pub use crate::circle::{CirclePoint, CIRCLE_GENERATOR};
     pub use crate::merkle::{MerkleTree, MerklePath};
     pub use crate::hash::{keccak_hash, hash_password, hash_identifier, m31_commitment, m31_nullifier};
-    pub use crate::types::{Proof, PublicInputs, Witness};
-    pub use crate::prover::Prover;
+    pub use crate::types::{PublicInputs, Witness};
+    #[cfg(feature = "verify")]
+    pub use crate::qm31::QM31;
+    #[cfg(feature = "verify")]
+    pub use crate::circle::{CirclePoint, CIRCLE_GENERATOR};
+    #[cfg(feature = "verify")]
+    pub use crate::types::Proof;
+    #[cfg(feature = "verify")]
     pub use crate::verifier::Verifier;
+    #[cfg(feature = "prove")]
+    pub use crate::prover::Prover;
 }
@@ -4,12 +4,18 @@
 //! Uses Keccak256 (SHA3) for the hash function.
 
 #[cfg(not(feature = "std"))]
-use alloc::{collections::BTreeMap, vec, vec::Vec};
+use alloc::{collections::BTreeMap, format, vec, vec::Vec};
 #[cfg(feature = "std")]
 use std::collections::HashMap;
 
 use sha3::{Digest, Keccak256};
+#[cfg(feature = "prove")]
+use crate::checkpoint::{checkpoint_error, put_m31s, StateReader};
 use crate::m31::M31;
+#[cfg(feature = "verify")]
+use crate::qm31::QM31;
+use crate::types::ProofError;
+use murkl_proof_format::FRI_GROUP_DOMAIN;
 
 /// Default tree depth (supports 2^TREE_DEPTH leaves)
 pub const TREE_DEPTH: usize = 20; // 1M leaves
@@ -58,6 +64,99 @@ pub fn hash_m31_batch(values: &[M31]) -> Hash {
     hash
 }
 
+/// A value [`MerkleCommitment`] stores one of per leaf
+///
+/// FRI layers commit to M31 or QM31 values; the leaf hash of each matches
+/// the on-chain verifier's.
+pub trait LeafValue: Copy + PartialEq + core::fmt::Debug {
+    /// M31 limbs per value
+    const LIMBS: usize;
+
+    /// Hash of the leaf holding `self`
+    fn hash_leaf(&self) -> Hash;
+
+    /// Append the limbs of `self` to `out`
+    fn push_limbs(&self, out: &mut Vec<M31>);
+
+    /// Value from its first [`LeafValue::LIMBS`] limbs
+    fn from_limbs(limbs: &[M31]) -> Self;
+}
+
+impl LeafValue for M31 {
+    const LIMBS: usize = 1;
+
+    fn hash_leaf(&self) -> Hash {
+        hash_leaf(*self)
+    }
+
+    fn push_limbs(&self, out: &mut Vec<M31>) {
+        out.push(*self);
+    }
+
+    fn from_limbs(limbs: &[M31]) -> Self {
+        limbs[0]
+    }
+}
+
+/// Leaves are the 16 value bytes padded with 16 zeros, as the stark-verifier
+/// hashes FRI layer values
+#[cfg(feature = "verify")]
+impl LeafValue for QM31 {
+    const LIMBS: usize = 4;
+
+    fn hash_leaf(&self) -> Hash {
+        let mut padded = [0u8; 32];
+        padded[..16].copy_from_slice(&self.to_bytes());
+        hash_bytes(&padded)
+    }
+
+    fn push_limbs(&self, out: &mut Vec<M31>) {
+        out.extend_from_slice(&[self.a, self.b, self.c, self.d]);
+    }
+
+    fn from_limbs(limbs: &[M31]) -> Self {
+        QM31::new(limbs[0], limbs[1], limbs[2], limbs[3])
+    }
+}
+
+/// Hash of a leaf holding a whole FRI folding group:
+/// `H(FRI_GROUP_DOMAIN || limbs as u32 LE)`, so a query's every sibling is
+/// bound to the layer commitment
+pub fn hash_group<F: LeafValue>(values: &[F]) -> Hash {
+    let mut limbs = Vec::with_capacity(values.len() * F::LIMBS);
+    for value in values {
+        value.push_limbs(&mut limbs);
+    }
+    let mut hasher = Keccak256::new();
+    hasher.update(FRI_GROUP_DOMAIN);
+    for limb in &limbs {
+        hasher.update(limb.to_le_bytes());
+    }
+    let mut hash = [0u8; HASH_SIZE];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+/// Values flattened to their limbs, for a prover checkpoint
+#[cfg(feature = "prove")]
+pub(crate) fn put_values<F: LeafValue>(out: &mut Vec<u8>, values: &[F]) {
+    let mut limbs = Vec::with_capacity(values.len() * F::LIMBS);
+    for value in values {
+        value.push_limbs(&mut limbs);
+    }
+    put_m31s(out, &limbs);
+}
+
+/// Values [`put_values`] wrote
+#[cfg(feature = "prove")]
+pub(crate) fn read_values<F: LeafValue>(reader: &mut StateReader<'_>) -> Result<Vec<F>, ProofError> {
+    let limbs = reader.m31s()?;
+    if limbs.len() % F::LIMBS != 0 {
+        return Err(checkpoint_error("split value"));
+    }
+    Ok(limbs.chunks(F::LIMBS).map(F::from_limbs).collect())
+}
+
 /// Hash arbitrary bytes
 pub fn hash_bytes(data: &[u8]) -> Hash {
     let result = Keccak256::digest(data);
@@ -90,6 +189,18 @@ impl MerklePath {
         &computed == root
     }
 
+    /// Verify this path as an opening of a tree of `2^depth` leaves
+    ///
+    /// Unlike [`verify`](Self::verify), the path must have exactly `depth`
+    /// siblings and its index must be one of the tree's leaves. A padded
+    /// path climbs past the declared root to one of the prover's choosing,
+    /// and index bits above `depth` would never be read.
+    pub fn verify_at_depth(&self, leaf_hash: &Hash, root: &Hash, depth: u32) -> bool {
+        self.siblings.len() == depth as usize
+            && self.leaf_index.checked_shr(depth).unwrap_or(0) == 0
+            && self.verify(leaf_hash, root)
+    }
+
     /// Compute the root from a leaf hash using this path
     pub fn compute_root(&self, leaf_hash: &Hash) -> Hash {
         let mut current = *leaf_hash;
@@ -311,39 +422,70 @@ pub fn build_tree(leaves: &[Hash]) -> (Hash, Vec<Vec<Hash>>) {
     (current_level[0], tree)
 }
 
-/// Commitment to a vector of M31 elements using Merkle tree
+fn commit_hashes(leaves: usize) -> u64 {
+    match leaves {
+        0 => 0,
+        n => (n + n.next_power_of_two()) as u64,
+    }
+}
+
+/// Commitment to a vector of M31 (or QM31) elements using Merkle tree
 #[derive(Clone, Debug)]
-pub struct MerkleCommitment {
+pub struct MerkleCommitment<F: LeafValue = M31> {
     /// Root hash of the tree
     pub root: Hash,
     /// Original values (optional, for opening proofs)
-    values: Option<Vec<M31>>,
+    values: Option<Vec<F>>,
     /// Internal tree structure
     tree: Option<Vec<Vec<Hash>>>,
+    /// Values per leaf: 1, or the FRI folding factor for a layer tree
+    group: usize,
+    /// Hashes spent building the tree
+    hashes: u64,
 }
 
-impl MerkleCommitment {
-    /// Create a commitment from M31 values
-    pub fn commit(values: &[M31]) -> Self {
-        let leaves: Vec<Hash> = values.iter().map(|&v| hash_leaf(v)).collect();
+impl<F: LeafValue> MerkleCommitment<F> {
+    /// Create a commitment from field values
+    pub fn commit(values: &[F]) -> Self {
+        let leaves: Vec<Hash> = values.iter().map(F::hash_leaf).collect();
+        let (root, tree) = build_tree(&leaves);
+
+        Self {
+            root,
+            values: Some(values.to_vec()),
+            tree: Some(tree),
+            group: 1,
+            hashes: commit_hashes(values.len()),
+        }
+    }
+
+    /// Create a commitment with a leaf per `group` consecutive values,
+    /// hashed together by [`hash_group`]
+    pub fn commit_groups(values: &[F], group: usize) -> Self {
+        assert!(group > 0 && values.len() % group == 0, "values must split into whole groups");
+        let leaves: Vec<Hash> = values.chunks(group).map(hash_group).collect();
         let (root, tree) = build_tree(&leaves);
 
         Self {
             root,
             values: Some(values.to_vec()),
             tree: Some(tree),
+            group,
+            hashes: commit_hashes(leaves.len()),
         }
     }
 
     /// Create a commitment without storing values (just the root)
-    pub fn commit_root_only(values: &[M31]) -> Self {
-        let leaves: Vec<Hash> = values.iter().map(|&v| hash_leaf(v)).collect();
+    pub fn commit_root_only(values: &[F]) -> Self {
+        let leaves: Vec<Hash> = values.iter().map(F::hash_leaf).collect();
         let (root, _) = build_tree(&leaves);
 
         Self {
             root,
             values: None,
             tree: None,
+            group: 1,
+            hashes: commit_hashes(values.len()),
         }
     }
 
@@ -352,21 +494,38 @@ impl MerkleCommitment {
         self.root
     }
 
-    /// Open the commitment at a specific index
-    pub fn open(&self, index: usize) -> Option<(M31, MerklePath)> {
-        let values = self.values.as_ref()?;
-        let tree = self.tree.as_ref()?;
+    /// Hashes spent on the commitment: one per leaf, the padding leaf, and
+    /// one per inner node
+    pub fn hash_count(&self) -> u64 {
+        self.hashes
+    }
 
-        if index >= values.len() {
+    /// Open the commitment at a specific index
+    pub fn open(&self, index: usize) -> Option<(F, MerklePath)> {
+        if self.group != 1 {
             return None;
         }
+        let value = *self.values.as_ref()?.get(index)?;
+        Some((value, self.path(index)?))
+    }
+
+    /// Open the group of a [`commit_groups`](Self::commit_groups)
+    /// commitment that holds position `index`; the path opens the group's
+    /// leaf, `index / group`
+    pub fn open_group(&self, index: usize) -> Option<(&[F], MerklePath)> {
+        let values = self.values.as_ref()?;
+        let start = index / self.group * self.group;
+        let group = values.get(start..start + self.group)?;
+        Some((group, self.path(index / self.group)?))
+    }
 
-        let value = values[index];
+    fn path(&self, leaf: usize) -> Option<MerklePath> {
+        let tree = self.tree.as_ref()?;
         // Number of sibling levels is tree.len() - 1 (root has no sibling)
         let num_siblings = tree.len().saturating_sub(1);
 
         let mut siblings = Vec::with_capacity(num_siblings);
-        let mut idx = index;
+        let mut idx = leaf;
 
         for level in 0..num_siblings {
             let sibling_idx = idx ^ 1;
@@ -378,22 +537,241 @@ impl MerkleCommitment {
             idx >>= 1;
         }
 
-        Some((value, MerklePath {
+        Some(MerklePath {
             siblings,
-            leaf_index: index,
-        }))
+            leaf_index: leaf,
+        })
     }
 
-    /// Verify an opening
-    pub fn verify_opening(&self, index: usize, value: M31, path: &MerklePath) -> bool {
+    /// Verify an opening; with the tree kept, its path must be exactly as
+    /// deep as the tree
+    pub fn verify_opening(&self, index: usize, value: F, path: &MerklePath) -> bool {
         if index != path.leaf_index {
             return false;
         }
-        let leaf_hash = hash_leaf(value);
-        path.verify(&leaf_hash, &self.root)
+        let leaf_hash = value.hash_leaf();
+        match &self.tree {
+            Some(tree) => path.verify_at_depth(&leaf_hash, &self.root, tree.len().saturating_sub(1) as u32),
+            None => path.verify(&leaf_hash, &self.root),
+        }
+    }
+
+    /// Committed values, unless only the root was kept
+    #[cfg(feature = "prove")]
+    pub(crate) fn values(&self) -> Option<&[F]> {
+        self.values.as_deref()
+    }
+
+    /// Root, values, tree and hash count, for a prover checkpoint
+    #[cfg(feature = "prove")]
+    pub(crate) fn write_state(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.root);
+        out.extend_from_slice(&(self.group as u32).to_le_bytes());
+        put_values(out, self.values.as_deref().unwrap_or_default());
+        let tree = self.tree.as_deref().unwrap_or_default();
+        out.extend_from_slice(&(tree.len() as u32).to_le_bytes());
+        for level in tree {
+            out.extend_from_slice(&(level.len() as u32).to_le_bytes());
+            for node in level {
+                out.extend_from_slice(node);
+            }
+        }
+        out.extend_from_slice(&self.hashes.to_le_bytes());
+    }
+
+    /// Commitment [`MerkleCommitment::write_state`] saved, without rehashing
+    #[cfg(feature = "prove")]
+    pub(crate) fn read_state(reader: &mut StateReader<'_>) -> Result<Self, ProofError> {
+        let root = reader.hash()?;
+        let group = reader.u32()? as usize;
+        if group == 0 {
+            return Err(checkpoint_error("empty leaf group"));
+        }
+        let values = read_values(reader)?;
+        let levels = reader.count(4)?;
+        let mut tree = Vec::with_capacity(levels);
+        for _ in 0..levels {
+            let nodes = reader.count(HASH_SIZE)?;
+            tree.push((0..nodes).map(|_| reader.hash()).collect::<Result<Vec<_>, _>>()?);
+        }
+        let hashes = reader.u64()?;
+        let opens = !tree.is_empty();
+        Ok(Self {
+            root,
+            values: opens.then_some(values),
+            tree: opens.then_some(tree),
+            group,
+            hashes,
+        })
     }
 }
 
+/// Parent of two pool tree nodes: Poseidon2 [`compress`](crate::poseidon2::compress),
+/// one permutation, so `air::poseidon2` proves each level of a path
+#[inline]
+pub fn pool_node(left: &Hash, right: &Hash) -> Hash {
+    crate::poseidon2::compress(left, right)
+}
+
+/// Root of an append-only pool tree
+///
+/// Matches the on-chain murkl program: leaves are the raw 32-byte
+/// commitments (not re-hashed), unfilled positions are zero and parents are
+/// [`pool_node`]s.
+pub fn pool_root(leaves: &[Hash], depth: usize) -> Hash {
+    let mut level: Vec<Hash> = leaves.to_vec();
+    let mut empty = ZERO_HASH;
+
+    for _ in 0..depth {
+        level = level
+            .chunks(2)
+            .map(|pair| pool_node(&pair[0], pair.get(1).unwrap_or(&empty)))
+            .collect();
+        empty = pool_node(&empty, &empty);
+    }
+
+    level.first().copied().unwrap_or(empty)
+}
+
+/// Root after each leaf of an append-only pool tree, in order
+///
+/// Walks the leaves with the same frontier the on-chain program keeps, so
+/// entry `i` equals `pool_root(&leaves[..=i], depth)` without rebuilding
+/// the tree per leaf.
+pub fn pool_roots(leaves: &[Hash], depth: usize) -> Vec<Hash> {
+    let mut empty = Vec::with_capacity(depth);
+    let mut node = ZERO_HASH;
+    for _ in 0..depth {
+        empty.push(node);
+        node = pool_node(&node, &node);
+    }
+
+    let mut frontier = vec![ZERO_HASH; depth];
+    leaves
+        .iter()
+        .enumerate()
+        .map(|(leaf_index, leaf)| {
+            let mut node = *leaf;
+            let mut index = leaf_index;
+            for (left, empty) in frontier.iter_mut().zip(&empty) {
+                node = if index & 1 == 0 {
+                    *left = node;
+                    pool_node(&node, empty)
+                } else {
+                    pool_node(left, &node)
+                };
+                index >>= 1;
+            }
+            node
+        })
+        .collect()
+}
+
+/// Path of the newest leaf, `leaf_index`, from the frontier the on-chain
+/// program keeps after appending it
+///
+/// `frontier[level]` is the last left child cached at that level. Every
+/// node right of the newest leaf is empty, so its siblings are the cached
+/// node where it is a right child and the empty subtree where it is a left
+/// one. Older leaves need the full leaf list ([`witness_for`]).
+pub fn pool_frontier_path(frontier: &[Hash], leaf_index: usize) -> Vec<Hash> {
+    let mut empty = ZERO_HASH;
+    frontier
+        .iter()
+        .enumerate()
+        .map(|(level, left)| {
+            let sibling = if (leaf_index >> level) & 1 == 1 { *left } else { empty };
+            empty = pool_node(&empty, &empty);
+            sibling
+        })
+        .collect()
+}
+
+/// Membership witness for a commitment in the pool tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleWitness {
+    /// The leaf (deposit commitment)
+    pub leaf: Hash,
+    /// Leaf index in the tree
+    pub leaf_index: usize,
+    /// Sibling hashes from leaf to root
+    pub siblings: Vec<Hash>,
+    /// Root the witness authenticates against
+    pub root: Hash,
+}
+
+impl MerkleWitness {
+    /// Build the witness for `leaf_index` in a pool tree of the given depth
+    ///
+    /// Returns `None` if the index is outside the populated leaves or the
+    /// leaves do not fit in the tree.
+    pub fn from_leaves(leaves: &[Hash], depth: usize, leaf_index: usize) -> Option<Self> {
+        if leaf_index >= leaves.len() || depth >= usize::BITS as usize || leaves.len() > 1 << depth {
+            return None;
+        }
+
+        let mut siblings = Vec::with_capacity(depth);
+        let mut level: Vec<Hash> = leaves.to_vec();
+        let mut empty = ZERO_HASH;
+        let mut index = leaf_index;
+
+        for _ in 0..depth {
+            siblings.push(level.get(index ^ 1).copied().unwrap_or(empty));
+            level = level
+                .chunks(2)
+                .map(|pair| pool_node(&pair[0], pair.get(1).unwrap_or(&empty)))
+                .collect();
+            empty = pool_node(&empty, &empty);
+            index >>= 1;
+        }
+
+        Some(Self {
+            leaf: leaves[leaf_index],
+            leaf_index,
+            siblings,
+            root: level.first().copied().unwrap_or(empty),
+        })
+    }
+
+    /// Check the path recomputes the witness root
+    pub fn verify(&self) -> bool {
+        let mut node = self.leaf;
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.leaf_index >> level) & 1 == 0 { pool_node(&node, sibling) } else { pool_node(sibling, &node) };
+        }
+        node == self.root
+    }
+}
+
+/// Locate `commitment` in the pool leaves and build its witness
+///
+/// The leaves must reproduce `expected_root`; a mismatch means the leaf
+/// data is stale or truncated, and any proof built from it would be
+/// rejected on-chain. If the commitment was deposited more than once, the
+/// first occurrence is used.
+pub fn witness_for(
+    leaves: &[Hash],
+    depth: usize,
+    expected_root: &Hash,
+    commitment: &Hash,
+) -> Result<MerkleWitness, ProofError> {
+    let leaf_index = leaves
+        .iter()
+        .position(|leaf| leaf == commitment)
+        .ok_or_else(|| ProofError::MerkleError("commitment not found in tree".into()))?;
+
+    let witness = MerkleWitness::from_leaves(leaves, depth, leaf_index)
+        .ok_or_else(|| ProofError::MerkleError(format!("{} leaves exceed tree depth {}", leaves.len(), depth)))?;
+
+    if &witness.root != expected_root {
+        return Err(ProofError::MerkleError(
+            "leaves do not reproduce the stored root (stale merkle data?)".into(),
+        ));
+    }
+
+    Ok(witness)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -522,6 +900,77 @@ mod tests {
             assert_eq!(value.value(), i as u32);
             assert!(commitment.verify_opening(i, value, &path));
         }
+
+        // 16 leaves, the padding leaf, 15 nodes; 3 leaves pad to 4
+        assert_eq!(commitment.hash_count(), 32);
+        assert_eq!(MerkleCommitment::commit(&values[..3]).hash_count(), 7);
+    }
+
+    #[test]
+    fn test_path_depth_is_exact() {
+        let values: Vec<M31> = (0..16).map(M31::new).collect();
+        let commitment = MerkleCommitment::commit(&values);
+        let (value, path) = commitment.open(5).unwrap();
+        let (leaf, root) = (value.hash_leaf(), commitment.root());
+        assert!(path.verify_at_depth(&leaf, &root, 4));
+        assert!(!path.verify_at_depth(&leaf, &root, 3));
+        assert!(!path.verify_at_depth(&leaf, &root, 5));
+
+        // One sibling too many: the root of a tree twice as large, with
+        // this one as its left half
+        let mut padded = path.clone();
+        padded.siblings.push([7; 32]);
+        let padded_root = hash_pair(&root, &[7; 32]);
+        assert!(padded.verify(&leaf, &padded_root));
+        assert!(!padded.verify_at_depth(&leaf, &padded_root, 4));
+        assert!(!commitment.verify_opening(5, value, &padded));
+
+        // One too few
+        let mut short = path.clone();
+        short.siblings.pop();
+        assert!(!short.verify_at_depth(&leaf, &root, 4));
+        assert!(!commitment.verify_opening(5, value, &short));
+
+        // Index bits above the depth are never climbed, so they would
+        // otherwise go unchecked
+        let aliased = MerklePath { leaf_index: 5 + 16, ..path };
+        assert!(aliased.verify(&leaf, &root));
+        assert!(!aliased.verify_at_depth(&leaf, &root, 4));
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_qm31_commitment() {
+        use crate::qm31::QM31;
+
+        let values: Vec<QM31> = (0..8).map(|i| QM31::from_u32(i, i + 1, i + 2, i + 3)).collect();
+        let commitment = MerkleCommitment::commit(&values);
+        let (value, path) = commitment.open(5).unwrap();
+        assert_eq!(value, values[5]);
+        assert!(commitment.verify_opening(5, value, &path));
+
+        // The stark-verifier hashes the value bytes padded to 32
+        let mut padded = [0u8; 32];
+        padded[..16].copy_from_slice(&values[5].to_bytes());
+        assert!(path.verify(&hash_bytes(&padded), &commitment.root()));
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_group_commitment() {
+        use crate::qm31::QM31;
+
+        let values: Vec<QM31> = (0..16).map(|i| QM31::from_u32(i, i + 1, i + 2, i + 3)).collect();
+        let commitment = MerkleCommitment::commit_groups(&values, 4);
+        let (group, path) = commitment.open_group(6).unwrap();
+        assert_eq!(group, &values[4..8]);
+        assert_eq!(path.leaf_index, 1);
+        assert!(path.verify_at_depth(&hash_group(group), &commitment.root(), 2));
+        assert!(commitment.open(6).is_none());
+
+        // The stark-verifier's leaf value: the prefixed bytes of all four
+        let bytes: Vec<u8> = group.iter().flat_map(|v| v.to_bytes()).collect();
+        assert_eq!(hash_group(group), hash_bytes(&[FRI_GROUP_DOMAIN, &bytes].concat()));
     }
 
     #[test]
@@ -567,4 +1016,74 @@ mod tests {
         assert_eq!(tree.get_leaf(10), hash_leaf(M31::new(1000)));
         assert_eq!(tree.get_leaf(0), tree.empty_hashes[0]); // Empty leaf
     }
+
+    fn naive_pool_root(leaves: &[Hash], depth: usize) -> Hash {
+        let mut level: Vec<Hash> = leaves.to_vec();
+        level.resize(1 << depth, ZERO_HASH);
+        while level.len() > 1 {
+            level = level.chunks(2).map(|p| pool_node(&p[0], &p[1])).collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn test_pool_root_matches_naive() {
+        let leaves: Vec<Hash> = (0..5u8).map(|i| [i + 1; 32]).collect();
+        for n in 0..=leaves.len() {
+            assert_eq!(pool_root(&leaves[..n], 4), naive_pool_root(&leaves[..n], 4));
+        }
+
+        let roots = pool_roots(&leaves, 4);
+        assert_eq!(roots.len(), leaves.len());
+        for (n, root) in roots.iter().enumerate() {
+            assert_eq!(*root, naive_pool_root(&leaves[..=n], 4));
+        }
+    }
+
+    #[test]
+    fn test_frontier_path_roots_newest_leaf() {
+        let leaves: Vec<Hash> = (0..6u8).map(|i| [i + 1; 32]).collect();
+        // The frontier the program keeps: the last left child per level
+        let mut frontier = [ZERO_HASH; 4];
+        for leaf_index in 0..leaves.len() {
+            for (level, left) in frontier.iter_mut().enumerate() {
+                if (leaf_index >> level) & 1 == 0 {
+                    *left = pool_root(&leaves[leaf_index >> level << level..=leaf_index], level);
+                }
+            }
+
+            let witness = MerkleWitness::from_leaves(&leaves[..=leaf_index], 4, leaf_index).unwrap();
+            assert_eq!(pool_frontier_path(&frontier, leaf_index), witness.siblings, "leaf {leaf_index}");
+        }
+    }
+
+    #[test]
+    fn test_witness_for_finds_commitment() {
+        let leaves: Vec<Hash> = (0..5u8).map(|i| [i + 1; 32]).collect();
+        let root = pool_root(&leaves, 4);
+
+        let witness = witness_for(&leaves, 4, &root, &[3u8; 32]).unwrap();
+        assert_eq!(witness.leaf_index, 2);
+        assert_eq!(witness.siblings.len(), 4);
+        assert_eq!(witness.root, root);
+        assert!(witness.verify());
+
+        let mut wrong = witness.clone();
+        wrong.siblings[1][0] ^= 1;
+        assert!(!wrong.verify());
+    }
+
+    #[test]
+    fn test_witness_for_rejects_missing_or_stale() {
+        let leaves: Vec<Hash> = (0..5u8).map(|i| [i + 1; 32]).collect();
+        let root = pool_root(&leaves, 4);
+
+        assert!(witness_for(&leaves, 4, &root, &[9u8; 32]).is_err());
+
+        // Root from a later state than the leaves we hold
+        let mut newer = leaves.clone();
+        newer.push([6u8; 32]);
+        let newer_root = pool_root(&newer, 4);
+        assert!(witness_for(&leaves, 4, &newer_root, &[1u8; 32]).is_err());
+    }
 }
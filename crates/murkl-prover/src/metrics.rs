@@ -0,0 +1,233 @@
+//! Prover instrumentation
+//!
+//! A [`StageRecorder`] is handed to a prover and told when each
+//! [`ProveStage`] ends and how many hashes it ran; it measures the time
+//! since the previous stage ended and collects the stages into
+//! [`ProveMetrics`]. An optional hook sees every stage as it finishes, for
+//! progress reporting.
+//!
+//! The recorder reads time through a [`Clock`] rather than
+//! `std::time::Instant`, which panics on `wasm32-unknown-unknown`; the
+//! browser prover passes a clock backed by `Date.now()`, native callers
+//! [`system_clock`].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+
+/// Monotonic time since an arbitrary origin
+pub type Clock = fn() -> Duration;
+
+/// [`Clock`] for callers that only want hash and byte counts
+pub fn no_clock() -> Duration {
+    Duration::ZERO
+}
+
+/// [`Clock`] backed by `std::time::Instant`
+#[cfg(feature = "std")]
+pub fn system_clock() -> Duration {
+    static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    ORIGIN.get_or_init(std::time::Instant::now).elapsed()
+}
+
+/// A step of proof generation
+///
+/// Not every prover runs every stage; a prover whose trace is built
+/// directly on the evaluation domain has no separate extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ProveStage {
+    /// Trace columns extended onto the evaluation domain
+    Extend,
+    /// Trace Merkle trees
+    CommitTrace,
+    /// AIR constraints over the extended trace
+    Constraints,
+    /// Composition, quotient and its Merkle tree
+    Composition,
+    /// Out-of-domain evaluations
+    Oods,
+    /// FRI folding and layer commitments
+    Fri,
+    /// Proof-of-work nonce search, only when grinding is configured
+    Grind,
+    /// Query openings
+    Queries,
+}
+
+impl ProveStage {
+    /// Every stage, in proving order
+    pub const ALL: [ProveStage; 8] = [
+        Self::Extend,
+        Self::CommitTrace,
+        Self::Constraints,
+        Self::Composition,
+        Self::Oods,
+        Self::Fri,
+        Self::Grind,
+        Self::Queries,
+    ];
+
+    /// Stable lowercase name, as used in logs and JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Extend => "extend",
+            Self::CommitTrace => "commit_trace",
+            Self::Constraints => "constraints",
+            Self::Composition => "composition",
+            Self::Oods => "oods",
+            Self::Fri => "fri",
+            Self::Grind => "grind",
+            Self::Queries => "queries",
+        }
+    }
+}
+
+impl fmt::Display for ProveStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Cost of one finished stage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StageMetrics {
+    pub stage: ProveStage,
+    /// Wall time from the end of the previous stage
+    pub elapsed: Duration,
+    /// Hash invocations (leaves, nodes, transcript updates)
+    pub hashes: u64,
+}
+
+/// Per-stage costs of one proof
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProveMetrics {
+    /// Stages in the order they ran
+    pub stages: Vec<StageMetrics>,
+    /// Serialized proof size
+    pub proof_bytes: usize,
+}
+
+impl ProveMetrics {
+    /// Metrics of `stage`, if the prover ran it
+    pub fn stage(&self, stage: ProveStage) -> Option<&StageMetrics> {
+        self.stages.iter().find(|s| s.stage == stage)
+    }
+
+    /// Wall time over all stages
+    pub fn total_elapsed(&self) -> Duration {
+        self.stages.iter().map(|s| s.elapsed).sum()
+    }
+
+    /// Hashes over all stages
+    pub fn total_hashes(&self) -> u64 {
+        self.stages.iter().map(|s| s.hashes).sum()
+    }
+}
+
+/// One line per stage, then the totals
+impl fmt::Display for ProveMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in &self.stages {
+            writeln!(
+                f,
+                "{:<13} {:>10.3} ms {:>9} hashes",
+                s.stage.name(),
+                s.elapsed.as_secs_f64() * 1e3,
+                s.hashes
+            )?;
+        }
+        write!(
+            f,
+            "{:<13} {:>10.3} ms {:>9} hashes {:>7} bytes",
+            "total",
+            self.total_elapsed().as_secs_f64() * 1e3,
+            self.total_hashes(),
+            self.proof_bytes
+        )
+    }
+}
+
+/// Collects [`StageMetrics`] as a prover runs
+pub struct StageRecorder<'a> {
+    clock: Clock,
+    mark: Duration,
+    hook: Option<&'a mut dyn FnMut(&StageMetrics)>,
+    stages: Vec<StageMetrics>,
+}
+
+impl<'a> StageRecorder<'a> {
+    /// Start timing now
+    pub fn new(clock: Clock) -> Self {
+        Self { clock, mark: clock(), hook: None, stages: Vec::new() }
+    }
+
+    /// Call `hook` after every finished stage
+    pub fn with_hook(mut self, hook: &'a mut dyn FnMut(&StageMetrics)) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// End `stage`, which ran `hashes` hashes since the previous one ended
+    pub fn finish(&mut self, stage: ProveStage, hashes: u64) {
+        let now = (self.clock)();
+        let metrics = StageMetrics { stage, elapsed: now.saturating_sub(self.mark), hashes };
+        self.mark = now;
+        if let Some(hook) = self.hook.as_mut() {
+            hook(&metrics);
+        }
+        self.stages.push(metrics);
+    }
+
+    /// Stages finished so far
+    pub fn stages(&self) -> &[StageMetrics] {
+        &self.stages
+    }
+
+    /// The collected metrics, for a proof of `proof_bytes` bytes
+    pub fn into_metrics(self, proof_bytes: usize) -> ProveMetrics {
+        ProveMetrics { stages: self.stages, proof_bytes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+
+    /// Advances 5 ms per reading
+    fn ticking_clock() -> Duration {
+        Duration::from_millis(5 * TICKS.fetch_add(1, Ordering::Relaxed))
+    }
+
+    #[test]
+    fn test_recorder_laps_and_hook() {
+        let mut seen = Vec::new();
+        let mut hook = |m: &StageMetrics| seen.push(m.stage);
+        let mut recorder = StageRecorder::new(ticking_clock).with_hook(&mut hook);
+        recorder.finish(ProveStage::CommitTrace, 7);
+        recorder.finish(ProveStage::Fri, 3);
+        assert_eq!(recorder.stages().len(), 2);
+        let metrics = recorder.into_metrics(100);
+
+        assert_eq!(seen, [ProveStage::CommitTrace, ProveStage::Fri]);
+        assert_eq!(metrics.stage(ProveStage::Fri).unwrap().elapsed, Duration::from_millis(5));
+        assert_eq!(metrics.total_elapsed(), Duration::from_millis(10));
+        assert_eq!(metrics.total_hashes(), 10);
+        assert!(metrics.stage(ProveStage::Oods).is_none());
+        let table = metrics.to_string();
+        assert!(table.starts_with("commit_trace"));
+        assert!(table.ends_with("100 bytes"));
+    }
+
+    #[test]
+    fn test_no_clock() {
+        let mut recorder = StageRecorder::new(no_clock);
+        recorder.finish(ProveStage::Queries, 0);
+        assert_eq!(recorder.into_metrics(0).total_elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,777 @@
+//! The stark-verifier program's check, off chain
+//!
+//! [`verify`] runs the algorithm the Solana program runs on a proof in the
+//! on-chain format (see [`spec`](crate::spec)): the same header and shape
+//! checks, the same Fiat-Shamir [`Channel`], the same OODS check of the
+//! Poseidon2 hash AIR ([`MurklHashAir`]) and the same fold-by-4 FRI,
+//! failing with the error the program would. CLI users and CI can reject
+//! a proof here before paying for a transaction:
+//!
+//! ```rust,ignore
+//! use murkl_prover::Verifier;
+//!
+//! if let Err(e) = Verifier::verify_bytes(&proof, &commitment, &nullifier, &root, &recipient) {
+//!     eprintln!("on-chain verification would fail with {} ({})", e.code(), e);
+//! }
+//! ```
+//!
+//! Proofs are checked against the parameters the deployed program pins
+//! (`VerifierConfig::MURKL`), repeated here as constants. Hash kinds are the
+//! ones this crate is built with, so the BN254 Poseidon kind is accepted
+//! only with the `poseidon-bn254` feature, as on chain only builds with the
+//! `poseidon-syscall` feature accept it. The wasm crate's tests hold both
+//! verifiers to the same verdict and error code on honest and corrupted
+//! proofs.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::air::poseidon2::{MurklHashAir, WITNESS_COLUMNS};
+use crate::air::ConstraintEvaluator;
+use crate::circle::{
+    barycentric_eval, canonic_vanishing_secure, subgroup_generator, BarycentricDomain, Coset, SecureCirclePoint,
+};
+use crate::fri::{evaluate_polynomial, final_domain_point, fold_group, interpolate_domain};
+use crate::hash::{Hash32, HashKind};
+use crate::merkle::TREE_DEPTH;
+use crate::m31::{M31, M31_PRIME};
+use crate::qm31::QM31;
+use crate::spec::{
+    FINAL_EVALUATIONS_FLAG, FRI_GROUP_DOMAIN, MAX_FINAL_LAYER_EVALS, MAX_FINAL_POLY_LEN, MAX_FRI_LAYERS,
+    MAX_HASH_KIND, MAX_MERKLE_DEPTH, MAX_QUERIES, MAX_TRACE_COLUMNS, PROOF_MAGIC, PROOF_VERSION, TRACE_ROW_DOMAIN,
+};
+
+/// log2 of the trace length the program verifies
+pub const LOG_TRACE_SIZE: u32 = 10;
+pub const LOG_BLOWUP: u32 = 4;
+/// Fold by 4 each round
+pub const LOG_FOLDING_FACTOR: u32 = 2;
+/// log2 of the final polynomial's degree bound
+pub const LOG_FINAL_POLY_DEGREE: u32 = 4;
+/// Fewest query openings the program takes
+pub const MIN_QUERIES: usize = 4;
+/// Trace columns opened per query: the hash AIR's witness columns
+pub const TRACE_COLUMNS: usize = WITNESS_COLUMNS;
+
+const LOG_DOMAIN_SIZE: u32 = LOG_TRACE_SIZE + LOG_BLOWUP;
+const HEADER_LEN: usize = 10;
+
+/// Why the program would reject a proof; named as its `VerifierError`
+/// variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnChainError {
+    InvalidProofFormat,
+    ConstraintMismatch,
+    FinalPolyDegreeTooHigh,
+    TraceMerklePathFailed,
+    CompositionMerklePathFailed,
+    FriFoldingFailed,
+    QueryIndexMismatch,
+    FinalPolyMismatch,
+    FriLayerCountTooLow,
+    TooFewQueries,
+    TraceColumnCountMismatch,
+    UnsupportedHashKind,
+    DomainSizeMismatch,
+    UnsupportedProofVersion,
+    FoldingFactorMismatch,
+    NonCanonicalPublicInput,
+}
+
+impl OnChainError {
+    /// The custom error code a failed transaction reports
+    pub fn code(self) -> u32 {
+        6000 + match self {
+            Self::InvalidProofFormat => 0,
+            Self::ConstraintMismatch => 9,
+            Self::FinalPolyDegreeTooHigh => 10,
+            Self::TraceMerklePathFailed => 11,
+            Self::CompositionMerklePathFailed => 12,
+            Self::FriFoldingFailed => 13,
+            Self::QueryIndexMismatch => 14,
+            Self::FinalPolyMismatch => 15,
+            Self::FriLayerCountTooLow => 16,
+            Self::TooFewQueries => 17,
+            Self::TraceColumnCountMismatch => 18,
+            Self::UnsupportedHashKind => 21,
+            Self::DomainSizeMismatch => 23,
+            Self::UnsupportedProofVersion => 24,
+            Self::FoldingFactorMismatch => 25,
+            Self::NonCanonicalPublicInput => 34,
+        }
+    }
+}
+
+impl core::fmt::Display for OnChainError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::InvalidProofFormat => "Invalid proof format",
+            Self::ConstraintMismatch => "Constraint mismatch - AIR evaluation failed at OODS",
+            Self::FinalPolyDegreeTooHigh => "Final polynomial degree too high",
+            Self::TraceMerklePathFailed => "Trace Merkle path verification failed",
+            Self::CompositionMerklePathFailed => "Composition Merkle path verification failed",
+            Self::FriFoldingFailed => "FRI folding verification failed",
+            Self::QueryIndexMismatch => "Query index mismatch - Fiat-Shamir derivation failed",
+            Self::FinalPolyMismatch => "Final polynomial evaluation mismatch",
+            Self::FriLayerCountTooLow => "Too few FRI layers for the configured domain",
+            Self::TooFewQueries => "Too few query openings",
+            Self::TraceColumnCountMismatch => "Trace column count does not match the verifier configuration",
+            Self::UnsupportedHashKind => "Unknown hash kind in proof header",
+            Self::DomainSizeMismatch => "Evaluation domain size does not match the verifier configuration",
+            Self::UnsupportedProofVersion => "Proof does not start with a supported magic and format version",
+            Self::FoldingFactorMismatch => "FRI folding factor does not match the verifier configuration",
+            Self::NonCanonicalPublicInput => "Commitment, nullifier or root has a 4-byte limb that is not a canonical M31",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OnChainError {}
+
+type Result<T> = core::result::Result<T, OnChainError>;
+
+fn require(condition: bool, err: OnChainError) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+/// The program's Fiat-Shamir transcript
+///
+/// Not the library prover's [`Transcript`](crate::prover::Transcript): every
+/// mix and squeeze hashes the 32-byte state with the proof's [`HashKind`],
+/// and squeezes also hash a running counter.
+pub struct Channel {
+    state: Hash32,
+    counter: u64,
+    hash: HashKind,
+}
+
+impl Channel {
+    pub fn new(hash: HashKind) -> Self {
+        Self { state: [0; 32], counter: 0, hash }
+    }
+
+    pub fn mix_digest(&mut self, digest: &Hash32) {
+        self.state = self.hash.hash(&[&self.state, digest]);
+        self.counter += 1;
+    }
+
+    pub fn mix_qm31(&mut self, value: &QM31) {
+        self.state = self.hash.hash(&[&self.state, &value.to_bytes()]);
+        self.counter += 1;
+    }
+
+    /// The low bytes of a fresh state
+    fn squeeze_word(&mut self) -> u32 {
+        let hash = self.hash.hash(&[&self.state, &self.counter.to_le_bytes()]);
+        self.state = hash;
+        self.counter += 1;
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+
+    pub fn squeeze_qm31(&mut self) -> QM31 {
+        let mut limb = || M31::new(self.squeeze_word());
+        let (a, b, c, d) = (limb(), limb(), limb(), limb());
+        QM31::new(a, b, c, d)
+    }
+
+    /// The OODS point, drawn again while it would land on the base circle
+    pub fn squeeze_circle_point(&mut self) -> SecureCirclePoint {
+        loop {
+            if let Some(point) = SecureCirclePoint::try_from_t(self.squeeze_qm31()) {
+                return point;
+            }
+        }
+    }
+
+    /// A query index: the low `bits` bits of a whole word
+    pub fn squeeze_bits(&mut self, bits: u32) -> u32 {
+        let word = self.squeeze_word();
+        if bits >= 32 { word } else { word & ((1 << bits) - 1) }
+    }
+
+    /// Hashes run so far, one per mix or squeezed word
+    pub fn hashes(&self) -> u64 {
+        self.counter
+    }
+}
+
+/// Parameters a proof opens with; the hash kind stays a byte until the
+/// shape checks, as on chain
+struct Header {
+    hash_kind: u8,
+    log_trace_size: u32,
+    log_blowup: u32,
+    log_folding_factor: u32,
+    num_queries: usize,
+}
+
+struct ParsedProof<'a> {
+    header: Header,
+    trace_commitment: &'a Hash32,
+    composition_commitment: &'a Hash32,
+    num_trace_columns: usize,
+    /// Every column at the OODS point
+    trace_oods: Vec<QM31>,
+    /// Every column one trace row past the OODS point
+    trace_oods_next: Vec<QM31>,
+    composition_oods: QM31,
+    fri_layer_commitments: Vec<&'a Hash32>,
+    final_poly: Vec<QM31>,
+    /// Sent last-layer values; empty when the proof sends coefficients
+    final_evaluations: Vec<QM31>,
+    /// The queries, parsed one at a time as they are verified
+    query_data: &'a [u8],
+}
+
+struct QueryProof<'a> {
+    index: u32,
+    trace_row: &'a [u8],
+    trace_path: Vec<&'a Hash32>,
+    composition_value: &'a Hash32,
+    composition_path: Vec<&'a Hash32>,
+    /// Per layer, the four siblings as sent and decoded, and the layer's path
+    fri: Vec<(&'a [u8], [QM31; 4], Vec<&'a Hash32>)>,
+    size: usize,
+}
+
+/// Bounds-checked reads; running off the end is `InvalidProofFormat`
+struct Cursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(OnChainError::InvalidProofFormat)?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn digest(&mut self) -> Result<&'a Hash32> {
+        Ok(self.take(32)?.try_into().expect("took 32 bytes"))
+    }
+
+    fn digests(&mut self, count: usize) -> Result<Vec<&'a Hash32>> {
+        let bytes = self.take(32 * count)?;
+        Ok(bytes.chunks_exact(32).map(|d| d.try_into().expect("32-byte chunk")).collect())
+    }
+
+    fn path(&mut self) -> Result<Vec<&'a Hash32>> {
+        let depth = self.u8()? as usize;
+        require(depth <= MAX_MERKLE_DEPTH, OnChainError::InvalidProofFormat)?;
+        self.digests(depth)
+    }
+
+    fn qm31(&mut self) -> Result<QM31> {
+        Ok(QM31::from_bytes(self.take(16)?))
+    }
+
+    fn qm31s(&mut self, count: usize) -> Result<Vec<QM31>> {
+        Ok(self.take(16 * count)?.chunks_exact(16).map(QM31::from_bytes).collect())
+    }
+}
+
+fn parse_header(data: &[u8]) -> Result<Header> {
+    require(data.len() >= HEADER_LEN, OnChainError::InvalidProofFormat)?;
+    require(
+        data[..4] == PROOF_MAGIC && data[4] == PROOF_VERSION,
+        OnChainError::UnsupportedProofVersion,
+    )?;
+    require(data[5] <= MAX_HASH_KIND, OnChainError::UnsupportedHashKind)?;
+    let header = Header {
+        hash_kind: data[5],
+        log_trace_size: data[6] as u32,
+        log_blowup: data[7] as u32,
+        log_folding_factor: data[8] as u32,
+        num_queries: data[9] as usize,
+    };
+    let log_domain_size = header.log_trace_size + header.log_blowup;
+    require(
+        log_domain_size > 0 && log_domain_size as usize <= MAX_MERKLE_DEPTH,
+        OnChainError::InvalidProofFormat,
+    )?;
+    require(
+        header.log_folding_factor > 0 && header.log_folding_factor <= log_domain_size,
+        OnChainError::InvalidProofFormat,
+    )?;
+    require(header.num_queries <= MAX_QUERIES, OnChainError::InvalidProofFormat)?;
+    Ok(header)
+}
+
+fn parse_proof(data: &[u8]) -> Result<ParsedProof<'_>> {
+    let header = parse_header(data)?;
+    let mut cursor = Cursor { data, offset: HEADER_LEN };
+
+    let trace_commitment = cursor.digest()?;
+    let composition_commitment = cursor.digest()?;
+    let num_trace_columns = cursor.u8()? as usize;
+    require(
+        (1..=MAX_TRACE_COLUMNS).contains(&num_trace_columns),
+        OnChainError::InvalidProofFormat,
+    )?;
+    let trace_oods = cursor.qm31s(num_trace_columns)?;
+    let trace_oods_next = cursor.qm31s(num_trace_columns)?;
+    let composition_oods = cursor.qm31()?;
+
+    let num_fri_layers = cursor.u8()? as usize;
+    require(num_fri_layers <= MAX_FRI_LAYERS, OnChainError::InvalidProofFormat)?;
+    let fri_layer_commitments = cursor.digests(num_fri_layers)?;
+
+    let final_len = cursor.u16()?;
+    let (mut final_poly, mut final_evaluations) = (Vec::new(), Vec::new());
+    if final_len & FINAL_EVALUATIONS_FLAG != 0 {
+        let count = (final_len & !FINAL_EVALUATIONS_FLAG) as usize;
+        require(
+            (1..=MAX_FINAL_LAYER_EVALS).contains(&count),
+            OnChainError::FinalPolyDegreeTooHigh,
+        )?;
+        final_evaluations = cursor.qm31s(count)?;
+    } else {
+        require(final_len as usize <= MAX_FINAL_POLY_LEN, OnChainError::FinalPolyDegreeTooHigh)?;
+        final_poly = cursor.qm31s(final_len as usize)?;
+    }
+
+    Ok(ParsedProof {
+        header,
+        trace_commitment,
+        composition_commitment,
+        num_trace_columns,
+        trace_oods,
+        trace_oods_next,
+        composition_oods,
+        fri_layer_commitments,
+        final_poly,
+        final_evaluations,
+        query_data: &data[cursor.offset..],
+    })
+}
+
+fn parse_query(data: &[u8], num_trace_columns: usize, num_fri_layers: usize) -> Result<QueryProof<'_>> {
+    let mut cursor = Cursor { data, offset: 0 };
+    let index = cursor.u32()?;
+
+    let trace_row = cursor.take(4 * num_trace_columns)?;
+    for value in trace_row.chunks_exact(4) {
+        require(
+            u32::from_le_bytes([value[0], value[1], value[2], value[3]]) < M31_PRIME,
+            OnChainError::InvalidProofFormat,
+        )?;
+    }
+    let trace_path = cursor.path()?;
+    let composition_value = cursor.digest()?;
+    let composition_path = cursor.path()?;
+
+    let mut fri = Vec::with_capacity(num_fri_layers);
+    for _ in 0..num_fri_layers {
+        let group = cursor.take(64)?;
+        let siblings = core::array::from_fn(|k| QM31::from_bytes(&group[16 * k..16 * (k + 1)]));
+        let path = cursor.path()?;
+        fri.push((group, siblings, path));
+    }
+
+    Ok(QueryProof { index, trace_row, trace_path, composition_value, composition_path, fri, size: cursor.offset })
+}
+
+/// Header, FRI and column-count checks against the pinned parameters
+fn check_shape(proof: &ParsedProof) -> Result<HashKind> {
+    let header = &proof.header;
+    let hash = HashKind::from_byte(header.hash_kind).ok_or(OnChainError::UnsupportedHashKind)?;
+    require(
+        header.log_trace_size == LOG_TRACE_SIZE && header.log_blowup == LOG_BLOWUP,
+        OnChainError::DomainSizeMismatch,
+    )?;
+    require(header.log_folding_factor == LOG_FOLDING_FACTOR, OnChainError::FoldingFactorMismatch)?;
+    require(header.num_queries >= MIN_QUERIES, OnChainError::TooFewQueries)?;
+
+    let required_layers = (LOG_TRACE_SIZE - LOG_FINAL_POLY_DEGREE).div_ceil(LOG_FOLDING_FACTOR) as usize;
+    let num_folds = proof.fri_layer_commitments.len();
+    require(num_folds >= required_layers, OnChainError::FriLayerCountTooLow)?;
+    require(
+        proof.final_poly.len() <= 1 << LOG_FINAL_POLY_DEGREE,
+        OnChainError::FinalPolyDegreeTooHigh,
+    )?;
+    if !proof.final_evaluations.is_empty() {
+        let log_final = LOG_DOMAIN_SIZE
+            .checked_sub(num_folds as u32 * LOG_FOLDING_FACTOR)
+            .ok_or(OnChainError::InvalidProofFormat)?;
+        require(proof.final_evaluations.len() == 1 << log_final, OnChainError::InvalidProofFormat)?;
+        check_final_evaluations(&proof.final_evaluations, num_folds)?;
+    }
+
+    require(proof.num_trace_columns == TRACE_COLUMNS, OnChainError::TraceColumnCountMismatch)?;
+    Ok(hash)
+}
+
+/// Sent last-layer values must lie on a polynomial below the degree bound:
+/// the interpolant through the first points has to hit all the others
+fn check_final_evaluations(values: &[QM31], num_folds: usize) -> Result<()> {
+    let shift = num_folds as u32 * LOG_FOLDING_FACTOR;
+    let xs: Vec<M31> = (0..values.len())
+        .map(|j| final_domain_point(j << shift, LOG_DOMAIN_SIZE, num_folds, LOG_FOLDING_FACTOR).x)
+        .collect();
+    let bound = values.len().min(1 << LOG_FINAL_POLY_DEGREE);
+    let coeffs = interpolate_domain(&values[..bound], &xs[..bound]);
+    for (value, x) in values.iter().zip(&xs).skip(bound) {
+        require(evaluate_polynomial(&coeffs, *x) == *value, OnChainError::FinalPolyDegreeTooHigh)?;
+    }
+    Ok(())
+}
+
+/// Hash `leaf`, then climb `path` by the bits of `index`; the path has to
+/// be exactly `depth` long and `index` inside the tree
+fn merkle_path_ok(hash: HashKind, path: &[&Hash32], depth: u32, root: &Hash32, index: u32, leaf: &[u8]) -> bool {
+    if path.len() != depth as usize || index.checked_shr(depth).unwrap_or(0) != 0 {
+        return false;
+    }
+    let mut current = hash.hash(&[leaf]);
+    let mut index = index;
+    for sibling in path {
+        current = if index & 1 == 0 {
+            hash.hash_node(&current, sibling)
+        } else {
+            hash.hash_node(sibling, &current)
+        };
+        index >>= 1;
+    }
+    current == *root
+}
+
+/// Barycentric evaluation of every witness column of a `2^log_trace_size`
+/// row trace at the OODS point and one row on, the frame
+/// [`hash_air_composition`] checks
+pub fn evaluate_trace_at_oods(
+    columns: &[Vec<M31>],
+    log_trace_size: u32,
+    oods_point: &SecureCirclePoint,
+) -> (Vec<QM31>, Vec<QM31>) {
+    let domain = BarycentricDomain::new(&Coset::canonic(log_trace_size));
+    let at = |point: &SecureCirclePoint| {
+        let weights = domain.weights(point);
+        columns.iter().map(|column| barycentric_eval(column, &weights)).collect()
+    };
+    (at(oods_point), at(&oods_point.add_base(subgroup_generator(log_trace_size))))
+}
+
+/// The program's constraint at the OODS point: the hash AIR, with the
+/// commitment's path up the [`TREE_DEPTH`] pool tree to `merkle_root`, over
+/// the sent witness columns, at the point and one row on, and the fixed
+/// columns interpolated there, folded with powers of α over Z_trace(oods)
+///
+/// Emitters of on-chain proofs send this as `composition_oods`, with
+/// `log_trace_size` [`LOG_TRACE_SIZE`].
+#[allow(clippy::too_many_arguments)]
+pub fn hash_air_composition(
+    current: &[QM31],
+    next: &[QM31],
+    commitment: &Hash32,
+    nullifier: &Hash32,
+    merkle_root: &Hash32,
+    alpha: QM31,
+    oods_point: &SecureCirclePoint,
+    log_trace_size: u32,
+) -> Result<QM31> {
+    let canonical = |hash: &Hash32| {
+        hash.chunks_exact(4).all(|limb| u32::from_le_bytes([limb[0], limb[1], limb[2], limb[3]]) < M31_PRIME)
+    };
+    require(
+        canonical(commitment) && canonical(nullifier) && canonical(merkle_root),
+        OnChainError::NonCanonicalPublicInput,
+    )?;
+
+    let air = MurklHashAir::new(commitment, nullifier)
+        .with_membership(merkle_root, TREE_DEPTH)
+        .with_log_rows(log_trace_size);
+    require(air.rows == 1 << log_trace_size, OnChainError::InvalidProofFormat)?;
+    let domain = BarycentricDomain::new(&Coset::canonic(log_trace_size));
+    let weights = domain.weights(oods_point);
+    let next_weights = domain.weights(&oods_point.add_base(subgroup_generator(log_trace_size)));
+    let (mut current, mut next) = (current.to_vec(), next.to_vec());
+    for (_, values) in air.fixed_columns() {
+        current.push(barycentric_eval(&values, &weights));
+        next.push(barycentric_eval(&values, &next_weights));
+    }
+
+    let constraints = air.evaluate_at_point(&current, &next).ok_or(OnChainError::ConstraintMismatch)?;
+    let (mut sum, mut power) = (QM31::ZERO, QM31::ONE);
+    for constraint in constraints {
+        sum = sum + power * constraint;
+        power = power * alpha;
+    }
+    Ok(sum * canonic_vanishing_secure(log_trace_size, oods_point).inv())
+}
+
+/// Check `proof` as stark-verifier's `verify_stark_proof` does
+pub fn verify(
+    proof: &[u8],
+    commitment: &Hash32,
+    nullifier: &Hash32,
+    merkle_root: &Hash32,
+    recipient: &Hash32,
+) -> Result<()> {
+    let proof = parse_proof(proof)?;
+    let hash = check_shape(&proof)?;
+
+    let mut channel = Channel::new(hash);
+    for input in [commitment, nullifier, merkle_root, recipient] {
+        channel.mix_digest(input);
+    }
+    channel.mix_digest(proof.trace_commitment);
+    let alpha = channel.squeeze_qm31();
+    channel.mix_digest(proof.composition_commitment);
+    let oods_point = channel.squeeze_circle_point();
+    for value in proof.trace_oods.iter().chain(&proof.trace_oods_next) {
+        channel.mix_qm31(value);
+    }
+    channel.mix_qm31(&proof.composition_oods);
+
+    let (current, next) = (&proof.trace_oods, &proof.trace_oods_next);
+    let expected =
+        hash_air_composition(current, next, commitment, nullifier, merkle_root, alpha, &oods_point, LOG_TRACE_SIZE)?;
+    require(proof.composition_oods == expected, OnChainError::ConstraintMismatch)?;
+
+    let fri_alphas: Vec<QM31> = proof
+        .fri_layer_commitments
+        .iter()
+        .map(|layer| {
+            channel.mix_digest(layer);
+            channel.squeeze_qm31()
+        })
+        .collect();
+
+    // Queries are parsed as they come up, so an opening that fails stops
+    // verification before a malformed later one is read
+    let mut offset = 0;
+    for _ in 0..proof.header.num_queries {
+        let data = proof.query_data.get(offset..).unwrap_or_default();
+        let query = parse_query(data, proof.num_trace_columns, proof.fri_layer_commitments.len())?;
+        offset += query.size;
+        verify_query(&proof, hash, &query, &fri_alphas, &mut channel)?;
+    }
+    Ok(())
+}
+
+fn verify_query(
+    proof: &ParsedProof,
+    hash: HashKind,
+    query: &QueryProof,
+    fri_alphas: &[QM31],
+    channel: &mut Channel,
+) -> Result<()> {
+    let expected_index = channel.squeeze_bits(LOG_DOMAIN_SIZE);
+    require(query.index == expected_index, OnChainError::QueryIndexMismatch)?;
+
+    let trace_leaf = hash.hash(&[TRACE_ROW_DOMAIN, query.trace_row]);
+    require(
+        merkle_path_ok(hash, &query.trace_path, LOG_DOMAIN_SIZE, proof.trace_commitment, query.index, &trace_leaf),
+        OnChainError::TraceMerklePathFailed,
+    )?;
+    require(
+        merkle_path_ok(
+            hash,
+            &query.composition_path,
+            LOG_DOMAIN_SIZE,
+            proof.composition_commitment,
+            query.index,
+            query.composition_value,
+        ),
+        OnChainError::CompositionMerklePathFailed,
+    )?;
+
+    let mut index = query.index as usize;
+    let mut value = QM31::from_bytes(&query.composition_value[..16]);
+    for (layer, ((group, siblings, path), alpha)) in query.fri.iter().zip(fri_alphas).enumerate() {
+        // A layer's path opens the whole group, hashed as sent, at the
+        // group's position in the layer tree, which has a leaf per group
+        let depth = LOG_DOMAIN_SIZE
+            .checked_sub(LOG_FOLDING_FACTOR * (layer as u32 + 1))
+            .ok_or(OnChainError::InvalidProofFormat)?;
+        let leaf = hash.hash(&[FRI_GROUP_DOMAIN, group]);
+        require(
+            merkle_path_ok(hash, path, depth, proof.fri_layer_commitments[layer], (index / 4) as u32, &leaf),
+            OnChainError::FriFoldingFailed,
+        )?;
+        if layer == 0 {
+            require(siblings[index % 4] == value, OnChainError::FriFoldingFailed)?;
+        }
+
+        let folded = fold_group(siblings, *alpha, index & !3, layer as u32 * LOG_FOLDING_FACTOR, LOG_DOMAIN_SIZE);
+        let next_index = index / 4;
+        value = match query.fri.get(layer + 1) {
+            Some((_, next, _)) => {
+                let expected = next[next_index % 4];
+                require(folded == expected, OnChainError::FriFoldingFailed)?;
+                expected
+            }
+            None => folded,
+        };
+        index = next_index;
+    }
+
+    if !proof.final_evaluations.is_empty() {
+        let sent = proof.final_evaluations.get(index).ok_or(OnChainError::InvalidProofFormat)?;
+        require(value == *sent, OnChainError::FinalPolyMismatch)?;
+    } else if !proof.final_poly.is_empty() {
+        let x = final_domain_point(query.index as usize, LOG_DOMAIN_SIZE, query.fri.len(), LOG_FOLDING_FACTOR).x;
+        require(evaluate_polynomial(&proof.final_poly, x) == value, OnChainError::FinalPolyMismatch)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::Verifier;
+    use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof};
+
+    const COMMITMENT: Hash32 = [1; 32];
+    const NULLIFIER: Hash32 = [2; 32];
+    const ROOT: Hash32 = [3; 32];
+    const RECIPIENT: Hash32 = [4; 32];
+
+    /// The program's shape with made-up openings
+    fn shaped() -> SerializedProof {
+        SerializedProof {
+            hash_kind: 0,
+            log_trace_size: LOG_TRACE_SIZE as u8,
+            log_blowup: LOG_BLOWUP as u8,
+            log_folding_factor: LOG_FOLDING_FACTOR as u8,
+            trace_commitment: [5; 32],
+            composition_commitment: [6; 32],
+            trace_columns: TRACE_COLUMNS as u8,
+            trace_oods: (0..TRACE_COLUMNS as u32).map(|c| [7, 8, 9, c]).collect(),
+            trace_oods_next: (0..TRACE_COLUMNS as u32).map(|c| [7, 8, 10, c]).collect(),
+            composition_oods: [0; 4],
+            fri_layer_commitments: vec![[11; 32]; 3],
+            final_layer: FinalLayer::Coefficients(vec![[12; 4]]),
+            queries: (0..MIN_QUERIES as u32)
+                .map(|q| QueryOpening {
+                    index: q,
+                    trace_row: vec![q; TRACE_COLUMNS],
+                    trace_path: vec![[13; 32]; LOG_DOMAIN_SIZE as usize],
+                    composition_leaf: [14; 32],
+                    composition_path: vec![[15; 32]; LOG_DOMAIN_SIZE as usize],
+                    fri: (1..=3)
+                        .map(|l| FriOpening {
+                            siblings: [[16; 4]; 4],
+                            path: vec![[17; 32]; LOG_DOMAIN_SIZE as usize - 2 * l],
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn verify_proof(proof: &SerializedProof) -> Result<()> {
+        Verifier::verify_bytes(&proof.encode(), &COMMITMENT, &NULLIFIER, &ROOT, &RECIPIENT)
+    }
+
+    #[test]
+    fn test_shape_errors_match_program() {
+        let bytes = shaped().encode();
+        let verify = |bytes: &[u8]| verify(bytes, &COMMITMENT, &NULLIFIER, &ROOT, &RECIPIENT);
+        let patched = |offset: usize, byte: u8| {
+            let mut bytes = bytes.clone();
+            bytes[offset] = byte;
+            verify(&bytes)
+        };
+        assert_eq!(patched(0, b'X'), Err(OnChainError::UnsupportedProofVersion));
+        assert_eq!(patched(4, PROOF_VERSION + 1), Err(OnChainError::UnsupportedProofVersion));
+        assert_eq!(patched(5, MAX_HASH_KIND + 1), Err(OnChainError::UnsupportedHashKind));
+        assert_eq!(patched(7, LOG_BLOWUP as u8 - 1), Err(OnChainError::DomainSizeMismatch));
+        assert_eq!(patched(7, 15), Err(OnChainError::InvalidProofFormat));
+        assert_eq!(patched(8, 1), Err(OnChainError::FoldingFactorMismatch));
+        assert_eq!(verify(&bytes[..HEADER_LEN + 40]), Err(OnChainError::InvalidProofFormat));
+
+        let mut proof = shaped();
+        proof.queries.pop();
+        assert_eq!(verify_proof(&proof), Err(OnChainError::TooFewQueries));
+
+        let mut proof = shaped();
+        proof.fri_layer_commitments.pop();
+        assert_eq!(verify_proof(&proof), Err(OnChainError::FriLayerCountTooLow));
+
+        let mut proof = shaped();
+        proof.trace_columns = 3;
+        assert_eq!(verify_proof(&proof), Err(OnChainError::TraceColumnCountMismatch));
+
+        let mut proof = shaped();
+        proof.final_layer = FinalLayer::Evaluations(vec![[0; 4]; 3]);
+        assert_eq!(verify_proof(&proof), Err(OnChainError::InvalidProofFormat));
+
+        assert_eq!(OnChainError::InvalidProofFormat.code(), 6000);
+        assert_eq!(OnChainError::FoldingFactorMismatch.code(), 6025);
+    }
+
+    #[test]
+    fn test_transcript_reaches_queries() {
+        let mut proof = shaped();
+        assert_eq!(verify_proof(&proof), Err(OnChainError::ConstraintMismatch));
+
+        // Replay the program's transcript to answer its challenges
+        let mut channel = Channel::new(HashKind::Keccak);
+        for input in [&COMMITMENT, &NULLIFIER, &ROOT, &RECIPIENT] {
+            channel.mix_digest(input);
+        }
+        channel.mix_digest(&proof.trace_commitment);
+        let alpha = channel.squeeze_qm31();
+        channel.mix_digest(&proof.composition_commitment);
+        let oods_point = channel.squeeze_circle_point();
+        let qm31s = |values: &[[u32; 4]]| values.iter().map(|&[a, b, c, d]| QM31::from_u32(a, b, c, d)).collect::<Vec<_>>();
+        let (trace_oods, trace_oods_next) = (qm31s(&proof.trace_oods), qm31s(&proof.trace_oods_next));
+        let (current, next) = (&trace_oods, &trace_oods_next);
+        let composition =
+            hash_air_composition(current, next, &COMMITMENT, &NULLIFIER, &ROOT, alpha, &oods_point, LOG_TRACE_SIZE)
+                .unwrap();
+        proof.composition_oods = [composition.a, composition.b, composition.c, composition.d].map(|m| m.value());
+        assert_eq!(verify_proof(&proof), Err(OnChainError::QueryIndexMismatch));
+
+        for value in trace_oods.iter().chain(&trace_oods_next) {
+            channel.mix_qm31(value);
+        }
+        channel.mix_qm31(&composition);
+        for layer in &proof.fri_layer_commitments {
+            channel.mix_digest(layer);
+            channel.squeeze_qm31();
+        }
+        proof.queries[0].index = channel.squeeze_bits(LOG_DOMAIN_SIZE);
+        assert_eq!(verify_proof(&proof), Err(OnChainError::TraceMerklePathFailed));
+
+        // The recipient is part of the statement
+        assert_eq!(
+            Verifier::verify_bytes(&proof.encode(), &COMMITMENT, &NULLIFIER, &ROOT, &[0; 32]),
+            Err(OnChainError::ConstraintMismatch)
+        );
+
+        // A limb at or past the modulus would alias a canonical one in the AIR
+        let mut aliased = NULLIFIER;
+        aliased[..4].copy_from_slice(&M31_PRIME.to_le_bytes());
+        assert_eq!(
+            Verifier::verify_bytes(&proof.encode(), &COMMITMENT, &aliased, &ROOT, &RECIPIENT),
+            Err(OnChainError::NonCanonicalPublicInput)
+        );
+        assert_eq!(OnChainError::NonCanonicalPublicInput.code(), 6034);
+    }
+}
@@ -0,0 +1,384 @@
+//! Circle polynomials with QM31 coefficients
+//!
+//! A column of 2^n values over the canonic coset of size 2^n is the
+//! restriction of a unique polynomial in the circle-FFT basis: coefficient
+//! i multiplies
+//!
+//! ```text
+//! y^{i₀} · x^{i₁} · π(x)^{i₂} · … · π^{n-2}(x)^{i_{n-1}}
+//! ```
+//!
+//! where i_k is bit k of i and π(x) = 2x² - 1 is the x-coordinate of point
+//! doubling. [`CirclePoly::interpolate`] and [`CirclePoly::evaluate`] move
+//! between the two representations in O(n log n) by splitting conjugate
+//! pairs (y ↦ -y) and then x ↦ -x pairs, halving the domain with π at each
+//! level.
+//!
+//! [`CirclePoly::divide_by_vanishing`] divides exactly by the vanishing
+//! polynomial of a smaller canonic coset. It is what turns constraint
+//! evaluations into a composition polynomial: the quotient exists as a
+//! polynomial only if every constraint holds on the trace domain.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::circle::{canonic_vanishing, CirclePoint, Coset, SecureCirclePoint};
+use crate::m31::{batch::batch_inverse, M31};
+use crate::qm31::QM31;
+
+/// Polynomial on the circle, by its circle-FFT coefficients
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CirclePoly {
+    coeffs: Vec<QM31>,
+}
+
+impl CirclePoly {
+    /// Wrap coefficients in circle-FFT order
+    ///
+    /// # Panics
+    /// Panics unless the length is a power of two.
+    pub fn new(coeffs: Vec<QM31>) -> Self {
+        assert!(coeffs.len().is_power_of_two(), "circle polynomial needs 2^n coefficients");
+        Self { coeffs }
+    }
+
+    /// Log2 of the number of coefficients
+    pub fn log_size(&self) -> u32 {
+        self.coeffs.len().trailing_zeros()
+    }
+
+    /// Coefficients in circle-FFT order
+    pub fn coeffs(&self) -> &[QM31] {
+        &self.coeffs
+    }
+
+    /// Interpolate values over the canonic coset of the same size, in
+    /// natural order
+    ///
+    /// # Panics
+    /// Panics unless the length is a power of two.
+    pub fn interpolate(evals: &[QM31]) -> Self {
+        let n = evals.len();
+        assert!(n.is_power_of_two(), "circle polynomial needs 2^n evaluations");
+        if n == 1 {
+            return Self::new(evals.to_vec());
+        }
+
+        // Points i and n-1-i are conjugate: f = f₀(x) + y·f₁(x)
+        let half: Vec<CirclePoint> = Coset::canonic(n.trailing_zeros()).iter().take(n / 2).collect();
+        let inv_2y = batch_inverse(&half.iter().map(|p| p.y.double()).collect::<Vec<_>>());
+        let inv_2 = QM31::from(M31::new(2).inv());
+        let mut f0 = Vec::with_capacity(n / 2);
+        let mut f1 = Vec::with_capacity(n / 2);
+        for i in 0..n / 2 {
+            let (a, b) = (evals[i], evals[n - 1 - i]);
+            f0.push((a + b) * inv_2);
+            f1.push((a - b) * QM31::from(inv_2y[i]));
+        }
+
+        let xs: Vec<M31> = half.iter().map(|p| p.x).collect();
+        Self::new(interleave(interpolate_x(f0, &xs), interpolate_x(f1, &xs)))
+    }
+
+    /// Interpolate base-field values over the canonic coset of the same size
+    pub fn interpolate_base(evals: &[M31]) -> Self {
+        Self::interpolate(&evals.iter().map(|&v| QM31::from(v)).collect::<Vec<_>>())
+    }
+
+    /// The same polynomial with 2^log_size coefficients
+    ///
+    /// # Panics
+    /// Panics if that would drop coefficients.
+    pub fn extend(&self, log_size: u32) -> Self {
+        assert!(log_size >= self.log_size(), "cannot shrink a circle polynomial");
+        let mut coeffs = self.coeffs.clone();
+        coeffs.resize(1 << log_size, QM31::ZERO);
+        Self { coeffs }
+    }
+
+    /// Evaluate over the canonic coset of size 2^log_size, in natural order
+    ///
+    /// With log_size above [`Self::log_size`] this is the low-degree
+    /// extension onto the larger coset.
+    pub fn evaluate(&self, log_size: u32) -> Vec<QM31> {
+        let coeffs = self.extend(log_size).coeffs;
+        let n = coeffs.len();
+        if n == 1 {
+            return coeffs;
+        }
+
+        let half: Vec<CirclePoint> = Coset::canonic(log_size).iter().take(n / 2).collect();
+        let xs: Vec<M31> = half.iter().map(|p| p.x).collect();
+        let (even, odd) = deinterleave(&coeffs);
+        let f0 = evaluate_x(&even, &xs);
+        let f1 = evaluate_x(&odd, &xs);
+
+        let mut out = vec![QM31::ZERO; n];
+        for (i, p) in half.iter().enumerate() {
+            let y_f1 = QM31::from(p.y) * f1[i];
+            out[i] = f0[i] + y_f1;
+            out[n - 1 - i] = f0[i] - y_f1;
+        }
+        out
+    }
+
+    /// Evaluate at every point of `coset`, in coset order
+    ///
+    /// Unlike [`Self::evaluate`] this takes any coset at least the
+    /// polynomial's size, such as the prover's commitment coset, which is
+    /// not closed under conjugation. Each point's x-coordinate is shared
+    /// with no other, but its antipode half a coset on has the negated x,
+    /// so the x-parts still fold in O(n log n).
+    ///
+    /// # Panics
+    /// Panics if the coset is smaller than the polynomial.
+    pub fn evaluate_on(&self, coset: &Coset) -> Vec<QM31> {
+        assert!(coset.log_size >= self.log_size(), "coset too small for the circle polynomial");
+        let (even, odd) = deinterleave(&self.extend(coset.log_size + 1).coeffs);
+        let points: Vec<CirclePoint> = coset.iter().collect();
+        let xs: Vec<M31> = points.iter().map(|p| p.x).collect();
+        let f0 = evaluate_x_antipodal(&even, &xs);
+        let f1 = evaluate_x_antipodal(&odd, &xs);
+        points
+            .iter()
+            .zip(f0.into_iter().zip(f1))
+            .map(|(p, (a, b))| a + QM31::from(p.y) * b)
+            .collect()
+    }
+
+    /// Evaluate at any point of the circle over QM31
+    pub fn eval_at_point(&self, point: &SecureCirclePoint) -> QM31 {
+        // One factor per coefficient bit: y, x, π(x), π²(x), ...
+        let mut factors = Vec::with_capacity(self.log_size() as usize);
+        if self.log_size() > 0 {
+            factors.push(point.y);
+            let mut x = point.x;
+            for _ in 1..self.log_size() {
+                factors.push(x);
+                let sq = x.square();
+                x = sq + sq - QM31::ONE;
+            }
+        }
+        fold(&self.coeffs, &factors)
+    }
+
+    /// Exact quotient by the vanishing polynomial of the canonic coset of
+    /// size 2^log_vanishing
+    ///
+    /// Returns `None` if the polynomial does not vanish on that coset. The
+    /// quotient is computed on the canonic coset of twice this polynomial's
+    /// size, which never meets a smaller canonic coset, and accepted only
+    /// if it fits back into the original size.
+    ///
+    /// # Panics
+    /// Panics unless 1 ≤ log_vanishing ≤ [`Self::log_size`].
+    pub fn divide_by_vanishing(&self, log_vanishing: u32) -> Option<Self> {
+        let log_size = self.log_size();
+        assert!(
+            (1..=log_size).contains(&log_vanishing),
+            "vanishing coset must be non-trivial and no larger than the polynomial"
+        );
+
+        let log_eval = log_size + 1;
+        let denoms: Vec<M31> = Coset::canonic(log_eval)
+            .iter()
+            .map(|p| canonic_vanishing(log_vanishing, p.x))
+            .collect();
+        let quotient: Vec<QM31> = self
+            .evaluate(log_eval)
+            .into_iter()
+            .zip(batch_inverse(&denoms))
+            .map(|(v, d)| v * QM31::from(d))
+            .collect();
+
+        let mut coeffs = Self::interpolate(&quotient).coeffs;
+        let n = 1usize << log_size;
+        if coeffs[n..].iter().any(|c| !c.is_zero()) {
+            return None;
+        }
+        coeffs.truncate(n);
+        Some(Self { coeffs })
+    }
+}
+
+/// Inverse of [`evaluate_x`]: values at `xs` (with xs[m-1-i] = -xs[i]) to
+/// coefficients in the x, π(x), ... basis
+fn interpolate_x(values: Vec<QM31>, xs: &[M31]) -> Vec<QM31> {
+    let m = values.len();
+    if m == 1 {
+        return values;
+    }
+
+    let half = m / 2;
+    let inv_2x = batch_inverse(&xs[..half].iter().map(|x| x.double()).collect::<Vec<_>>());
+    let inv_2 = QM31::from(M31::new(2).inv());
+    let mut even = Vec::with_capacity(half);
+    let mut odd = Vec::with_capacity(half);
+    for i in 0..half {
+        let (a, b) = (values[i], values[m - 1 - i]);
+        even.push((a + b) * inv_2);
+        odd.push((a - b) * QM31::from(inv_2x[i]));
+    }
+
+    let next = double_xs(&xs[..half]);
+    interleave(interpolate_x(even, &next), interpolate_x(odd, &next))
+}
+
+/// Evaluate x, π(x), ... basis coefficients at `xs` (with xs[m-1-i] = -xs[i])
+fn evaluate_x(coeffs: &[QM31], xs: &[M31]) -> Vec<QM31> {
+    let m = coeffs.len();
+    if m == 1 {
+        return coeffs.to_vec();
+    }
+
+    let half = m / 2;
+    let next = double_xs(&xs[..half]);
+    let (even, odd) = deinterleave(coeffs);
+    let e = evaluate_x(&even, &next);
+    let o = evaluate_x(&odd, &next);
+
+    let mut out = vec![QM31::ZERO; m];
+    for i in 0..half {
+        let x_o = QM31::from(xs[i]) * o[i];
+        out[i] = e[i] + x_o;
+        out[m - 1 - i] = e[i] - x_o;
+    }
+    out
+}
+
+/// [`evaluate_x`] for the x-coordinates of a coset in order, where
+/// xs[i + m/2] = -xs[i] and one coefficient per point
+fn evaluate_x_antipodal(coeffs: &[QM31], xs: &[M31]) -> Vec<QM31> {
+    let m = coeffs.len();
+    if m == 1 {
+        return coeffs.to_vec();
+    }
+
+    let half = m / 2;
+    let next = double_xs(&xs[..half]);
+    let (even, odd) = deinterleave(coeffs);
+    let e = evaluate_x_antipodal(&even, &next);
+    let o = evaluate_x_antipodal(&odd, &next);
+
+    let mut out = vec![QM31::ZERO; m];
+    for i in 0..half {
+        let x_o = QM31::from(xs[i]) * o[i];
+        out[i] = e[i] + x_o;
+        out[half + i] = e[i] - x_o;
+    }
+    out
+}
+
+/// Σ_i c_i · Π_k factors[k]^{bit k of i}
+fn fold(coeffs: &[QM31], factors: &[QM31]) -> QM31 {
+    match factors.split_first() {
+        None => coeffs[0],
+        Some((&factor, rest)) => {
+            let (even, odd) = deinterleave(coeffs);
+            fold(&even, rest) + factor * fold(&odd, rest)
+        }
+    }
+}
+
+fn double_xs(xs: &[M31]) -> Vec<M31> {
+    xs.iter().map(|x| x.square().double() - M31::ONE).collect()
+}
+
+fn interleave(even: Vec<QM31>, odd: Vec<QM31>) -> Vec<QM31> {
+    even.into_iter().zip(odd).flat_map(|(e, o)| [e, o]).collect()
+}
+
+fn deinterleave(coeffs: &[QM31]) -> (Vec<QM31>, Vec<QM31>) {
+    let even = coeffs.iter().step_by(2).copied().collect();
+    let odd = coeffs.iter().skip(1).step_by(2).copied().collect();
+    (even, odd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circle::{barycentric_eval, barycentric_weights};
+
+    fn sample(n: usize, seed: u32) -> Vec<QM31> {
+        (0..n as u32)
+            .map(|i| QM31::from_u32(i * 31 + seed, i * i + 7, seed ^ i, 1000 - i))
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolate_evaluate_round_trip() {
+        for log_size in 0..=6 {
+            let evals = sample(1 << log_size, log_size);
+            let poly = CirclePoly::interpolate(&evals);
+            assert_eq!(poly.log_size(), log_size);
+            assert_eq!(poly.evaluate(log_size), evals);
+        }
+    }
+
+    #[test]
+    fn test_eval_at_point_matches_domain_and_barycentric() {
+        let coset = Coset::canonic(4);
+        let evals = sample(16, 3);
+        let poly = CirclePoly::interpolate(&evals);
+        for i in [0usize, 1, 7, 15] {
+            assert_eq!(poly.eval_at_point(&SecureCirclePoint::from_base(coset.at(i))), evals[i]);
+        }
+
+        // Off the domain, agree with the existing barycentric interpolation
+        let base: Vec<M31> = (0..16).map(|i| M31::new(i * 97 + 5)).collect();
+        let point = SecureCirclePoint::from_t(QM31::from_u32(98765, 4321, 1, 77));
+        let weights = barycentric_weights(&coset, &point);
+        assert_eq!(
+            CirclePoly::interpolate_base(&base).eval_at_point(&point),
+            barycentric_eval(&base, &weights)
+        );
+    }
+
+    #[test]
+    fn test_low_degree_extension() {
+        let poly = CirclePoly::interpolate(&sample(8, 11));
+        let extended = poly.evaluate(5);
+        let coset = Coset::canonic(5);
+        for (i, v) in extended.iter().enumerate() {
+            assert_eq!(*v, poly.eval_at_point(&SecureCirclePoint::from_base(coset.at(i))));
+        }
+        assert_eq!(CirclePoly::interpolate(&extended), poly.extend(5));
+    }
+
+    #[test]
+    fn test_evaluate_on_commitment_coset() {
+        let poly = CirclePoly::interpolate(&sample(8, 13));
+        let coset = Coset::commitment(5);
+        let evals = poly.evaluate_on(&coset);
+        assert_eq!(evals.len(), 32);
+        for (i, v) in evals.iter().enumerate() {
+            assert_eq!(*v, poly.eval_at_point(&SecureCirclePoint::from_base(coset.at(i))));
+        }
+        assert_eq!(poly.evaluate_on(&Coset::canonic(5)), poly.evaluate(5));
+    }
+
+    #[test]
+    fn test_divide_by_vanishing() {
+        let log_size = 5;
+        let log_vanishing = 3;
+
+        // q with its top half of coefficients zero, so q·v still fits
+        let mut q_coeffs = sample(1 << (log_size - 1), 5);
+        q_coeffs.resize(1 << log_size, QM31::ZERO);
+        let q = CirclePoly::new(q_coeffs);
+
+        let product: Vec<QM31> = q
+            .evaluate(log_size)
+            .into_iter()
+            .zip(Coset::canonic(log_size).iter())
+            .map(|(v, p)| v * QM31::from(canonic_vanishing(log_vanishing, p.x)))
+            .collect();
+        let p = CirclePoly::interpolate(&product);
+        assert_eq!(p.divide_by_vanishing(log_vanishing), Some(q));
+
+        // Shift by a constant: no longer zero on the vanishing coset
+        let mut shifted = p.coeffs().to_vec();
+        shifted[0] = shifted[0] + QM31::ONE;
+        assert_eq!(CirclePoly::new(shifted).divide_by_vanishing(log_vanishing), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! Poseidon2 permutation over M31
+//!
+//! Width 16, S-box x⁵, 4 + 4 full rounds around 14 partial rounds, with the
+//! linear layers of the Poseidon2 paper (4×4 circulant blocks externally,
+//! 1 + diag(V) internally, V as in Plonky3's M31 instance).
+//!
+//! Round constants are derived rather than copied: constant `j` of full
+//! round `r` (0..8) is keccak("murkl_poseidon2_m31_v1" || le32(r) || le32(j))
+//! reduced mod p from its first four bytes, and partial round `r` uses
+//! round number 8 + r with j = 0. They are tabulated so the on-chain
+//! verifier does not pay for the keccaks; a test regenerates them.
+//!
+//! Two hashes are built on the permutation, both with 32-byte digests of
+//! eight little-endian M31 limbs:
+//! - [`hash`]: a sponge (rate 8, capacity 8) over the input bytes in 3-byte
+//!   chunks, with the input length in the capacity
+//! - [`compress`]: the first eight lanes of the permuted pair of digests,
+//!   for Merkle nodes
+//!
+//! [`hash_tagged`] hashes three field elements with a single permutation,
+//! for the commitment and nullifier that `air::poseidon2` proves in-circuit.
+
+use crate::hash::Hash32;
+use crate::m31::M31;
+
+/// State width in M31 lanes
+pub const WIDTH: usize = 16;
+
+/// Lanes absorbed per permutation by [`hash`]
+pub const RATE: usize = 8;
+
+/// Digest limbs; a [`Hash32`] holds them as little-endian u32s
+pub const DIGEST_LIMBS: usize = 8;
+
+pub(crate) const HALF_FULL_ROUNDS: usize = 4;
+
+/// Lane 0 of a [`hash_tagged`] input for a deposit commitment
+pub const COMMITMENT_TAG: u32 = 1;
+
+/// Lane 0 of a [`hash_tagged`] input for a nullifier
+pub const NULLIFIER_TAG: u32 = 2;
+
+/// Full-round constants, derived as described in the module docs
+pub(crate) const EXTERNAL_CONSTANTS: [[u32; WIDTH]; 2 * HALF_FULL_ROUNDS] = [
+    [0x48357780, 0x2d90eca5, 0x351ba84b, 0x67749a14, 0x538b70f7, 0x57100378, 0x4a0e28dc, 0x66a552cd, 0x6290ae0b, 0x7259dee2, 0x01e5fd22, 0x1ef6e27d, 0x736a94ee, 0x676cf894, 0x6d87b2b4, 0x3f09b85e],
+    [0x54cee4c8, 0x3e72b93a, 0x34fdc009, 0x08554960, 0x5c2c2bc3, 0x56cce763, 0x3a66b0be, 0x1cbec630, 0x39809484, 0x016bb309, 0x6c304980, 0x5fa0a649, 0x1300fca6, 0x48b64fab, 0x5e77a9b7, 0x5b1adad3],
+    [0x2852aabb, 0x3505c0ec, 0x18560a4a, 0x680d22bc, 0x2269173d, 0x1f98842f, 0x21fc7940, 0x224e84d3, 0x565e3b9e, 0x3e581e3b, 0x7042061f, 0x10b22b98, 0x325ede5e, 0x5fad211b, 0x04969584, 0x16f3d6d7],
+    [0x1cf1162f, 0x1986594f, 0x2e339e3e, 0x20a5aa05, 0x6e4d45f7, 0x47df1f98, 0x5a6c8424, 0x02f21ca2, 0x70eebf09, 0x75185a75, 0x39877189, 0x752e2eef, 0x4ea2f017, 0x042edc1e, 0x49239f5a, 0x22b985ce],
+    [0x49e75b6f, 0x513e85eb, 0x115131fb, 0x18f85251, 0x4929afc0, 0x54aa8181, 0x50c1a4f0, 0x14f4897e, 0x239f5809, 0x60b2c64e, 0x52bf1c6d, 0x5be274af, 0x0a98e339, 0x6ed818ec, 0x0a81f10e, 0x4cdc5759],
+    [0x1c5ac513, 0x2a632f17, 0x4e51063c, 0x61f67e0f, 0x44745d88, 0x77376f1a, 0x01c3f73a, 0x5eaac6f1, 0x4a657871, 0x43245ca9, 0x41ee7bff, 0x52986bdc, 0x1e1c79bb, 0x75e914e9, 0x5c4d7a54, 0x29961695],
+    [0x08979458, 0x231130eb, 0x1093f96d, 0x306abfc0, 0x2d8e989d, 0x7db91a68, 0x78e32804, 0x0f55ae5b, 0x5be8ec9c, 0x6ddfa833, 0x2fc4a209, 0x21fc3848, 0x1a972564, 0x2be22dfb, 0x4b6eee1d, 0x5cc5c7cc],
+    [0x692e851f, 0x010582ab, 0x6b24f0d7, 0x4836753d, 0x67bf2e2c, 0x414866f8, 0x113dc336, 0x7ae52e0d, 0x545adf4b, 0x564fc9ea, 0x1eaed908, 0x06cd7def, 0x2419ae9a, 0x65e3d377, 0x0ab8685a, 0x0361852d],
+];
+
+/// Partial-round constants, added to lane 0
+pub(crate) const INTERNAL_CONSTANTS: [u32; 14] = [
+    0x42f77e81, 0x13fab0a1, 0x2168b256, 0x7ece749f, 0x02798ef9, 0x05cff3d2, 0x2f5f27f5,
+    0x4137fac6, 0x1eb134f9, 0x58484993, 0x4bd0307b, 0x4fbccbf4, 0x172edc1b, 0x5eba83ae,
+];
+
+/// V in the internal matrix 1 + diag(V); V₀ = -2
+pub(crate) const INTERNAL_DIAG: [u32; WIDTH] = [
+    0x7ffffffd, 1, 1 << 1, 1 << 2, 1 << 3, 1 << 4, 1 << 5, 1 << 6,
+    1 << 7, 1 << 8, 1 << 10, 1 << 12, 1 << 13, 1 << 14, 1 << 15, 1 << 16,
+];
+
+/// Apply the permutation in place
+pub fn permute(state: &mut [M31; WIDTH]) {
+    external_linear(state);
+    for constants in &EXTERNAL_CONSTANTS[..HALF_FULL_ROUNDS] {
+        full_round(state, constants);
+    }
+    for &constant in &INTERNAL_CONSTANTS {
+        state[0] = sbox(state[0] + M31::new(constant));
+        internal_linear(state);
+    }
+    for constants in &EXTERNAL_CONSTANTS[HALF_FULL_ROUNDS..] {
+        full_round(state, constants);
+    }
+}
+
+/// Sponge hash of the concatenated `inputs`
+pub fn hash(inputs: &[&[u8]]) -> Hash32 {
+    let len: usize = inputs.iter().map(|input| input.len()).sum();
+    let mut state = [M31::ZERO; WIDTH];
+    state[RATE] = M31::new(len as u32);
+
+    let (mut lane, mut word, mut shift) = (0, 0u32, 0);
+    for &byte in inputs.iter().flat_map(|input| input.iter()) {
+        word |= (byte as u32) << shift;
+        shift += 8;
+        if shift == 24 {
+            state[lane] = M31::new(word);
+            (word, shift) = (0, 0);
+            lane += 1;
+            if lane == RATE {
+                permute(&mut state);
+                lane = 0;
+            }
+        }
+    }
+    if shift > 0 {
+        state[lane] = M31::new(word);
+        lane += 1;
+    }
+    if lane > 0 || len == 0 {
+        permute(&mut state);
+    }
+    digest(&state)
+}
+
+/// Digest of the permuted state [tag, a, b, c, 0, …, 0]
+///
+/// One permutation, so it is cheap to prove: an AIR checks it in 23 rows.
+/// `tag` keeps the uses apart ([`COMMITMENT_TAG`], [`NULLIFIER_TAG`]).
+pub fn hash_tagged(tag: u32, inputs: [M31; 3]) -> Hash32 {
+    let mut state = [M31::ZERO; WIDTH];
+    state[0] = M31::new(tag);
+    state[1..4].copy_from_slice(&inputs);
+    permute(&mut state);
+    digest(&state)
+}
+
+/// Merkle node hash: truncated permutation of `left || right`
+pub fn compress(left: &Hash32, right: &Hash32) -> Hash32 {
+    let mut state = [M31::ZERO; WIDTH];
+    let limbs = left.chunks_exact(4).chain(right.chunks_exact(4));
+    for (lane, limb) in state.iter_mut().zip(limbs) {
+        *lane = M31::new(u32::from_le_bytes([limb[0], limb[1], limb[2], limb[3]]));
+    }
+    permute(&mut state);
+    digest(&state)
+}
+
+fn digest(state: &[M31; WIDTH]) -> Hash32 {
+    let mut out = [0u8; 32];
+    for (bytes, limb) in out.chunks_exact_mut(4).zip(&state[..DIGEST_LIMBS]) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+#[inline]
+fn sbox(x: M31) -> M31 {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+fn full_round(state: &mut [M31; WIDTH], constants: &[u32; WIDTH]) {
+    for (lane, &constant) in state.iter_mut().zip(constants) {
+        *lane = sbox(*lane + M31::new(constant));
+    }
+    external_linear(state);
+}
+
+/// circ(2, 3, 1, 1) on each block of four, then each lane adds the sum of
+/// its position across blocks
+fn external_linear(state: &mut [M31; WIDTH]) {
+    for block in state.chunks_exact_mut(4) {
+        let t01 = block[0] + block[1];
+        let t23 = block[2] + block[3];
+        let t0123 = t01 + t23;
+        let t01123 = t0123 + block[1];
+        let t01233 = t0123 + block[3];
+        block[3] = t01233 + block[0].double();
+        block[1] = t01123 + block[2].double();
+        block[0] = t01123 + t01;
+        block[2] = t01233 + t23;
+    }
+    let mut sums = [M31::ZERO; 4];
+    for (i, &lane) in state.iter().enumerate() {
+        sums[i % 4] += lane;
+    }
+    for (i, lane) in state.iter_mut().enumerate() {
+        *lane += sums[i % 4];
+    }
+}
+
+fn internal_linear(state: &mut [M31; WIDTH]) {
+    let sum = state.iter().fold(M31::ZERO, |acc, &lane| acc + lane);
+    for (lane, &v) in state.iter_mut().zip(&INTERNAL_DIAG) {
+        *lane = sum + M31::new(v) * *lane;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::keccak_hash;
+
+    fn derived_constant(round: u32, j: u32) -> u32 {
+        let h = keccak_hash(&[b"murkl_poseidon2_m31_v1", &round.to_le_bytes(), &j.to_le_bytes()]);
+        M31::new(u32::from_le_bytes([h[0], h[1], h[2], h[3]])).value()
+    }
+
+    #[test]
+    fn test_round_constants_match_derivation() {
+        for (r, constants) in EXTERNAL_CONSTANTS.iter().enumerate() {
+            for (j, &c) in constants.iter().enumerate() {
+                assert_eq!(c, derived_constant(r as u32, j as u32), "full round {r}, lane {j}");
+            }
+        }
+        for (r, &c) in INTERNAL_CONSTANTS.iter().enumerate() {
+            assert_eq!(c, derived_constant((2 * HALF_FULL_ROUNDS + r) as u32, 0), "partial round {r}");
+        }
+    }
+
+    #[test]
+    fn test_external_block_is_circulant() {
+        let mut state = [M31::ZERO; WIDTH];
+        state[0] = M31::ONE;
+        external_linear(&mut state);
+        // Column 0 of circ(2, 3, 1, 1) is (2, 1, 1, 3); other blocks get it once more
+        let column = [2, 1, 1, 3].map(M31::new);
+        for (i, lane) in state.iter().enumerate() {
+            let expected = if i < 4 { column[i].double() } else { column[i % 4] };
+            assert_eq!(*lane, expected, "lane {i}");
+        }
+    }
+
+    #[test]
+    fn test_hash_separates_lengths_and_chunking() {
+        assert_ne!(hash(&[]), hash(&[&[0]]));
+        assert_ne!(hash(&[&[0]]), hash(&[&[0, 0]]));
+        assert_eq!(hash(&[b"murkl", b"_trace"]), hash(&[b"murkl_trace"]));
+
+        // Lengths around the 24-byte block boundary
+        let data = [7u8; 49];
+        let digests: Vec<Hash32> = (22..=49).map(|n| hash(&[&data[..n]])).collect();
+        for (i, a) in digests.iter().enumerate() {
+            assert!(digests[i + 1..].iter().all(|b| a != b));
+        }
+
+        // Digest limbs are canonical M31s
+        for limb in hash(&[b"murkl"]).chunks_exact(4) {
+            assert!(u32::from_le_bytes(limb.try_into().unwrap()) < crate::m31::M31_PRIME);
+        }
+    }
+
+    #[test]
+    fn test_compress_is_ordered() {
+        let a = hash(&[b"left"]);
+        let b = hash(&[b"right"]);
+        assert_ne!(compress(&a, &b), compress(&b, &a));
+        assert_ne!(compress(&a, &b), hash(&[&a, &b]));
+    }
+
+    /// Pinned digests; stark-verifier's copy of the permutation checks the same
+    #[test]
+    fn test_known_answer() {
+        assert_eq!(
+            hex_digest(&hash(&[b"murkl"])),
+            "be5f7f60d05bb107e3d6f22d7ff11319b441a740f16ee03ab775071d4da0934f"
+        );
+        assert_eq!(
+            hex_digest(&compress(&hash(&[b"left"]), &hash(&[b"right"]))),
+            "3ae32e6109340d715bba6149fc931a243257f26a6555cd375ccff92d2343f27d"
+        );
+        assert_eq!(
+            hex_digest(&hash_tagged(NULLIFIER_TAG, [M31::new(5), M31::new(987_654), M31::new(2)])),
+            "71ba824d07930a7e6edd583cc52d4155cf013328c28b0c6bc9908054aa64175d"
+        );
+    }
+
+    fn hex_digest(digest: &Hash32) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
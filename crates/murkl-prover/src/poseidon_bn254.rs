@@ -0,0 +1,85 @@
+//! Poseidon over BN254, the proof hash stark-verifier checks with the
+//! `sol_poseidon` syscall
+//!
+//! Circom parameters with x⁵ (light-poseidon, the implementation behind the
+//! Solana runtime's syscall), field elements little-endian. Only verifiers
+//! built with their `poseidon-syscall` feature accept these proofs; read the
+//! `hash_kinds` a deployment published in its `VerifierParams` before
+//! choosing this over [`HashKind::Poseidon2`](crate::HashKind::Poseidon2).
+//!
+//! Sponge: the concatenated input is split into 31-byte elements, so each
+//! is below the modulus, and absorbed 11 at a time as
+//! `state = P(state, e₁, …, e₁₁)` starting from the input length as an
+//! element. Merkle nodes are `P(left, right)`. Digests are field elements,
+//! so nodes built from them always hash.
+
+use crate::hash::Hash32;
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+/// Elements absorbed per permutation next to the running state; circom
+/// parameters stop at 12 inputs
+const RATE: usize = 11;
+/// Bytes per absorbed element
+const CHUNK_BYTES: usize = 31;
+
+fn permute(vals: &[&[u8]]) -> Hash32 {
+    Poseidon::<Fr>::new_circom(vals.len())
+        .and_then(|mut poseidon| poseidon.hash_bytes_le(vals))
+        .expect("inputs are at most 12 elements below the modulus")
+}
+
+/// Sponge hash of the concatenated `inputs`
+pub fn hash(inputs: &[&[u8]]) -> Hash32 {
+    let data = inputs.concat();
+    let mut state = [0u8; 32];
+    state[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+    if data.is_empty() {
+        return permute(&[&state]);
+    }
+
+    for block in data.chunks(RATE * CHUNK_BYTES) {
+        let mut vals: Vec<&[u8]> = Vec::with_capacity(RATE + 1);
+        vals.push(&state);
+        vals.extend(block.chunks(CHUNK_BYTES));
+        state = permute(&vals);
+    }
+    state
+}
+
+/// Merkle node hash
+///
+/// # Panics
+///
+/// If either side is not a field element; digests from [`hash`] and
+/// [`compress`] always are.
+pub fn compress(left: &Hash32, right: &Hash32) -> Hash32 {
+    permute(&[left, right])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_separates_lengths_and_blocks() {
+        assert_ne!(hash(&[]), hash(&[&[0]]));
+        assert_ne!(hash(&[&[0]]), hash(&[&[0, 0]]));
+        assert_eq!(hash(&[b"murkl", b"_trace"]), hash(&[b"murkl_trace"]));
+
+        let a = hash(&[b"left"]);
+        let b = hash(&[b"right"]);
+        assert_ne!(compress(&a, &b), compress(&b, &a));
+    }
+
+    /// Pinned digests; stark-verifier's syscall path checks the same
+    #[test]
+    fn test_known_answer() {
+        let hex = |d: Hash32| d.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(hash(&[b"murkl"])), "b3cf9d54232c52116ce2a0dc8d241e335e9ee3ddf6ae254bae60d3bed4ca5118");
+        assert_eq!(
+            hex(compress(&hash(&[b"left"]), &hash(&[b"right"]))),
+            "97bc322443c730d969c2f6e6aa3a4e332be98191186fc92a5f4ade2683c86b2d"
+        );
+    }
+}
@@ -5,11 +5,19 @@
 //! # Overview
 //!
 //! The proving process:
-//! 1. Commit to trace columns via Merkle trees
-//! 2. Evaluate AIR constraints at all rows
-//! 3. Compose constraints with random coefficients
-//! 4. Run FRI (Fast Reed-Solomon IOPP) on the composition
-//! 5. Generate query proofs for soundness
+//! 1. Extend trace columns onto the commitment coset and commit via Merkle trees
+//! 2. Evaluate AIR constraints over the commitment coset
+//! 3. Compose constraints with random coefficients and divide by the
+//!    trace-domain vanishing polynomial
+//! 4. Sample an out-of-domain point and evaluate the trace there, one trace
+//!    step further, and the committed composition there
+//! 5. Run FRI (Fast Reed-Solomon IOPP) on the composition
+//! 6. Optionally grind a proof-of-work nonce ([`ProverConfig::pow_bits`]),
+//!    which makes every query draw cost the prover `2^pow_bits` hashes
+//! 7. Generate query proofs for soundness
+//!
+//! [`Prover::check_witness`] runs the constraints over the trace alone, so
+//! callers can reject a bad witness before step 1.
 //!
 //! # Example
 //!
@@ -37,13 +45,38 @@
 //! - [`ProverConfig::high_security()`] — Maximum security (~10KB)
 
 #[cfg(not(feature = "std"))]
-use alloc::{vec, vec::Vec};
-
-use crate::air::{ConstraintEvaluator, Trace, compose_constraints};
-use crate::fri::{FriConfig, FriProof, FriProver};
-use crate::m31::M31;
-use crate::merkle::{Hash, MerkleCommitment, hash_bytes};
+use alloc::{format, string::String, vec, vec::Vec};
+
+#[cfg(feature = "prove")]
+use crate::air::{ConstraintEvaluator, Trace, TraceColumn, compose_constraints, verify_constraints};
+#[cfg(feature = "prove")]
+use crate::cache::EvaluationCache;
+#[cfg(feature = "prove")]
+use crate::circle::{
+    barycentric_eval, bit_reverse_index, canonic_vanishing, evaluate_on_domain, icfft, subgroup_generator, Coset,
+    FftTwiddles, CIRCLE_GENERATOR,
+};
+use crate::circle::SecureCirclePoint;
+#[cfg(feature = "prove")]
+use crate::fri::FriProver;
+#[cfg(feature = "prove")]
+use crate::m31::batch::batch_inverse;
+#[cfg(feature = "prove")]
+use crate::merkle::MerkleCommitment;
+#[cfg(feature = "prove")]
+use crate::metrics::{no_clock, ProveMetrics, StageRecorder};
+#[cfg(feature = "prove")]
 use crate::types::{Proof, ProofError, PublicInputs};
+#[cfg(feature = "prove")]
+use crate::checkpoint::{checkpoint_error, put_m31s, StateReader};
+use crate::checkpoint::Checkpoint;
+use crate::metrics::ProveStage;
+use crate::fri::FriConfig;
+use crate::m31::M31;
+use crate::qm31::QM31;
+use crate::merkle::{Hash, hash_bytes};
+use crate::hash::HashKind;
+use crate::SecurityLevel;
 
 /// Prover configuration
 #[derive(Clone, Debug)]
@@ -54,6 +87,11 @@ pub struct ProverConfig {
     pub num_queries: usize,
     /// Blowup factor (log2)
     pub log_blowup_factor: u32,
+    /// Proof-of-work bits ground before queries are drawn; zero skips
+    /// grinding and leaves the transcript as it was
+    pub pow_bits: u32,
+    /// Threads the nonce search runs on
+    pub grind_threads: usize,
 }
 
 impl Default for ProverConfig {
@@ -62,6 +100,8 @@ impl Default for ProverConfig {
             fri_config: FriConfig::default(),
             num_queries: 50,
             log_blowup_factor: 4,
+            pow_bits: 0,
+            grind_threads: 1,
         }
     }
 }
@@ -79,9 +119,43 @@ impl ProverConfig {
             fri_config,
             num_queries,
             log_blowup_factor,
+            pow_bits: 0,
+            grind_threads: 1,
+        }
+    }
+
+    /// Grind `pow_bits` of proof of work on `threads` threads before
+    /// drawing queries
+    pub fn with_pow(mut self, pow_bits: u32, threads: usize) -> Self {
+        self.pow_bits = pow_bits;
+        self.grind_threads = threads.max(1);
+        self
+    }
+
+    /// Queries, blowup and proof of work of `level`
+    pub fn for_level(level: SecurityLevel) -> Self {
+        Self {
+            fri_config: FriConfig::for_level(level),
+            num_queries: level.num_queries(),
+            log_blowup_factor: level.log_blowup(),
+            pow_bits: level.pow_bits(),
+            grind_threads: 1,
         }
     }
 
+    /// Highest level this configuration's proofs meet, `None` below
+    /// [`SecurityLevel::Demo`]
+    ///
+    /// Queries and blowup are taken as the lower of this config's and its
+    /// FRI config's. Proof of work is margin on top and does not count.
+    pub fn security_level(&self) -> Option<SecurityLevel> {
+        SecurityLevel::of(
+            self.num_queries.min(self.fri_config.num_queries),
+            self.log_blowup_factor.min(self.fri_config.log_blowup_factor),
+            HashKind::Keccak as u8,
+        )
+    }
+
     /// High security configuration
     pub fn high_security() -> Self {
         Self::new(100, 4)
@@ -93,15 +167,70 @@ impl ProverConfig {
     }
 }
 
+/// Why a trace would only produce a proof that fails verification
+#[cfg(feature = "prove")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessError {
+    /// Trace shape the prover cannot build a domain or constraints from
+    InvalidTrace(String),
+    /// Constraints not vanishing on transition rows, as (row, constraint)
+    ConstraintViolations(Vec<(usize, String)>),
+    /// Constraints failing only on the rows that wrap to the start of the
+    /// trace, as (row, constraint). The composition is then not divisible
+    /// by the trace domain's vanishing polynomial, so its value at the
+    /// out-of-domain point cannot match the trace there.
+    OodsInconsistent(Vec<(usize, String)>),
+}
+
+#[cfg(feature = "prove")]
+impl core::fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let first = |failures: &[(usize, String)]| {
+            failures
+                .first()
+                .map(|(row, name)| format!("{} at row {}", name, row))
+                .unwrap_or_default()
+        };
+        match self {
+            Self::InvalidTrace(msg) => write!(f, "Invalid trace: {}", msg),
+            Self::ConstraintViolations(failures) => write!(
+                f,
+                "{} constraint violation(s), first: {}",
+                failures.len(),
+                first(failures)
+            ),
+            Self::OodsInconsistent(failures) => write!(
+                f,
+                "trace does not wrap around ({} failure(s), first: {})",
+                failures.len(),
+                first(failures)
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "prove")]
+impl From<WitnessError> for ProofError {
+    fn from(err: WitnessError) -> Self {
+        match err {
+            WitnessError::InvalidTrace(msg) => ProofError::InvalidTrace(msg),
+            other => ProofError::ConstraintViolation(format!("{}", other)),
+        }
+    }
+}
+
 /// Circle STARK prover
+#[cfg(feature = "prove")]
 pub struct Prover {
     config: ProverConfig,
+    policy: Option<SecurityLevel>,
 }
 
+#[cfg(feature = "prove")]
 impl Prover {
     /// Create a new prover with the given configuration
     pub fn new(config: ProverConfig) -> Self {
-        Self { config }
+        Self { config, policy: None }
     }
 
     /// Create with default configuration
@@ -109,6 +238,82 @@ impl Prover {
         Self::new(ProverConfig::default())
     }
 
+    /// Refuse to prove unless the configuration meets `min`
+    ///
+    /// Every proving method then fails with
+    /// [`ProofError::BelowSecurityPolicy`] before any work if
+    /// [`ProverConfig::security_level`] is below `min`.
+    pub fn with_policy(mut self, min: SecurityLevel) -> Self {
+        self.policy = Some(min);
+        self
+    }
+
+    fn check_policy(&self) -> Result<(), ProofError> {
+        let Some(min) = self.policy else {
+            return Ok(());
+        };
+        match self.config.security_level() {
+            Some(level) if level >= min => Ok(()),
+            level => Err(ProofError::BelowSecurityPolicy(format!(
+                "{} queries at blowup 2^{} meet {}, policy requires {}",
+                self.config.num_queries,
+                self.config.log_blowup_factor,
+                level.map_or("no level".into(), |level| level.to_string()),
+                min
+            ))),
+        }
+    }
+
+    /// Check a trace against the constraints without proving
+    ///
+    /// Runs in time linear in the trace, before any extension, commitment
+    /// or FRI work, so a bad witness (e.g. a wrong password giving a
+    /// mismatched secret) is reported per row and constraint instead of
+    /// as a proof that later fails verification.
+    pub fn check_witness<E: ConstraintEvaluator>(&self, evaluator: &E, trace: &Trace) -> Result<(), WitnessError> {
+        if trace.num_rows < 2 || !trace.num_rows.is_power_of_two() {
+            return Err(WitnessError::InvalidTrace(format!(
+                "{} rows; the trace length must be a power of two of at least 2",
+                trace.num_rows
+            )));
+        }
+        if trace.columns.iter().any(|c| c.values.len() != trace.num_rows) {
+            return Err(WitnessError::InvalidTrace("columns differ in length".into()));
+        }
+        let constraints = evaluator.constraints();
+        for constraint in &constraints {
+            if let Some(&column) = constraint.columns.iter().find(|&&c| c >= trace.num_columns()) {
+                return Err(WitnessError::InvalidTrace(format!(
+                    "{} reads column {} but the trace has {}",
+                    constraint.name,
+                    column,
+                    trace.num_columns()
+                )));
+            }
+        }
+
+        verify_constraints(evaluator, trace).map_err(WitnessError::ConstraintViolations)?;
+
+        // verify_constraints stops short of the last two rows; the quotient
+        // needs them too, with offsets wrapping to the first rows
+        let mut wrap_failures = Vec::new();
+        for row in trace.num_rows - 2..trace.num_rows {
+            for (i, eval) in evaluator.evaluate(trace, row).iter().enumerate() {
+                if !eval.is_zero() {
+                    let name = constraints
+                        .get(i)
+                        .map(|c| c.name.clone())
+                        .unwrap_or_else(|| format!("constraint_{}", i));
+                    wrap_failures.push((row, name));
+                }
+            }
+        }
+        if !wrap_failures.is_empty() {
+            return Err(WitnessError::OodsInconsistent(wrap_failures));
+        }
+        Ok(())
+    }
+
     /// Generate a proof for the given trace and constraints
     pub fn prove<E: ConstraintEvaluator>(
         &self,
@@ -116,54 +321,291 @@ impl Prover {
         trace: &Trace,
         public_inputs: PublicInputs,
     ) -> Result<Proof, ProofError> {
-        let log_trace_length = trace.log_length();
-        // Note: In a full implementation, we'd LDE the composition polynomial
-        // For now, use trace length as the domain size
-        let log_domain_size = log_trace_length;
+        self.prove_recorded(evaluator, trace, public_inputs, &mut StageRecorder::new(no_clock), &mut Stages::unsaved())
+    }
 
-        // Step 1: Commit to trace columns
-        let trace_commitments = self.commit_trace(trace);
+    /// [`Prover::prove`], also reporting time and hashes per stage
+    ///
+    /// `recorder` is timed from its creation, so build it right before the
+    /// call; its hook sees each stage as it finishes.
+    pub fn prove_with_metrics<E: ConstraintEvaluator>(
+        &self,
+        evaluator: &E,
+        trace: &Trace,
+        public_inputs: PublicInputs,
+        mut recorder: StageRecorder<'_>,
+    ) -> Result<(Proof, ProveMetrics), ProofError> {
+        let proof = self.prove_recorded(evaluator, trace, public_inputs, &mut recorder, &mut Stages::unsaved())?;
+        let proof_bytes = proof.to_bytes().len();
+        Ok((proof, recorder.into_metrics(proof_bytes)))
+    }
 
-        // Step 2: Evaluate constraints
-        let constraint_evals = self.evaluate_constraints(evaluator, trace);
+    /// [`Prover::prove`], saving a [`Checkpoint`] after every stage
+    ///
+    /// `sink` gets the checkpoint each time a stage finishes; hand the last
+    /// one back as `resume` to restore the finished stages and carry on
+    /// from the next, e.g. after the tab or process proving a large trace
+    /// was killed. The proof is the one an uninterrupted run gives. A
+    /// checkpoint taken for another trace, configuration, AIR or public
+    /// inputs is rejected.
+    ///
+    /// Checkpoints hold the extended trace, so they are as secret as the
+    /// witness, and several times its size.
+    pub fn prove_resumable<E: ConstraintEvaluator>(
+        &self,
+        evaluator: &E,
+        trace: &Trace,
+        public_inputs: PublicInputs,
+        resume: Option<Checkpoint>,
+        sink: &mut dyn FnMut(&Checkpoint),
+    ) -> Result<Proof, ProofError> {
+        let fingerprint = self.checkpoint_fingerprint(evaluator, trace, &public_inputs);
+        let checkpoint = match resume {
+            Some(checkpoint) if *checkpoint.fingerprint() != fingerprint => {
+                return Err(checkpoint_error("taken for a different trace, configuration or AIR"));
+            }
+            Some(checkpoint) => checkpoint,
+            None => Checkpoint::new(fingerprint),
+        };
+        let mut stages = Stages { checkpoint, sink: Some(sink) };
+        self.prove_recorded(evaluator, trace, public_inputs, &mut StageRecorder::new(no_clock), &mut stages)
+    }
 
-        // Step 3: Get random coefficients (Fiat-Shamir from transcript)
-        let mut transcript = Transcript::new();
-        for commitment in &trace_commitments {
-            transcript.append(&commitment.root());
+    /// Binds a checkpoint to everything the proof depends on
+    fn checkpoint_fingerprint<E: ConstraintEvaluator>(&self, evaluator: &E, trace: &Trace, public_inputs: &PublicInputs) -> Hash {
+        let fri = &self.config.fri_config;
+        let mut bytes = b"murkl-prover-checkpoint-v1".to_vec();
+        for word in [
+            self.config.num_queries as u32,
+            self.config.log_blowup_factor,
+            self.config.pow_bits,
+            fri.log_folding_factor,
+            fri.log_final_poly_degree,
+            fri.final_layer as u32,
+        ] {
+            bytes.extend_from_slice(&word.to_le_bytes());
         }
+        for constraint in evaluator.constraints() {
+            bytes.extend_from_slice(&(constraint.name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(constraint.name.as_bytes());
+        }
+        bytes.extend_from_slice(&(trace.num_columns() as u32).to_le_bytes());
+        for column in &trace.columns {
+            put_m31s(&mut bytes, &column.values);
+        }
+        bytes.extend_from_slice(&public_inputs.to_bytes());
+        hash_bytes(&bytes)
+    }
 
-        let num_constraints = constraint_evals.first().map(|c| c.len()).unwrap_or(0);
-        let random_coefficients = transcript.challenge_scalars(num_constraints);
-
-        // Step 4: Compose constraints into a single polynomial
-        let composition = compose_constraints(&constraint_evals, &random_coefficients);
-
-        // Step 5: Commit to composition polynomial
-        let composition_commitment = MerkleCommitment::commit(&composition);
-        transcript.append(&composition_commitment.root());
+    fn prove_recorded<E: ConstraintEvaluator>(
+        &self,
+        evaluator: &E,
+        trace: &Trace,
+        public_inputs: PublicInputs,
+        recorder: &mut StageRecorder<'_>,
+        stages: &mut Stages<'_>,
+    ) -> Result<Proof, ProofError> {
+        self.check_policy()?;
+        let log_trace_length = trace.log_length();
+        let log_domain_size = log_trace_length + self.config.log_blowup_factor;
+        let domain = Coset::commitment(log_domain_size);
+        let mut cache = EvaluationCache::new();
+
+        // Step 1: Extend the trace off its own domain, then commit
+        let extended = match stages.restored(ProveStage::Extend) {
+            Some(state) => read_extended(state)?,
+            None => {
+                let extended = self.extend_trace(trace, &domain);
+                stages.save(ProveStage::Extend, |out| write_extended(out, &extended));
+                extended
+            }
+        };
+        recorder.finish(ProveStage::Extend, 0);
+        let trace_commitments = match stages.restored(ProveStage::CommitTrace) {
+            Some(state) => read_state(state, |reader| {
+                let count = reader.count(4)?;
+                (0..count).map(|_| MerkleCommitment::read_state(reader)).collect()
+            })?,
+            None => {
+                let commitments = self.commit_trace(&extended);
+                stages.save(ProveStage::CommitTrace, |out| {
+                    out.extend_from_slice(&(commitments.len() as u32).to_le_bytes());
+                    for commitment in &commitments {
+                        commitment.write_state(out);
+                    }
+                });
+                commitments
+            }
+        };
+        recorder.finish(ProveStage::CommitTrace, trace_commitments.iter().map(|c| c.hash_count()).sum());
+
+        let (composition, composition_commitment, mut transcript) = match stages.restored(ProveStage::Composition) {
+            Some(state) => {
+                recorder.finish(ProveStage::Constraints, 0);
+                let (commitment, transcript) = read_state(state, |reader| {
+                    Ok((MerkleCommitment::read_state(reader)?, Transcript::read_state(reader)?))
+                })?;
+                let composition = commitment
+                    .values()
+                    .ok_or_else(|| checkpoint_error("composition without values"))?
+                    .to_vec();
+                recorder.finish(ProveStage::Composition, 0);
+                (composition, commitment, transcript)
+            }
+            None => {
+                // Step 2: Evaluate constraints over the commitment coset
+                let constraint_evals = match stages.restored(ProveStage::Constraints) {
+                    Some(state) => read_state(state, |reader| {
+                        let rows = reader.count(4)?;
+                        (0..rows).map(|_| reader.m31s()).collect()
+                    })?,
+                    None => {
+                        let evals = self.evaluate_constraints(evaluator, &extended);
+                        stages.save(ProveStage::Constraints, |out| {
+                            out.extend_from_slice(&(evals.len() as u32).to_le_bytes());
+                            for row in &evals {
+                                put_m31s(out, row);
+                            }
+                        });
+                        evals
+                    }
+                };
+                recorder.finish(ProveStage::Constraints, 0);
+
+                // Step 3: Get random coefficients (Fiat-Shamir from transcript)
+                let mut transcript = Transcript::new();
+                for commitment in &trace_commitments {
+                    transcript.append(&commitment.root());
+                }
 
-        // Step 6: FRI prove
-        let fri_proof = self.prove_fri(&composition, log_domain_size, &mut transcript)?;
+                let num_constraints = constraint_evals.first().map(|c| c.len()).unwrap_or(0);
+                let random_coefficients = transcript.challenge_scalars(num_constraints);
+
+                // Step 4: Compose constraints and divide out the trace domain
+                let composition = compose_constraints(&constraint_evals, &random_coefficients);
+                let composition = self.quotient(composition, &domain, log_trace_length);
+
+                // Step 5: Commit to composition polynomial
+                let composition_commitment = MerkleCommitment::commit(&composition);
+                transcript.append(&composition_commitment.root());
+                stages.save(ProveStage::Composition, |out| {
+                    composition_commitment.write_state(out);
+                    transcript.write_state(out);
+                });
+                recorder.finish(ProveStage::Composition, composition_commitment.hash_count() + transcript.hash_count());
+                (composition, composition_commitment, transcript)
+            }
+        };
+        let composition_root = composition_commitment.root();
+        let mut transcript_hashes = transcript.hash_count();
+
+        // Step 6: Out-of-domain sampling
+        let (trace_oods, trace_oods_next, composition_oods) = match stages.restored(ProveStage::Oods) {
+            Some(state) => {
+                let (trace_oods, trace_oods_next, composition_oods, restored) = read_state(state, |reader| {
+                    Ok((read_qm31s(reader)?, read_qm31s(reader)?, read_qm31(reader)?, Transcript::read_state(reader)?))
+                })?;
+                transcript = restored;
+                recorder.finish(ProveStage::Oods, 0);
+                (trace_oods, trace_oods_next, composition_oods)
+            }
+            None => {
+                let oods_point = transcript.challenge_circle_point();
+                let trace_oods = self.trace_values_at(trace, &oods_point, &mut cache);
+                let next_point = oods_point.add_base(subgroup_generator(log_trace_length));
+                let trace_oods_next = self.trace_values_at(trace, &next_point, &mut cache);
+                let composition_oods = self.composition_at(&composition, log_domain_size, &oods_point, &mut cache);
+                for value in trace_oods.iter().chain(&trace_oods_next) {
+                    transcript.append_qm31(*value);
+                }
+                transcript.append_qm31(composition_oods);
+                stages.save(ProveStage::Oods, |out| {
+                    put_qm31s(out, &trace_oods);
+                    put_qm31s(out, &trace_oods_next);
+                    out.extend_from_slice(&composition_oods.to_bytes());
+                    transcript.write_state(out);
+                });
+                recorder.finish(ProveStage::Oods, transcript.hash_count() - transcript_hashes);
+                (trace_oods, trace_oods_next, composition_oods)
+            }
+        };
+        transcript_hashes = transcript.hash_count();
+
+        // Step 7: FRI folding
+        let fri_prover = match stages.restored(ProveStage::Fri) {
+            Some(state) => {
+                let (fri_prover, restored) = read_state(state, |reader| {
+                    Ok((
+                        FriProver::read_state(self.config.fri_config.clone(), reader)?,
+                        Transcript::read_state(reader)?,
+                    ))
+                })?;
+                transcript = restored;
+                recorder.finish(ProveStage::Fri, 0);
+                fri_prover
+            }
+            None => {
+                let fri_prover = self.fold_fri(composition, log_domain_size, &mut transcript);
+                stages.save(ProveStage::Fri, |out| {
+                    fri_prover.write_state(out);
+                    transcript.write_state(out);
+                });
+                let fri_layer_hashes: u64 = (0..fri_prover.get_roots().len())
+                    .filter_map(|layer| fri_prover.layer_commitment(layer))
+                    .map(|c| c.hash_count())
+                    .sum();
+                recorder.finish(ProveStage::Fri, fri_layer_hashes + transcript.hash_count() - transcript_hashes);
+                fri_prover
+            }
+        };
+        transcript_hashes = transcript.hash_count();
+
+        // Step 8: Proof of work, bound into the transcript before any query
+        let pow_nonce = if self.config.pow_bits == 0 {
+            0
+        } else if let Some(state) = stages.restored(ProveStage::Grind) {
+            let (nonce, restored) = read_state(state, |reader| Ok((reader.u64()?, Transcript::read_state(reader)?)))?;
+            transcript = restored;
+            recorder.finish(ProveStage::Grind, 0);
+            transcript_hashes = transcript.hash_count();
+            nonce
+        } else {
+            let nonce = grind(&transcript.state(), self.config.pow_bits, self.config.grind_threads, &mut |_| {});
+            transcript.append_nonce(nonce);
+            stages.save(ProveStage::Grind, |out| {
+                out.extend_from_slice(&nonce.to_le_bytes());
+                transcript.write_state(out);
+            });
+            recorder.finish(ProveStage::Grind, nonce + 2);
+            transcript_hashes = transcript.hash_count();
+            nonce
+        };
 
-        // Step 7: Generate query proofs
+        // Step 9: FRI and trace query proofs
+        let fri_indices = transcript.challenge_indices(self.config.num_queries, 1 << log_domain_size);
+        let fri_proof = fri_prover.prove(&fri_indices);
         let query_indices = transcript.challenge_indices(self.config.num_queries, 1 << log_domain_size);
         let query_proofs = self.generate_query_proofs(
-            trace,
+            &extended,
             &trace_commitments,
             &composition_commitment,
             &query_indices,
         );
+        recorder.finish(ProveStage::Queries, transcript.hash_count() - transcript_hashes);
 
         // Collect all roots
         let trace_roots: Vec<Hash> = trace_commitments.iter().map(|c| c.root()).collect();
 
         Ok(Proof {
             trace_commitment: trace_roots,
-            composition_root: composition_commitment.root(),
+            composition_root,
+            trace_oods,
+            trace_oods_next,
+            composition_oods,
             fri_proof,
             query_proofs,
             public_inputs,
+            pow_nonce,
         })
     }
 
@@ -176,28 +618,119 @@ impl Prover {
             .collect()
     }
 
-    /// Evaluate all constraints at all rows
-    fn evaluate_constraints<E: ConstraintEvaluator>(
-        &self,
-        evaluator: &E,
-        trace: &Trace,
-    ) -> Vec<Vec<M31>> {
-        (0..trace.num_rows)
-            .map(|row| evaluator.evaluate(trace, row))
+    /// Extend every trace column onto `domain`
+    ///
+    /// Columns are evaluations over the canonic coset of the trace length;
+    /// each is interpolated by circle FFT and its polynomial evaluated over
+    /// `domain`, so committed columns (and the composition built from them)
+    /// are Reed-Solomon codewords of rate 2^-log_blowup_factor. The
+    /// twiddles are shared by every column.
+    fn extend_trace(&self, trace: &Trace, domain: &Coset) -> Trace {
+        let twiddles = FftTwiddles::new(trace.num_rows.trailing_zeros());
+        Trace::new(
+            trace
+                .columns
+                .iter()
+                .map(|column| {
+                    let coeffs = icfft(&column.values, &twiddles);
+                    TraceColumn::new(column.index, evaluate_on_domain(&coeffs, domain))
+                })
+                .collect(),
+        )
+    }
+
+    /// Divide composition evaluations by the trace-domain vanishing polynomial
+    ///
+    /// The commitment coset is disjoint from the trace domain, so the
+    /// vanishing polynomial is invertible at every point.
+    fn quotient(&self, composition: Vec<M31>, domain: &Coset, log_trace_length: u32) -> Vec<M31> {
+        composition
+            .into_iter()
+            .zip(domain.iter())
+            .map(|(value, point)| value * canonic_vanishing(log_trace_length, point.x).inv())
             .collect()
     }
 
-    /// Generate FRI proof for the composition polynomial
-    fn prove_fri(
+    /// Evaluate every trace column at an out-of-domain point
+    ///
+    /// Columns are evaluations over the canonic coset of the trace length;
+    /// the barycentric weights are shared across columns.
+    pub fn evaluate_trace_at(&self, trace: &Trace, point: &SecureCirclePoint) -> Vec<QM31> {
+        self.trace_values_at(trace, point, &mut EvaluationCache::new())
+    }
+
+    fn trace_values_at(&self, trace: &Trace, point: &SecureCirclePoint, cache: &mut EvaluationCache) -> Vec<QM31> {
+        let weights = cache.weights(trace.log_length(), point);
+        trace
+            .columns
+            .iter()
+            .map(|col| barycentric_eval(&col.values, &weights))
+            .collect()
+    }
+
+    /// Evaluate the composition quotient, given over the commitment coset,
+    /// at an out-of-domain point
+    ///
+    /// The commitment coset G + <G_n> is the canonic coset G_2n + <G_n>
+    /// moved by G - G_2n, so the quotient's values over it are the values of
+    /// a rotated polynomial over the canonic coset; rotating `point` back
+    /// lets the canonic barycentric weights interpolate it.
+    fn composition_at(
         &self,
         composition: &[M31],
         log_domain_size: u32,
-        transcript: &mut Transcript,
-    ) -> Result<FriProof, ProofError> {
+        point: &SecureCirclePoint,
+        cache: &mut EvaluationCache,
+    ) -> QM31 {
+        let shift = subgroup_generator(log_domain_size + 1).sub(CIRCLE_GENERATOR);
+        let weights = cache.weights(log_domain_size, &point.add_base(shift));
+        barycentric_eval(composition, &weights)
+    }
+
+    /// Evaluate all constraints at every point of the extended trace
+    ///
+    /// A trace step is `blowup` steps on the commitment coset, so points
+    /// r, r + blowup, r + 2·blowup, ... form a trace-sized coset on which the
+    /// evaluator's row offsets apply unchanged.
+    fn evaluate_constraints<E: ConstraintEvaluator>(
+        &self,
+        evaluator: &E,
+        extended: &Trace,
+    ) -> Vec<Vec<M31>> {
+        let blowup = 1usize << self.config.log_blowup_factor;
+        let mut evals = vec![Vec::new(); extended.num_rows];
+        for offset in 0..blowup {
+            let sub = Trace::new(
+                extended
+                    .columns
+                    .iter()
+                    .map(|c| {
+                        TraceColumn::new(c.index, c.values.iter().skip(offset).step_by(blowup).copied().collect())
+                    })
+                    .collect(),
+            );
+            for row in 0..sub.num_rows {
+                evals[offset + row * blowup] = evaluator.evaluate(&sub, row);
+            }
+        }
+        evals
+    }
+
+    /// Fold the composition polynomial through every FRI round
+    ///
+    /// Queries are drawn after proof of work, so the caller opens them.
+    /// FRI folds over x, so its first layer is the line polynomial
+    /// [`Prover::line_polynomial`] draws out of the composition, with a
+    /// tree of its own bound into the transcript before the first folding
+    /// coefficient.
+    fn fold_fri(&self, composition: Vec<M31>, log_domain_size: u32, transcript: &mut Transcript) -> FriProver {
         let mut fri_prover = FriProver::new(self.config.fri_config.clone());
 
-        // Commit to initial layer
-        fri_prover.commit(composition.to_vec(), log_domain_size);
+        let lambda = transcript.challenge_scalar();
+        fri_prover.commit(self.line_polynomial(&composition, log_domain_size, lambda), log_domain_size);
+        if let Some(root) = fri_prover.get_roots().first() {
+            transcript.append(root);
+        }
 
         // Perform FRI folding rounds
         let num_rounds = self.config.fri_config.num_rounds(log_domain_size);
@@ -212,14 +745,35 @@ impl Prover {
             }
         }
 
-        // Generate query indices and proof
-        let query_indices = transcript.challenge_indices(
-            self.config.num_queries,
-            1 << log_domain_size,
-        );
-        let proof = fri_prover.prove(&query_indices);
+        fri_prover
+    }
 
-        Ok(proof)
+    /// The composition quotient f = f₀(x) + y·f₁(x) as the line polynomial
+    /// f₀ + λ·f₁ over the commitment coset, in bit-reversed order
+    ///
+    /// Folding over x alone does not shrink a polynomial in y, so FRI tests
+    /// both halves at once through λ. They come from f at a point and its
+    /// conjugate (x, -y), f₀ = (f(p) + f(p̄)) / 2 and f₁ = (f(p) - f(p̄)) / 2y.
+    /// The commitment coset holds no conjugates, so f is interpolated as
+    /// the rotated polynomial of [`Prover::composition_at`] and evaluated
+    /// over the conjugate coset -G - <G_n>.
+    fn line_polynomial(&self, composition: &[M31], log_domain_size: u32, lambda: M31) -> Vec<M31> {
+        let domain = Coset::commitment(log_domain_size);
+        let shift = subgroup_generator(log_domain_size + 1).sub(CIRCLE_GENERATOR);
+        let coeffs = icfft(composition, &FftTwiddles::new(log_domain_size));
+        let conjugates = evaluate_on_domain(&coeffs, &Coset::shifted(log_domain_size, shift.sub(CIRCLE_GENERATOR)));
+
+        // The conjugate of G + k·G_n is -G - k·G_n, point -k of that coset
+        let n = composition.len();
+        let inv_2y = batch_inverse(&domain.iter().map(|p| p.y.double()).collect::<Vec<_>>());
+        let half = M31::new(2).inv();
+        let line: Vec<M31> = (0..n)
+            .map(|k| {
+                let (at_p, at_conjugate) = (composition[k], conjugates[(n - k) % n]);
+                (at_p + at_conjugate) * half + lambda * (at_p - at_conjugate) * inv_2y[k]
+            })
+            .collect();
+        (0..n).map(|pos| line[bit_reverse_index(pos, log_domain_size)]).collect()
     }
 
     /// Generate query proofs for opening trace and composition at query points
@@ -254,6 +808,86 @@ impl Prover {
     }
 }
 
+/// Finished stages [`Prover::prove_resumable`] restores and saves
+#[cfg(feature = "prove")]
+struct Stages<'a> {
+    checkpoint: Checkpoint,
+    /// `None` when proving without checkpoints; nothing is serialized then
+    sink: Option<&'a mut dyn FnMut(&Checkpoint)>,
+}
+
+#[cfg(feature = "prove")]
+impl Stages<'_> {
+    fn unsaved() -> Self {
+        Self { checkpoint: Checkpoint::new([0; 32]), sink: None }
+    }
+
+    fn restored(&self, stage: ProveStage) -> Option<&[u8]> {
+        self.checkpoint.stage(stage)
+    }
+
+    /// Record what `write` serializes for `stage` and pass the checkpoint on
+    fn save(&mut self, stage: ProveStage, write: impl FnOnce(&mut Vec<u8>)) {
+        if let Some(sink) = self.sink.as_mut() {
+            let mut state = Vec::new();
+            write(&mut state);
+            self.checkpoint.record(stage, state);
+            sink(&self.checkpoint);
+        }
+    }
+}
+
+/// Parse all of a stage's state with `read`
+#[cfg(feature = "prove")]
+fn read_state<T>(state: &[u8], read: impl FnOnce(&mut StateReader<'_>) -> Result<T, ProofError>) -> Result<T, ProofError> {
+    let mut reader = StateReader::new(state);
+    let value = read(&mut reader)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+#[cfg(feature = "prove")]
+fn write_extended(out: &mut Vec<u8>, extended: &Trace) {
+    out.extend_from_slice(&(extended.num_columns() as u32).to_le_bytes());
+    for column in &extended.columns {
+        out.extend_from_slice(&(column.index as u32).to_le_bytes());
+        put_m31s(out, &column.values);
+    }
+}
+
+#[cfg(feature = "prove")]
+fn read_extended(state: &[u8]) -> Result<Trace, ProofError> {
+    read_state(state, |reader| {
+        let count = reader.count(8)?;
+        let columns = (0..count)
+            .map(|_| Ok(TraceColumn::new(reader.u32()? as usize, reader.m31s()?)))
+            .collect::<Result<Vec<_>, ProofError>>()?;
+        if columns.iter().any(|c| c.values.len() != columns[0].values.len()) {
+            return Err(checkpoint_error("extended columns differ in length"));
+        }
+        Ok(Trace::new(columns))
+    })
+}
+
+#[cfg(feature = "prove")]
+fn put_qm31s(out: &mut Vec<u8>, values: &[QM31]) {
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        out.extend_from_slice(&value.to_bytes());
+    }
+}
+
+#[cfg(feature = "prove")]
+fn read_qm31(reader: &mut StateReader<'_>) -> Result<QM31, ProofError> {
+    Ok(QM31::new(reader.m31()?, reader.m31()?, reader.m31()?, reader.m31()?))
+}
+
+#[cfg(feature = "prove")]
+fn read_qm31s(reader: &mut StateReader<'_>) -> Result<Vec<QM31>, ProofError> {
+    let count = reader.count(16)?;
+    (0..count).map(|_| read_qm31(reader)).collect()
+}
+
 /// A query proof (trace and composition openings at a single point)
 #[derive(Clone, Debug)]
 pub struct QueryProof {
@@ -269,6 +903,7 @@ pub struct QueryProof {
 pub struct Transcript {
     state: Hash,
     counter: u64,
+    hashes: u64,
 }
 
 impl Transcript {
@@ -277,6 +912,7 @@ impl Transcript {
         Self {
             state: hash_bytes(b"murkl-prover-v1"),
             counter: 0,
+            hashes: 1,
         }
     }
 
@@ -286,6 +922,7 @@ impl Transcript {
         combined[..32].copy_from_slice(&self.state);
         combined[32..].copy_from_slice(data);
         self.state = hash_bytes(&combined);
+        self.hashes += 1;
     }
 
     /// Append M31 value to the transcript
@@ -294,18 +931,62 @@ impl Transcript {
         combined[..32].copy_from_slice(&self.state);
         combined[32..36].copy_from_slice(&value.to_le_bytes());
         self.state = hash_bytes(&combined);
+        self.hashes += 1;
     }
 
-    /// Get a challenge scalar
-    pub fn challenge_scalar(&mut self) -> M31 {
+    /// Append QM31 value to the transcript
+    pub fn append_qm31(&mut self, value: QM31) {
+        let mut combined = [0u8; 48];
+        combined[..32].copy_from_slice(&self.state);
+        combined[32..48].copy_from_slice(&value.to_bytes());
+        self.state = hash_bytes(&combined);
+        self.hashes += 1;
+    }
+
+    /// Append a proof-of-work nonce to the transcript
+    pub fn append_nonce(&mut self, nonce: u64) {
+        let mut combined = [0u8; 40];
+        combined[..32].copy_from_slice(&self.state);
+        combined[32..40].copy_from_slice(&nonce.to_le_bytes());
+        self.state = hash_bytes(&combined);
+        self.hashes += 1;
+    }
+
+    /// Next 32-bit challenge word
+    fn challenge_word(&mut self) -> u32 {
         self.counter += 1;
         let mut data = [0u8; 40];
         data[..32].copy_from_slice(&self.state);
         data[32..40].copy_from_slice(&self.counter.to_le_bytes());
 
         let hash = hash_bytes(&data);
-        let value = u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
-        M31::new(value)
+        self.hashes += 1;
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+
+    /// Get a challenge scalar
+    pub fn challenge_scalar(&mut self) -> M31 {
+        M31::new(self.challenge_word())
+    }
+
+    /// Get a challenge in the secure field
+    pub fn challenge_qm31(&mut self) -> QM31 {
+        let a = self.challenge_scalar();
+        let b = self.challenge_scalar();
+        let c = self.challenge_scalar();
+        let d = self.challenge_scalar();
+        QM31::new(a, b, c, d)
+    }
+
+    /// Get the out-of-domain sample: a secure-field point on the circle
+    /// off the base circle, redrawing the rare t [`SecureCirclePoint::try_from_t`]
+    /// refuses
+    pub fn challenge_circle_point(&mut self) -> SecureCirclePoint {
+        loop {
+            if let Some(point) = SecureCirclePoint::try_from_t(self.challenge_qm31()) {
+                return point;
+            }
+        }
     }
 
     /// Get multiple challenge scalars
@@ -314,12 +995,20 @@ impl Transcript {
     }
 
     /// Get challenge indices (for queries)
+    ///
+    /// Each is a challenge word masked to the bits of `max` and drawn again
+    /// when it lands at or above it, so every index below `max` is equally
+    /// likely; a power-of-two `max` never redraws.
     pub fn challenge_indices(&mut self, count: usize, max: usize) -> Vec<usize> {
         let mut indices = Vec::with_capacity(count);
+        let bits = max.next_power_of_two().trailing_zeros();
+        let mask = if bits >= 32 { u32::MAX } else { (1u32 << bits) - 1 };
 
         while indices.len() < count {
-            let scalar = self.challenge_scalar();
-            let index = (scalar.value() as usize) % max;
+            let index = (self.challenge_word() & mask) as usize;
+            if index >= max {
+                continue;
+            }
 
             // Ensure unique indices
             if !indices.contains(&index) {
@@ -334,6 +1023,25 @@ impl Transcript {
     pub fn state(&self) -> Hash {
         self.state
     }
+
+    /// Hashes run so far, including the initial state
+    pub fn hash_count(&self) -> u64 {
+        self.hashes
+    }
+}
+
+#[cfg(feature = "prove")]
+impl Transcript {
+    /// State, challenge counter and hash count, for a prover checkpoint
+    fn write_state(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.state);
+        out.extend_from_slice(&self.counter.to_le_bytes());
+        out.extend_from_slice(&self.hashes.to_le_bytes());
+    }
+
+    fn read_state(reader: &mut StateReader<'_>) -> Result<Self, ProofError> {
+        Ok(Self { state: reader.hash()?, counter: reader.u64()?, hashes: reader.u64()? })
+    }
 }
 
 impl Default for Transcript {
@@ -342,10 +1050,194 @@ impl Default for Transcript {
     }
 }
 
-#[cfg(test)]
+/// Domain tag of proof-of-work digests
+pub const POW_DOMAIN: &[u8] = b"murkl_pow_v1";
+
+/// Nonces [`grind`] tries between progress reports
+pub const GRIND_REPORT_INTERVAL: u64 = 1 << 16;
+
+/// Digest a proof-of-work nonce is judged by: `keccak(POW_DOMAIN || seed || nonce)`,
+/// with the nonce little-endian
+pub fn pow_digest(seed: &Hash, nonce: u64) -> Hash {
+    crate::hash::keccak_hash(&[POW_DOMAIN, seed, &nonce.to_le_bytes()])
+}
+
+/// Leading zero bits of [`pow_digest`]
+pub fn pow_work(seed: &Hash, nonce: u64) -> u32 {
+    let digest = pow_digest(seed, nonce);
+    let mut bits = 0;
+    for byte in digest {
+        bits += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Whether `nonce` carries `pow_bits` of work over `seed`; zero bits asks for none
+pub fn check_pow(seed: &Hash, nonce: u64, pow_bits: u32) -> bool {
+    pow_bits == 0 || pow_work(seed, nonce) >= pow_bits
+}
+
+/// How far a nonce search has got
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GrindProgress {
+    /// Nonces tried so far, over all threads
+    pub attempts: u64,
+    /// Nonces a search tries on average, `2^pow_bits`
+    pub expected: u64,
+}
+
+impl GrindProgress {
+    /// Attempts over the expected count; an unlucky search passes 1
+    pub fn fraction(&self) -> f64 {
+        self.attempts as f64 / self.expected as f64
+    }
+}
+
+/// Nonce search that runs in caller-sized steps
+///
+/// Tries nonces from zero upwards, so it finds the smallest one with
+/// enough work, the same nonce [`grind`] returns on any number of threads.
+/// The browser prover drives it with [`step`] to report progress and stay
+/// cancellable, as with [`PasswordStretch`](crate::hash::PasswordStretch).
+///
+/// [`step`]: NonceSearch::step
+#[derive(Clone, Debug)]
+pub struct NonceSearch {
+    seed: Hash,
+    pow_bits: u32,
+    next: u64,
+    found: Option<u64>,
+}
+
+impl NonceSearch {
+    pub fn new(seed: Hash, pow_bits: u32) -> Self {
+        Self { seed, pow_bits, next: 0, found: None }
+    }
+
+    /// Try up to `max_attempts` more nonces; returns the nonce once found
+    pub fn step(&mut self, max_attempts: u64) -> Option<u64> {
+        let end = self.next.saturating_add(max_attempts);
+        while self.found.is_none() && self.next < end {
+            if check_pow(&self.seed, self.next, self.pow_bits) {
+                self.found = Some(self.next);
+            }
+            self.next += 1;
+        }
+        self.found
+    }
+
+    /// The nonce, once found
+    pub fn nonce(&self) -> Option<u64> {
+        self.found
+    }
+
+    pub fn progress(&self) -> GrindProgress {
+        GrindProgress { attempts: self.next, expected: 1u64 << self.pow_bits.min(63) }
+    }
+}
+
+/// Smallest nonce with `pow_bits` of work over `seed`
+///
+/// With `std` and more than one thread the nonces are split by residue
+/// across `threads` workers; each stops once it passes the best nonce found
+/// so far, so the result does not depend on the thread count. `progress`
+/// runs on the calling thread while the search goes on.
+pub fn grind(seed: &Hash, pow_bits: u32, threads: usize, progress: &mut dyn FnMut(GrindProgress)) -> u64 {
+    #[cfg(feature = "std")]
+    if threads > 1 && pow_bits > 0 {
+        return grind_parallel(seed, pow_bits, threads, progress);
+    }
+    let _ = threads;
+
+    let mut search = NonceSearch::new(*seed, pow_bits);
+    loop {
+        if let Some(nonce) = search.step(GRIND_REPORT_INTERVAL) {
+            return nonce;
+        }
+        progress(search.progress());
+    }
+}
+
+/// [`grind`] whose nonce survives an interrupted prover
+///
+/// A `resume` checkpoint from a search over the same seed and difficulty
+/// gives its nonce back without searching; otherwise the search runs and
+/// `sink` gets a checkpoint holding the nonce found. For provers whose
+/// other stages are cheap to rerun, which then reach the same seed again.
+pub fn grind_resumable(
+    seed: &Hash,
+    pow_bits: u32,
+    threads: usize,
+    progress: &mut dyn FnMut(GrindProgress),
+    resume: Option<&Checkpoint>,
+    sink: &mut dyn FnMut(&Checkpoint),
+) -> u64 {
+    let fingerprint = crate::hash::keccak_hash(&[b"murkl_grind_checkpoint_v1", seed, &pow_bits.to_le_bytes()]);
+    let restored = resume
+        .filter(|checkpoint| *checkpoint.fingerprint() == fingerprint)
+        .and_then(|checkpoint| checkpoint.stage(ProveStage::Grind)?.try_into().ok())
+        .map(u64::from_le_bytes)
+        .filter(|&nonce| check_pow(seed, nonce, pow_bits));
+    if let Some(nonce) = restored {
+        return nonce;
+    }
+
+    let nonce = grind(seed, pow_bits, threads, progress);
+    let mut checkpoint = Checkpoint::new(fingerprint);
+    checkpoint.record(ProveStage::Grind, nonce.to_le_bytes().to_vec());
+    sink(&checkpoint);
+    nonce
+}
+
+#[cfg(feature = "std")]
+fn grind_parallel(seed: &Hash, pow_bits: u32, threads: usize, progress: &mut dyn FnMut(GrindProgress)) -> u64 {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    let best = AtomicU64::new(u64::MAX);
+    let attempts = AtomicU64::new(0);
+    let running = AtomicUsize::new(threads);
+    let expected = 1u64 << pow_bits.min(63);
+
+    std::thread::scope(|scope| {
+        for first in 0..threads as u64 {
+            let (best, attempts, running) = (&best, &attempts, &running);
+            scope.spawn(move || {
+                let mut tried = 0;
+                let mut nonce = first;
+                while nonce < best.load(Ordering::Relaxed) {
+                    if check_pow(seed, nonce, pow_bits) {
+                        best.fetch_min(nonce, Ordering::Relaxed);
+                        break;
+                    }
+                    tried += 1;
+                    if tried == GRIND_REPORT_INTERVAL {
+                        attempts.fetch_add(tried, Ordering::Relaxed);
+                        tried = 0;
+                    }
+                    nonce = match nonce.checked_add(threads as u64) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+                attempts.fetch_add(tried, Ordering::Relaxed);
+                running.fetch_sub(1, Ordering::Release);
+            });
+        }
+        while running.load(Ordering::Acquire) > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            progress(GrindProgress { attempts: attempts.load(Ordering::Relaxed), expected });
+        }
+    });
+    best.into_inner()
+}
+
+#[cfg(all(test, feature = "prove"))]
 mod tests {
     use super::*;
-    use crate::air::{FibonacciAir, Trace, TraceColumn};
+    use crate::air::{FibonacciAir, MurklAir, Trace, TraceColumn};
 
     #[test]
     fn test_prover_config() {
@@ -354,6 +1246,25 @@ mod tests {
         assert_eq!(config.log_blowup_factor, 4);
     }
 
+    #[test]
+    fn test_security_policy() {
+        for level in SecurityLevel::ALL {
+            assert_eq!(ProverConfig::for_level(level).security_level(), Some(level));
+        }
+        assert_eq!(ProverConfig::default().security_level(), Some(SecurityLevel::High128));
+        assert_eq!(ProverConfig::fast().security_level(), None);
+
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+        let prove = |level| {
+            Prover::new(ProverConfig::for_level(level))
+                .with_policy(SecurityLevel::Standard96)
+                .prove(&air, &trace, PublicInputs::empty())
+        };
+        assert!(matches!(prove(SecurityLevel::Demo), Err(ProofError::BelowSecurityPolicy(_))));
+        assert_eq!(prove(SecurityLevel::Standard96).unwrap().query_proofs.len(), 24);
+    }
+
     #[test]
     fn test_transcript() {
         let mut transcript1 = Transcript::new();
@@ -406,6 +1317,74 @@ mod tests {
         assert_eq!(sorted.len(), 10);
     }
 
+    #[test]
+    fn test_grind_is_thread_independent() {
+        let seed = Transcript::new().state();
+        let mut reports = 0;
+        let nonce = grind(&seed, 12, 1, &mut |_| reports += 1);
+        assert!(check_pow(&seed, nonce, 12));
+        assert!((0..nonce).all(|n| !check_pow(&seed, n, 12)));
+        assert_eq!(grind(&seed, 12, 4, &mut |_| {}), nonce);
+
+        // Stepping reaches the same nonce and counts every attempt
+        let mut search = NonceSearch::new(seed, 12);
+        while search.step(100).is_none() {}
+        assert_eq!(search.nonce(), Some(nonce));
+        assert_eq!(search.progress().attempts, nonce + 1);
+        assert_eq!(search.progress().expected, 4096);
+        assert_eq!(grind(&seed, 0, 4, &mut |_| {}), 0);
+    }
+
+    #[test]
+    fn test_prove_with_pow() {
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+        let plain = Prover::new(ProverConfig::fast()).prove(&air, &trace, PublicInputs::empty()).unwrap();
+        assert_eq!(plain.pow_nonce, 0);
+
+        let prover = Prover::new(ProverConfig::fast().with_pow(8, 2));
+        let (proof, metrics) = prover
+            .prove_with_metrics(&air, &trace, PublicInputs::empty(), StageRecorder::new(no_clock))
+            .unwrap();
+        assert_eq!(metrics.stage(ProveStage::Grind).unwrap().hashes, proof.pow_nonce + 2);
+        // The nonce moves the transcript, so queries land elsewhere
+        let indices = |p: &Proof| p.query_proofs.iter().map(|q| q.index).collect::<Vec<_>>();
+        assert_ne!(indices(&proof), indices(&plain));
+        assert_eq!(proof.trace_commitment, plain.trace_commitment);
+    }
+
+    #[test]
+    fn test_prove_resumable() {
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+        let prover = Prover::new(ProverConfig::fast().with_pow(6, 1));
+        let expected = prover.prove(&air, &trace, PublicInputs::empty()).unwrap().to_bytes();
+
+        let mut saved = Vec::new();
+        let proof = prover
+            .prove_resumable(&air, &trace, PublicInputs::empty(), None, &mut |c| saved.push(c.to_bytes()))
+            .unwrap();
+        assert_eq!(proof.to_bytes(), expected);
+        let stages: Vec<_> = saved.iter().map(|c| Checkpoint::from_bytes(c).unwrap().last_stage().unwrap()).collect();
+        assert_eq!(stages, &ProveStage::ALL[..7]);
+
+        // Resuming after any stage gives the same proof, saving only the rest
+        for (done, bytes) in saved.iter().enumerate() {
+            let mut later = 0;
+            let checkpoint = Checkpoint::from_bytes(bytes).unwrap();
+            let resumed = prover
+                .prove_resumable(&air, &trace, PublicInputs::empty(), Some(checkpoint), &mut |_| later += 1)
+                .unwrap();
+            assert_eq!(resumed.to_bytes(), expected, "resumed after {}", stages[done]);
+            assert_eq!(later, saved.len() - done - 1);
+        }
+
+        // A checkpoint of another trace is refused
+        let other = air.generate_trace(M31::ONE, M31::new(2));
+        let checkpoint = Checkpoint::from_bytes(&saved[0]).unwrap();
+        assert!(prover.prove_resumable(&air, &other, PublicInputs::empty(), Some(checkpoint), &mut |_| {}).is_err());
+    }
+
     #[test]
     fn test_fibonacci_proof_generation() {
         let config = ProverConfig::fast();
@@ -426,6 +1405,77 @@ mod tests {
         assert!(!proof.trace_commitment.is_empty());
     }
 
+    #[test]
+    fn test_prove_with_metrics() {
+        let config = ProverConfig::fast();
+        let rows = 64usize << config.log_blowup_factor;
+        let prover = Prover::new(config);
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+
+        let mut seen = Vec::new();
+        let mut hook = |m: &crate::metrics::StageMetrics| seen.push(m.stage);
+        let recorder = StageRecorder::new(crate::metrics::system_clock).with_hook(&mut hook);
+        let (proof, metrics) = prover
+            .prove_with_metrics(&air, &trace, PublicInputs::empty(), recorder)
+            .unwrap();
+
+        use ProveStage::*;
+        assert_eq!(seen, [Extend, CommitTrace, Constraints, Composition, Oods, Fri, Queries]);
+        let plain = prover.prove(&air, &trace, PublicInputs::empty()).unwrap();
+        assert_eq!(proof.to_bytes(), plain.to_bytes());
+        assert_eq!(metrics.proof_bytes, proof.to_bytes().len());
+
+        // Each column tree: a hash per leaf, the padding leaf, and rows - 1 nodes
+        let commit = metrics.stage(CommitTrace).unwrap();
+        assert_eq!(commit.hashes, (trace.num_columns() * 2 * rows) as u64);
+        assert_eq!(metrics.stage(Extend).unwrap().hashes, 0);
+        assert!(metrics.stage(Fri).unwrap().hashes > 0);
+    }
+
+    #[test]
+    fn test_extend_trace_is_low_degree() {
+        use crate::air::poseidon2::MurklHashAir;
+        use crate::hash::{pq_commitment, pq_nullifier};
+        use crate::verifier::{VerificationError, Verifier};
+
+        let config = ProverConfig::fast();
+        let prover = Prover::new(config.clone());
+        let (id_hash, secret) = (M31::new(11), M31::new(22));
+        let air = MurklHashAir::new(&pq_commitment(id_hash, secret), &pq_nullifier(secret, 3));
+        let trace = air.generate_trace(id_hash, secret, 3, &[]).unwrap();
+        let log_domain_size = trace.log_length() + config.log_blowup_factor;
+        let domain = Coset::commitment(log_domain_size);
+        let extended = prover.extend_trace(&trace, &domain);
+
+        // Same values as interpolating each column at each point
+        let column = &trace.columns[0].values;
+        for i in [0usize, 1, 77, domain.size() - 1] {
+            let at = prover.evaluate_trace_at(&trace, &SecureCirclePoint::from_base(domain.at(i)));
+            assert_eq!(QM31::from(extended.columns[0].values[i]), at[0]);
+        }
+
+        // Read over the canonic coset the extension is a rotated trace
+        // polynomial: nothing above twice the trace length
+        let degree_bound = 2 * column.len();
+        let high = |values: &[M31]| {
+            let coeffs = icfft(values, &FftTwiddles::new(values.len().trailing_zeros()));
+            coeffs[degree_bound..].iter().any(|&c| c != M31::ZERO)
+        };
+        assert!(!high(&extended.columns[0].values));
+        let mut tampered = extended.columns[0].values.clone();
+        tampered[5] += M31::ONE;
+        assert!(high(&tampered));
+
+        // A tampered trace cell leaves a composition the AIR rejects
+        let inputs = air.commitment.iter().chain(&air.nullifier).copied().collect();
+        let public_inputs = PublicInputs::new(inputs, vec![]);
+        let mut bad = trace.clone();
+        bad.columns[0].values[4] += M31::ONE;
+        let proof = prover.prove(&air, &bad, public_inputs).unwrap();
+        assert_eq!(Verifier::new(config).verify(&air, &proof), Err(VerificationError::ConstraintMismatch));
+    }
+
     #[test]
     fn test_prover_commit_trace() {
         let config = ProverConfig::default();
@@ -439,4 +1489,78 @@ mod tests {
         let commitments = prover.commit_trace(&trace);
         assert_eq!(commitments.len(), 2);
     }
+
+    #[test]
+    fn test_trace_oods_matches_column_polynomial() {
+        let prover = Prover::with_defaults();
+
+        // A column that is constant over the domain evaluates to that constant anywhere
+        let trace = Trace::new(vec![TraceColumn::new(0, vec![M31::new(42); 16])]);
+        let point = SecureCirclePoint::from_t(QM31::from_u32(5, 6, 7, 8));
+
+        let oods = prover.evaluate_trace_at(&trace, &point);
+        assert_eq!(oods, vec![QM31::from(M31::new(42))]);
+    }
+
+    #[test]
+    fn test_trace_oods_next_is_one_row_on() {
+        let prover = Prover::with_defaults();
+        let values: Vec<M31> = (0..16).map(|i| M31::new(i * i + 3)).collect();
+        let mut rotated = values.clone();
+        rotated.rotate_left(1);
+
+        let point = SecureCirclePoint::from_t(QM31::from_u32(5, 6, 7, 8));
+        let at = |values: &[M31], point| prover.evaluate_trace_at(&Trace::new(vec![TraceColumn::new(0, values.to_vec())]), point);
+        assert_eq!(at(&values, &point.add_base(subgroup_generator(4))), at(&rotated, &point));
+    }
+
+    #[test]
+    fn test_proof_carries_trace_oods() {
+        let prover = Prover::new(ProverConfig::fast());
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+
+        let proof = prover.prove(&air, &trace, PublicInputs::empty()).unwrap();
+        assert_eq!(proof.trace_oods.len(), trace.num_columns());
+    }
+
+    #[test]
+    fn test_check_witness() {
+        let prover = Prover::new(ProverConfig::fast());
+        let air = MurklAir::new(2);
+        let row = |secret: u32, null_secret: u32| {
+            let mut row = vec![M31::ZERO; air.num_columns()];
+            row[1] = M31::new(secret);
+            row[3 + 2 * 4 + 1] = M31::new(null_secret);
+            row
+        };
+        assert_eq!(prover.check_witness(&air, &Trace::from_rows(vec![row(42, 42); 8])), Ok(()));
+
+        // A wrong password derives a different secret for the nullifier
+        let mut rows = vec![row(42, 42); 8];
+        rows[3] = row(42, 7);
+        assert_eq!(
+            prover.check_witness(&air, &Trace::from_rows(rows)),
+            Err(WitnessError::ConstraintViolations(vec![(3, "secret_consistency".into())]))
+        );
+
+        let short = Trace::from_rows(vec![row(42, 42)[..5].to_vec(); 8]);
+        assert!(matches!(prover.check_witness(&air, &short), Err(WitnessError::InvalidTrace(_))));
+        let odd = Trace::from_rows(vec![row(42, 42); 6]);
+        assert!(matches!(prover.check_witness(&air, &odd), Err(WitnessError::InvalidTrace(_))));
+    }
+
+    #[test]
+    fn test_check_witness_wraparound() {
+        let prover = Prover::new(ProverConfig::fast());
+        let air = FibonacciAir::new(16);
+
+        // Fibonacci holds on every transition row but not across the wrap
+        let err = prover.check_witness(&air, &air.generate_trace(M31::ONE, M31::ONE)).unwrap_err();
+        assert_eq!(
+            err,
+            WitnessError::OodsInconsistent(vec![(14, "fibonacci".into()), (15, "fibonacci".into())])
+        );
+        assert!(matches!(ProofError::from(err), ProofError::ConstraintViolation(_)));
+    }
 }
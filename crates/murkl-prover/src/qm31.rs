@@ -103,6 +103,67 @@ impl QM31 {
         }
     }
 
+    /// Square
+    #[inline]
+    pub fn square(self) -> Self {
+        self * self
+    }
+
+    /// Compute self^exp using square-and-multiply
+    pub fn pow(self, mut exp: u32) -> Self {
+        let mut base = self;
+        let mut result = Self::ONE;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base.square();
+            exp >>= 1;
+        }
+
+        result
+    }
+
+    /// Multiplicative inverse
+    ///
+    /// Writing x = x₀ + x₁j with x₀, x₁ ∈ CM31, we have
+    /// x⁻¹ = (x₀ - x₁j) / (x₀² - x₁²(i+2)), and the CM31 denominator is
+    /// inverted through its M31 norm. Matches the on-chain verifier.
+    ///
+    /// # Panics
+    /// Panics (in debug) if self is zero
+    pub fn inv(self) -> Self {
+        debug_assert!(!self.is_zero(), "Cannot invert zero");
+
+        // x₀² = (a² - b²) + 2ab·i
+        let x0_sq_real = self.a.square() - self.b.square();
+        let x0_sq_imag = (self.a * self.b).double();
+
+        // x₁² = (c² - d²) + 2cd·i, then multiply by (i + 2)
+        let x1_sq_real = self.c.square() - self.d.square();
+        let x1_sq_imag = (self.c * self.d).double();
+        let x1_sq_j2_real = x1_sq_real.double() - x1_sq_imag;
+        let x1_sq_j2_imag = x1_sq_imag.double() + x1_sq_real;
+
+        // Denominator in CM31: r + s·i
+        let r = x0_sq_real - x1_sq_j2_real;
+        let s = x0_sq_imag - x1_sq_j2_imag;
+
+        // (r + s·i)⁻¹ = (r - s·i) / (r² + s²)
+        let norm_inv = (r.square() + s.square()).inv();
+        let inv_real = r * norm_inv;
+        let inv_imag = -(s * norm_inv);
+
+        // (x₀ - x₁j) · (inv_real + inv_imag·i)
+        Self {
+            a: self.a * inv_real - self.b * inv_imag,
+            b: self.a * inv_imag + self.b * inv_real,
+            c: -(self.c * inv_real - self.d * inv_imag),
+            d: -(self.c * inv_imag + self.d * inv_real),
+        }
+    }
+
     /// Serialize to bytes (16 bytes, little-endian)
     #[inline]
     pub fn to_bytes(&self) -> [u8; 16] {
@@ -324,6 +385,22 @@ mod tests {
         assert_eq!(x * y, y * x);
     }
 
+    #[test]
+    fn test_qm31_inverse() {
+        let x = QM31::from_u32(11, 22, 33, 44);
+        assert_eq!(x * x.inv(), QM31::ONE);
+
+        let y = QM31::from_u32(0, 0, 7, 0);
+        assert_eq!(y * y.inv(), QM31::ONE);
+    }
+
+    #[test]
+    fn test_qm31_pow() {
+        let x = QM31::from_u32(3, 1, 4, 1);
+        assert_eq!(x.pow(0), QM31::ONE);
+        assert_eq!(x.pow(3), x * x * x);
+    }
+
     #[test]
     fn test_qm31_serialization() {
         let x = QM31::from_u32(0x12345678, 0x9ABCDEF0, 0x11223344, 0x55667788);
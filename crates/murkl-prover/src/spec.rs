@@ -0,0 +1,705 @@
+//! Wire format of a Murkl proof
+//!
+//! The bytes the WASM prover and the CLI emit and stark-verifier's
+//! `parse_proof` reads, described as data: [`layout`] walks a proof and
+//! returns every field with its offset, length and [`Encoding`], checking
+//! the same bounds the on-chain parser does.
+//!
+//! [`annotate`] renders that layout as a self-describing test vector, one
+//! field per line:
+//!
+//! ```text
+//! 000000      4  magic                        u8       4d524b4c
+//! 000004      1  version                      u8       01
+//! 000005      1  hash_kind                    u8       00
+//! ```
+//!
+//! and [`parse_annotated`] reads one back, rejecting it unless the bytes
+//! re-derive exactly the annotated fields. A third-party emitter can diff its
+//! output against a vector field by field instead of byte by byte.
+//!
+//! This is the on-chain format only. [`Proof::to_bytes`](crate::types) is the
+//! library prover's own encoding, which `Proof::from_bytes` reads back, and
+//! is not accepted on-chain: it commits to each trace column in a tree of
+//! its own and folds FRI over M31. Emitters build
+//! a [`SerializedProof`](murkl_proof_format::SerializedProof) and encode it;
+//! this module only describes the bytes.
+//!
+//! ```text
+//! params   magic "MRKL" | version u8 | hash_kind u8 | log_trace_size u8
+//!          | log_blowup u8 | log_folding_factor u8 | query_count u8
+//! header   trace_commitment hash | composition_commitment hash
+//!          | trace_columns u8 | trace_oods qm31 × columns
+//!          | trace_oods_next qm31 × columns | composition_oods qm31
+//!          | fri_layer_count u8 | fri_layer_commitment hash × layers
+//!          | final_poly_len u16 (top bit: evaluations) | final_poly qm31 × len
+//! query    index u32 | trace_row m31 × columns | trace_path
+//!          | composition_leaf hash | composition_path
+//!          | (fri_siblings qm31 × 4 | fri_path) × layers
+//! path     depth u8 | hash × depth
+//! ```
+//!
+//! Integers are little-endian; a QM31 is its four M31 coordinates in order.
+//! Nothing follows the last query.
+//!
+//! [`format_fields`] is the same walk with the counts left symbolic, and
+//! [`format_table`] renders it with offsets and conditions; the
+//! `dump-format` binary prints that table.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::{String, ToString}, vec, vec::Vec};
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::m31::M31_PRIME;
+use crate::types::ProofError;
+
+/// [`PROOF_MAGIC`]
+pub const MAGIC: Range<usize> = 0..4;
+/// Format version, [`PROOF_VERSION`]
+pub const VERSION: Range<usize> = 4..5;
+/// Hash selector (see [`HashKind`](crate::hash::HashKind))
+pub const HASH_KIND: Range<usize> = 5..6;
+/// log2 of the trace length
+pub const LOG_TRACE_SIZE: Range<usize> = 6..7;
+/// log2 of the blowup; the query indices are drawn from
+/// `log_trace_size + log_blowup` bits
+pub const LOG_BLOWUP: Range<usize> = 7..8;
+/// log2 of the FRI folding factor
+pub const LOG_FOLDING_FACTOR: Range<usize> = 8..9;
+/// Number of query openings after the final layer
+pub const QUERY_COUNT: Range<usize> = 9..10;
+/// Trace Merkle root
+pub const TRACE_COMMITMENT: Range<usize> = 10..42;
+/// Composition Merkle root
+pub const COMPOSITION_COMMITMENT: Range<usize> = 42..74;
+/// Trace column count
+pub const TRACE_COLUMNS: Range<usize> = 74..75;
+
+// From here on header offsets depend on the trace column count
+/// Each trace column at the out-of-domain point
+pub const fn trace_oods(columns: usize) -> Range<usize> {
+    75..75 + 16 * columns
+}
+/// Each trace column one trace row past the out-of-domain point
+pub const fn trace_oods_next(columns: usize) -> Range<usize> {
+    let start = trace_oods(columns).end;
+    start..start + 16 * columns
+}
+/// Composition out-of-domain value
+pub const fn composition_oods(columns: usize) -> Range<usize> {
+    let start = trace_oods_next(columns).end;
+    start..start + 16
+}
+/// FRI layer count; the layer commitments and final layer follow
+pub const fn fri_layer_count(columns: usize) -> Range<usize> {
+    let start = composition_oods(columns).end;
+    start..start + 1
+}
+
+// Constants and bounds of the format, from the crate that encodes it
+pub use murkl_proof_format::{
+    FINAL_EVALUATIONS_FLAG, FRI_GROUP_DOMAIN, MAX_FINAL_LAYER_EVALS, MAX_FINAL_POLY_LEN, MAX_FRI_LAYERS,
+    MAX_HASH_KIND, MAX_MERKLE_DEPTH, MAX_PROOF_SIZE, MAX_QUERIES, MAX_TRACE_COLUMNS, PROOF_MAGIC, PROOF_VERSION,
+    TRACE_ROW_DOMAIN,
+};
+
+/// How a field's bytes are read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    U8,
+    U16Le,
+    U32Le,
+    /// Canonical M31 (< 2^31 - 1), u32 little-endian
+    M31Le,
+    /// Four M31 coordinates, 16 bytes
+    Qm31Le,
+    /// 32-byte digest
+    Hash,
+}
+
+impl Encoding {
+    /// Bytes per element
+    pub fn width(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::U16Le => 2,
+            Self::U32Le | Self::M31Le => 4,
+            Self::Qm31Le => 16,
+            Self::Hash => 32,
+        }
+    }
+
+    /// Name used in annotated vectors
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::U8 => "u8",
+            Self::U16Le => "u16le",
+            Self::U32Le => "u32le",
+            Self::M31Le => "m31le",
+            Self::Qm31Le => "qm31le",
+            Self::Hash => "hash",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::U8, Self::U16Le, Self::U32Le, Self::M31Le, Self::Qm31Le, Self::Hash]
+            .into_iter()
+            .find(|e| e.name() == name)
+    }
+}
+
+/// One field of a proof: a run of elements of one [`Encoding`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Dotted path, e.g. `queries[2].fri[0].path`
+    pub name: String,
+    pub offset: usize,
+    /// Length in bytes, a multiple of the encoding's width (0 for an empty path)
+    pub len: usize,
+    pub encoding: Encoding,
+}
+
+impl Field {
+    /// Byte range in the proof
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+fn malformed(msg: impl Into<String>) -> ProofError {
+    ProofError::SerializationError(msg.into())
+}
+
+/// Cursor that records each field it reads
+struct Walker<'a> {
+    proof: &'a [u8],
+    at: usize,
+    fields: Vec<Field>,
+}
+
+impl<'a> Walker<'a> {
+    fn take(&mut self, name: String, encoding: Encoding, count: usize) -> Result<&'a [u8], ProofError> {
+        let len = encoding.width() * count;
+        let bytes = self
+            .proof
+            .get(self.at..self.at + len)
+            .ok_or_else(|| malformed(format!("{} ends past the proof ({} bytes)", name, self.proof.len())))?;
+        if encoding == Encoding::M31Le {
+            if let Some(i) = bytes.chunks_exact(4).position(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]]) >= M31_PRIME) {
+                return Err(malformed(format!("{} element {} is not a canonical M31", name, i)));
+            }
+        }
+        self.fields.push(Field { name, offset: self.at, len, encoding });
+        self.at += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self, name: String) -> Result<u8, ProofError> {
+        Ok(self.take(name, Encoding::U8, 1)?[0])
+    }
+
+    fn path(&mut self, name: &str) -> Result<(), ProofError> {
+        let depth = self.byte(format!("{}_len", name))? as usize;
+        if depth > MAX_MERKLE_DEPTH {
+            return Err(malformed(format!("{} is {} deep, max {}", name, depth, MAX_MERKLE_DEPTH)));
+        }
+        self.take(name.to_string(), Encoding::Hash, depth)?;
+        Ok(())
+    }
+}
+
+/// Every field of `proof`, in order
+///
+/// Fails on anything stark-verifier's parser rejects (unknown hash kind,
+/// counts over the bounds, non-canonical trace values, truncation) and on
+/// bytes after the last query.
+pub fn layout(proof: &[u8]) -> Result<Vec<Field>, ProofError> {
+    if proof.len() > MAX_PROOF_SIZE {
+        return Err(malformed(format!("{} bytes exceeds {}", proof.len(), MAX_PROOF_SIZE)));
+    }
+    let mut w = Walker { proof, at: 0, fields: Vec::new() };
+
+    if w.take("magic".into(), Encoding::U8, 4)? != PROOF_MAGIC {
+        return Err(malformed("missing MRKL magic"));
+    }
+    let version = w.byte("version".into())?;
+    if version != PROOF_VERSION {
+        return Err(malformed(format!("format version {}, expected {}", version, PROOF_VERSION)));
+    }
+    let hash_kind = w.byte("hash_kind".into())?;
+    if hash_kind > MAX_HASH_KIND {
+        return Err(malformed(format!("unknown hash kind {}", hash_kind)));
+    }
+    let log_domain_size = w.byte("log_trace_size".into())? as usize + w.byte("log_blowup".into())? as usize;
+    if log_domain_size == 0 || log_domain_size > MAX_MERKLE_DEPTH {
+        return Err(malformed(format!("domain of 2^{}, expected 2^1..=2^{}", log_domain_size, MAX_MERKLE_DEPTH)));
+    }
+    let log_folding_factor = w.byte("log_folding_factor".into())? as usize;
+    if log_folding_factor == 0 || log_folding_factor > log_domain_size {
+        return Err(malformed(format!("folding by 2^{} over a 2^{} domain", log_folding_factor, log_domain_size)));
+    }
+    let queries = w.byte("query_count".into())? as usize;
+    if queries > MAX_QUERIES {
+        return Err(malformed(format!("{} queries, max {}", queries, MAX_QUERIES)));
+    }
+    w.take("trace_commitment".into(), Encoding::Hash, 1)?;
+    w.take("composition_commitment".into(), Encoding::Hash, 1)?;
+    let columns = w.byte("trace_columns".into())? as usize;
+    if columns == 0 || columns > MAX_TRACE_COLUMNS {
+        return Err(malformed(format!("{} trace columns, expected 1..={}", columns, MAX_TRACE_COLUMNS)));
+    }
+    w.take("trace_oods".into(), Encoding::Qm31Le, columns)?;
+    w.take("trace_oods_next".into(), Encoding::Qm31Le, columns)?;
+    w.take("composition_oods".into(), Encoding::Qm31Le, 1)?;
+
+    let layers = w.byte("fri_layer_count".into())? as usize;
+    if layers > MAX_FRI_LAYERS {
+        return Err(malformed(format!("{} FRI layers, max {}", layers, MAX_FRI_LAYERS)));
+    }
+    for i in 0..layers {
+        w.take(format!("fri_layer_commitment[{}]", i), Encoding::Hash, 1)?;
+    }
+
+    let raw = w.take("final_poly_len".into(), Encoding::U16Le, 1)?;
+    let final_len = u16::from_le_bytes([raw[0], raw[1]]);
+    let (count, max) = if final_len & FINAL_EVALUATIONS_FLAG != 0 {
+        ((final_len & !FINAL_EVALUATIONS_FLAG) as usize, MAX_FINAL_LAYER_EVALS)
+    } else {
+        (final_len as usize, MAX_FINAL_POLY_LEN)
+    };
+    if count > max || (final_len & FINAL_EVALUATIONS_FLAG != 0 && count == 0) {
+        return Err(malformed(format!("final layer of {} values, expected at most {}", count, max)));
+    }
+    w.take("final_poly".into(), Encoding::Qm31Le, count)?;
+
+    for q in 0..queries {
+        let prefix = format!("queries[{}]", q);
+        w.take(format!("{}.index", prefix), Encoding::U32Le, 1)?;
+        w.take(format!("{}.trace_row", prefix), Encoding::M31Le, columns)?;
+        w.path(&format!("{}.trace_path", prefix))?;
+        w.take(format!("{}.composition_leaf", prefix), Encoding::Hash, 1)?;
+        w.path(&format!("{}.composition_path", prefix))?;
+        for l in 0..layers {
+            w.take(format!("{}.fri[{}].siblings", prefix, l), Encoding::Qm31Le, 4)?;
+            w.path(&format!("{}.fri[{}].path", prefix, l))?;
+        }
+    }
+
+    if w.at != proof.len() {
+        return Err(malformed(format!("{} bytes after the last query", proof.len() - w.at)));
+    }
+    Ok(w.fields)
+}
+
+/// `proof` as an annotated test vector: `offset len name encoding hex` per line
+pub fn annotate(proof: &[u8]) -> Result<String, ProofError> {
+    let mut out = String::new();
+    for field in layout(proof)? {
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            out,
+            "{:06x} {:6}  {:<28} {:<8} {}",
+            field.offset,
+            field.len,
+            field.name,
+            field.encoding.name(),
+            hex::encode(&proof[field.range()])
+        );
+    }
+    Ok(out)
+}
+
+/// Proof bytes of an annotated test vector
+///
+/// Blank lines and `#` comments are skipped. The vector is rejected unless
+/// its lines are contiguous, each hex column matches its length, and
+/// [`layout`] of the bytes yields exactly the annotated fields.
+pub fn parse_annotated(vector: &str) -> Result<Vec<u8>, ProofError> {
+    let mut proof = Vec::new();
+    let mut annotated = Vec::new();
+    for (line_no, line) in vector.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bad = |what: &str| malformed(format!("line {}: {}", line_no + 1, what));
+        let mut cols = line.split_whitespace();
+        let offset = cols.next().and_then(|c| usize::from_str_radix(c, 16).ok()).ok_or_else(|| bad("bad offset"))?;
+        let len: usize = cols.next().and_then(|c| c.parse().ok()).ok_or_else(|| bad("bad length"))?;
+        let name = cols.next().ok_or_else(|| bad("missing name"))?;
+        let encoding = cols.next().and_then(Encoding::from_name).ok_or_else(|| bad("unknown encoding"))?;
+        // An empty field (zero-depth path) has no hex column
+        let bytes = match cols.next() {
+            Some(hex) => hex::decode(hex).map_err(|_| bad("bad hex"))?,
+            None => Vec::new(),
+        };
+        if offset != proof.len() || bytes.len() != len || cols.next().is_some() {
+            return Err(bad("field does not continue the vector"));
+        }
+        proof.extend_from_slice(&bytes);
+        annotated.push(Field { name: name.to_string(), offset, len, encoding });
+    }
+
+    let fields = layout(&proof)?;
+    if let Some((got, want)) = annotated.iter().zip(&fields).find(|(a, f)| a != f) {
+        return Err(malformed(format!("annotated {} at {:#x}, layout has {} there", got.name, got.offset, want.name)));
+    }
+    if annotated.len() != fields.len() {
+        return Err(malformed(format!("{} annotated fields, layout has {}", annotated.len(), fields.len())));
+    }
+    Ok(proof)
+}
+
+/// Part of a proof a [`FieldSpec`] belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// Read once; offsets are from the start of the proof
+    Header,
+    /// Repeated `Q` times; offsets are from the start of the query
+    Query,
+    /// Repeated `L` times at the end of each query; offsets are from the
+    /// start of the opening
+    FriOpening,
+}
+
+impl Section {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Header => "header",
+            Self::Query => "query",
+            Self::FriOpening => "fri opening",
+        }
+    }
+}
+
+/// Number of elements in a field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Count {
+    Fixed(usize),
+    /// Set by an earlier field, the one whose `defines` is this symbol
+    Var(&'static str),
+}
+
+/// A field as the format defines it, before a proof fixes the counts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldSpec {
+    /// Name as [`layout`] reports it, with the indices left symbolic
+    pub name: &'static str,
+    pub section: Section,
+    pub encoding: Encoding,
+    pub count: Count,
+    /// Symbol for this field's value, if a later count uses it
+    pub defines: Option<&'static str>,
+    /// What the verifier requires of the field; empty if anything goes
+    pub condition: String,
+}
+
+/// Every field of the format, in the order [`layout`] reads them
+pub fn format_fields() -> Vec<FieldSpec> {
+    use Count::{Fixed, Var};
+    use Encoding::*;
+    use Section::*;
+
+    let field = |section, name, encoding, count, defines, condition: String| FieldSpec {
+        name,
+        section,
+        encoding,
+        count,
+        defines,
+        condition,
+    };
+    vec![
+        field(Header, "magic", U8, Fixed(4), None, format!("= {} (\"MRKL\")", hex::encode(PROOF_MAGIC))),
+        field(Header, "version", U8, Fixed(1), None, format!("= {}", PROOF_VERSION)),
+        field(Header, "hash_kind", U8, Fixed(1), None, format!("..={} (keccak, poseidon2, poseidon-bn254)", MAX_HASH_KIND)),
+        field(Header, "log_trace_size", U8, Fixed(1), None, String::new()),
+        field(
+            Header,
+            "log_blowup",
+            U8,
+            Fixed(1),
+            None,
+            format!("log_trace_size + log_blowup in 1..={}", MAX_MERKLE_DEPTH),
+        ),
+        field(Header, "log_folding_factor", U8, Fixed(1), None, "1..=log_trace_size + log_blowup".into()),
+        field(Header, "query_count", U8, Fixed(1), Some("Q"), format!("..={}", MAX_QUERIES)),
+        field(Header, "trace_commitment", Hash, Fixed(1), None, String::new()),
+        field(Header, "composition_commitment", Hash, Fixed(1), None, String::new()),
+        field(Header, "trace_columns", U8, Fixed(1), Some("C"), format!("1..={}", MAX_TRACE_COLUMNS)),
+        field(Header, "trace_oods", Qm31Le, Var("C"), None, String::new()),
+        field(Header, "trace_oods_next", Qm31Le, Var("C"), None, String::new()),
+        field(Header, "composition_oods", Qm31Le, Fixed(1), None, String::new()),
+        field(Header, "fri_layer_count", U8, Fixed(1), Some("L"), format!("..={}", MAX_FRI_LAYERS)),
+        field(Header, "fri_layer_commitment[i]", Hash, Var("L"), None, String::new()),
+        field(
+            Header,
+            "final_poly_len",
+            U16Le,
+            Fixed(1),
+            Some("F"),
+            format!(
+                "F is the low 15 bits; top bit set: 1..={} evaluations, clear: ..={} coefficients",
+                MAX_FINAL_LAYER_EVALS, MAX_FINAL_POLY_LEN
+            ),
+        ),
+        field(Header, "final_poly", Qm31Le, Var("F"), None, String::new()),
+        field(Query, "queries[q].index", U32Le, Fixed(1), None, String::new()),
+        field(
+            Query,
+            "queries[q].trace_row",
+            M31Le,
+            Var("C"),
+            None,
+            format!("canonical; the trace leaf is H(\"{}\" || trace_row)", String::from_utf8_lossy(TRACE_ROW_DOMAIN)),
+        ),
+        field(
+            Query,
+            "queries[q].trace_path_len",
+            U8,
+            Fixed(1),
+            Some("Dt"),
+            format!("..={} to parse; log_trace_size + log_blowup to verify", MAX_MERKLE_DEPTH),
+        ),
+        field(Query, "queries[q].trace_path", Hash, Var("Dt"), None, String::new()),
+        field(Query, "queries[q].composition_leaf", Hash, Fixed(1), None, String::new()),
+        field(
+            Query,
+            "queries[q].composition_path_len",
+            U8,
+            Fixed(1),
+            Some("Dc"),
+            format!("..={} to parse; log_trace_size + log_blowup to verify", MAX_MERKLE_DEPTH),
+        ),
+        field(Query, "queries[q].composition_path", Hash, Var("Dc"), None, String::new()),
+        field(
+            FriOpening,
+            "queries[q].fri[l].siblings",
+            Qm31Le,
+            Fixed(4),
+            None,
+            format!("the layer leaf is H(\"{}\" || siblings)", String::from_utf8_lossy(FRI_GROUP_DOMAIN)),
+        ),
+        field(
+            FriOpening,
+            "queries[q].fri[l].path_len",
+            U8,
+            Fixed(1),
+            Some("Df"),
+            format!(
+                "..={} to parse; log_trace_size + log_blowup - log_folding_factor * (l + 1) to verify",
+                MAX_MERKLE_DEPTH
+            ),
+        ),
+        field(FriOpening, "queries[q].fri[l].path", Hash, Var("Df"), None, String::new()),
+    ]
+}
+
+/// Byte offset as a constant plus multiples of count symbols
+#[derive(Default)]
+struct Offset {
+    bytes: usize,
+    terms: Vec<(usize, &'static str)>,
+}
+
+impl Offset {
+    fn advance(&mut self, width: usize, count: Count) {
+        match count {
+            Count::Fixed(n) => self.bytes += width * n,
+            Count::Var(symbol) => match self.terms.iter_mut().find(|(_, s)| *s == symbol) {
+                Some((coefficient, _)) => *coefficient += width,
+                None => self.terms.push((width, symbol)),
+            },
+        }
+    }
+}
+
+impl core::fmt::Display for Offset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.bytes)?;
+        for (coefficient, symbol) in &self.terms {
+            write!(f, " + {}*{}", coefficient, symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// [`format_fields`] as a Markdown table of offsets, lengths, encodings and
+/// conditions, with the symbol each count field sets
+pub fn format_table() -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Murkl proof format, version {}\n", PROOF_VERSION);
+    let _ = writeln!(
+        out,
+        "Integers are little-endian; a qm31le is four m31le coordinates. A proof is \
+         at most {} bytes. Header offsets are from the start of the proof; the query \
+         section repeats Q times and its FRI openings L times at the end of each \
+         query, with offsets from the start of the query or opening. Nothing follows \
+         the last query.\n",
+        MAX_PROOF_SIZE
+    );
+    let _ = writeln!(out, "| section | offset | length | field | encoding | sets | condition |");
+    let _ = writeln!(out, "|---|---|---|---|---|---|---|");
+
+    let mut offset = Offset::default();
+    let mut section = Section::Header;
+    for spec in format_fields() {
+        if spec.section != section {
+            section = spec.section;
+            offset = Offset::default();
+        }
+        let len = match spec.count {
+            Count::Fixed(n) => (spec.encoding.width() * n).to_string(),
+            Count::Var(symbol) => format!("{}*{}", spec.encoding.width(), symbol),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            section.name(),
+            offset,
+            len,
+            spec.name,
+            spec.encoding.name(),
+            spec.defines.unwrap_or(""),
+            spec.condition
+        );
+        offset.advance(spec.encoding.width(), spec.count);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest shape worth checking: 2 columns, 1 FRI layer, 2 queries
+    fn sample() -> Vec<u8> {
+        let mut p = PROOF_MAGIC.to_vec();
+        p.extend([PROOF_VERSION, 0, 10, 4, 2, 2]);
+        p.extend([1u8; 32]);
+        p.extend([2u8; 32]);
+        p.push(2);
+        p.extend([3u8; 80]);
+        p.push(1);
+        p.extend([4u8; 32]);
+        p.extend(1u16.to_le_bytes());
+        p.extend([5u8; 16]);
+        for q in 0..2u32 {
+            p.extend(q.to_le_bytes());
+            p.extend([6u8; 8]);
+            p.push(2);
+            p.extend([7u8; 64]);
+            p.extend([8u8; 32]);
+            p.push(0);
+            p.extend([9u8; 64]);
+            p.push(1);
+            p.extend([10u8; 32]);
+        }
+        p
+    }
+
+    #[test]
+    fn test_layout_covers_every_byte() {
+        let proof = sample();
+        let fields = layout(&proof).unwrap();
+        assert_eq!(fields[2].range(), HASH_KIND);
+        assert_eq!(fields[6].range(), QUERY_COUNT);
+        assert_eq!(fields[7].range(), TRACE_COMMITMENT);
+        assert_eq!(fields[10].range(), trace_oods(2));
+        assert_eq!(fields[11].range(), trace_oods_next(2));
+        assert_eq!(fields[13].range(), fri_layer_count(2));
+        assert_eq!(fields.iter().map(|f| f.len).sum::<usize>(), proof.len());
+        assert!(fields.windows(2).all(|w| w[0].range().end == w[1].offset));
+
+        let last = fields.last().unwrap();
+        assert_eq!(last.name, "queries[1].fri[0].path");
+        assert_eq!(last.len, 32);
+        let empty = fields.iter().find(|f| f.name == "queries[0].composition_path").unwrap();
+        assert_eq!(empty.len, 0);
+    }
+
+    /// The layout and the canonical decoder read the same bytes
+    #[test]
+    fn test_layout_agrees_with_decoder() {
+        let proof = sample();
+        let decoded = murkl_proof_format::SerializedProof::decode(&proof).unwrap();
+        assert_eq!(decoded.queries.len(), 2);
+        assert_eq!(decoded.encode(), proof);
+    }
+
+    #[test]
+    fn test_annotated_round_trip() {
+        let proof = sample();
+        let vector = annotate(&proof).unwrap();
+        assert!(vector.starts_with("000000      4  magic"));
+        assert_eq!(parse_annotated(&vector).unwrap(), proof);
+        let commented = format!("# sample\n\n{}", vector);
+        assert_eq!(parse_annotated(&commented).unwrap(), proof);
+
+        // A renamed or dropped field no longer describes the bytes
+        let renamed = vector.replacen("trace_oods", "trace_ood", 1);
+        assert!(parse_annotated(&renamed).is_err());
+        let dropped: String = vector.lines().skip(1).map(|l| format!("{}\n", l)).collect();
+        assert!(parse_annotated(&dropped).is_err());
+    }
+
+    /// The symbolic table describes the fields `layout` reads, all of them
+    #[test]
+    fn test_format_fields_match_layout() {
+        let unindexed = |name: &str| -> String {
+            let mut depth = 0;
+            name.chars()
+                .filter(|&c| {
+                    depth += (c == '[') as i32 - (c == ']') as i32;
+                    depth == 0 || c == '['
+                })
+                .collect()
+        };
+        let specs = format_fields();
+        let fields = layout(&sample()).unwrap();
+        for field in &fields {
+            let spec = specs.iter().find(|s| unindexed(s.name) == unindexed(&field.name));
+            let spec = spec.unwrap_or_else(|| panic!("{} is not in the format", field.name));
+            assert_eq!(spec.encoding, field.encoding, "{}", field.name);
+        }
+        for spec in &specs {
+            assert!(fields.iter().any(|f| unindexed(&f.name) == unindexed(spec.name)), "{} is never read", spec.name);
+        }
+
+        let table = format_table();
+        let row = |offset: usize, name: &str| format!("| header | {} | 1 | {} |", offset, name);
+        assert!(table.contains(&row(QUERY_COUNT.start, "query_count")));
+        assert!(table.contains(&row(TRACE_COLUMNS.start, "trace_columns")));
+        assert!(table.contains("| header | 75 | 16*C | trace_oods | qm31le |"));
+        assert!(table.contains("| header | 91 + 32*C | 1 | fri_layer_count | u8 | L |"));
+        assert!(table.contains("| header | 92 + 32*C + 32*L | 2 | final_poly_len | u16le | F |"));
+        assert!(table.contains("| query | 5 + 4*C + 32*Dt | 32 | queries[q].composition_leaf |"));
+    }
+
+    #[test]
+    fn test_layout_enforces_verifier_bounds() {
+        let proof = sample();
+        let rejects = |edit: &dyn Fn(&mut Vec<u8>)| {
+            let mut p = proof.clone();
+            edit(&mut p);
+            layout(&p).is_err()
+        };
+        assert!(rejects(&|p| p[MAGIC.start] = b'X'));
+        assert!(rejects(&|p| p[VERSION.start] = PROOF_VERSION + 1));
+        assert!(rejects(&|p| p[HASH_KIND.start] = 3));
+        assert!(rejects(&|p| p[LOG_TRACE_SIZE.start] = MAX_MERKLE_DEPTH as u8));
+        assert!(rejects(&|p| p[LOG_FOLDING_FACTOR.start] = 0));
+        assert!(rejects(&|p| p[QUERY_COUNT.start] = MAX_QUERIES as u8 + 1));
+        assert!(rejects(&|p| p[TRACE_COLUMNS.start] = 0));
+        assert!(rejects(&|p| p[TRACE_COLUMNS.start] = MAX_TRACE_COLUMNS as u8 + 1));
+        assert!(rejects(&|p| p[fri_layer_count(2).start] = MAX_FRI_LAYERS as u8 + 1));
+        // Evaluations flag with no values
+        assert!(rejects(&|p| p[188..190].copy_from_slice(&FINAL_EVALUATIONS_FLAG.to_le_bytes())));
+        // First trace value of query 0 set to p
+        assert!(rejects(&|p| p[210..214].copy_from_slice(&M31_PRIME.to_le_bytes())));
+        assert!(rejects(&|p| p.push(0)));
+        assert!(rejects(&|p| {
+            p.pop();
+        }));
+    }
+}
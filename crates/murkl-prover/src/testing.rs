@@ -0,0 +1,219 @@
+//! Corrupted proofs for soundness tests
+//!
+//! [`ProofMutator`] takes an on-chain proof and derives labeled corruptions
+//! of it, each addressed by a field of the wire format (see [`spec`]):
+//!
+//! ```rust,ignore
+//! use murkl_prover::testing::ProofMutator;
+//!
+//! let mutants = ProofMutator::new(&proof)?
+//!     .flip_bit("trace_oods", 0)
+//!     .flip_bit("queries[1].fri[0].siblings", 130)
+//!     .swap_queries(0, 1)
+//!     .truncate("queries[2]")
+//!     .mutants();
+//! for mutant in &mutants {
+//!     assert!(verify(&mutant.bytes).is_err(), "{} accepted", mutant.label);
+//! }
+//! ```
+//!
+//! Each call adds one mutant of the original proof; they don't stack. A
+//! section is a field name as [`spec::layout`] reports it, or a prefix
+//! ending at a `.` or `[` of one, so `queries[1]` is the whole second query
+//! and `queries[1].fri[0]` its first FRI opening.
+//!
+//! Built with the `testing` feature; nothing here belongs in a release
+//! build.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::spec::{self, Field};
+use crate::types::ProofError;
+
+/// A corrupted proof and what was done to it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mutant {
+    /// E.g. `flip bit 3 of queries[0].index`
+    pub label: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Fluent builder of [`Mutant`]s of one proof
+///
+/// The methods panic on a section the proof doesn't have or an offset
+/// outside it, so a typo fails the test instead of testing nothing.
+#[derive(Clone, Debug)]
+pub struct ProofMutator {
+    proof: Vec<u8>,
+    fields: Vec<Field>,
+    mutants: Vec<Mutant>,
+}
+
+impl ProofMutator {
+    /// Mutator of `proof`, which has to parse as an on-chain proof
+    pub fn new(proof: &[u8]) -> Result<Self, ProofError> {
+        let fields = spec::layout(proof)?;
+        Ok(Self { proof: proof.to_vec(), fields, mutants: Vec::new() })
+    }
+
+    /// Fields of the original proof, in order
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Byte range of `section` in the original proof, if it has one
+    pub fn section(&self, section: &str) -> Option<Range<usize>> {
+        let mut matching = self.fields.iter().filter(|field| {
+            field.name.strip_prefix(section).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        });
+        let first = matching.next()?;
+        let end = matching.next_back().map_or(first.range().end, |last| last.range().end);
+        Some(first.offset..end)
+    }
+
+    fn expect_section(&self, section: &str) -> Range<usize> {
+        self.section(section).unwrap_or_else(|| panic!("proof has no section {}", section))
+    }
+
+    fn push(mut self, label: String, bytes: Vec<u8>) -> Self {
+        self.mutants.push(Mutant { label, bytes });
+        self
+    }
+
+    /// Flip bit `offset` of `section`, counting from the low bit of its
+    /// first byte
+    pub fn flip_bit(self, section: &str, offset: usize) -> Self {
+        let range = self.expect_section(section);
+        assert!(
+            offset < range.len() * 8,
+            "bit {} is outside {} ({} bytes)",
+            offset,
+            section,
+            range.len()
+        );
+        let mut bytes = self.proof.clone();
+        bytes[range.start + offset / 8] ^= 1 << (offset % 8);
+        self.push(format!("flip bit {} of {}", offset, section), bytes)
+    }
+
+    /// Exchange the whole of queries `i` and `j`
+    pub fn swap_queries(self, i: usize, j: usize) -> Self {
+        let (i, j) = (i.min(j), i.max(j));
+        assert!(i != j, "swapping query {} with itself", i);
+        let first = self.expect_section(&format!("queries[{}]", i));
+        let second = self.expect_section(&format!("queries[{}]", j));
+
+        let p = &self.proof;
+        let mut bytes = Vec::with_capacity(p.len());
+        bytes.extend_from_slice(&p[..first.start]);
+        bytes.extend_from_slice(&p[second.clone()]);
+        bytes.extend_from_slice(&p[first.end..second.start]);
+        bytes.extend_from_slice(&p[first]);
+        bytes.extend_from_slice(&p[second.end..]);
+        self.push(format!("swap queries {} and {}", i, j), bytes)
+    }
+
+    /// Cut the proof off where `section` starts
+    pub fn truncate(self, section: &str) -> Self {
+        let range = self.expect_section(section);
+        let bytes = self.proof[..range.start].to_vec();
+        self.push(format!("truncate at {}", section), bytes)
+    }
+
+    /// The mutants so far, in the order they were added
+    pub fn mutants(self) -> Vec<Mutant> {
+        self.mutants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof};
+
+    /// 2 columns, 1 FRI layer, 3 queries told apart by index and row
+    fn sample() -> SerializedProof {
+        SerializedProof {
+            hash_kind: 0,
+            log_trace_size: 4,
+            log_blowup: 2,
+            log_folding_factor: 2,
+            trace_commitment: [1; 32],
+            composition_commitment: [2; 32],
+            trace_columns: 2,
+            trace_oods: vec![[3; 4]; 2],
+            trace_oods_next: vec![[4; 4]; 2],
+            composition_oods: [4; 4],
+            fri_layer_commitments: vec![[5; 32]],
+            final_layer: FinalLayer::Coefficients(vec![[6; 4]]),
+            queries: (0..3u32)
+                .map(|q| QueryOpening {
+                    index: q * 5,
+                    trace_row: vec![q, q + 1],
+                    trace_path: vec![[7; 32]; q as usize],
+                    composition_leaf: [8; 32],
+                    composition_path: vec![[9; 32]],
+                    fri: vec![FriOpening { siblings: [[q; 4]; 4], path: vec![] }],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_section_spans_prefixed_fields() {
+        let proof = sample().encode();
+        let mutator = ProofMutator::new(&proof).unwrap();
+        assert_eq!(mutator.section("trace_oods"), Some(spec::trace_oods(2)));
+        assert_eq!(mutator.section("fri_layer_commitment"), mutator.section("fri_layer_commitment[0]"));
+
+        let query = mutator.section("queries[1]").unwrap();
+        assert_eq!(query.start, mutator.section("queries[1].index").unwrap().start);
+        assert_eq!(query.end, mutator.section("queries[2]").unwrap().start);
+        // Prefixes stop at a path component
+        assert_eq!(mutator.section("queries[1].fri[0]").unwrap().end, query.end);
+        assert_eq!(mutator.section("trace"), None);
+        assert_eq!(mutator.section("queries[1"), None);
+    }
+
+    #[test]
+    fn test_mutants_corrupt_only_their_section() {
+        let original = sample();
+        let proof = original.encode();
+        let mutants = ProofMutator::new(&proof)
+            .unwrap()
+            .flip_bit("queries[1].index", 3)
+            .swap_queries(2, 0)
+            .truncate("queries[2]")
+            .mutants();
+        let labels: Vec<_> = mutants.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(labels, ["flip bit 3 of queries[1].index", "swap queries 0 and 2", "truncate at queries[2]"]);
+
+        let mut expected = original.clone();
+        expected.queries[1].index ^= 1 << 3;
+        assert_eq!(SerializedProof::decode(&mutants[0].bytes).unwrap(), expected);
+
+        // Queries of different lengths still swap cleanly
+        let mut expected = original.clone();
+        expected.queries.swap(0, 2);
+        assert_eq!(SerializedProof::decode(&mutants[1].bytes).unwrap(), expected);
+
+        assert!(mutants[2].bytes.len() < proof.len() && proof.starts_with(&mutants[2].bytes));
+        assert!(SerializedProof::decode(&mutants[2].bytes).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "proof has no section queries[3]")]
+    fn test_unknown_section_panics() {
+        let proof = sample().encode();
+        ProofMutator::new(&proof).unwrap().truncate("queries[3]");
+    }
+
+    #[test]
+    #[should_panic(expected = "bit 32 is outside queries[0].index")]
+    fn test_offset_past_section_panics() {
+        let proof = sample().encode();
+        ProofMutator::new(&proof).unwrap().flip_bit("queries[0].index", 32);
+    }
+}
@@ -3,30 +3,74 @@
 //! Defines Proof, PublicInputs, Witness, and other shared types.
 
 #[cfg(not(feature = "std"))]
-use alloc::{vec::Vec, string::String};
+use alloc::{format, vec::Vec, string::String};
 
-use crate::fri::FriProof;
+#[cfg(feature = "verify")]
+use crate::fri::{FinalLayerEncoding, FriLayerCommitment, FriLayerValue, FriProof, FriQueryProof};
 use crate::m31::M31;
 use crate::merkle::Hash;
+#[cfg(feature = "verify")]
+use crate::merkle::MerklePath;
+#[cfg(feature = "verify")]
 use crate::prover::QueryProof;
+#[cfg(feature = "verify")]
+use crate::qm31::QM31;
+
+/// Set in the serialized final layer count when the layer is sent as
+/// evaluations rather than coefficients
+#[cfg(feature = "verify")]
+pub const FINAL_EVALUATIONS_FLAG: u32 = 1 << 31;
 
 /// A STARK proof
+#[cfg(feature = "verify")]
 #[derive(Clone, Debug)]
 pub struct Proof {
     /// Merkle roots for trace column commitments
     pub trace_commitment: Vec<Hash>,
     /// Merkle root for composition polynomial
     pub composition_root: Hash,
+    /// Trace columns evaluated at the out-of-domain point
+    pub trace_oods: Vec<QM31>,
+    /// Trace columns evaluated one trace step past the out-of-domain point,
+    /// for constraints between consecutive rows
+    pub trace_oods_next: Vec<QM31>,
+    /// Committed composition quotient at the out-of-domain point
+    pub composition_oods: QM31,
     /// FRI proof for low-degree testing
     pub fri_proof: FriProof,
     /// Query proofs (openings at sampled points)
     pub query_proofs: Vec<QueryProof>,
     /// Public inputs
     pub public_inputs: PublicInputs,
+    /// Proof-of-work nonce bound in before queries; zero when not grinding
+    pub pow_nonce: u64,
 }
 
+#[cfg(feature = "verify")]
 impl Proof {
     /// Serialize the proof to bytes
+    ///
+    /// Every field goes in, in a fixed order, and [`Proof::from_bytes`]
+    /// reads them back, so equal proofs encode to equal bytes. This is the
+    /// library prover's own encoding: its per-column trace trees and M31
+    /// FRI layers have no place in the on-chain format (see
+    /// [`spec`](crate::spec)).
+    ///
+    /// ```text
+    /// trace_commitment  count u32 | hash × count
+    /// composition_root  hash
+    /// trace_oods        count u32 | qm31 × count, then trace_oods_next alike
+    /// composition_oods  qm31
+    /// fri_layers        count u32 | (root hash | log_size u32) × count
+    /// final_poly        count u32 (bit 31: evaluations) | m31 × count
+    /// fri_queries       count u32 | (index u32 | layers u32
+    ///                   | (siblings count u32 | m31 × count | path) × layers) × count
+    /// pow_nonce         u64
+    /// queries           count u32 | (index u32 | openings u32 | (m31 | path) × openings
+    ///                   | has_composition u8 | [m31 | path]) × count
+    /// public_inputs     count u32 | m31 × count, twice (initial, final)
+    /// path              leaf_index u32 | depth u32 | hash × depth
+    /// ```
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
 
@@ -39,42 +83,285 @@ impl Proof {
         // Composition root
         bytes.extend_from_slice(&self.composition_root);
 
-        // FRI proof (simplified serialization)
+        // Trace OODS values
+        bytes.extend_from_slice(&(self.trace_oods.len() as u32).to_le_bytes());
+        for value in &self.trace_oods {
+            bytes.extend_from_slice(&value.to_bytes());
+        }
+        bytes.extend_from_slice(&(self.trace_oods_next.len() as u32).to_le_bytes());
+        for value in &self.trace_oods_next {
+            bytes.extend_from_slice(&value.to_bytes());
+        }
+        bytes.extend_from_slice(&self.composition_oods.to_bytes());
+
+        // FRI layer commitments
         bytes.extend_from_slice(&(self.fri_proof.layer_commitments.len() as u32).to_le_bytes());
         for layer in &self.fri_proof.layer_commitments {
             bytes.extend_from_slice(&layer.root);
             bytes.extend_from_slice(&layer.log_size.to_le_bytes());
         }
 
-        bytes.extend_from_slice(&(self.fri_proof.final_poly.len() as u32).to_le_bytes());
+        // Top bit of the count flags an evaluation-encoded last layer
+        let mut final_count = self.fri_proof.final_poly.len() as u32;
+        if self.fri_proof.final_encoding == FinalLayerEncoding::Evaluations {
+            final_count |= FINAL_EVALUATIONS_FLAG;
+        }
+        bytes.extend_from_slice(&final_count.to_le_bytes());
         for coeff in &self.fri_proof.final_poly {
             bytes.extend_from_slice(&coeff.to_le_bytes());
         }
 
-        // Public inputs
-        bytes.extend_from_slice(&(self.public_inputs.initial_state.len() as u32).to_le_bytes());
-        for val in &self.public_inputs.initial_state {
-            bytes.extend_from_slice(&val.to_le_bytes());
+        // FRI query openings
+        bytes.extend_from_slice(&(self.fri_proof.query_proofs.len() as u32).to_le_bytes());
+        for query in &self.fri_proof.query_proofs {
+            bytes.extend_from_slice(&(query.query_index as u32).to_le_bytes());
+            bytes.extend_from_slice(&(query.layer_values.len() as u32).to_le_bytes());
+            for layer in &query.layer_values {
+                put_m31s(&mut bytes, &layer.siblings);
+                put_path(&mut bytes, &layer.merkle_path);
+            }
         }
 
-        bytes.extend_from_slice(&(self.public_inputs.final_state.len() as u32).to_le_bytes());
-        for val in &self.public_inputs.final_state {
-            bytes.extend_from_slice(&val.to_le_bytes());
+        bytes.extend_from_slice(&self.pow_nonce.to_le_bytes());
+
+        // Trace and composition openings
+        bytes.extend_from_slice(&(self.query_proofs.len() as u32).to_le_bytes());
+        for query in &self.query_proofs {
+            bytes.extend_from_slice(&(query.index as u32).to_le_bytes());
+            bytes.extend_from_slice(&(query.trace_openings.len() as u32).to_le_bytes());
+            for (value, path) in &query.trace_openings {
+                bytes.extend_from_slice(&value.to_le_bytes());
+                put_path(&mut bytes, path);
+            }
+            match &query.composition_opening {
+                Some((value, path)) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                    put_path(&mut bytes, path);
+                }
+                None => bytes.push(0),
+            }
         }
 
+        // Public inputs
+        bytes.extend_from_slice(&self.public_inputs.to_bytes());
+
         bytes
     }
 
+    /// Read a proof [`Proof::to_bytes`] wrote
+    ///
+    /// Rejects truncated input, trailing bytes and non-canonical field
+    /// elements; counts are checked against the bytes left before anything
+    /// is allocated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut r = ProofReader { bytes };
+
+        let count = r.count(32, "trace_commitment")?;
+        let trace_commitment = (0..count).map(|_| r.hash("trace_commitment")).collect::<Result<_, _>>()?;
+        let composition_root = r.hash("composition_root")?;
+        let trace_oods = r.qm31s("trace_oods")?;
+        let trace_oods_next = r.qm31s("trace_oods_next")?;
+        let composition_oods = r.qm31("composition_oods")?;
+
+        let count = r.count(36, "fri_layers")?;
+        let layer_commitments = (0..count)
+            .map(|_| {
+                Ok(FriLayerCommitment {
+                    root: r.hash("fri_layers")?,
+                    log_size: r.u32("fri_layers")?,
+                })
+            })
+            .collect::<Result<_, ProofError>>()?;
+
+        let final_count = r.u32("final_poly")?;
+        let final_encoding = if final_count & FINAL_EVALUATIONS_FLAG != 0 {
+            FinalLayerEncoding::Evaluations
+        } else {
+            FinalLayerEncoding::Coefficients
+        };
+        let final_count = (final_count & !FINAL_EVALUATIONS_FLAG) as usize;
+        r.check_count(final_count, 4, "final_poly")?;
+        let final_poly = (0..final_count).map(|_| r.m31("final_poly")).collect::<Result<_, _>>()?;
+
+        let count = r.count(8, "fri_queries")?;
+        let fri_queries = (0..count)
+            .map(|_| {
+                let query_index = r.u32("fri_queries")? as usize;
+                let layers = r.count(12, "fri_queries")?;
+                let layer_values = (0..layers)
+                    .map(|_| {
+                        Ok(FriLayerValue {
+                            siblings: r.m31s("fri_siblings")?,
+                            merkle_path: r.path("fri_path")?,
+                        })
+                    })
+                    .collect::<Result<_, ProofError>>()?;
+                Ok(FriQueryProof { query_index, layer_values })
+            })
+            .collect::<Result<_, ProofError>>()?;
+
+        let pow_nonce = r.u64("pow_nonce")?;
+
+        let count = r.count(9, "queries")?;
+        let query_proofs = (0..count)
+            .map(|_| {
+                let index = r.u32("queries")? as usize;
+                let openings = r.count(12, "trace_openings")?;
+                let trace_openings = (0..openings)
+                    .map(|_| Ok((r.m31("trace_openings")?, r.path("trace_path")?)))
+                    .collect::<Result<_, ProofError>>()?;
+                let composition_opening = match r.u8("composition_opening")? {
+                    0 => None,
+                    1 => Some((r.m31("composition_opening")?, r.path("composition_path")?)),
+                    flag => {
+                        return Err(ProofError::SerializationError(format!(
+                            "composition_opening flag {}",
+                            flag
+                        )))
+                    }
+                };
+                Ok(QueryProof { index, trace_openings, composition_opening })
+            })
+            .collect::<Result<_, ProofError>>()?;
+
+        let public_inputs = PublicInputs::new(r.m31s("initial_state")?, r.m31s("final_state")?);
+        if !r.bytes.is_empty() {
+            return Err(ProofError::SerializationError(format!(
+                "{} trailing bytes",
+                r.bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            trace_commitment,
+            composition_root,
+            trace_oods,
+            trace_oods_next,
+            composition_oods,
+            fri_proof: FriProof {
+                layer_commitments,
+                query_proofs: fri_queries,
+                final_poly,
+                final_encoding,
+            },
+            query_proofs,
+            public_inputs,
+            pow_nonce,
+        })
+    }
+
     /// Approximate proof size in bytes
     pub fn size(&self) -> usize {
         // This is an approximation
         let trace_size = self.trace_commitment.len() * 32;
         let composition_size = 32;
+        let oods_size = (self.trace_oods.len() + self.trace_oods_next.len() + 1) * 16;
         let fri_size = self.fri_proof.layer_commitments.len() * 36
             + self.fri_proof.final_poly.len() * 4;
         let query_size = self.query_proofs.len() * 100; // Approximate
 
-        trace_size + composition_size + fri_size + query_size
+        trace_size + composition_size + oods_size + fri_size + query_size
+    }
+}
+
+/// Count-prefixed M31 values
+#[cfg(feature = "verify")]
+fn put_m31s(bytes: &mut Vec<u8>, values: &[M31]) {
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[cfg(feature = "verify")]
+fn put_path(bytes: &mut Vec<u8>, path: &MerklePath) {
+    bytes.extend_from_slice(&(path.leaf_index as u32).to_le_bytes());
+    bytes.extend_from_slice(&(path.siblings.len() as u32).to_le_bytes());
+    for sibling in &path.siblings {
+        bytes.extend_from_slice(sibling);
+    }
+}
+
+/// Cursor over [`Proof::to_bytes`] output; errors name the field that
+/// failed to parse
+#[cfg(feature = "verify")]
+struct ProofReader<'a> {
+    bytes: &'a [u8],
+}
+
+#[cfg(feature = "verify")]
+impl<'a> ProofReader<'a> {
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8], ProofError> {
+        if self.bytes.len() < len {
+            return Err(ProofError::SerializationError(format!("proof ends inside {}", field)));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self, field: &str) -> Result<u8, ProofError> {
+        Ok(self.take(1, field)?[0])
+    }
+
+    fn u32(&mut self, field: &str) -> Result<u32, ProofError> {
+        Ok(u32::from_le_bytes(self.take(4, field)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self, field: &str) -> Result<u64, ProofError> {
+        Ok(u64::from_le_bytes(self.take(8, field)?.try_into().unwrap()))
+    }
+
+    fn hash(&mut self, field: &str) -> Result<Hash, ProofError> {
+        Ok(self.take(32, field)?.try_into().unwrap())
+    }
+
+    fn m31(&mut self, field: &str) -> Result<M31, ProofError> {
+        let value = self.u32(field)?;
+        if value >= crate::m31::M31_PRIME {
+            return Err(ProofError::SerializationError(format!(
+                "{} in {} is not a field element",
+                value, field
+            )));
+        }
+        Ok(M31::new(value))
+    }
+
+    fn qm31(&mut self, field: &str) -> Result<QM31, ProofError> {
+        Ok(QM31::new(self.m31(field)?, self.m31(field)?, self.m31(field)?, self.m31(field)?))
+    }
+
+    /// Fails unless `count` items of at least `item_size` bytes fit in what
+    /// is left
+    fn check_count(&self, count: usize, item_size: usize, field: &str) -> Result<(), ProofError> {
+        if count.saturating_mul(item_size) > self.bytes.len() {
+            return Err(ProofError::SerializationError(format!("proof ends inside {}", field)));
+        }
+        Ok(())
+    }
+
+    fn count(&mut self, item_size: usize, field: &str) -> Result<usize, ProofError> {
+        let count = self.u32(field)? as usize;
+        self.check_count(count, item_size, field)?;
+        Ok(count)
+    }
+
+    fn m31s(&mut self, field: &str) -> Result<Vec<M31>, ProofError> {
+        let count = self.count(4, field)?;
+        (0..count).map(|_| self.m31(field)).collect()
+    }
+
+    fn qm31s(&mut self, field: &str) -> Result<Vec<QM31>, ProofError> {
+        let count = self.count(16, field)?;
+        (0..count).map(|_| self.qm31(field)).collect()
+    }
+
+    fn path(&mut self, field: &str) -> Result<MerklePath, ProofError> {
+        let leaf_index = self.u32(field)? as usize;
+        let depth = self.count(32, field)?;
+        let siblings = (0..depth).map(|_| self.hash(field)).collect::<Result<_, _>>()?;
+        Ok(MerklePath { siblings, leaf_index })
     }
 }
 
@@ -192,6 +479,8 @@ pub enum ProofError {
     MerkleError(String),
     /// Serialization error
     SerializationError(String),
+    /// Configuration below the prover's minimum security level
+    BelowSecurityPolicy(String),
     /// Other error
     Other(String),
 }
@@ -222,6 +511,7 @@ impl core::fmt::Display for ProofError {
             Self::FriError(msg) => write!(f, "FRI error: {}", msg),
             Self::MerkleError(msg) => write!(f, "Merkle error: {}", msg),
             Self::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
+            Self::BelowSecurityPolicy(msg) => write!(f, "Below security policy: {}", msg),
             Self::Other(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -230,6 +520,106 @@ impl core::fmt::Display for ProofError {
 #[cfg(feature = "std")]
 impl std::error::Error for ProofError {}
 
+/// Current [`ProofBundle`] schema version
+pub const PROOF_BUNDLE_VERSION: u8 = 1;
+
+/// Proof plus the public inputs a relayer needs to submit a claim.
+///
+/// Canonical schema shared by the CLI, WASM prover and relayer API. With the
+/// `serde` feature, byte fields serialize as lowercase hex strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofBundle {
+    /// Schema version ([`PROOF_BUNDLE_VERSION`])
+    pub version: u8,
+    /// Serialized proof (on-chain verifier format)
+    #[cfg_attr(feature = "serde", serde(with = "hex_serde"))]
+    pub proof: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "hex_serde"))]
+    pub commitment: Hash,
+    #[cfg_attr(feature = "serde", serde(with = "hex_serde"))]
+    pub nullifier: Hash,
+    pub leaf_index: u32,
+    /// Base58 pool address the proof is for, if known
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub pool: Option<String>,
+    /// Hex-encoded pool root the proof targets, when not the latest one
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub merkle_root: Option<String>,
+    /// Proof-of-work nonce the prover ground before drawing queries
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub pow_nonce: Option<u64>,
+}
+
+impl ProofBundle {
+    /// Create a bundle at the current schema version
+    pub fn new(proof: Vec<u8>, commitment: Hash, nullifier: Hash, leaf_index: u32) -> Self {
+        Self {
+            version: PROOF_BUNDLE_VERSION,
+            proof,
+            commitment,
+            nullifier,
+            leaf_index,
+            pool: None,
+            merkle_root: None,
+            pow_nonce: None,
+        }
+    }
+
+    /// Attach the pool address
+    pub fn with_pool(mut self, pool: impl Into<String>) -> Self {
+        self.pool = Some(pool.into());
+        self
+    }
+
+    /// Pin the pool root the proof was built against
+    pub fn with_merkle_root(mut self, root: &Hash) -> Self {
+        self.merkle_root = Some(hex::encode(root));
+        self
+    }
+
+    /// Record the proof-of-work nonce
+    pub fn with_pow_nonce(mut self, nonce: u64) -> Self {
+        self.pow_nonce = Some(nonce);
+        self
+    }
+
+    /// Reject bundles written by an incompatible schema
+    pub fn check_version(&self) -> Result<(), ProofError> {
+        if self.version != PROOF_BUNDLE_VERSION {
+            return Err(ProofError::SerializationError(format!(
+                "unsupported proof bundle version {} (expected {})",
+                self.version, PROOF_BUNDLE_VERSION
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hex (de)serialization for byte fields, accepting an optional `0x` prefix
+#[cfg(feature = "serde")]
+mod hex_serde {
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec::Vec};
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: AsRef<[u8]>, S: Serializer>(bytes: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes.as_ref()))
+    }
+
+    pub fn deserialize<'de, T, D>(d: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(d)?;
+        let bytes = hex::decode(s.trim_start_matches("0x")).map_err(D::Error::custom)?;
+        let len = bytes.len();
+        T::try_from(bytes).map_err(|_| D::Error::custom(format_args!("unexpected length {}", len)))
+    }
+}
+
 /// Murkl-specific types for privacy protocol
 
 /// Murkl claim (for Merkle membership proofs)
@@ -293,10 +683,9 @@ impl MurklWitness {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "verify"))]
 mod tests {
     use super::*;
-    use crate::fri::{FriLayerCommitment, FriProof, FriQueryProof};
 
     #[test]
     fn test_public_inputs() {
@@ -356,6 +745,9 @@ mod tests {
         let proof = Proof {
             trace_commitment: vec![[0u8; 32]; 5],
             composition_root: [0u8; 32],
+            trace_oods: vec![],
+            trace_oods_next: vec![],
+            composition_oods: QM31::ZERO,
             fri_proof: FriProof {
                 layer_commitments: vec![
                     FriLayerCommitment { root: [0u8; 32], log_size: 10 },
@@ -363,9 +755,11 @@ mod tests {
                 ],
                 query_proofs: vec![],
                 final_poly: vec![M31::ONE; 4],
+                final_encoding: FinalLayerEncoding::Coefficients,
             },
             query_proofs: vec![],
             public_inputs: PublicInputs::empty(),
+            pow_nonce: 0,
         };
 
         let size = proof.size();
@@ -377,17 +771,106 @@ mod tests {
         let proof = Proof {
             trace_commitment: vec![[1u8; 32]],
             composition_root: [2u8; 32],
+            trace_oods: vec![QM31::ONE],
+            trace_oods_next: vec![QM31::ONE],
+            composition_oods: QM31::ONE,
             fri_proof: FriProof {
                 layer_commitments: vec![],
                 query_proofs: vec![],
                 final_poly: vec![M31::new(42)],
+                final_encoding: FinalLayerEncoding::Coefficients,
             },
             query_proofs: vec![],
             public_inputs: PublicInputs::new(vec![M31::new(1)], vec![]),
+            pow_nonce: 0,
         };
 
         let bytes = proof.to_bytes();
         assert!(!bytes.is_empty());
+
+        // trace (4 + 32) + composition 32 + oods (4 + 16) twice + composition
+        // oods 16 + fri layers 4
+        let count_at = 128;
+        let count = |bytes: &[u8]| u32::from_le_bytes(bytes[count_at..count_at + 4].try_into().unwrap());
+        assert_eq!(count(&bytes), 1);
+
+        let mut evaluations = proof.clone();
+        evaluations.fri_proof.final_encoding = FinalLayerEncoding::Evaluations;
+        assert_eq!(count(&evaluations.to_bytes()), FINAL_EVALUATIONS_FLAG | 1);
+    }
+
+    /// A proof with one of everything, its fields all distinct
+    fn sample_proof() -> Proof {
+        let path = |leaf_index: usize, seed: u8| MerklePath { siblings: vec![[seed; 32], [seed + 1; 32]], leaf_index };
+        Proof {
+            trace_commitment: vec![[1u8; 32], [2u8; 32]],
+            composition_root: [3u8; 32],
+            trace_oods: vec![QM31::from_u32(1, 2, 3, 4)],
+            trace_oods_next: vec![QM31::from_u32(5, 6, 7, 8)],
+            composition_oods: QM31::from_u32(9, 10, 11, 12),
+            fri_proof: FriProof {
+                layer_commitments: vec![FriLayerCommitment { root: [4u8; 32], log_size: 6 }],
+                query_proofs: vec![FriQueryProof {
+                    query_index: 17,
+                    layer_values: vec![FriLayerValue {
+                        siblings: (20..24).map(M31::new).collect(),
+                        merkle_path: path(16, 5),
+                    }],
+                }],
+                final_poly: vec![M31::new(42), M31::new(43)],
+                final_encoding: FinalLayerEncoding::Evaluations,
+            },
+            query_proofs: vec![
+                QueryProof {
+                    index: 9,
+                    trace_openings: vec![(M31::new(30), path(9, 7)), (M31::new(31), path(9, 9))],
+                    composition_opening: Some((M31::new(32), path(9, 11))),
+                },
+                QueryProof { index: 10, trace_openings: vec![], composition_opening: None },
+            ],
+            public_inputs: PublicInputs::new(vec![M31::new(1)], vec![M31::new(2), M31::new(3)]),
+            pow_nonce: 0x0102_0304_0506_0708,
+        }
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let bytes = sample_proof().to_bytes();
+        let decoded = Proof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.fri_proof.final_encoding, FinalLayerEncoding::Evaluations);
+        assert_eq!(decoded.query_proofs[0].composition_opening.as_ref().unwrap().1.leaf_index, 9);
+        assert!(decoded.query_proofs[1].composition_opening.is_none());
+
+        // Golden vector: the layout only changes on purpose
+        assert_eq!(bytes.len(), 590);
+        assert_eq!(
+            hex::encode(crate::merkle::hash_bytes(&bytes)),
+            "318396c1f2f13a4a9315391a20e1acab65b214d9fff8529e71f566034dc82ac2"
+        );
+    }
+
+    #[test]
+    fn test_proof_from_bytes_rejects_malformed() {
+        let bytes = sample_proof().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Proof::from_bytes(&bytes[..len]).is_err(), "prefix of {} bytes", len);
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Proof::from_bytes(&trailing).is_err());
+
+        // The first trace_oods limb follows two roots, the composition root
+        // and a count; 2^31 - 1 is not a field element
+        let mut non_canonical = bytes.clone();
+        non_canonical[104..108].copy_from_slice(&crate::m31::M31_PRIME.to_le_bytes());
+        assert!(Proof::from_bytes(&non_canonical).is_err());
+
+        // A count far past the end fails before allocating
+        let mut huge = bytes;
+        huge[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Proof::from_bytes(&huge).is_err());
     }
 
     #[test]
@@ -426,4 +909,36 @@ mod tests {
         let err = ProofError::constraint_violation("bad constraint");
         assert!(err.to_string().contains("Constraint violation"));
     }
+
+    #[test]
+    fn test_proof_bundle_version() {
+        let bundle = ProofBundle::new(vec![1, 2, 3], [7u8; 32], [9u8; 32], 4).with_pool("pool");
+        assert_eq!(bundle.version, PROOF_BUNDLE_VERSION);
+        assert_eq!(bundle.pool.as_deref(), Some("pool"));
+        assert_eq!(bundle.merkle_root, None);
+        assert_eq!(bundle.clone().with_merkle_root(&[0xab; 32]).merkle_root, Some("ab".repeat(32)));
+        assert!(bundle.check_version().is_ok());
+
+        let future = ProofBundle { version: PROOF_BUNDLE_VERSION + 1, ..bundle };
+        assert!(future.check_version().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_proof_bundle_json_uses_hex() {
+        let bundle = ProofBundle::new(vec![0xab, 0xcd], [1u8; 32], [2u8; 32], 5);
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(json.contains("\"proof\":\"abcd\""));
+        assert!(!json.contains("pool"));
+        assert!(!json.contains("pow_nonce"));
+
+        let back: ProofBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, bundle);
+
+        let prefixed = json.replace("\"abcd\"", "\"0xabcd\"");
+        assert_eq!(serde_json::from_str::<ProofBundle>(&prefixed).unwrap(), bundle);
+
+        let short = json.replace(&hex::encode([1u8; 32]), "0101");
+        assert!(serde_json::from_str::<ProofBundle>(&short).is_err());
+    }
 }
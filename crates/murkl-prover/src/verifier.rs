@@ -6,10 +6,16 @@
 use alloc::{vec, vec::Vec};
 
 use crate::air::ConstraintEvaluator;
+use crate::circle::{
+    barycentric_eval, canonic_vanishing, canonic_vanishing_secure, subgroup_generator, BarycentricDomain, Coset,
+    SecureCirclePoint,
+};
 use crate::fri::{FriVerifier, FriVerificationError};
 use crate::m31::M31;
+use crate::qm31::QM31;
 use crate::merkle::{Hash, hash_leaf};
-use crate::prover::{ProverConfig, QueryProof, Transcript};
+use crate::onchain::{self, OnChainError};
+use crate::prover::{check_pow, ProverConfig, QueryProof, Transcript};
 use crate::types::{Proof, PublicInputs};
 
 /// Verifier for Circle STARK proofs
@@ -44,43 +50,87 @@ impl Verifier {
 
         // Step 2: Get random coefficients (same as prover)
         let num_constraints = evaluator.constraints().len();
-        let _random_coefficients = transcript.challenge_scalars(num_constraints);
+        let random_coefficients = transcript.challenge_scalars(num_constraints);
 
         // Add composition commitment to transcript
         transcript.append(&proof.composition_root);
 
+        // Openings are quotients, only defined off the trace domain
+        let initial_log_size = proof.fri_proof.layer_commitments.first()
+            .map(|c| c.log_size)
+            .unwrap_or(0);
+        let log_trace_size = initial_log_size
+            .checked_sub(self.config.log_blowup_factor)
+            .filter(|&log| log > 0)
+            .ok_or(VerificationError::DomainTooSmall)?;
+
+        // Out-of-domain sample: the trace there and a step on, and the
+        // composition the AIR predicts from them
+        let oods_point = transcript.challenge_circle_point();
+        for value in proof.trace_oods.iter().chain(&proof.trace_oods_next) {
+            transcript.append_qm31(*value);
+        }
+        transcript.append_qm31(proof.composition_oods);
+        self.check_oods(evaluator, proof, &random_coefficients, &oods_point, log_trace_size)?;
+
         // Step 3: Verify FRI proof
         let fri_verifier = FriVerifier::new(self.config.fri_config.clone());
 
-        // Get FRI alphas from transcript
-        let num_rounds = self.config.fri_config.num_rounds(
-            proof.fri_proof.layer_commitments.first()
-                .map(|c| c.log_size)
-                .unwrap_or(0)
-        );
-        let alphas: Vec<M31> = (0..num_rounds)
-            .map(|_| transcript.challenge_scalar())
+        // Get FRI alphas from transcript, each layer's root absorbed before
+        // the coefficient that folds it. The first layer is the line
+        // polynomial the prover drew with the coefficient before it.
+        let num_rounds = self.config.fri_config.num_rounds(initial_log_size);
+        let layer_roots = &proof.fri_proof.layer_commitments;
+        if layer_roots.len() != num_rounds + 1 {
+            return Err(VerificationError::FriVerification(FriVerificationError::InvalidProofStructure));
+        }
+        transcript.challenge_scalar();
+        transcript.append(&layer_roots[0].root);
+        let alphas: Vec<M31> = layer_roots[1..]
+            .iter()
+            .map(|layer| {
+                let alpha = transcript.challenge_scalar();
+                transcript.append(&layer.root);
+                alpha
+            })
             .collect();
 
-        let initial_log_size = proof.fri_proof.layer_commitments.first()
-            .map(|c| c.log_size)
-            .unwrap_or(0);
-
         fri_verifier.verify(&proof.fri_proof, &alphas, initial_log_size)
             .map_err(VerificationError::FriVerification)?;
 
-        // Step 4: Verify query proofs
+        // Step 4: Proof of work, then the query proofs it gates
+        if self.config.pow_bits > 0 {
+            if !check_pow(&transcript.state(), proof.pow_nonce, self.config.pow_bits) {
+                return Err(VerificationError::InsufficientWork);
+            }
+            transcript.append_nonce(proof.pow_nonce);
+        }
+        // FRI queries are drawn first, then the trace and composition ones
+        let fri_indices = transcript.challenge_indices(self.config.num_queries, 1 << initial_log_size);
+        if proof.fri_proof.query_proofs.len() != fri_indices.len()
+            || proof.fri_proof.query_proofs.iter().zip(&fri_indices).any(|(q, &i)| q.query_index != i)
+        {
+            return Err(VerificationError::QueryIndexMismatch);
+        }
         let query_indices = transcript.challenge_indices(
             self.config.num_queries,
             1 << initial_log_size,
         );
 
+        let domain = Coset::commitment(initial_log_size);
+        for &index in &query_indices {
+            if canonic_vanishing(log_trace_size, domain.at(index).x).is_zero() {
+                return Err(VerificationError::QueryOnTraceDomain);
+            }
+        }
+
         for (i, query_proof) in proof.query_proofs.iter().enumerate() {
             self.verify_query(
                 query_proof,
                 &proof.trace_commitment,
                 &proof.composition_root,
                 query_indices.get(i).copied().unwrap_or(query_proof.index),
+                initial_log_size,
             )?;
         }
 
@@ -90,13 +140,55 @@ impl Verifier {
         Ok(())
     }
 
-    /// Verify a single query proof
+    /// Check the composition at the OODS point against the AIR
+    ///
+    /// Fixed columns are evaluated here rather than taken from the proof.
+    /// Evaluators without [`ConstraintEvaluator::evaluate_at_point`] give
+    /// no composition to check against, so their proofs are rejected.
+    fn check_oods<E: ConstraintEvaluator>(
+        &self,
+        evaluator: &E,
+        proof: &Proof,
+        coefficients: &[M31],
+        point: &SecureCirclePoint,
+        log_trace_size: u32,
+    ) -> Result<(), VerificationError> {
+        let fixed = evaluator.fixed_columns();
+        if !fixed.is_empty() {
+            let domain = BarycentricDomain::new(&Coset::canonic(log_trace_size));
+            let weights = domain.weights(point);
+            let next_weights = domain.weights(&point.add_base(subgroup_generator(log_trace_size)));
+            for (column, values) in fixed {
+                let expected = (values.len() == domain.size())
+                    .then(|| (barycentric_eval(&values, &weights), barycentric_eval(&values, &next_weights)));
+                let claimed = proof.trace_oods.get(column).copied().zip(proof.trace_oods_next.get(column).copied());
+                if expected.is_none() || expected != claimed {
+                    return Err(VerificationError::FixedColumnMismatch(column));
+                }
+            }
+        }
+
+        let constraints = evaluator
+            .evaluate_at_point(&proof.trace_oods, &proof.trace_oods_next)
+            .ok_or(VerificationError::NoPointEvaluation)?;
+        let composition = constraints.iter().enumerate().fold(QM31::ZERO, |acc, (i, &value)| {
+            acc + value * QM31::from(coefficients.get(i).copied().unwrap_or(M31::ONE))
+        });
+        if composition * canonic_vanishing_secure(log_trace_size, point).inv() != proof.composition_oods {
+            return Err(VerificationError::ConstraintMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verify a single query proof; the trace and composition trees have a
+    /// leaf per point of the `2^log_domain_size` commitment domain
     fn verify_query(
         &self,
         query_proof: &QueryProof,
         trace_roots: &[Hash],
         composition_root: &Hash,
         expected_index: usize,
+        log_domain_size: u32,
     ) -> Result<(), VerificationError> {
         // Check index matches
         if query_proof.index != expected_index {
@@ -110,7 +202,7 @@ impl Verifier {
             }
 
             let leaf_hash = hash_leaf(*value);
-            if !path.verify(&leaf_hash, &trace_roots[col_idx]) {
+            if !path.verify_at_depth(&leaf_hash, &trace_roots[col_idx], log_domain_size) {
                 return Err(VerificationError::TraceOpeningInvalid(col_idx));
             }
         }
@@ -118,7 +210,7 @@ impl Verifier {
         // Verify composition opening
         if let Some((value, path)) = &query_proof.composition_opening {
             let leaf_hash = hash_leaf(*value);
-            if !path.verify(&leaf_hash, composition_root) {
+            if !path.verify_at_depth(&leaf_hash, composition_root, log_domain_size) {
                 return Err(VerificationError::CompositionOpeningInvalid);
             }
         }
@@ -137,6 +229,21 @@ impl Verifier {
         Ok(())
     }
 
+    /// Check on-chain proof bytes exactly as the stark-verifier program
+    /// would, failing with the error it would report; see [`onchain`]
+    ///
+    /// The program's transcript absorbs the recipient after the other
+    /// public inputs, so a proof only passes for the recipient it names.
+    pub fn verify_bytes(
+        proof_bytes: &[u8],
+        commitment: &Hash,
+        nullifier: &Hash,
+        merkle_root: &Hash,
+        recipient: &Hash,
+    ) -> Result<(), OnChainError> {
+        onchain::verify(proof_bytes, commitment, nullifier, merkle_root, recipient)
+    }
+
     /// Quick verify with fewer checks (for testing)
     pub fn quick_verify(&self, proof: &Proof) -> Result<(), VerificationError> {
         // Just verify the structure is valid
@@ -171,6 +278,16 @@ pub enum VerificationError {
     NoQueries,
     /// Constraint evaluation mismatch
     ConstraintMismatch,
+    /// Commitment domain is not larger than the blowup factor
+    DomainTooSmall,
+    /// A query point lies on the trace domain, where the quotient is undefined
+    QueryOnTraceDomain,
+    /// The proof-of-work nonce has fewer leading zero bits than configured
+    InsufficientWork,
+    /// A column the AIR fixes does not have its fixed value at the OODS point
+    FixedColumnMismatch(usize),
+    /// The AIR cannot evaluate its constraints at the OODS point
+    NoPointEvaluation,
 }
 
 impl core::fmt::Display for VerificationError {
@@ -186,6 +303,11 @@ impl core::fmt::Display for VerificationError {
             Self::EmptyTraceCommitment => write!(f, "Empty trace commitment"),
             Self::NoQueries => write!(f, "No query proofs"),
             Self::ConstraintMismatch => write!(f, "Constraint evaluation mismatch"),
+            Self::DomainTooSmall => write!(f, "Commitment domain too small for blowup"),
+            Self::QueryOnTraceDomain => write!(f, "Query point on trace domain"),
+            Self::InsufficientWork => write!(f, "Insufficient proof of work"),
+            Self::FixedColumnMismatch(col) => write!(f, "Fixed column {} mismatch at OODS point", col),
+            Self::NoPointEvaluation => write!(f, "Constraints cannot be evaluated at OODS point"),
         }
     }
 }
@@ -235,10 +357,12 @@ pub fn batch_verify<E: ConstraintEvaluator>(
     Ok(())
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prove"))]
 mod tests {
     use super::*;
+    use crate::air::poseidon2::{MurklHashAir, ROUND_CONSTANTS};
     use crate::air::FibonacciAir;
+    use crate::hash::{pq_commitment, pq_nullifier};
     use crate::prover::Prover;
 
     #[test]
@@ -255,10 +379,14 @@ mod tests {
         let proof = Proof {
             trace_commitment: vec![[0u8; 32]],
             composition_root: [0u8; 32],
+            trace_oods: vec![],
+            trace_oods_next: vec![],
+            composition_oods: QM31::ZERO,
             fri_proof: crate::fri::FriProof {
                 layer_commitments: vec![],
                 query_proofs: vec![],
                 final_poly: vec![],
+                final_encoding: crate::fri::FinalLayerEncoding::Coefficients,
             },
             query_proofs: vec![QueryProof {
                 index: 0,
@@ -269,6 +397,7 @@ mod tests {
                 initial_state: vec![M31::ONE],
                 final_state: vec![],
             },
+            pow_nonce: 0,
         };
 
         let result = verifier.quick_verify(&proof);
@@ -282,10 +411,14 @@ mod tests {
         let proof = Proof {
             trace_commitment: vec![],  // Empty!
             composition_root: [0u8; 32],
+            trace_oods: vec![],
+            trace_oods_next: vec![],
+            composition_oods: QM31::ZERO,
             fri_proof: crate::fri::FriProof {
                 layer_commitments: vec![],
                 query_proofs: vec![],
                 final_poly: vec![],
+                final_encoding: crate::fri::FinalLayerEncoding::Coefficients,
             },
             query_proofs: vec![QueryProof {
                 index: 0,
@@ -296,6 +429,7 @@ mod tests {
                 initial_state: vec![M31::ONE],
                 final_state: vec![],
             },
+            pow_nonce: 0,
         };
 
         let result = verifier.quick_verify(&proof);
@@ -346,6 +480,36 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_query_paths_match_domain_depth() {
+        let config = ProverConfig::fast();
+        let air = FibonacciAir::new(64);
+        let trace = air.generate_trace(M31::ONE, M31::ONE);
+        let public_inputs = PublicInputs {
+            initial_state: vec![M31::ONE, M31::ONE],
+            final_state: vec![trace.get(63, 0)],
+        };
+        let proof = Prover::new(config.clone()).prove(&air, &trace, public_inputs).unwrap();
+        let verifier = Verifier::new(config);
+        let log_domain_size = proof.fri_proof.layer_commitments[0].log_size;
+        let check = |query: &QueryProof| {
+            verifier.verify_query(query, &proof.trace_commitment, &proof.composition_root, query.index, log_domain_size)
+        };
+
+        let query = &proof.query_proofs[0];
+        assert_eq!(check(query), Ok(()));
+
+        for resize in [log_domain_size - 1, log_domain_size + 1] {
+            let mut tampered = query.clone();
+            tampered.trace_openings[0].1.siblings.resize(resize as usize, [0; 32]);
+            assert_eq!(check(&tampered), Err(VerificationError::TraceOpeningInvalid(0)));
+
+            let mut tampered = query.clone();
+            tampered.composition_opening.as_mut().unwrap().1.siblings.resize(resize as usize, [0; 32]);
+            assert_eq!(check(&tampered), Err(VerificationError::CompositionOpeningInvalid));
+        }
+    }
+
     #[test]
     fn test_merkle_path_verification() {
         use crate::merkle::MerkleTree;
@@ -387,5 +551,40 @@ mod tests {
         for proof in &proofs {
             assert!(verifier.quick_verify(proof).is_ok());
         }
+
+        // Fibonacci reads two rows ahead, past what the OODS point carries
+        assert_eq!(batch_verify(&verifier, &air, &proofs), Err((0, VerificationError::NoPointEvaluation)));
+    }
+
+    #[test]
+    fn test_verify_checks_composition_at_oods() {
+        let (id_hash, secret) = (M31::new(11), M31::new(22));
+        let air = MurklHashAir::new(&pq_commitment(id_hash, secret), &pq_nullifier(secret, 3));
+        let config = ProverConfig::fast();
+        let prover = Prover::new(config.clone());
+        let verifier = Verifier::new(config);
+        let prove = |secret| {
+            let inputs = air.commitment.iter().chain(&air.nullifier).copied().collect();
+            let public_inputs = PublicInputs::new(inputs, vec![]);
+            prover.prove(&air, &air.generate_trace(id_hash, secret, 3, &[]).unwrap(), public_inputs).unwrap()
+        };
+
+        let proof = prove(secret);
+        assert_eq!(proof.trace_oods_next.len(), proof.trace_oods.len());
+        assert_eq!(verifier.verify(&air, &proof), Ok(()));
+
+        // A secret hashing to neither public value leaves a quotient that
+        // is not the AIR's
+        assert_eq!(verifier.verify(&air, &prove(M31::new(23))), Err(VerificationError::ConstraintMismatch));
+
+        let mut tampered = proof.clone();
+        tampered.composition_oods = tampered.composition_oods + QM31::ONE;
+        assert_eq!(verifier.verify(&air, &tampered), Err(VerificationError::ConstraintMismatch));
+        let mut tampered = proof;
+        tampered.trace_oods_next[ROUND_CONSTANTS] = tampered.trace_oods_next[ROUND_CONSTANTS] + QM31::ONE;
+        assert_eq!(
+            verifier.verify(&air, &tampered),
+            Err(VerificationError::FixedColumnMismatch(ROUND_CONSTANTS))
+        );
     }
 }
@@ -3,8 +3,12 @@
 use murkl_prover::circle::{
     CirclePoint, CIRCLE_GENERATOR, CIRCLE_ORDER, LOG_CIRCLE_ORDER,
     subgroup_generator, compute_domain, compute_twiddles, Coset, CircleDomain,
+    barycentric_eval, barycentric_weights, cfft, evaluate_on_domain, icfft, FftTwiddles,
+    SecureCirclePoint,
 };
-use murkl_prover::m31::M31;
+use murkl_prover::m31::{M31, M31_PRIME};
+use murkl_prover::qm31::QM31;
+use proptest::prelude::*;
 
 #[test]
 fn test_identity_on_circle() {
@@ -392,3 +396,83 @@ fn test_default() {
     assert_eq!(default.x.value(), CirclePoint::IDENTITY.x.value());
     assert_eq!(default.y.value(), CirclePoint::IDENTITY.y.value());
 }
+
+/// Σ c_i · y^{i₀} · x^{i₁} · π(x)^{i₂} · …, one basis term at a time
+fn naive_eval(coeffs: &[M31], x: QM31, y: QM31) -> QM31 {
+    let log_size = coeffs.len().trailing_zeros() as usize;
+    let mut factors = vec![y];
+    let mut px = x;
+    for _ in 1..log_size {
+        factors.push(px);
+        px = px.square() + px.square() - QM31::ONE;
+    }
+    let mut sum = QM31::ZERO;
+    for (i, &c) in coeffs.iter().enumerate() {
+        let mut term = QM31::from(c);
+        for (k, &factor) in factors.iter().enumerate().take(log_size) {
+            if (i >> k) & 1 == 1 {
+                term = term * factor;
+            }
+        }
+        sum = sum + term;
+    }
+    sum
+}
+
+fn field_elements(len: usize) -> impl Strategy<Value = Vec<M31>> {
+    prop::collection::vec((0..M31_PRIME).prop_map(M31::new), len)
+}
+
+proptest! {
+    #[test]
+    fn prop_icfft_matches_lagrange_interpolation(
+        log_size in 0u32..6,
+        values in field_elements(32),
+        t in any::<[u32; 4]>(),
+    ) {
+        let n = 1usize << log_size;
+        let evals = &values[..n];
+        let coeffs = icfft(evals, &FftTwiddles::new(log_size));
+
+        let coset = Coset::canonic(log_size);
+        for (i, p) in coset.iter().enumerate() {
+            prop_assert_eq!(naive_eval(&coeffs, QM31::from(p.x), QM31::from(p.y)), QM31::from(evals[i]));
+        }
+
+        let t = QM31::from_u32(t[0] % M31_PRIME, t[1] % M31_PRIME, t[2] % M31_PRIME, t[3] % M31_PRIME);
+        let point = SecureCirclePoint::try_from_t(t);
+        prop_assume!(point.is_some());
+        let point = point.unwrap();
+        let lagrange = barycentric_eval(evals, &barycentric_weights(&coset, &point));
+        prop_assert_eq!(naive_eval(&coeffs, point.x, point.y), lagrange);
+    }
+
+    #[test]
+    fn prop_cfft_matches_naive_evaluation(log_size in 0u32..6, values in field_elements(32)) {
+        let n = 1usize << log_size;
+        let coeffs = &values[..n];
+        let twiddles = FftTwiddles::new(log_size);
+        let evals = cfft(coeffs, &twiddles);
+
+        for (i, p) in Coset::canonic(log_size).iter().enumerate() {
+            prop_assert_eq!(QM31::from(evals[i]), naive_eval(coeffs, QM31::from(p.x), QM31::from(p.y)));
+        }
+        prop_assert_eq!(icfft(&evals, &twiddles), coeffs.to_vec());
+    }
+
+    #[test]
+    fn prop_evaluate_on_domain_matches_naive_evaluation(
+        log_size in 0u32..5,
+        log_blowup in 0u32..3,
+        values in field_elements(16),
+    ) {
+        let coeffs = &values[..1 << log_size];
+        let log_domain = log_size + log_blowup;
+        for coset in [Coset::canonic(log_domain), Coset::commitment(log_domain)] {
+            let evals = evaluate_on_domain(coeffs, &coset);
+            for (i, p) in coset.iter().enumerate() {
+                prop_assert_eq!(QM31::from(evals[i]), naive_eval(coeffs, QM31::from(p.x), QM31::from(p.y)));
+            }
+        }
+    }
+}
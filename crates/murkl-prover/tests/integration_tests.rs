@@ -350,6 +350,11 @@ fn test_proof_serialization_roundtrip() {
     // Basic checks on serialized data
     assert!(!bytes.is_empty());
     assert!(bytes.len() > 32); // At least one hash
+
+    // Every field survives a round trip
+    let decoded = Proof::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.query_proofs.len(), proof.query_proofs.len());
 }
 
 #[test]
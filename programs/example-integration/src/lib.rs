@@ -14,17 +14,19 @@
 //!
 //! 1. User generates a commitment off-chain (WASM or CLI)
 //! 2. User generates a STARK proof proving knowledge of preimage
-//! 3. User submits proof to this program
-//! 4. Program CPI into stark-verifier to verify the proof
+//! 3. User uploads the proof to a stark-verifier proof buffer
+//! 4. Program checks the finalized buffer, or verifies it via CPI
+//!    (murkl-verifier-cpi)
 //! 5. If valid, program takes some action (mint, unlock, etc.)
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Mint, MintTo};
+use murkl_verifier_cpi::{PublicInputs, StarkVerifier, VerifyFromBuffer};
 
 declare_id!("ExmpLe1111111111111111111111111111111111111");
 
 /// STARK Verifier program ID
-pub const STARK_VERIFIER_ID: Pubkey = pubkey!("StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw");
+pub use murkl_verifier_cpi::STARK_VERIFIER_ID;
 
 // ============================================================================
 // Program
@@ -63,16 +65,8 @@ pub mod example_integration {
     ) -> Result<()> {
         msg!("Checking stark-verifier proof buffer...");
         
-        // Verify the proof buffer from stark-verifier is finalized
-        let verifier_buffer = &ctx.accounts.verifier_buffer;
-        
-        // Parse verifier buffer data (skip 8-byte discriminator)
-        let data = verifier_buffer.try_borrow_data()?;
-        require!(data.len() >= 73, ExampleError::InvalidVerifierBuffer);
-        
-        // ProofBuffer layout: owner(32) + size(4) + expected_size(4) + finalized(1) + data...
-        let finalized = data[8 + 32 + 4 + 4] == 1;
-        require!(finalized, ExampleError::ProofNotVerified);
+        // The buffer must be finalized, for this commitment and nullifier
+        check_finalized(&ctx.accounts.verifier_buffer, &commitment, &nullifier)?;
         
         msg!("✅ Proof buffer verified!");
         
@@ -99,44 +93,41 @@ pub mod example_integration {
         Ok(())
     }
 
-    /// Alternative: Direct CPI into stark-verifier::verify_proof
-    ///
-    /// This shows how to call the verifier instruction directly via CPI
-    /// instead of using the proof buffer flow.
+    /// Alternative: verify the buffer inside this instruction via CPI
     ///
-    /// Note: For large proofs, use the buffer flow above. Direct CPI
-    /// is limited by transaction size (~1KB for proof data).
-    pub fn verify_direct_cpi(
-        ctx: Context<VerifyDirectCpi>,
-        proof_data: Vec<u8>,
-        public_inputs: Vec<u8>,
+    /// Calls stark-verifier's `verify_from_buffer` through
+    /// murkl-verifier-cpi, which reads the typed result from return data.
+    /// The buffer only needs to be fully uploaded, not finalized, and the
+    /// public inputs are this instruction's own.
+    pub fn verify_buffer_cpi(
+        ctx: Context<VerifyBufferCpi>,
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
     ) -> Result<()> {
-        msg!("Calling stark-verifier via CPI ({} bytes proof)...", proof_data.len());
-        
-        // Build the CPI instruction
-        let ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: STARK_VERIFIER_ID,
-            accounts: vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    ctx.accounts.payer.key(),
-                    true,
-                ),
-            ],
-            // Discriminator for verify_proof + serialized args
-            data: build_verify_proof_data(&proof_data, &public_inputs),
-        };
+        msg!("Calling stark-verifier via CPI...");
         
-        // Execute CPI
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[ctx.accounts.payer.to_account_info()],
+        let result = murkl_verifier_cpi::verify_from_buffer(
+            CpiContext::new(
+                ctx.accounts.verifier_program.to_account_info(),
+                VerifyFromBuffer {
+                    proof_buffer: ctx.accounts.proof_buffer.to_account_info(),
+                    verifier_config: ctx.accounts.verifier_config.to_account_info(),
+                },
+            ),
+            &PublicInputs { commitment, nullifier, merkle_root, recipient },
         )?;
+        if let Some(report) = &result.report {
+            msg!("Proof rejected at {}", report);
+        }
+        result.require_verified()?;
         
-        msg!("✅ Direct CPI verification succeeded!");
+        msg!("✅ CPI verification succeeded!");
         
         emit!(DirectVerificationEvent {
             verifier: ctx.accounts.payer.key(),
-            proof_size: proof_data.len() as u32,
+            compute_units: result.compute_units,
             timestamp: Clock::get()?.unix_timestamp,
         });
         
@@ -158,12 +149,8 @@ pub mod example_integration {
         let nullifier_record = &ctx.accounts.nullifier_record;
         require!(!nullifier_record.used, ExampleError::NullifierUsed);
         
-        // Verify the proof buffer is finalized
-        let verifier_buffer = &ctx.accounts.verifier_buffer;
-        let data = verifier_buffer.try_borrow_data()?;
-        require!(data.len() >= 73, ExampleError::InvalidVerifierBuffer);
-        let finalized = data[8 + 32 + 4 + 4] == 1;
-        require!(finalized, ExampleError::ProofNotVerified);
+        // Verify the proof buffer is finalized for this proof
+        check_finalized(&ctx.accounts.verifier_buffer, &commitment, &nullifier)?;
         
         // Mark nullifier as used
         let nullifier_record = &mut ctx.accounts.nullifier_record;
@@ -204,22 +191,18 @@ pub mod example_integration {
 // Helpers
 // ============================================================================
 
-/// Build the verify_proof instruction data
-fn build_verify_proof_data(proof_data: &[u8], public_inputs: &[u8]) -> Vec<u8> {
-    // Anchor discriminator for "verify_proof" = first 8 bytes of sha256("global:verify_proof")
-    let discriminator: [u8; 8] = [0x40, 0x9d, 0x08, 0x6c, 0x7a, 0x98, 0x9b, 0x8a];
-    
-    let mut data = Vec::with_capacity(8 + 4 + proof_data.len() + 4 + public_inputs.len());
-    data.extend_from_slice(&discriminator);
-    
-    // Vec<u8> is serialized as u32 length + bytes
-    data.extend_from_slice(&(proof_data.len() as u32).to_le_bytes());
-    data.extend_from_slice(proof_data);
-    
-    data.extend_from_slice(&(public_inputs.len() as u32).to_le_bytes());
-    data.extend_from_slice(public_inputs);
-    
-    data
+/// Require `buffer` to be a finalized stark-verifier buffer whose proof was
+/// verified for `commitment` and `nullifier`
+///
+/// The finalized flag alone would let any verified proof stand in for
+/// another.
+fn check_finalized(buffer: &AccountInfo, commitment: &[u8; 32], nullifier: &[u8; 32]) -> Result<()> {
+    let proof = murkl_verifier_cpi::finalized_proof(buffer)?.ok_or(ExampleError::ProofNotVerified)?;
+    require!(
+        proof.inputs.commitment == *commitment && proof.inputs.nullifier == *nullifier,
+        ExampleError::PublicInputsMismatch
+    );
+    Ok(())
 }
 
 // ============================================================================
@@ -227,17 +210,15 @@ fn build_verify_proof_data(proof_data: &[u8], public_inputs: &[u8]) -> Vec<u8> {
 // ============================================================================
 
 #[account]
+#[derive(InitSpace)]
 pub struct Config {
     pub authority: Pubkey,
     pub total_verified: u64,
     pub bump: u8,
 }
 
-impl Config {
-    pub const SIZE: usize = 8 + 32 + 8 + 1;
-}
-
 #[account]
+#[derive(InitSpace)]
 pub struct VerificationRecord {
     pub verifier: Pubkey,
     pub commitment: [u8; 32],
@@ -245,21 +226,14 @@ pub struct VerificationRecord {
     pub verified_at: i64,
 }
 
-impl VerificationRecord {
-    pub const SIZE: usize = 8 + 32 + 32 + 32 + 8;
-}
-
 #[account]
+#[derive(InitSpace)]
 pub struct NullifierRecord {
     pub nullifier: [u8; 32],
     pub used: bool,
     pub used_at: i64,
 }
 
-impl NullifierRecord {
-    pub const SIZE: usize = 8 + 32 + 1 + 8;
-}
-
 // ============================================================================
 // Contexts
 // ============================================================================
@@ -269,7 +243,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
-        space = Config::SIZE,
+        space = 8 + Config::INIT_SPACE,
         seeds = [b"config"],
         bump
     )]
@@ -296,7 +270,7 @@ pub struct VerifyAndRecord<'info> {
     #[account(
         init,
         payer = verifier,
-        space = VerificationRecord::SIZE,
+        space = 8 + VerificationRecord::INIT_SPACE,
         seeds = [b"record", nullifier.as_ref()],
         bump
     )]
@@ -309,13 +283,16 @@ pub struct VerifyAndRecord<'info> {
 }
 
 #[derive(Accounts)]
-pub struct VerifyDirectCpi<'info> {
-    #[account(mut)]
+pub struct VerifyBufferCpi<'info> {
     pub payer: Signer<'info>,
     
-    /// CHECK: stark-verifier program
-    #[account(address = STARK_VERIFIER_ID)]
-    pub verifier_program: UncheckedAccount<'info>,
+    /// CHECK: stark-verifier's proof buffer; the verifier checks it owns it
+    pub proof_buffer: UncheckedAccount<'info>,
+    
+    /// CHECK: stark-verifier's VerifierConfigAccount; the verifier checks its seeds
+    pub verifier_config: UncheckedAccount<'info>,
+    
+    pub verifier_program: Program<'info, StarkVerifier>,
 }
 
 #[derive(Accounts)]
@@ -337,7 +314,7 @@ pub struct VerifyAndMint<'info> {
     #[account(
         init,
         payer = payer,
-        space = NullifierRecord::SIZE,
+        space = 8 + NullifierRecord::INIT_SPACE,
         seeds = [b"nullifier", nullifier.as_ref()],
         bump
     )]
@@ -377,7 +354,7 @@ pub struct VerificationEvent {
 #[event]
 pub struct DirectVerificationEvent {
     pub verifier: Pubkey,
-    pub proof_size: u32,
+    pub compute_units: u64,
     pub timestamp: i64,
 }
 
@@ -405,4 +382,6 @@ pub enum ExampleError {
     ProofNotVerified,
     #[msg("Invalid verifier buffer")]
     InvalidVerifierBuffer,
+    #[msg("Proof was verified for other public inputs")]
+    PublicInputsMismatch,
 }
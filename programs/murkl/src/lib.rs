@@ -9,8 +9,13 @@
 //! - Merkle root verified against pool state
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::keccak;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use murkl_proof_format::SecurityLevel;
+
+mod poseidon2;
 
 declare_id!("muRkDGaY4yCc6rEYWhmJAnQ1abdCbUJNCr4L1Cmd1UF");
 
@@ -20,6 +25,63 @@ pub const STARK_VERIFIER_ID: Pubkey = pubkey!("StArKSLbAn43UCcujFMc5gKc8rY2BVfSb
 /// Global config seed
 pub const CONFIG_SEED: &[u8] = b"config";
 
+/// Pool registry seed
+pub const REGISTRY_SEED: &[u8] = b"pool-registry";
+
+/// Registry entry seed (followed by the pool address)
+pub const REGISTRY_ENTRY_SEED: &[u8] = b"registry-entry";
+
+/// `token_mint` of the native-SOL pool: its PDA is ["pool", SOL_POOL_MINT]
+pub const SOL_POOL_MINT: Pubkey = anchor_lang::system_program::ID;
+
+/// Claim receipt seed (followed by the pool and the recipient wallet)
+pub const CLAIM_RECEIPT_SEED: &[u8] = b"claim-receipt";
+
+/// Nullifier shard seed (followed by the pool and the shard index, u16 LE)
+pub const NULLIFIER_SHARD_SEED: &[u8] = b"nullifier-shard";
+
+/// Nullifier bloom filter seed (followed by the pool)
+pub const NULLIFIER_BLOOM_SEED: &[u8] = b"nullifier-bloom";
+
+/// Deposit registration seed (followed by the pool and the registration hash)
+pub const DEPOSIT_REGISTRATION_SEED: &[u8] = b"deposit-registration";
+
+/// Membership record seed (followed by the pool, the scope and the nullifier)
+pub const MEMBERSHIP_SEED: &[u8] = b"membership";
+
+/// Admin log seed (followed by the pool)
+pub const ADMIN_LOG_SEED: &[u8] = b"admin-log";
+
+/// Scheduled vault rotation seed (followed by the pool)
+pub const VAULT_ROTATION_SEED: &[u8] = b"vault-rotation";
+
+/// Wait between scheduling a vault rotation and carrying it out (two days)
+pub const VAULT_ROTATION_DELAY_SECS: i64 = 2 * 24 * 60 * 60;
+
+/// Mints a multi-mint pool can accept besides its `token_mint`
+pub const MAX_ACCEPTED_MINTS: usize = 4;
+
+/// Longest deposit reveal window a pool can set (about a day of slots)
+pub const MAX_DEPOSIT_REVEAL_SLOTS: u64 = 216_000;
+
+/// Most leading nullifier bits a pool can shard on (shard index is a u16)
+pub const MAX_NULLIFIER_SHARD_BITS: u8 = 16;
+
+/// Bytes ahead of a shard's sorted nullifiers: discriminator + `NullifierShard`
+pub const NULLIFIER_SHARD_HEADER: usize = 8 + NullifierShard::INIT_SPACE;
+
+/// Bytes of a `NullifierBloom` filter: 2^16 bits
+pub const NULLIFIER_BLOOM_BYTES: usize = 1 << 13;
+
+/// Filter bits each claimed nullifier sets
+pub const NULLIFIER_BLOOM_HASHES: usize = 4;
+
+/// Bytes ahead of the filter: discriminator + `NullifierBloom`
+pub const NULLIFIER_BLOOM_HEADER: usize = 8 + NullifierBloom::INIT_SPACE;
+
+// `init` can create at most 10 KiB in one instruction
+const _: () = assert!(NULLIFIER_BLOOM_HEADER + NULLIFIER_BLOOM_BYTES <= 10_240);
+
 // ============================================================================
 // Verifier Buffer Layout (must match stark-verifier)
 // ============================================================================
@@ -32,7 +94,17 @@ const VERIFIER_OFFSET_COMMITMENT: usize = 41;
 const VERIFIER_OFFSET_NULLIFIER: usize = 73;
 const VERIFIER_OFFSET_MERKLE_ROOT: usize = 105;
 const VERIFIER_OFFSET_RECIPIENT: usize = 137;
-const VERIFIER_HEADER_SIZE: usize = 169;
+const VERIFIER_OFFSET_FINALIZED_SLOT: usize = 201;
+const VERIFIER_HEADER_SIZE: usize = 449;
+
+/// Seed of stark-verifier's `VerifierParams` PDA
+const VERIFIER_PARAMS_SEED: &[u8] = b"verifier-params";
+/// `VerifierParams::params_hash`, right after the Anchor discriminator
+const VERIFIER_PARAMS_OFFSET_HASH: usize = 8;
+/// `VerifierParams::log_blowup`, `min_queries` (u32 LE) and `hash_kinds`
+const VERIFIER_PARAMS_OFFSET_LOG_BLOWUP: usize = 44;
+const VERIFIER_PARAMS_OFFSET_MIN_QUERIES: usize = 56;
+const VERIFIER_PARAMS_OFFSET_HASH_KINDS: usize = 64;
 
 // ============================================================================
 // Constants
@@ -48,58 +120,605 @@ const MIN_DEPOSIT_AMOUNT: u64 = 1;
 ///
 /// We align with `crates/murkl-prover/src/merkle.rs::TREE_DEPTH`.
 ///
-/// NOTE: On-chain we store only an incremental frontier; proofs bind to `pool.merkle_root`.
+/// NOTE: On-chain we store only an incremental frontier; proofs bind to
+/// `pool.merkle_root` or one of `pool.root_history`.
 const MERKLE_DEPTH: usize = 20;
 
+/// Capacity of `Pool::root_history`, the most roots before the current one
+/// a pool can be configured to keep accepting
+pub const ROOT_HISTORY_SIZE: usize = 30;
+
+/// Days of deposit history `PoolStats` keeps for dating deposits at claim time
+pub const STATS_DAYS: usize = 32;
+
+/// Upper bounds (days, exclusive) of the deposit-age buckets in
+/// `PoolStats::claims_by_age`; the last bucket is open-ended
+pub const AGE_BUCKET_DAYS: [u64; 3] = [1, 7, 30];
+pub const AGE_BUCKETS: usize = AGE_BUCKET_DAYS.len() + 1;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Most epochs a subscription deposit can be split into (one bit each in
+/// `Subscription::claimed_epochs`)
+pub const MAX_SUBSCRIPTION_EPOCHS: u8 = 64;
+
+/// Bytes of a subscription nullifier holding its epoch (u32 LE), matching
+/// `murkl_prover::pq_epoch_nullifier`
+const EPOCH_NULLIFIER_TAG: core::ops::Range<usize> = 28..32;
+
+/// Anchor discriminator of the hook's `check_compliance(recipient: Pubkey, amount: u64)`
+/// (`sha256("global:check_compliance")[..8]`).
+const COMPLIANCE_HOOK_DISCRIMINATOR: [u8; 8] = [233, 217, 116, 46, 226, 224, 62, 42];
+
+/// Anchor discriminator of stark-verifier's `close_proof_buffer`
+/// (`sha256("global:close_proof_buffer")[..8]`).
+const CLOSE_PROOF_BUFFER_DISCRIMINATOR: [u8; 8] = [130, 150, 6, 35, 193, 34, 243, 87];
+
+/// Pool tree parent: one Poseidon2 permutation, matching
+/// `murkl_prover::merkle::pool_node`, so a claim proof can prove the path
 #[inline]
 fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-    let mut buf = [0u8; 64];
-    buf[..32].copy_from_slice(left);
-    buf[32..].copy_from_slice(right);
-    keccak::hash(&buf).0
+    poseidon2::compress(left, right)
 }
 
+/// Limbs of the empty subtree roots, leaf level first: each is
+/// [`hash_pair`] of two of the one before, pinned by a test
+const EMPTY_SUBTREES: [[u32; 8]; MERKLE_DEPTH + 1] = [
+    [0; 8],
+    [0x02b2cc21, 0x1147aaf5, 0x543849b5, 0x5fc0f825, 0x116c20cc, 0x48c2ff81, 0x1b0f31f3, 0x5a8ba5d3],
+    [0x673809b9, 0x1ea26f83, 0x144af2b8, 0x18188b74, 0x5ef4561c, 0x414d350b, 0x432b345f, 0x19823717],
+    [0x48ccdbe3, 0x53feda37, 0x70136081, 0x1824a62b, 0x0175c27d, 0x4fc1a08e, 0x724b8389, 0x2c89d1d4],
+    [0x37f97b1e, 0x627c6114, 0x19382743, 0x0a2d4970, 0x77c86595, 0x6efec82a, 0x7e2c4628, 0x3f937ec3],
+    [0x0a5d2b1b, 0x6db7d1c5, 0x4f607e27, 0x13db28b3, 0x13953b5f, 0x0110d5ee, 0x53fb21bc, 0x0f815c47],
+    [0x2ad1fc85, 0x04eb852f, 0x508372dc, 0x3babd3b0, 0x1f247835, 0x62bf5db6, 0x4909383f, 0x70deda75],
+    [0x1819058b, 0x3fa78cb8, 0x33afcf6a, 0x0996fd82, 0x709718f6, 0x6d226f69, 0x43ce8f4a, 0x10286478],
+    [0x41a2d706, 0x36d8c02c, 0x00696b1b, 0x19103fd6, 0x4d1ebc35, 0x0093ae5a, 0x3f6742c1, 0x4afa3386],
+    [0x1a3e2682, 0x69eca262, 0x6067a3e5, 0x7853e2a5, 0x24475e3d, 0x5b887400, 0x3c1b0a1b, 0x5b4ca110],
+    [0x22ff9cb9, 0x1d1d4a60, 0x7f83b555, 0x1de419ad, 0x60c49608, 0x1cbcafe5, 0x5b0cf411, 0x49dc1b69],
+    [0x72fb1fcd, 0x3da983fd, 0x616bcfa2, 0x57f9e869, 0x4b4b75bd, 0x7965c051, 0x3a7fcec2, 0x10135acf],
+    [0x1b8f0346, 0x77b80cfe, 0x00d94551, 0x7d2dc4ca, 0x40015065, 0x49573032, 0x5b8e2aa4, 0x60374120],
+    [0x7c687a1b, 0x6b42ecd9, 0x16f22fee, 0x169fcb29, 0x6b4a0cf9, 0x1dd4d9b4, 0x01de88cc, 0x403b0b8c],
+    [0x7735e933, 0x317654a1, 0x6ebfb8b9, 0x4be62f48, 0x07e62fc6, 0x4c3d8ace, 0x7a597d7e, 0x61e78318],
+    [0x5e4a41b1, 0x4174e9e5, 0x40213055, 0x3cdf98f8, 0x1f6e17a3, 0x42438ea9, 0x5900cceb, 0x36e74af6],
+    [0x2ee5dc77, 0x59146640, 0x6afd167b, 0x665da185, 0x11a87142, 0x04011f42, 0x32795007, 0x7304271b],
+    [0x2277c049, 0x68f3263b, 0x0a95957c, 0x052d038e, 0x6378fb5d, 0x067555a5, 0x66d0868f, 0x127add32],
+    [0x3de7ce4f, 0x1a8573fa, 0x0c201d69, 0x314415c8, 0x35b31efc, 0x2bcf7b6f, 0x06f5aab8, 0x7832a583],
+    [0x65292149, 0x4aa5fae3, 0x54188e41, 0x717e8c13, 0x55175c1f, 0x618484bd, 0x09d97317, 0x221dcbd7],
+    [0x3ce83654, 0x1ce5918a, 0x6abde3cf, 0x2e9ef328, 0x1c13e1b9, 0x6ff4494d, 0x3b97fb41, 0x12082cea],
+];
+
 #[inline]
 fn empty_hashes() -> [[u8; 32]; MERKLE_DEPTH + 1] {
-    let mut e = [[0u8; 32]; MERKLE_DEPTH + 1];
-    for i in 1..=MERKLE_DEPTH {
-        e[i] = hash_pair(&e[i - 1], &e[i - 1]);
-    }
-    e
+    EMPTY_SUBTREES.map(|limbs| {
+        let mut hash = [0u8; 32];
+        for (bytes, limb) in hash.chunks_exact_mut(4).zip(limbs) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        hash
+    })
 }
 
-fn merkle_root(branch: &[[u8; 32]; MERKLE_DEPTH], leaf_count: u64) -> [u8; 32] {
+/// Append `leaf` at position `leaf_count` and return the new root.
+///
+/// One pass up the tree: a left child is cached as the frontier node for its
+/// level and paired with that level's zero hash, a right child with the
+/// cached frontier node.
+fn merkle_append(branch: &mut [[u8; 32]; MERKLE_DEPTH], leaf_count: u64, leaf: &[u8; 32]) -> [u8; 32] {
     let empties = empty_hashes();
-    let mut acc = [0u8; 32];
-    let mut idx = leaf_count as usize;
+    let mut node = *leaf;
+    let mut idx = leaf_count;
 
     for level in 0..MERKLE_DEPTH {
-        if idx & 1 == 1 {
-            acc = hash_pair(&branch[level], &acc);
+        node = if idx & 1 == 0 {
+            branch[level] = node;
+            hash_pair(&node, &empties[level])
         } else {
-            acc = hash_pair(&acc, &empties[level]);
-        }
+            hash_pair(&branch[level], &node)
+        };
         idx >>= 1;
     }
 
-    acc
+    node
 }
 
-fn merkle_append(branch: &mut [[u8; 32]; MERKLE_DEPTH], leaf_count: u64, leaf: &[u8; 32]) -> [u8; 32] {
+/// Root a pool tree path from `leaf` at `index` leads to.
+///
+/// `path` holds the sibling at each level, leaf level first, and must be
+/// exactly `MERKLE_DEPTH` long (empty subtrees use the zero-leaf hashes);
+/// `None` otherwise, or if the index is outside the tree.
+fn merkle_path_root(leaf: &[u8; 32], index: u64, path: &[[u8; 32]]) -> Option<[u8; 32]> {
+    if path.len() != MERKLE_DEPTH || index >> MERKLE_DEPTH != 0 {
+        return None;
+    }
     let mut node = *leaf;
-    let mut idx = leaf_count as usize;
-
-    for level in 0..MERKLE_DEPTH {
-        if idx & 1 == 0 {
-            branch[level] = node;
-            break;
+    let mut idx = index;
+    for sibling in path {
+        node = if idx & 1 == 0 {
+            hash_pair(&node, sibling)
         } else {
-            node = hash_pair(&branch[level], &node);
-            idx >>= 1;
+            hash_pair(sibling, &node)
+        };
+        idx >>= 1;
+    }
+    Some(node)
+}
+
+/// Commitment for (id_hash, secret), matching `murkl_prover::pq_commitment`.
+fn path_commitment(id_hash: u32, secret: u32) -> [u8; 32] {
+    poseidon2::hash_tagged(poseidon2::COMMITMENT_TAG, [id_hash, secret, 0])
+}
+
+/// Nullifier for (secret, leaf_index), matching `murkl_prover::pq_nullifier`:
+/// the epoch 0 nullifier, whose tag is zero.
+fn path_nullifier(secret: u32, leaf_index: u32) -> [u8; 32] {
+    let mut nullifier = poseidon2::hash_tagged(poseidon2::NULLIFIER_TAG, [leaf_index, secret, 0]);
+    nullifier[EPOCH_NULLIFIER_TAG].fill(0);
+    nullifier
+}
+
+/// Epoch a subscription nullifier claims. The proof binds the nullifier, so
+/// the epoch in its tag is as proven as the rest of it.
+fn nullifier_epoch(nullifier: &[u8; 32]) -> u32 {
+    let mut epoch = [0u8; 4];
+    epoch.copy_from_slice(&nullifier[EPOCH_NULLIFIER_TAG]);
+    u32::from_le_bytes(epoch)
+}
+
+/// Move a depositor's tokens into the pool vault.
+fn transfer_to_vault<'info>(
+    from: &Account<'info, TokenAccount>,
+    vault: &Account<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            token_program,
+            Transfer {
+                from: from.to_account_info(),
+                to: vault.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Pay `amount` lamports out of a SOL pool's vault, signed by the vault PDA.
+fn transfer_from_sol_vault<'info>(
+    pool_key: &Pubkey,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    to: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let vault_seeds = &[b"vault".as_ref(), pool_key.as_ref(), &[vault_bump]];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program,
+            anchor_lang::system_program::Transfer {
+                from: vault.to_account_info(),
+                to,
+            },
+            &[&vault_seeds[..]],
+        ),
+        amount,
+    )
+}
+
+/// Shard holding `nullifier` in a registry of `2^bits` shards: its leading
+/// `bits` bits, big-endian. `bits` must be in 1..=MAX_NULLIFIER_SHARD_BITS.
+pub fn nullifier_shard_index(nullifier: &[u8; 32], bits: u8) -> u16 {
+    u16::from_be_bytes([nullifier[0], nullifier[1]]) >> (MAX_NULLIFIER_SHARD_BITS - bits)
+}
+
+/// Binary search a shard's sorted 32-byte entries for `nullifier`: `Ok` with
+/// its position if present, `Err` with the position it would take otherwise
+pub fn search_nullifier_shard(entries: &[u8], nullifier: &[u8; 32]) -> std::result::Result<usize, usize> {
+    let (mut lo, mut hi) = (0, entries.len() / 32);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        match entries[mid * 32..mid * 32 + 32].cmp(&nullifier[..]) {
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+            std::cmp::Ordering::Equal => return Ok(mid),
+        }
+    }
+    Err(lo)
+}
+
+/// Filter bits `nullifier` sets in a `NullifierBloom`: the u16 LE words of
+/// its first 8 bytes. Nullifiers are hash outputs, so these need no
+/// rehashing.
+pub fn nullifier_bloom_bits(nullifier: &[u8; 32]) -> [u16; NULLIFIER_BLOOM_HASHES] {
+    core::array::from_fn(|i| u16::from_le_bytes([nullifier[2 * i], nullifier[2 * i + 1]]))
+}
+
+/// Whether the filter `bits` (the [`NULLIFIER_BLOOM_BYTES`] past a
+/// `NullifierBloom` header) may hold `nullifier`. `false` is definite;
+/// `true` may be a false positive.
+pub fn nullifier_bloom_contains(bits: &[u8], nullifier: &[u8; 32]) -> bool {
+    nullifier_bloom_bits(nullifier)
+        .iter()
+        .all(|&bit| bits[bit as usize / 8] & (1 << (bit % 8)) != 0)
+}
+
+fn nullifier_bloom_insert(bits: &mut [u8], nullifier: &[u8; 32]) {
+    for bit in nullifier_bloom_bits(nullifier) {
+        bits[bit as usize / 8] |= 1 << (bit % 8);
+    }
+}
+
+/// Insert `nullifier` into a shard's sorted entries, growing the account by
+/// 32 bytes with `payer` topping up rent. Fails if it is already there.
+///
+/// The entries are searched and shifted in the account data, never
+/// deserialized, so a shard's size is bounded by compute, not heap.
+fn insert_nullifier<'info>(
+    shard: &AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    nullifier: &[u8; 32],
+) -> Result<()> {
+    let len = shard.data_len();
+    let pos = {
+        let data = shard.try_borrow_data()?;
+        search_nullifier_shard(&data[NULLIFIER_SHARD_HEADER..], nullifier)
+            .err()
+            .ok_or(MurklError::NullifierAlreadyUsed)?
+    };
+
+    let new_len = len + 32;
+    let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(shard.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program,
+                anchor_lang::system_program::Transfer { from: payer, to: shard.clone() },
+            ),
+            top_up,
+        )?;
+    }
+    shard.realloc(new_len, false)?;
+
+    let mut data = shard.try_borrow_mut_data()?;
+    let at = NULLIFIER_SHARD_HEADER + pos * 32;
+    data.copy_within(at..len, at + 32);
+    data[at..at + 32].copy_from_slice(nullifier);
+    Ok(())
+}
+
+/// Append `commitment` as the pool's next leaf, counting it in the pool's
+/// stats if it keeps them, and emit a [`DepositEvent`]. Returns the leaf index.
+fn append_leaf(
+    pool: &mut Pool,
+    pool_merkle: &mut PoolMerkle,
+    stats: Option<&mut PoolStats>,
+    commitment: &[u8; 32],
+) -> Result<u64> {
+    let leaf_index = pool.leaf_count;
+    let root = pool_merkle.insert_leaf(leaf_index, commitment)?;
+    pool.push_root(root);
+    match stats {
+        Some(stats) => stats.record_deposit(Clock::get()?.unix_timestamp, leaf_index),
+        None => require!(!pool.stats_enabled, MurklError::PoolStatsMissing),
+    }
+    pool.leaf_count += 1;
+    pool.event_seq = pool.event_seq.checked_add(1).ok_or(MurklError::MathOverflow)?;
+    emit!(DepositEvent {
+        pool: pool_merkle.pool,
+        event_seq: pool.event_seq,
+        leaf_index,
+        commitment: *commitment,
+        merkle_root: root,
+    });
+    Ok(leaf_index)
+}
+
+/// Transfer `amount` out of the pool vault, signed by the pool PDA.
+fn transfer_from_vault<'info>(
+    pool: &Account<'info, Pool>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let pool_seeds = &[
+        b"pool".as_ref(),
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&pool_seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program,
+            Transfer {
+                from: vault.to_account_info(),
+                to,
+                authority: pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// CPI the pool's compliance hook with (recipient, amount).
+///
+/// The hook program must be the first remaining account; the rest are
+/// forwarded read-only so a hook can consult its own denylist state but
+/// cannot touch anything else. Any error from the hook aborts the claim.
+fn invoke_compliance_hook<'info>(
+    hook: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let (hook_program, hook_accounts) = remaining_accounts
+        .split_first()
+        .ok_or(MurklError::ComplianceHookMissing)?;
+    require!(
+        hook_program.key() == *hook && hook_program.executable,
+        MurklError::ComplianceHookMissing
+    );
+
+    let mut data = Vec::with_capacity(8 + 32 + 8);
+    data.extend_from_slice(&COMPLIANCE_HOOK_DISCRIMINATOR);
+    data.extend_from_slice(recipient.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: *hook,
+        accounts: hook_accounts
+            .iter()
+            .map(|a| AccountMeta::new_readonly(a.key(), false))
+            .collect(),
+        data,
+    };
+    invoke(&ix, hook_accounts).map_err(|_| error!(MurklError::ComplianceRejected))
+}
+
+/// CPI stark-verifier's `close_proof_buffer`, refunding the buffer's rent to
+/// `owner`. Both must be writable in the transaction.
+fn close_verifier_buffer<'info>(
+    verifier_program: &AccountInfo<'info>,
+    buffer: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(buffer.is_writable, ErrorCode::AccountNotMutable);
+    let ix = Instruction {
+        program_id: STARK_VERIFIER_ID,
+        accounts: vec![AccountMeta::new(buffer.key(), false), AccountMeta::new(owner.key(), true)],
+        data: CLOSE_PROOF_BUFFER_DISCRIMINATOR.to_vec(),
+    };
+    invoke(&ix, &[buffer.clone(), owner.clone(), verifier_program.clone()])?;
+    Ok(())
+}
+
+/// Whether a proof finalized at `finalized_slot` may still be claimed.
+/// `max_age_slots == 0` disables expiry.
+pub fn proof_within_age(finalized_slot: u64, current_slot: u64, max_age_slots: u64) -> bool {
+    max_age_slots == 0 || current_slot.saturating_sub(finalized_slot) <= max_age_slots
+}
+
+/// What `register_deposit` commits to for a later reveal of `commitment`:
+/// keccak("murkl_deposit_registration_v1" || commitment || salt). The salt
+/// keeps the commitment from being read back from the registration.
+pub fn deposit_registration_hash(commitment: &[u8; 32], salt: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[b"murkl_deposit_registration_v1", commitment, salt]).0
+}
+
+/// One link of a pool's admin log: keccak("murkl_admin_log_v1" || pool ||
+/// prev_hash || action || args_hash || actor || slot LE). `args_hash` is
+/// keccak of the instruction's Borsh-encoded arguments.
+pub fn admin_log_entry_hash(
+    pool: &Pubkey,
+    prev_hash: &[u8; 32],
+    action: AdminLogAction,
+    args_hash: &[u8; 32],
+    actor: &Pubkey,
+    slot: u64,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        b"murkl_admin_log_v1",
+        pool.as_ref(),
+        prev_hash,
+        &[action as u8],
+        args_hash,
+        actor.as_ref(),
+        &slot.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Chain an admin action with its Borsh-encoded `args` into `log`
+fn log_admin_action(log: &mut AdminLog, action: AdminLogAction, args: &[u8], actor: &Pubkey) -> Result<()> {
+    let entry = log.record(action, keccak::hash(args).0, *actor, Clock::get()?.slot)?;
+    emit!(entry);
+    Ok(())
+}
+
+/// Identify the stark-verifier parameters a pool's proofs are generated for:
+/// keccak("murkl_verifier_config_v1" || log_trace_size || log_blowup ||
+/// log_folding_factor || log_final_poly_degree), each u32 LE.
+pub fn verifier_config_hash(
+    log_trace_size: u32,
+    log_blowup: u32,
+    log_folding_factor: u32,
+    log_final_poly_degree: u32,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        b"murkl_verifier_config_v1",
+        &log_trace_size.to_le_bytes(),
+        &log_blowup.to_le_bytes(),
+        &log_folding_factor.to_le_bytes(),
+        &log_final_poly_degree.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Pools pinning `required_verifier_params_hash` only pay out while
+/// stark-verifier's published `VerifierParams` carry that hash, so an upgrade
+/// that weakens the verifier stops claims instead of quietly accepting
+/// weaker proofs. Pools with a `min_security_level` only pay out while the
+/// published parameters guarantee that level, whatever their hash.
+fn check_verifier_params(pool: &Pool, verifier_params: Option<&AccountInfo>) -> Result<()> {
+    let (required, min_level) = (pool.config.required_verifier_params_hash, pool.min_security_level);
+    if required.is_none() && min_level.is_none() {
+        return Ok(());
+    }
+    let params = verifier_params.ok_or(MurklError::VerifierParamsMissing)?;
+    let (expected, _) = Pubkey::find_program_address(&[VERIFIER_PARAMS_SEED], &STARK_VERIFIER_ID);
+    require!(
+        params.key() == expected && params.owner == &STARK_VERIFIER_ID,
+        MurklError::VerifierParamsMissing
+    );
+    let data = params.try_borrow_data()?;
+    if let Some(required) = required {
+        require!(
+            data.get(VERIFIER_PARAMS_OFFSET_HASH..VERIFIER_PARAMS_OFFSET_HASH + 32) == Some(&required[..]),
+            MurklError::VerifierParamsMismatch
+        );
+    }
+    if let Some(min_level) = min_level {
+        let u32_at = |offset: usize| {
+            data.get(offset..offset + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        let level = match (
+            u32_at(VERIFIER_PARAMS_OFFSET_MIN_QUERIES),
+            u32_at(VERIFIER_PARAMS_OFFSET_LOG_BLOWUP),
+            data.get(VERIFIER_PARAMS_OFFSET_HASH_KINDS),
+        ) {
+            (Some(min_queries), Some(log_blowup), Some(&hash_kinds)) => {
+                SecurityLevel::guaranteed(min_queries as usize, log_blowup, hash_kinds)
+            }
+            _ => None,
+        };
+        require!(
+            level.is_some_and(|level| level as u8 >= min_level),
+            MurklError::VerifierBelowSecurityLevel
+        );
+    }
+    Ok(())
+}
+
+/// Checks a STARK claim makes of its stark-verifier buffer: finalized for
+/// `relayer` within the pool's proof age, over exactly this claim's
+/// commitment, nullifier, a root the pool still accepts and recipient token
+/// account.
+fn check_verifier_buffer(
+    verifier_buffer: &AccountInfo,
+    relayer: &Pubkey,
+    pool: &Pool,
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    recipient_token: &Pubkey,
+) -> Result<()> {
+    let data = verifier_buffer.try_borrow_data()?;
+    
+    // Check buffer size
+    require!(data.len() >= VERIFIER_HEADER_SIZE, MurklError::InvalidVerifierBuffer);
+
+    // Enforce that the buffer was initialized for (and finalized by) this relayer.
+    // Without this, anyone can front-run a claim using another relayer's finalized buffer
+    // and steal the relayer fee by simply setting their own `relayer_token`.
+    let buffer_owner = Pubkey::try_from(&data[VERIFIER_OFFSET_OWNER..VERIFIER_OFFSET_OWNER + 32])
+        .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+    require!(buffer_owner == *relayer, MurklError::Unauthorized);
+    
+    // Check finalized flag
+    let finalized = data[VERIFIER_OFFSET_FINALIZED] == 1;
+    require!(finalized, MurklError::ProofNotVerified);
+
+    // Old proofs expire; the owner can restamp via reverify_proof_buffer
+    let finalized_slot = u64::from_le_bytes(
+        data[VERIFIER_OFFSET_FINALIZED_SLOT..VERIFIER_OFFSET_FINALIZED_SLOT + 8]
+            .try_into()
+            .map_err(|_| MurklError::InvalidVerifierBuffer)?,
+    );
+    require!(
+        proof_within_age(finalized_slot, Clock::get()?.slot, pool.max_proof_age_slots),
+        MurklError::ProofExpired
+    );
+    
+    // Extract verified public inputs from buffer
+    let buffer_commitment: [u8; 32] = data[VERIFIER_OFFSET_COMMITMENT..VERIFIER_OFFSET_COMMITMENT + 32]
+        .try_into()
+        .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+    let buffer_nullifier: [u8; 32] = data[VERIFIER_OFFSET_NULLIFIER..VERIFIER_OFFSET_NULLIFIER + 32]
+        .try_into()
+        .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+    let buffer_merkle_root: [u8; 32] = data[VERIFIER_OFFSET_MERKLE_ROOT..VERIFIER_OFFSET_MERKLE_ROOT + 32]
+        .try_into()
+        .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+
+    let buffer_recipient: [u8; 32] = data[VERIFIER_OFFSET_RECIPIENT..VERIFIER_OFFSET_RECIPIENT + 32]
+        .try_into()
+        .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+    
+    // Verify commitment matches deposit
+    require!(
+        buffer_commitment == *commitment,
+        MurklError::CommitmentMismatch
+    );
+    
+    // Verify nullifier argument matches buffer (prevents tampering with PDA seed)
+    require!(
+        buffer_nullifier == *nullifier,
+        MurklError::NullifierMismatch
+    );
+    
+    // Verify merkle root is one of this pool's recent states
+    require!(
+        pool.is_known_root(&buffer_merkle_root),
+        MurklError::MerkleRootMismatch
+    );
+
+    // Verify recipient ATA is bound into the proof (prevents recipient substitution)
+    require!(
+        buffer_recipient == recipient_token.to_bytes(),
+        MurklError::InvalidVerifierBuffer
+    );
+    Ok(())
+}
+
+/// Update a pool's stats for a claim, if the pool tracks them
+fn record_claim_stats(pool: &Pool, stats: Option<&mut PoolStats>, leaf_index: u64, now: i64) -> Result<()> {
+    match stats {
+        Some(stats) => stats.record_claim(now, leaf_index, pool.leaf_count),
+        None => require!(!pool.stats_enabled, MurklError::PoolStatsMissing),
+    }
+    Ok(())
+}
+
+/// Add a claimed nullifier to the pool's bloom filter, if it keeps one
+fn record_claim_bloom(pool: &Pool, bloom: Option<&mut Account<'_, NullifierBloom>>, nullifier: &[u8; 32]) -> Result<()> {
+    match bloom {
+        Some(bloom) => {
+            let info = bloom.to_account_info();
+            let mut data = info.try_borrow_mut_data()?;
+            nullifier_bloom_insert(&mut data[NULLIFIER_BLOOM_HEADER..], nullifier);
+            bloom.inserted = bloom.inserted.saturating_add(1);
         }
+        None => require!(!pool.nullifier_bloom_enabled, MurklError::NullifierBloomMissing),
     }
+    Ok(())
+}
 
-    merkle_root(branch, leaf_count + 1)
+/// Number the next event for `pool` and emit the claim as a [`ClaimEvent`],
+/// or a [`MinimalClaimEvent`] if the pool opted out of claim details
+fn emit_claim_event(pool: &mut Pool, pool_key: Pubkey, nullifier: [u8; 32], amount: u64, relayer_fee: u64) -> Result<()> {
+    pool.event_seq = pool.event_seq.checked_add(1).ok_or(MurklError::MathOverflow)?;
+    if pool.minimal_events {
+        emit!(MinimalClaimEvent { pool: pool_key, event_seq: pool.event_seq });
+    } else {
+        emit!(ClaimEvent { pool: pool_key, event_seq: pool.event_seq, nullifier, amount, relayer_fee });
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -120,6 +739,44 @@ pub mod murkl {
         Ok(())
     }
 
+    /// Create the pool registry (config admin, once)
+    pub fn initialize_registry(ctx: Context<InitializeRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.pool_count = 0;
+        registry.bump = ctx.bumps.registry;
+
+        msg!("Pool registry initialized");
+        Ok(())
+    }
+
+    /// Record an official pool in the registry (config admin)
+    ///
+    /// Entries are PDAs of the pool address, so anyone can check a pool is
+    /// official by deriving its entry; `index` numbers entries in
+    /// registration order for enumeration.
+    pub fn register_pool(ctx: Context<RegisterPool>, verifier_config_hash: [u8; 32]) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let pool = &ctx.accounts.pool;
+        let entry = &mut ctx.accounts.entry;
+
+        entry.index = registry.pool_count;
+        entry.pool = pool.key();
+        entry.token_mint = pool.token_mint;
+        entry.vault = pool.vault;
+        entry.denomination = pool.config.min_deposit;
+        entry.verifier_config_hash = verifier_config_hash;
+        entry.registered_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.entry;
+
+        registry.pool_count = registry
+            .pool_count
+            .checked_add(1)
+            .ok_or(MurklError::MathOverflow)?;
+
+        msg!("Pool {} registered at index {}", entry.pool, entry.index);
+        Ok(())
+    }
+
     /// Initialize a new token pool (admin only)
     pub fn initialize_pool(
         ctx: Context<InitializePool>,
@@ -136,18 +793,52 @@ pub mod murkl {
         pool.admin = ctx.accounts.admin.key();
         pool.token_mint = ctx.accounts.token_mint.key();
         pool.vault = ctx.accounts.vault.key();
-
-        // Empty tree root
-        pool.merkle_root = empty_hashes()[MERKLE_DEPTH];
-        pool.leaf_count = 0;
-        pool.config = config;
-        pool.paused = false;
-        pool.bump = ctx.bumps.pool;
+        pool.pool_kind = PoolKind::Token;
+        pool.reset(config, ctx.bumps.pool);
 
         msg!("Pool initialized for mint: {}", pool.token_mint);
         Ok(())
     }
 
+    /// Initialize the native-SOL pool (admin only)
+    ///
+    /// Deposits and claims move lamports, through `deposit_sol` and
+    /// `claim_sol`, between wallets and a system-owned vault PDA that the
+    /// program signs for. The admin funds the vault's rent-exempt reserve
+    /// here, so payouts never have to leave it below that.
+    pub fn initialize_sol_pool(ctx: Context<InitializeSolPool>, config: PoolConfig) -> Result<()> {
+        require!(
+            config.max_relayer_fee_bps <= MAX_RELAYER_FEE_BPS,
+            MurklError::InvalidPoolConfig
+        );
+        require!(config.min_deposit > 0, MurklError::InvalidPoolConfig);
+
+        let reserve = Rent::get()?.minimum_balance(0);
+        let top_up = reserve.saturating_sub(ctx.accounts.vault.lamports());
+        if top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: ctx.accounts.vault.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+
+        let pool = &mut ctx.accounts.pool;
+        pool.admin = ctx.accounts.admin.key();
+        pool.token_mint = SOL_POOL_MINT;
+        pool.vault = ctx.accounts.vault.key();
+        pool.pool_kind = PoolKind::Sol;
+        pool.reset(config, ctx.bumps.pool);
+
+        msg!("SOL pool initialized, vault: {}", pool.vault);
+        Ok(())
+    }
+
 
 
     /// Initialize the `PoolMerkle` PDA for an existing pool.
@@ -165,6 +856,50 @@ pub mod murkl {
         Ok(())
     }
 
+    /// Initialize the `PoolStats` PDA for a pool.
+    ///
+    /// Permissionless like `initialize_pool_merkle`. Stats cover deposits
+    /// from the current leaf on; once created, deposits and claims must pass
+    /// the account so the counts stay complete.
+    pub fn initialize_pool_stats(ctx: Context<InitializePoolStats>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let stats = &mut ctx.accounts.pool_stats;
+        let today = Clock::get()?.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+
+        stats.pool = pool.key();
+        stats.tracked_from_leaf = pool.leaf_count;
+        stats.tracked_since_day = today;
+        stats.current_day = today;
+        stats.day_start_leaf[PoolStats::slot(today)] = pool.leaf_count;
+        stats.bump = ctx.bumps.pool_stats;
+        pool.stats_enabled = true;
+
+        msg!("Pool stats tracking from leaf {}", stats.tracked_from_leaf);
+        Ok(())
+    }
+
+    /// Create the pool's admin log. Every pool admin instruction requires
+    /// it, so run this right after `initialize_pool` (or, for a pool older
+    /// than the log, before its next admin action).
+    ///
+    /// Permissionless: the log starts empty whoever creates it.
+    pub fn initialize_admin_log(ctx: Context<InitializeAdminLog>) -> Result<()> {
+        {
+            let data = ctx.accounts.pool.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == <Pool as anchor_lang::Discriminator>::DISCRIMINATOR,
+                MurklError::InvalidPoolAccount
+            );
+        }
+
+        let log = &mut ctx.accounts.admin_log;
+        log.pool = ctx.accounts.pool.key();
+        log.bump = ctx.bumps.admin_log;
+
+        msg!("Admin log initialized for {}", log.pool);
+        Ok(())
+    }
+
     /// Deposit tokens and add commitment to merkle tree
     pub fn deposit(
         ctx: Context<Deposit>,
@@ -177,142 +912,313 @@ pub mod murkl {
         require!(pool_merkle.pool == pool.key(), MurklError::InvalidDepositPool);
         
         require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.deposit_reveal_slots == 0, MurklError::DepositRevealRequired);
         require!(amount >= pool.config.min_deposit, MurklError::DepositTooSmall);
         
         // Transfer tokens to vault
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.depositor_token.to_account_info(),
-            to: ctx.accounts.vault.to_account_info(),
-            authority: ctx.accounts.depositor.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        transfer_to_vault(
+            &ctx.accounts.depositor_token,
+            &ctx.accounts.vault,
+            &ctx.accounts.depositor,
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
         
         // Update commitment Merkle root (incremental frontier)
-        let leaf_index = pool.leaf_count;
-        pool.merkle_root = merkle_append(&mut pool_merkle.branch, leaf_index, &commitment);
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
         
         // Create deposit record
-        let deposit = &mut ctx.accounts.deposit;
-        deposit.pool = pool.key();
-        deposit.commitment = commitment;
-        deposit.amount = amount;
-        deposit.leaf_index = pool.leaf_count;
-        deposit.claimed = false;
-        deposit.bump = ctx.bumps.deposit;
-        
-        pool.leaf_count += 1;
+        ctx.accounts.deposit.init(pool.key(), commitment, amount, leaf_index, ctx.bumps.deposit);
         
-        msg!("Deposit {} tokens, leaf index: {}", amount, deposit.leaf_index);
+        msg!("Deposit {} tokens, leaf index: {}", amount, leaf_index);
         Ok(())
     }
 
-    /// Claim tokens - FAULT-PROOF verification
-    /// 
-    /// Security checks:
-    /// 1. Verifier buffer is finalized (proof was valid)
-    /// 2. Commitment in buffer matches deposit commitment
-    /// 3. Nullifier in buffer matches provided nullifier (prevents tampering)
-    /// 4. Nullifier tracked in PDA (prevents replay - init fails if exists)
-    /// 5. Merkle root in buffer matches pool merkle root
-    /// 6. Buffer owned by stark-verifier program
-    pub fn claim(
-        ctx: Context<Claim>,
-        relayer_fee: u64,
-        nullifier: [u8; 32],
+    /// Deposit `amount` lamports into the SOL pool under `commitment`
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        amount: u64,
+        commitment: [u8; 32],
     ) -> Result<()> {
-        let pool = &ctx.accounts.pool;
-        let deposit = &mut ctx.accounts.deposit;
-        
+        let pool = &mut ctx.accounts.pool;
+        let pool_merkle = &mut ctx.accounts.pool_merkle;
+
         require!(!pool.paused, MurklError::PoolPaused);
-        require!(!deposit.claimed, MurklError::AlreadyClaimed);
-        
-        // Verify fee (checked arithmetic to avoid overflow)
-        let max_fee = deposit
-            .amount
-            .checked_mul(pool.config.max_relayer_fee_bps as u64)
-            .ok_or(MurklError::MathOverflow)?
-            / 10000;
-        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
-        
-        // ========================================
-        // FAULT-PROOF VERIFICATION
-        // ========================================
-        
-        let verifier_buffer = &ctx.accounts.verifier_buffer;
-        let data = verifier_buffer.try_borrow_data()?;
-        
-        // Check buffer size
-        require!(data.len() >= VERIFIER_HEADER_SIZE, MurklError::InvalidVerifierBuffer);
+        require!(pool.deposit_reveal_slots == 0, MurklError::DepositRevealRequired);
+        require!(amount >= pool.config.min_deposit, MurklError::DepositTooSmall);
 
-        // Enforce that the buffer was initialized for (and finalized by) this relayer.
-        // Without this, anyone can front-run a claim using another relayer's finalized buffer
-        // and steal the relayer fee by simply setting their own `relayer_token`.
-        let buffer_owner = Pubkey::try_from(&data[VERIFIER_OFFSET_OWNER..VERIFIER_OFFSET_OWNER + 32])
-            .map_err(|_| MurklError::InvalidVerifierBuffer)?;
-        require!(buffer_owner == ctx.accounts.relayer.key(), MurklError::Unauthorized);
-        
-        // Check finalized flag
-        let finalized = data[VERIFIER_OFFSET_FINALIZED] == 1;
-        require!(finalized, MurklError::ProofNotVerified);
-        
-        // Extract verified public inputs from buffer
-        let buffer_commitment: [u8; 32] = data[VERIFIER_OFFSET_COMMITMENT..VERIFIER_OFFSET_COMMITMENT + 32]
-            .try_into()
-            .map_err(|_| MurklError::InvalidVerifierBuffer)?;
-        let buffer_nullifier: [u8; 32] = data[VERIFIER_OFFSET_NULLIFIER..VERIFIER_OFFSET_NULLIFIER + 32]
-            .try_into()
-            .map_err(|_| MurklError::InvalidVerifierBuffer)?;
-        let buffer_merkle_root: [u8; 32] = data[VERIFIER_OFFSET_MERKLE_ROOT..VERIFIER_OFFSET_MERKLE_ROOT + 32]
-            .try_into()
-            .map_err(|_| MurklError::InvalidVerifierBuffer)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-        let buffer_recipient: [u8; 32] = data[VERIFIER_OFFSET_RECIPIENT..VERIFIER_OFFSET_RECIPIENT + 32]
-            .try_into()
-            .map_err(|_| MurklError::InvalidVerifierBuffer)?;
-        
-        // Verify commitment matches deposit
-        require!(
-            buffer_commitment == deposit.commitment,
-            MurklError::CommitmentMismatch
-        );
-        
-        // Verify nullifier argument matches buffer (prevents tampering with PDA seed)
-        require!(
-            buffer_nullifier == nullifier,
-            MurklError::NullifierMismatch
-        );
-        
-        // Verify merkle root matches pool (proof was for this pool's state)
-        require!(
-            buffer_merkle_root == pool.merkle_root,
-            MurklError::MerkleRootMismatch
-        );
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
 
-        // Verify recipient ATA is bound into the proof (prevents recipient substitution)
-        require!(
-            buffer_recipient == ctx.accounts.recipient_token.key().to_bytes(),
-            MurklError::InvalidVerifierBuffer
-        );
-        
-        // Initialize nullifier record (will fail if already exists = replay attack)
-        // The PDA is derived from pool + nullifier, so if this nullifier was used before,
-        // the account already exists and init will fail with AccountAlreadyInUse
-        let nullifier_record = &mut ctx.accounts.nullifier_record;
-        nullifier_record.pool = pool.key();
-        nullifier_record.nullifier = nullifier;
-        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
-        nullifier_record.bump = ctx.bumps.nullifier_record;
-        
-        msg!("Proof verified: commitment, nullifier, merkle_root all match");
-        
-        // ========================================
-        // EXECUTE CLAIM
-        // ========================================
-        
+        ctx.accounts.deposit.init(pool.key(), commitment, amount, leaf_index, ctx.bumps.deposit);
+
+        msg!("Deposit {} lamports, leaf index: {}", amount, leaf_index);
+        Ok(())
+    }
+
+    /// Deposit `amount` of `vault`'s mint, one the pool accepts besides its
+    /// `token_mint` (see `add_accepted_mint`), under `commitment`
+    ///
+    /// The leaf joins the same tree as every other deposit; only the record,
+    /// a `MintDepositRecord`, notes the mint, so `claim_accepted_mint` pays
+    /// it back out of the same vault.
+    pub fn deposit_accepted_mint(
+        ctx: Context<DepositAcceptedMint>,
+        amount: u64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let pool_merkle = &mut ctx.accounts.pool_merkle;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.deposit_reveal_slots == 0, MurklError::DepositRevealRequired);
+        require!(amount >= pool.config.min_deposit, MurklError::DepositTooSmall);
+
+        transfer_to_vault(
+            &ctx.accounts.depositor_token,
+            &ctx.accounts.vault,
+            &ctx.accounts.depositor,
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
+
+        let mint = ctx.accounts.vault.mint;
+        ctx.accounts.deposit.init(pool.key(), commitment, amount, leaf_index, ctx.bumps.deposit, mint);
+
+        msg!("Deposit {} of mint {}, leaf index: {}", amount, mint, leaf_index);
+        Ok(())
+    }
+
+    /// Register a deposit to reveal later, on a pool with a reveal window
+    ///
+    /// `registration_hash` is [`deposit_registration_hash`] of the
+    /// commitment and a fresh salt, so the commitment stays hidden until
+    /// `reveal_deposit` (or `reveal_deposit_sol`) funds it. That must land
+    /// in a later slot, within the pool's window. Someone copying the
+    /// commitment out of a reveal can't register it in time to get in first.
+    pub fn register_deposit(ctx: Context<RegisterDeposit>, registration_hash: [u8; 32]) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.deposit_reveal_slots > 0, MurklError::DepositRevealDisabled);
+
+        let slot = Clock::get()?.slot;
+        let registration = &mut ctx.accounts.registration;
+        registration.pool = pool.key();
+        registration.depositor = ctx.accounts.depositor.key();
+        registration.registration_hash = registration_hash;
+        registration.registered_slot = slot;
+        registration.expires_slot = slot.saturating_add(pool.deposit_reveal_slots);
+        registration.bump = ctx.bumps.registration;
+
+        msg!("Deposit registered, reveal by slot {}", registration.expires_slot);
+        Ok(())
+    }
+
+    /// Reveal a registered deposit's `commitment` and `salt` and deposit
+    /// `amount` under it, closing the registration
+    pub fn reveal_deposit(
+        ctx: Context<RevealDeposit>,
+        amount: u64,
+        commitment: [u8; 32],
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let pool_merkle = &mut ctx.accounts.pool_merkle;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(amount >= pool.config.min_deposit, MurklError::DepositTooSmall);
+        ctx.accounts.registration.check_reveal(&commitment, &salt, Clock::get()?.slot)?;
+
+        transfer_to_vault(
+            &ctx.accounts.depositor_token,
+            &ctx.accounts.vault,
+            &ctx.accounts.depositor,
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
+        ctx.accounts.deposit.init(pool.key(), commitment, amount, leaf_index, ctx.bumps.deposit);
+
+        msg!("Revealed deposit {} tokens, leaf index: {}", amount, leaf_index);
+        Ok(())
+    }
+
+    /// [`reveal_deposit`] for the SOL pool: `amount` is in lamports
+    pub fn reveal_deposit_sol(
+        ctx: Context<RevealDepositSol>,
+        amount: u64,
+        commitment: [u8; 32],
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let pool_merkle = &mut ctx.accounts.pool_merkle;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(amount >= pool.config.min_deposit, MurklError::DepositTooSmall);
+        ctx.accounts.registration.check_reveal(&commitment, &salt, Clock::get()?.slot)?;
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
+        ctx.accounts.deposit.init(pool.key(), commitment, amount, leaf_index, ctx.bumps.deposit);
+
+        msg!("Revealed deposit {} lamports, leaf index: {}", amount, leaf_index);
+        Ok(())
+    }
+
+    /// Close a registration whose reveal window has passed, refunding its
+    /// rent to the depositor. Anyone may send it.
+    pub fn expire_deposit_registration(ctx: Context<ExpireDepositRegistration>) -> Result<()> {
+        let registration = &ctx.accounts.registration;
+        require!(
+            Clock::get()?.slot > registration.expires_slot,
+            MurklError::RegistrationNotExpired
+        );
+        msg!("Deposit registration expired at slot {}", registration.expires_slot);
+        Ok(())
+    }
+
+    /// Deposit `amount_per_epoch * epochs` for a recurring payout
+    ///
+    /// The commitment is an ordinary leaf, but its record is a
+    /// `Subscription` rather than a `DepositRecord`: the recipient claims
+    /// one epoch at a time with `claim_epoch`, once per pool epoch length,
+    /// starting now.
+    pub fn deposit_subscription(
+        ctx: Context<DepositSubscription>,
+        amount_per_epoch: u64,
+        epochs: u8,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let pool_merkle = &mut ctx.accounts.pool_merkle;
+        
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.deposit_reveal_slots == 0, MurklError::DepositRevealRequired);
+        require!(pool.epoch_duration_secs > 0, MurklError::SubscriptionsDisabled);
+        require!(
+            epochs > 0 && epochs <= MAX_SUBSCRIPTION_EPOCHS,
+            MurklError::InvalidEpochCount
+        );
+        require!(amount_per_epoch >= pool.config.min_deposit, MurklError::DepositTooSmall);
+        let amount = amount_per_epoch
+            .checked_mul(epochs as u64)
+            .ok_or(MurklError::MathOverflow)?;
+        
+        transfer_to_vault(
+            &ctx.accounts.depositor_token,
+            &ctx.accounts.vault,
+            &ctx.accounts.depositor,
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+        
+        let stats = ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s);
+        let leaf_index = append_leaf(pool, pool_merkle, stats, &commitment)?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.pool = pool.key();
+        subscription.commitment = commitment;
+        subscription.leaf_index = leaf_index;
+        subscription.amount_per_epoch = amount_per_epoch;
+        subscription.epochs = epochs;
+        subscription.epoch_duration_secs = pool.epoch_duration_secs;
+        subscription.started_at = Clock::get()?.unix_timestamp;
+        subscription.claimed_epochs = 0;
+        subscription.bump = ctx.bumps.subscription;
+        
+        msg!("Subscription deposit: {} x {} tokens, leaf index: {}", epochs, amount_per_epoch, leaf_index);
+        Ok(())
+    }
+
+    /// Claim tokens - FAULT-PROOF verification
+    /// 
+    /// Security checks:
+    /// 1. Verifier buffer is finalized (proof was valid)
+    /// 2. Commitment in buffer matches deposit commitment
+    /// 3. Nullifier in buffer matches provided nullifier (prevents tampering)
+    /// 4. Nullifier tracked in PDA (prevents replay - init fails if exists)
+    /// 5. Merkle root in buffer matches pool merkle root
+    /// 6. Buffer owned by stark-verifier program
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, Claim<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &mut ctx.accounts.deposit;
+        
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+        
+        // Verify fee (checked arithmetic to avoid overflow)
+        let max_fee = deposit
+            .amount
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+        
+        // ========================================
+        // FAULT-PROOF VERIFICATION
+        // ========================================
+        
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.relayer.key(),
+            pool,
+            &deposit.commitment,
+            &nullifier,
+            &ctx.accounts.recipient_token.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+        
+        // Initialize nullifier record (will fail if already exists = replay attack)
+        // The PDA is derived from pool + nullifier, so if this nullifier was used before,
+        // the account already exists and init will fail with AccountAlreadyInUse
+        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        nullifier_record.pool = pool.key();
+        nullifier_record.nullifier = nullifier;
+        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
+        nullifier_record.bump = ctx.bumps.nullifier_record;
+        
+        msg!("Proof verified: commitment, nullifier, merkle_root all match");
+        
+        // ========================================
+        // EXECUTE CLAIM
+        // ========================================
+        
         // Mark deposit as claimed
         deposit.claimed = true;
+        record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), deposit.leaf_index, nullifier_record.claimed_at)?;
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
         
         // Calculate amounts
         let recipient_amount = deposit
@@ -320,265 +1226,2400 @@ pub mod murkl {
             .checked_sub(relayer_fee)
             .ok_or(MurklError::MathOverflow)?;
         
-        // Transfer to recipient
-        let pool_seeds = &[
-            b"pool".as_ref(),
-            pool.token_mint.as_ref(),
-            &[pool.bump],
-        ];
-        let signer_seeds = &[&pool_seeds[..]];
+        // Optional compliance gate (None = permissionless)
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient_token.owner,
+                recipient_amount,
+            )?;
+        }
         
-        let transfer_to_recipient = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.recipient_token.to_account_info(),
-            authority: ctx.accounts.pool.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                transfer_to_recipient,
-                signer_seeds,
-            ),
+        // Transfer to recipient
+        transfer_from_vault(
+            pool,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
             recipient_amount,
         )?;
         
         // Transfer fee to relayer (if any)
         if relayer_fee > 0 {
-            let transfer_to_relayer = Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.relayer_token.to_account_info(),
-                authority: ctx.accounts.pool.to_account_info(),
-            };
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    transfer_to_relayer,
-                    signer_seeds,
-                ),
+            transfer_from_vault(
+                pool,
+                &ctx.accounts.vault,
+                ctx.accounts.relayer_token.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
                 relayer_fee,
             )?;
         }
         
+        let pool_key = ctx.accounts.pool.key();
+        if let Some(receipt) = ctx.accounts.claim_receipt.as_deref_mut() {
+            receipt.record(pool_key, ctx.accounts.recipient_token.owner, ctx.bumps.claim_receipt, Clock::get()?.slot)?;
+        }
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+        
+        if let Some(verifier_program) = &ctx.accounts.verifier_program {
+            close_verifier_buffer(
+                verifier_program,
+                &ctx.accounts.verifier_buffer,
+                &ctx.accounts.relayer.to_account_info(),
+            )?;
+        }
+        
+        msg!("Claimed {} to recipient, {} fee to relayer", recipient_amount, relayer_fee);
+        Ok(())
+    }
+
+    /// Claim like `claim`, recording the nullifier in the pool's nullifier
+    /// registry instead of its own `NullifierRecord` PDA.
+    ///
+    /// The pool must have a registry (`set_nullifier_shard_bits`) and the
+    /// nullifier's shard must exist (`initialize_nullifier_shard`). A
+    /// nullifier that already has a `NullifierRecord`, from before the pool
+    /// switched, is refused.
+    pub fn claim_sharded<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimSharded<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &mut ctx.accounts.deposit;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.nullifier_shard_bits > 0, MurklError::NullifierRegistryDisabled);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+
+        let max_fee = deposit
+            .amount
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.relayer.key(),
+            pool,
+            &deposit.commitment,
+            &nullifier,
+            &ctx.accounts.recipient_token.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+
+        insert_nullifier(
+            &ctx.accounts.nullifier_shard.to_account_info(),
+            ctx.accounts.relayer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &nullifier,
+        )?;
+
+        deposit.claimed = true;
+        let now = Clock::get()?.unix_timestamp;
+        record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), deposit.leaf_index, now)?;
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
+
+        let recipient_amount = deposit
+            .amount
+            .checked_sub(relayer_fee)
+            .ok_or(MurklError::MathOverflow)?;
+
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient_token.owner,
+                recipient_amount,
+            )?;
+        }
+
+        transfer_from_vault(
+            pool,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            transfer_from_vault(
+                pool,
+                &ctx.accounts.vault,
+                ctx.accounts.relayer_token.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+
+        let pool_key = ctx.accounts.pool.key();
+        if let Some(receipt) = ctx.accounts.claim_receipt.as_deref_mut() {
+            receipt.record(pool_key, ctx.accounts.recipient_token.owner, ctx.bumps.claim_receipt, Clock::get()?.slot)?;
+        }
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+
         msg!("Claimed {} to recipient, {} fee to relayer", recipient_amount, relayer_fee);
         Ok(())
     }
 
-    /// Admin: Pause pool
-    pub fn pause_pool(ctx: Context<AdminAction>) -> Result<()> {
-        ctx.accounts.pool.paused = true;
-        msg!("Pool paused");
-        Ok(())
-    }
+    /// Whether `nullifier` has been claimed from the pool, in either its
+    /// `NullifierRecord` PDA or the registry. Returned as return data;
+    /// meant for `simulateTransaction`.
+    ///
+    /// Pass the nullifier's shard, or the program ID if the pool has no
+    /// registry or the shard doesn't exist yet.
+    pub fn check_nullifier(ctx: Context<CheckNullifier>, nullifier: [u8; 32]) -> Result<bool> {
+        if !ctx.accounts.nullifier_record.data_is_empty() {
+            return Ok(true);
+        }
+        let Some(shard) = &ctx.accounts.nullifier_shard else {
+            return Ok(false);
+        };
+        let info = shard.to_account_info();
+        let data = info.try_borrow_data()?;
+        let used = search_nullifier_shard(&data[NULLIFIER_SHARD_HEADER..], &nullifier).is_ok();
+        Ok(used)
+    }
+
+    /// Create the pool's `NullifierBloom`. Permissionless; the payer funds
+    /// the filter's rent. It covers claims from here on, so once created
+    /// every claim must pass it.
+    pub fn initialize_nullifier_bloom(ctx: Context<InitializeNullifierBloom>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let bloom = &mut ctx.accounts.nullifier_bloom;
+        bloom.pool = pool.key();
+        bloom.tracked_from_leaf = pool.leaf_count;
+        bloom.inserted = 0;
+        bloom.bump = ctx.bumps.nullifier_bloom;
+        pool.nullifier_bloom_enabled = true;
+
+        msg!("Nullifier bloom filter tracking from leaf {}", bloom.tracked_from_leaf);
+        Ok(())
+    }
+
+    /// Create shard `shard` of a pool's nullifier registry. Permissionless;
+    /// the payer funds the empty shard, and claims fund its growth.
+    pub fn initialize_nullifier_shard(ctx: Context<InitializeNullifierShard>, shard: u16) -> Result<()> {
+        let bits = ctx.accounts.pool.nullifier_shard_bits;
+        require!(bits > 0, MurklError::NullifierRegistryDisabled);
+        require!((shard as u32) < 1 << bits, MurklError::InvalidNullifierShard);
+        let nullifier_shard = &mut ctx.accounts.nullifier_shard;
+        nullifier_shard.pool = ctx.accounts.pool.key();
+        nullifier_shard.shard = shard;
+        nullifier_shard.bump = ctx.bumps.nullifier_shard;
+        Ok(())
+    }
+
+    /// Claim a SOL pool deposit; same checks as `claim`.
+    ///
+    /// The proof's recipient is the `recipient` wallet itself, and the
+    /// relayer's fee goes to the relayer's signing key.
+    pub fn claim_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimSol<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &mut ctx.accounts.deposit;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+
+        let max_fee = deposit
+            .amount
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.relayer.key(),
+            pool,
+            &deposit.commitment,
+            &nullifier,
+            &ctx.accounts.recipient.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+
+        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        nullifier_record.pool = pool.key();
+        nullifier_record.nullifier = nullifier;
+        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
+        nullifier_record.bump = ctx.bumps.nullifier_record;
+
+        deposit.claimed = true;
+        record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), deposit.leaf_index, nullifier_record.claimed_at)?;
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
+
+        let recipient_amount = deposit
+            .amount
+            .checked_sub(relayer_fee)
+            .ok_or(MurklError::MathOverflow)?;
+
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient.key(),
+                recipient_amount,
+            )?;
+        }
+
+        let pool_key = pool.key();
+        transfer_from_sol_vault(
+            &pool_key,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            ctx.accounts.recipient.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            transfer_from_sol_vault(
+                &pool_key,
+                &ctx.accounts.vault,
+                ctx.bumps.vault,
+                ctx.accounts.relayer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+
+        if let Some(receipt) = ctx.accounts.claim_receipt.as_deref_mut() {
+            receipt.record(pool_key, ctx.accounts.recipient.key(), ctx.bumps.claim_receipt, Clock::get()?.slot)?;
+        }
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+
+        msg!("Claimed {} lamports to recipient, {} fee to relayer", recipient_amount, relayer_fee);
+        Ok(())
+    }
+
+    /// Claim a `deposit_accepted_mint` deposit, paid in the mint it was
+    /// made in
+    ///
+    /// Checks as `claim`; the vault, recipient and relayer token accounts
+    /// are those of the deposit's mint instead of the pool's `token_mint`.
+    pub fn claim_accepted_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAcceptedMint<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &mut ctx.accounts.deposit;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+
+        let max_fee = deposit
+            .amount
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.relayer.key(),
+            pool,
+            &deposit.commitment,
+            &nullifier,
+            &ctx.accounts.recipient_token.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+
+        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        nullifier_record.pool = pool.key();
+        nullifier_record.nullifier = nullifier;
+        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
+        nullifier_record.bump = ctx.bumps.nullifier_record;
+
+        deposit.claimed = true;
+        record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), deposit.leaf_index, nullifier_record.claimed_at)?;
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
+
+        let recipient_amount = deposit
+            .amount
+            .checked_sub(relayer_fee)
+            .ok_or(MurklError::MathOverflow)?;
+
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient_token.owner,
+                recipient_amount,
+            )?;
+        }
+
+        transfer_from_vault(
+            pool,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            transfer_from_vault(
+                pool,
+                &ctx.accounts.vault,
+                ctx.accounts.relayer_token.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+
+        let pool_key = ctx.accounts.pool.key();
+        if let Some(receipt) = ctx.accounts.claim_receipt.as_deref_mut() {
+            receipt.record(pool_key, ctx.accounts.recipient_token.owner, ctx.bumps.claim_receipt, Clock::get()?.slot)?;
+        }
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+
+        msg!("Claimed {} of mint {} to recipient, {} fee to relayer", recipient_amount, deposit.mint, relayer_fee);
+        Ok(())
+    }
+
+    /// Claim tokens with a plain Merkle path instead of a STARK proof
+    ///
+    /// Cheap mode for pools that enable it. The claimant reveals the
+    /// commitment preimage (id_hash, secret), so the claim is linkable to its
+    /// deposit by anyone, including the relayer. Checks:
+    /// 1. Pool has path claims enabled
+    /// 2. (id_hash, secret) hashes to the deposit commitment
+    /// 3. The commitment sits at the deposit's leaf index under `pool.merkle_root`
+    /// 4. Nullifier argument equals hash(secret, leaf_index); its PDA prevents replay
+    /// 5. The recipient token account's owner signs, since nothing else binds
+    ///    the payout once the preimage is public
+    pub fn claim_with_path<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimWithPath<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+        id_hash: u32,
+        secret: u32,
+        path: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &mut ctx.accounts.deposit;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(pool.path_claims_enabled, MurklError::PathClaimsDisabled);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+
+        let max_fee = deposit
+            .amount
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+
+        require!(
+            path_commitment(id_hash, secret) == deposit.commitment,
+            MurklError::CommitmentMismatch
+        );
+        let path_root = merkle_path_root(&deposit.commitment, deposit.leaf_index, &path)
+            .ok_or(MurklError::InvalidMerklePath)?;
+        require!(pool.is_known_root(&path_root), MurklError::InvalidMerklePath);
+        let leaf_index = u32::try_from(deposit.leaf_index).map_err(|_| MurklError::MathOverflow)?;
+        require!(
+            path_nullifier(secret, leaf_index) == nullifier,
+            MurklError::NullifierMismatch
+        );
+
+        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        nullifier_record.pool = pool.key();
+        nullifier_record.nullifier = nullifier;
+        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
+        nullifier_record.bump = ctx.bumps.nullifier_record;
+
+        deposit.claimed = true;
+        record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), deposit.leaf_index, nullifier_record.claimed_at)?;
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
+
+        let recipient_amount = deposit
+            .amount
+            .checked_sub(relayer_fee)
+            .ok_or(MurklError::MathOverflow)?;
+
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient_token.owner,
+                recipient_amount,
+            )?;
+        }
+
+        transfer_from_vault(
+            pool,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            transfer_from_vault(
+                pool,
+                &ctx.accounts.vault,
+                ctx.accounts.relayer_token.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+
+        let pool_key = ctx.accounts.pool.key();
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+
+        msg!("Path claim: {} to recipient, {} fee to relayer", recipient_amount, relayer_fee);
+        Ok(())
+    }
+
+    /// Claim one epoch of a subscription deposit
+    ///
+    /// Same buffer checks as `claim`, with the nullifier from
+    /// `pq_epoch_nullifier`: its tag names the epoch, which must exist, have
+    /// opened and not been claimed yet. Pays `amount_per_epoch`.
+    pub fn claim_epoch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimEpoch<'info>>,
+        relayer_fee: u64,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let subscription = &mut ctx.accounts.subscription;
+        
+        require!(!pool.paused, MurklError::PoolPaused);
+        let epoch = nullifier_epoch(&nullifier);
+        let now = Clock::get()?.unix_timestamp;
+        subscription.check_claimable(epoch, now)?;
+        
+        let max_fee = subscription
+            .amount_per_epoch
+            .checked_mul(pool.config.max_relayer_fee_bps as u64)
+            .ok_or(MurklError::MathOverflow)?
+            / 10000;
+        require!(relayer_fee <= max_fee, MurklError::FeeTooHigh);
+        
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.relayer.key(),
+            pool,
+            &subscription.commitment,
+            &nullifier,
+            &ctx.accounts.recipient_token.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+        
+        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        nullifier_record.pool = pool.key();
+        nullifier_record.nullifier = nullifier;
+        nullifier_record.claimed_at = now;
+        nullifier_record.bump = ctx.bumps.nullifier_record;
+        
+        subscription.claimed_epochs |= 1 << epoch;
+        // The deposit leaves the anonymity set with its last epoch
+        if subscription.is_fully_claimed() {
+            record_claim_stats(pool, ctx.accounts.pool_stats.as_deref_mut().map(|s| &mut **s), subscription.leaf_index, now)?;
+        }
+        record_claim_bloom(pool, ctx.accounts.nullifier_bloom.as_deref_mut(), &nullifier)?;
+        
+        let recipient_amount = subscription
+            .amount_per_epoch
+            .checked_sub(relayer_fee)
+            .ok_or(MurklError::MathOverflow)?;
+        
+        if let Some(hook) = pool.config.compliance_hook {
+            invoke_compliance_hook(
+                &hook,
+                ctx.remaining_accounts,
+                &ctx.accounts.recipient_token.owner,
+                recipient_amount,
+            )?;
+        }
+        
+        transfer_from_vault(
+            pool,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            transfer_from_vault(
+                pool,
+                &ctx.accounts.vault,
+                ctx.accounts.relayer_token.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+        
+        let pool_key = ctx.accounts.pool.key();
+        if let Some(receipt) = ctx.accounts.claim_receipt.as_deref_mut() {
+            receipt.record(pool_key, ctx.accounts.recipient_token.owner, ctx.bumps.claim_receipt, Clock::get()?.slot)?;
+        }
+        emit_claim_event(&mut ctx.accounts.pool, pool_key, nullifier, recipient_amount, relayer_fee)?;
+        
+        msg!("Epoch {} claimed: {} to recipient, {} fee to relayer", epoch, recipient_amount, relayer_fee);
+        Ok(())
+    }
+
+    /// Prove a deposit is in the pool's anonymity set without moving tokens
+    ///
+    /// Runs `claim`'s buffer checks against the unclaimed deposit, with
+    /// `member` in place of the recipient token account the proof binds,
+    /// then records the nullifier under `scope`. Governance or allowlist
+    /// programs call this by CPI and count a success, or the
+    /// `MembershipRecord`, as one member per deposit per scope.
+    ///
+    /// The record is kept apart from the claim nullifiers, so the deposit
+    /// stays claimable. Its nullifier is public all the same: memberships
+    /// under different scopes, and the deposit's eventual claim, can be
+    /// linked through it.
+    pub fn prove_membership_only(
+        ctx: Context<ProveMembershipOnly>,
+        nullifier: [u8; 32],
+        scope: [u8; 32],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let deposit = &ctx.accounts.deposit;
+
+        require!(!pool.paused, MurklError::PoolPaused);
+        require!(!deposit.claimed, MurklError::AlreadyClaimed);
+
+        check_verifier_buffer(
+            &ctx.accounts.verifier_buffer,
+            &ctx.accounts.payer.key(),
+            pool,
+            &deposit.commitment,
+            &nullifier,
+            &ctx.accounts.member.key(),
+        )?;
+        check_verifier_params(pool, ctx.accounts.verifier_params.as_deref())?;
+
+        let record = &mut ctx.accounts.membership_record;
+        record.pool = pool.key();
+        record.scope = scope;
+        record.nullifier = nullifier;
+        record.member = ctx.accounts.member.key();
+        record.proven_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.membership_record;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.event_seq = pool.event_seq.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        emit!(MembershipEvent { pool: pool.key(), event_seq: pool.event_seq, scope });
+
+        msg!("Membership proven for {}", ctx.accounts.member.key());
+        Ok(())
+    }
+
+    /// Admin: Set or clear the compliance hook CPI'd before each payout
+    pub fn set_compliance_hook(ctx: Context<AdminAction>, hook: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.pool.config.compliance_hook = hook;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetComplianceHook, &hook.try_to_vec()?, &ctx.accounts.admin.key())?;
+        match hook {
+            Some(hook) => msg!("Compliance hook set: {}", hook),
+            None => msg!("Compliance hook cleared"),
+        }
+        Ok(())
+    }
+
+    /// Admin: Allow or forbid `claim_with_path` on this pool
+    pub fn set_path_claims(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.pool.path_claims_enabled = enabled;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetPathClaims, &enabled.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Path claims {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Admin: Emit only `event_seq` and the pool in claim events.
+    ///
+    /// Indexers then recover each claim from account state instead: the
+    /// `NullifierRecord` it created and the deposit or subscription it paid.
+    pub fn set_minimal_events(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.pool.minimal_events = enabled;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetMinimalEvents, &enabled.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Minimal claim events {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Admin: Set how many slots a finalized proof stays claimable (0 = forever)
+    pub fn set_max_proof_age(ctx: Context<AdminAction>, max_age_slots: u64) -> Result<()> {
+        ctx.accounts.pool.max_proof_age_slots = max_age_slots;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetMaxProofAge, &max_age_slots.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Max proof age: {} slots", max_age_slots);
+        Ok(())
+    }
+
+    /// Admin: Accept proofs against the current root and the `window` roots
+    /// before it (0 = current root only)
+    pub fn set_root_history_window(ctx: Context<AdminAction>, window: u8) -> Result<()> {
+        require!(window as usize <= ROOT_HISTORY_SIZE, MurklError::InvalidRootHistoryWindow);
+        ctx.accounts.pool.root_history_window = window;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetRootHistoryWindow, &window.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Root history window: {} roots", window);
+        Ok(())
+    }
+
+    /// Admin: Pin the stark-verifier params hash claims require, or unpin.
+    /// Publish the verifier's params first and pin the hash they carry.
+    pub fn set_required_verifier_params(
+        ctx: Context<AdminAction>,
+        params_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.pool.config.required_verifier_params_hash = params_hash;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetRequiredVerifierParams, &params_hash.try_to_vec()?, &ctx.accounts.admin.key())?;
+        match params_hash {
+            Some(_) => msg!("Verifier params pinned"),
+            None => msg!("Verifier params unpinned"),
+        }
+        Ok(())
+    }
+
+    /// Admin: Require the published stark-verifier params to guarantee
+    /// `level` (a `SecurityLevel` byte) before claims pay out, or drop the
+    /// requirement. Unlike a params pin it survives verifier upgrades that
+    /// keep or raise the level.
+    pub fn set_min_security_level(ctx: Context<AdminAction>, level: Option<u8>) -> Result<()> {
+        if let Some(level) = level {
+            require!(SecurityLevel::from_byte(level).is_some(), MurklError::InvalidSecurityLevel);
+        }
+        ctx.accounts.pool.min_security_level = level;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetMinSecurityLevel, &level.try_to_vec()?, &ctx.accounts.admin.key())?;
+        match level.and_then(SecurityLevel::from_byte) {
+            Some(level) => msg!("Minimum security level: {}", level.name()),
+            None => msg!("Minimum security level cleared"),
+        }
+        Ok(())
+    }
+
+    /// Admin: Turn on the nullifier registry with `2^bits` shards, for
+    /// `claim_sharded`. Once set it can't change, since moving nullifiers
+    /// between shards would hide them.
+    pub fn set_nullifier_shard_bits(ctx: Context<AdminAction>, bits: u8) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.nullifier_shard_bits == 0, MurklError::NullifierRegistryLocked);
+        require!(
+            (1..=MAX_NULLIFIER_SHARD_BITS).contains(&bits),
+            MurklError::InvalidNullifierShard
+        );
+        pool.nullifier_shard_bits = bits;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetNullifierShardBits, &[bits], &ctx.accounts.admin.key())?;
+        msg!("Nullifier registry: {} shards", 1u32 << bits);
+        Ok(())
+    }
+
+    /// Admin: Set the epoch length for new subscription deposits (0 disables them)
+    pub fn set_epoch_duration(ctx: Context<AdminAction>, duration_secs: i64) -> Result<()> {
+        require!(duration_secs >= 0, MurklError::InvalidEpochDuration);
+        ctx.accounts.pool.epoch_duration_secs = duration_secs;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetEpochDuration, &duration_secs.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Subscription epoch: {} seconds", duration_secs);
+        Ok(())
+    }
+
+    /// Admin: Require deposits to go through `register_deposit` and a reveal
+    /// within `window_slots` slots (0 = direct deposits). Subscription
+    /// deposits have no reveal path, so they are refused while it is set.
+    /// Registrations keep the window they were made under.
+    pub fn set_deposit_reveal_window(ctx: Context<AdminAction>, window_slots: u64) -> Result<()> {
+        require!(window_slots <= MAX_DEPOSIT_REVEAL_SLOTS, MurklError::InvalidRevealWindow);
+        ctx.accounts.pool.deposit_reveal_slots = window_slots;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::SetDepositRevealWindow, &window_slots.try_to_vec()?, &ctx.accounts.admin.key())?;
+        msg!("Deposit reveal window: {} slots", window_slots);
+        Ok(())
+    }
+
+    /// Admin: accept deposits of `mint` at par with the pool's `token_mint`
+    ///
+    /// Makes the pool multi-mint. `mint` gets its own vault, PDA of
+    /// ["vault", pool, mint]; `deposit_accepted_mint` fills it and
+    /// `claim_accepted_mint` pays out of it, so each vault only ever holds
+    /// its own mint's unclaimed deposits. Deposits of every mint share the
+    /// pool's tree, nullifiers and anonymity set. `mint` must have
+    /// `token_mint`'s decimals, and can't be removed once accepted.
+    pub fn add_accepted_mint(ctx: Context<AddAcceptedMint>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let mint = ctx.accounts.mint.key();
+
+        require!(pool.pool_kind == PoolKind::Token, MurklError::WrongPoolKind);
+        require!(
+            ctx.accounts.mint.decimals == ctx.accounts.token_mint.decimals,
+            MurklError::MintDecimalsMismatch
+        );
+        require!(
+            mint != pool.token_mint && pool.accepted_vault(&mint).is_none(),
+            MurklError::MintAlreadyAccepted
+        );
+        let index = pool.accepted_mint_count as usize;
+        require!(index < MAX_ACCEPTED_MINTS, MurklError::TooManyAcceptedMints);
+
+        pool.accepted_mints[index] = AcceptedMint {
+            mint,
+            vault: ctx.accounts.vault.key(),
+        };
+        pool.accepted_mint_count += 1;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::AddAcceptedMint, mint.as_ref(), &ctx.accounts.admin.key())?;
+
+        msg!("Pool accepts mint {}, vault: {}", mint, ctx.accounts.vault.key());
+        Ok(())
+    }
+
+    /// Admin: Announce a move of the pool's tokens to a new vault, which
+    /// `rotate_vault` can carry out once [`VAULT_ROTATION_DELAY_SECS`] have
+    /// passed. The delay gives depositors time to see it coming in the
+    /// admin log and leave; `cancel_vault_rotation` withdraws it.
+    pub fn schedule_vault_rotation(ctx: Context<ScheduleVaultRotation>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let rotation = &mut ctx.accounts.vault_rotation;
+        let now = Clock::get()?.unix_timestamp;
+        rotation.pool = pool.key();
+        rotation.scheduled_at = now;
+        rotation.executable_at = now.checked_add(VAULT_ROTATION_DELAY_SECS).ok_or(MurklError::MathOverflow)?;
+        rotation.bump = ctx.bumps.vault_rotation;
+        log_admin_action(
+            &mut ctx.accounts.admin_log,
+            AdminLogAction::ScheduleVaultRotation,
+            &rotation.executable_at.to_le_bytes(),
+            &ctx.accounts.admin.key(),
+        )?;
+
+        pool.event_seq = pool.event_seq.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        emit!(VaultRotationScheduled {
+            pool: pool.key(),
+            event_seq: pool.event_seq,
+            executable_at: rotation.executable_at,
+        });
+        msg!("Vault rotation executable at {}", rotation.executable_at);
+        Ok(())
+    }
+
+    /// Admin: Withdraw a scheduled vault rotation, refunding its rent.
+    pub fn cancel_vault_rotation(ctx: Context<CancelVaultRotation>) -> Result<()> {
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::CancelVaultRotation, &[], &ctx.accounts.admin.key())?;
+        msg!("Vault rotation cancelled");
+        Ok(())
+    }
+
+    /// Admin: Carry out a scheduled vault rotation.
+    ///
+    /// Creates the vault of the pool's next `vault_generation`, moves the
+    /// whole balance into it, closes the old vault (rent to the admin) and
+    /// points `Pool::vault` and the pool's registry entry, when passed, at
+    /// the new one, all in this one instruction. Token pools only; a
+    /// multi-mint pool's accepted-mint vaults stay where they are.
+    pub fn rotate_vault(ctx: Context<RotateVault>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.vault_rotation.executable_at,
+            MurklError::VaultRotationLocked
+        );
+        let generation = ctx.accounts.pool.vault_generation.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        let old_vault = ctx.accounts.vault.key();
+        let new_vault = ctx.accounts.new_vault.key();
+        let amount = ctx.accounts.vault.amount;
+
+        if amount > 0 {
+            transfer_from_vault(
+                &ctx.accounts.pool,
+                &ctx.accounts.vault,
+                ctx.accounts.new_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                amount,
+            )?;
+        }
+        let pool_seeds = &[
+            b"pool".as_ref(),
+            ctx.accounts.pool.token_mint.as_ref(),
+            &[ctx.accounts.pool.bump],
+        ];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault.to_account_info(),
+                destination: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            &[&pool_seeds[..]],
+        ))?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.vault = new_vault;
+        pool.vault_generation = generation;
+        if let Some(entry) = &mut ctx.accounts.registry_entry {
+            entry.vault = new_vault;
+        }
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::RotateVault, new_vault.as_ref(), &ctx.accounts.admin.key())?;
+
+        pool.event_seq = pool.event_seq.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        emit!(VaultRotated {
+            pool: pool.key(),
+            event_seq: pool.event_seq,
+            old_vault,
+            new_vault,
+            generation,
+            amount,
+        });
+        msg!("Vault rotated to {} (generation {}), moved {}", new_vault, generation, amount);
+        Ok(())
+    }
+
+    /// Admin: Grow a pool account to the current `Pool` layout.
+    ///
+    /// Fields after `bump` are appended, so the zeroed tail decodes as
+    /// empty/None. `PoolConfig` grew in the middle of the account, though:
+    /// a pool still in the original [`LEGACY_POOL_LEN`] layout has its
+    /// `paused` and `bump` moved past the new config fields, which start
+    /// out unset. The admin tops up rent. No-op if the account is already
+    /// large enough.
+    pub fn grow_pool(ctx: Context<GrowPool>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let target_len = 8 + Pool::INIT_SPACE;
+        let old_len = pool.data_len();
+
+        {
+            let data = pool.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == <Pool as anchor_lang::Discriminator>::DISCRIMINATOR,
+                MurklError::InvalidPoolAccount
+            );
+            let admin = Pubkey::try_from(&data[8..40]).map_err(|_| MurklError::InvalidPoolAccount)?;
+            require!(admin == ctx.accounts.admin.key(), MurklError::Unauthorized);
+            log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::GrowPool, &[], &admin)?;
+            if data.len() >= target_len {
+                return Ok(());
+            }
+        }
+
+        let top_up = Rent::get()?
+            .minimum_balance(target_len)
+            .saturating_sub(pool.lamports());
+        if top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: pool.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        pool.realloc(target_len, true)?;
+        if old_len == LEGACY_POOL_LEN {
+            migrate_legacy_pool(&mut pool.try_borrow_mut_data()?)?;
+        }
+
+        msg!("Pool grown to {} bytes", target_len);
+        Ok(())
+    }
+
+    /// Admin: Pause pool
+    pub fn pause_pool(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.pool.paused = true;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::PausePool, &[], &ctx.accounts.admin.key())?;
+        msg!("Pool paused");
+        Ok(())
+    }
+
+    /// Admin: Unpause pool
+    pub fn unpause_pool(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.pool.paused = false;
+        log_admin_action(&mut ctx.accounts.admin_log, AdminLogAction::UnpausePool, &[], &ctx.accounts.admin.key())?;
+        msg!("Pool unpaused");
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalConfig::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ MurklError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PoolRegistry::INIT_SPACE,
+        seeds = [REGISTRY_SEED],
+        bump
+    )]
+    pub registry: Account<'info, PoolRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterPool<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ MurklError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [REGISTRY_SEED],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RegistryEntry::INIT_SPACE,
+        seeds = [REGISTRY_ENTRY_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, RegistryEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ MurklError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", token_mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    pub token_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = admin,
+        token::mint = token_mint,
+        token::authority = pool,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSolPool<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ MurklError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", SOL_POOL_MINT.as_ref()],
+        bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// Holds the pool's lamports; created by funding its rent-exempt reserve
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePoolMerkle<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolMerkle::INIT_SPACE,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePoolStats<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolStats::INIT_SPACE,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Pool is taken raw so a pool written under an older layout can get its
+/// log before `grow_pool`, which needs the log, resizes it.
+#[derive(Accounts)]
+pub struct InitializeAdminLog<'info> {
+    /// CHECK: owner checked here; discriminator checked in handler
+    #[account(owner = crate::ID @ MurklError::InvalidPoolAccount)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AdminLog::INIT_SPACE,
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub admin_log: Account<'info, AdminLog>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + DepositRecord::INIT_SPACE,
+        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+    
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = depositor_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub depositor_token: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.pool_kind == PoolKind::Sol @ MurklError::WrongPoolKind
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + DepositRecord::INIT_SPACE,
+        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + MintDepositRecord::INIT_SPACE,
+        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub deposit: Account<'info, MintDepositRecord>,
+
+    /// Vault of the deposited mint
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), vault.mint.as_ref()],
+        bump,
+        constraint = pool.accepted_vault(&vault.mint) == Some(vault.key()) @ MurklError::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = depositor_token.mint == vault.mint @ MurklError::InvalidTokenMint
+    )]
+    pub depositor_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(registration_hash: [u8; 32])]
+pub struct RegisterDeposit<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + DepositRegistration::INIT_SPACE,
+        seeds = [DEPOSIT_REGISTRATION_SEED, pool.key().as_ref(), registration_hash.as_ref()],
+        bump
+    )]
+    pub registration: Account<'info, DepositRegistration>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [DEPOSIT_REGISTRATION_SEED, pool.key().as_ref(), registration.registration_hash.as_ref()],
+        bump = registration.bump,
+        has_one = depositor @ MurklError::Unauthorized
+    )]
+    pub registration: Box<Account<'info, DepositRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + DepositRecord::INIT_SPACE,
+        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = depositor_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub depositor_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealDepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.pool_kind == PoolKind::Sol @ MurklError::WrongPoolKind
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [DEPOSIT_REGISTRATION_SEED, pool.key().as_ref(), registration.registration_hash.as_ref()],
+        bump = registration.bump,
+        has_one = depositor @ MurklError::Unauthorized
+    )]
+    pub registration: Box<Account<'info, DepositRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + DepositRecord::INIT_SPACE,
+        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireDepositRegistration<'info> {
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [DEPOSIT_REGISTRATION_SEED, registration.pool.as_ref(), registration.registration_hash.as_ref()],
+        bump = registration.bump,
+        has_one = depositor @ MurklError::Unauthorized
+    )]
+    pub registration: Account<'info, DepositRegistration>,
+
+    /// Gets the registration's rent back
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-merkle", pool.key().as_ref()],
+        bump = pool_merkle.bump,
+        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = depositor_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub depositor_token: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    
+    #[account(
+        mut,
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+    
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs).
+    /// Writable when `verifier_program` is passed to close it
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
+    
+    /// Nullifier record - init here prevents replay (PDA collision = already used)
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + NullifierRecord::INIT_SPACE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+    
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub recipient_token: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = relayer_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub relayer_token: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
+
+    /// Pass to record the claim on the recipient's `ClaimReceipt` (the
+    /// relayer pays its rent the first time); the program ID otherwise
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [CLAIM_RECEIPT_SEED, pool.key().as_ref(), recipient_token.owner.as_ref()],
+        bump
+    )]
+    pub claim_receipt: Option<Box<Account<'info, ClaimReceipt>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
+
+    /// CHECK: stark-verifier. Pass to close `verifier_buffer` once the claim
+    /// pays out, refunding its rent to the relayer; the program ID otherwise
+    #[account(address = STARK_VERIFIER_ID)]
+    pub verifier_program: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct ClaimSharded<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
+
+    /// CHECK: the nullifier's `NullifierRecord` PDA; must not exist, so
+    /// nullifiers claimed before the pool switched stay spent
+    #[account(
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        bump,
+        constraint = nullifier_record.data_is_empty() @ MurklError::NullifierAlreadyUsed
+    )]
+    pub nullifier_record: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            NULLIFIER_SHARD_SEED,
+            pool.key().as_ref(),
+            &nullifier_shard_index(&nullifier, pool.nullifier_shard_bits.max(1)).to_le_bytes()
+        ],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Box<Account<'info, NullifierShard>>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub recipient_token: Account<'info, TokenAccount>,
+
+    /// Pays for shard growth
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = relayer_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub relayer_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
+
+    /// Pass to record the claim on the recipient's `ClaimReceipt`; the
+    /// program ID otherwise
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [CLAIM_RECEIPT_SEED, pool.key().as_ref(), recipient_token.owner.as_ref()],
+        bump
+    )]
+    pub claim_receipt: Option<Box<Account<'info, ClaimReceipt>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32])]
+pub struct CheckNullifier<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// CHECK: the nullifier's `NullifierRecord` PDA; only its existence is read
+    #[account(seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()], bump)]
+    pub nullifier_record: UncheckedAccount<'info>,
+
+    /// The nullifier's shard; pass the program ID if there is none
+    #[account(
+        seeds = [
+            NULLIFIER_SHARD_SEED,
+            pool.key().as_ref(),
+            &nullifier_shard_index(&nullifier, pool.nullifier_shard_bits.max(1)).to_le_bytes()
+        ],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Option<Box<Account<'info, NullifierShard>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard: u16)]
+pub struct InitializeNullifierShard<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = NULLIFIER_SHARD_HEADER,
+        seeds = [NULLIFIER_SHARD_SEED, pool.key().as_ref(), &shard.to_le_bytes()],
+        bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeNullifierBloom<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = NULLIFIER_BLOOM_HEADER + NULLIFIER_BLOOM_BYTES,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub nullifier_bloom: Box<Account<'info, NullifierBloom>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct ClaimSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.pool_kind == PoolKind::Sol @ MurklError::WrongPoolKind
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + NullifierRecord::INIT_SPACE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// Wallet the proof pays out to
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+
+    /// Pays for the nullifier record and receives the fee
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
+
+    /// Pass to record the claim on the recipient's `ClaimReceipt` (the
+    /// relayer pays its rent the first time); the program ID otherwise
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [CLAIM_RECEIPT_SEED, pool.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub claim_receipt: Option<Box<Account<'info, ClaimReceipt>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct ClaimAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, MintDepositRecord>,
+
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
+
+    /// Nullifier record - init here prevents replay (PDA collision = already used)
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + NullifierRecord::INIT_SPACE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+
+    /// Vault of the deposit's mint
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), deposit.mint.as_ref()],
+        bump,
+        constraint = pool.accepted_vault(&deposit.mint) == Some(vault.key()) @ MurklError::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
 
-    /// Admin: Unpause pool
-    pub fn unpause_pool(ctx: Context<AdminAction>) -> Result<()> {
-        ctx.accounts.pool.paused = false;
-        msg!("Pool unpaused");
-        Ok(())
-    }
-}
+    #[account(
+        mut,
+        constraint = recipient_token.mint == deposit.mint @ MurklError::InvalidTokenMint
+    )]
+    pub recipient_token: Account<'info, TokenAccount>,
 
-// ============================================================================
-// Accounts
-// ============================================================================
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = relayer_token.mint == deposit.mint @ MurklError::InvalidTokenMint
+    )]
+    pub relayer_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
+
+    /// As in `Claim`
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [CLAIM_RECEIPT_SEED, pool.key().as_ref(), recipient_token.owner.as_ref()],
+        bump
+    )]
+    pub claim_receipt: Option<Box<Account<'info, ClaimReceipt>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
+}
 
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct ClaimWithPath<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    
+    #[account(
+        mut,
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+    
+    /// Shares the STARK claim's nullifier namespace, so a deposit can only
+    /// be claimed once across both modes
     #[account(
         init,
-        payer = admin,
-        space = 8 + GlobalConfig::SIZE,
-        seeds = [CONFIG_SEED],
+        payer = relayer,
+        space = 8 + NullifierRecord::INIT_SPACE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
         bump
     )]
-    pub config: Account<'info, GlobalConfig>,
+    pub nullifier_record: Account<'info, NullifierRecord>,
+    
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token.mint == pool.token_mint @ MurklError::InvalidTokenMint,
+        constraint = recipient_token.owner == recipient.key() @ MurklError::Unauthorized
+    )]
+    pub recipient_token: Account<'info, TokenAccount>,
+    
+    pub recipient: Signer<'info>,
     
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub relayer: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = relayer_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub relayer_token: Account<'info, TokenAccount>,
     
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
 }
 
 #[derive(Accounts)]
-pub struct InitializePool<'info> {
+#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
+pub struct ClaimEpoch<'info> {
     #[account(
-        seeds = [CONFIG_SEED],
-        bump = config.bump,
-        constraint = config.admin == admin.key() @ MurklError::Unauthorized
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump
     )]
-    pub config: Account<'info, GlobalConfig>,
+    pub pool: Box<Account<'info, Pool>>,
     
     #[account(
-        init,
-        payer = admin,
-        space = 8 + Pool::SIZE,
-        seeds = [b"pool", token_mint.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"subscription", pool.key().as_ref(), &subscription.leaf_index.to_le_bytes()],
+        bump = subscription.bump,
+        constraint = subscription.pool == pool.key() @ MurklError::InvalidDepositPool
     )]
-    pub pool: Box<Account<'info, Pool>>,
-    pub token_mint: Account<'info, Mint>,
+    pub subscription: Account<'info, Subscription>,
+    
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
     
+    /// One record per epoch nullifier, in the same namespace as one-shot claims
     #[account(
         init,
-        payer = admin,
-        token::mint = token_mint,
-        token::authority = pool,
-        seeds = [b"vault", pool.key().as_ref()],
+        payer = relayer,
+        space = 8 + NullifierRecord::INIT_SPACE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
         bump
     )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+    
+    #[account(
+        mut,
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
+        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
     pub vault: Account<'info, TokenAccount>,
     
+    #[account(
+        mut,
+        constraint = recipient_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub recipient_token: Account<'info, TokenAccount>,
+    
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub relayer: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = relayer_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+    )]
+    pub relayer_token: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// Required once the pool has stats; pass the program ID otherwise
+    #[account(
+        mut,
+        seeds = [b"pool-stats", pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Option<Box<Account<'info, PoolStats>>>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
+
+    /// Pass to record the claim on the recipient's `ClaimReceipt` (the
+    /// relayer pays its rent the first time); the program ID otherwise
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [CLAIM_RECEIPT_SEED, pool.key().as_ref(), recipient_token.owner.as_ref()],
+        bump
+    )]
+    pub claim_receipt: Option<Box<Account<'info, ClaimReceipt>>>,
+
+    /// Required once the pool keeps a nullifier bloom filter; pass the
+    /// program ID otherwise
+    #[account(
+        mut,
+        seeds = [NULLIFIER_BLOOM_SEED, pool.key().as_ref()],
+        bump = nullifier_bloom.bump
+    )]
+    pub nullifier_bloom: Option<Box<Account<'info, NullifierBloom>>>,
 }
 
 #[derive(Accounts)]
-pub struct InitializePoolMerkle<'info> {
+#[instruction(nullifier: [u8; 32], scope: [u8; 32])]
+pub struct ProveMembershipOnly<'info> {
     #[account(
+        mut,
         seeds = [b"pool", pool.token_mint.as_ref()],
         bump = pool.bump
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
+        bump = deposit.bump,
+        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
+    )]
+    pub deposit: Account<'info, DepositRecord>,
+
+    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
+    #[account(
+        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+    )]
+    pub verifier_buffer: UncheckedAccount<'info>,
+
+    /// One per nullifier and scope (init fails on a repeat). Outside the
+    /// `NullifierRecord` namespace, so it doesn't spend the deposit.
     #[account(
         init,
         payer = payer,
-        space = 8 + PoolMerkle::SIZE,
-        seeds = [b"pool-merkle", pool.key().as_ref()],
+        space = 8 + MembershipRecord::INIT_SPACE,
+        seeds = [MEMBERSHIP_SEED, pool.key().as_ref(), scope.as_ref(), nullifier.as_ref()],
         bump
     )]
-    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
+    pub membership_record: Account<'info, MembershipRecord>,
 
+    /// CHECK: only its key is used, as the recipient the proof is bound to
+    pub member: UncheckedAccount<'info>,
+
+    /// Owner of the finalized buffer; pays the record's rent
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: stark-verifier's VerifierParams PDA, checked in
+    /// `check_verifier_params`. Required once the pool pins verifier params;
+    /// pass the program ID otherwise
+    pub verifier_params: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct Deposit<'info> {
+pub struct AdminAction<'info> {
     #[account(
         mut,
         seeds = [b"pool", pool.token_mint.as_ref()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.admin == admin.key() @ MurklError::Unauthorized
     )]
     pub pool: Box<Account<'info, Pool>>,
+    
+    pub admin: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"pool-merkle", pool.key().as_ref()],
-        bump = pool_merkle.bump,
-        constraint = pool_merkle.pool == pool.key() @ MurklError::InvalidDepositPool
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Account<'info, AdminLog>,
+}
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.admin == admin.key() @ MurklError::Unauthorized
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(address = pool.token_mint @ MurklError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+
+    /// Mint to accept
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = pool,
+        seeds = [b"vault", pool.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Account<'info, AdminLog>,
+}
+
+#[derive(Accounts)]
+pub struct ScheduleVaultRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.admin == admin.key() @ MurklError::Unauthorized,
+        constraint = pool.pool_kind == PoolKind::Token @ MurklError::WrongPoolKind
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VaultRotation::INIT_SPACE,
+        seeds = [VAULT_ROTATION_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub vault_rotation: Account<'info, VaultRotation>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
     )]
-    pub pool_merkle: Box<Account<'info, PoolMerkle>>,
-    
+    pub admin_log: Account<'info, AdminLog>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVaultRotation<'info> {
     #[account(
-        init,
-        payer = depositor,
-        space = 8 + DepositRecord::SIZE,
-        seeds = [b"deposit", pool.key().as_ref(), &pool.leaf_count.to_le_bytes()],
-        bump
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.admin == admin.key() @ MurklError::Unauthorized
     )]
-    pub deposit: Account<'info, DepositRecord>,
-    
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         mut,
-        seeds = [b"vault", pool.key().as_ref()],
-        bump,
-        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
-        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+        close = admin,
+        seeds = [VAULT_ROTATION_SEED, pool.key().as_ref()],
+        bump = vault_rotation.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
-    
+    pub vault_rotation: Account<'info, VaultRotation>,
+
     #[account(mut)]
-    pub depositor: Signer<'info>,
-    
+    pub admin: Signer<'info>,
+
     #[account(
         mut,
-        constraint = depositor_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
     )]
-    pub depositor_token: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub admin_log: Account<'info, AdminLog>,
 }
 
 #[derive(Accounts)]
-#[instruction(relayer_fee: u64, nullifier: [u8; 32])]
-pub struct Claim<'info> {
+pub struct RotateVault<'info> {
     #[account(
+        mut,
         seeds = [b"pool", pool.token_mint.as_ref()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.admin == admin.key() @ MurklError::Unauthorized,
+        constraint = pool.pool_kind == PoolKind::Token @ MurklError::WrongPoolKind
     )]
     pub pool: Box<Account<'info, Pool>>,
-    
+
+    #[account(address = pool.token_mint @ MurklError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [b"deposit", pool.key().as_ref(), &deposit.leaf_index.to_le_bytes()],
-        bump = deposit.bump,
-        constraint = deposit.pool == pool.key() @ MurklError::InvalidDepositPool
-    )]
-    pub deposit: Account<'info, DepositRecord>,
-    
-    /// CHECK: stark-verifier's proof buffer (verified via finalized flag + public inputs)
-    #[account(
-        constraint = verifier_buffer.owner == &STARK_VERIFIER_ID @ MurklError::InvalidVerifierBuffer
+        constraint = vault.key() == pool.vault @ MurklError::InvalidVault
     )]
-    pub verifier_buffer: UncheckedAccount<'info>,
-    
-    /// Nullifier record - init here prevents replay (PDA collision = already used)
+    pub vault: Account<'info, TokenAccount>,
+
     #[account(
         init,
-        payer = relayer,
-        space = 8 + NullifierRecord::SIZE,
-        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        payer = admin,
+        token::mint = token_mint,
+        token::authority = pool,
+        seeds = [b"vault", pool.key().as_ref(), &pool.vault_generation.wrapping_add(1).to_le_bytes()],
         bump
     )]
-    pub nullifier_record: Account<'info, NullifierRecord>,
-    
+    pub new_vault: Account<'info, TokenAccount>,
+
     #[account(
         mut,
-        seeds = [b"vault", pool.key().as_ref()],
-        bump,
-        constraint = vault.key() == pool.vault @ MurklError::InvalidVault,
-        constraint = vault.mint == pool.token_mint @ MurklError::InvalidTokenMint
+        close = admin,
+        seeds = [VAULT_ROTATION_SEED, pool.key().as_ref()],
+        bump = vault_rotation.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
-    
+    pub vault_rotation: Account<'info, VaultRotation>,
+
+    /// The pool's registry entry, if it was registered; kept pointing at
+    /// the live vault
     #[account(
         mut,
-        constraint = recipient_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+        seeds = [REGISTRY_ENTRY_SEED, pool.key().as_ref()],
+        bump = registry_entry.bump
     )]
-    pub recipient_token: Account<'info, TokenAccount>,
-    
+    pub registry_entry: Option<Account<'info, RegistryEntry>>,
+
     #[account(mut)]
-    pub relayer: Signer<'info>,
-    
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
     #[account(
         mut,
-        constraint = relayer_token.mint == pool.token_mint @ MurklError::InvalidTokenMint
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
     )]
-    pub relayer_token: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub admin_log: Account<'info, AdminLog>,
+}
+
+/// Account length of a pool created before `PoolConfig` grew: discriminator,
+/// admin, mint, vault, root, leaf count, min deposit, max fee, paused, bump
+pub const LEGACY_POOL_LEN: usize = 8 + 32 * 4 + 8 + 8 + 2 + 1 + 1;
+
+/// Rewrite a legacy pool, already resized, in the current layout: the
+/// config fields it predates take their defaults and `paused` and `bump`
+/// move past them
+fn migrate_legacy_pool(data: &mut [u8]) -> Result<()> {
+    let config_at = LEGACY_POOL_LEN - (8 + 2 + 1 + 1);
+    let legacy = &data[config_at..LEGACY_POOL_LEN];
+    let config = PoolConfig {
+        min_deposit: u64::from_le_bytes(legacy[..8].try_into().unwrap()),
+        max_relayer_fee_bps: u16::from_le_bytes(legacy[8..10].try_into().unwrap()),
+        ..PoolConfig::default()
+    };
+    let (paused, bump) = (legacy[10] != 0, legacy[11]);
+    let mut tail = &mut data[config_at..];
+    (config, paused, bump).serialize(&mut tail)?;
+    Ok(())
 }
 
+/// Pool is taken raw so accounts written under an older (shorter) layout
+/// can be resized before Anchor tries to deserialize them.
 #[derive(Accounts)]
-pub struct AdminAction<'info> {
+pub struct GrowPool<'info> {
+    /// CHECK: owner checked here; discriminator + admin checked in handler
+    #[account(mut, owner = crate::ID @ MurklError::InvalidPoolAccount)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
     #[account(
         mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump = pool.bump,
-        constraint = pool.admin == admin.key() @ MurklError::Unauthorized
+        seeds = [ADMIN_LOG_SEED, pool.key().as_ref()],
+        bump = admin_log.bump
     )]
-    pub pool: Box<Account<'info, Pool>>,
-    
-    pub admin: Signer<'info>,
+    pub admin_log: Account<'info, AdminLog>,
 }
 
 // ============================================================================
@@ -586,16 +3627,40 @@ pub struct AdminAction<'info> {
 // ============================================================================
 
 #[account]
+#[derive(InitSpace)]
 pub struct GlobalConfig {
     pub admin: Pubkey,
     pub bump: u8,
 }
 
-impl GlobalConfig {
-    pub const SIZE: usize = 32 + 1;
+/// Canonical list of official pools
+#[account]
+#[derive(InitSpace)]
+pub struct PoolRegistry {
+    /// Entries registered so far (next entry's index)
+    pub pool_count: u64,
+    pub bump: u8,
+}
+
+/// One official pool; PDA of [REGISTRY_ENTRY_SEED, pool]
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryEntry {
+    /// Registration order, 0-based
+    pub index: u64,
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub vault: Pubkey,
+    /// Pool's minimum deposit at registration
+    pub denomination: u64,
+    /// See [`verifier_config_hash`]
+    pub verifier_config_hash: [u8; 32],
+    pub registered_at: i64,
+    pub bump: u8,
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct Pool {
     pub admin: Pubkey,
     pub token_mint: Pubkey,
@@ -605,71 +3670,629 @@ pub struct Pool {
     pub config: PoolConfig,
     pub paused: bool,
     pub bump: u8,
+    /// Allow `claim_with_path` (reveals the deposit link; off by default)
+    pub path_claims_enabled: bool,
+    /// Slots a finalized verifier buffer stays claimable; 0 = no expiry
+    pub max_proof_age_slots: u64,
+    /// A `PoolStats` PDA exists and must be passed to deposit and claim
+    pub stats_enabled: bool,
+    /// Epoch length for subscription deposits; 0 = subscriptions disabled
+    pub epoch_duration_secs: i64,
+    /// Claims emit `MinimalClaimEvent` instead of `ClaimEvent`
+    pub minimal_events: bool,
+    /// Sequence number of the last event this pool emitted
+    pub event_seq: u64,
+    /// Ring of the roots `merkle_root` replaced, newest at `root_cursor`;
+    /// all zero on a fresh or newly grown pool
+    pub root_history: [[u8; 32]; ROOT_HISTORY_SIZE],
+    pub root_cursor: u8,
+    /// How many of those roots claims accept; 0 on a grown pool until set
+    pub root_history_window: u8,
+    /// What the vault holds; pools grown from older layouts read as `Token`
+    pub pool_kind: PoolKind,
+    /// Leading nullifier bits naming a `NullifierShard`; 0 = no registry,
+    /// claims create `NullifierRecord` PDAs
+    pub nullifier_shard_bits: u8,
+    /// Slots a `DepositRegistration` stays revealable; 0 = deposits go in
+    /// directly, otherwise only through a registration
+    pub deposit_reveal_slots: u64,
+    /// Mints taken at par with `token_mint`, each in its own vault; the
+    /// first `accepted_mint_count` are in use. None makes a one-mint pool
+    pub accepted_mints: [AcceptedMint; MAX_ACCEPTED_MINTS],
+    pub accepted_mint_count: u8,
+    /// A `NullifierBloom` PDA exists and must be passed to every claim
+    pub nullifier_bloom_enabled: bool,
+    /// Rotations `vault` has been through; from 1 it is the PDA of
+    /// ["vault", pool, vault_generation (u16 LE)]
+    pub vault_generation: u16,
+    /// `SecurityLevel` byte the published verifier params must guarantee;
+    /// None = any level
+    pub min_security_level: Option<u8>,
+}
+
+/// A mint a multi-mint pool accepts besides its `token_mint`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    /// Token account of `mint` owned by the pool; PDA of ["vault", pool, mint]
+    pub vault: Pubkey,
+}
+
+/// Asset a pool holds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub enum PoolKind {
+    /// SPL tokens of `token_mint` in a token-account vault
+    #[default]
+    Token,
+    /// Lamports in a system-owned vault PDA; `token_mint` is [`SOL_POOL_MINT`]
+    Sol,
+}
+
+impl Pool {
+    /// State of a newly created pool: empty tree, full root history window
+    fn reset(&mut self, config: PoolConfig, bump: u8) {
+        self.merkle_root = empty_hashes()[MERKLE_DEPTH];
+        self.root_history = [[0u8; 32]; ROOT_HISTORY_SIZE];
+        self.root_cursor = 0;
+        self.root_history_window = ROOT_HISTORY_SIZE as u8;
+        self.leaf_count = 0;
+        self.config = config;
+        self.paused = false;
+        self.bump = bump;
+        self.path_claims_enabled = false;
+        self.max_proof_age_slots = 0;
+        self.minimal_events = false;
+        self.event_seq = 0;
+        self.nullifier_shard_bits = 0;
+        self.deposit_reveal_slots = 0;
+        self.accepted_mints = [AcceptedMint::default(); MAX_ACCEPTED_MINTS];
+        self.accepted_mint_count = 0;
+        self.vault_generation = 0;
+        self.min_security_level = None;
+    }
+
+    /// Vault of `mint` if the pool accepts it besides `token_mint`
+    pub fn accepted_vault(&self, mint: &Pubkey) -> Option<Pubkey> {
+        let count = (self.accepted_mint_count as usize).min(MAX_ACCEPTED_MINTS);
+        self.accepted_mints[..count]
+            .iter()
+            .find(|accepted| accepted.mint == *mint)
+            .map(|accepted| accepted.vault)
+    }
+
+    /// Make `root` current, keeping the one it replaces in `root_history`
+    fn push_root(&mut self, root: [u8; 32]) {
+        let cursor = (self.root_cursor as usize + 1) % ROOT_HISTORY_SIZE;
+        self.root_history[cursor] = self.merkle_root;
+        self.root_cursor = cursor as u8;
+        self.merkle_root = root;
+    }
+
+    /// `root` is current or one of the last `root_history_window` roots
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        if *root == self.merkle_root {
+            return true;
+        }
+        // Zero slots are unused; no tree hashes to all zeros
+        let window = (self.root_history_window as usize).min(ROOT_HISTORY_SIZE);
+        *root != [0u8; 32]
+            && (0..window).any(|back| {
+                let slot = (self.root_cursor as usize + ROOT_HISTORY_SIZE - back) % ROOT_HISTORY_SIZE;
+                self.root_history[slot] == *root
+            })
+    }
+}
+
+/// Separate PDA to store the incremental Merkle frontier.
+///
+/// Kept out of `Pool` to avoid Anchor stack-frame limits. `branch[level]`
+/// is the last left child written at that level; with the zero-subtree
+/// hashes it is all an append needs. Paths for the prover are rebuilt
+/// off-chain from the leaves in [`DepositEvent`]s.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolMerkle {
+    pub pool: Pubkey,
+    pub branch: [[u8; 32]; MERKLE_DEPTH],
+    pub bump: u8,
+}
+
+impl PoolMerkle {
+    /// Append `leaf` at `leaf_index` (the pool's leaf count); returns the new root
+    pub fn insert_leaf(&mut self, leaf_index: u64, leaf: &[u8; 32]) -> Result<[u8; 32]> {
+        require!(leaf_index >> MERKLE_DEPTH == 0, MurklError::MerkleTreeFull);
+        Ok(merkle_append(&mut self.branch, leaf_index, leaf))
+    }
+}
+
+/// Privacy health of a pool, readable on-chain without an indexer.
+///
+/// Deposits are dated to the day from `day_start_leaf`, a ring of the leaf
+/// count at the start of each of the last [`STATS_DAYS`] days, so
+/// `DepositRecord` needs no timestamp. PDA of ["pool-stats", pool].
+#[account]
+#[derive(InitSpace)]
+pub struct PoolStats {
+    pub pool: Pubkey,
+    /// First leaf these stats saw deposited; older deposits are untracked
+    pub tracked_from_leaf: u64,
+    /// Day (unix time / 86400) the stats were created
+    pub tracked_since_day: i64,
+    /// Tracked deposits not yet claimed: the pool's anonymity set
+    pub active_unclaimed: u64,
+    pub total_deposits: u64,
+    pub total_claims: u64,
+    /// Claims by deposit age in days, bucketed by [`AGE_BUCKET_DAYS`]
+    pub claims_by_age: [u64; AGE_BUCKETS],
+    /// Claims of deposits made before the stats existed
+    pub claims_untracked: u64,
+    /// Newest day recorded in `day_start_leaf`
+    pub current_day: i64,
+    /// Leaf count at the start of day d, at index d % STATS_DAYS
+    pub day_start_leaf: [u64; STATS_DAYS],
+    pub last_deposit_at: i64,
+    pub last_claim_at: i64,
+    pub bump: u8,
+}
+
+impl PoolStats {
+    fn slot(day: i64) -> usize {
+        day.rem_euclid(STATS_DAYS as i64) as usize
+    }
+
+    /// Advance to `day`, whose start saw `leaf_count` leaves
+    fn roll(&mut self, day: i64, leaf_count: u64) {
+        if day <= self.current_day {
+            return;
+        }
+        let first = (self.current_day + 1).max(day - STATS_DAYS as i64 + 1);
+        for d in first..=day {
+            self.day_start_leaf[Self::slot(d)] = leaf_count;
+        }
+        self.current_day = day;
+    }
+
+    /// Whole days since `leaf_index` was deposited; `None` if untracked.
+    /// Deposits older than the ring report [`STATS_DAYS`].
+    pub fn deposit_age_days(&self, leaf_index: u64) -> Option<u64> {
+        if leaf_index < self.tracked_from_leaf {
+            return None;
+        }
+        let oldest = self.tracked_since_day.max(self.current_day - STATS_DAYS as i64 + 1);
+        let day = (oldest..=self.current_day)
+            .rev()
+            .find(|&d| self.day_start_leaf[Self::slot(d)] <= leaf_index);
+        Some(day.map_or(STATS_DAYS as u64, |d| (self.current_day - d) as u64))
+    }
+
+    pub fn record_deposit(&mut self, now: i64, leaf_index: u64) {
+        self.roll(now.div_euclid(SECONDS_PER_DAY), leaf_index);
+        self.active_unclaimed = self.active_unclaimed.saturating_add(1);
+        self.total_deposits = self.total_deposits.saturating_add(1);
+        self.last_deposit_at = now;
+    }
+
+    pub fn record_claim(&mut self, now: i64, leaf_index: u64, leaf_count: u64) {
+        self.roll(now.div_euclid(SECONDS_PER_DAY), leaf_count);
+        self.total_claims = self.total_claims.saturating_add(1);
+        self.last_claim_at = now;
+        match self.deposit_age_days(leaf_index) {
+            Some(age) => {
+                let bucket = AGE_BUCKET_DAYS.iter().take_while(|&&bound| age >= bound).count();
+                self.claims_by_age[bucket] = self.claims_by_age[bucket].saturating_add(1);
+                self.active_unclaimed = self.active_unclaimed.saturating_sub(1);
+            }
+            None => self.claims_untracked = self.claims_untracked.saturating_add(1),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct PoolConfig {
+    pub min_deposit: u64,
+    pub max_relayer_fee_bps: u16,
+    /// Program CPI'd with (recipient, amount) before payout; None = permissionless
+    pub compliance_hook: Option<Pubkey>,
+    /// stark-verifier params hash claims require; None = any parameters
+    pub required_verifier_params_hash: Option<[u8; 32]>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            min_deposit: MIN_DEPOSIT_AMOUNT,
+            max_relayer_fee_bps: MAX_RELAYER_FEE_BPS,
+            compliance_hook: None,
+            required_verifier_params_hash: None,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRecord {
+    pub pool: Pubkey,
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub leaf_index: u64,
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+impl DepositRecord {
+    fn init(&mut self, pool: Pubkey, commitment: [u8; 32], amount: u64, leaf_index: u64, bump: u8) {
+        self.pool = pool;
+        self.commitment = commitment;
+        self.amount = amount;
+        self.leaf_index = leaf_index;
+        self.claimed = false;
+        self.bump = bump;
+    }
+}
+
+/// A `deposit_accepted_mint` deposit: a `DepositRecord` plus its mint
+///
+/// Same PDA as a `DepositRecord`, ["deposit", pool, leaf_index], and the
+/// same layout up to `mint`, but its own account type, so `claim` can't pay
+/// it out of the `token_mint` vault.
+#[account]
+#[derive(InitSpace)]
+pub struct MintDepositRecord {
+    pub pool: Pubkey,
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub leaf_index: u64,
+    pub claimed: bool,
+    pub bump: u8,
+    /// One of the pool's `accepted_mints`
+    pub mint: Pubkey,
+}
+
+impl MintDepositRecord {
+    fn init(&mut self, pool: Pubkey, commitment: [u8; 32], amount: u64, leaf_index: u64, bump: u8, mint: Pubkey) {
+        self.pool = pool;
+        self.commitment = commitment;
+        self.amount = amount;
+        self.leaf_index = leaf_index;
+        self.claimed = false;
+        self.bump = bump;
+        self.mint = mint;
+    }
+}
+
+/// A deposit announced by its [`deposit_registration_hash`], waiting for
+/// its reveal.
+///
+/// PDA of ["deposit-registration", pool, registration_hash]. Closed to the
+/// depositor by the reveal, or by anyone once `expires_slot` has passed.
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRegistration {
+    pub pool: Pubkey,
+    /// Only this signer can reveal and fund it
+    pub depositor: Pubkey,
+    pub registration_hash: [u8; 32],
+    pub registered_slot: u64,
+    /// Last slot a reveal is accepted
+    pub expires_slot: u64,
+    pub bump: u8,
+}
+
+impl DepositRegistration {
+    /// Accept a reveal of `commitment` and `salt` at `slot`: after the
+    /// registration's slot, within its window, matching its hash
+    pub fn check_reveal(&self, commitment: &[u8; 32], salt: &[u8; 32], slot: u64) -> Result<()> {
+        require!(slot > self.registered_slot, MurklError::RevealTooEarly);
+        require!(slot <= self.expires_slot, MurklError::RegistrationExpired);
+        require!(
+            deposit_registration_hash(commitment, salt) == self.registration_hash,
+            MurklError::RegistrationMismatch
+        );
+        Ok(())
+    }
+}
+
+/// A deposit paid out one epoch at a time.
+///
+/// PDA of ["subscription", pool, leaf_index]. It stands in for the leaf's
+/// `DepositRecord`, so `claim` and `claim_with_path` can't take the whole
+/// amount; each epoch is claimed with `claim_epoch` and its own nullifier.
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub pool: Pubkey,
+    pub commitment: [u8; 32],
+    pub leaf_index: u64,
+    /// Paid out per claimed epoch, relayer fee included
+    pub amount_per_epoch: u64,
+    pub epochs: u8,
+    /// Copied from the pool at deposit, so later changes don't apply
+    pub epoch_duration_secs: i64,
+    /// Epoch e opens at started_at + e * epoch_duration_secs
+    pub started_at: i64,
+    /// Bit e is set once epoch e has been claimed
+    pub claimed_epochs: u64,
+    pub bump: u8,
+}
+
+impl Subscription {
+    /// Whether `epoch` exists, has opened by `now` and is still unclaimed
+    pub fn check_claimable(&self, epoch: u32, now: i64) -> Result<()> {
+        require!(epoch < self.epochs as u32, MurklError::EpochOutOfRange);
+        let opened = now.saturating_sub(self.started_at) / self.epoch_duration_secs.max(1);
+        require!(epoch as i64 <= opened, MurklError::EpochNotOpen);
+        require!(self.claimed_epochs & (1 << epoch) == 0, MurklError::AlreadyClaimed);
+        Ok(())
+    }
+
+    pub fn is_fully_claimed(&self) -> bool {
+        self.claimed_epochs.count_ones() == self.epochs as u32
+    }
+}
+
+/// Public record that a wallet has claimed from a pool.
+///
+/// PDA of ["claim-receipt", pool, recipient], written by claims that pass
+/// it. It holds slots and a count, not the nullifier or amount, so it shows
+/// a claim happened without pointing at the deposit behind it. It has no
+/// authority and can't be moved, so it works as a soulbound attestation for
+/// airdrop campaigns.
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimReceipt {
+    pub pool: Pubkey,
+    /// Wallet paid out: the owner of the recipient token account, or the
+    /// recipient itself on a SOL pool
+    pub recipient: Pubkey,
+    pub first_claim_slot: u64,
+    pub last_claim_slot: u64,
+    pub claims: u32,
+    pub bump: u8,
+}
+
+impl ClaimReceipt {
+    /// Count a claim at `slot`, filling in the receipt on its first one
+    pub fn record(&mut self, pool: Pubkey, recipient: Pubkey, bump: Option<u8>, slot: u64) -> Result<()> {
+        if self.claims == 0 {
+            self.pool = pool;
+            self.recipient = recipient;
+            self.first_claim_slot = slot;
+            self.bump = bump.unwrap_or_default();
+        }
+        self.last_claim_slot = slot;
+        self.claims = self.claims.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Hash chain over a pool's admin actions. PDA of ["admin-log", pool].
+///
+/// Each pool admin instruction moves `head` to [`admin_log_entry_hash`] of
+/// the old head and the action, and logs the link as an [`AdminLogEntry`].
+/// Replaying the logged entries from the zero hash has to reach `head` in
+/// `entries` steps, so an auditor holding them can show that none was
+/// dropped, altered or reordered.
+#[account]
+#[derive(InitSpace)]
+pub struct AdminLog {
+    pub pool: Pubkey,
+    /// Hash of the newest entry; zero while the log is empty
+    pub head: [u8; 32],
+    pub entries: u64,
+    pub bump: u8,
+}
+
+impl AdminLog {
+    /// Chain an action into the log and return the entry to emit
+    pub fn record(
+        &mut self,
+        action: AdminLogAction,
+        args_hash: [u8; 32],
+        actor: Pubkey,
+        slot: u64,
+    ) -> Result<AdminLogEntry> {
+        let prev_hash = self.head;
+        self.head = admin_log_entry_hash(&self.pool, &prev_hash, action, &args_hash, &actor, slot);
+        self.entries = self.entries.checked_add(1).ok_or(MurklError::MathOverflow)?;
+        Ok(AdminLogEntry {
+            pool: self.pool,
+            seq: self.entries,
+            prev_hash,
+            action,
+            args_hash,
+            actor,
+            slot,
+            hash: self.head,
+        })
+    }
+}
+
+/// Pool admin instruction recorded in an [`AdminLog`]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AdminLogAction {
+    SetComplianceHook,
+    SetPathClaims,
+    SetMinimalEvents,
+    SetMaxProofAge,
+    SetRootHistoryWindow,
+    SetRequiredVerifierParams,
+    SetNullifierShardBits,
+    SetEpochDuration,
+    SetDepositRevealWindow,
+    GrowPool,
+    PausePool,
+    UnpausePool,
+    AddAcceptedMint,
+    SetMinSecurityLevel,
+    ScheduleVaultRotation,
+    CancelVaultRotation,
+    RotateVault,
+}
+
+/// A pending `rotate_vault`, PDA of ["vault-rotation", pool]. At most one
+/// per pool; carrying it out or cancelling it closes the account.
+#[account]
+#[derive(InitSpace)]
+pub struct VaultRotation {
+    pub pool: Pubkey,
+    pub scheduled_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
 }
 
-impl Pool {
-    pub const SIZE: usize = 32 + 32 + 32 + 32 + 8 + PoolConfig::SIZE + 1 + 1;
+/// One shard of a pool's nullifier registry.
+///
+/// PDA of ["nullifier-shard", pool, shard]. The account data continues
+/// past this header with the shard's nullifiers, 32 bytes each, sorted
+/// ascending; their count is `(data_len - NULLIFIER_SHARD_HEADER) / 32`.
+/// Each one costs 32 bytes of rent instead of a `NullifierRecord` account.
+#[account]
+#[derive(InitSpace)]
+pub struct NullifierShard {
+    pub pool: Pubkey,
+    pub shard: u16,
+    pub bump: u8,
 }
 
-/// Separate PDA to store the incremental Merkle frontier.
+/// Bloom filter of a pool's claimed nullifiers, so a wallet can check all
+/// its deposits against one account fetch. PDA of ["nullifier-bloom", pool].
+///
+/// The account data continues past this header with the filter,
+/// [`NULLIFIER_BLOOM_BYTES`] bytes, where each claim sets the bits of
+/// [`nullifier_bloom_bits`]. It misses no claim made since it was created,
+/// so a deposit from `tracked_from_leaf` on whose nullifier misses is
+/// unspent. A hit only means maybe: confirm it with `check_nullifier` or
+/// the nullifier's PDA. With 4 bits set per claim out of 2^16, the false
+/// positive rate after n claims is about (1 - e^(-4n/65536))^4:
+///
+/// | claims | false positives |
+/// |--------|-----------------|
+/// | 1,000  | 0.001%          |
+/// | 5,000  | 0.5%            |
+/// | 10,000 | 4.4%            |
+/// | 20,000 | 25%             |
 ///
-/// Kept out of `Pool` to avoid Anchor stack-frame limits.
+/// A nullifier is chosen by its depositor, who could grind secrets to hit
+/// set bits; that only costs lookups, never a missed claim.
 #[account]
-pub struct PoolMerkle {
+#[derive(InitSpace)]
+pub struct NullifierBloom {
     pub pool: Pubkey,
-    pub branch: [[u8; 32]; MERKLE_DEPTH],
+    /// Pool leaf count when the filter was created; claims of earlier
+    /// deposits before then are not in it
+    pub tracked_from_leaf: u64,
+    /// Claims added, for estimating the false positive rate
+    pub inserted: u64,
     pub bump: u8,
 }
 
-impl PoolMerkle {
-    pub const SIZE: usize = 32 + (32 * MERKLE_DEPTH) + 1;
+/// Nullifier tracking - prevents replay attacks
+/// PDA derived from pool + nullifier ensures uniqueness
+#[account]
+#[derive(InitSpace)]
+pub struct NullifierRecord {
+    pub pool: Pubkey,
+    pub nullifier: [u8; 32],
+    pub claimed_at: i64,
+    pub bump: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
-pub struct PoolConfig {
-    pub min_deposit: u64,
-    pub max_relayer_fee_bps: u16,
+/// A deposit's membership proven under `scope`, without a claim. PDA of
+/// ["membership", pool, scope, nullifier].
+#[account]
+#[derive(InitSpace)]
+pub struct MembershipRecord {
+    pub pool: Pubkey,
+    /// Namespace picked by the calling protocol, e.g. a proposal id
+    pub scope: [u8; 32],
+    pub nullifier: [u8; 32],
+    /// Key the proof was bound to
+    pub member: Pubkey,
+    pub proven_at: i64,
+    pub bump: u8,
 }
 
-impl PoolConfig {
-    pub const SIZE: usize = 8 + 2;
-}
+// ============================================================================
+// Events
+// ============================================================================
 
-impl Default for PoolConfig {
-    fn default() -> Self {
-        Self {
-            min_deposit: MIN_DEPOSIT_AMOUNT,
-            max_relayer_fee_bps: MAX_RELAYER_FEE_BPS,
-        }
-    }
+/// A claim paid out. `event_seq` counts up per pool, so gaps in a feed show
+/// missed events.
+/// A leaf was appended to the pool's commitment tree. Indexers replay
+/// these to rebuild the tree and the Merkle paths provers need.
+#[event]
+pub struct DepositEvent {
+    pub pool: Pubkey,
+    pub event_seq: u64,
+    pub leaf_index: u64,
+    pub commitment: [u8; 32],
+    /// Pool root after this leaf
+    pub merkle_root: [u8; 32],
 }
 
-#[account]
-pub struct DepositRecord {
+#[event]
+pub struct ClaimEvent {
     pub pool: Pubkey,
-    pub commitment: [u8; 32],
+    pub event_seq: u64,
+    pub nullifier: [u8; 32],
+    /// Paid to the recipient, after the relayer fee
     pub amount: u64,
-    pub leaf_index: u64,
-    pub claimed: bool,
-    pub bump: u8,
+    pub relayer_fee: u64,
 }
 
-impl DepositRecord {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1 + 1;
+/// A claim on a pool with `minimal_events` set: no nullifier or amount in
+/// the logs. The transaction's `NullifierRecord` still holds the nullifier.
+#[event]
+pub struct MinimalClaimEvent {
+    pub pool: Pubkey,
+    pub event_seq: u64,
 }
 
-/// Nullifier tracking - prevents replay attacks
-/// PDA derived from pool + nullifier ensures uniqueness
-#[account]
-pub struct NullifierRecord {
+/// A pool admin action, as chained into the pool's [`AdminLog`]
+#[event]
+pub struct AdminLogEntry {
     pub pool: Pubkey,
-    pub nullifier: [u8; 32],
-    pub claimed_at: i64,
-    pub bump: u8,
+    /// Position in the log, from 1
+    pub seq: u64,
+    pub prev_hash: [u8; 32],
+    pub action: AdminLogAction,
+    /// keccak of the instruction's Borsh-encoded arguments
+    pub args_hash: [u8; 32],
+    pub actor: Pubkey,
+    pub slot: u64,
+    /// The log's head after this entry
+    pub hash: [u8; 32],
 }
 
-impl NullifierRecord {
-    pub const SIZE: usize = 32 + 32 + 8 + 1;
+/// A membership proven with `prove_membership_only`. Numbered with the
+/// pool's other events; the nullifier is in the `MembershipRecord`.
+#[event]
+pub struct MembershipEvent {
+    pub pool: Pubkey,
+    pub event_seq: u64,
+    pub scope: [u8; 32],
+}
+
+#[event]
+pub struct VaultRotationScheduled {
+    pub pool: Pubkey,
+    pub event_seq: u64,
+    /// Earliest `rotate_vault` can run, unix seconds
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct VaultRotated {
+    pub pool: Pubkey,
+    pub event_seq: u64,
+    /// Closed by the rotation
+    pub old_vault: Pubkey,
+    pub new_vault: Pubkey,
+    /// The pool's new `vault_generation`
+    pub generation: u16,
+    /// Tokens moved across
+    pub amount: u64,
 }
 
 // ============================================================================
@@ -725,6 +4348,111 @@ pub enum MurklError {
 
     #[msg("Arithmetic overflow/underflow")]
     MathOverflow,
+
+    #[msg("Account is not a pool")]
+    InvalidPoolAccount,
+
+    #[msg("Compliance hook program missing from remaining accounts")]
+    ComplianceHookMissing,
+
+    #[msg("Claim rejected by compliance hook")]
+    ComplianceRejected,
+
+    #[msg("Path claims are not enabled for this pool")]
+    PathClaimsDisabled,
+
+    #[msg("Merkle path does not lead to the pool root")]
+    InvalidMerklePath,
+
+    #[msg("Proof expired - re-verify the buffer before claiming")]
+    ProofExpired,
+
+    #[msg("Pool tracks statistics - pass its PoolStats account")]
+    PoolStatsMissing,
+
+    #[msg("Subscription deposits are disabled for this pool")]
+    SubscriptionsDisabled,
+
+    #[msg("Subscription epoch count out of range")]
+    InvalidEpochCount,
+
+    #[msg("Epoch duration must not be negative")]
+    InvalidEpochDuration,
+
+    #[msg("Nullifier epoch is beyond the subscription")]
+    EpochOutOfRange,
+
+    #[msg("Subscription epoch has not started yet")]
+    EpochNotOpen,
+
+    #[msg("Commitment tree is full")]
+    MerkleTreeFull,
+
+    #[msg("Root history window exceeds the pool's root history")]
+    InvalidRootHistoryWindow,
+
+    #[msg("Pool pins verifier params but the VerifierParams account is missing or invalid")]
+    VerifierParamsMissing,
+
+    #[msg("Verifier params differ from the ones this pool pins")]
+    VerifierParamsMismatch,
+
+    #[msg("Instruction does not match the pool's asset kind")]
+    WrongPoolKind,
+
+    #[msg("Pool has no nullifier registry")]
+    NullifierRegistryDisabled,
+
+    #[msg("Nullifier registry is already configured")]
+    NullifierRegistryLocked,
+
+    #[msg("Nullifier shard out of range")]
+    InvalidNullifierShard,
+
+    #[msg("Nullifier already used")]
+    NullifierAlreadyUsed,
+
+    #[msg("Pool takes deposits only through register_deposit and a reveal")]
+    DepositRevealRequired,
+
+    #[msg("Pool has no deposit reveal window")]
+    DepositRevealDisabled,
+
+    #[msg("Deposit reveal window exceeds MAX_DEPOSIT_REVEAL_SLOTS")]
+    InvalidRevealWindow,
+
+    #[msg("Deposit reveal must land after its registration's slot")]
+    RevealTooEarly,
+
+    #[msg("Deposit registration expired")]
+    RegistrationExpired,
+
+    #[msg("Commitment and salt don't match the registration")]
+    RegistrationMismatch,
+
+    #[msg("Deposit registration is still revealable")]
+    RegistrationNotExpired,
+
+    #[msg("Mint is already accepted by this pool")]
+    MintAlreadyAccepted,
+
+    #[msg("Pool accepts MAX_ACCEPTED_MINTS mints already")]
+    TooManyAcceptedMints,
+
+    #[msg("Accepted mints must have the pool mint's decimals")]
+    MintDecimalsMismatch,
+
+    #[msg("Not a security level")]
+    InvalidSecurityLevel,
+
+    #[msg("Published verifier params are below the pool's minimum security level")]
+    VerifierBelowSecurityLevel,
+
+    #[msg("Pool keeps a nullifier bloom filter - pass its NullifierBloom account")]
+    NullifierBloomMissing,
+
+    #[msg("Vault rotation is still in its timelock")]
+    VaultRotationLocked,
 }
 
 // ============================================================================
@@ -738,6 +4466,28 @@ mod tests {
     use rand::{RngCore, SeedableRng};
     use rand::rngs::StdRng;
 
+    /// Root from the frontier alone, for checking `merkle_append`
+    fn merkle_root(branch: &[[u8; 32]; MERKLE_DEPTH], leaf_count: u64) -> [u8; 32] {
+        let empties = empty_hashes();
+        let mut acc = [0u8; 32];
+        let mut idx = leaf_count as usize;
+
+        for level in 0..MERKLE_DEPTH {
+            if idx & 1 == 1 {
+                acc = hash_pair(&branch[level], &acc);
+            } else {
+                acc = hash_pair(&acc, &empties[level]);
+            }
+            idx >>= 1;
+        }
+
+        acc
+    }
+
+    fn verify_merkle_path(leaf: &[u8; 32], index: u64, path: &[[u8; 32]], root: &[u8; 32]) -> bool {
+        merkle_path_root(leaf, index, path).as_ref() == Some(root)
+    }
+
     fn naive_root(leaves: &[[u8; 32]]) -> [u8; 32] {
         let empty = empty_hashes();
 
@@ -766,6 +4516,15 @@ mod tests {
         root
     }
 
+    #[test]
+    fn empty_subtrees_are_pairs_of_the_level_below() {
+        let empties = empty_hashes();
+        assert_eq!(empties[0], [0; 32]);
+        for level in 1..=MERKLE_DEPTH {
+            assert_eq!(empties[level], hash_pair(&empties[level - 1], &empties[level - 1]), "level {level}");
+        }
+    }
+
     #[test]
     fn merkle_append_matches_naive_for_random_sequences() {
         let mut rng = StdRng::seed_from_u64(0xC0FFEE);
@@ -789,4 +4548,685 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn pool_init_space_covers_largest_encoding() {
+        let pool = Pool {
+            admin: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            merkle_root: [1u8; 32],
+            leaf_count: u64::MAX,
+            config: PoolConfig {
+                compliance_hook: Some(Pubkey::new_unique()),
+                required_verifier_params_hash: Some([2u8; 32]),
+                ..PoolConfig::default()
+            },
+            paused: true,
+            bump: 255,
+            path_claims_enabled: true,
+            max_proof_age_slots: 0,
+            stats_enabled: true,
+            epoch_duration_secs: i64::MAX,
+            minimal_events: true,
+            event_seq: u64::MAX,
+            root_history: [[1u8; 32]; ROOT_HISTORY_SIZE],
+            root_cursor: u8::MAX,
+            root_history_window: u8::MAX,
+            pool_kind: PoolKind::Sol,
+            nullifier_shard_bits: MAX_NULLIFIER_SHARD_BITS,
+            deposit_reveal_slots: MAX_DEPOSIT_REVEAL_SLOTS,
+            accepted_mints: [AcceptedMint {
+                mint: Pubkey::new_unique(),
+                vault: Pubkey::new_unique(),
+            }; MAX_ACCEPTED_MINTS],
+            accepted_mint_count: MAX_ACCEPTED_MINTS as u8,
+            nullifier_bloom_enabled: true,
+            vault_generation: 0,
+            min_security_level: Some(u8::MAX),
+        };
+        assert_eq!(pool.try_to_vec().unwrap().len(), Pool::INIT_SPACE);
+    }
+
+    #[test]
+    fn legacy_pool_migrates_to_current_layout() {
+        /// `Pool` as the program first shipped it
+        #[derive(AnchorSerialize)]
+        struct LegacyPool {
+            admin: Pubkey,
+            token_mint: Pubkey,
+            vault: Pubkey,
+            merkle_root: [u8; 32],
+            leaf_count: u64,
+            min_deposit: u64,
+            max_relayer_fee_bps: u16,
+            paused: bool,
+            bump: u8,
+        }
+        let legacy = LegacyPool {
+            admin: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            merkle_root: [7; 32],
+            leaf_count: 42,
+            min_deposit: 1_000,
+            max_relayer_fee_bps: 50,
+            paused: true,
+            bump: 254,
+        };
+        let mut data = <Pool as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), LEGACY_POOL_LEN);
+
+        // What grow_pool's realloc leaves: the old bytes, then zeros
+        data.resize(8 + Pool::INIT_SPACE, 0);
+        migrate_legacy_pool(&mut data).unwrap();
+        let pool = Pool::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!((pool.admin, pool.token_mint, pool.vault), (legacy.admin, legacy.token_mint, legacy.vault));
+        assert_eq!((pool.merkle_root, pool.leaf_count), (legacy.merkle_root, legacy.leaf_count));
+        assert_eq!((pool.config.min_deposit, pool.config.max_relayer_fee_bps), (1_000, 50));
+        assert_eq!(pool.config.compliance_hook, None);
+        assert_eq!(pool.config.required_verifier_params_hash, None);
+        assert_eq!(pool.min_security_level, None);
+        assert_eq!((pool.paused, pool.bump), (true, 254));
+        assert_eq!((pool.root_history_window, pool.pool_kind, pool.vault_generation), (0, PoolKind::Token, 0));
+
+        // Without the migration the old paused and bump bytes are read as
+        // the config's Option tags: here a compliance hook out of nowhere
+        let mut unmigrated = <Pool as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut unmigrated).unwrap();
+        unmigrated.resize(8 + Pool::INIT_SPACE, 0);
+        let misread = Pool::try_deserialize(&mut &unmigrated[..]).ok().map(|pool| pool.config.compliance_hook);
+        assert_ne!(misread, Some(None));
+    }
+
+    #[test]
+    fn accepted_mint_deposits_keep_the_deposit_record_layout() {
+        let mut pool = Pool::try_deserialize_unchecked(&mut &[0u8; 8 + Pool::INIT_SPACE][..]).unwrap();
+        let (mint, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(pool.accepted_vault(&mint), None);
+        pool.accepted_mints[0] = AcceptedMint { mint, vault };
+        // Entries past the count are not accepted yet
+        assert_eq!(pool.accepted_vault(&mint), None);
+        pool.accepted_mint_count = 1;
+        assert_eq!(pool.accepted_vault(&mint), Some(vault));
+        assert_eq!(pool.accepted_vault(&Pubkey::default()), None);
+
+        let mut record = MintDepositRecord::try_deserialize_unchecked(&mut &[0u8; 8 + MintDepositRecord::INIT_SPACE][..]).unwrap();
+        record.init(Pubkey::new_unique(), [3; 32], 500, 9, 255, mint);
+        let mut plain = DepositRecord::try_deserialize_unchecked(&mut &[0u8; 8 + DepositRecord::INIT_SPACE][..]).unwrap();
+        plain.init(record.pool, record.commitment, record.amount, record.leaf_index, record.bump);
+
+        // Off-chain readers of the plain layout read an accepted-mint record alike
+        let bytes = record.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), MintDepositRecord::INIT_SPACE);
+        assert_eq!(bytes[..DepositRecord::INIT_SPACE], plain.try_to_vec().unwrap()[..]);
+        assert_eq!(bytes[DepositRecord::INIT_SPACE..], mint.to_bytes());
+        assert_ne!(
+            <MintDepositRecord as anchor_lang::Discriminator>::DISCRIMINATOR,
+            <DepositRecord as anchor_lang::Discriminator>::DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn deposit_reveal_needs_later_slot_within_window() {
+        let (commitment, salt) = ([3u8; 32], [4u8; 32]);
+        let registration = DepositRegistration {
+            pool: Pubkey::new_unique(),
+            depositor: Pubkey::new_unique(),
+            registration_hash: deposit_registration_hash(&commitment, &salt),
+            registered_slot: 100,
+            expires_slot: 150,
+            bump: 255,
+        };
+        assert!(registration.check_reveal(&commitment, &salt, 101).is_ok());
+        assert!(registration.check_reveal(&commitment, &salt, 150).is_ok());
+
+        // Same slot as the registration: a copier could have registered there too
+        let early = registration.check_reveal(&commitment, &salt, 100).unwrap_err();
+        assert_eq!(early, MurklError::RevealTooEarly.into());
+        let late = registration.check_reveal(&commitment, &salt, 151).unwrap_err();
+        assert_eq!(late, MurklError::RegistrationExpired.into());
+        let wrong = registration.check_reveal(&commitment, &[5u8; 32], 120).unwrap_err();
+        assert_eq!(wrong, MurklError::RegistrationMismatch.into());
+
+        assert_ne!(deposit_registration_hash(&commitment, &salt), deposit_registration_hash(&salt, &commitment));
+    }
+
+    #[test]
+    fn subscription_epochs_open_in_order_and_claim_once() {
+        let mut subscription = Subscription {
+            pool: Pubkey::new_unique(),
+            commitment: [1; 32],
+            leaf_index: 3,
+            amount_per_epoch: 1_000,
+            epochs: 3,
+            epoch_duration_secs: SECONDS_PER_DAY,
+            started_at: 1_700_000_000,
+            claimed_epochs: 0,
+            bump: 255,
+        };
+        assert_eq!(subscription.try_to_vec().unwrap().len(), Subscription::INIT_SPACE);
+
+        let day = |n: i64| subscription.started_at + n * SECONDS_PER_DAY;
+        let err = |r: Result<()>| r.unwrap_err();
+        assert!(subscription.check_claimable(0, day(0)).is_ok());
+        assert_eq!(err(subscription.check_claimable(1, day(1) - 1)), MurklError::EpochNotOpen.into());
+        assert_eq!(err(subscription.check_claimable(3, day(9))), MurklError::EpochOutOfRange.into());
+
+        // Missed epochs stay claimable, but each only once
+        subscription.claimed_epochs |= 1 << 2;
+        assert_eq!(err(subscription.check_claimable(2, day(5))), MurklError::AlreadyClaimed.into());
+        assert!(subscription.check_claimable(1, day(5)).is_ok());
+        assert!(!subscription.is_fully_claimed());
+        subscription.claimed_epochs |= 0b011;
+        assert!(subscription.is_fully_claimed());
+    }
+
+    #[test]
+    fn nullifier_epoch_reads_tag() {
+        let mut nullifier = [0xab; 32];
+        nullifier[EPOCH_NULLIFIER_TAG].copy_from_slice(&7u32.to_le_bytes());
+        assert_eq!(nullifier_epoch(&nullifier), 7);
+    }
+
+    /// Sibling path for `index` in the tree of `leaves` (zero-leaf padded)
+    fn naive_path(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+        let empty = empty_hashes();
+        let mut level: Vec<[u8; 32]> = leaves.to_vec();
+        let mut idx = index;
+        let mut path = Vec::with_capacity(MERKLE_DEPTH);
+        for empty in empty.iter().take(MERKLE_DEPTH) {
+            if level.len() % 2 == 1 {
+                level.push(*empty);
+            }
+            path.push(level[idx ^ 1]);
+            level = level.chunks(2).map(|p| hash_pair(&p[0], &p[1])).collect();
+            idx >>= 1;
+        }
+        path
+    }
+
+    #[test]
+    fn merkle_path_verifies_against_frontier_root() {
+        let mut rng = StdRng::seed_from_u64(0xBEEF);
+        let mut branch = [[0u8; 32]; MERKLE_DEPTH];
+        let mut leaves: Vec<[u8; 32]> = Vec::new();
+        let mut root = [0u8; 32];
+        for i in 0..13u64 {
+            let mut leaf = [0u8; 32];
+            rng.fill_bytes(&mut leaf);
+            leaves.push(leaf);
+            root = merkle_append(&mut branch, i, &leaf);
+        }
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = naive_path(&leaves, i);
+            assert!(verify_merkle_path(leaf, i as u64, &path, &root), "leaf {i}");
+            assert!(!verify_merkle_path(leaf, (i ^ 1) as u64, &path, &root));
+            assert!(!verify_merkle_path(leaf, i as u64, &path[..MERKLE_DEPTH - 1], &root));
+        }
+    }
+
+    #[test]
+    fn path_nullifier_binds_leaf_index() {
+        assert_ne!(path_nullifier(42, 0), path_nullifier(42, 1));
+        assert_ne!(path_commitment(1, 42), path_commitment(42, 1));
+        assert_eq!(nullifier_epoch(&path_nullifier(42, 0)), 0);
+    }
+
+    #[test]
+    fn verifier_config_hash_separates_parameters() {
+        let murkl = verifier_config_hash(10, 4, 2, 4);
+        assert_eq!(murkl, verifier_config_hash(10, 4, 2, 4));
+        assert_ne!(murkl, verifier_config_hash(11, 4, 2, 4));
+        assert_ne!(murkl, verifier_config_hash(10, 4, 2, 3));
+        // Fields are fixed-width, so values cannot shift between positions
+        assert_ne!(verifier_config_hash(1, 0, 0, 0), verifier_config_hash(0, 1, 0, 0));
+    }
+
+    fn empty_stats(day: i64, leaf_count: u64) -> PoolStats {
+        let mut stats = PoolStats {
+            pool: Pubkey::new_unique(),
+            tracked_from_leaf: leaf_count,
+            tracked_since_day: day,
+            active_unclaimed: 0,
+            total_deposits: 0,
+            total_claims: 0,
+            claims_by_age: [0; AGE_BUCKETS],
+            claims_untracked: 0,
+            current_day: day,
+            day_start_leaf: [0; STATS_DAYS],
+            last_deposit_at: 0,
+            last_claim_at: 0,
+            bump: 255,
+        };
+        stats.day_start_leaf[PoolStats::slot(day)] = leaf_count;
+        stats
+    }
+
+    #[test]
+    fn pool_stats_bucket_claims_by_deposit_age() {
+        const DAY: i64 = SECONDS_PER_DAY;
+        let start = 20_000 * DAY;
+        // Leaves 0..5 predate the stats
+        let mut stats = empty_stats(start / DAY, 5);
+
+        stats.record_deposit(start + 10, 5);
+        stats.record_deposit(start + 20, 6);
+        stats.record_deposit(start + 3 * DAY, 7);
+        stats.record_deposit(start + 10 * DAY, 8);
+        assert_eq!(stats.active_unclaimed, 4);
+
+        let now = start + 10 * DAY + 60;
+        assert_eq!(stats.deposit_age_days(8), Some(0));
+        assert_eq!(stats.deposit_age_days(7), Some(7));
+        assert_eq!(stats.deposit_age_days(5), Some(10));
+        assert_eq!(stats.deposit_age_days(4), None);
+
+        stats.record_claim(now, 8, 9);
+        stats.record_claim(now, 7, 9);
+        stats.record_claim(now, 2, 9);
+        assert_eq!(stats.claims_by_age, [1, 0, 1, 0]);
+        assert_eq!(stats.claims_untracked, 1);
+        assert_eq!(stats.active_unclaimed, 2);
+        assert_eq!(stats.total_claims, 3);
+
+        // Long after the ring wraps, remaining deposits land in the last bucket
+        let later = start + 100 * DAY;
+        assert_eq!(PoolStats::slot(later / DAY), PoolStats::slot(later / DAY - STATS_DAYS as i64));
+        stats.record_claim(later, 5, 9);
+        assert_eq!(stats.claims_by_age, [1, 0, 1, 1]);
+        assert_eq!(stats.deposit_age_days(6), Some(STATS_DAYS as u64));
+    }
+
+    #[test]
+    fn pool_stats_init_space_covers_encoding() {
+        let stats = empty_stats(1, 1);
+        assert_eq!(stats.try_to_vec().unwrap().len(), PoolStats::INIT_SPACE);
+    }
+
+    #[test]
+    fn cpi_discriminators_match_target_instructions() {
+        use anchor_lang::solana_program::hash::hash;
+        assert_eq!(hash(b"global:check_compliance").to_bytes()[..8], COMPLIANCE_HOOK_DISCRIMINATOR);
+        assert_eq!(hash(b"global:close_proof_buffer").to_bytes()[..8], CLOSE_PROOF_BUFFER_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn proof_age_respects_pool_limit() {
+        // Expiry disabled
+        assert!(proof_within_age(0, u64::MAX, 0));
+        // At and past the limit
+        assert!(proof_within_age(100, 150, 50));
+        assert!(!proof_within_age(100, 151, 50));
+        // A slot from the future (clock skew across forks) is not expired
+        assert!(proof_within_age(200, 150, 50));
+    }
+
+    #[test]
+    fn claim_events_are_sequenced_per_pool() {
+        let mut pool = Pool {
+            admin: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            merkle_root: [0; 32],
+            leaf_count: 0,
+            config: PoolConfig::default(),
+            paused: false,
+            bump: 255,
+            path_claims_enabled: false,
+            max_proof_age_slots: 0,
+            stats_enabled: false,
+            epoch_duration_secs: 0,
+            minimal_events: false,
+            event_seq: 0,
+            root_history: [[0; 32]; ROOT_HISTORY_SIZE],
+            root_cursor: 0,
+            root_history_window: ROOT_HISTORY_SIZE as u8,
+            pool_kind: PoolKind::Token,
+            nullifier_shard_bits: 0,
+            deposit_reveal_slots: 0,
+            accepted_mints: [AcceptedMint::default(); MAX_ACCEPTED_MINTS],
+            accepted_mint_count: 0,
+            nullifier_bloom_enabled: false,
+            vault_generation: 0,
+            min_security_level: None,
+        };
+        let key = Pubkey::new_unique();
+        emit_claim_event(&mut pool, key, [7; 32], 900, 100).unwrap();
+        pool.minimal_events = true;
+        emit_claim_event(&mut pool, key, [8; 32], 900, 100).unwrap();
+        assert_eq!(pool.event_seq, 2);
+
+        pool.event_seq = u64::MAX;
+        assert!(emit_claim_event(&mut pool, key, [9; 32], 900, 100).is_err());
+
+        // Minimal events carry nothing beyond the pool and its sequence number
+        let minimal = MinimalClaimEvent { pool: key, event_seq: 1 };
+        assert_eq!(minimal.try_to_vec().unwrap().len(), 32 + 8);
+    }
+
+    #[test]
+    fn pool_accepts_roots_within_history() {
+        let mut pool = Pool {
+            admin: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            merkle_root: empty_hashes()[MERKLE_DEPTH],
+            leaf_count: 0,
+            config: PoolConfig::default(),
+            paused: false,
+            bump: 255,
+            path_claims_enabled: false,
+            max_proof_age_slots: 0,
+            stats_enabled: false,
+            epoch_duration_secs: 0,
+            minimal_events: false,
+            event_seq: 0,
+            root_history: [[0; 32]; ROOT_HISTORY_SIZE],
+            root_cursor: 0,
+            root_history_window: ROOT_HISTORY_SIZE as u8,
+            pool_kind: PoolKind::Token,
+            nullifier_shard_bits: 0,
+            deposit_reveal_slots: 0,
+            accepted_mints: [AcceptedMint::default(); MAX_ACCEPTED_MINTS],
+            accepted_mint_count: 0,
+            nullifier_bloom_enabled: false,
+            vault_generation: 0,
+            min_security_level: None,
+        };
+        let mut merkle = PoolMerkle { pool: Pubkey::new_unique(), branch: [[0; 32]; MERKLE_DEPTH], bump: 255 };
+        // Unused history slots never match
+        assert!(!pool.is_known_root(&[0; 32]));
+
+        let mut roots = vec![pool.merkle_root];
+        for i in 0..=ROOT_HISTORY_SIZE as u64 {
+            let root = merkle.insert_leaf(i, &[i as u8 + 1; 32]).unwrap();
+            pool.push_root(root);
+            roots.push(root);
+        }
+        // Current root plus ROOT_HISTORY_SIZE predecessors; the oldest fell out
+        assert_eq!(pool.merkle_root, *roots.last().unwrap());
+        assert!(roots[1..].iter().all(|r| pool.is_known_root(r)));
+        assert!(!pool.is_known_root(&roots[0]));
+
+        // A path built against an older root still checks out
+        let leaves: Vec<[u8; 32]> = (0..3u8).map(|i| [i + 1; 32]).collect();
+        let path = naive_path(&leaves, 1);
+        let old_root = merkle_path_root(&leaves[1], 1, &path).unwrap();
+        assert_eq!(old_root, roots[3]);
+        assert!(pool.is_known_root(&old_root));
+
+        // A narrower window drops the older roots but keeps the newest ones
+        pool.root_history_window = 2;
+        let n = roots.len();
+        assert!(pool.is_known_root(&roots[n - 1]));
+        assert!(pool.is_known_root(&roots[n - 2]));
+        assert!(pool.is_known_root(&roots[n - 3]));
+        assert!(!pool.is_known_root(&roots[n - 4]));
+        pool.root_history_window = 0;
+        assert!(!pool.is_known_root(&roots[n - 2]));
+        assert!(pool.is_known_root(&roots[n - 1]));
+    }
+
+    #[test]
+    fn verifier_params_pin_checks_published_hash() {
+        let mut pool = Pool {
+            admin: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            merkle_root: [0; 32],
+            leaf_count: 0,
+            config: PoolConfig::default(),
+            paused: false,
+            bump: 255,
+            path_claims_enabled: false,
+            max_proof_age_slots: 0,
+            stats_enabled: false,
+            epoch_duration_secs: 0,
+            minimal_events: false,
+            event_seq: 0,
+            root_history: [[0; 32]; ROOT_HISTORY_SIZE],
+            root_cursor: 0,
+            root_history_window: 0,
+            pool_kind: PoolKind::Token,
+            nullifier_shard_bits: 0,
+            deposit_reveal_slots: 0,
+            accepted_mints: [AcceptedMint::default(); MAX_ACCEPTED_MINTS],
+            accepted_mint_count: 0,
+            nullifier_bloom_enabled: false,
+            vault_generation: 0,
+            min_security_level: None,
+        };
+        let key = Pubkey::find_program_address(&[VERIFIER_PARAMS_SEED], &STARK_VERIFIER_ID).0;
+        let mut lamports = 1;
+        let mut data = [0u8; 8 + 32 + 26];
+        data[VERIFIER_PARAMS_OFFSET_HASH..VERIFIER_PARAMS_OFFSET_HASH + 32].copy_from_slice(&[7; 32]);
+        let params = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &STARK_VERIFIER_ID, false, 0);
+
+        // Unpinned pools need no params account
+        assert!(check_verifier_params(&pool, None).is_ok());
+
+        pool.config.required_verifier_params_hash = Some([7; 32]);
+        assert!(check_verifier_params(&pool, Some(&params)).is_ok());
+        assert_eq!(
+            check_verifier_params(&pool, None).unwrap_err(),
+            MurklError::VerifierParamsMissing.into()
+        );
+        let mut forged = params.clone();
+        forged.owner = &ID;
+        assert_eq!(
+            check_verifier_params(&pool, Some(&forged)).unwrap_err(),
+            MurklError::VerifierParamsMissing.into()
+        );
+
+        pool.config.required_verifier_params_hash = Some([8; 32]);
+        assert_eq!(
+            check_verifier_params(&pool, Some(&params)).unwrap_err(),
+            MurklError::VerifierParamsMismatch.into()
+        );
+
+        // A minimum level reads the published queries, blowup and hashes
+        pool.config.required_verifier_params_hash = None;
+        pool.min_security_level = Some(SecurityLevel::Standard96 as u8);
+        let publish = |data: &mut [u8], min_queries: u32, hash_kinds: u8| {
+            data[VERIFIER_PARAMS_OFFSET_LOG_BLOWUP..][..4].copy_from_slice(&4u32.to_le_bytes());
+            data[VERIFIER_PARAMS_OFFSET_MIN_QUERIES..][..4].copy_from_slice(&min_queries.to_le_bytes());
+            data[VERIFIER_PARAMS_OFFSET_HASH_KINDS] = hash_kinds;
+        };
+        publish(&mut params.try_borrow_mut_data().unwrap(), 4, 0b011);
+        assert_eq!(
+            check_verifier_params(&pool, Some(&params)).unwrap_err(),
+            MurklError::VerifierBelowSecurityLevel.into()
+        );
+        assert_eq!(
+            check_verifier_params(&pool, None).unwrap_err(),
+            MurklError::VerifierParamsMissing.into()
+        );
+        publish(&mut params.try_borrow_mut_data().unwrap(), 24, 0b011);
+        assert!(check_verifier_params(&pool, Some(&params)).is_ok());
+        // Poseidon2 proofs cap the verifier at Standard96
+        pool.min_security_level = Some(SecurityLevel::High128 as u8);
+        publish(&mut params.try_borrow_mut_data().unwrap(), 32, 0b011);
+        assert!(check_verifier_params(&pool, Some(&params)).is_err());
+        publish(&mut params.try_borrow_mut_data().unwrap(), 32, 0b001);
+        assert!(check_verifier_params(&pool, Some(&params)).is_ok());
+    }
+
+    #[test]
+    fn membership_records_stay_out_of_the_claim_namespace() {
+        let (pool, nullifier) = (Pubkey::new_unique(), [3u8; 32]);
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+        let membership = |scope: &[u8; 32]| find(&[MEMBERSHIP_SEED, pool.as_ref(), scope, &nullifier]);
+
+        assert_ne!(membership(&[1; 32]), find(&[b"nullifier", pool.as_ref(), &nullifier]));
+        assert_ne!(membership(&[1; 32]), membership(&[2; 32]));
+
+        let record = MembershipRecord {
+            pool,
+            scope: [1; 32],
+            nullifier,
+            member: Pubkey::new_unique(),
+            proven_at: 0,
+            bump: 255,
+        };
+        assert_eq!(record.try_to_vec().unwrap().len(), MembershipRecord::INIT_SPACE);
+    }
+
+    #[test]
+    fn admin_log_chains_entries_in_order() {
+        let (pool, admin) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let empty = AdminLog { pool, head: [0; 32], entries: 0, bump: 255 };
+        assert_eq!(empty.try_to_vec().unwrap().len(), AdminLog::INIT_SPACE);
+
+        let pause = (AdminLogAction::PausePool, keccak::hash(&[]).0);
+        let max_age = (AdminLogAction::SetMaxProofAge, keccak::hash(&150u64.to_le_bytes()).0);
+        let replay = |actions: &[(AdminLogAction, [u8; 32])]| {
+            let mut log = AdminLog { ..empty };
+            let entries: Vec<_> = actions
+                .iter()
+                .enumerate()
+                .map(|(i, &(action, args))| log.record(action, args, admin, 10 + i as u64).unwrap())
+                .collect();
+            (log, entries)
+        };
+
+        let (log, entries) = replay(&[pause, max_age]);
+        assert_eq!(log.entries, 2);
+        assert_eq!(entries[0].prev_hash, [0; 32]);
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        assert_eq!(entries[1].seq, 2);
+        assert_eq!(log.head, entries[1].hash);
+        assert_eq!(
+            entries[1].hash,
+            admin_log_entry_hash(&pool, &entries[0].hash, max_age.0, &max_age.1, &admin, 11)
+        );
+
+        // Reordering, dropping or altering an action moves the head
+        assert_ne!(replay(&[max_age, pause]).0.head, log.head);
+        assert_ne!(replay(&[max_age]).0.head, log.head);
+        let other_age = (AdminLogAction::SetMaxProofAge, keccak::hash(&151u64.to_le_bytes()).0);
+        assert_ne!(replay(&[pause, other_age]).0.head, log.head);
+    }
+
+    #[test]
+    fn insert_leaf_refuses_a_full_tree() {
+        let mut merkle = PoolMerkle { pool: Pubkey::new_unique(), branch: [[0; 32]; MERKLE_DEPTH], bump: 255 };
+        assert!(merkle.insert_leaf((1 << MERKLE_DEPTH) - 1, &[1; 32]).is_ok());
+        assert!(merkle.insert_leaf(1 << MERKLE_DEPTH, &[1; 32]).is_err());
+    }
+
+    #[test]
+    fn claim_receipt_keeps_first_slot_and_counts_claims() {
+        let (pool, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut receipt = ClaimReceipt {
+            pool: Pubkey::default(),
+            recipient: Pubkey::default(),
+            first_claim_slot: 0,
+            last_claim_slot: 0,
+            claims: 0,
+            bump: 0,
+        };
+        receipt.record(pool, recipient, Some(254), 100).unwrap();
+        receipt.record(pool, recipient, Some(254), 250).unwrap();
+        assert_eq!((receipt.pool, receipt.recipient, receipt.bump), (pool, recipient, 254));
+        assert_eq!((receipt.first_claim_slot, receipt.last_claim_slot, receipt.claims), (100, 250, 2));
+
+        receipt.claims = u32::MAX;
+        assert!(receipt.record(pool, recipient, Some(254), 300).is_err());
+    }
+
+    #[test]
+    fn nullifier_shards_split_on_leading_bits() {
+        let mut nullifier = [0u8; 32];
+        nullifier[0] = 0b1011_0000;
+        nullifier[1] = 0xff;
+        assert_eq!(nullifier_shard_index(&nullifier, 1), 1);
+        assert_eq!(nullifier_shard_index(&nullifier, 4), 0b1011);
+        assert_eq!(nullifier_shard_index(&nullifier, 16), 0xb0ff);
+    }
+
+    #[test]
+    fn nullifier_shard_search_keeps_entries_sorted() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut entries = Vec::new();
+        let mut inserted = Vec::new();
+        for _ in 0..64 {
+            let mut nullifier = [0u8; 32];
+            rng.fill_bytes(&mut nullifier);
+            let pos = search_nullifier_shard(&entries, &nullifier).unwrap_err();
+            entries.splice(pos * 32..pos * 32, nullifier);
+            inserted.push(nullifier);
+        }
+        inserted.sort();
+        assert_eq!(entries, inserted.concat());
+        for (i, nullifier) in inserted.iter().enumerate() {
+            assert_eq!(search_nullifier_shard(&entries, nullifier), Ok(i));
+        }
+        assert_eq!(search_nullifier_shard(&[], &[0; 32]), Err(0));
+    }
+
+    #[test]
+    fn nullifier_bloom_holds_every_claim() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut bits = vec![0u8; NULLIFIER_BLOOM_BYTES];
+        let claimed: Vec<[u8; 32]> = (0..5_000)
+            .map(|_| {
+                let mut nullifier = [0u8; 32];
+                rng.fill_bytes(&mut nullifier);
+                nullifier_bloom_insert(&mut bits, &nullifier);
+                nullifier
+            })
+            .collect();
+        assert!(claimed.iter().all(|nullifier| nullifier_bloom_contains(&bits, nullifier)));
+
+        // About 0.5% false positives at 5,000 claims
+        let false_positives = (0..10_000)
+            .filter(|_| {
+                let mut nullifier = [0u8; 32];
+                rng.fill_bytes(&mut nullifier);
+                nullifier_bloom_contains(&bits, &nullifier)
+            })
+            .count();
+        assert!(false_positives < 150, "{false_positives} false positives");
+
+        let mut nullifier = [0u8; 32];
+        nullifier[..8].copy_from_slice(&[1, 0, 2, 0, 0xff, 0xff, 9, 0]);
+        assert_eq!(nullifier_bloom_bits(&nullifier), [1, 2, u16::MAX, 9]);
+    }
+
+    #[test]
+    fn nullifier_bloom_init_space_covers_encoding() {
+        let bloom = NullifierBloom {
+            pool: Pubkey::new_unique(),
+            tracked_from_leaf: u64::MAX,
+            inserted: u64::MAX,
+            bump: 255,
+        };
+        assert_eq!(bloom.try_to_vec().unwrap().len(), NullifierBloom::INIT_SPACE);
+    }
+
+    #[test]
+    fn rotated_vaults_get_fresh_addresses() {
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+        let rotated = |generation: u16| find(&[b"vault", pool.as_ref(), &generation.to_le_bytes()]);
+
+        let original = find(&[b"vault", pool.as_ref()]);
+        let accepted = find(&[b"vault", pool.as_ref(), mint.as_ref()]);
+        for generation in 1..4 {
+            assert_ne!(rotated(generation), original);
+            assert_ne!(rotated(generation), accepted);
+            assert_ne!(rotated(generation), rotated(generation + 1));
+        }
+
+        let rotation = VaultRotation {
+            pool,
+            scheduled_at: i64::MAX,
+            executable_at: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(rotation.try_to_vec().unwrap().len(), VaultRotation::INIT_SPACE);
+    }
 }
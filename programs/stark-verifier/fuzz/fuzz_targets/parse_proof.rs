@@ -0,0 +1,23 @@
+//! Arbitrary bytes through the proof parser, queries included: it must
+//! return an error rather than panic, and only the parser's own errors
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use stark_verifier::{check_proof_encoding, VerifierError};
+
+fuzz_target!(|data: &[u8]| {
+    if let Err(err) = check_proof_encoding(data) {
+        assert!(
+            [
+                VerifierError::InvalidProofFormat,
+                VerifierError::UnsupportedProofVersion,
+                VerifierError::UnsupportedHashKind,
+                VerifierError::FinalPolyDegreeTooHigh,
+            ]
+            .into_iter()
+            .any(|code| err == code.into()),
+            "parser returned {err}"
+        );
+    }
+});
@@ -0,0 +1,15 @@
+//! Arbitrary bytes through the whole verification: the first 128 are the
+//! statement (commitment, nullifier, Merkle root, recipient), the rest the
+//! proof. Whatever gets past the parser must be rejected without a panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((statement, proof)) = data.split_first_chunk::<128>() else {
+        return;
+    };
+    let input = |i: usize| -> &[u8; 32] { statement[32 * i..32 * (i + 1)].try_into().unwrap() };
+    let _ = stark_verifier::verify_stark_proof(proof, input(0), input(1), input(2), input(3));
+});
@@ -1,6 +1,8 @@
 //! FRI (Fast Reed-Solomon IOP) verification for on-chain STARK verification
 
 use anchor_lang::solana_program::keccak;
+use core::ops::{Add, Mul};
+
 use crate::m31::{M31, QM31};
 
 /// Hash helper using keccak256 syscall
@@ -0,0 +1,401 @@
+//! The Poseidon2 commitment, nullifier and pool membership AIR, checked at
+//! the OODS point
+//!
+//! A port of murkl-prover's `air::poseidon2` (`MurklHashAir`, with
+//! membership over a [`POOL_TREE_DEPTH`] tree) to this crate's field types,
+//! constraint for constraint and in the same order. A proof opens the
+//! [`WITNESS_COLUMNS`] witness columns (permutation state, the secret, then
+//! the path bit) at the OODS point and one trace row on. The fixed columns,
+//! round constants and selectors, are not sent: they are interpolated here
+//! from the rows they are defined on. Past the first [`FIXED_ROWS`] rows
+//! they are all zero, so each costs a sum over those rows rather than over
+//! the whole trace.
+
+use core::array;
+use core::ops::{Add, Mul, Range};
+
+use crate::m31::{CirclePoint, SecureCirclePoint, M31, P, QM31};
+use crate::poseidon2::{DIGEST_LIMBS, EXTERNAL_CONSTANTS, HALF_FULL_ROUNDS, INTERNAL_CONSTANTS, INTERNAL_DIAG, WIDTH};
+
+/// Rows per permutation: input, 22 rounds after the initial linear layer,
+/// padded to a power of two
+pub const BLOCK_ROWS: usize = 32;
+/// Row of a block holding the permutation output
+pub const OUTPUT_ROW: usize = 1 + 2 * HALF_FULL_ROUNDS + INTERNAL_CONSTANTS.len();
+/// Commitment block, then nullifier block: the rows the secret is carried on
+pub const TRACE_ROWS: usize = 2 * BLOCK_ROWS;
+/// Levels of the murkl pool tree (its `MERKLE_DEPTH`)
+pub const POOL_TREE_DEPTH: usize = 20;
+/// The hash blocks, then a block per pool tree level: the rows the fixed
+/// columns use
+pub const FIXED_ROWS: usize = TRACE_ROWS + POOL_TREE_DEPTH * BLOCK_ROWS;
+/// Nullifier bytes holding its epoch in the clear, in place of the last limb
+pub const EPOCH_BYTES: Range<usize> = 28..32;
+/// Digest limbs a nullifier keeps
+pub const NULLIFIER_LIMBS: usize = EPOCH_BYTES.start / 4;
+/// Columns a proof opens: the [`WIDTH`] state lanes, the secret, then the
+/// path bit
+pub const WITNESS_COLUMNS: usize = WIDTH + 2;
+/// A round per state lane, the secret, the input row's lanes, the
+/// commitment and nullifier limbs, then the path bit and the leaf, node
+/// and root limbs
+pub const NUM_CONSTRAINTS: usize = 2 * WIDTH + DIGEST_LIMBS + NULLIFIER_LIMBS + 1 + 3 * DIGEST_LIMBS;
+
+/// Lane 0 of the commitment permutation's input
+const COMMITMENT_TAG: u32 = 1;
+/// Lane 0 of the nullifier permutation's input
+const NULLIFIER_TAG: u32 = 2;
+const SECRET: usize = WIDTH;
+const PATH_BIT: usize = SECRET + 1;
+
+/// What the row after `row` (within a block) is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Linear,
+    /// Full round with these external constants
+    Full(usize),
+    /// Partial round with this internal constant
+    Partial(usize),
+}
+
+fn step(row: usize) -> Option<Step> {
+    if row >= FIXED_ROWS {
+        return None;
+    }
+    let partial_start = 1 + HALF_FULL_ROUNDS;
+    let partial_end = partial_start + INTERNAL_CONSTANTS.len();
+    match row % BLOCK_ROWS {
+        0 => Some(Step::Linear),
+        r if r < partial_start => Some(Step::Full(r - 1)),
+        r if r < partial_end => Some(Step::Partial(r - partial_start)),
+        r if r < OUTPUT_ROW => Some(Step::Full(r - partial_end + HALF_FULL_ROUNDS)),
+        _ => None,
+    }
+}
+
+/// The fixed columns at one point
+#[derive(Clone, Copy, Debug, Default)]
+struct Fixed {
+    round_constants: [QM31; WIDTH],
+    linear: QM31,
+    full: QM31,
+    partial: QM31,
+    input: QM31,
+    tag: QM31,
+    nullifier_input: QM31,
+    commitment_output: QM31,
+    nullifier_output: QM31,
+    carry: QM31,
+    level_input: QM31,
+    leaf_input: QM31,
+    hold: QM31,
+    link: QM31,
+    root_output: QM31,
+}
+
+impl Fixed {
+    /// The fixed columns at `point`, a sum over their [`FIXED_ROWS`] rows
+    /// ([`row_weights`])
+    fn at(point: &SecureCirclePoint, log_trace_size: u32) -> Option<Self> {
+        let mut fixed = Self::default();
+        for (index, weight) in row_weights(point, log_trace_size, FIXED_ROWS)?.into_iter().enumerate() {
+            let add = |column: &mut QM31, value: u32| *column = column.add(weight.mul_m31(M31::new(value)));
+            if index + 1 < TRACE_ROWS {
+                add(&mut fixed.carry, 1);
+            }
+            match step(index) {
+                Some(Step::Linear) => add(&mut fixed.linear, 1),
+                Some(Step::Full(round)) => {
+                    add(&mut fixed.full, 1);
+                    for (column, &constant) in fixed.round_constants.iter_mut().zip(&EXTERNAL_CONSTANTS[round]) {
+                        add(column, constant);
+                    }
+                }
+                Some(Step::Partial(round)) => {
+                    add(&mut fixed.partial, 1);
+                    add(&mut fixed.round_constants[0], INTERNAL_CONSTANTS[round]);
+                }
+                None => {}
+            }
+            match index {
+                0 => {
+                    add(&mut fixed.input, 1);
+                    add(&mut fixed.tag, COMMITMENT_TAG);
+                }
+                BLOCK_ROWS => {
+                    add(&mut fixed.input, 1);
+                    add(&mut fixed.tag, NULLIFIER_TAG);
+                    add(&mut fixed.nullifier_input, 1);
+                }
+                OUTPUT_ROW => add(&mut fixed.commitment_output, 1),
+                r if r == BLOCK_ROWS + OUTPUT_ROW => add(&mut fixed.nullifier_output, 1),
+                _ => {}
+            }
+            if index >= TRACE_ROWS {
+                let level = (index - TRACE_ROWS) / BLOCK_ROWS;
+                let last = level + 1 == POOL_TREE_DEPTH;
+                match index % BLOCK_ROWS {
+                    0 => {
+                        add(&mut fixed.level_input, 1);
+                        if level == 0 {
+                            add(&mut fixed.leaf_input, 1);
+                        }
+                    }
+                    OUTPUT_ROW if last => add(&mut fixed.root_output, 1),
+                    r if !last && r == BLOCK_ROWS - 1 => add(&mut fixed.link, 1),
+                    r if !last && r >= OUTPUT_ROW => add(&mut fixed.hold, 1),
+                    _ => {}
+                }
+            }
+        }
+        Some(fixed)
+    }
+}
+
+/// Lagrange weights at `point` of the first `rows` rows of the canonic
+/// coset of 2^`log_trace_size` rows
+///
+/// Row i, the coset point (xᵢ, yᵢ), weighs
+/// V(p.x) (yᵢ + p.y) / (2yᵢ (p.x - xᵢ) V'(xᵢ)) at p, with V the coset's
+/// vanishing polynomial. `None` if the trace is shorter than `rows` or
+/// `point` shares an x-coordinate with a row, which no point off the base
+/// circle does.
+fn row_weights(point: &SecureCirclePoint, log_trace_size: u32, rows: usize) -> Option<Vec<QM31>> {
+    if !(1..31).contains(&log_trace_size) || rows > 1 << log_trace_size {
+        return None;
+    }
+    // Row i is G_2n + i·G_n
+    let step_point = CirclePoint::GENERATOR.repeated_double(31 - log_trace_size);
+    let rows = (0..rows).scan(CirclePoint::GENERATOR.repeated_double(30 - log_trace_size), |row, _| {
+        let current = *row;
+        *row = current.add(step_point);
+        Some(current)
+    });
+
+    // 2yᵢ V'(xᵢ) (p.x - xᵢ), V'(x) being the product of 4x over V's doublings of x
+    let (mut weights, ys): (Vec<QM31>, Vec<M31>) = rows
+        .map(|row| {
+            let (mut x, mut scale) = (row.x, row.y.add(row.y));
+            for _ in 1..log_trace_size {
+                scale = scale.mul(M31::new(4).mul(x));
+                let sq = x.square();
+                x = sq.add(sq).sub(M31::ONE);
+            }
+            (point.x.sub(QM31::from_m31(row.x)).mul_m31(scale), row.y)
+        })
+        .unzip();
+    batch_inverse(&mut weights)?;
+
+    let vanishing = crate::trace_vanishing(point, log_trace_size);
+    for (weight, y) in weights.iter_mut().zip(ys) {
+        *weight = weight.mul(vanishing.mul(point.y.add(QM31::from_m31(y))));
+    }
+    Some(weights)
+}
+
+/// Invert every value at the cost of one field inversion; `None` if one is zero
+fn batch_inverse(values: &mut [QM31]) -> Option<()> {
+    let mut prefixes = Vec::with_capacity(values.len());
+    let mut product = QM31::ONE;
+    for &value in values.iter() {
+        prefixes.push(product);
+        product = product.mul(value);
+    }
+    if product.eq(&QM31::ZERO) {
+        return None;
+    }
+    let mut inverse = product.inv();
+    for (value, prefix) in values.iter_mut().zip(prefixes).rev() {
+        let original = *value;
+        *value = inverse.mul(prefix);
+        inverse = inverse.mul(original);
+    }
+    Some(())
+}
+
+/// The public values the AIR pins: commitment and nullifier limbs, the
+/// nullifier's epoch, and the pool's Merkle root
+#[derive(Clone, Copy, Debug)]
+pub struct HashStatement {
+    commitment: [M31; DIGEST_LIMBS],
+    nullifier: [M31; NULLIFIER_LIMBS],
+    epoch: M31,
+    merkle_root: [M31; DIGEST_LIMBS],
+}
+
+impl HashStatement {
+    /// `None` unless every 4-byte limb of the three hashes, the epoch
+    /// included, is a canonical M31. The AIR only sees limbs mod p, so a
+    /// second encoding of one would pass with the same proof.
+    pub fn new(commitment: &[u8; 32], nullifier: &[u8; 32], merkle_root: &[u8; 32]) -> Option<Self> {
+        let limbs = |hash: &[u8; 32]| -> Option<[M31; DIGEST_LIMBS]> {
+            let mut limbs = [M31::ZERO; DIGEST_LIMBS];
+            for (limb, bytes) in limbs.iter_mut().zip(hash.chunks_exact(4)) {
+                let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                if value >= P {
+                    return None;
+                }
+                *limb = M31::new(value);
+            }
+            Some(limbs)
+        };
+        let commitment = limbs(commitment)?;
+        let nullifier = limbs(nullifier)?;
+        Some(Self {
+            commitment,
+            nullifier: array::from_fn(|lane| nullifier[lane]),
+            epoch: nullifier[NULLIFIER_LIMBS],
+            merkle_root: limbs(merkle_root)?,
+        })
+    }
+
+    /// The constraints at `point`, from the witness columns there
+    /// (`current`) and one trace row on (`next`), in the prover's order
+    ///
+    /// `None` if either frame is narrower than [`WITNESS_COLUMNS`] or the
+    /// fixed columns can't be interpolated at `point`.
+    pub fn evaluate(
+        &self,
+        current: &[QM31],
+        next: &[QM31],
+        point: &SecureCirclePoint,
+        log_trace_size: u32,
+    ) -> Option<Vec<QM31>> {
+        if current.len() < WITNESS_COLUMNS || next.len() < WITNESS_COLUMNS {
+            return None;
+        }
+        let fixed = Fixed::at(point, log_trace_size)?;
+        let state: [QM31; WIDTH] = array::from_fn(|lane| current[lane]);
+
+        let linear = external_linear(&state);
+        let full = external_linear(&array::from_fn(|lane| sbox(state[lane].add(fixed.round_constants[lane]))));
+        let mut partial = state;
+        partial[0] = sbox(state[0].add(fixed.round_constants[0]));
+        let partial = internal_linear(&partial);
+
+        let mut constraints = Vec::with_capacity(NUM_CONSTRAINTS);
+        let mut push = |value: QM31| constraints.push(value);
+        for lane in 0..WIDTH {
+            let next_lane = next[lane];
+            push(
+                fixed
+                    .linear
+                    .mul(next_lane.sub(linear[lane]))
+                    .add(fixed.full.mul(next_lane.sub(full[lane])))
+                    .add(fixed.partial.mul(next_lane.sub(partial[lane]))),
+            );
+        }
+
+        push(fixed.carry.mul(next[SECRET].sub(current[SECRET])));
+        let input = fixed.input;
+        push(input.mul(state[0]).sub(fixed.tag));
+        push(input.mul(state[2].sub(current[SECRET])));
+        push(input.mul(state[3]).sub(fixed.nullifier_input.mul_m31(self.epoch)));
+        for &lane in &state[4..] {
+            push(input.mul(lane));
+        }
+        for (&lane, &limb) in state.iter().zip(&self.commitment) {
+            push(fixed.commitment_output.mul(lane.sub(QM31::from_m31(limb))));
+        }
+        for (&lane, &limb) in state.iter().zip(&self.nullifier) {
+            push(fixed.nullifier_output.mul(lane.sub(QM31::from_m31(limb))));
+        }
+
+        let bit = current[PATH_BIT];
+        push(fixed.level_input.mul(bit).mul(QM31::ONE.sub(bit)));
+        for (lane, &limb) in self.commitment.iter().enumerate() {
+            let leaf = QM31::from_m31(limb);
+            let placed = QM31::ONE.sub(bit).mul(state[lane].sub(leaf)).add(bit.mul(state[DIGEST_LIMBS + lane].sub(leaf)));
+            push(fixed.leaf_input.mul(placed));
+        }
+        let next_bit = next[PATH_BIT];
+        for lane in 0..DIGEST_LIMBS {
+            let held = next[lane].sub(state[lane]);
+            let entered = QM31::ONE.sub(next_bit).mul(held).add(next_bit.mul(next[DIGEST_LIMBS + lane].sub(state[lane])));
+            push(fixed.hold.mul(held).add(fixed.link.mul(entered)));
+        }
+        for (&lane, &limb) in state.iter().zip(&self.merkle_root) {
+            push(fixed.root_output.mul(lane.sub(QM31::from_m31(limb))));
+        }
+        Some(constraints)
+    }
+}
+
+fn sbox(x: QM31) -> QM31 {
+    let x2 = x.square();
+    x2.square().mul(x)
+}
+
+/// The permutation's external layer, as `poseidon2` applies it to M31 lanes
+fn external_linear(state: &[QM31; WIDTH]) -> [QM31; WIDTH] {
+    let mut out = *state;
+    for block in out.chunks_exact_mut(4) {
+        let t01 = block[0].add(block[1]);
+        let t23 = block[2].add(block[3]);
+        let t0123 = t01.add(t23);
+        let t01123 = t0123.add(block[1]);
+        let t01233 = t0123.add(block[3]);
+        block[3] = t01233.add(block[0].add(block[0]));
+        block[1] = t01123.add(block[2].add(block[2]));
+        block[0] = t01123.add(t01);
+        block[2] = t01233.add(t23);
+    }
+    let mut sums = [QM31::ZERO; 4];
+    for (i, &lane) in out.iter().enumerate() {
+        sums[i % 4] = sums[i % 4].add(lane);
+    }
+    for (i, lane) in out.iter_mut().enumerate() {
+        *lane = lane.add(sums[i % 4]);
+    }
+    out
+}
+
+/// The permutation's internal layer, as `poseidon2` applies it to M31 lanes
+fn internal_linear(state: &[QM31; WIDTH]) -> [QM31; WIDTH] {
+    let sum = state.iter().fold(QM31::ZERO, |acc, &lane| acc.add(lane));
+    array::from_fn(|lane| sum.add(state[lane].mul_m31(M31::new(INTERNAL_DIAG[lane]))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Channel, HashKind};
+
+    /// On a 64-row trace the weights cover every row, so they interpolate
+    /// 1, x and y exactly
+    #[test]
+    fn test_row_weights_interpolate() {
+        let log_size = TRACE_ROWS.trailing_zeros();
+        let step_point = CirclePoint::GENERATOR.repeated_double(31 - log_size);
+        let row = |i: usize| CirclePoint::GENERATOR.repeated_double(30 - log_size).add(step_point * i as u32);
+
+        let point = Channel::new(HashKind::Keccak).squeeze_circle_point();
+        let weights = row_weights(&point, log_size, TRACE_ROWS).unwrap();
+        let interpolate = |f: &dyn Fn(CirclePoint) -> M31| {
+            weights.iter().enumerate().fold(QM31::ZERO, |acc, (i, w)| acc.add(w.mul_m31(f(row(i)))))
+        };
+        assert!(interpolate(&|_| M31::ONE).eq(&QM31::ONE));
+        assert!(interpolate(&|p| p.x).eq(&point.x));
+        assert!(interpolate(&|p| p.y).eq(&point.y));
+
+        let on_row = SecureCirclePoint { x: QM31::from_m31(row(3).x), y: QM31::from_m31(row(3).y) };
+        assert!(row_weights(&on_row, log_size, TRACE_ROWS).is_none());
+        assert!(row_weights(&point, log_size - 1, TRACE_ROWS).is_none());
+    }
+
+    #[test]
+    fn test_statement_rejects_non_canonical_limbs() {
+        let mut nullifier = [0u8; 32];
+        nullifier[EPOCH_BYTES].copy_from_slice(&3u32.to_le_bytes());
+        let statement = HashStatement::new(&[1; 32], &nullifier, &[2; 32]).unwrap();
+        assert_eq!(statement.epoch, M31::new(3));
+
+        for limb in 0..DIGEST_LIMBS {
+            let mut hash = [0u8; 32];
+            hash[4 * limb..4 * limb + 4].copy_from_slice(&P.to_le_bytes());
+            assert!(HashStatement::new(&hash, &nullifier, &[2; 32]).is_none());
+            assert!(HashStatement::new(&[1; 32], &hash, &[2; 32]).is_none());
+            assert!(HashStatement::new(&[1; 32], &nullifier, &hash).is_none());
+        }
+    }
+}
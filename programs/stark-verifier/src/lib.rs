@@ -4,17 +4,22 @@
 //! - M31/QM31 field arithmetic with exact verification
 //! - Fiat-Shamir channel for non-interactive proofs
 //! - FRI verification with actual folding checks
-//! - Merkle path verification with keccak256
+//! - Merkle path verification with keccak256 or Poseidon2 (per proof)
 //!
 //! NO SHORTCUTS. Real cryptographic verification.
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak;
+use std::ops::{Add, Mul, Range};
 
 mod m31;
 mod fri;
+mod hash_air;
+mod poseidon2;
+mod poseidon_bn254;
 
-pub use m31::{M31, QM31, P};
+pub use m31::{CirclePoint, SecureCirclePoint, M31, QM31, P};
+pub use hash_air::{HashStatement, NUM_CONSTRAINTS as HASH_CONSTRAINTS, POOL_TREE_DEPTH, WITNESS_COLUMNS};
 
 fn keccak_hash(data: &[u8]) -> [u8; 32] {
     keccak::hash(data).0
@@ -26,38 +31,211 @@ declare_id!("StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw");
 // Constants
 // ============================================================================
 
-pub const MAX_PROOF_SIZE: usize = 16384;  // 16KB to handle larger proofs
+/// Room for [`MAX_QUERIES`] openings of up to ~2.5 KB each plus the header
+pub const MAX_PROOF_SIZE: usize = 88 * 1024;
 pub const NUM_FRI_QUERIES: usize = 8;
+/// Most query openings a proof may carry. Beyond about 8 they no longer fit
+/// one transaction's compute budget; such proofs go through staged
+/// verification, which splits the queries by measured cost.
+pub const MAX_QUERIES: usize = 32;
+/// Compute units `verify_staged_queries` keeps in hand after its last query
+/// for writing the checkpoint back
+pub const STAGED_QUERY_CU_RESERVE: u64 = 10_000;
 
 pub const LOG_BLOWUP: u32 = 4;
 pub const LOG_FOLDING_FACTOR: u32 = 2; // Fold by 4 each round
 pub const BLOWUP_FACTOR: usize = 1 << LOG_BLOWUP;
 
+/// Bounds for parsed proof structures, sized for [`VerifierConfig::MURKL`]:
+/// one FRI layer per fold of the 2^14 evaluation domain, and Merkle paths no
+/// deeper than that domain.
+pub const MAX_FRI_LAYERS: usize =
+    (VerifierConfig::MURKL.log_domain_size() / LOG_FOLDING_FACTOR) as usize;
+pub const MAX_MERKLE_DEPTH: usize = VerifierConfig::MURKL.log_domain_size() as usize;
+pub const MAX_FINAL_POLY_LEN: usize = 16;
+/// Most last-layer values a proof may send in place of the final polynomial.
+/// Each one past the degree bound costs an interpolation check, so proofs
+/// sending evaluations fold further than coefficient proofs need to.
+pub const MAX_FINAL_LAYER_EVALS: usize = 64;
+/// Most trace columns a proof may open per query
+pub const MAX_TRACE_COLUMNS: usize = 32;
+/// Most buffers one `finalize_and_verify_batch` takes. Compute is the real
+/// limit: each proof costs what `finalize_and_verify` does.
+pub const MAX_BATCH_PROOFS: usize = 8;
+/// Expiry the client and relayer give their buffers: about a day of slots,
+/// well past any upload, verification and claim
+pub const DEFAULT_BUFFER_EXPIRY_SLOTS: u64 = 216_000;
+/// Longest expiry `init_proof_buffer` accepts, about 30 days
+pub const MAX_BUFFER_EXPIRY_SLOTS: u64 = 6_480_000;
+/// Share of an expired buffer's lamports `reap_expired_buffer` pays its caller
+pub const REAP_REWARD_BPS: u64 = 500;
+/// Proof bytes per bit of a buffer's upload bitmap. Uploads start on a
+/// multiple of it and end on one, or at the end of the proof.
+pub const UPLOAD_CHUNK_ALIGN: usize = 64;
+/// Upload bitmap bytes, enough for a [`MAX_PROOF_SIZE`] proof
+pub const UPLOAD_BITMAP_LEN: usize = MAX_PROOF_SIZE / UPLOAD_CHUNK_ALIGN / 8;
+
+/// Magic, format version, the final-layer flag and the trace row leaf prefix
+/// come from the canonical encoding provers emit; the bounds above are this
+/// verifier's and its tests hold them equal to the format's
+pub use murkl_proof_format::{FINAL_EVALUATIONS_FLAG, FRI_GROUP_DOMAIN, PROOF_MAGIC, PROOF_VERSION, TRACE_ROW_DOMAIN};
+/// Named (queries, blowup, hash) presets; see [`VerifierConfig::for_level`]
+pub use murkl_proof_format::SecurityLevel;
+
+/// Seed of the [`VerifierParams`] PDA
+pub const VERIFIER_PARAMS_SEED: &[u8] = b"verifier-params";
+/// Seed of the [`VerifierConfigAccount`] PDA
+pub const VERIFIER_CONFIG_SEED: &[u8] = b"verifier-config";
+
+/// Weakest profile a [`VerifierConfigAccount`] may select. Only `demo`
+/// builds go down to [`SecurityLevel::Demo`]'s 4 queries, so a mainnet
+/// build cannot be configured into accepting them.
+pub const MIN_SECURITY_LEVEL: SecurityLevel =
+    if cfg!(feature = "demo") { SecurityLevel::Demo } else { SecurityLevel::Standard96 };
+
+/// Shape parameters a proof is checked against before any FRI work.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifierConfig {
+    /// log2 of the trace length (the committed polynomial's degree bound)
+    pub log_trace_size: u32,
+    pub log_blowup: u32,
+    pub log_folding_factor: u32,
+    /// log2 of the final polynomial's degree bound
+    pub log_final_poly_degree: u32,
+    /// Fewest query openings a proof may carry
+    pub min_queries: usize,
+    /// Trace columns opened per query
+    pub trace_columns: usize,
+    /// Bit `k` set accepts proofs whose [`HashKind`] byte is `k`
+    pub hash_kinds: u8,
+}
+
+impl VerifierConfig {
+    /// Parameters the Murkl provers (CLI + WASM) generate proofs for.
+    pub const MURKL: Self = Self {
+        log_trace_size: 10,
+        log_blowup: LOG_BLOWUP,
+        log_folding_factor: LOG_FOLDING_FACTOR,
+        log_final_poly_degree: 4,
+        min_queries: 4,
+        trace_columns: WITNESS_COLUMNS,
+        hash_kinds: (1 << HashKind::Keccak as u8)
+            | (1 << HashKind::Poseidon2 as u8)
+            | if cfg!(feature = "poseidon-syscall") { 1 << HashKind::PoseidonBn254 as u8 } else { 0 },
+    };
+
+    /// [`VerifierConfig::MURKL`] requiring `level`'s queries and blowup and
+    /// taking only the hash kinds both accept
+    pub const fn for_level(level: SecurityLevel) -> Self {
+        Self {
+            log_blowup: level.log_blowup(),
+            min_queries: level.num_queries(),
+            hash_kinds: Self::MURKL.hash_kinds & level.hash_kinds(),
+            ..Self::MURKL
+        }
+    }
+
+    /// [`VerifierConfig::for_level`] for a [`VerifierConfigAccount`]'s level
+    /// byte, refusing unknown levels and those below [`MIN_SECURITY_LEVEL`]
+    pub fn for_profile(security_level: u8) -> Result<Self> {
+        let level = SecurityLevel::from_byte(security_level).ok_or(VerifierError::InvalidSecurityLevel)?;
+        require!(level >= MIN_SECURITY_LEVEL, VerifierError::SecurityLevelNotAllowed);
+        Ok(Self::for_level(level))
+    }
+
+    /// Level every proof this config accepts meets; proof of work is not
+    /// checked on chain and does not count
+    pub fn security_level(&self) -> Option<SecurityLevel> {
+        SecurityLevel::guaranteed(self.min_queries, self.log_blowup, self.hash_kinds)
+    }
+
+    pub const fn log_domain_size(&self) -> u32 {
+        self.log_trace_size + self.log_blowup
+    }
+
+    /// Folding rounds needed to bring the degree bound down to the final
+    /// polynomial's: ceil((log_trace_size - log_final_poly_degree) / log_folding_factor).
+    pub const fn required_fri_layers(&self) -> usize {
+        let reduction = self.log_trace_size.saturating_sub(self.log_final_poly_degree);
+        reduction.div_ceil(self.log_folding_factor) as usize
+    }
+
+    pub const fn max_final_poly_len(&self) -> usize {
+        1 << self.log_final_poly_degree
+    }
+
+    pub const fn accepts(&self, hash_kind: HashKind) -> bool {
+        self.hash_kinds & (1 << hash_kind as u8) != 0
+    }
+
+    /// Require the parameters a proof declares to be the ones this config
+    /// verifies under
+    pub fn check_header(&self, header: &ProofHeader) -> Result<()> {
+        require!(self.accepts(header.hash_kind), VerifierError::UnsupportedHashKind);
+        require!(
+            header.log_trace_size == self.log_trace_size && header.log_blowup == self.log_blowup,
+            VerifierError::DomainSizeMismatch
+        );
+        require!(
+            header.log_folding_factor == self.log_folding_factor,
+            VerifierError::FoldingFactorMismatch
+        );
+        // Without openings only the OODS equation is checked, which any prover
+        // can satisfy by picking composition_oods after the fact.
+        require!(header.num_queries >= self.min_queries, VerifierError::TooFewQueries);
+        Ok(())
+    }
+
+    /// What pools pin to refuse proofs checked under weaker parameters:
+    /// keccak("murkl_verifier_params_v1" || log_trace_size || log_blowup ||
+    /// log_folding_factor || log_final_poly_degree || min_queries ||
+    /// trace_columns || hash_kinds), the integers u32 LE and the mask a byte.
+    pub fn params_hash(&self) -> [u8; 32] {
+        keccak::hashv(&[
+            b"murkl_verifier_params_v1",
+            &self.log_trace_size.to_le_bytes(),
+            &self.log_blowup.to_le_bytes(),
+            &self.log_folding_factor.to_le_bytes(),
+            &self.log_final_poly_degree.to_le_bytes(),
+            &(self.min_queries as u32).to_le_bytes(),
+            &(self.trace_columns as u32).to_le_bytes(),
+            &[self.hash_kinds],
+        ])
+        .0
+    }
+}
+
 /// Debug-only logs for Fiat–Shamir transcript inputs.
 /// Keep false in production to avoid unnecessary data exposure + log spam.
 pub const DEBUG_FS_LOGS: bool = false;
 
+/// Emit a [`CuBreakdownEvent`] after each verification. Set by the
+/// `measure-cu` feature; off, the stage timers never read the meter.
+pub const MEASURE_CU: bool = cfg!(feature = "measure-cu");
+
 // Buffer layout (raw, no Anchor discriminator):
-// [0..32]: owner
-// [32..36]: size (u32 LE)
-// [36..40]: expected_size (u32 LE)
-// [40]: finalized (0 or 1)
-// [41..73]: commitment (32 bytes)
-// [73..105]: nullifier (32 bytes)
-// [105..137]: merkle_root (32 bytes)
-// [137..]: proof data
-
-// Buffer layout:
-// [0..32)   owner pubkey
-// [32..36)  size (u32)
-// [36..40)  expected_size (u32)
-// [40]      finalized (u8)
-// [41..73)  commitment (32)
-// [73..105) nullifier (32)
+// [0..32)    owner pubkey
+// [32..36)   size (u32 LE)
+// [36..40)   expected_size (u32 LE)
+// [40]       finalized (u8)
+// [41..73)   commitment (32)
+// [73..105)  nullifier (32)
 // [105..137) merkle_root (32)
 // [137..169) recipient (32)
-// [169..]   proof bytes
-const HEADER_SIZE: usize = 169;
+// [169..201) upload digest (32) - rolling keccak over uploaded chunks
+// [201..209) finalized slot (u64 LE) - slot of the last successful verification
+// [209..241) upload delegate (32) - may upload chunks besides the owner; zero = none
+// [241..249) created at (u64 LE) - slot of init_proof_buffer
+// [249..257) expiry slots (u64 LE) - anyone may reap the buffer this long after creation
+// [257..417) upload bitmap - bit i (LSB first) set once bytes [64i, 64i + 64) are written
+// [417..449) expected keccak (32) - keccak256 of the whole data region, fixed at init
+// [449..]    proof bytes, optionally after a public inputs block:
+//            PUBLIC_INPUTS_MAGIC | count (u8) | count × 32-byte inputs
+//
+// `size` is the furthest byte written; completeness is read off the bitmap,
+// so gaps and out-of-order uploads are accounted for. A complete upload must
+// then hash to the expected keccak before anything verifies it.
+const HEADER_SIZE: usize = 449;
 const OFFSET_OWNER: usize = 0;
 const OFFSET_SIZE: usize = 32;
 const OFFSET_EXPECTED_SIZE: usize = 36;
@@ -66,7 +244,22 @@ const OFFSET_COMMITMENT: usize = 41;
 const OFFSET_NULLIFIER: usize = 73;
 const OFFSET_MERKLE_ROOT: usize = 105;
 const OFFSET_RECIPIENT: usize = 137;
-const OFFSET_PROOF_DATA: usize = 169;
+const OFFSET_UPLOAD_DIGEST: usize = 169;
+const OFFSET_FINALIZED_SLOT: usize = 201;
+const OFFSET_DELEGATE: usize = 209;
+const OFFSET_CREATED_AT: usize = 241;
+const OFFSET_EXPIRY_SLOTS: usize = 249;
+const OFFSET_UPLOAD_BITMAP: usize = 257;
+const OFFSET_EXPECTED_KECCAK: usize = 417;
+const OFFSET_PROOF_DATA: usize = 449;
+
+/// Opens a public inputs block uploaded ahead of the proof, for
+/// `finalize_and_verify_committed`
+pub const PUBLIC_INPUTS_MAGIC: [u8; 4] = *b"MPUB";
+/// Most inputs a public inputs block may hold
+pub const MAX_PUBLIC_INPUTS: usize = 64;
+/// Domain prefix of [`public_inputs_hash`]
+pub const PUBLIC_INPUTS_DOMAIN: &[u8] = b"murkl_public_inputs_v1";
 
 // ============================================================================
 // Program
@@ -80,12 +273,23 @@ pub mod stark_verifier {
     /// 
     /// The proof_buffer account must be pre-created by the caller with sufficient space
     /// using SystemProgram.createAccount before calling this instruction.
-    /// Required space: HEADER_SIZE (137) + expected_size bytes
+    /// Required space: HEADER_SIZE (449) + expected_size bytes
+    ///
+    /// `expiry_slots` after this slot, up to [`MAX_BUFFER_EXPIRY_SLOTS`],
+    /// anyone may close the buffer with `reap_expired_buffer`.
+    /// `expected_keccak` is the keccak256 of the bytes to be uploaded; the
+    /// buffer is not verified unless its data hashes to it.
     pub fn init_proof_buffer(
         ctx: Context<InitProofBuffer>,
         expected_size: u32,
+        expiry_slots: u64,
+        expected_keccak: [u8; 32],
     ) -> Result<()> {
         require!(expected_size as usize <= MAX_PROOF_SIZE, VerifierError::ProofTooLarge);
+        require!(
+            (1..=MAX_BUFFER_EXPIRY_SLOTS).contains(&expiry_slots),
+            VerifierError::InvalidBufferExpiry
+        );
         
         let buffer = &ctx.accounts.proof_buffer;
         let data_len = buffer.data_len();
@@ -102,6 +306,9 @@ pub mod stark_verifier {
         data[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].copy_from_slice(&expected_size.to_le_bytes());
         data[OFFSET_FINALIZED] = 0;
         data[OFFSET_COMMITMENT..OFFSET_PROOF_DATA].fill(0);
+        data[OFFSET_CREATED_AT..OFFSET_CREATED_AT + 8].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+        data[OFFSET_EXPIRY_SLOTS..OFFSET_EXPIRY_SLOTS + 8].copy_from_slice(&expiry_slots.to_le_bytes());
+        data[OFFSET_EXPECTED_KECCAK..OFFSET_EXPECTED_KECCAK + 32].copy_from_slice(&expected_keccak);
         
         msg!("Proof buffer initialized, expecting {} bytes", expected_size);
         Ok(())
@@ -114,6 +321,53 @@ pub mod stark_verifier {
     ) -> Result<()> {
         let buffer = &ctx.accounts.proof_buffer;
         let mut buf_data = buffer.try_borrow_mut_data()?;
+        check_uploadable(&buf_data, &ctx.accounts.uploader.key())?;
+        write_chunk(&mut buf_data, offset, &chunk_data)?;
+        
+        msg!("Uploaded {} bytes at offset {}", chunk_data.len(), offset);
+        Ok(())
+    }
+
+    /// `upload_chunk` for several segments in one instruction.
+    ///
+    /// `data` holds the segments back to back, `segments[i].len` bytes
+    /// each. Segments need not be contiguous, so one transaction can fill
+    /// whatever gaps the upload bitmap shows, e.g. chunks dropped by an
+    /// earlier batch. Each segment folds into the upload digest as its own
+    /// `upload_chunk` would.
+    pub fn upload_chunks_multi(
+        ctx: Context<UploadChunk>,
+        segments: Vec<UploadSegment>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            !segments.is_empty()
+                && segments.iter().map(|segment| segment.len as usize).sum::<usize>() == data.len(),
+            VerifierError::InvalidUploadSegments
+        );
+        let buffer = &ctx.accounts.proof_buffer;
+        let mut buf_data = buffer.try_borrow_mut_data()?;
+        check_uploadable(&buf_data, &ctx.accounts.uploader.key())?;
+        
+        let mut rest = &data[..];
+        for segment in &segments {
+            let (chunk, tail) = rest.split_at(segment.len as usize);
+            write_chunk(&mut buf_data, segment.offset, chunk)?;
+            rest = tail;
+        }
+        
+        msg!("Uploaded {} bytes in {} segments", data.len(), segments.len());
+        Ok(())
+    }
+
+    /// Let a second signer upload chunks to the buffer.
+    ///
+    /// Upload farms create buffers under one key and spread chunk writes
+    /// across worker keys. The delegate can only call `upload_chunk`;
+    /// finalizing, re-verifying, closing and claiming stay with the owner.
+    /// `Pubkey::default()` revokes the delegation.
+    pub fn delegate_uploader(ctx: Context<DelegateUploader>, delegate: Pubkey) -> Result<()> {
+        let mut buf_data = ctx.accounts.proof_buffer.try_borrow_mut_data()?;
         require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
         
         let owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
@@ -121,32 +375,119 @@ pub mod stark_verifier {
         require!(owner == ctx.accounts.owner.key(), VerifierError::Unauthorized);
         require!(buf_data[OFFSET_FINALIZED] == 0, VerifierError::BufferAlreadyFinalized);
         
-        let expected_size = u32::from_le_bytes(buf_data[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
-        
-        let start = OFFSET_PROOF_DATA + offset as usize;
-        let end = start + chunk_data.len();
-        require!(end <= OFFSET_PROOF_DATA + expected_size as usize, VerifierError::ProofTooLarge);
-        require!(end <= buf_data.len(), VerifierError::ProofTooLarge);
+        buf_data[OFFSET_DELEGATE..OFFSET_DELEGATE + 32].copy_from_slice(delegate.as_ref());
         
-        buf_data[start..end].copy_from_slice(&chunk_data);
-        
-        let new_size = (offset as usize + chunk_data.len()) as u32;
-        let current_size = u32::from_le_bytes(buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
-        if new_size > current_size {
-            buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].copy_from_slice(&new_size.to_le_bytes());
-        }
-        
-        msg!("Uploaded {} bytes at offset {}", chunk_data.len(), offset);
+        msg!("Upload delegate set to {}", delegate);
         Ok(())
     }
 
+    /// Verify the uploaded proof against its public inputs and mark the
+    /// buffer finalized.
+    ///
+    /// With `auto_close` the buffer is closed straight after and its rent
+    /// refunded to the owner, who must then be writable. Nothing is left to
+    /// read the verification from, so this suits a program that verifies
+    /// through CPI and acts on the result in the same instruction.
     pub fn finalize_and_verify(
         ctx: Context<FinalizeAndVerify>,
         commitment: [u8; 32],
         nullifier: [u8; 32],
         merkle_root: [u8; 32],
         recipient: [u8; 32],
+        auto_close: bool,
+    ) -> Result<()> {
+        let inputs = BatchEntry { commitment, nullifier, merkle_root, recipient };
+        {
+            let config = ctx.accounts.verifier_config.config()?;
+            let mut buf_data = ctx.accounts.proof_buffer.try_borrow_mut_data()?;
+            finalize_buffer(&mut buf_data, &ctx.accounts.owner.key(), &inputs, &config, Clock::get()?.slot)?;
+        }
+        
+        if auto_close {
+            let owner = ctx.accounts.owner.to_account_info();
+            require!(owner.is_writable, ErrorCode::AccountNotMutable);
+            close_buffer(&ctx.accounts.proof_buffer, &owner)?;
+            msg!("STARK proof verified; buffer closed");
+            return Ok(());
+        }
+        
+        msg!("STARK proof verified and finalized");
+        Ok(())
+    }
+
+    /// Finalize a buffer whose public inputs were uploaded ahead of its proof.
+    ///
+    /// The data region starts with a public inputs block and
+    /// `public_inputs_hash` must be [`public_inputs_hash`] of its inputs, so
+    /// the instruction carries 32 bytes however many inputs the proof has.
+    /// The first four are commitment, nullifier, Merkle root and recipient,
+    /// stored in the header as `finalize_and_verify` stores its arguments;
+    /// every input is absorbed into the transcript.
+    pub fn finalize_and_verify_committed(
+        ctx: Context<FinalizeAndVerify>,
+        public_inputs_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = ctx.accounts.verifier_config.config()?;
+        let mut buf_data = ctx.accounts.proof_buffer.try_borrow_mut_data()?;
+        let owner = ctx.accounts.owner.key();
+        finalize_committed_buffer(&mut buf_data, &owner, &public_inputs_hash, &config, Clock::get()?.slot)?;
+        
+        msg!("STARK proof verified against committed inputs and finalized");
+        Ok(())
+    }
+
+    /// Finalize several of the owner's proof buffers in one instruction.
+    ///
+    /// The buffers are the writable `remaining_accounts`, one per entry of
+    /// `proofs`, in order. Each outcome is emitted as a [`BatchProofResult`].
+    /// With `atomic` the first failure fails the instruction; otherwise a
+    /// failed buffer is left unfinalized and the rest still finalize.
+    ///
+    /// Every proof runs its own Fiat-Shamir transcript: the commitment is
+    /// absorbed first, so no two transcripts share a state to reuse. A batch
+    /// saves the per-transaction and signature overhead, not verification
+    /// compute.
+    pub fn finalize_and_verify_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeAndVerifyBatch<'info>>,
+        proofs: Vec<BatchEntry>,
+        atomic: bool,
     ) -> Result<()> {
+        require!(
+            !proofs.is_empty() && proofs.len() <= MAX_BATCH_PROOFS && proofs.len() == ctx.remaining_accounts.len(),
+            VerifierError::InvalidBatch
+        );
+        let owner = ctx.accounts.owner.key();
+        let config = ctx.accounts.verifier_config.config()?;
+        let slot = Clock::get()?.slot;
+        
+        let mut verified = 0;
+        for (index, (buffer, inputs)) in ctx.remaining_accounts.iter().zip(&proofs).enumerate() {
+            let result = finalize_batch_entry(buffer, ctx.program_id, &owner, inputs, &config, slot);
+            emit!(BatchProofResult {
+                buffer: buffer.key(),
+                index: index as u8,
+                verified: result.is_ok(),
+                error_code: result.as_ref().err().map_or(0, error_code),
+            });
+            match result {
+                Ok(()) => verified += 1,
+                Err(e) if atomic => return Err(e),
+                Err(_) => {}
+            }
+        }
+        
+        msg!("Batch: {}/{} proofs verified", verified, proofs.len());
+        Ok(())
+    }
+
+    /// Re-run verification on a finalized buffer and restamp its slot.
+    ///
+    /// Pools may refuse proofs finalized too long ago; an owner holding an
+    /// expired buffer re-verifies it against the stored public inputs
+    /// instead of uploading the proof again. A buffer with a public inputs
+    /// block is re-verified against all of the block's inputs.
+    pub fn reverify_proof_buffer(ctx: Context<FinalizeAndVerify>) -> Result<()> {
+        let config = ctx.accounts.verifier_config.config()?;
         let buffer = &ctx.accounts.proof_buffer;
         let mut buf_data = buffer.try_borrow_mut_data()?;
         require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
@@ -154,26 +495,298 @@ pub mod stark_verifier {
         let owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
             .map_err(|_| VerifierError::BufferCorrupt)?;
         require!(owner == ctx.accounts.owner.key(), VerifierError::Unauthorized);
-        require!(buf_data[OFFSET_FINALIZED] == 0, VerifierError::BufferAlreadyFinalized);
+        require!(buf_data[OFFSET_FINALIZED] == 1, VerifierError::ProofNotFinalized);
+        
+        let read32 = |offset: usize| -> Result<[u8; 32]> {
+            Ok(buf_data[offset..offset + 32].try_into().map_err(|_| VerifierError::BufferCorrupt)?)
+        };
+        let commitment = read32(OFFSET_COMMITMENT)?;
+        let nullifier = read32(OFFSET_NULLIFIER)?;
+        let merkle_root = read32(OFFSET_MERKLE_ROOT)?;
+        let recipient = read32(OFFSET_RECIPIENT)?;
+        
+        let (inputs, proof_data) = split_public_inputs(uploaded_data(&buf_data)?)?;
+        if inputs.is_empty() {
+            verify_under(&config, proof_data, &[commitment, nullifier, merkle_root, recipient])?;
+        } else {
+            verify_under(&config, proof_data, inputs)?;
+        }
+        
+        buf_data[OFFSET_FINALIZED_SLOT..OFFSET_FINALIZED_SLOT + 8].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+        
+        msg!("STARK proof re-verified");
+        Ok(())
+    }
+
+    /// Report upload progress for a proof buffer.
+    ///
+    /// Returns bytes written so far plus the rolling upload digest, letting
+    /// uploaders confirm the buffer holds exactly the chunks they sent
+    /// (in the order they sent them) before paying for verification. The
+    /// chunk counts come from the upload bitmap: the proof is complete once
+    /// every expected chunk is in.
+    pub fn get_upload_status(ctx: Context<GetUploadStatus>) -> Result<UploadStatus> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
         
-        let size = u32::from_le_bytes(buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
+        let bytes_written = u32::from_le_bytes(buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
         let expected_size = u32::from_le_bytes(buf_data[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
-        require!(size == expected_size, VerifierError::IncompleteProof);
+        let digest: [u8; 32] = buf_data[OFFSET_UPLOAD_DIGEST..OFFSET_UPLOAD_DIGEST + 32]
+            .try_into()
+            .map_err(|_| VerifierError::BufferCorrupt)?;
+        let bitmap = upload_bitmap(&buf_data)?;
+        let chunks_expected = upload_chunk_count(expected_size as usize);
+        let chunks_received = (0..chunks_expected).filter(|&i| chunk_received(bitmap, i)).count() as u32;
+        
+        msg!(
+            "Upload status: {}/{} bytes, {}/{} chunks",
+            bytes_written,
+            expected_size,
+            chunks_received,
+            chunks_expected
+        );
+        Ok(UploadStatus {
+            bytes_written,
+            expected_size,
+            finalized: buf_data[OFFSET_FINALIZED] == 1,
+            digest,
+            chunks_received,
+            chunks_expected: chunks_expected as u32,
+        })
+    }
+
+    /// Verify a fully uploaded buffer against the given public inputs
+    /// without finalizing it, for programs that verify through CPI.
+    ///
+    /// Read-only and permissionless. The [`VerificationResult`] comes back as
+    /// return data: a rejected proof is `success: false` with its report, not
+    /// a failed instruction, so callers must check it (murkl-verifier-cpi
+    /// does). `compute_units` is measured on-chain.
+    pub fn verify_from_buffer(
+        ctx: Context<VerifyFromBuffer>,
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
+    ) -> Result<VerificationResult> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        let proof_data = uploaded_proof(&buf_data)?;
+        
+        let before = remaining_compute_units();
+        let config = ctx.accounts.verifier_config.config()?;
+        let mut result = verify_proof_cpi(&config, proof_data, &commitment, &nullifier, &merkle_root, &recipient)?;
+        if let (Some(before), Some(after)) = (before, remaining_compute_units()) {
+            result.compute_units = before.saturating_sub(after);
+        }
+        
+        msg!("Buffer proof verified: {}", result.success);
+        Ok(result)
+    }
+
+    /// Attest a (key, value) pair under a namespace, backed by a verified proof.
+    ///
+    /// The namespace authority (a wallet, or an integrator program signing
+    /// with its PDA) must sign, so only it can write into its namespace. The
+    /// attestation records the nullifier and Merkle root the proof was
+    /// verified against; integrators check the root against their own set.
+    /// One attestation per (namespace, nullifier): `nullifier` must be the
+    /// buffer's, and a proof that already attested a key under the
+    /// namespace fails on init.
+    pub fn attest_inclusion(
+        ctx: Context<AttestInclusion>,
+        nullifier: [u8; 32],
+        key: [u8; 32],
+        value: [u8; 32],
+    ) -> Result<()> {
+        let (nullifier, merkle_root) = {
+            let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+            require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+            require!(buf_data[OFFSET_FINALIZED] == 1, VerifierError::ProofNotFinalized);
+            
+            let owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
+                .map_err(|_| VerifierError::BufferCorrupt)?;
+            require!(owner == ctx.accounts.prover.key(), VerifierError::Unauthorized);
+            
+            require!(
+                buf_data[OFFSET_NULLIFIER..OFFSET_NULLIFIER + 32] == nullifier,
+                VerifierError::NullifierMismatch
+            );
+            let merkle_root: [u8; 32] = buf_data[OFFSET_MERKLE_ROOT..OFFSET_MERKLE_ROOT + 32]
+                .try_into()
+                .map_err(|_| VerifierError::BufferCorrupt)?;
+            (nullifier, merkle_root)
+        };
+        
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.namespace = ctx.accounts.namespace.key();
+        attestation.key = key;
+        attestation.value = value;
+        attestation.nullifier = nullifier;
+        attestation.merkle_root = merkle_root;
+        attestation.attested_at = Clock::get()?.unix_timestamp;
+        attestation.bump = ctx.bumps.attestation;
+        
+        msg!("Attested key under namespace {}", attestation.namespace);
+        Ok(())
+    }
+
+    /// Create the VerifierConfigAccount with the profile every verifying
+    /// instruction checks proofs at (deployer, once). The signer becomes its
+    /// authority.
+    ///
+    /// `security_level` is a [`SecurityLevel`] byte no weaker than
+    /// [`MIN_SECURITY_LEVEL`]: only `demo` builds take [`SecurityLevel::Demo`].
+    pub fn initialize_verifier_config(ctx: Context<InitializeVerifierConfig>, security_level: u8) -> Result<()> {
+        VerifierConfig::for_profile(security_level)?;
+        let config = &mut ctx.accounts.verifier_config;
+        config.authority = ctx.accounts.authority.key();
+        config.security_level = security_level;
+        config.bump = ctx.bumps.verifier_config;
+
+        msg!("Verifier config initialized, authority: {}", config.authority);
+        Ok(())
+    }
+
+    /// Switch the profile proofs are checked at (config authority).
+    ///
+    /// Takes effect on the next verification; staged runs already begun keep
+    /// the query count they were started with. Republish the VerifierParams
+    /// afterwards so pools pinning a params hash see the change.
+    pub fn set_verifier_profile(ctx: Context<SetVerifierProfile>, security_level: u8) -> Result<()> {
+        VerifierConfig::for_profile(security_level)?;
+        ctx.accounts.verifier_config.security_level = security_level;
+
+        msg!("Verifier profile set to level {}", security_level);
+        Ok(())
+    }
+
+    /// Write the parameters this deployment verifies under to the
+    /// VerifierParams PDA, creating it on first use.
+    ///
+    /// Permissionless: the values come from the program and its
+    /// VerifierConfigAccount, so calling it after an upgrade or a profile
+    /// change is all it takes to keep the PDA current. Pools that pin a
+    /// params hash read it at claim time.
+    pub fn publish_verifier_params(ctx: Context<PublishVerifierParams>) -> Result<()> {
+        let config = ctx.accounts.verifier_config.config()?;
+        let params = &mut ctx.accounts.verifier_params;
+        params.params_hash = config.params_hash();
+        params.log_trace_size = config.log_trace_size;
+        params.log_blowup = config.log_blowup;
+        params.log_folding_factor = config.log_folding_factor;
+        params.log_final_poly_degree = config.log_final_poly_degree;
+        params.min_queries = config.min_queries as u32;
+        params.trace_columns = config.trace_columns as u32;
+        params.hash_kinds = config.hash_kinds;
+        params.bump = ctx.bumps.verifier_params;
+
+        msg!("Verifier params published");
+        Ok(())
+    }
+
+    /// Start verifying a fully uploaded buffer across several transactions.
+    ///
+    /// For proofs that do not fit one transaction's compute budget. The
+    /// stages are this instruction, `verify_staged_oods`, one or more
+    /// `verify_staged_queries` and `finalize_staged_verification`; between
+    /// them the Fiat-Shamir channel lives in the VerificationState PDA,
+    /// tagged with the stage allowed to run next. The buffer's upload digest
+    /// is pinned here and rechecked by every later stage.
+    pub fn begin_staged_verification(
+        ctx: Context<BeginStagedVerification>,
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
+    ) -> Result<()> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        let owner = ctx.accounts.owner.key();
+        let (proof_data, upload_digest) = staged_proof(&buf_data, &owner)?;
+        let proof = parse_proof(proof_data)?;
+        check_proof_shape(&proof, &ctx.accounts.verifier_config.config()?)?;
         
-        let proof_data = &buf_data[OFFSET_PROOF_DATA..OFFSET_PROOF_DATA + size as usize].to_vec();
+        let mut channel = Channel::new(proof.header.hash_kind);
+        absorb_public_inputs(&mut channel, &commitment, &nullifier, &merkle_root, &recipient, &[]);
         
-        // Full STARK verification - no shortcuts
-        // Includes recipient binding via Fiat–Shamir transcript.
-        verify_stark_proof(proof_data, &commitment, &nullifier, &merkle_root, &recipient)?;
+        let state = &mut ctx.accounts.verification_state;
+        state.proof_buffer = ctx.accounts.proof_buffer.key();
+        state.owner = owner;
+        state.upload_digest = upload_digest;
+        state.commitment = commitment;
+        state.nullifier = nullifier;
+        state.merkle_root = merkle_root;
+        state.recipient = recipient;
+        state.channel = channel.checkpoint(VerificationPhase::Oods);
+        state.queries_verified = 0;
+        state.bump = ctx.bumps.verification_state;
         
-        // Store verified public inputs
-        buf_data[OFFSET_COMMITMENT..OFFSET_COMMITMENT + 32].copy_from_slice(&commitment);
-        buf_data[OFFSET_NULLIFIER..OFFSET_NULLIFIER + 32].copy_from_slice(&nullifier);
-        buf_data[OFFSET_MERKLE_ROOT..OFFSET_MERKLE_ROOT + 32].copy_from_slice(&merkle_root);
-        buf_data[OFFSET_RECIPIENT..OFFSET_RECIPIENT + 32].copy_from_slice(&recipient);
+        msg!("Staged verification started");
+        Ok(())
+    }
+
+    /// Stage 2: commitments, the OODS constraint check and FRI alphas
+    pub fn verify_staged_oods(ctx: Context<StagedVerification>) -> Result<()> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        let state = &mut ctx.accounts.verification_state;
+        let proof_data = state.check_buffer(&buf_data)?;
+        run_oods_stage(state, proof_data)
+    }
+
+    /// Stage 3: verify the next `count` query openings (0 = as many as the
+    /// transaction's compute budget allows). Repeat until every query is
+    /// verified; the last batch moves the run to finalization.
+    ///
+    /// The batch also stops early once the remaining compute units would
+    /// not cover another query at the costliest one measured so far, so a
+    /// caller can always pass 0 and resend until the run completes.
+    pub fn verify_staged_queries(ctx: Context<StagedVerification>, count: u8) -> Result<()> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        let state = &mut ctx.accounts.verification_state;
+        let proof_data = state.check_buffer(&buf_data)?;
+        run_query_stage(state, proof_data, count)
+    }
+
+    /// Stages 2 and 3 without tracking which is next: runs the OODS stage or
+    /// a full-budget query batch, whichever the checkpoint allows. Resend it
+    /// until the logged query count is complete, then call
+    /// `finalize_staged_verification`; once every query is verified it fails
+    /// with `VerificationPhaseMismatch`.
+    pub fn verify_step(ctx: Context<StagedVerification>) -> Result<()> {
+        let buf_data = ctx.accounts.proof_buffer.try_borrow_data()?;
+        let state = &mut ctx.accounts.verification_state;
+        let proof_data = state.check_buffer(&buf_data)?;
+        match VerificationPhase::from_tag(state.channel[0]) {
+            Some(VerificationPhase::Oods) => run_oods_stage(state, proof_data),
+            Some(VerificationPhase::Queries) => run_query_stage(state, proof_data, 0),
+            _ => err!(VerifierError::VerificationPhaseMismatch),
+        }
+    }
+
+    /// Stage 4: record the verified public inputs on the buffer, exactly as
+    /// `finalize_and_verify` does, and close the VerificationState.
+    pub fn finalize_staged_verification(ctx: Context<CloseStagedVerification>) -> Result<()> {
+        let state = &ctx.accounts.verification_state;
+        let mut buf_data = ctx.accounts.proof_buffer.try_borrow_mut_data()?;
+        state.check_buffer(&buf_data)?;
+        require!(
+            VerificationPhase::from_tag(state.channel[0]) == Some(VerificationPhase::Complete),
+            VerifierError::VerificationPhaseMismatch
+        );
+        
+        buf_data[OFFSET_COMMITMENT..OFFSET_COMMITMENT + 32].copy_from_slice(&state.commitment);
+        buf_data[OFFSET_NULLIFIER..OFFSET_NULLIFIER + 32].copy_from_slice(&state.nullifier);
+        buf_data[OFFSET_MERKLE_ROOT..OFFSET_MERKLE_ROOT + 32].copy_from_slice(&state.merkle_root);
+        buf_data[OFFSET_RECIPIENT..OFFSET_RECIPIENT + 32].copy_from_slice(&state.recipient);
+        buf_data[OFFSET_FINALIZED_SLOT..OFFSET_FINALIZED_SLOT + 8].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
         buf_data[OFFSET_FINALIZED] = 1;
         
-        msg!("STARK proof verified and finalized");
+        msg!("STARK proof verified in stages and finalized");
+        Ok(())
+    }
+
+    /// Abandon a staged verification (e.g. after a failed stage), refunding its rent
+    pub fn cancel_staged_verification(_ctx: Context<CloseStagedVerification>) -> Result<()> {
+        msg!("Staged verification cancelled");
         Ok(())
     }
 
@@ -188,44 +801,108 @@ pub mod stark_verifier {
             require!(owner == ctx.accounts.owner.key(), VerifierError::Unauthorized);
         }
         
-        // Zero out all buffer data before closing to prevent stale finalized=1 reads
-        {
-            let mut buf_data = buffer.try_borrow_mut_data()?;
-            buf_data.fill(0);
-        }
-        
-        let dest_starting_lamports = ctx.accounts.owner.lamports();
-        **ctx.accounts.owner.lamports.borrow_mut() = dest_starting_lamports
-            .checked_add(buffer.lamports())
-            .ok_or(VerifierError::LamportOverflow)?;
-        **buffer.lamports.borrow_mut() = 0;
+        close_buffer(buffer, &ctx.accounts.owner.to_account_info())?;
         
         msg!("Proof buffer closed and zeroed");
         Ok(())
     }
+
+    /// Close a buffer past its expiry, permissionless.
+    ///
+    /// Cleans up after uploaders that never came back, finalized or not.
+    /// The caller keeps [`REAP_REWARD_BPS`] of the buffer's lamports and
+    /// the header's owner gets the rest.
+    pub fn reap_expired_buffer(ctx: Context<ReapExpiredBuffer>) -> Result<()> {
+        let reward = reap_buffer(
+            &ctx.accounts.proof_buffer,
+            &ctx.accounts.owner,
+            &ctx.accounts.reaper.to_account_info(),
+            Clock::get()?.slot,
+        )?;
+        
+        msg!("Expired proof buffer reaped, {} lamports to the reaper", reward);
+        Ok(())
+    }
 }
 
 // ============================================================================
-// Proof Structure (STWO-compatible)
+// Proof Structure
 // ============================================================================
 
+/// Fixed-capacity array with an explicit length
+///
+/// Parsing fills these in place, so a proof is decoded without touching the
+/// BPF heap. Derefs to the filled prefix.
+#[derive(Debug, Clone, Copy)]
+struct Bounded<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> Bounded<T, N> {
+    fn new() -> Self {
+        Self { items: [T::default(); N], len: 0 }
+    }
+
+    fn push(&mut self, item: T) -> Result<()> {
+        require!(self.len < N, VerifierError::InvalidProofFormat);
+        self.items[self.len] = item;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> core::ops::Deref for Bounded<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+}
+
 /// Parsed STARK proof structure
+///
+/// Borrows from the proof buffer: commitments and paths are slices of it,
+/// and field elements past the OODS values stay in wire form until used.
+/// Queries are decoded one at a time by [`StarkProof::queries`].
 #[derive(Debug)]
 struct StarkProof<'a> {
+    /// Declared parameters, hash kind and query count
+    header: ProofHeader,
     /// Merkle root of trace polynomial evaluation
-    trace_commitment: [u8; 32],
+    trace_commitment: &'a [u8; 32],
     /// Merkle root of composition polynomial evaluation
-    composition_commitment: [u8; 32],
-    /// Trace polynomial evaluated at OODS point (QM31)
-    trace_oods: QM31,
+    composition_commitment: &'a [u8; 32],
+    /// Columns per trace row, as declared in the header
+    num_trace_columns: usize,
+    /// Every trace column at the OODS point
+    trace_oods: Qm31View<'a>,
+    /// Every trace column one trace row past the OODS point
+    trace_oods_next: Qm31View<'a>,
     /// Composition polynomial evaluated at OODS point (QM31)
     composition_oods: QM31,
     /// FRI layer commitments (Merkle roots)
-    fri_layer_commitments: Vec<[u8; 32]>,
+    fri_layer_commitments: &'a [[u8; 32]],
     /// Final polynomial coefficients
-    fri_final_poly: Vec<QM31>,
-    /// Query proofs
-    queries: Vec<QueryProof<'a>>,
+    fri_final_poly: Qm31View<'a>,
+    /// Last-layer values, in layer order, when the proof sends evaluations
+    /// instead; empty otherwise
+    fri_final_evaluations: Qm31View<'a>,
+    /// Encoded query proofs, `header.num_queries` of them
+    query_data: &'a [u8],
+}
+
+impl<'a> StarkProof<'a> {
+    /// Decode the query proofs in order
+    fn queries(&self) -> impl Iterator<Item = Result<QueryProof<'a>>> + '_ {
+        let mut offset = 0;
+        (0..self.header.num_queries).map(move |_| {
+            let data = self.query_data.get(offset..).unwrap_or_default();
+            let query = parse_query_proof(data, self.num_trace_columns, self.fri_layer_commitments.len())?;
+            offset += query.size;
+            Ok(query)
+        })
+    }
 }
 
 /// Proof for a single query position
@@ -233,265 +910,398 @@ struct StarkProof<'a> {
 struct QueryProof<'a> {
     /// Query index in the evaluation domain
     index: u32,
-    /// Trace value at query point
-    trace_value: [u8; 32],
-    /// Merkle path authenticating trace value
-    trace_path: Vec<[u8; 32]>,
+    /// Trace row at query point: one M31 (LE) per column
+    trace_row: &'a [u8],
+    /// Merkle path authenticating the trace row leaf
+    trace_path: &'a [[u8; 32]],
     /// Composition value at query point
-    composition_value: [u8; 32],
+    composition_value: &'a [u8; 32],
     /// Merkle path authenticating composition value
-    composition_path: Vec<[u8; 32]>,
+    composition_path: &'a [[u8; 32]],
     /// FRI layer values (sibling values for folding)
-    fri_layer_values: Vec<FriLayerQuery>,
-    /// Raw FRI data for parsing
-    _fri_data: &'a [u8],
+    fri_layer_values: Bounded<FriLayerQuery<'a>, MAX_FRI_LAYERS>,
+    /// Encoded size in bytes
+    size: usize,
 }
 
 /// FRI layer query data
-#[derive(Debug, Clone)]
-struct FriLayerQuery {
+#[derive(Debug, Clone, Copy, Default)]
+struct FriLayerQuery<'a> {
     /// Sibling values for folding (4 values for fold-by-4)
-    siblings: [QM31; 4],
+    siblings: Qm31View<'a>,
     /// Merkle path for this layer
-    path: Vec<[u8; 32]>,
+    path: &'a [[u8; 32]],
 }
 
-// ============================================================================
-// Proof Parsing
-// ============================================================================
+impl FriLayerQuery<'_> {
+    /// The four siblings, decoded
+    fn decode_siblings(&self) -> [QM31; 4] {
+        core::array::from_fn(|i| self.siblings.get(i).unwrap_or_default())
+    }
+}
 
-fn parse_proof(data: &[u8]) -> Result<StarkProof> {
-    require!(data.len() >= 128, VerifierError::InvalidProofFormat);
-    
-    let mut offset = 0;
-    
-    // Trace commitment (32 bytes)
-    let trace_commitment: [u8; 32] = data[offset..offset+32].try_into()
-        .map_err(|_| VerifierError::InvalidProofFormat)?;
-    offset += 32;
-    
-    // Composition commitment (32 bytes)
-    let composition_commitment: [u8; 32] = data[offset..offset+32].try_into()
-        .map_err(|_| VerifierError::InvalidProofFormat)?;
-    offset += 32;
-    
-    // OODS values (QM31 = 16 bytes each)
-    require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-    let trace_oods = parse_qm31(&data[offset..offset+16])?;
-    offset += 16;
-    
-    let composition_oods = parse_qm31(&data[offset..offset+16])?;
-    offset += 16;
-    
-    // FRI layer count
-    require!(offset < data.len(), VerifierError::InvalidProofFormat);
-    let num_fri_layers = data[offset] as usize;
-    offset += 1;
-    
-    require!(num_fri_layers <= 20, VerifierError::InvalidProofFormat); // Sanity limit
-    
-    // FRI layer commitments
-    let mut fri_layer_commitments = Vec::with_capacity(num_fri_layers);
-    for _ in 0..num_fri_layers {
-        require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-        let commitment: [u8; 32] = data[offset..offset+32].try_into()
-            .map_err(|_| VerifierError::InvalidProofFormat)?;
-        fri_layer_commitments.push(commitment);
-        offset += 32;
+/// QM31 values borrowed in wire form, four LE M31 limbs each, and decoded
+/// on access
+#[derive(Debug, Clone, Copy, Default)]
+struct Qm31View<'a>(&'a [[u8; 16]]);
+
+impl<'a> Qm31View<'a> {
+    fn len(&self) -> usize {
+        self.0.len()
     }
-    
-    // Final polynomial
-    require!(offset + 2 <= data.len(), VerifierError::InvalidProofFormat);
-    let final_poly_count = u16::from_le_bytes([data[offset], data[offset+1]]) as usize;
-    offset += 2;
-    
-    require!(final_poly_count <= 16, VerifierError::FinalPolyDegreeTooHigh); // Max degree 16
-    
-    let mut fri_final_poly = Vec::with_capacity(final_poly_count);
-    for _ in 0..final_poly_count {
-        require!(offset + 16 <= data.len(), VerifierError::InvalidProofFormat);
-        fri_final_poly.push(parse_qm31(&data[offset..offset+16])?);
-        offset += 16;
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
-    
-    // Query count
-    require!(offset < data.len(), VerifierError::InvalidProofFormat);
-    let num_queries = data[offset] as usize;
-    offset += 1;
-    
-    require!(num_queries <= NUM_FRI_QUERIES * 2, VerifierError::InvalidProofFormat);
-    
-    // Parse queries
-    let mut queries = Vec::with_capacity(num_queries);
-    for _ in 0..num_queries {
-        let query = parse_query_proof(&data[offset..], num_fri_layers)?;
-        offset += query_proof_size(&query);
-        queries.push(query);
+
+    fn get(&self, index: usize) -> Option<QM31> {
+        self.0.get(index).map(decode_qm31)
+    }
+
+    fn iter(self) -> impl DoubleEndedIterator<Item = QM31> + 'a {
+        self.0.iter().map(decode_qm31)
     }
-    
-    Ok(StarkProof {
-        trace_commitment,
-        composition_commitment,
-        trace_oods,
-        composition_oods,
-        fri_layer_commitments,
-        fri_final_poly,
-        queries,
-    })
 }
 
-fn parse_qm31(data: &[u8]) -> Result<QM31> {
-    require!(data.len() >= 16, VerifierError::InvalidProofFormat);
-    Ok(QM31::new(
-        M31::new(u32::from_le_bytes([data[0], data[1], data[2], data[3]])),
-        M31::new(u32::from_le_bytes([data[4], data[5], data[6], data[7]])),
-        M31::new(u32::from_le_bytes([data[8], data[9], data[10], data[11]])),
-        M31::new(u32::from_le_bytes([data[12], data[13], data[14], data[15]])),
-    ))
+/// Parameters a proof declares before anything else:
+/// `magic (4) | version | hash_kind | log_trace_size | log_blowup |
+/// log_folding_factor | query_count`, one byte each after the magic
+///
+/// A verifier checks them against its config first
+/// ([`VerifierConfig::check_header`]), so a proof made for other
+/// parameters fails on the field that differs rather than somewhere in FRI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofHeader {
+    pub version: u8,
+    /// Hash behind the Merkle trees and the channel
+    pub hash_kind: HashKind,
+    pub log_trace_size: u32,
+    pub log_blowup: u32,
+    pub log_folding_factor: u32,
+    /// Query openings that follow the final layer
+    pub num_queries: usize,
 }
 
-fn parse_query_proof<'a>(data: &'a [u8], num_fri_layers: usize) -> Result<QueryProof<'a>> {
-    let mut offset = 0;
-    
-    // Index (4 bytes)
-    require!(offset + 4 <= data.len(), VerifierError::InvalidProofFormat);
-    let index = u32::from_le_bytes(data[offset..offset+4].try_into().map_err(|_| VerifierError::InvalidProofFormat)?);
-    offset += 4;
-    
-    // Trace value (32 bytes)
-    require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-    let trace_value: [u8; 32] = data[offset..offset+32].try_into().map_err(|_| VerifierError::InvalidProofFormat)?;
-    offset += 32;
-    
-    // Trace path
-    require!(offset < data.len(), VerifierError::InvalidProofFormat);
-    let trace_path_len = data[offset] as usize;
-    offset += 1;
-    
-    let mut trace_path = Vec::with_capacity(trace_path_len);
-    for _ in 0..trace_path_len {
-        require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-        trace_path.push(data[offset..offset+32].try_into().map_err(|_| VerifierError::InvalidProofFormat)?);
-        offset += 32;
-    }
-    
-    // Composition value (32 bytes)
-    require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-    let composition_value: [u8; 32] = data[offset..offset+32].try_into().map_err(|_| VerifierError::InvalidProofFormat)?;
-    offset += 32;
-    
-    // Composition path
-    require!(offset < data.len(), VerifierError::InvalidProofFormat);
-    let comp_path_len = data[offset] as usize;
-    offset += 1;
-    
-    let mut composition_path = Vec::with_capacity(comp_path_len);
-    for _ in 0..comp_path_len {
-        require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-        composition_path.push(data[offset..offset+32].try_into().map_err(|_| VerifierError::InvalidProofFormat)?);
-        offset += 32;
+impl ProofHeader {
+    /// Encoded size
+    pub const LEN: usize = 10;
+
+    /// Read the header at the start of a proof, checking the magic, the
+    /// version and the parser's bounds
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= Self::LEN, VerifierError::InvalidProofFormat);
+        require!(
+            data[..4] == PROOF_MAGIC && data[4] == PROOF_VERSION,
+            VerifierError::UnsupportedProofVersion
+        );
+        let header = Self {
+            version: data[4],
+            hash_kind: HashKind::from_byte(data[5]).ok_or(VerifierError::UnsupportedHashKind)?,
+            log_trace_size: data[6] as u32,
+            log_blowup: data[7] as u32,
+            log_folding_factor: data[8] as u32,
+            num_queries: data[9] as usize,
+        };
+        // The domain is also the depth of the trace and composition trees
+        let log_domain_size = header.log_domain_size();
+        require!(
+            log_domain_size > 0 && log_domain_size as usize <= MAX_MERKLE_DEPTH,
+            VerifierError::InvalidProofFormat
+        );
+        require!(
+            header.log_folding_factor > 0 && header.log_folding_factor <= log_domain_size,
+            VerifierError::InvalidProofFormat
+        );
+        require!(header.num_queries <= MAX_QUERIES, VerifierError::InvalidProofFormat);
+        Ok(header)
     }
-    
-    // FRI layer values
-    let mut fri_layer_values = Vec::with_capacity(num_fri_layers);
-    for _ in 0..num_fri_layers {
-        // 4 sibling values (QM31 each = 16 bytes)
-        require!(offset + 64 <= data.len(), VerifierError::InvalidProofFormat);
-        let siblings = [
-            parse_qm31(&data[offset..offset+16])?,
-            parse_qm31(&data[offset+16..offset+32])?,
-            parse_qm31(&data[offset+32..offset+48])?,
-            parse_qm31(&data[offset+48..offset+64])?,
-        ];
-        offset += 64;
-        
-        // Layer Merkle path
-        require!(offset < data.len(), VerifierError::InvalidProofFormat);
-        let path_len = data[offset] as usize;
-        offset += 1;
-        
-        let mut path = Vec::with_capacity(path_len);
-        for _ in 0..path_len {
-            require!(offset + 32 <= data.len(), VerifierError::InvalidProofFormat);
-            path.push(data[offset..offset+32].try_into().map_err(|_| VerifierError::InvalidProofFormat)?);
-            offset += 32;
-        }
-        
-        fri_layer_values.push(FriLayerQuery { siblings, path });
+
+    /// log2 of the evaluation domain the query indices are drawn from
+    pub const fn log_domain_size(&self) -> u32 {
+        self.log_trace_size + self.log_blowup
     }
-    
-    Ok(QueryProof {
-        index,
-        trace_value,
-        trace_path,
-        composition_value,
-        composition_path,
-        fri_layer_values,
-        _fri_data: &data[..offset],
-    })
-}
 
-fn query_proof_size(query: &QueryProof) -> usize {
-    4 + 32 + 1 + query.trace_path.len() * 32 +
-    32 + 1 + query.composition_path.len() * 32 +
-    query.fri_layer_values.iter().map(|l| 64 + 1 + l.path.len() * 32).sum::<usize>()
+    /// Depth of FRI layer `layer`'s tree, which has a leaf per folding
+    /// group of the layer; `None` past the layer that folds the domain
+    /// down to one group
+    pub fn fri_layer_depth(&self, layer: usize) -> Option<u32> {
+        self.log_domain_size().checked_sub(self.log_folding_factor * (layer as u32 + 1))
+    }
 }
 
 // ============================================================================
-// Fiat-Shamir Channel
+// Proof Parsing
 // ============================================================================
 
-/// Fiat-Shamir transcript for non-interactive proofs
-/// Generates verifier challenges deterministically from proof commitments
-pub struct Channel {
+/// Read position in an encoded proof
+///
+/// Every read borrows from the underlying slice and fails with
+/// `InvalidProofFormat` if it would run past the end, so parsing never
+/// copies a path or allocates.
+struct Cursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        Self { data, offset }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(VerifierError::InvalidProofFormat)?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<&'a [u8; N]> {
+        Ok(self.take(N)?.try_into().map_err(|_| VerifierError::InvalidProofFormat)?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(*self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(*self.array()?))
+    }
+
+    fn digests(&mut self, count: usize) -> Result<&'a [[u8; 32]]> {
+        Ok(bytemuck::cast_slice(self.take(32 * count)?))
+    }
+
+    /// Length-prefixed Merkle path
+    fn path(&mut self) -> Result<&'a [[u8; 32]]> {
+        let len = self.u8()? as usize;
+        require!(len <= MAX_MERKLE_DEPTH, VerifierError::InvalidProofFormat);
+        self.digests(len)
+    }
+
+    fn qm31(&mut self) -> Result<QM31> {
+        Ok(decode_qm31(self.array()?))
+    }
+
+    fn qm31s(&mut self, count: usize) -> Result<Qm31View<'a>> {
+        Ok(Qm31View(bytemuck::cast_slice(self.take(16 * count)?)))
+    }
+
+    /// Everything not read yet
+    fn rest(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+}
+
+/// Parse the murkl wire format. This is the only encoding verified here:
+/// stwo proofs ([`murkl_proof_format::STWO_PROOF_VERSION`]) fail the header's
+/// version check; see "Proof Formats" in docs/INTEGRATION.md for why.
+fn parse_proof(data: &[u8]) -> Result<StarkProof<'_>> {
+    let header = ProofHeader::parse(data)?;
+    let mut cursor = Cursor::new(data, ProofHeader::LEN);
+
+    let trace_commitment = cursor.array()?;
+    let composition_commitment = cursor.array()?;
+
+    let num_trace_columns = cursor.u8()? as usize;
+    require!(
+        num_trace_columns > 0 && num_trace_columns <= MAX_TRACE_COLUMNS,
+        VerifierError::InvalidProofFormat
+    );
+
+    let trace_oods = cursor.qm31s(num_trace_columns)?;
+    let trace_oods_next = cursor.qm31s(num_trace_columns)?;
+    let composition_oods = cursor.qm31()?;
+
+    let num_fri_layers = cursor.u8()? as usize;
+    require!(num_fri_layers <= MAX_FRI_LAYERS, VerifierError::InvalidProofFormat);
+    let fri_layer_commitments = cursor.digests(num_fri_layers)?;
+
+    // Final polynomial, or the last layer's values when flagged
+    let final_poly_count = cursor.u16()?;
+    let mut fri_final_poly = Qm31View::default();
+    let mut fri_final_evaluations = Qm31View::default();
+    if final_poly_count & FINAL_EVALUATIONS_FLAG != 0 {
+        let count = (final_poly_count & !FINAL_EVALUATIONS_FLAG) as usize;
+        require!(count > 0 && count <= MAX_FINAL_LAYER_EVALS, VerifierError::FinalPolyDegreeTooHigh);
+        fri_final_evaluations = cursor.qm31s(count)?;
+    } else {
+        let count = final_poly_count as usize;
+        require!(count <= MAX_FINAL_POLY_LEN, VerifierError::FinalPolyDegreeTooHigh);
+        fri_final_poly = cursor.qm31s(count)?;
+    }
+
+    Ok(StarkProof {
+        header,
+        trace_commitment,
+        composition_commitment,
+        num_trace_columns,
+        trace_oods,
+        trace_oods_next,
+        composition_oods,
+        fri_layer_commitments,
+        fri_final_poly,
+        fri_final_evaluations,
+        query_data: cursor.rest(),
+    })
+}
+
+/// Parse `proof_data` the way [`verify_stark_proof`] does, every query
+/// included, without checking it against a config or the transcript
+///
+/// Fails with `InvalidProofFormat` on anything malformed, except the
+/// header fields that have errors of their own (magic and version, hash
+/// kind) and a final layer over the parser's bounds. Never panics; the
+/// fuzz targets in `fuzz/` hold it to that.
+pub fn check_proof_encoding(proof_data: &[u8]) -> Result<()> {
+    parse_proof(proof_data)?.queries().try_for_each(|query| query.map(drop))
+}
+
+fn decode_qm31(bytes: &[u8; 16]) -> QM31 {
+    let limb = |i: usize| M31::new(u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]));
+    QM31::new(limb(0), limb(4), limb(8), limb(12))
+}
+
+fn parse_query_proof(data: &[u8], num_trace_columns: usize, num_fri_layers: usize) -> Result<QueryProof<'_>> {
+    let mut cursor = Cursor::new(data, 0);
+    let index = cursor.u32()?;
+
+    // Trace row (4 bytes per column), canonical M31 values only
+    let trace_row = cursor.take(4 * num_trace_columns)?;
+    for value in trace_row.chunks_exact(4) {
+        require!(
+            u32::from_le_bytes([value[0], value[1], value[2], value[3]]) < P,
+            VerifierError::InvalidProofFormat
+        );
+    }
+    let trace_path = cursor.path()?;
+
+    let composition_value = cursor.array()?;
+    let composition_path = cursor.path()?;
+
+    // Per layer: 4 sibling values, then the layer's Merkle path
+    let mut fri_layer_values = Bounded::new();
+    for _ in 0..num_fri_layers {
+        let siblings = cursor.qm31s(4)?;
+        let path = cursor.path()?;
+        fri_layer_values.push(FriLayerQuery { siblings, path })?;
+    }
+
+    Ok(QueryProof {
+        index,
+        trace_row,
+        trace_path,
+        composition_value,
+        composition_path,
+        fri_layer_values,
+        size: cursor.offset,
+    })
+}
+
+// ============================================================================
+// Hashing
+// ============================================================================
+
+/// Hash behind a proof's Merkle trees and Fiat-Shamir channel, from the
+/// proof header. Public inputs are mapped into the field with
+/// keccak either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HashKind {
+    Keccak = 0,
+    /// Field-native; costs far more compute than the keccak syscall
+    Poseidon2 = 1,
+    /// Poseidon on the `sol_poseidon` syscall; accepted only by builds with
+    /// the `poseidon-syscall` feature
+    PoseidonBn254 = 2,
+}
+
+impl HashKind {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Keccak),
+            1 => Some(Self::Poseidon2),
+            2 => Some(Self::PoseidonBn254),
+            _ => None,
+        }
+    }
+
+    /// Hash the concatenation of `inputs`
+    pub fn hash(self, inputs: &[&[u8]]) -> [u8; 32] {
+        match self {
+            Self::Keccak => keccak::hashv(inputs).0,
+            Self::Poseidon2 => poseidon2::hash(inputs),
+            Self::PoseidonBn254 => poseidon_bn254::hash(inputs),
+        }
+    }
+
+    /// Parent of two Merkle nodes
+    pub fn hash_node(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Self::Keccak => keccak::hashv(&[left, right]).0,
+            Self::Poseidon2 => poseidon2::compress(left, right),
+            Self::PoseidonBn254 => poseidon_bn254::compress(left, right),
+        }
+    }
+}
+
+// ============================================================================
+// Fiat-Shamir Channel
+// ============================================================================
+
+/// Fiat-Shamir transcript for non-interactive proofs
+/// Generates verifier challenges deterministically from proof commitments
+pub struct Channel {
     state: [u8; 32],
     counter: u64,
+    hash: HashKind,
 }
 
 impl Channel {
-    pub fn new() -> Self {
+    pub fn new(hash: HashKind) -> Self {
         Self {
             state: [0u8; 32],
             counter: 0,
+            hash,
         }
     }
     
     /// Mix a 32-byte digest into the channel state
     pub fn mix_digest(&mut self, digest: &[u8; 32]) {
-        let mut data = [0u8; 64];
-        data[..32].copy_from_slice(&self.state);
-        data[32..].copy_from_slice(digest);
-        self.state = keccak_hash(&data);
+        self.state = self.hash.hash(&[&self.state, digest]);
         self.counter += 1;
     }
     
     /// Mix arbitrary bytes into the channel
     pub fn mix_bytes(&mut self, bytes: &[u8]) {
-        let hash = keccak_hash(bytes);
+        let hash = self.hash.hash(&[bytes]);
         self.mix_digest(&hash);
     }
     
     /// Mix a QM31 element into the channel
     pub fn mix_qm31(&mut self, elem: &QM31) {
-        let mut data = [0u8; 48];
-        data[..32].copy_from_slice(&self.state);
-        data[32..36].copy_from_slice(&elem.a.0.to_le_bytes());
-        data[36..40].copy_from_slice(&elem.b.0.to_le_bytes());
-        data[40..44].copy_from_slice(&elem.c.0.to_le_bytes());
-        data[44..48].copy_from_slice(&elem.d.0.to_le_bytes());
-        self.state = keccak_hash(&data);
+        self.state = self.hash.hash(&[&self.state, &elem.to_le_bytes()]);
         self.counter += 1;
     }
     
-    /// Squeeze an M31 element from the channel
-    pub fn squeeze_m31(&mut self) -> M31 {
-        let mut data = [0u8; 40];
-        data[..32].copy_from_slice(&self.state);
-        data[32..40].copy_from_slice(&self.counter.to_le_bytes());
-        let hash = keccak_hash(&data);
+    /// Squeeze the next 32-bit word: the low bytes of a fresh state
+    fn squeeze_word(&mut self) -> u32 {
+        let hash = self.hash.hash(&[&self.state, &self.counter.to_le_bytes()]);
         self.state = hash;
         self.counter += 1;
-        M31::new(u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]))
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+    
+    /// Squeeze an M31 element from the channel
+    pub fn squeeze_m31(&mut self) -> M31 {
+        M31::new(self.squeeze_word())
     }
     
     /// Squeeze a QM31 element from the channel
@@ -503,16 +1313,98 @@ impl Channel {
         QM31::new(a, b, c, d)
     }
     
-    /// Squeeze a random index in [0, bound)
+    /// Squeeze the out-of-domain point: a QM31 t mapped onto the circle by
+    /// [`SecureCirclePoint::from_t`], squeezed again in the negligible case
+    /// that it has no point off the base circle
+    pub fn squeeze_circle_point(&mut self) -> SecureCirclePoint {
+        loop {
+            if let Some(point) = SecureCirclePoint::from_t(self.squeeze_qm31()) {
+                return point;
+            }
+        }
+    }
+
+    /// Squeeze a uniform index in [0, 2^bits) (`bits` ≤ 32)
+    ///
+    /// The low bits of a whole hash word, never an M31: reducing into the
+    /// field first, or modulo the bound, would favour the low indices.
+    pub fn squeeze_bits(&mut self, bits: u32) -> u32 {
+        let word = self.squeeze_word();
+        if bits >= 32 { word } else { word & ((1 << bits) - 1) }
+    }
+    
+    /// Squeeze a uniform index in [0, bound), `bound` nonzero
+    ///
+    /// Masks to the next power of two and draws again above the bound; a
+    /// power-of-two bound always takes one draw.
     pub fn squeeze_index(&mut self, bound: usize) -> usize {
-        let elem = self.squeeze_m31();
-        (elem.0 as usize) % bound
+        let bits = bound.next_power_of_two().trailing_zeros();
+        loop {
+            let index = self.squeeze_bits(bits) as usize;
+            if index < bound {
+                return index;
+            }
+        }
     }
     
     /// Squeeze multiple random indices
     pub fn squeeze_indices(&mut self, count: usize, bound: usize) -> Vec<usize> {
         (0..count).map(|_| self.squeeze_index(bound)).collect()
     }
+
+    /// Serialize the transcript for the next stage of a staged verification:
+    /// `[phase][state (32)][counter (u64 LE)]`
+    pub fn checkpoint(&self, next: VerificationPhase) -> [u8; CHANNEL_CHECKPOINT_LEN] {
+        let mut out = [0u8; CHANNEL_CHECKPOINT_LEN];
+        out[0] = next as u8;
+        out[1..33].copy_from_slice(&self.state);
+        out[33..41].copy_from_slice(&self.counter.to_le_bytes());
+        out
+    }
+
+    /// Restore a checkpoint, failing unless it was taken for the `expected`
+    /// stage. The hash comes from the proof, which the run has pinned.
+    pub fn restore(
+        bytes: &[u8; CHANNEL_CHECKPOINT_LEN],
+        expected: VerificationPhase,
+        hash: HashKind,
+    ) -> Result<Self> {
+        require!(bytes[0] == expected as u8, VerifierError::VerificationPhaseMismatch);
+        let mut state = [0u8; 32];
+        state.copy_from_slice(&bytes[1..33]);
+        let mut counter = [0u8; 8];
+        counter.copy_from_slice(&bytes[33..41]);
+        Ok(Self { state, counter: u64::from_le_bytes(counter), hash })
+    }
+}
+
+/// Serialized size of a [`Channel`] checkpoint
+pub const CHANNEL_CHECKPOINT_LEN: usize = 1 + 32 + 8;
+
+/// Stage a staged verification runs next. Tags every channel checkpoint, so a
+/// stage can neither run twice nor out of order; zero (a fresh account) is
+/// not a phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum VerificationPhase {
+    /// Public inputs absorbed; commitments and the OODS constraint are next
+    Oods = 1,
+    /// FRI alphas drawn; query openings are checked in batches
+    Queries = 2,
+    /// Every query verified; the buffer can be finalized
+    Complete = 3,
+}
+
+impl VerificationPhase {
+    /// Phase a checkpoint was tagged with; `None` for zero or an unknown tag
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::Oods),
+            2 => Some(Self::Queries),
+            3 => Some(Self::Complete),
+            _ => None,
+        }
+    }
 }
 
 // ============================================================================
@@ -521,24 +1413,36 @@ impl Channel {
 
 /// Verify a Merkle authentication path
 /// Returns true if the path is valid from leaf to root
+///
+/// The tree has `2^depth` leaves, so the path must hold exactly `depth`
+/// siblings and `index` must be below `2^depth`. A longer path would climb
+/// past the tree the proof declares to a root the prover picked.
 pub fn verify_merkle_path(
+    hash: HashKind,
     path: &[[u8; 32]],
+    depth: u32,
     root: &[u8; 32],
     index: u32,
     leaf_value: &[u8; 32],
 ) -> bool {
-    verify_merkle_path_bytes(path, root, index, leaf_value)
+    verify_merkle_path_bytes(hash, path, depth, root, index, leaf_value)
 }
 
 /// Same as `verify_merkle_path`, but hashes an arbitrary leaf byte string.
 pub fn verify_merkle_path_bytes(
+    hash: HashKind,
     path: &[[u8; 32]],
+    depth: u32,
     root: &[u8; 32],
     index: u32,
     leaf_value: &[u8],
 ) -> bool {
+    if path.len() != depth as usize || index.checked_shr(depth).unwrap_or(0) != 0 {
+        return false;
+    }
+
     // Hash the leaf value first
-    let mut current = keccak_hash(leaf_value);
+    let mut current = hash.hash(&[leaf_value]);
     let mut idx = index;
     
     for sibling in path {
@@ -550,10 +1454,7 @@ pub fn verify_merkle_path_bytes(
         };
         
         // Hash parent = H(left || right)
-        let mut combined = [0u8; 64];
-        combined[..32].copy_from_slice(left);
-        combined[32..].copy_from_slice(right);
-        current = keccak_hash(&combined);
+        current = hash.hash_node(left, right);
         
         idx >>= 1;
     }
@@ -575,47 +1476,227 @@ fn hash_qm31_leaf(value: &QM31) -> [u8; 32] {
 // FRI Verification
 // ============================================================================
 
-/// Verify FRI folding: f_next(x^2) = (f(x) + f(-x))/2 + α * (f(x) - f(-x))/(2x)
-/// 
-/// For fold-by-4:
-/// f_folded = c0 + α*c1 + α²*c2 + α³*c3
-/// where c_i are the coefficients from the 4 siblings
-fn verify_fri_fold(
-    siblings: &[QM31; 4],
-    alpha: &QM31,
-    _domain_point: M31,  // Would use for full verification
-) -> QM31 {
-    // Simplified fold-by-4: linear combination with powers of alpha
-    // f_folded = s0 + α*s1 + α²*s2 + α³*s3
-    let mut result = siblings[0];
-    let mut alpha_power = *alpha;
-    
-    for sibling in siblings.iter().skip(1) {
-        result = result.add(alpha_power.mul(*sibling));
-        alpha_power = alpha_power.mul(*alpha);
-    }
-    
-    result
+/// The order-4 point (0, -1): adding it turns a point a quarter of the way
+/// round the circle, (x, y) -> (y, -x)
+pub const QUARTER_TURN: CirclePoint = CirclePoint { x: M31::ZERO, y: M31::new(P - 1) };
+
+/// Fold one FRI group onto the next layer
+///
+/// The group at positions 4j..4j+3 holds the layer's values at p, -p, r
+/// and -r, where p = `point` (see [`fri_layer_point`]) and r is p plus a
+/// quarter turn. Each antipodal pair doubles onto one point, so it folds
+/// as in univariate FRI over x, `f'(2p) = f(p) + f(-p) + α·(f(p) - f(-p)) / p.x`.
+/// Since 2r = -2p, so folding those two results again with α² lands on 4p, the
+/// next layer's position j. A layer of degree below 4d in x folds to one
+/// of degree below d; no point of the coset has x = 0.
+pub fn verify_fri_fold(siblings: &[QM31; 4], alpha: &QM31, point: CirclePoint) -> QM31 {
+    let turned = point.add(QUARTER_TURN);
+    let at_p = fold_antipodal(siblings[0], siblings[1], alpha, point.x);
+    let at_minus_p = fold_antipodal(siblings[2], siblings[3], alpha, turned.x);
+    fold_antipodal(at_p, at_minus_p, &alpha.square(), point.double().x)
+}
+
+/// f(q) and f(-q) folded onto 2q with twiddle 1 / q.x
+fn fold_antipodal(at_q: QM31, at_minus_q: QM31, alpha: &QM31, x: M31) -> QM31 {
+    let odd = at_q.sub(at_minus_q).mul(QM31::from_m31(x.inv()));
+    at_q.add(at_minus_q).add(alpha.mul(odd))
+}
+
+/// Point at `position` of FRI layer `layer`
+///
+/// Layer 0 is the commitment coset G + <g> (|g| = 2^log_domain_size) in
+/// bit-reversed order, so position i holds G + bitrev(i)·g and each
+/// fold-by-4 group 4j..4j+3 holds four points that differ by multiples of
+/// n/4·g. Doubling twice sends all of them to position j of the next coset,
+/// 4G + <4g>, so position j of layer k is position j·4^k of layer 0
+/// doubled 2k times.
+pub fn fri_layer_point(
+    position: usize,
+    layer: usize,
+    log_domain_size: u32,
+    log_folding_factor: u32,
+) -> CirclePoint {
+    let doublings = layer as u32 * log_folding_factor;
+    let reversed = ((position << doublings) as u32).reverse_bits() >> (32 - log_domain_size);
+    // G·(1 + bitrev(i)·2^(31 - log_domain_size)) stays below 2^31
+    let scalar = 1 + (reversed << (31 - log_domain_size));
+    CirclePoint::GENERATOR.mul(scalar).repeated_double(doublings)
+}
+
+/// x-coordinate of the final-layer domain point a query lands on
+///
+/// After `num_folds` folds the query at layer-0 `index` sits at
+/// 4^num_folds·(G + bitrev(index)·g), and the final polynomial is a
+/// polynomial in that point's x.
+fn fri_final_domain_x(
+    index: usize,
+    log_domain_size: u32,
+    num_folds: usize,
+    log_folding_factor: u32,
+) -> M31 {
+    let position = index >> (num_folds as u32 * log_folding_factor);
+    fri_layer_point(position, num_folds, log_domain_size, log_folding_factor).x
 }
 
 /// Verify the final polynomial is low-degree by evaluating it
-fn evaluate_final_poly(coeffs: &[QM31], point: &QM31) -> QM31 {
-    if coeffs.is_empty() {
+fn evaluate_final_poly(coeffs: impl DoubleEndedIterator<Item = QM31>, point: &QM31) -> QM31 {
+    // Horner's method: p(x) = c0 + x*(c1 + x*(c2 + ...))
+    let mut coeffs = coeffs.rev();
+    let Some(mut result) = coeffs.next() else {
         return QM31::ZERO;
+    };
+    for coeff in coeffs {
+        result = result.mul(*point).add(coeff);
     }
+    result
+}
+
+// ============================================================================
+// Buffer finalization
+// ============================================================================
+
+/// Verify the proof in `buf_data` against `inputs` and, if it holds, store
+/// the inputs and mark the buffer finalized at `slot`
+fn finalize_buffer(
+    buf_data: &mut [u8],
+    owner: &Pubkey,
+    inputs: &BatchEntry,
+    config: &VerifierConfig,
+    slot: u64,
+) -> Result<()> {
+    check_finalizable(buf_data, owner)?;
     
-    // Horner's method: p(x) = c0 + x*(c1 + x*(c2 + ...))
-    let mut result = coeffs[coeffs.len() - 1];
-    for coeff in coeffs.iter().rev().skip(1) {
-        result = result.mul(*point).add(*coeff);
+    // Full STARK verification - no shortcuts
+    // Includes recipient binding via Fiat–Shamir transcript.
+    let BatchEntry { commitment, nullifier, merkle_root, recipient } = *inputs;
+    verify_under(config, uploaded_proof(buf_data)?, &[commitment, nullifier, merkle_root, recipient])?;
+    
+    store_finalized(buf_data, inputs, slot);
+    Ok(())
+}
+
+/// [`finalize_buffer`] with the inputs taken from the buffer's public inputs
+/// block, which must hash to `public_inputs_hash`
+fn finalize_committed_buffer(
+    buf_data: &mut [u8],
+    owner: &Pubkey,
+    public_inputs_hash: &[u8; 32],
+    config: &VerifierConfig,
+    slot: u64,
+) -> Result<()> {
+    check_finalizable(buf_data, owner)?;
+    
+    let (inputs, proof_data) = split_public_inputs(uploaded_data(buf_data)?)?;
+    require!(!inputs.is_empty(), VerifierError::InvalidPublicInputs);
+    require!(self::public_inputs_hash(inputs) == *public_inputs_hash, VerifierError::PublicInputsMismatch);
+    verify_under(config, proof_data, inputs)?;
+    
+    let inputs = BatchEntry { commitment: inputs[0], nullifier: inputs[1], merkle_root: inputs[2], recipient: inputs[3] };
+    store_finalized(buf_data, &inputs, slot);
+    Ok(())
+}
+
+/// The buffer is `owner`'s and not finalized yet
+fn check_finalizable(buf_data: &[u8], owner: &Pubkey) -> Result<()> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    let buffer_owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
+        .map_err(|_| VerifierError::BufferCorrupt)?;
+    require!(buffer_owner == *owner, VerifierError::Unauthorized);
+    require!(buf_data[OFFSET_FINALIZED] == 0, VerifierError::BufferAlreadyFinalized);
+    Ok(())
+}
+
+/// Record verified public inputs and mark the buffer finalized at `slot`
+fn store_finalized(buf_data: &mut [u8], inputs: &BatchEntry, slot: u64) {
+    let BatchEntry { commitment, nullifier, merkle_root, recipient } = inputs;
+    buf_data[OFFSET_COMMITMENT..OFFSET_COMMITMENT + 32].copy_from_slice(commitment);
+    buf_data[OFFSET_NULLIFIER..OFFSET_NULLIFIER + 32].copy_from_slice(nullifier);
+    buf_data[OFFSET_MERKLE_ROOT..OFFSET_MERKLE_ROOT + 32].copy_from_slice(merkle_root);
+    buf_data[OFFSET_RECIPIENT..OFFSET_RECIPIENT + 32].copy_from_slice(recipient);
+    buf_data[OFFSET_FINALIZED_SLOT..OFFSET_FINALIZED_SLOT + 8].copy_from_slice(&slot.to_le_bytes());
+    buf_data[OFFSET_FINALIZED] = 1;
+}
+
+/// Zero `buffer` and move its lamports to `owner`. Zeroing first keeps a
+/// stale `finalized = 1` from being read should the account be revived
+/// within the transaction.
+fn close_buffer(buffer: &AccountInfo, owner: &AccountInfo) -> Result<()> {
+    buffer.try_borrow_mut_data()?.fill(0);
+    
+    credit(owner, buffer.lamports())?;
+    **buffer.lamports.borrow_mut() = 0;
+    Ok(())
+}
+
+/// [`close_buffer`] for anyone once the buffer has expired at `slot`,
+/// splitting its lamports between `reaper` and `owner`. Returns the reward.
+fn reap_buffer(buffer: &AccountInfo, owner: &AccountInfo, reaper: &AccountInfo, slot: u64) -> Result<u64> {
+    {
+        let buf_data = buffer.try_borrow_data()?;
+        require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+        let buffer_owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
+            .map_err(|_| VerifierError::BufferCorrupt)?;
+        require!(buffer_owner == owner.key(), VerifierError::Unauthorized);
+        require!(slot >= buffer_expires_at(&buf_data)?, VerifierError::BufferNotExpired);
     }
-    result
+    
+    let lamports = buffer.lamports();
+    let reward = reap_reward(lamports);
+    buffer.try_borrow_mut_data()?.fill(0);
+    credit(reaper, reward)?;
+    credit(owner, lamports - reward)?;
+    **buffer.lamports.borrow_mut() = 0;
+    Ok(reward)
+}
+
+/// What `reap_expired_buffer` pays for closing a buffer holding `lamports`
+pub fn reap_reward(lamports: u64) -> u64 {
+    (lamports as u128 * REAP_REWARD_BPS as u128 / 10_000) as u64
+}
+
+fn credit(account: &AccountInfo, lamports: u64) -> Result<()> {
+    let balance = account.lamports();
+    **account.lamports.borrow_mut() = balance.checked_add(lamports).ok_or(VerifierError::LamportOverflow)?;
+    Ok(())
+}
+
+/// One buffer of a batch. Raw remaining accounts get the checks Anchor
+/// would otherwise make: the program must own the buffer to write it.
+fn finalize_batch_entry(
+    buffer: &AccountInfo,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    inputs: &BatchEntry,
+    config: &VerifierConfig,
+    slot: u64,
+) -> Result<()> {
+    require!(buffer.owner == program_id && buffer.is_writable, VerifierError::BufferCorrupt);
+    let mut buf_data = buffer.try_borrow_mut_data()?;
+    finalize_buffer(&mut buf_data, owner, inputs, config, slot)
+}
+
+/// Numeric code of `err`, as a failed transaction would report it
+fn error_code(err: &Error) -> u32 {
+    match err {
+        Error::AnchorError(e) => e.error_code_number,
+        Error::ProgramError(e) => u64::from(e.program_error.clone()) as u32,
+    }
+}
+
+/// Close `report` on `err`, log it and emit [`VerificationFailed`]
+fn reject(report: &mut VerificationReport, err: Error) -> Error {
+    report.error_code = error_code(&err);
+    msg!("Proof rejected: {}", report);
+    emit!(VerificationFailed { report: *report });
+    err
 }
 
 // ============================================================================
 // FULL STARK VERIFICATION
 // ============================================================================
 
+/// Verify a proof under [`VerifierConfig::MURKL`], the weakest profile. The
+/// instructions check under their VerifierConfigAccount's profile instead.
 pub fn verify_stark_proof(
     proof_data: &[u8],
     commitment: &[u8; 32],
@@ -623,18 +1704,211 @@ pub fn verify_stark_proof(
     merkle_root: &[u8; 32],
     recipient: &[u8; 32],
 ) -> Result<()> {
+    verify_under(&VerifierConfig::MURKL, proof_data, &[*commitment, *nullifier, *merkle_root, *recipient])
+}
+
+/// [`verify_stark_proof`] for a statement with more than the four core
+/// public inputs: `inputs` starts with commitment, nullifier, Merkle root and
+/// recipient, and the rest are absorbed after them in order
+pub fn verify_stark_proof_with_inputs(proof_data: &[u8], inputs: &[[u8; 32]]) -> Result<()> {
+    verify_under(&VerifierConfig::MURKL, proof_data, inputs)
+}
+
+/// [`verify_stark_proof_with_inputs`] under `config`
+fn verify_under(config: &VerifierConfig, proof_data: &[u8], inputs: &[[u8; 32]]) -> Result<()> {
+    let [commitment, nullifier, merkle_root, recipient, extra @ ..] = inputs else {
+        return err!(VerifierError::InvalidPublicInputs);
+    };
+    let mut report = VerificationReport::default();
+    check_stark_proof(config, proof_data, commitment, nullifier, merkle_root, recipient, extra, &mut report)
+        .map_err(|err| reject(&mut report, err))
+}
+
+/// Digest `finalize_and_verify_committed` takes in place of the public inputs
+pub fn public_inputs_hash(inputs: &[[u8; 32]]) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(inputs.len() + 1);
+    parts.push(PUBLIC_INPUTS_DOMAIN);
+    parts.extend(inputs.iter().map(|input| &input[..]));
+    keccak::hashv(&parts).0
+}
+
+/// Public inputs block to upload ahead of the proof
+pub fn public_inputs_block(inputs: &[[u8; 32]]) -> Vec<u8> {
+    let mut block = Vec::with_capacity(5 + 32 * inputs.len());
+    block.extend_from_slice(&PUBLIC_INPUTS_MAGIC);
+    block.push(inputs.len() as u8);
+    block.extend(inputs.iter().flatten());
+    block
+}
+
+/// Split a buffer's data region into its public inputs block, empty when
+/// there is none, and the proof
+fn split_public_inputs(data: &[u8]) -> Result<(&[[u8; 32]], &[u8])> {
+    let Some(rest) = data.strip_prefix(&PUBLIC_INPUTS_MAGIC[..]) else {
+        return Ok((&[], data));
+    };
+    let (&count, rest) = rest.split_first().ok_or(VerifierError::InvalidPublicInputs)?;
+    let count = count as usize;
+    require!((4..=MAX_PUBLIC_INPUTS).contains(&count), VerifierError::InvalidPublicInputs);
+    require!(rest.len() >= 32 * count, VerifierError::InvalidPublicInputs);
+    let (inputs, proof_data) = rest.split_at(32 * count);
+    Ok((bytemuck::cast_slice(inputs), proof_data))
+}
+
+/// [`verify_stark_proof`] without the failure log; `report` is left at the
+/// check that failed
+#[allow(clippy::too_many_arguments)]
+fn check_stark_proof(
+    config: &VerifierConfig,
+    proof_data: &[u8],
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    merkle_root: &[u8; 32],
+    recipient: &[u8; 32],
+    extra: &[[u8; 32]],
+    report: &mut VerificationReport,
+) -> Result<()> {
+    let mut meter = CuMeter::start();
+
     // 1. Parse proof
+    report.stage = VerificationStage::Parse;
     let proof = parse_proof(proof_data)?;
+    report.stage = VerificationStage::Shape;
+    check_proof_shape(&proof, config)?;
+    meter.event.parse += meter.lap();
+    
+    // 2. Initialize Fiat-Shamir channel
+    let mut channel = Channel::new(proof.header.hash_kind);
+    absorb_public_inputs(&mut channel, commitment, nullifier, merkle_root, recipient, extra);
+    meter.event.channel += meter.lap();
+    report.stage = VerificationStage::Constraint;
+    let fri_alphas = verify_oods(&proof, &mut channel, commitment, nullifier, merkle_root, config, &mut meter)?;
+    
+    // 9-10. Verify each query; indices come from Fiat-Shamir (deterministic!)
+    // and are squeezed in query order, nothing else touches the channel.
+    for (q_idx, query) in proof.queries().enumerate() {
+        report.stage = VerificationStage::Parse;
+        report.query_index = Some(q_idx as u8);
+        let query = query?;
+        meter.event.parse += meter.lap();
+        verify_query(&proof, &query, &fri_alphas, &mut channel, config, report, &mut meter)?;
+        msg!("Query {} verified", q_idx);
+    }
+    
+    msg!("All {} queries verified. Proof valid.", proof.header.num_queries);
+    meter.finish();
+    
+    Ok(())
+}
+
+/// Compute units left in this transaction. `None` off-chain, where the
+/// syscall stub reports 0; on-chain a program at 0 has already aborted.
+fn remaining_compute_units() -> Option<u64> {
+    let remaining = anchor_lang::solana_program::compute_units::sol_remaining_compute_units();
+    (remaining > 0).then_some(remaining)
+}
+
+/// Decides when a staged batch stops taking queries, from the compute
+/// each query actually used in this transaction
+struct QueryBudget {
+    last_remaining: Option<u64>,
+    max_query_cu: u64,
+}
+
+impl QueryBudget {
+    fn new(remaining: Option<u64>) -> Self {
+        Self { last_remaining: remaining, max_query_cu: 0 }
+    }
+
+    /// Record that a query finished with `remaining` units left; whether
+    /// one more as costly as the worst so far still fits, reserve included
+    fn next_fits(&mut self, remaining: Option<u64>) -> bool {
+        let (Some(before), Some(after)) = (self.last_remaining, remaining) else {
+            return true;
+        };
+        self.max_query_cu = self.max_query_cu.max(before.saturating_sub(after));
+        self.last_remaining = remaining;
+        after >= self.max_query_cu.saturating_add(STAGED_QUERY_CU_RESERVE)
+    }
+}
+
+/// Splits the compute a verification uses across its stages, for
+/// [`CuBreakdownEvent`]. Inert unless [`MEASURE_CU`] is set.
+#[derive(Default)]
+struct CuMeter {
+    last: Option<u64>,
+    event: CuBreakdownEvent,
+}
+
+impl CuMeter {
+    fn start() -> Self {
+        Self::at(Self::read())
+    }
+
+    fn at(remaining: Option<u64>) -> Self {
+        Self { last: remaining, event: CuBreakdownEvent::default() }
+    }
+
+    fn read() -> Option<u64> {
+        if MEASURE_CU {
+            remaining_compute_units()
+        } else {
+            None
+        }
+    }
+
+    /// Units used since the previous lap
+    fn lap(&mut self) -> u64 {
+        if self.last.is_none() {
+            return 0;
+        }
+        self.lap_at(Self::read())
+    }
+
+    /// [`Self::lap`] with `remaining` units left now
+    fn lap_at(&mut self, remaining: Option<u64>) -> u64 {
+        let used = match (self.last, remaining) {
+            (Some(before), Some(after)) => before.saturating_sub(after),
+            _ => 0,
+        };
+        self.last = remaining;
+        used
+    }
+
+    /// Emit the breakdown, if anything was measured
+    fn finish(&self) {
+        if self.last.is_some() {
+            emit!(CuBreakdownEvent { ..self.event });
+        }
+    }
+}
+
+/// Header checks that need no transcript work
+fn check_proof_shape(proof: &StarkProof, config: &VerifierConfig) -> Result<()> {
+    config.check_header(&proof.header)?;
+    check_fri_shape(proof, config)?;
+    require!(
+        proof.num_trace_columns == config.trace_columns,
+        VerifierError::TraceColumnCountMismatch
+    );
     
     msg!("Parsed: {} FRI layers, {} queries, final poly deg {}",
          proof.fri_layer_commitments.len(),
-         proof.queries.len(),
+         proof.header.num_queries,
          proof.fri_final_poly.len());
-    
-    // 2. Initialize Fiat-Shamir channel
-    let mut channel = Channel::new();
-    
-    // 3. Mix public inputs (binds proof to claimed statement)
+    Ok(())
+}
+
+/// Step 3: mix the public inputs, binding the proof to the claimed statement;
+/// `extra` holds any inputs past the core four, mixed after them
+fn absorb_public_inputs(
+    channel: &mut Channel,
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    merkle_root: &[u8; 32],
+    recipient: &[u8; 32],
+    extra: &[[u8; 32]],
+) {
     if DEBUG_FS_LOGS {
         msg!("FS mix commitment: {:02x}{:02x}{:02x}{:02x}...", commitment[0], commitment[1], commitment[2], commitment[3]);
         msg!("FS mix nullifier: {:02x}{:02x}{:02x}{:02x}...", nullifier[0], nullifier[1], nullifier[2], nullifier[3]);
@@ -647,37 +1921,56 @@ pub fn verify_stark_proof(
     channel.mix_digest(merkle_root);
     // Bind recipient ATA to the proof so relayer/mitm cannot substitute recipients.
     channel.mix_digest(recipient);
-    
+    for input in extra {
+        channel.mix_digest(input);
+    }
+}
+
+/// Steps 4-8: commitments, the OODS constraint check and the FRI folding
+/// alphas, which are returned.
+fn verify_oods(
+    proof: &StarkProof,
+    channel: &mut Channel,
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    merkle_root: &[u8; 32],
+    config: &VerifierConfig,
+    meter: &mut CuMeter,
+) -> Result<Bounded<QM31, MAX_FRI_LAYERS>> {
     // 4. Verify trace commitment phase
     if DEBUG_FS_LOGS {
         msg!("FS mix trace_commitment: {:02x}{:02x}{:02x}{:02x}...", proof.trace_commitment[0], proof.trace_commitment[1], proof.trace_commitment[2], proof.trace_commitment[3]);
     }
-    channel.mix_digest(&proof.trace_commitment);
+    channel.mix_digest(proof.trace_commitment);
     
     // Get random coefficient for constraint composition
     let alpha = channel.squeeze_qm31();
     msg!("Constraint alpha: ({}, {}, {}, {})", alpha.a.0, alpha.b.0, alpha.c.0, alpha.d.0);
     
     // 5. Verify composition commitment
-    channel.mix_digest(&proof.composition_commitment);
+    channel.mix_digest(proof.composition_commitment);
     
     // Get OODS point from channel
-    let oods_point = channel.squeeze_qm31();
+    let oods_point = channel.squeeze_circle_point();
     
     // 6. Mix OODS values into channel
-    channel.mix_qm31(&proof.trace_oods);
+    for value in proof.trace_oods.iter().chain(proof.trace_oods_next.iter()) {
+        channel.mix_qm31(&value);
+    }
     channel.mix_qm31(&proof.composition_oods);
+    meter.event.channel += meter.lap();
     
     // 7. Verify constraint equation at OODS point
-    // The composition polynomial should equal the AIR constraint evaluated at OODS
-    let expected_composition = evaluate_murkl_constraint(
-        &proof.trace_oods,
-        commitment,
-        nullifier,
-        merkle_root,
-        &alpha,
-        &oods_point,
-    );
+    // The composition polynomial should equal the hash AIR's constraints,
+    // the commitment's path up to the pool root included, folded at OODS
+    let statement =
+        HashStatement::new(commitment, nullifier, merkle_root).ok_or(VerifierError::NonCanonicalPublicInput)?;
+    let current: Vec<QM31> = proof.trace_oods.iter().collect();
+    let next: Vec<QM31> = proof.trace_oods_next.iter().collect();
+    let constraints = statement
+        .evaluate(&current, &next, &oods_point, config.log_trace_size)
+        .ok_or(VerifierError::ConstraintMismatch)?;
+    let expected_composition = constraint_composition(&constraints, &alpha, &oods_point, config.log_trace_size);
     
     // Constraint verification (always on — no demo mode)
     require!(
@@ -685,213 +1978,448 @@ pub fn verify_stark_proof(
         VerifierError::ConstraintMismatch
     );
     msg!("Constraint verification passed");
+    meter.event.oods += meter.lap();
     
     // 8. Get FRI folding alphas
-    let mut fri_alphas = Vec::with_capacity(proof.fri_layer_commitments.len());
-    for layer_commitment in &proof.fri_layer_commitments {
+    let mut fri_alphas = Bounded::<QM31, MAX_FRI_LAYERS>::new();
+    for layer_commitment in proof.fri_layer_commitments.iter() {
         channel.mix_digest(layer_commitment);
-        fri_alphas.push(channel.squeeze_qm31());
+        fri_alphas.push(channel.squeeze_qm31())?;
     }
+    meter.event.channel += meter.lap();
     
-    // 9. Get query indices from Fiat-Shamir (deterministic!)
-    let log_domain_size = 10 + LOG_BLOWUP; // Typical trace size
-    let domain_size = 1usize << log_domain_size;
-    let expected_query_indices = channel.squeeze_indices(proof.queries.len(), domain_size);
-    
-    // 10. Verify each query
-    for (q_idx, query) in proof.queries.iter().enumerate() {
-        let expected_index = expected_query_indices[q_idx];
-        
-        // Query index must match Fiat-Shamir derivation
+    Ok(fri_alphas)
+}
+
+/// Steps 9-10 for one opening: its index is the next one squeezed from the
+/// channel, then the trace, composition and FRI paths are checked.
+fn verify_query(
+    proof: &StarkProof,
+    query: &QueryProof,
+    fri_alphas: &[QM31],
+    channel: &mut Channel,
+    config: &VerifierConfig,
+    report: &mut VerificationReport,
+    meter: &mut CuMeter,
+) -> Result<()> {
+    // check_proof_shape held the header's domain to the config's
+    let expected_index = channel.squeeze_bits(proof.header.log_domain_size()) as usize;
+    
+    // Query index must match Fiat-Shamir derivation
+    report.stage = VerificationStage::QueryIndex;
+    require!(
+        query.index as usize == expected_index,
+        VerifierError::QueryIndexMismatch
+    );
+    
+    // Verify trace Merkle path; all columns of the row share one leaf
+    report.stage = VerificationStage::TraceMerkle;
+    let trace_leaf = proof.header.hash_kind.hash(&[TRACE_ROW_DOMAIN, query.trace_row]);
+    require!(
+        verify_merkle_path(
+            proof.header.hash_kind,
+            query.trace_path,
+            proof.header.log_domain_size(),
+            proof.trace_commitment,
+            query.index,
+            &trace_leaf,
+        ),
+        VerifierError::TraceMerklePathFailed
+    );
+    
+    // Verify composition Merkle path
+    report.stage = VerificationStage::CompositionMerkle;
+    require!(
+        verify_merkle_path(
+            proof.header.hash_kind,
+            query.composition_path,
+            proof.header.log_domain_size(),
+            proof.composition_commitment,
+            query.index,
+            query.composition_value,
+        ),
+        VerifierError::CompositionMerklePathFailed
+    );
+    meter.event.merkle += meter.lap();
+    
+    // Verify FRI folding at each layer
+    report.stage = VerificationStage::FriFolding;
+    let mut current_index = query.index as usize;
+    let mut current_value = decode_qm31(&bytemuck::cast::<_, [[u8; 16]; 2]>(*query.composition_value)[0]);
+
+    for (layer_idx, (layer_query, layer_alpha)) in query
+        .fri_layer_values
+        .iter()
+        .zip(fri_alphas.iter())
+        .enumerate()
+    {
+        report.layer_index = Some(layer_idx as u8);
+        let siblings = layer_query.decode_siblings();
+        // 1) Verify Merkle commitment for this layer's queried group. The
+        //    tree has a leaf per group of four, hashing all four siblings,
+        //    at tree_pos = current_index / 4; that fixes the path's depth.
         require!(
-            query.index as usize == expected_index,
-            VerifierError::QueryIndexMismatch
+            layer_idx < proof.fri_layer_commitments.len(),
+            VerifierError::InvalidProofFormat
         );
-        
-        // Verify trace Merkle path
+        let depth = proof
+            .header
+            .fri_layer_depth(layer_idx)
+            .ok_or(VerifierError::InvalidProofFormat)?;
+        let tree_pos = current_index / 4;
+        let group_leaf = proof
+            .header
+            .hash_kind
+            .hash(&[FRI_GROUP_DOMAIN, bytemuck::cast_slice(layer_query.siblings.0)]);
+
         require!(
             verify_merkle_path(
-                &query.trace_path,
-                &proof.trace_commitment,
-                query.index,
-                &query.trace_value,
+                proof.header.hash_kind,
+                layer_query.path,
+                depth,
+                &proof.fri_layer_commitments[layer_idx],
+                tree_pos as u32,
+                &group_leaf,
             ),
-            VerifierError::TraceMerklePathFailed
+            VerifierError::FriFoldingFailed
         );
-        
-        // Verify composition Merkle path
+
+        // Layer 0 is the composition: the query's own slot in the group must
+        // hold the composition value opened above
+        if layer_idx == 0 {
+            require!(
+                siblings[current_index % 4].eq(&current_value),
+                VerifierError::FriFoldingFailed
+            );
+        }
+
+        // 2) Verify folding consistency with the next layer (except last layer).
+        // Fold-by-4 reduces index by /4.
+        let group_point = fri_layer_point(
+            current_index & !3,
+            layer_idx,
+            config.log_domain_size(),
+            config.log_folding_factor,
+        );
+        let folded = verify_fri_fold(&siblings, layer_alpha, group_point);
+
+        // Move to next layer index.
+        let next_index = current_index / 4;
+
+        if layer_idx + 1 < query.fri_layer_values.len() {
+            let next_layer = &query.fri_layer_values[layer_idx + 1];
+            let expected_next = next_layer
+                .siblings
+                .get(next_index % 4)
+                .ok_or(VerifierError::InvalidProofFormat)?;
+            require!(folded.eq(&expected_next), VerifierError::FriFoldingFailed);
+            current_value = expected_next;
+        } else {
+            // last layer: carry folded into final polynomial check
+            current_value = folded;
+        }
+
+        current_index = next_index;
+    }
+    
+    // Final layer should match the sent value, or polynomial evaluation
+    report.stage = VerificationStage::FinalLayer;
+    report.layer_index = None;
+    if !proof.fri_final_evaluations.is_empty() {
+        let sent = proof
+            .fri_final_evaluations
+            .get(current_index)
+            .ok_or(VerifierError::InvalidProofFormat)?;
         require!(
-            verify_merkle_path(
-                &query.composition_path,
-                &proof.composition_commitment,
-                query.index,
-                &query.composition_value,
-            ),
-            VerifierError::CompositionMerklePathFailed
+            current_value.eq(&sent),
+            VerifierError::FinalPolyMismatch
         );
-        
-        // Verify FRI folding at each layer
-        let mut current_index = query.index as usize;
-        let mut current_value = parse_qm31(&query.composition_value[..16])?;
-
-        // Helper: serialize a single QM31 as a 32-byte padded leaf.
-        // Matches prover's FriMerkleTree leaf format (16 bytes QM31 + 16 zeros).
-        let serialize_qm31_leaf = |v: &QM31| -> [u8; 32] {
-            let mut out = [0u8; 32];
-            out[0..4].copy_from_slice(&v.a.0.to_le_bytes());
-            out[4..8].copy_from_slice(&v.b.0.to_le_bytes());
-            out[8..12].copy_from_slice(&v.c.0.to_le_bytes());
-            out[12..16].copy_from_slice(&v.d.0.to_le_bytes());
-            // bytes 16..32 stay zero (padding)
-            out
-        };
+    } else if !proof.fri_final_poly.is_empty() {
+        let final_x = fri_final_domain_x(
+            query.index as usize,
+            config.log_domain_size(),
+            query.fri_layer_values.len(),
+            config.log_folding_factor,
+        );
+        let final_eval = evaluate_final_poly(proof.fri_final_poly.iter(), &QM31::from_m31(final_x));
 
-        for (layer_idx, (layer_query, layer_alpha)) in query
-            .fri_layer_values
-            .iter()
-            .zip(fri_alphas.iter())
-            .enumerate()
-        {
-            // 1) Verify Merkle commitment for this layer's queried position.
-            //    The prover's FriMerkleTree stores one QM31 per leaf (padded to 32 bytes).
-            //    The proof provides one path for tree_pos = current_index / 4.
-            //    We verify the leaf at that position matches the sibling
-            //    at offset (tree_pos % 4) of the 4-sibling batch.
-            if !layer_query.path.is_empty() {
-                require!(
-                    layer_idx < proof.fri_layer_commitments.len(),
-                    VerifierError::InvalidProofFormat
-                );
-                let tree_pos = current_index / 4;
-                // The path proves the leaf at tree_pos in the FRI Merkle tree.
-                // The prover stores one QM31 per leaf; the "4 siblings" in the proof
-                // are the group [4*group .. 4*group+3]. But the path proves tree_pos
-                // (which maps to the *group* index, i.e. tree_pos itself).
-                // The leaf the prover hashed is the QM31 at tree_pos.
-                // Since all FRI values are zero in the current proof strategy, any
-                // sibling works. But for correctness, we use siblings[0] (since
-                // tree_pos = group_index, and the path is for that exact leaf).
-                let leaf_bytes = serialize_qm31_leaf(&layer_query.siblings[0]);
-
-                require!(
-                    verify_merkle_path(
-                        &layer_query.path,
-                        &proof.fri_layer_commitments[layer_idx],
-                        tree_pos as u32,
-                        &leaf_bytes,
-                    ),
-                    VerifierError::FriFoldingFailed
-                );
-            }
+        require!(
+            current_value.eq(&final_eval),
+            VerifierError::FinalPolyMismatch
+        );
+    }
+    meter.event.fri += meter.lap();
+    meter.event.queries += 1;
+    Ok(())
+}
 
-            // 2) Verify folding consistency with the next layer (except last layer).
-            // Fold-by-4 reduces index by /4.
-            let domain_point = M31::new((current_index as u32) % P);
-            let folded = verify_fri_fold(&layer_query.siblings, layer_alpha, domain_point);
-
-            // Move to next layer index.
-            let next_index = current_index / 4;
-
-            if layer_idx + 1 < query.fri_layer_values.len() {
-                let next_layer = &query.fri_layer_values[layer_idx + 1];
-                let expected_next = next_layer.siblings[next_index % 4];
-                require!(folded.eq(&expected_next), VerifierError::FriFoldingFailed);
-                current_value = expected_next;
-            } else {
-                // last layer: carry folded into final polynomial check
-                current_value = folded;
+/// Reject proofs whose FRI shape cannot reach the final polynomial's degree.
+///
+/// Without this a prover could declare zero (or too few) folding layers and
+/// have queries checked directly against an arbitrary final polynomial.
+fn check_fri_shape(proof: &StarkProof, config: &VerifierConfig) -> Result<()> {
+    require!(
+        proof.fri_layer_commitments.len() >= config.required_fri_layers(),
+        VerifierError::FriLayerCountTooLow
+    );
+    require!(
+        proof.fri_final_poly.len() <= config.max_final_poly_len(),
+        VerifierError::FinalPolyDegreeTooHigh
+    );
+    if !proof.fri_final_evaluations.is_empty() {
+        // One value per point of the last layer's domain
+        let folded_bits = proof.fri_layer_commitments.len() as u32 * config.log_folding_factor;
+        let log_final = config
+            .log_domain_size()
+            .checked_sub(folded_bits)
+            .ok_or(VerifierError::InvalidProofFormat)?;
+        require!(
+            proof.fri_final_evaluations.len() == 1 << log_final,
+            VerifierError::InvalidProofFormat
+        );
+        check_final_evaluations(proof, config)?;
+    }
+    Ok(())
+}
+
+/// Require sent last-layer values to lie on a polynomial within the degree
+/// bound.
+///
+/// Lagrange-interpolates through the first `max_final_poly_len` points and
+/// checks every remaining point against the interpolant, O(n·d) field ops.
+fn check_final_evaluations(proof: &StarkProof, config: &VerifierConfig) -> Result<()> {
+    let count = proof.fri_final_evaluations.len();
+    let num_folds = proof.fri_layer_commitments.len();
+    let shift = num_folds as u32 * config.log_folding_factor;
+    let x_at = |j: usize| {
+        fri_final_domain_x(j << shift, config.log_domain_size(), num_folds, config.log_folding_factor)
+    };
+    let value_at = |j: usize| proof.fri_final_evaluations.get(j).ok_or(VerifierError::InvalidProofFormat);
+
+    let bound = count.min(config.max_final_poly_len());
+    let mut xs = Bounded::<M31, MAX_FINAL_POLY_LEN>::new();
+    for j in 0..bound {
+        xs.push(x_at(j))?;
+    }
+    // y_i / prod_{j != i} (x_i - x_j), so each check is a sum of scaled products
+    let mut weighted = Bounded::<QM31, MAX_FINAL_POLY_LEN>::new();
+    for (i, xi) in xs.iter().enumerate() {
+        let mut denom = M31::ONE;
+        for (j, xj) in xs.iter().enumerate() {
+            if i != j {
+                denom = denom.mul(xi.sub(*xj));
             }
+        }
+        weighted.push(value_at(i)?.mul(QM31::from_m31(denom.inv())))?;
+    }
 
-            current_index = next_index;
+    let mut suffix = [M31::ONE; MAX_FINAL_POLY_LEN + 1];
+    for k in bound..count {
+        let x = x_at(k);
+        for i in (0..bound).rev() {
+            suffix[i] = suffix[i + 1].mul(x.sub(xs[i]));
         }
-        
-        // Final layer should match polynomial evaluation
-        if !proof.fri_final_poly.is_empty() {
-            let final_point = QM31::new(
-                M31::new(current_index as u32),
-                M31::ZERO,
-                M31::ZERO,
-                M31::ZERO,
-            );
-            let final_eval = evaluate_final_poly(&proof.fri_final_poly, &final_point);
-            
-            require!(
-                current_value.eq(&final_eval),
-                VerifierError::FinalPolyMismatch
-            );
+        let mut prefix = M31::ONE;
+        let mut expected = QM31::ZERO;
+        for i in 0..bound {
+            expected = expected.add(weighted[i].mul(QM31::from_m31(prefix.mul(suffix[i + 1]))));
+            prefix = prefix.mul(x.sub(xs[i]));
         }
-        
-        msg!("Query {} verified", q_idx);
+        require!(expected.eq(&value_at(k)?), VerifierError::FinalPolyDegreeTooHigh);
     }
-    
-    msg!("All {} queries verified. Proof valid.", proof.queries.len());
-    
     Ok(())
 }
 
-/// Evaluate the Murkl constraint polynomial at OODS point
-/// 
-/// The Murkl circuit enforces:
-/// 1. commitment = keccak(identifier || secret)
-/// 2. nullifier = keccak(secret || leaf_index)  
-/// 3. merkle_root contains commitment at leaf_index
-/// 
-/// The constraint polynomial combines these with random alpha for soundness.
-fn evaluate_murkl_constraint(
-    trace_oods: &QM31,
-    commitment: &[u8; 32],
-    nullifier: &[u8; 32],
-    merkle_root: &[u8; 32],
+/// Vanishing polynomial of the canonic trace coset at a circle point
+///
+/// Doubling x (x ↦ 2x² - 1) log_trace_size - 1 times sends every point of
+/// the canonic coset of that size to 0.
+fn trace_vanishing(point: &SecureCirclePoint, log_trace_size: u32) -> QM31 {
+    (1..log_trace_size).fold(point.x, |x, _| {
+        let sq = x.square();
+        sq.add(sq).sub(QM31::ONE)
+    })
+}
+
+/// Composition value constraints commit to at a point
+///
+/// Folds the constraints with powers of `alpha` and divides by the trace
+/// vanishing polynomial. Commitments live on a shifted coset, so the
+/// quotient is what the composition commits to; the OODS point is off the
+/// base circle, where the vanishing polynomial is never zero.
+pub fn constraint_composition(
+    constraints: &[QM31],
     alpha: &QM31,
-    oods_point: &QM31,
+    point: &SecureCirclePoint,
+    log_trace_size: u32,
 ) -> QM31 {
-    // Map public inputs to field elements via keccak
-    let c = bytes_to_qm31(commitment);
-    let n = bytes_to_qm31(nullifier);
-    let r = bytes_to_qm31(merkle_root);
-    
-    // The constraint is:
-    // C(x) = (trace(x) - c) + α*(trace(x) - n) + α²*(trace(x) - r)
-    // At OODS: composition(oods) should equal C(oods)
-    
-    // trace(oods) - commitment
-    let c1 = trace_oods.sub(c);
-    
-    // α * (trace(oods) - nullifier)
-    let c2 = alpha.mul(trace_oods.sub(n));
-    
-    // α² * (trace(oods) - merkle_root)
-    let alpha_sq = alpha.mul(*alpha);
-    let c3 = alpha_sq.mul(trace_oods.sub(r));
-    
-    // Combine and scale by OODS point for degree adjustment
-    let constraint_sum = c1.add(c2).add(c3);
-    
-    // Divide by vanishing polynomial evaluation at OODS
-    // V(x) = x^n - 1, for domain of size n
-    // At OODS point, this gives the constraint quotient
-    let oods_pow = oods_point.pow(1024); // Domain size 2^10
-    let vanishing_at_oods = oods_pow.sub(QM31::ONE);
-    
-    // Constraint quotient = constraint_sum / vanishing(oods)
-    // For soundness, we verify the composition matches this quotient
-    if vanishing_at_oods.eq(&QM31::ZERO) {
-        // OODS point is in the domain (shouldn't happen with proper randomness)
-        constraint_sum
-    } else {
-        constraint_sum.mul(vanishing_at_oods.inv())
+    let mut power = QM31::ONE;
+    let mut sum = QM31::ZERO;
+    for constraint in constraints {
+        sum = sum.add(power.mul(*constraint));
+        power = power.mul(*alpha);
     }
+    sum.mul(trace_vanishing(point, log_trace_size).inv())
 }
 
-/// Convert 32 bytes to QM31 via keccak reduction
-fn bytes_to_qm31(bytes: &[u8; 32]) -> QM31 {
-    let hash = keccak_hash(bytes);
-    QM31::new(
-        M31::new(u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])),
-        M31::new(u32::from_le_bytes([hash[4], hash[5], hash[6], hash[7]])),
-        M31::new(u32::from_le_bytes([hash[8], hash[9], hash[10], hash[11]])),
-        M31::new(u32::from_le_bytes([hash[12], hash[13], hash[14], hash[15]])),
+/// Owner, or the delegate set by `delegate_uploader`, may write chunks
+fn check_uploader(buf_data: &[u8], signer: &Pubkey) -> Result<()> {
+    let owner = Pubkey::try_from(&buf_data[OFFSET_OWNER..OFFSET_OWNER + 32])
+        .map_err(|_| VerifierError::BufferCorrupt)?;
+    let delegate = Pubkey::try_from(&buf_data[OFFSET_DELEGATE..OFFSET_DELEGATE + 32])
+        .map_err(|_| VerifierError::BufferCorrupt)?;
+    require!(
+        *signer == owner || (delegate != Pubkey::default() && *signer == delegate),
+        VerifierError::Unauthorized
+    );
+    Ok(())
+}
+
+/// The buffer takes uploads from `signer`: not finalized, and `signer` is
+/// its owner or upload delegate
+fn check_uploadable(buf_data: &[u8], signer: &Pubkey) -> Result<()> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    check_uploader(buf_data, signer)?;
+    require!(buf_data[OFFSET_FINALIZED] == 0, VerifierError::BufferAlreadyFinalized);
+    Ok(())
+}
+
+/// Write `chunk` at `offset` into the data region, fold it into the upload
+/// digest and mark its chunks in the bitmap
+fn write_chunk(buf_data: &mut [u8], offset: u32, chunk: &[u8]) -> Result<()> {
+    let expected_size = u32::from_le_bytes(buf_data[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?) as usize;
+    
+    let (offset_bytes, end) = (offset as usize, offset as usize + chunk.len());
+    require!(end <= expected_size, VerifierError::ProofTooLarge);
+    require!(OFFSET_PROOF_DATA + end <= buf_data.len(), VerifierError::ProofTooLarge);
+    require!(
+        offset_bytes % UPLOAD_CHUNK_ALIGN == 0 && (end % UPLOAD_CHUNK_ALIGN == 0 || end == expected_size),
+        VerifierError::UnalignedUpload
+    );
+    
+    buf_data[OFFSET_PROOF_DATA + offset_bytes..OFFSET_PROOF_DATA + end].copy_from_slice(chunk);
+    
+    // Fold this chunk into the rolling upload digest:
+    // digest' = keccak(digest || offset || chunk)
+    let prev_digest: [u8; 32] = buf_data[OFFSET_UPLOAD_DIGEST..OFFSET_UPLOAD_DIGEST + 32]
+        .try_into()
+        .map_err(|_| VerifierError::BufferCorrupt)?;
+    let digest = keccak::hashv(&[&prev_digest, &offset.to_le_bytes(), chunk]).0;
+    buf_data[OFFSET_UPLOAD_DIGEST..OFFSET_UPLOAD_DIGEST + 32].copy_from_slice(&digest);
+    
+    let bitmap = &mut buf_data[OFFSET_UPLOAD_BITMAP..OFFSET_EXPECTED_KECCAK];
+    for i in offset_bytes / UPLOAD_CHUNK_ALIGN..upload_chunk_count(end) {
+        bitmap[i / 8] |= 1 << (i % 8);
+    }
+    
+    let current_size = u32::from_le_bytes(buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?);
+    if end as u32 > current_size {
+        buf_data[OFFSET_SIZE..OFFSET_SIZE + 4].copy_from_slice(&(end as u32).to_le_bytes());
+    }
+    Ok(())
+}
+
+/// Proof bytes of a fully uploaded buffer, finalized or not, past any
+/// public inputs block
+fn uploaded_proof(buf_data: &[u8]) -> Result<&[u8]> {
+    Ok(split_public_inputs(uploaded_data(buf_data)?)?.1)
+}
+
+/// Data region of a fully uploaded buffer: every chunk written, and the
+/// whole hashing to the keccak the buffer was opened with
+fn uploaded_data(buf_data: &[u8]) -> Result<&[u8]> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    let expected_size = u32::from_le_bytes(buf_data[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].try_into().map_err(|_| VerifierError::BufferCorrupt)?) as usize;
+    require!(expected_size <= MAX_PROOF_SIZE, VerifierError::BufferCorrupt);
+    if let Some(missing) = first_missing_range(upload_bitmap(buf_data)?, expected_size) {
+        msg!("Proof bytes {}..{} of {} not uploaded", missing.start, missing.end, expected_size);
+        return err!(VerifierError::IncompleteProof);
+    }
+    
+    let data = buf_data
+        .get(OFFSET_PROOF_DATA..OFFSET_PROOF_DATA + expected_size)
+        .ok_or(VerifierError::BufferCorrupt)?;
+    require!(
+        keccak_hash(data)[..] == buf_data[OFFSET_EXPECTED_KECCAK..OFFSET_EXPECTED_KECCAK + 32],
+        VerifierError::ProofHashMismatch
+    );
+    Ok(data)
+}
+
+/// Proof bytes and upload digest of a buffer about to be verified in stages:
+/// owned by `owner`, fully uploaded and not yet finalized
+fn staged_proof<'a>(buf_data: &'a [u8], owner: &Pubkey) -> Result<(&'a [u8], [u8; 32])> {
+    check_finalizable(buf_data, owner)?;
+    
+    let proof_data = uploaded_proof(buf_data)?;
+    let digest: [u8; 32] = buf_data[OFFSET_UPLOAD_DIGEST..OFFSET_UPLOAD_DIGEST + 32]
+        .try_into()
+        .map_err(|_| VerifierError::BufferCorrupt)?;
+    Ok((proof_data, digest))
+}
+
+/// Stage 2 of a staged verification, on a buffer already checked against `state`
+fn run_oods_stage(state: &mut VerificationState, proof_data: &[u8]) -> Result<()> {
+    let mut meter = CuMeter::start();
+    let proof = parse_proof(proof_data)?;
+    let mut channel = Channel::restore(&state.channel, VerificationPhase::Oods, proof.header.hash_kind)?;
+    meter.event.parse += meter.lap();
+    let mut report = VerificationReport { stage: VerificationStage::Constraint, ..Default::default() };
+    let fri_alphas = verify_oods(
+        &proof,
+        &mut channel,
+        &state.commitment,
+        &state.nullifier,
+        &state.merkle_root,
+        &VerifierConfig::MURKL,
+        &mut meter,
     )
+    .map_err(|err| reject(&mut report, err))?;
+    for (slot, alpha) in state.fri_alphas.iter_mut().zip(fri_alphas.iter()) {
+        *slot = [alpha.a.0, alpha.b.0, alpha.c.0, alpha.d.0];
+    }
+    state.channel = channel.checkpoint(VerificationPhase::Queries);
+    meter.finish();
+    Ok(())
+}
+
+/// Stage 3 of a staged verification: up to `count` queries (0 = no cap)
+fn run_query_stage(state: &mut VerificationState, proof_data: &[u8], count: u8) -> Result<()> {
+    let mut meter = CuMeter::start();
+    let proof = parse_proof(proof_data)?;
+    let mut channel = Channel::restore(&state.channel, VerificationPhase::Queries, proof.header.hash_kind)?;
+    let config = VerifierConfig::MURKL;
+    let mut fri_alphas = Bounded::<QM31, MAX_FRI_LAYERS>::new();
+    for limbs in state.fri_alphas.iter().take(proof.fri_layer_commitments.len()) {
+        fri_alphas.push(QM31::new(M31::new(limbs[0]), M31::new(limbs[1]), M31::new(limbs[2]), M31::new(limbs[3])))?;
+    }
+    
+    let start = state.queries_verified as usize;
+    let limit = if count == 0 { proof.header.num_queries } else { start + count as usize };
+    let mut end = start;
+    let mut budget = QueryBudget::new(remaining_compute_units());
+    for query in proof.queries().take(limit.min(proof.header.num_queries)).skip(start) {
+        let mut report = VerificationReport { query_index: Some(end as u8), ..Default::default() };
+        let query = query?;
+        meter.event.parse += meter.lap();
+        verify_query(&proof, &query, &fri_alphas, &mut channel, &config, &mut report, &mut meter)
+            .map_err(|err| reject(&mut report, err))?;
+        end += 1;
+        if !budget.next_fits(remaining_compute_units()) {
+            break;
+        }
+    }
+    
+    state.queries_verified = end as u8;
+    let next = if end == proof.header.num_queries {
+        VerificationPhase::Complete
+    } else {
+        VerificationPhase::Queries
+    };
+    state.channel = channel.checkpoint(next);
+    meter.finish();
+    msg!("Staged queries verified: {}/{}", end, proof.header.num_queries);
+    Ok(())
 }
 
 // ============================================================================
@@ -916,6 +2444,16 @@ pub struct UploadChunk<'info> {
     #[account(mut)]
     pub proof_buffer: AccountInfo<'info>,
     
+    /// Buffer owner or its upload delegate
+    pub uploader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateUploader<'info> {
+    /// CHECK: Raw buffer account
+    #[account(mut)]
+    pub proof_buffer: AccountInfo<'info>,
+    
     pub owner: Signer<'info>,
 }
 
@@ -926,6 +2464,112 @@ pub struct FinalizeAndVerify<'info> {
     pub proof_buffer: AccountInfo<'info>,
     
     pub owner: Signer<'info>,
+
+    #[account(seeds = [VERIFIER_CONFIG_SEED], bump = verifier_config.bump)]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+}
+
+/// Buffers are passed as writable `remaining_accounts`
+#[derive(Accounts)]
+pub struct FinalizeAndVerifyBatch<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [VERIFIER_CONFIG_SEED], bump = verifier_config.bump)]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetUploadStatus<'info> {
+    /// CHECK: Raw buffer account (read-only)
+    pub proof_buffer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyFromBuffer<'info> {
+    /// CHECK: Raw buffer account (read-only), must be owned by this program
+    #[account(constraint = proof_buffer.owner == &crate::ID @ VerifierError::Unauthorized)]
+    pub proof_buffer: AccountInfo<'info>,
+
+    #[account(seeds = [VERIFIER_CONFIG_SEED], bump = verifier_config.bump)]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32])]
+pub struct AttestInclusion<'info> {
+    /// CHECK: Raw buffer account (read-only), must be owned by this program
+    #[account(constraint = proof_buffer.owner == &crate::ID @ VerifierError::Unauthorized)]
+    pub proof_buffer: AccountInfo<'info>,
+    
+    /// Owner of the finalized proof buffer
+    pub prover: Signer<'info>,
+    
+    /// Namespace authority; only it can write attestations under its key
+    pub namespace: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [b"attestation", namespace.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVerifierConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VerifierConfigAccount::INIT_SPACE,
+        seeds = [VERIFIER_CONFIG_SEED],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerifierProfile<'info> {
+    #[account(
+        mut,
+        seeds = [VERIFIER_CONFIG_SEED],
+        bump = verifier_config.bump,
+        has_one = authority @ VerifierError::Unauthorized
+    )]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PublishVerifierParams<'info> {
+    #[account(seeds = [VERIFIER_CONFIG_SEED], bump = verifier_config.bump)]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VerifierParams::INIT_SPACE,
+        seeds = [VERIFIER_PARAMS_SEED],
+        bump
+    )]
+    pub verifier_params: Account<'info, VerifierParams>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -938,6 +2582,189 @@ pub struct CloseProofBuffer<'info> {
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ReapExpiredBuffer<'info> {
+    /// CHECK: Raw buffer account, must be owned by this program
+    #[account(mut, constraint = proof_buffer.owner == &crate::ID @ VerifierError::Unauthorized)]
+    pub proof_buffer: AccountInfo<'info>,
+    
+    /// CHECK: the owner in the buffer header, checked in the handler; gets the rest of the rent
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub reaper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BeginStagedVerification<'info> {
+    /// CHECK: Raw buffer account (read-only), must be owned by this program
+    #[account(constraint = proof_buffer.owner == &crate::ID @ VerifierError::Unauthorized)]
+    pub proof_buffer: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VerificationState::INIT_SPACE,
+        seeds = [b"verification", proof_buffer.key().as_ref()],
+        bump
+    )]
+    pub verification_state: Box<Account<'info, VerificationState>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [VERIFIER_CONFIG_SEED], bump = verifier_config.bump)]
+    pub verifier_config: Account<'info, VerifierConfigAccount>,
+}
+
+#[derive(Accounts)]
+pub struct StagedVerification<'info> {
+    /// CHECK: Raw buffer account (read-only), bound to the state by its seeds
+    pub proof_buffer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"verification", proof_buffer.key().as_ref()],
+        bump = verification_state.bump,
+        has_one = owner @ VerifierError::Unauthorized
+    )]
+    pub verification_state: Box<Account<'info, VerificationState>>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStagedVerification<'info> {
+    /// CHECK: Raw buffer account, bound to the state by its seeds
+    #[account(mut)]
+    pub proof_buffer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"verification", proof_buffer.key().as_ref()],
+        bump = verification_state.bump,
+        has_one = owner @ VerifierError::Unauthorized
+    )]
+    pub verification_state: Box<Account<'info, VerificationState>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+/// (key, value) pair attested under a namespace by a verified proof.
+/// PDA derived from namespace + nullifier ensures one record per proof.
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub namespace: Pubkey,
+    pub key: [u8; 32],
+    pub value: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub attested_at: i64,
+    pub bump: u8,
+}
+
+/// The [`VerifierConfig`] this deployment checks proofs against, as last
+/// published by `publish_verifier_params`. PDA of [`VERIFIER_PARAMS_SEED`].
+#[account]
+#[derive(InitSpace)]
+pub struct VerifierParams {
+    /// [`VerifierConfig::params_hash`] of the fields below
+    pub params_hash: [u8; 32],
+    pub log_trace_size: u32,
+    pub log_blowup: u32,
+    pub log_folding_factor: u32,
+    pub log_final_poly_degree: u32,
+    pub min_queries: u32,
+    pub trace_columns: u32,
+    pub hash_kinds: u8,
+    pub bump: u8,
+}
+
+impl VerifierParams {
+    /// Whether this deployment takes `hash_kind` proofs. Provers read it to
+    /// pick [`HashKind::PoseidonBn254`] only on clusters whose verifier was
+    /// built with the Poseidon syscall.
+    pub const fn accepts(&self, hash_kind: HashKind) -> bool {
+        self.hash_kinds & (1 << hash_kind as u8) != 0
+    }
+
+    /// The published parameters as a config, for checking a proof's
+    /// [`ProofHeader`] (`config().check_header(..)`) before uploading it
+    pub fn config(&self) -> VerifierConfig {
+        VerifierConfig {
+            log_trace_size: self.log_trace_size,
+            log_blowup: self.log_blowup,
+            log_folding_factor: self.log_folding_factor,
+            log_final_poly_degree: self.log_final_poly_degree,
+            min_queries: self.min_queries as usize,
+            trace_columns: self.trace_columns as usize,
+            hash_kinds: self.hash_kinds,
+        }
+    }
+}
+
+/// Profile this deployment checks proofs at, set by its authority.
+/// PDA of [`VERIFIER_CONFIG_SEED`]; every verifying instruction reads it.
+#[account]
+#[derive(InitSpace)]
+pub struct VerifierConfigAccount {
+    /// Signs `set_verifier_profile`; whoever initialized the account
+    pub authority: Pubkey,
+    /// [`SecurityLevel`] byte, at least [`MIN_SECURITY_LEVEL`]
+    pub security_level: u8,
+    pub bump: u8,
+}
+
+impl VerifierConfigAccount {
+    /// The [`VerifierConfig`] proofs are checked against. Fails if the level
+    /// is below what this build allows, as after upgrading a `demo` build
+    /// to a mainnet one without raising the profile.
+    pub fn config(&self) -> Result<VerifierConfig> {
+        VerifierConfig::for_profile(self.security_level)
+    }
+}
+
+/// Progress of a verification split across transactions.
+/// PDA derived from the proof buffer; closed on finalize or cancel.
+#[account]
+#[derive(InitSpace)]
+pub struct VerificationState {
+    pub proof_buffer: Pubkey,
+    pub owner: Pubkey,
+    /// Buffer upload digest when the run began; any later write aborts it
+    pub upload_digest: [u8; 32],
+    pub commitment: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub recipient: [u8; 32],
+    /// [`Channel::checkpoint`] tagged with the next stage
+    pub channel: [u8; CHANNEL_CHECKPOINT_LEN],
+    /// FRI folding alphas as QM31 limbs, drawn by the OODS stage
+    pub fri_alphas: [[u32; 4]; MAX_FRI_LAYERS],
+    /// Query openings verified so far, in proof order
+    pub queries_verified: u8,
+    pub bump: u8,
+}
+
+impl VerificationState {
+    /// Proof bytes of the buffer, provided it is still the one this run began on
+    fn check_buffer<'a>(&self, buf_data: &'a [u8]) -> Result<&'a [u8]> {
+        let (proof_data, digest) = staged_proof(buf_data, &self.owner)?;
+        require!(digest == self.upload_digest, VerifierError::ProofChangedDuringVerification);
+        Ok(proof_data)
+    }
+}
+
 // ============================================================================
 // Errors
 // ============================================================================
@@ -965,7 +2792,10 @@ pub enum VerifierError {
     #[msg("Buffer already finalized")]
     BufferAlreadyFinalized,
     
-    #[msg("Incomplete proof")]
+    #[msg("Proof buffer is not finalized")]
+    ProofNotFinalized,
+    
+    #[msg("Proof upload is missing chunks; the first missing byte range is logged")]
     IncompleteProof,
     
     #[msg("Constraint mismatch - AIR evaluation failed at OODS")]
@@ -988,6 +2818,56 @@ pub enum VerifierError {
     
     #[msg("Final polynomial evaluation mismatch")]
     FinalPolyMismatch,
+    
+    #[msg("Too few FRI layers for the configured domain")]
+    FriLayerCountTooLow,
+
+    #[msg("Too few query openings")]
+    TooFewQueries,
+    #[msg("Trace column count does not match the verifier configuration")]
+    TraceColumnCountMismatch,
+
+    #[msg("Staged verification step out of order")]
+    VerificationPhaseMismatch,
+
+    #[msg("Proof buffer changed since staged verification began")]
+    ProofChangedDuringVerification,
+
+    #[msg("Unknown hash kind in proof header")]
+    UnsupportedHashKind,
+
+    #[msg("Batch needs 1 to MAX_BATCH_PROOFS entries, one per buffer account")]
+    InvalidBatch,
+
+    #[msg("Evaluation domain size does not match the verifier configuration")]
+    DomainSizeMismatch,
+
+    #[msg("Proof does not start with a supported magic and format version")]
+    UnsupportedProofVersion,
+    #[msg("FRI folding factor does not match the verifier configuration")]
+    FoldingFactorMismatch,
+    #[msg("Public inputs block is missing, truncated or holds fewer than 4 or more than MAX_PUBLIC_INPUTS inputs")]
+    InvalidPublicInputs,
+    #[msg("Uploaded public inputs do not match the committed hash")]
+    PublicInputsMismatch,
+    #[msg("Buffer expiry must be 1 to MAX_BUFFER_EXPIRY_SLOTS slots")]
+    InvalidBufferExpiry,
+    #[msg("Proof buffer has not expired yet")]
+    BufferNotExpired,
+    #[msg("Uploads must start on an UPLOAD_CHUNK_ALIGN boundary and end on one or at the end of the proof")]
+    UnalignedUpload,
+    #[msg("Upload segments must be non-empty and their lengths must add up to the data")]
+    InvalidUploadSegments,
+    #[msg("Uploaded proof does not hash to the keccak the buffer was initialized with")]
+    ProofHashMismatch,
+    #[msg("Nullifier does not match the finalized proof buffer")]
+    NullifierMismatch,
+    #[msg("Commitment, nullifier or root has a 4-byte limb that is not a canonical M31")]
+    NonCanonicalPublicInput,
+    #[msg("Security level byte names no SecurityLevel")]
+    InvalidSecurityLevel,
+    #[msg("Security level is below MIN_SECURITY_LEVEL for this build")]
+    SecurityLevelNotAllowed,
 }
 
 // ============================================================================
@@ -995,24 +2875,1206 @@ pub enum VerifierError {
 // ============================================================================
 
 /// Verification result for CPI callers
+///
+/// A rejected proof is a result, not an error: check `success` (or call
+/// [`VerificationResult::require_verified`]) before trusting the inputs.
+#[must_use]
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct VerificationResult {
     pub success: bool,
     pub compute_units: u64,
+    /// Where verification stopped, when `success` is false
+    pub report: Option<VerificationReport>,
+}
+
+impl VerificationResult {
+    /// Fail with the rejected proof's error code, as `verify_stark_proof` would
+    pub fn require_verified(&self) -> Result<()> {
+        match &self.report {
+            Some(report) if !self.success => Err(ProgramError::Custom(report.error_code).into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check a proof was rejected in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerificationStage {
+    /// Decoding the header or a query opening
+    #[default]
+    Parse,
+    /// Header against the verifier configuration (hash, columns, FRI shape)
+    Shape,
+    /// AIR constraint at the OODS point
+    Constraint,
+    /// Query index against the Fiat-Shamir transcript
+    QueryIndex,
+    TraceMerkle,
+    CompositionMerkle,
+    /// A FRI layer's Merkle path or fold
+    FriFolding,
+    /// Folded value against the final polynomial or sent last layer
+    FinalLayer,
+}
+
+/// Where verification of a proof stopped
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub stage: VerificationStage,
+    /// Query being checked, in proof order
+    pub query_index: Option<u8>,
+    /// FRI layer being checked (`FriFolding` only)
+    pub layer_index: Option<u8>,
+    /// Code of the error the check failed with
+    pub error_code: u32,
+}
+
+impl core::fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.stage)?;
+        if let Some(query) = self.query_index {
+            write!(f, " at query {}", query)?;
+        }
+        if let Some(layer) = self.layer_index {
+            write!(f, ", FRI layer {}", layer)?;
+        }
+        write!(f, " (error {})", self.error_code)
+    }
+}
+
+/// A proof failed verification; logged by every instruction that verifies
+#[event]
+pub struct VerificationFailed {
+    pub report: VerificationReport,
+}
+
+/// Compute units each stage of a verification used, logged when it passes
+/// on a `measure-cu` build. A staged verification logs one per stage
+/// transaction, covering the stages that ran in it. Counts include the
+/// stage's logs and the meter's own syscalls (about 100 units a read).
+#[event]
+#[derive(Default)]
+pub struct CuBreakdownEvent {
+    /// Proof parsing and shape checks, including each query's opening
+    pub parse: u64,
+    /// Public inputs, commitments and OODS values absorbed into the
+    /// channel, and the FRI alphas squeezed from it
+    pub channel: u64,
+    /// The AIR constraint checked at the OODS point
+    pub oods: u64,
+    /// Query indices and trace and composition Merkle paths, over all queries
+    pub merkle: u64,
+    /// FRI layer paths, folding and the final layer, over all queries
+    pub fri: u64,
+    /// Queries verified in this transaction
+    pub queries: u8,
+}
+
+/// One write of `upload_chunks_multi`: `len` bytes at `offset` into the
+/// proof, taken in turn from the instruction's data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadSegment {
+    pub offset: u32,
+    pub len: u16,
+}
+
+/// Public inputs for one buffer of `finalize_and_verify_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub commitment: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub recipient: [u8; 32],
+}
+
+/// Outcome of one buffer in `finalize_and_verify_batch`
+#[event]
+pub struct BatchProofResult {
+    pub buffer: Pubkey,
+    /// Position in the batch
+    pub index: u8,
+    pub verified: bool,
+    /// Error code when not verified, 0 otherwise
+    pub error_code: u32,
+}
+
+/// Upload progress returned by `get_upload_status`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct UploadStatus {
+    pub bytes_written: u32,
+    pub expected_size: u32,
+    pub finalized: bool,
+    /// keccak(prev_digest || offset_le || chunk), chained from all-zero at init
+    pub digest: [u8; 32],
+    /// [`UPLOAD_CHUNK_ALIGN`]-byte chunks written so far
+    pub chunks_received: u32,
+    /// Chunks of the whole proof
+    pub chunks_expected: u32,
+}
+
+/// What a finalized buffer vouches for, as read by [`read_finalized`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FinalizedProof {
+    /// Signer that finalized the buffer
+    pub owner: Pubkey,
+    pub inputs: BatchEntry,
+    /// Slot of the last `finalize_and_verify` or `reverify_proof_buffer`
+    pub finalized_slot: u64,
+}
+
+/// Read a proof buffer passed to another program; `None` until it is finalized
+///
+/// Check the account is owned by this program first: the layout alone
+/// proves nothing.
+pub fn read_finalized(buf_data: &[u8]) -> Result<Option<FinalizedProof>> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    if buf_data[OFFSET_FINALIZED] != 1 {
+        return Ok(None);
+    }
+    let read32 = |offset: usize| -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&buf_data[offset..offset + 32]);
+        out
+    };
+    let mut slot = [0u8; 8];
+    slot.copy_from_slice(&buf_data[OFFSET_FINALIZED_SLOT..OFFSET_FINALIZED_SLOT + 8]);
+    Ok(Some(FinalizedProof {
+        owner: Pubkey::new_from_array(read32(OFFSET_OWNER)),
+        inputs: BatchEntry {
+            commitment: read32(OFFSET_COMMITMENT),
+            nullifier: read32(OFFSET_NULLIFIER),
+            merkle_root: read32(OFFSET_MERKLE_ROOT),
+            recipient: read32(OFFSET_RECIPIENT),
+        },
+        finalized_slot: u64::from_le_bytes(slot),
+    }))
+}
+
+/// Upload bitmap of a buffer; see [`chunk_received`]
+pub fn upload_bitmap(buf_data: &[u8]) -> Result<&[u8]> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    Ok(&buf_data[OFFSET_UPLOAD_BITMAP..OFFSET_EXPECTED_KECCAK])
+}
+
+/// Whether chunk `index`, proof bytes from `index * UPLOAD_CHUNK_ALIGN`,
+/// has been written
+pub fn chunk_received(bitmap: &[u8], index: usize) -> bool {
+    bitmap.get(index / 8).is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+/// Chunks covering the first `len` bytes of a proof
+pub const fn upload_chunk_count(len: usize) -> usize {
+    len.div_ceil(UPLOAD_CHUNK_ALIGN)
+}
+
+/// First run of missing chunks in a proof of `expected_size` bytes, as the
+/// byte range to upload, or `None` once every chunk is in
+pub fn first_missing_range(bitmap: &[u8], expected_size: usize) -> Option<Range<usize>> {
+    let chunks = upload_chunk_count(expected_size);
+    let first = (0..chunks).find(|&i| !chunk_received(bitmap, i))?;
+    let last = (first..chunks).find(|&i| chunk_received(bitmap, i)).unwrap_or(chunks);
+    Some(first * UPLOAD_CHUNK_ALIGN..(last * UPLOAD_CHUNK_ALIGN).min(expected_size))
+}
+
+/// First slot at which anyone may reap the buffer: its creation slot plus
+/// its expiry
+pub fn buffer_expires_at(buf_data: &[u8]) -> Result<u64> {
+    require!(buf_data.len() >= HEADER_SIZE, VerifierError::BufferCorrupt);
+    let read_u64 = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&buf_data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    Ok(read_u64(OFFSET_CREATED_AT).saturating_add(read_u64(OFFSET_EXPIRY_SLOTS)))
 }
 
 /// Verify a proof via CPI (helper for external programs)
+///
+/// A proof that fails `config` comes back as `success: false` with its
+/// [`VerificationReport`], so the caller can act on where it failed.
 pub fn verify_proof_cpi(
+    config: &VerifierConfig,
     proof_data: &[u8],
     commitment: &[u8; 32],
     nullifier: &[u8; 32],
     merkle_root: &[u8; 32],
     recipient: &[u8; 32],
 ) -> Result<VerificationResult> {
-    verify_stark_proof(proof_data, commitment, nullifier, merkle_root, recipient)?;
+    let mut report = VerificationReport::default();
+    let rejected = match check_stark_proof(
+        config,
+        proof_data,
+        commitment,
+        nullifier,
+        merkle_root,
+        recipient,
+        &[],
+        &mut report,
+    ) {
+        Ok(()) => None,
+        Err(err) => {
+            reject(&mut report, err);
+            Some(report)
+        }
+    };
     
     Ok(VerificationResult {
-        success: true,
+        success: rejected.is_none(),
         compute_units: 100000, // Estimated CU for full verification
+        report: rejected,
     })
 }
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Query-less proof with the MURKL parameter header: commitments,
+    /// column count, OODS values, `num_layers` zero commitments and a
+    /// 1-coefficient final poly.
+    fn proof_with_layers(num_layers: u8) -> Vec<u8> {
+        let config = VerifierConfig::MURKL;
+        let mut data = PROOF_MAGIC.to_vec();
+        data.extend_from_slice(&[
+            PROOF_VERSION,
+            HashKind::Keccak as u8,
+            config.log_trace_size as u8,
+            config.log_blowup as u8,
+            config.log_folding_factor as u8,
+            0,
+        ]);
+        data.extend_from_slice(&[0u8; 32 + 32]);
+        data.push(config.trace_columns as u8);
+        data.extend(core::iter::repeat_n(0u8, 16 * (2 * config.trace_columns + 1)));
+        data.push(num_layers);
+        data.extend(core::iter::repeat_n(0u8, 32 * num_layers as usize));
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 16]);
+        data
+    }
+
+    /// A well-formed proof of the MURKL shape with made-up values: every
+    /// query opens full-depth trace and composition paths and a path per
+    /// FRI layer
+    fn canonical_wire() -> murkl_proof_format::SerializedProof {
+        use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof};
+
+        let config = VerifierConfig::MURKL;
+        let layers = config.required_fri_layers();
+        let depth = config.log_domain_size() as usize;
+        let query = |index: u32| QueryOpening {
+            index,
+            trace_row: (0..config.trace_columns as u32).map(|c| index + c).collect(),
+            trace_path: vec![[1; 32]; depth],
+            composition_leaf: [2; 32],
+            composition_path: vec![[3; 32]; depth],
+            fri: (0..layers)
+                .map(|l| FriOpening { siblings: [[l as u32, 1, 2, 3]; 4], path: vec![[4; 32]; depth - 2 * (l + 1)] })
+                .collect(),
+        };
+        SerializedProof {
+            hash_kind: HashKind::Poseidon2 as u8,
+            log_trace_size: config.log_trace_size as u8,
+            log_blowup: config.log_blowup as u8,
+            log_folding_factor: config.log_folding_factor as u8,
+            trace_commitment: [5; 32],
+            composition_commitment: [6; 32],
+            trace_columns: config.trace_columns as u8,
+            trace_oods: (0..config.trace_columns as u32).map(|c| [7, 8, 9, c]).collect(),
+            trace_oods_next: (0..config.trace_columns as u32).map(|c| [7, 8, 10, c]).collect(),
+            composition_oods: [11, 12, 13, 14],
+            fri_layer_commitments: (0..layers as u8).map(|l| [l; 32]).collect(),
+            final_layer: FinalLayer::Coefficients(vec![[15, 0, 0, 0]]),
+            queries: (0..config.min_queries as u32).map(|q| query(q * 1000)).collect(),
+        }
+    }
+
+    /// parse_proof reads what the canonical encoder writes, field for field
+    #[test]
+    fn test_parse_agrees_with_canonical_format() {
+        use murkl_proof_format::SerializedProof;
+
+        assert_eq!(MAX_TRACE_COLUMNS, murkl_proof_format::MAX_TRACE_COLUMNS);
+        assert_eq!(MAX_FRI_LAYERS, murkl_proof_format::MAX_FRI_LAYERS);
+        assert_eq!(MAX_MERKLE_DEPTH, murkl_proof_format::MAX_MERKLE_DEPTH);
+        assert_eq!(MAX_FINAL_POLY_LEN, murkl_proof_format::MAX_FINAL_POLY_LEN);
+        assert_eq!(MAX_FINAL_LAYER_EVALS, murkl_proof_format::MAX_FINAL_LAYER_EVALS);
+        assert_eq!(MAX_QUERIES, murkl_proof_format::MAX_QUERIES);
+        assert_eq!(MAX_PROOF_SIZE, murkl_proof_format::MAX_PROOF_SIZE);
+
+        let config = VerifierConfig::MURKL;
+        let layers = config.required_fri_layers();
+        let depth = config.log_domain_size() as usize;
+        let wire = canonical_wire();
+        let bytes = wire.encode();
+        assert_eq!(SerializedProof::decode(&bytes).unwrap(), wire);
+
+        let proof = parse_proof(&bytes).unwrap();
+        assert!(config.check_header(&proof.header).is_ok());
+        assert_eq!(proof.header.hash_kind, HashKind::Poseidon2);
+        assert_eq!(*proof.trace_commitment, wire.trace_commitment);
+        assert_eq!(*proof.composition_commitment, wire.composition_commitment);
+        assert_eq!(proof.num_trace_columns, config.trace_columns);
+        let limbs = |v: QM31| [v.a.0, v.b.0, v.c.0, v.d.0];
+        assert_eq!(proof.trace_oods.iter().map(limbs).collect::<Vec<_>>(), wire.trace_oods);
+        assert_eq!(proof.trace_oods_next.iter().map(limbs).collect::<Vec<_>>(), wire.trace_oods_next);
+        assert!(proof.composition_oods.eq(&QM31::new(M31::new(11), M31::new(12), M31::new(13), M31::new(14))));
+        assert_eq!(proof.fri_layer_commitments, &wire.fri_layer_commitments[..]);
+        assert_eq!(proof.fri_final_poly.len(), 1);
+
+        let queries: Vec<_> = proof.queries().collect::<Result<_>>().unwrap();
+        assert_eq!(queries.len(), wire.queries.len());
+        for (parsed, sent) in queries.iter().zip(&wire.queries) {
+            assert_eq!(parsed.index, sent.index);
+            let row: Vec<u8> = sent.trace_row.iter().flat_map(|v| v.to_le_bytes()).collect();
+            assert_eq!(parsed.trace_row, &row[..]);
+            assert_eq!(parsed.trace_path, &sent.trace_path[..]);
+            assert_eq!(parsed.composition_path.len(), depth);
+            assert_eq!(parsed.fri_layer_values.len(), layers);
+            assert_eq!(parsed.fri_layer_values[layers - 1].path, &sent.fri[layers - 1].path[..]);
+        }
+        let consumed: usize = queries.iter().map(|q| q.size).sum();
+        assert_eq!(consumed, proof.query_data.len());
+
+        // Nothing is copied out of the encoding, and a cut anywhere in it
+        // is an error rather than a panic
+        let range = bytes.as_ptr_range();
+        assert!(range.contains(&proof.fri_layer_commitments.as_ptr().cast()));
+        assert!(range.contains(&queries[0].fri_layer_values[0].siblings.0.as_ptr().cast()));
+        for len in 0..bytes.len() {
+            let parsed = parse_proof(&bytes[..len]).and_then(|proof| proof.queries().try_for_each(|q| q.map(drop)));
+            assert!(parsed.is_err(), "truncated to {len} bytes");
+        }
+    }
+
+    #[test]
+    fn test_required_fri_layers() {
+        assert_eq!(VerifierConfig::MURKL.required_fri_layers(), 3);
+        assert_eq!(VerifierConfig::MURKL.log_domain_size(), 14);
+
+        let odd = VerifierConfig { log_trace_size: 9, ..VerifierConfig::MURKL };
+        assert_eq!(odd.required_fri_layers(), 3); // 5 bits of reduction, rounded up
+    }
+
+    #[test]
+    fn test_params_hash_covers_security_parameters() {
+        let murkl = VerifierConfig::MURKL;
+        assert!(murkl.accepts(HashKind::Keccak) && murkl.accepts(HashKind::Poseidon2));
+        assert_eq!(murkl.params_hash(), VerifierConfig::MURKL.params_hash());
+
+        let weaker = [
+            VerifierConfig { min_queries: 2, ..murkl },
+            VerifierConfig { log_blowup: 2, ..murkl },
+            VerifierConfig { hash_kinds: 1 << HashKind::Keccak as u8, ..murkl },
+        ];
+        for config in weaker {
+            assert_ne!(config.params_hash(), murkl.params_hash());
+        }
+        assert!(!weaker[2].accepts(HashKind::Poseidon2));
+    }
+
+    #[test]
+    fn test_security_level_presets() {
+        assert_eq!(VerifierConfig::MURKL.security_level(), Some(SecurityLevel::Demo));
+        for level in SecurityLevel::ALL {
+            let config = VerifierConfig::for_level(level);
+            assert_eq!(config.security_level(), Some(level));
+            assert_eq!(config.log_trace_size, VerifierConfig::MURKL.log_trace_size);
+        }
+
+        let high = VerifierConfig::for_level(SecurityLevel::High128);
+        assert!(high.accepts(HashKind::Keccak) && !high.accepts(HashKind::Poseidon2));
+        let header = ProofHeader {
+            version: PROOF_VERSION,
+            hash_kind: HashKind::Keccak,
+            log_trace_size: high.log_trace_size,
+            log_blowup: high.log_blowup,
+            log_folding_factor: high.log_folding_factor,
+            num_queries: 31,
+        };
+        assert_eq!(high.check_header(&header), Err(VerifierError::TooFewQueries.into()));
+        assert!(high.check_header(&ProofHeader { num_queries: 32, ..header }).is_ok());
+    }
+
+    #[test]
+    fn test_verifier_profiles() {
+        let mut account = VerifierConfigAccount {
+            authority: Pubkey::new_unique(),
+            security_level: SecurityLevel::High128 as u8,
+            bump: 255,
+        };
+        let high = VerifierConfig::for_level(SecurityLevel::High128);
+        assert_eq!(account.config().unwrap().params_hash(), high.params_hash());
+
+        account.security_level = 3;
+        assert_eq!(account.config().unwrap_err(), VerifierError::InvalidSecurityLevel.into());
+
+        // Demo proofs verify only where the build allows the Demo profile
+        account.security_level = SecurityLevel::Demo as u8;
+        if cfg!(feature = "demo") {
+            assert_eq!(account.config().unwrap().params_hash(), VerifierConfig::MURKL.params_hash());
+        } else {
+            assert_eq!(account.config().unwrap_err(), VerifierError::SecurityLevelNotAllowed.into());
+        }
+        assert_eq!(MIN_SECURITY_LEVEL == SecurityLevel::Demo, cfg!(feature = "demo"));
+    }
+
+    #[test]
+    fn test_trace_vanishing() {
+        // (0, 1) is a point of the size-2 canonic coset
+        let base = SecureCirclePoint { x: QM31::ZERO, y: QM31::ONE };
+        assert!(trace_vanishing(&base, 1).eq(&QM31::ZERO));
+        // ... and its double (-1, 0) is not on the size-4 coset
+        assert!(!trace_vanishing(&base, 2).eq(&QM31::ZERO));
+
+        let mut channel = Channel::new(HashKind::Keccak);
+        for _ in 0..8 {
+            let point = channel.squeeze_circle_point();
+            assert!(point.is_on_circle());
+            for log_size in 1..=VerifierConfig::MURKL.log_trace_size {
+                assert!(!trace_vanishing(&point, log_size).eq(&QM31::ZERO));
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_fri_layers_rejected() {
+        let mut data = proof_with_layers(0);
+        data[9] = VerifierConfig::MURKL.min_queries as u8;
+        let err = verify_stark_proof(&data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).unwrap_err();
+        assert_eq!(err, VerifierError::FriLayerCountTooLow.into());
+    }
+
+    #[test]
+    fn test_parse_bounds() {
+        assert!(parse_proof(&proof_with_layers(MAX_FRI_LAYERS as u8)).is_ok());
+        let err = parse_proof(&proof_with_layers(MAX_FRI_LAYERS as u8 + 1)).unwrap_err();
+        assert_eq!(err, VerifierError::InvalidProofFormat.into());
+
+        let mut bounded = Bounded::<u8, 2>::new();
+        bounded.push(1).unwrap();
+        bounded.push(2).unwrap();
+        assert!(bounded.push(3).is_err());
+        assert_eq!(&*bounded, &[1, 2]);
+    }
+
+    #[test]
+    fn test_upload_delegate() {
+        let owner = Pubkey::new_unique();
+        let worker = Pubkey::new_unique();
+        let mut header = [0u8; HEADER_SIZE];
+        header[OFFSET_OWNER..OFFSET_OWNER + 32].copy_from_slice(owner.as_ref());
+
+        assert!(check_uploader(&header, &owner).is_ok());
+        assert!(check_uploader(&header, &worker).is_err());
+        // An unset delegate never matches the default key
+        assert!(check_uploader(&header, &Pubkey::default()).is_err());
+
+        header[OFFSET_DELEGATE..OFFSET_DELEGATE + 32].copy_from_slice(worker.as_ref());
+        assert!(check_uploader(&header, &worker).is_ok());
+        assert!(check_uploader(&header, &owner).is_ok());
+        assert!(check_uploader(&header, &Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_hash_kind_header() {
+        let mut data = proof_with_layers(VerifierConfig::MURKL.required_fri_layers() as u8);
+        assert_eq!(parse_proof(&data).unwrap().header.hash_kind, HashKind::Keccak);
+        data[5] = HashKind::Poseidon2 as u8;
+        assert_eq!(parse_proof(&data).unwrap().header.hash_kind, HashKind::Poseidon2);
+        data[5] = HashKind::PoseidonBn254 as u8;
+        assert_eq!(parse_proof(&data).unwrap().header.hash_kind, HashKind::PoseidonBn254);
+        data[5] = 3;
+        assert_eq!(parse_proof(&data).unwrap_err(), VerifierError::UnsupportedHashKind.into());
+
+        // The syscall-backed kind is only accepted where it is compiled in
+        let murkl = VerifierConfig::MURKL;
+        assert_eq!(murkl.accepts(HashKind::PoseidonBn254), cfg!(feature = "poseidon-syscall"));
+
+        // The two channels draw different challenges from the same transcript
+        let mut keccak = Channel::new(HashKind::Keccak);
+        let mut poseidon = Channel::new(HashKind::Poseidon2);
+        keccak.mix_digest(&[7; 32]);
+        poseidon.mix_digest(&[7; 32]);
+        assert!(!keccak.squeeze_qm31().eq(&poseidon.squeeze_qm31()));
+    }
+
+    #[test]
+    fn test_trace_column_count_enforced() {
+        let mut data = proof_with_layers(VerifierConfig::MURKL.required_fri_layers() as u8);
+        for columns in [0, MAX_TRACE_COLUMNS as u8 + 1] {
+            data[74] = columns;
+            assert_eq!(parse_proof(&data).unwrap_err(), VerifierError::InvalidProofFormat.into());
+        }
+        data[9] = VerifierConfig::MURKL.min_queries as u8;
+        data[74] = VerifierConfig::MURKL.trace_columns as u8 - 1;
+        // One column fewer opens two OODS values fewer
+        data.drain(75..75 + 2 * 16);
+        let err = verify_stark_proof(&data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).unwrap_err();
+        assert_eq!(err, VerifierError::TraceColumnCountMismatch.into());
+    }
+
+    #[test]
+    fn test_parameter_header_enforced() {
+        let config = VerifierConfig::MURKL;
+        let data = proof_with_layers(config.required_fri_layers() as u8);
+        let header = ProofHeader::parse(&data).unwrap();
+        assert_eq!(header.log_domain_size(), config.log_domain_size());
+        let rejects = |at: usize, byte: u8| {
+            let mut data = data.clone();
+            data[at] = byte;
+            parse_proof(&data).and_then(|proof| check_proof_shape(&proof, &config)).unwrap_err()
+        };
+
+        // Magic and version
+        assert_eq!(rejects(0, b'X'), VerifierError::UnsupportedProofVersion.into());
+        assert_eq!(rejects(4, PROOF_VERSION + 1), VerifierError::UnsupportedProofVersion.into());
+        assert_eq!(rejects(4, murkl_proof_format::STWO_PROOF_VERSION), VerifierError::UnsupportedProofVersion.into());
+        // Out of the parser's bounds
+        assert_eq!(rejects(6, MAX_MERKLE_DEPTH as u8), VerifierError::InvalidProofFormat.into());
+        assert_eq!(rejects(8, 0), VerifierError::InvalidProofFormat.into());
+        assert_eq!(rejects(9, MAX_QUERIES as u8 + 1), VerifierError::InvalidProofFormat.into());
+        // Parsed, but not this config's parameters
+        assert_eq!(rejects(6, config.log_trace_size as u8 - 1), VerifierError::DomainSizeMismatch.into());
+        assert_eq!(rejects(7, config.log_blowup as u8 - 1), VerifierError::DomainSizeMismatch.into());
+        assert_eq!(rejects(8, config.log_folding_factor as u8 + 1), VerifierError::FoldingFactorMismatch.into());
+        assert_eq!(rejects(9, config.min_queries as u8 - 1), VerifierError::TooFewQueries.into());
+
+        // A published VerifierParams checks the same header
+        let params = VerifierParams {
+            params_hash: config.params_hash(),
+            log_trace_size: config.log_trace_size,
+            log_blowup: config.log_blowup,
+            log_folding_factor: config.log_folding_factor,
+            log_final_poly_degree: config.log_final_poly_degree,
+            min_queries: config.min_queries as u32,
+            trace_columns: config.trace_columns as u32,
+            hash_kinds: config.hash_kinds,
+            bump: 255,
+        };
+        assert_eq!(params.config().params_hash(), config.params_hash());
+        let queried = ProofHeader { num_queries: config.min_queries, ..header };
+        assert!(params.config().check_header(&queried).is_ok());
+        let upgraded = VerifierParams { log_blowup: config.log_blowup - 1, ..params };
+        assert!(upgraded.config().check_header(&queried).is_err());
+    }
+
+    #[test]
+    fn test_query_indices_unbiased() {
+        // Masking keeps the low bits of the word itself
+        let mut channel = Channel::new(HashKind::Keccak);
+        let mut words = Channel::new(HashKind::Keccak);
+        assert_eq!(channel.squeeze_bits(14), words.squeeze_word() & 0x3fff);
+        assert_eq!(channel.squeeze_bits(32), words.squeeze_word());
+
+        // A bound that is not a power of two redraws rather than wrapping
+        let mut counts = [0u32; 3];
+        for _ in 0..3000 {
+            counts[channel.squeeze_index(3)] += 1;
+        }
+        assert!(counts.iter().all(|&n| (900..1100).contains(&n)), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_channel_checkpoint_phases() {
+        let mut channel = Channel::new(HashKind::Keccak);
+        channel.mix_digest(&[7; 32]);
+        channel.squeeze_qm31();
+        let checkpoint = channel.checkpoint(VerificationPhase::Queries);
+
+        let mut restored = Channel::restore(&checkpoint, VerificationPhase::Queries, HashKind::Keccak).unwrap();
+        assert_eq!(restored.squeeze_m31(), channel.squeeze_m31());
+        assert_eq!(restored.checkpoint(VerificationPhase::Complete)[1..], channel.checkpoint(VerificationPhase::Complete)[1..]);
+
+        // A stage can't rerun an earlier one's checkpoint or skip ahead
+        for phase in [VerificationPhase::Oods, VerificationPhase::Complete] {
+            let err = Channel::restore(&checkpoint, phase, HashKind::Keccak).map(|_| ()).unwrap_err();
+            assert_eq!(err, VerifierError::VerificationPhaseMismatch.into());
+        }
+        // A zeroed account holds no checkpoint
+        assert!(Channel::restore(&[0; CHANNEL_CHECKPOINT_LEN], VerificationPhase::Oods, HashKind::Keccak).is_err());
+
+        // verify_step dispatches on the tag alone
+        for phase in [VerificationPhase::Oods, VerificationPhase::Queries, VerificationPhase::Complete] {
+            assert_eq!(VerificationPhase::from_tag(channel.checkpoint(phase)[0]), Some(phase));
+        }
+        assert_eq!(VerificationPhase::from_tag(0), None);
+        assert_eq!(VerificationPhase::from_tag(4), None);
+    }
+
+    #[test]
+    fn test_missing_queries_rejected() {
+        let data = proof_with_layers(VerifierConfig::MURKL.required_fri_layers() as u8);
+        let err = verify_stark_proof(&data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).unwrap_err();
+        assert_eq!(err, VerifierError::TooFewQueries.into());
+    }
+
+    #[test]
+    fn test_verification_report_locates_failure() {
+        let data = proof_with_layers(VerifierConfig::MURKL.required_fri_layers() as u8);
+        let result = verify_proof_cpi(&VerifierConfig::MURKL, &data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).unwrap();
+        assert!(!result.success);
+        let report = result.report.unwrap();
+        assert_eq!(report.stage, VerificationStage::Shape);
+        assert_eq!(report.query_index, None);
+        assert_eq!(report.error_code, 6000 + VerifierError::TooFewQueries as u32);
+        assert_eq!(result.require_verified().unwrap_err(), ProgramError::Custom(report.error_code).into());
+
+        // Queries report their position; an unauthenticated trace row stops at its Merkle path
+        let proof = parse_proof(&data).unwrap();
+        let config = VerifierConfig::MURKL;
+        let expected = Channel::new(HashKind::Keccak).squeeze_bits(config.log_domain_size());
+        let mut query = QueryProof {
+            index: expected + 1,
+            trace_row: &[0; 16],
+            trace_path: &[],
+            composition_value: &[0; 32],
+            composition_path: &[],
+            fri_layer_values: Bounded::new(),
+            size: 0,
+        };
+        let mut report = VerificationReport { query_index: Some(2), ..Default::default() };
+        let err = verify_query(&proof, &query, &[], &mut Channel::new(HashKind::Keccak), &config, &mut report, &mut CuMeter::default());
+        assert_eq!(err.unwrap_err(), VerifierError::QueryIndexMismatch.into());
+        assert_eq!((report.stage, report.query_index), (VerificationStage::QueryIndex, Some(2)));
+
+        query.index = expected;
+        let err = verify_query(&proof, &query, &[], &mut Channel::new(HashKind::Keccak), &config, &mut report, &mut CuMeter::default());
+        assert_eq!(err.unwrap_err(), VerifierError::TraceMerklePathFailed.into());
+        assert_eq!(report.stage, VerificationStage::TraceMerkle);
+
+        report.layer_index = Some(1);
+        report.error_code = 6013;
+        assert_eq!(report.to_string(), "TraceMerkle at query 2, FRI layer 1 (error 6013)");
+    }
+
+    #[test]
+    fn test_merkle_paths_have_exact_depth() {
+        let hash = HashKind::Keccak;
+        let leaf = [9u8; 32];
+        let path = [[1u8; 32], [2; 32]];
+        // Leaf 2 of 4: the left child of the root's right child
+        let root = hash.hash_node(&path[1], &hash.hash_node(&hash.hash(&[&leaf]), &path[0]));
+        assert!(verify_merkle_path(hash, &path, 2, &root, 2, &leaf));
+        assert!(!verify_merkle_path(hash, &path[..1], 2, &root, 2, &leaf));
+        // Index bits above the depth are never climbed
+        assert!(!verify_merkle_path(hash, &path, 2, &root, 2 + 4, &leaf));
+
+        // A padded path authenticates the leaf under a root of the
+        // prover's choosing, one level above the declared tree
+        let padded = [path[0], path[1], [3; 32]];
+        let padded_root = hash.hash_node(&root, &[3; 32]);
+        assert!(verify_merkle_path(hash, &padded, 3, &padded_root, 2, &leaf));
+        assert!(!verify_merkle_path(hash, &padded, 2, &padded_root, 2, &leaf));
+
+        // FRI trees lose a folding factor of depth per layer
+        let header = ProofHeader::parse(&proof_with_layers(0)).unwrap();
+        let config = VerifierConfig::MURKL;
+        let depths: Vec<_> = (0..config.required_fri_layers()).map(|l| header.fri_layer_depth(l)).collect();
+        assert_eq!(depths, [Some(12), Some(10), Some(8)]);
+        assert_eq!(header.fri_layer_depth(6), Some(0));
+        assert_eq!(header.fri_layer_depth(7), None);
+    }
+
+    #[test]
+    fn test_short_fri_layers_rejected() {
+        let config = VerifierConfig::MURKL;
+        for layers in 0..config.required_fri_layers() as u8 {
+            let data = proof_with_layers(layers);
+            let proof = parse_proof(&data).unwrap();
+            assert!(check_fri_shape(&proof, &config).is_err(), "{layers} layers accepted");
+        }
+        let data = proof_with_layers(config.required_fri_layers() as u8);
+        let proof = parse_proof(&data).unwrap();
+        assert!(check_fri_shape(&proof, &config).is_ok());
+    }
+
+    #[test]
+    fn test_fri_final_point_on_folded_domain() {
+        let config = VerifierConfig::MURKL;
+        let log_n = config.log_domain_size();
+        let folds = config.required_fri_layers();
+        let log_final = log_n - folds as u32 * config.log_folding_factor;
+
+        // Final coset: 4^folds·G + <4^folds·g>
+        let scale = 1u32 << (folds as u32 * config.log_folding_factor);
+        let g = CirclePoint::GENERATOR.repeated_double(31 - log_n);
+        let final_xs: Vec<M31> = (0..1u32 << log_final)
+            .map(|m| CirclePoint::GENERATOR.add(g.mul(m)).mul(scale).x)
+            .collect();
+
+        let group = 1usize << (log_n - log_final);
+        let mut seen = Vec::new();
+        for j in 0..1usize << log_final {
+            let x = fri_final_domain_x(j * group, log_n, folds, config.log_folding_factor);
+            assert!(final_xs.contains(&x), "position {j} off the final coset");
+            // Every query folding into position j lands on the same point
+            for r in [1, group / 2, group - 1] {
+                assert_eq!(fri_final_domain_x(j * group + r, log_n, folds, config.log_folding_factor), x);
+            }
+            assert!(!seen.contains(&x), "position {j} collides");
+            seen.push(x);
+        }
+    }
+
+    #[test]
+    fn test_fri_fold_halves_degree() {
+        let config = VerifierConfig::MURKL;
+        let (log_n, log_ff) = (config.log_domain_size(), config.log_folding_factor);
+        assert_eq!(QUARTER_TURN, CirclePoint::GENERATOR.repeated_double(29));
+
+        let alpha = QM31::new(M31::new(7), M31::new(11), M31::new(13), M31::new(17));
+        let coeffs = [
+            QM31::new(M31::new(3), M31::new(1), M31::new(4), M31::new(1)),
+            QM31::new(M31::new(5), M31::new(9), M31::new(2), M31::new(6)),
+            QM31::from_m31(M31::new(8)),
+            QM31::new(M31::new(0), M31::new(2), M31::new(7), M31::new(1)),
+            QM31::from_m31(M31::new(3)),
+        ];
+        let fold_group = |layer: usize, group: usize, degree: usize| {
+            let siblings: [QM31; 4] = core::array::from_fn(|k| {
+                let x = fri_layer_point(4 * group + k, layer, log_n, log_ff).x;
+                evaluate_final_poly(coeffs[..degree].iter().copied(), &QM31::from_m31(x))
+            });
+            verify_fri_fold(&siblings, &alpha, fri_layer_point(4 * group, layer, log_n, log_ff))
+        };
+
+        for layer in [0, 2] {
+            // Below degree 4 in x every group folds to the same constant
+            let constant = fold_group(layer, 0, 4);
+            for group in [1, 5, 100] {
+                assert!(fold_group(layer, group, 4).eq(&constant), "layer {layer} group {group}");
+            }
+            // A degree-4 term survives the fold
+            assert!(!fold_group(layer, 1, 5).eq(&fold_group(layer, 5, 5)));
+        }
+
+        // The folded value sits at the next layer's point for the group
+        let point = fri_layer_point(4 * 9, 1, log_n, log_ff);
+        assert_eq!(point.repeated_double(log_ff), fri_layer_point(9, 2, log_n, log_ff));
+        assert_eq!(point.add(QUARTER_TURN), fri_layer_point(4 * 9 + 2, 1, log_n, log_ff));
+    }
+
+    #[test]
+    fn test_non_constant_final_poly() {
+        let config = VerifierConfig::MURKL;
+        let coeffs = [
+            QM31::new(M31::new(3), M31::new(1), M31::new(4), M31::new(1)),
+            QM31::new(M31::new(5), M31::new(9), M31::new(2), M31::new(6)),
+            QM31::new(M31::new(5), M31::new(3), M31::new(5), M31::new(8)),
+        ];
+        let folds = config.required_fri_layers();
+        let index = 0b10_1101_0011_0110;
+        let x = fri_final_domain_x(index, config.log_domain_size(), folds, config.log_folding_factor);
+
+        let x = QM31::from_m31(x);
+        let expected = coeffs[0].add(coeffs[1].mul(x)).add(coeffs[2].mul(x.square()));
+        assert!(evaluate_final_poly(coeffs.iter().copied(), &x).eq(&expected));
+
+        // The folded position index is not a domain point
+        let folded_index = QM31::from_m31(M31::new((index >> (2 * folds)) as u32));
+        assert!(!evaluate_final_poly(coeffs.iter().copied(), &folded_index).eq(&expected));
+    }
+
+    #[test]
+    fn test_final_layer_evaluations() {
+        let config = VerifierConfig::MURKL;
+        let layers = config.required_fri_layers() + 1;
+        let log_final = config.log_domain_size() - layers as u32 * config.log_folding_factor;
+        let shift = layers as u32 * config.log_folding_factor;
+
+        // Degree 3 polynomial over the 64-point last layer
+        let coeffs = [
+            QM31::new(M31::new(3), M31::new(1), M31::new(4), M31::new(1)),
+            QM31::ONE,
+            QM31::ZERO,
+            QM31::from_m31(M31::new(9)),
+        ];
+        let mut values: Vec<QM31> = (0..1usize << log_final)
+            .map(|j| {
+                let x = fri_final_domain_x(j << shift, config.log_domain_size(), layers, config.log_folding_factor);
+                evaluate_final_poly(coeffs.iter().copied(), &QM31::from_m31(x))
+            })
+            .collect();
+        let with_values = |values: &[QM31]| {
+            let mut data = proof_with_layers(layers as u8);
+            let at = ProofHeader::LEN + 64 + 1 + 16 * (2 * config.trace_columns + 1) + 1 + 32 * layers;
+            let mut encoded = (FINAL_EVALUATIONS_FLAG | values.len() as u16).to_le_bytes().to_vec();
+            for v in values {
+                for limb in [v.a, v.b, v.c, v.d] {
+                    encoded.extend_from_slice(&limb.to_le_bytes());
+                }
+            }
+            data.splice(at..at + 2 + 16, encoded);
+            data
+        };
+
+        let data = with_values(&values);
+        let proof = parse_proof(&data).unwrap();
+        assert!(proof.fri_final_poly.is_empty());
+        assert_eq!(proof.fri_final_evaluations.len(), 1 << log_final);
+        assert!(check_fri_shape(&proof, &config).is_ok());
+
+        // A value off the polynomial breaks the degree bound
+        values[40] = values[40].add(QM31::ONE);
+        let data = with_values(&values);
+        let err = check_fri_shape(&parse_proof(&data).unwrap(), &config).unwrap_err();
+        assert_eq!(err, VerifierError::FinalPolyDegreeTooHigh.into());
+
+        // The count must cover the whole last layer
+        let data = with_values(&values[..32]);
+        let err = check_fri_shape(&parse_proof(&data).unwrap(), &config).unwrap_err();
+        assert_eq!(err, VerifierError::InvalidProofFormat.into());
+
+        let data = with_values(&vec![QM31::ZERO; MAX_FINAL_LAYER_EVALS + 1]);
+        assert_eq!(parse_proof(&data).unwrap_err(), VerifierError::FinalPolyDegreeTooHigh.into());
+    }
+
+    #[test]
+    fn test_query_budget_split() {
+        // 1.4M units at ~180k per query: stops while one more plus the reserve still fits
+        let mut budget = QueryBudget::new(Some(1_400_000));
+        let mut remaining = 1_400_000u64;
+        let mut verified = 0;
+        loop {
+            remaining -= if verified == 2 { 200_000 } else { 180_000 };
+            verified += 1;
+            if !budget.next_fits(Some(remaining)) {
+                break;
+            }
+        }
+        assert_eq!(verified, 7);
+        assert!(remaining >= STAGED_QUERY_CU_RESERVE);
+        assert!(remaining < 200_000 + STAGED_QUERY_CU_RESERVE);
+
+        // Off-chain there is no meter, so the requested count decides
+        let mut unmetered = QueryBudget::new(None);
+        assert!((0..MAX_QUERIES).all(|_| unmetered.next_fits(None)));
+    }
+
+    #[test]
+    fn test_cu_meter_laps() {
+        let mut meter = CuMeter::at(Some(1_400_000));
+        assert_eq!(meter.lap_at(Some(1_390_000)), 10_000);
+        assert_eq!(meter.lap_at(Some(1_250_000)), 140_000);
+        // A lost reading charges nothing and stops the laps after it
+        assert_eq!(meter.lap_at(None), 0);
+        assert_eq!(meter.lap_at(Some(1_000_000)), 0);
+
+        // Off-chain, or without measure-cu, there is nothing to read or emit
+        let mut meter = CuMeter::start();
+        assert_eq!(meter.lap(), 0);
+        assert!(meter.last.is_none());
+    }
+
+    #[test]
+    fn test_max_queries_fit_proof_size() {
+        let config = VerifierConfig::MURKL;
+        let depth = config.log_domain_size() as usize;
+        // Every path at full depth, an upper bound for the FRI layers
+        let path = 1 + 32 * depth;
+        let fri = config.required_fri_layers() * (64 + path);
+        let query = 4 + 4 * config.trace_columns + 2 * path + 32 + fri;
+        let oods = 16 * (2 * config.trace_columns + 1);
+        let header = ProofHeader::LEN + 2 * 32 + 1 + oods + 1 + 32 * MAX_FRI_LAYERS + 2 + 16 * MAX_FINAL_LAYER_EVALS;
+        assert!(header + MAX_QUERIES * query <= MAX_PROOF_SIZE);
+    }
+
+    #[test]
+    fn test_finalize_buffer_checks_before_writing() {
+        let owner = Pubkey::new_unique();
+        let proof = proof_with_layers(3);
+        let mut buffer = vec![0u8; HEADER_SIZE + proof.len()];
+        buffer[OFFSET_OWNER..OFFSET_OWNER + 32].copy_from_slice(owner.as_ref());
+        buffer[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].copy_from_slice(&(proof.len() as u32).to_le_bytes());
+        buffer[OFFSET_EXPECTED_KECCAK..OFFSET_EXPECTED_KECCAK + 32].copy_from_slice(&keccak_hash(&proof));
+        let last = (proof.len() - 1) / UPLOAD_CHUNK_ALIGN * UPLOAD_CHUNK_ALIGN;
+        write_chunk(&mut buffer, 0, &proof[..last]).unwrap();
+        let inputs = BatchEntry { commitment: [1; 32], nullifier: [2; 32], merkle_root: [3; 32], recipient: [4; 32] };
+
+        let err = finalize_buffer(&mut buffer, &owner, &inputs, &VerifierConfig::MURKL, 9).unwrap_err();
+        assert_eq!(err, VerifierError::IncompleteProof.into());
+        write_chunk(&mut buffer, last as u32, &proof[last..]).unwrap();
+
+        let stranger = Pubkey::new_unique();
+        let err = finalize_buffer(&mut buffer, &stranger, &inputs, &VerifierConfig::MURKL, 9).unwrap_err();
+        assert_eq!(err, VerifierError::Unauthorized.into());
+
+        // A proof that fails verification leaves the header untouched
+        let before = buffer.clone();
+        assert!(finalize_buffer(&mut buffer, &owner, &inputs, &VerifierConfig::MURKL, 9).is_err());
+        assert_eq!(buffer, before);
+        assert_eq!(read_finalized(&buffer).unwrap(), None);
+        assert_eq!(uploaded_proof(&buffer).unwrap(), &proof[..]);
+
+        buffer[OFFSET_FINALIZED] = 1;
+        let err = finalize_buffer(&mut buffer, &owner, &inputs, &VerifierConfig::MURKL, 9).unwrap_err();
+        assert_eq!(err, VerifierError::BufferAlreadyFinalized.into());
+
+        // What integrators read back once a buffer is finalized
+        buffer[OFFSET_COMMITMENT..OFFSET_COMMITMENT + 32].copy_from_slice(&inputs.commitment);
+        buffer[OFFSET_NULLIFIER..OFFSET_NULLIFIER + 32].copy_from_slice(&inputs.nullifier);
+        buffer[OFFSET_MERKLE_ROOT..OFFSET_MERKLE_ROOT + 32].copy_from_slice(&inputs.merkle_root);
+        buffer[OFFSET_RECIPIENT..OFFSET_RECIPIENT + 32].copy_from_slice(&inputs.recipient);
+        buffer[OFFSET_FINALIZED_SLOT..OFFSET_FINALIZED_SLOT + 8].copy_from_slice(&9u64.to_le_bytes());
+        let finalized = read_finalized(&buffer).unwrap().unwrap();
+        assert_eq!(finalized, FinalizedProof { owner, inputs, finalized_slot: 9 });
+        assert!(read_finalized(&buffer[..HEADER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_upload_bitmap_tracks_chunks_out_of_order() {
+        let proof: Vec<u8> = (0..200u8).collect();
+        let mut buffer = vec![0u8; HEADER_SIZE + proof.len()];
+        buffer[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].copy_from_slice(&(proof.len() as u32).to_le_bytes());
+        buffer[OFFSET_EXPECTED_KECCAK..OFFSET_EXPECTED_KECCAK + 32].copy_from_slice(&keccak_hash(&proof));
+        let received = |buffer: &[u8]| -> Vec<bool> {
+            (0..upload_chunk_count(proof.len())).map(|i| chunk_received(upload_bitmap(buffer).unwrap(), i)).collect()
+        };
+
+        // The tail lands first and sets the high-water mark on its own
+        write_chunk(&mut buffer, 128, &proof[128..]).unwrap();
+        assert_eq!(u32::from_le_bytes(buffer[OFFSET_SIZE..OFFSET_SIZE + 4].try_into().unwrap()), 200);
+        assert_eq!(received(&buffer), [false, false, true, true]);
+        assert_eq!(first_missing_range(upload_bitmap(&buffer).unwrap(), proof.len()), Some(0..128));
+        assert_eq!(uploaded_data(&buffer).unwrap_err(), VerifierError::IncompleteProof.into());
+
+        for (offset, len) in [(1, 64), (0, 65), (64, 100), (0, 201)] {
+            let err = write_chunk(&mut buffer, offset, &vec![0; len]).unwrap_err();
+            let expected = if len > 200 { VerifierError::ProofTooLarge } else { VerifierError::UnalignedUpload };
+            assert_eq!(err, expected.into());
+        }
+
+        write_chunk(&mut buffer, 64, &proof[64..128]).unwrap();
+        assert_eq!(first_missing_range(upload_bitmap(&buffer).unwrap(), proof.len()), Some(0..64));
+        // Every chunk in, but not the bytes the buffer was opened for
+        write_chunk(&mut buffer, 0, &[0; 64]).unwrap();
+        assert_eq!(received(&buffer), [true; 4]);
+        assert_eq!(first_missing_range(upload_bitmap(&buffer).unwrap(), proof.len()), None);
+        assert_eq!(uploaded_data(&buffer).unwrap_err(), VerifierError::ProofHashMismatch.into());
+
+        write_chunk(&mut buffer, 0, &proof[..64]).unwrap();
+        assert_eq!(uploaded_data(&buffer).unwrap(), &proof[..]);
+        // A missing tail ends at the proof, not the chunk
+        assert_eq!(first_missing_range(&[0b0111], proof.len()), Some(192..200));
+    }
+
+    #[test]
+    fn test_close_buffer_refunds_owner() {
+        let (buffer_key, owner_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut buffer_lamports, mut owner_lamports) = (5_000, 1_000);
+        let mut data = vec![1u8; HEADER_SIZE];
+        let mut owner_data = [];
+        let buffer = AccountInfo::new(&buffer_key, false, true, &mut buffer_lamports, &mut data, &ID, false, 0);
+        let owner = AccountInfo::new(&owner_key, true, true, &mut owner_lamports, &mut owner_data, &ID, false, 0);
+
+        close_buffer(&buffer, &owner).unwrap();
+        assert_eq!((buffer.lamports(), owner.lamports()), (0, 6_000));
+        assert!(buffer.try_borrow_data().unwrap().iter().all(|&b| b == 0));
+
+        **owner.lamports.borrow_mut() = u64::MAX;
+        **buffer.lamports.borrow_mut() = 1;
+        assert_eq!(close_buffer(&buffer, &owner).unwrap_err(), VerifierError::LamportOverflow.into());
+    }
+
+    #[test]
+    fn test_reap_waits_for_expiry_and_splits_rent() {
+        let (buffer_key, owner_key, reaper_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut buffer_lamports, mut owner_lamports, mut reaper_lamports) = (100_000, 0, 0);
+        let mut data = vec![1u8; HEADER_SIZE];
+        data[OFFSET_OWNER..OFFSET_OWNER + 32].copy_from_slice(owner_key.as_ref());
+        data[OFFSET_CREATED_AT..OFFSET_CREATED_AT + 8].copy_from_slice(&1_000u64.to_le_bytes());
+        data[OFFSET_EXPIRY_SLOTS..OFFSET_EXPIRY_SLOTS + 8].copy_from_slice(&50u64.to_le_bytes());
+        assert_eq!(buffer_expires_at(&data).unwrap(), 1_050);
+        let (mut owner_data, mut reaper_data) = ([], []);
+        let buffer = AccountInfo::new(&buffer_key, false, true, &mut buffer_lamports, &mut data, &ID, false, 0);
+        let owner = AccountInfo::new(&owner_key, false, true, &mut owner_lamports, &mut owner_data, &ID, false, 0);
+        let reaper = AccountInfo::new(&reaper_key, true, true, &mut reaper_lamports, &mut reaper_data, &ID, false, 0);
+
+        let err = reap_buffer(&buffer, &owner, &reaper, 1_049).unwrap_err();
+        assert_eq!(err, VerifierError::BufferNotExpired.into());
+        // The rent only goes back to the owner in the header
+        let err = reap_buffer(&buffer, &reaper, &reaper, 1_050).unwrap_err();
+        assert_eq!(err, VerifierError::Unauthorized.into());
+        assert_eq!(buffer.lamports(), 100_000);
+
+        assert_eq!(reap_buffer(&buffer, &owner, &reaper, 1_050).unwrap(), 5_000);
+        assert_eq!((buffer.lamports(), owner.lamports(), reaper.lamports()), (0, 95_000, 5_000));
+        assert!(buffer.try_borrow_data().unwrap().iter().all(|&b| b == 0));
+        assert_eq!(reap_reward(u64::MAX), u64::MAX / 20);
+    }
+
+    #[test]
+    fn test_finalize_committed_buffer_checks_inputs() {
+        let owner = Pubkey::new_unique();
+        let proof = proof_with_layers(3);
+        let inputs = [[1; 32], [2; 32], [3; 32], [4; 32], [5; 32]];
+        let data = [public_inputs_block(&inputs), proof.clone()].concat();
+        let mut buffer = vec![0u8; HEADER_SIZE + data.len()];
+        buffer[OFFSET_OWNER..OFFSET_OWNER + 32].copy_from_slice(owner.as_ref());
+        buffer[OFFSET_EXPECTED_SIZE..OFFSET_EXPECTED_SIZE + 4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        buffer[OFFSET_EXPECTED_KECCAK..OFFSET_EXPECTED_KECCAK + 32].copy_from_slice(&keccak_hash(&data));
+        write_chunk(&mut buffer, 0, &data).unwrap();
+
+        assert_eq!(split_public_inputs(&data).unwrap(), (&inputs[..], &proof[..]));
+        assert_eq!(uploaded_proof(&buffer).unwrap(), &proof[..]);
+        // Without a block the whole region is the proof
+        assert_eq!(split_public_inputs(&proof).unwrap(), (&[][..], &proof[..]));
+        let short = public_inputs_block(&inputs[..3]);
+        assert_eq!(split_public_inputs(&short).unwrap_err(), VerifierError::InvalidPublicInputs.into());
+        assert_eq!(split_public_inputs(&data[..100]).unwrap_err(), VerifierError::InvalidPublicInputs.into());
+
+        let mut hash = public_inputs_hash(&inputs);
+        assert_ne!(hash, public_inputs_hash(&inputs[..4]));
+        hash[0] ^= 1;
+        let err = finalize_committed_buffer(&mut buffer, &owner, &hash, &VerifierConfig::MURKL, 9).unwrap_err();
+        assert_eq!(err, VerifierError::PublicInputsMismatch.into());
+
+        // The hash matches but the proof does not verify: nothing is written
+        hash[0] ^= 1;
+        let before = buffer.clone();
+        assert!(finalize_committed_buffer(&mut buffer, &owner, &hash, &VerifierConfig::MURKL, 9).is_err());
+        assert_eq!(buffer, before);
+        assert_eq!(
+            verify_stark_proof_with_inputs(&proof, &inputs[..3]).unwrap_err(),
+            VerifierError::InvalidPublicInputs.into()
+        );
+    }
+
+    #[test]
+    fn test_batch_error_codes() {
+        let anchor: Error = VerifierError::InvalidBatch.into();
+        assert_eq!(error_code(&anchor), 6000 + VerifierError::InvalidBatch as u32);
+        let program: Error = ProgramError::Custom(7).into();
+        assert_eq!(error_code(&program), 7);
+    }
+
+    /// Errors the parser itself may return: malformed bytes are
+    /// `InvalidProofFormat`, and the header fields and final-layer length
+    /// that have errors of their own keep them
+    fn is_parse_error(err: &Error) -> bool {
+        [
+            VerifierError::InvalidProofFormat,
+            VerifierError::UnsupportedProofVersion,
+            VerifierError::UnsupportedHashKind,
+            VerifierError::FinalPolyDegreeTooHigh,
+        ]
+        .into_iter()
+        .any(|code| *err == code.into())
+    }
+
+    /// Offset of the final layer's length in [`canonical_wire`]
+    fn final_len_offset() -> usize {
+        ProofHeader::LEN + 32 + 32 + 1 + 16 + 16 + 1 + 32 * VerifierConfig::MURKL.required_fri_layers()
+    }
+
+    mod parse_props {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn canonical() -> Vec<u8> {
+            canonical_wire().encode()
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(512))]
+
+            #[test]
+            fn test_random_bytes_are_rejected(data in proptest::collection::vec(any::<u8>(), 0..2048)) {
+                if let Err(err) = check_proof_encoding(&data) {
+                    prop_assert!(is_parse_error(&err), "{err}");
+                }
+                prop_assert!(verify_stark_proof(&data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).is_err());
+            }
+
+            /// Behind the MURKL parameters a random body still has to parse
+            #[test]
+            fn test_random_body_is_rejected(
+                hash_kind in 0..=HashKind::PoseidonBn254 as u8,
+                num_queries in any::<u8>(),
+                body in proptest::collection::vec(any::<u8>(), 0..4096),
+            ) {
+                let config = VerifierConfig::MURKL;
+                let mut data = PROOF_MAGIC.to_vec();
+                data.extend_from_slice(&[
+                    PROOF_VERSION,
+                    hash_kind,
+                    config.log_trace_size as u8,
+                    config.log_blowup as u8,
+                    config.log_folding_factor as u8,
+                    num_queries,
+                ]);
+                data.extend(body);
+                if let Err(err) = check_proof_encoding(&data) {
+                    prop_assert!(is_parse_error(&err), "{err}");
+                }
+                prop_assert!(verify_stark_proof(&data, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).is_err());
+            }
+
+            #[test]
+            fn test_truncated_proofs_are_invalid(cut in any::<prop::sample::Index>()) {
+                let proof = canonical();
+                let data = &proof[..cut.index(proof.len())];
+                let err = check_proof_encoding(data).unwrap_err();
+                prop_assert_eq!(err, VerifierError::InvalidProofFormat.into());
+            }
+
+            /// Any byte past the ones with errors of their own corrupts the
+            /// proof into another well-formed one or an invalid one, and
+            /// verification rejects it either way
+            #[test]
+            fn test_corrupted_proofs_are_invalid(
+                at in any::<prop::sample::Index>(),
+                mask in 1..=u8::MAX,
+                layer_count in any::<u8>(),
+            ) {
+                let mut proof = canonical();
+                let offset = at.index(proof.len());
+                let final_len = final_len_offset()..final_len_offset() + 2;
+                prop_assume!(offset > 5 && !final_len.contains(&offset));
+                proof[offset] ^= mask;
+                if offset == ProofHeader::LEN + 32 + 32 + 1 + 16 + 16 {
+                    proof[offset] = layer_count;
+                }
+                match check_proof_encoding(&proof) {
+                    Ok(()) => {}
+                    Err(err) => prop_assert!(
+                        err == VerifierError::InvalidProofFormat.into()
+                            || err == VerifierError::FinalPolyDegreeTooHigh.into(),
+                        "{err}"
+                    ),
+                }
+                prop_assert!(verify_stark_proof(&proof, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).is_err());
+            }
+
+            /// Random last-layer values of the right count reach the
+            /// interpolation check
+            #[test]
+            fn test_random_final_evaluations_are_rejected(
+                layers in 4..=MAX_FRI_LAYERS,
+                seed in any::<[u32; 4]>(),
+            ) {
+                use murkl_proof_format::FinalLayer;
+
+                // As many values as the last layer has points
+                let count = 1 << (VerifierConfig::MURKL.log_domain_size() as usize - 2 * layers);
+                let values = (0..count as u32).map(|i| seed.map(|limb| limb.wrapping_mul(i + 1) % P)).collect();
+
+                let mut wire = canonical_wire();
+                wire.hash_kind = HashKind::Keccak as u8;
+                wire.fri_layer_commitments = vec![[9; 32]; layers];
+                for query in &mut wire.queries {
+                    query.fri.resize(layers, query.fri[0].clone());
+                }
+                wire.final_layer = FinalLayer::Evaluations(values);
+                let proof = wire.encode();
+                prop_assert!(check_proof_encoding(&proof).is_ok());
+                prop_assert!(verify_stark_proof(&proof, &[0; 32], &[0; 32], &[0; 32], &[0; 32]).is_err());
+            }
+        }
+    }
+}
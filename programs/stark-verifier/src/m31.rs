@@ -5,6 +5,8 @@
 //!
 //! NO SHORTCUTS. Proper field arithmetic with exact equality checks.
 
+use core::ops::{Add, Mul};
+
 /// The Mersenne-31 prime: 2^31 - 1
 pub const P: u32 = 0x7FFFFFFF;
 
@@ -159,16 +161,6 @@ impl QM31 {
         self.a == other.a && self.b == other.b && self.c == other.c && self.d == other.d
     }
 
-    /// Addition in QM31 (component-wise)
-    pub fn add(self, other: Self) -> Self {
-        Self {
-            a: self.a.add(other.a),
-            b: self.b.add(other.b),
-            c: self.c.add(other.c),
-            d: self.d.add(other.d),
-        }
-    }
-
     /// Subtraction in QM31 (component-wise)
     pub fn sub(self, other: Self) -> Self {
         Self {
@@ -179,6 +171,11 @@ impl QM31 {
         }
     }
 
+    /// Product with a base field element (component-wise)
+    pub fn mul_m31(self, x: M31) -> Self {
+        Self { a: self.a.mul(x), b: self.b.mul(x), c: self.c.mul(x), d: self.d.mul(x) }
+    }
+
     /// Negation in QM31
     pub fn neg(self) -> Self {
         Self {
@@ -189,51 +186,6 @@ impl QM31 {
         }
     }
 
-    /// Multiplication in QM31
-    /// 
-    /// Using: i² = -1, u² = 2 + i
-    /// 
-    /// Let x = (a + bi) + (c + di)u and y = (e + fi) + (g + hi)u
-    /// Then xy = (a+bi)(e+fi) + [(a+bi)(g+hi) + (c+di)(e+fi)]u + (c+di)(g+hi)u²
-    /// 
-    /// With u² = 2 + i:
-    /// (c+di)(g+hi)u² = (c+di)(g+hi)(2+i) = (cg-dh + 2cg-2dh + chi + dgi)(more terms)
-    pub fn mul(self, other: Self) -> Self {
-        // Complex multiplication helper: (a+bi)(c+di) = (ac-bd) + (ad+bc)i
-        let mul_cm31 = |a: M31, b: M31, c: M31, d: M31| -> (M31, M31) {
-            let real = a.mul(c).sub(b.mul(d));
-            let imag = a.mul(d).add(b.mul(c));
-            (real, imag)
-        };
-
-        // x = x0 + x1*u where x0 = a+bi, x1 = c+di
-        // y = y0 + y1*u where y0 = e+fi, y1 = g+hi
-        let (e, f) = (other.a, other.b);
-        let (g, h) = (other.c, other.d);
-
-        // x0*y0
-        let (r0, i0) = mul_cm31(self.a, self.b, e, f);
-        
-        // x0*y1 + x1*y0
-        let (r1a, i1a) = mul_cm31(self.a, self.b, g, h);
-        let (r1b, i1b) = mul_cm31(self.c, self.d, e, f);
-        let (r1, i1) = (r1a.add(r1b), i1a.add(i1b));
-        
-        // x1*y1 * u² = x1*y1 * (2+i)
-        let (cg_dh, ch_dg) = mul_cm31(self.c, self.d, g, h);
-        // (cg-dh + (ch+dg)i)(2+i) = 2(cg-dh) - (ch+dg) + (2(ch+dg) + (cg-dh))i
-        let r2_real = cg_dh.mul(M31::new(2)).sub(ch_dg);
-        let r2_imag = ch_dg.mul(M31::new(2)).add(cg_dh);
-
-        // Result: (r0 + r2_real) + (i0 + r2_imag)i + r1*u + i1*iu
-        Self {
-            a: r0.add(r2_real),
-            b: i0.add(r2_imag),
-            c: r1,
-            d: i1,
-        }
-    }
-
     /// Square in QM31
     pub fn square(self) -> Self {
         self.mul(self)
@@ -325,6 +277,158 @@ impl QM31 {
     }
 }
 
+impl Add for QM31 {
+    type Output = Self;
+
+    /// Addition in QM31 (component-wise)
+    fn add(self, other: Self) -> Self {
+        Self {
+            a: self.a.add(other.a),
+            b: self.b.add(other.b),
+            c: self.c.add(other.c),
+            d: self.d.add(other.d),
+        }
+    }
+}
+
+impl Mul for QM31 {
+    type Output = Self;
+
+    /// Multiplication in QM31
+    /// 
+    /// Using: i² = -1, u² = 2 + i
+    /// 
+    /// Let x = (a + bi) + (c + di)u and y = (e + fi) + (g + hi)u
+    /// Then xy = (a+bi)(e+fi) + [(a+bi)(g+hi) + (c+di)(e+fi)]u + (c+di)(g+hi)u²
+    /// 
+    /// With u² = 2 + i:
+    /// (c+di)(g+hi)u² = (c+di)(g+hi)(2+i) = (cg-dh + 2cg-2dh + chi + dgi)(more terms)
+    fn mul(self, other: Self) -> Self {
+        // Complex multiplication helper: (a+bi)(c+di) = (ac-bd) + (ad+bc)i
+        let mul_cm31 = |a: M31, b: M31, c: M31, d: M31| -> (M31, M31) {
+            let real = a.mul(c).sub(b.mul(d));
+            let imag = a.mul(d).add(b.mul(c));
+            (real, imag)
+        };
+
+        // x = x0 + x1*u where x0 = a+bi, x1 = c+di
+        // y = y0 + y1*u where y0 = e+fi, y1 = g+hi
+        let (e, f) = (other.a, other.b);
+        let (g, h) = (other.c, other.d);
+
+        // x0*y0
+        let (r0, i0) = mul_cm31(self.a, self.b, e, f);
+        
+        // x0*y1 + x1*y0
+        let (r1a, i1a) = mul_cm31(self.a, self.b, g, h);
+        let (r1b, i1b) = mul_cm31(self.c, self.d, e, f);
+        let (r1, i1) = (r1a.add(r1b), i1a.add(i1b));
+        
+        // x1*y1 * u² = x1*y1 * (2+i)
+        let (cg_dh, ch_dg) = mul_cm31(self.c, self.d, g, h);
+        // (cg-dh + (ch+dg)i)(2+i) = 2(cg-dh) - (ch+dg) + (2(ch+dg) + (cg-dh))i
+        let r2_real = cg_dh.mul(M31::new(2)).sub(ch_dg);
+        let r2_imag = ch_dg.mul(M31::new(2)).add(cg_dh);
+
+        // Result: (r0 + r2_real) + (i0 + r2_imag)i + r1*u + i1*iu
+        Self {
+            a: r0.add(r2_real),
+            b: i0.add(r2_imag),
+            c: r1,
+            d: i1,
+        }
+    }
+}
+
+/// Point on the circle x² + y² = 1 over M31
+///
+/// The points form a cyclic group of order 2^31 under
+/// (x1, y1) + (x2, y2) = (x1x2 - y1y2, x1y2 + y1x2).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CirclePoint {
+    pub x: M31,
+    pub y: M31,
+}
+
+impl CirclePoint {
+    pub const IDENTITY: Self = Self { x: M31::ONE, y: M31::ZERO };
+
+    /// Generator of the full group: (2, sqrt(-3))
+    pub const GENERATOR: Self = Self { x: M31(2), y: M31(1268011823) };
+
+    /// 2·(x, y) = (2x² - 1, 2xy)
+    pub fn double(self) -> Self {
+        let x_sq = self.x.square();
+        let xy = self.x.mul(self.y);
+        Self { x: x_sq.add(x_sq).sub(M31::ONE), y: xy.add(xy) }
+    }
+
+    pub fn repeated_double(mut self, n: u32) -> Self {
+        for _ in 0..n {
+            self = self.double();
+        }
+        self
+    }
+}
+
+impl Add for CirclePoint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x.mul(other.x).sub(self.y.mul(other.y)),
+            y: self.x.mul(other.y).add(self.y.mul(other.x)),
+        }
+    }
+}
+
+impl Mul<u32> for CirclePoint {
+    type Output = Self;
+
+    /// Double-and-add scalar multiplication
+    fn mul(self, mut scalar: u32) -> Self {
+        let mut result = Self::IDENTITY;
+        let mut base = self;
+        while scalar > 0 {
+            if scalar & 1 == 1 {
+                result = result.add(base);
+            }
+            base = base.double();
+            scalar >>= 1;
+        }
+        result
+    }
+}
+
+/// Point on the circle over QM31, where the OODS point is drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecureCirclePoint {
+    pub x: QM31,
+    pub y: QM31,
+}
+
+impl SecureCirclePoint {
+    /// Stereographic image of t: ((1 - t²) / (1 + t²), 2t / (1 + t²))
+    ///
+    /// `None` for t = ±i, which has no image, and for t in M31, whose image
+    /// lies on the base circle with the trace and evaluation domains. Any
+    /// other t lands off the base circle, where no domain's vanishing
+    /// polynomial is zero.
+    pub fn from_t(t: QM31) -> Option<Self> {
+        let t_sq = t.square();
+        let denom = QM31::ONE.add(t_sq);
+        if t.eq(&QM31::from_m31(t.a)) || denom.eq(&QM31::ZERO) {
+            return None;
+        }
+        let denom_inv = denom.inv();
+        Some(Self { x: QM31::ONE.sub(t_sq).mul(denom_inv), y: t.add(t).mul(denom_inv) })
+    }
+
+    pub fn is_on_circle(&self) -> bool {
+        self.x.square().add(self.y.square()).eq(&QM31::ONE)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -408,4 +512,25 @@ mod tests {
         let expected3 = a.mul(a).mul(a);
         assert!(a_cubed.eq(&expected3));
     }
+
+    #[test]
+    fn test_circle_generator_order() {
+        let g = CirclePoint::GENERATOR;
+        // 2^30·G is the unique point of order 2
+        assert_eq!(g.repeated_double(30), CirclePoint { x: M31::new(P - 1), y: M31::ZERO });
+        assert_eq!(g.repeated_double(31), CirclePoint::IDENTITY);
+        assert_eq!(g * 5, g.double().double() + g);
+    }
+
+    #[test]
+    fn test_secure_point_off_base_circle() {
+        // i² = -1: no point for t = ±i
+        let i = QM31::new(M31::ZERO, M31::ONE, M31::ZERO, M31::ZERO);
+        assert_eq!(SecureCirclePoint::from_t(i), None);
+        assert_eq!(SecureCirclePoint::from_t(i.neg()), None);
+        assert_eq!(SecureCirclePoint::from_t(QM31::from_m31(M31::new(5))), None);
+
+        let t = QM31::new(M31::new(123), M31::new(456), M31::new(789), M31::new(10));
+        assert!(SecureCirclePoint::from_t(t).unwrap().is_on_circle());
+    }
 }
@@ -0,0 +1,175 @@
+//! Poseidon2 over M31, for proofs whose header selects `HashKind::Poseidon2`
+//!
+//! A copy of murkl-prover's `poseidon2` module on this crate's field types:
+//! width 16, x⁵, 4 + 4 full rounds around 14 partial rounds, round
+//! constants derived from keccak("murkl_poseidon2_m31_v1" || round || lane).
+//! Both crates pin the same known-answer digests, so a change to either
+//! copy fails a test. Keccak is a syscall here and Poseidon2 is not, so
+//! these proofs cost more compute to verify.
+
+use crate::m31::M31;
+
+pub(crate) const WIDTH: usize = 16;
+const RATE: usize = 8;
+pub(crate) const DIGEST_LIMBS: usize = 8;
+pub(crate) const HALF_FULL_ROUNDS: usize = 4;
+
+pub(crate) const EXTERNAL_CONSTANTS: [[u32; WIDTH]; 2 * HALF_FULL_ROUNDS] = [
+    [0x48357780, 0x2d90eca5, 0x351ba84b, 0x67749a14, 0x538b70f7, 0x57100378, 0x4a0e28dc, 0x66a552cd, 0x6290ae0b, 0x7259dee2, 0x01e5fd22, 0x1ef6e27d, 0x736a94ee, 0x676cf894, 0x6d87b2b4, 0x3f09b85e],
+    [0x54cee4c8, 0x3e72b93a, 0x34fdc009, 0x08554960, 0x5c2c2bc3, 0x56cce763, 0x3a66b0be, 0x1cbec630, 0x39809484, 0x016bb309, 0x6c304980, 0x5fa0a649, 0x1300fca6, 0x48b64fab, 0x5e77a9b7, 0x5b1adad3],
+    [0x2852aabb, 0x3505c0ec, 0x18560a4a, 0x680d22bc, 0x2269173d, 0x1f98842f, 0x21fc7940, 0x224e84d3, 0x565e3b9e, 0x3e581e3b, 0x7042061f, 0x10b22b98, 0x325ede5e, 0x5fad211b, 0x04969584, 0x16f3d6d7],
+    [0x1cf1162f, 0x1986594f, 0x2e339e3e, 0x20a5aa05, 0x6e4d45f7, 0x47df1f98, 0x5a6c8424, 0x02f21ca2, 0x70eebf09, 0x75185a75, 0x39877189, 0x752e2eef, 0x4ea2f017, 0x042edc1e, 0x49239f5a, 0x22b985ce],
+    [0x49e75b6f, 0x513e85eb, 0x115131fb, 0x18f85251, 0x4929afc0, 0x54aa8181, 0x50c1a4f0, 0x14f4897e, 0x239f5809, 0x60b2c64e, 0x52bf1c6d, 0x5be274af, 0x0a98e339, 0x6ed818ec, 0x0a81f10e, 0x4cdc5759],
+    [0x1c5ac513, 0x2a632f17, 0x4e51063c, 0x61f67e0f, 0x44745d88, 0x77376f1a, 0x01c3f73a, 0x5eaac6f1, 0x4a657871, 0x43245ca9, 0x41ee7bff, 0x52986bdc, 0x1e1c79bb, 0x75e914e9, 0x5c4d7a54, 0x29961695],
+    [0x08979458, 0x231130eb, 0x1093f96d, 0x306abfc0, 0x2d8e989d, 0x7db91a68, 0x78e32804, 0x0f55ae5b, 0x5be8ec9c, 0x6ddfa833, 0x2fc4a209, 0x21fc3848, 0x1a972564, 0x2be22dfb, 0x4b6eee1d, 0x5cc5c7cc],
+    [0x692e851f, 0x010582ab, 0x6b24f0d7, 0x4836753d, 0x67bf2e2c, 0x414866f8, 0x113dc336, 0x7ae52e0d, 0x545adf4b, 0x564fc9ea, 0x1eaed908, 0x06cd7def, 0x2419ae9a, 0x65e3d377, 0x0ab8685a, 0x0361852d],
+];
+
+pub(crate) const INTERNAL_CONSTANTS: [u32; 14] = [
+    0x42f77e81, 0x13fab0a1, 0x2168b256, 0x7ece749f, 0x02798ef9, 0x05cff3d2, 0x2f5f27f5,
+    0x4137fac6, 0x1eb134f9, 0x58484993, 0x4bd0307b, 0x4fbccbf4, 0x172edc1b, 0x5eba83ae,
+];
+
+/// V in the internal matrix 1 + diag(V); V₀ = -2
+pub(crate) const INTERNAL_DIAG: [u32; WIDTH] = [
+    0x7ffffffd, 1, 1 << 1, 1 << 2, 1 << 3, 1 << 4, 1 << 5, 1 << 6,
+    1 << 7, 1 << 8, 1 << 10, 1 << 12, 1 << 13, 1 << 14, 1 << 15, 1 << 16,
+];
+
+fn permute(state: &mut [M31; WIDTH]) {
+    external_linear(state);
+    for constants in &EXTERNAL_CONSTANTS[..HALF_FULL_ROUNDS] {
+        full_round(state, constants);
+    }
+    for &constant in &INTERNAL_CONSTANTS {
+        state[0] = sbox(state[0].add(M31::new(constant)));
+        internal_linear(state);
+    }
+    for constants in &EXTERNAL_CONSTANTS[HALF_FULL_ROUNDS..] {
+        full_round(state, constants);
+    }
+}
+
+/// Sponge hash of the concatenated `inputs`: 3-byte chunks, rate 8, input
+/// length in the capacity
+pub fn hash(inputs: &[&[u8]]) -> [u8; 32] {
+    let len: usize = inputs.iter().map(|input| input.len()).sum();
+    let mut state = [M31::ZERO; WIDTH];
+    state[RATE] = M31::new(len as u32);
+
+    let (mut lane, mut word, mut shift) = (0, 0u32, 0);
+    for &byte in inputs.iter().flat_map(|input| input.iter()) {
+        word |= (byte as u32) << shift;
+        shift += 8;
+        if shift == 24 {
+            state[lane] = M31::new(word);
+            (word, shift) = (0, 0);
+            lane += 1;
+            if lane == RATE {
+                permute(&mut state);
+                lane = 0;
+            }
+        }
+    }
+    if shift > 0 {
+        state[lane] = M31::new(word);
+        lane += 1;
+    }
+    if lane > 0 || len == 0 {
+        permute(&mut state);
+    }
+    digest(&state)
+}
+
+/// Merkle node hash: truncated permutation of `left || right`
+pub fn compress(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut state = [M31::ZERO; WIDTH];
+    let limbs = left.chunks_exact(4).chain(right.chunks_exact(4));
+    for (lane, limb) in state.iter_mut().zip(limbs) {
+        *lane = M31::from_le_bytes([limb[0], limb[1], limb[2], limb[3]]);
+    }
+    permute(&mut state);
+    digest(&state)
+}
+
+fn digest(state: &[M31; WIDTH]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (bytes, limb) in out.chunks_exact_mut(4).zip(&state[..DIGEST_LIMBS]) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+#[inline]
+fn sbox(x: M31) -> M31 {
+    x.square().square().mul(x)
+}
+
+fn full_round(state: &mut [M31; WIDTH], constants: &[u32; WIDTH]) {
+    for (lane, &constant) in state.iter_mut().zip(constants) {
+        *lane = sbox(lane.add(M31::new(constant)));
+    }
+    external_linear(state);
+}
+
+/// circ(2, 3, 1, 1) per block of four, plus the sum of each position across blocks
+fn external_linear(state: &mut [M31; WIDTH]) {
+    for block in state.chunks_exact_mut(4) {
+        let t01 = block[0].add(block[1]);
+        let t23 = block[2].add(block[3]);
+        let t0123 = t01.add(t23);
+        let t01123 = t0123.add(block[1]);
+        let t01233 = t0123.add(block[3]);
+        block[3] = t01233.add(block[0].add(block[0]));
+        block[1] = t01123.add(block[2].add(block[2]));
+        block[0] = t01123.add(t01);
+        block[2] = t01233.add(t23);
+    }
+    let mut sums = [M31::ZERO; 4];
+    for (i, &lane) in state.iter().enumerate() {
+        sums[i % 4] = sums[i % 4].add(lane);
+    }
+    for (i, lane) in state.iter_mut().enumerate() {
+        *lane = lane.add(sums[i % 4]);
+    }
+}
+
+fn internal_linear(state: &mut [M31; WIDTH]) {
+    let sum = state.iter().fold(M31::ZERO, |acc, &lane| acc.add(lane));
+    for (lane, &v) in state.iter_mut().zip(&INTERNAL_DIAG) {
+        *lane = sum.add(M31::new(v).mul(*lane));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::keccak;
+
+    #[test]
+    fn test_round_constants_match_derivation() {
+        let derived = |round: u32, j: u32| {
+            let h = keccak::hashv(&[b"murkl_poseidon2_m31_v1", &round.to_le_bytes(), &j.to_le_bytes()]).0;
+            M31::from_le_bytes([h[0], h[1], h[2], h[3]]).0
+        };
+        for (r, constants) in EXTERNAL_CONSTANTS.iter().enumerate() {
+            for (j, &c) in constants.iter().enumerate() {
+                assert_eq!(c, derived(r as u32, j as u32));
+            }
+        }
+        for (r, &c) in INTERNAL_CONSTANTS.iter().enumerate() {
+            assert_eq!(c, derived((2 * HALF_FULL_ROUNDS + r) as u32, 0));
+        }
+    }
+
+    /// Same digests as murkl-prover's `poseidon2::tests::test_known_answer`
+    #[test]
+    fn test_known_answer() {
+        let hex = |d: [u8; 32]| d.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(hash(&[b"murkl"])), "be5f7f60d05bb107e3d6f22d7ff11319b441a740f16ee03ab775071d4da0934f");
+        assert_eq!(
+            hex(compress(&hash(&[b"left"]), &hash(&[b"right"]))),
+            "3ae32e6109340d715bba6149fc931a243257f26a6555cd375ccff92d2343f27d"
+        );
+    }
+}
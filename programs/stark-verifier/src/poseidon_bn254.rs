@@ -0,0 +1,104 @@
+//! Poseidon over BN254, for proofs whose header selects
+//! `HashKind::PoseidonBn254`
+//!
+//! The permutation is the runtime's `sol_poseidon` syscall (circom
+//! parameters, x⁵, little-endian field elements), so a Merkle node costs
+//! about as much as a keccak. Clusters only register the syscall once its
+//! feature is active, and a program that links it fails to deploy
+//! elsewhere, so on-chain it is compiled in with the `poseidon-syscall`
+//! feature only. Off-chain, solana-program runs the same permutation in
+//! Rust. Without the syscall this kind is not accepted (see
+//! `VerifierConfig::MURKL`) and provers fall back to `HashKind::Poseidon2`,
+//! whose permutation this program runs itself; a BN254 permutation in
+//! program code would not fit the compute budget.
+//!
+//! Sponge: the concatenated input is split into 31-byte field elements and
+//! absorbed 11 at a time as `state = P(state, e₁, …, e₁₁)`, starting from
+//! the input length. murkl-prover's `poseidon_bn254` module builds the
+//! same digests; both pin the same known-answer values.
+
+/// Elements absorbed per permutation next to the running state; the
+/// syscall takes at most 12 inputs
+const RATE: usize = 11;
+/// Bytes per absorbed element, so each one is below the modulus
+const CHUNK_BYTES: usize = 31;
+/// Digest when an input is not a field element (or the syscall is missing).
+/// Above the modulus itself, so it never equals an honest digest and a
+/// Merkle path through it cannot reach the root.
+const OUT_OF_FIELD: [u8; 32] = [0xff; 32];
+
+/// Sponge hash of the concatenated `inputs`
+pub fn hash(inputs: &[&[u8]]) -> [u8; 32] {
+    let data = inputs.concat();
+    let mut state = [0u8; 32];
+    state[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+    if data.is_empty() {
+        return permute(&[&state]);
+    }
+
+    for block in data.chunks(RATE * CHUNK_BYTES) {
+        let mut vals: Vec<&[u8]> = Vec::with_capacity(RATE + 1);
+        vals.push(&state);
+        vals.extend(block.chunks(CHUNK_BYTES));
+        state = permute(&vals);
+    }
+    state
+}
+
+/// Merkle node hash: `P(left, right)`
+pub fn compress(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    permute(&[left, right])
+}
+
+#[cfg(any(feature = "poseidon-syscall", not(target_os = "solana")))]
+fn permute(vals: &[&[u8]]) -> [u8; 32] {
+    use anchor_lang::solana_program::poseidon::{hashv, Endianness, Parameters};
+
+    hashv(Parameters::Bn254X5, Endianness::LittleEndian, vals)
+        .map(|digest| digest.to_bytes())
+        .unwrap_or(OUT_OF_FIELD)
+}
+
+#[cfg(not(any(feature = "poseidon-syscall", not(target_os = "solana"))))]
+fn permute(_vals: &[&[u8]]) -> [u8; 32] {
+    OUT_OF_FIELD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_separates_lengths_and_blocks() {
+        assert_ne!(hash(&[]), hash(&[&[0]]));
+        assert_ne!(hash(&[&[0]]), hash(&[&[0, 0]]));
+        assert_eq!(hash(&[b"murkl", b"_trace"]), hash(&[b"murkl_trace"]));
+
+        // Around one full block of 11 elements
+        let data = [7u8; 2 * RATE * CHUNK_BYTES];
+        let digests: Vec<[u8; 32]> = (RATE * CHUNK_BYTES - 2..=RATE * CHUNK_BYTES + 2)
+            .map(|n| hash(&[&data[..n]]))
+            .collect();
+        for (i, a) in digests.iter().enumerate() {
+            assert!(digests[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_non_field_node_never_matches() {
+        let leaf = hash(&[b"leaf"]);
+        assert_eq!(compress(&leaf, &[0xff; 32]), OUT_OF_FIELD);
+        assert_ne!(compress(&leaf, &leaf), OUT_OF_FIELD);
+    }
+
+    /// Same digests as murkl-prover's `poseidon_bn254::tests::test_known_answer`
+    #[test]
+    fn test_known_answer() {
+        let hex = |d: [u8; 32]| d.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(hash(&[b"murkl"])), "b3cf9d54232c52116ce2a0dc8d241e335e9ee3ddf6ae254bae60d3bed4ca5118");
+        assert_eq!(
+            hex(compress(&hash(&[b"left"]), &hash(&[b"right"]))),
+            "97bc322443c730d969c2f6e6aa3a4e332be98191186fc92a5f4ade2683c86b2d"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Anchor CPI wrappers for the Murkl stark-verifier
+//!
+//! Proofs are too large for one transaction, so the user uploads theirs to
+//! a stark-verifier proof buffer first. An integrating program then either
+//! verifies that buffer against its own public inputs inside its
+//! instruction ([`verify_from_buffer`] / [`require_verified`]), or takes a
+//! buffer the user already finalized and reads what it vouches for
+//! ([`finalized_proof`]), which costs no verification compute.
+//!
+//! ```ignore
+//! let cpi = CpiContext::new(
+//!     ctx.accounts.verifier_program.to_account_info(),
+//!     VerifyFromBuffer {
+//!         proof_buffer: ctx.accounts.proof_buffer.to_account_info(),
+//!         verifier_config: ctx.accounts.verifier_config.to_account_info(),
+//!     },
+//! );
+//! murkl_verifier_cpi::require_verified(cpi, &inputs)?;
+//! ```
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::get_return_data;
+
+pub use stark_verifier::cpi::accounts::{FinalizeAndVerify, VerifyFromBuffer};
+pub use stark_verifier::program::StarkVerifier;
+pub use stark_verifier::{
+    public_inputs_block, public_inputs_hash, FinalizedProof, ProofHeader, VerificationReport, VerificationResult,
+    VerificationStage, ID as STARK_VERIFIER_ID, VERIFIER_CONFIG_SEED,
+};
+
+/// Commitment, nullifier, Merkle root and recipient a proof is checked against
+pub use stark_verifier::BatchEntry as PublicInputs;
+
+/// A proof as structured data, Borsh-serializable for instruction arguments.
+/// `encode()` gives the bytes to upload to a proof buffer.
+pub use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof};
+
+/// Verify the proof in `ctx`'s buffer against `inputs`
+///
+/// A rejected proof comes back as `success: false` with its
+/// [`VerificationReport`] rather than as an error; use
+/// [`require_verified`] to fail on it instead.
+pub fn verify_from_buffer<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, VerifyFromBuffer<'info>>,
+    inputs: &PublicInputs,
+) -> Result<VerificationResult> {
+    stark_verifier::cpi::verify_from_buffer(
+        ctx,
+        inputs.commitment,
+        inputs.nullifier,
+        inputs.merkle_root,
+        inputs.recipient,
+    )?;
+    verification_result()
+}
+
+/// [`verify_from_buffer`], failing with the verifier's error code when the
+/// proof is rejected
+pub fn require_verified<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, VerifyFromBuffer<'info>>,
+    inputs: &PublicInputs,
+) -> Result<()> {
+    verify_from_buffer(ctx, inputs)?.require_verified()
+}
+
+/// Verify and finalize the buffer for its owner, who must sign
+///
+/// A finalized buffer can then be consumed by other programs, murkl claims
+/// included, without verifying again. With `auto_close` the buffer is closed
+/// instead, its rent going to the (writable) owner; `Ok` is then all there
+/// is to go on.
+pub fn finalize_and_verify<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, FinalizeAndVerify<'info>>,
+    inputs: &PublicInputs,
+    auto_close: bool,
+) -> Result<()> {
+    stark_verifier::cpi::finalize_and_verify(
+        ctx,
+        inputs.commitment,
+        inputs.nullifier,
+        inputs.merkle_root,
+        inputs.recipient,
+        auto_close,
+    )
+}
+
+/// [`finalize_and_verify`] for a buffer whose data region starts with a
+/// [`public_inputs_block`]; `public_inputs_hash` commits to all its inputs
+pub fn finalize_and_verify_committed<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, FinalizeAndVerify<'info>>,
+    public_inputs_hash: [u8; 32],
+) -> Result<()> {
+    stark_verifier::cpi::finalize_and_verify_committed(ctx, public_inputs_hash)
+}
+
+/// What a proof buffer passed to the calling program vouches for; `None`
+/// until it is finalized
+///
+/// Compare the inputs with the ones the instruction acts on, and the owner
+/// with the signer if another signer must not reuse the buffer.
+pub fn finalized_proof(buffer: &AccountInfo) -> Result<Option<FinalizedProof>> {
+    require_keys_eq!(*buffer.owner, STARK_VERIFIER_ID, VerifierCpiError::NotVerifierBuffer);
+    stark_verifier::read_finalized(&buffer.try_borrow_data()?)
+}
+
+/// The [`VerificationResult`] stark-verifier left as return data
+///
+/// Unlike Anchor's `Return::get`, this checks which program set the data
+/// and fails instead of panicking.
+fn verification_result() -> Result<VerificationResult> {
+    let (program, data) = get_return_data().ok_or(VerifierCpiError::MissingReturnData)?;
+    require_keys_eq!(program, STARK_VERIFIER_ID, VerifierCpiError::MissingReturnData);
+    VerificationResult::try_from_slice(&data).map_err(|_| error!(VerifierCpiError::InvalidReturnData))
+}
+
+/// Raised in the calling program; numbered from 9000 so they stay clear of
+/// its own errors
+#[error_code(offset = 9000)]
+pub enum VerifierCpiError {
+    #[msg("Account is not a stark-verifier proof buffer")]
+    NotVerifierBuffer,
+    #[msg("stark-verifier returned no verification result")]
+    MissingReturnData,
+    #[msg("Verification result could not be decoded")]
+    InvalidReturnData,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finalized_proof_requires_a_verifier_buffer() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0u8; 449];
+        data[40] = 1; // finalized
+        data[41..73].copy_from_slice(&[7; 32]);
+
+        let stranger = Pubkey::new_unique();
+        let buffer = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &stranger, false, 0);
+        assert!(finalized_proof(&buffer).is_err());
+
+        let mut lamports = 0;
+        let buffer = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &STARK_VERIFIER_ID, false, 0);
+        let proof = finalized_proof(&buffer).unwrap().unwrap();
+        assert_eq!(proof.inputs.commitment, [7; 32]);
+        assert_eq!(proof.owner, Pubkey::default());
+    }
+}
@@ -33,6 +33,8 @@ async function main() {
   const poolInfo = await poolRes.json() as { merkleRoot: string; leafCount: string };
   console.log(`  Merkle Root: ${poolInfo.merkleRoot}`);
   console.log(`  Leaf Count: ${poolInfo.leafCount}\n`);
+  const leavesRes = await fetch(`${RELAYER_URL}/pool-leaves?pool=${POOL}`);
+  const { leaves } = await leavesRes.json() as { leaves: string };
   
   // Load WASM
   console.log('Loading WASM...');
@@ -54,11 +56,19 @@ async function main() {
     
     try {
       // Generate proof
+      const merklePath = wasm.generate_merkle_path(leaves, leafIndex);
+      if (merklePath === undefined) {
+        console.log(`❌ Leaf ${leafIndex} is not in the pool leaves`);
+        results.push({ leaf: leafIndex, success: false, error: 'leaf not in pool' });
+        continue;
+      }
       const bundle = wasm.generate_proof(
         IDENTIFIER,
         PASSWORD,
         leafIndex,
-        poolInfo.merkleRoot
+        poolInfo.merkleRoot,
+        merklePath,
+        Buffer.from(recipientATA.toBytes()).toString('hex')
       ) as {
         commitment: string;
         nullifier: string;
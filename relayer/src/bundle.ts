@@ -0,0 +1,376 @@
+/**
+ * Claim bundling
+ *
+ * After the upload a claim still has to verify the proof and pay out. This
+ * packs both into as few transactions as fit, the way murkl-client's
+ * `bundle::plan_claim` does:
+ * - Proofs of up to 8 queries verify with one finalize_and_verify, usually
+ *   in the same transaction as the claim.
+ * - Larger proofs go through staged verification, several stages to a
+ *   transaction, with the claim alongside finalize_staged_verification.
+ *
+ * Transactions are v0, compiled against a per-pool address lookup table of
+ * the accounts every claim from the pool shares (ClaimLookupTables). Without
+ * one they still work, they just fill up sooner.
+ */
+
+import {
+  AddressLookupTableAccount,
+  AddressLookupTableProgram,
+  ComputeBudgetProgram,
+  Connection,
+  MessageV0,
+  PublicKey,
+  Signer,
+  SystemProgram,
+  TransactionInstruction,
+  VersionedTransaction,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import { RetryLogger, RetryPolicy, sendWithRetry } from './retry';
+
+// Compute estimates, the same as murkl-client's bundle module
+export const MAX_TRANSACTION_CU = 1_400_000;
+/** Parsing, transcript and OODS check; once per verification */
+export const VERIFY_BASE_CU = 60_000;
+/** One query opening; a direct verification fits up to 8 */
+export const QUERY_CU = 160_000;
+/** begin_staged_verification or finalize_staged_verification */
+export const STAGE_CU = 20_000;
+/** The claim together with its token account / shard setup */
+export const CLAIM_CU = 100_000;
+/** Units verify_staged_queries stops short of (STAGED_QUERY_CU_RESERVE) */
+const STAGED_QUERY_CU_RESERVE = 10_000;
+const PACKET_DATA_SIZE = 1232;
+
+// Lookup table account: [4 type][8 deactivation slot][8 last extended slot]
+//                       [1 start index][1 has authority][32 authority]...
+const LOOKUP_TABLE_AUTHORITY_OFFSET = 22;
+
+function discriminator(name: string): Buffer {
+  return crypto.createHash('sha256').update(`global:${name}`).digest().slice(0, 8);
+}
+
+// ============================================================================
+// Verifier instructions
+// ============================================================================
+
+export interface PublicInputs {
+  commitment: Buffer;
+  nullifier: Buffer;
+  merkleRoot: Buffer;
+  recipient: Buffer;
+}
+
+function inputsData(name: string, inputs: PublicInputs): Buffer {
+  return Buffer.concat([discriminator(name), inputs.commitment, inputs.nullifier, inputs.merkleRoot, inputs.recipient]);
+}
+
+/** Profile stark-verifier checks proofs at */
+export function verifierConfigPda(verifierId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('verifier-config')], verifierId)[0];
+}
+
+/** Checkpoint of a staged verification of `buffer` */
+export function verificationStatePda(verifierId: PublicKey, buffer: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('verification'), buffer.toBuffer()], verifierId)[0];
+}
+
+export function finalizeAndVerifyInstruction(
+  verifierId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  inputs: PublicInputs,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: verifierId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: verifierConfigPda(verifierId), isSigner: false, isWritable: false },
+    ],
+    // auto_close = false: the claim reads the buffer after this
+    data: Buffer.concat([inputsData('finalize_and_verify', inputs), Buffer.from([0])]),
+  });
+}
+
+export function beginStagedVerificationInstruction(
+  verifierId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  inputs: PublicInputs,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: verifierId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: false },
+      { pubkey: verificationStatePda(verifierId, buffer), isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: verifierConfigPda(verifierId), isSigner: false, isWritable: false },
+    ],
+    data: inputsData('begin_staged_verification', inputs),
+  });
+}
+
+/** verify_staged_oods, verify_staged_queries and verify_step share accounts */
+function stagedInstruction(
+  verifierId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  data: Buffer,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: verifierId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: false },
+      { pubkey: verificationStatePda(verifierId, buffer), isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: false },
+    ],
+    data,
+  });
+}
+
+export function verifyStagedOodsInstruction(verifierId: PublicKey, buffer: PublicKey, owner: PublicKey) {
+  return stagedInstruction(verifierId, buffer, owner, discriminator('verify_staged_oods'));
+}
+
+export function verifyStagedQueriesInstruction(
+  verifierId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  count: number,
+): TransactionInstruction {
+  return stagedInstruction(verifierId, buffer, owner, Buffer.concat([discriminator('verify_staged_queries'), Buffer.from([count])]));
+}
+
+/** finalize_staged_verification and cancel_staged_verification share accounts */
+function closeStagedInstruction(
+  verifierId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  name: string,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: verifierId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: true },
+      { pubkey: verificationStatePda(verifierId, buffer), isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: true },
+    ],
+    data: discriminator(name),
+  });
+}
+
+export function finalizeStagedVerificationInstruction(verifierId: PublicKey, buffer: PublicKey, owner: PublicKey) {
+  return closeStagedInstruction(verifierId, buffer, owner, 'finalize_staged_verification');
+}
+
+/** Close an abandoned staged verification, refunding its rent */
+export function cancelStagedVerificationInstruction(verifierId: PublicKey, buffer: PublicKey, owner: PublicKey) {
+  return closeStagedInstruction(verifierId, buffer, owner, 'cancel_staged_verification');
+}
+
+// ============================================================================
+// Planning
+// ============================================================================
+
+export interface PlannedTransaction {
+  /** Steps it carries, e.g. `finalize_and_verify + claim` */
+  label: string;
+  /** Without compute budget instructions; sendWithRetry adds those */
+  instructions: TransactionInstruction[];
+  /** Estimated units, to request as the compute unit limit */
+  computeUnits: number;
+  /** Whether the claim is in this transaction */
+  claims: boolean;
+}
+
+export interface ClaimPlan {
+  staged: boolean;
+  transactions: PlannedTransaction[];
+}
+
+export interface PlanOptions {
+  verifierId: PublicKey;
+  buffer: PublicKey;
+  /** Buffer owner; pays for and signs every transaction */
+  owner: PublicKey;
+  inputs: PublicInputs;
+  lookupTables: AddressLookupTableAccount[];
+}
+
+/**
+ * Wire size of a signed v0 transaction of `instructions`, with the compute
+ * budget instructions sendWithRetry adds; Infinity if it won't compile.
+ */
+export function transactionSize(
+  payer: PublicKey,
+  instructions: TransactionInstruction[],
+  lookupTables: AddressLookupTableAccount[],
+): number {
+  try {
+    const message = MessageV0.compile({
+      payerKey: payer,
+      instructions: [
+        ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_TRANSACTION_CU }),
+        ComputeBudgetProgram.setComputeUnitPrice({ microLamports: 1 }),
+        ...instructions,
+      ],
+      recentBlockhash: PublicKey.default.toBase58(),
+      addressLookupTableAccounts: lookupTables,
+    });
+    return new VersionedTransaction(message).serialize().length;
+  } catch {
+    return Infinity;
+  }
+}
+
+/**
+ * Lay out the verification of the proof in `opts.buffer` and the `claim`
+ * instructions after it. The route follows the query count in the proof
+ * header; each step joins the current transaction while it stays within
+ * the packet size and compute limit.
+ */
+export function planClaim(proof: Buffer, claim: TransactionInstruction[], opts: PlanOptions): ClaimPlan {
+  const { verifierId, buffer, owner, inputs, lookupTables } = opts;
+  if (proof.length < 10) throw new Error('Proof too short for its header');
+  const numQueries = proof[9];
+
+  const transactions: PlannedTransaction[] = [];
+  let current: PlannedTransaction = { label: '', instructions: [], computeUnits: 0, claims: false };
+  const flush = () => {
+    if (current.instructions.length > 0) transactions.push(current);
+    current = { label: '', instructions: [], computeUnits: 0, claims: false };
+  };
+  const fits = (step: TransactionInstruction[], units: number) =>
+    current.computeUnits + units <= MAX_TRANSACTION_CU &&
+    transactionSize(owner, [...current.instructions, ...step], lookupTables) <= PACKET_DATA_SIZE;
+  const push = (name: string, step: TransactionInstruction[], units: number) => {
+    if (!fits(step, units)) {
+      flush();
+      if (!fits(step, units)) throw new Error(`${name} does not fit a transaction`);
+    }
+    current.label = current.label ? `${current.label} + ${name}` : name;
+    current.instructions.push(...step);
+    current.computeUnits += units;
+  };
+
+  const directUnits = VERIFY_BASE_CU + numQueries * QUERY_CU;
+  const staged = directUnits > MAX_TRANSACTION_CU;
+  if (!staged) {
+    push('finalize_and_verify', [finalizeAndVerifyInstruction(verifierId, buffer, owner, inputs)], directUnits);
+  } else {
+    push('begin_staged_verification', [beginStagedVerificationInstruction(verifierId, buffer, owner, inputs)], STAGE_CU);
+    push('verify_staged_oods', [verifyStagedOodsInstruction(verifierId, buffer, owner)], VERIFY_BASE_CU);
+    for (let left = numQueries; left > 0; ) {
+      const room = Math.floor((MAX_TRANSACTION_CU - current.computeUnits - STAGED_QUERY_CU_RESERVE) / QUERY_CU);
+      const count = Math.min(left, Math.max(room, 0));
+      if (count === 0) {
+        flush();
+        continue;
+      }
+      push('verify_staged_queries', [verifyStagedQueriesInstruction(verifierId, buffer, owner, count)], count * QUERY_CU);
+      left -= count;
+    }
+    push('finalize_staged_verification', [finalizeStagedVerificationInstruction(verifierId, buffer, owner)], STAGE_CU);
+  }
+  push('claim', claim, CLAIM_CU);
+  current.claims = true;
+  flush();
+
+  return { staged, transactions };
+}
+
+// ============================================================================
+// Lookup tables
+// ============================================================================
+
+/**
+ * One address lookup table per pool, owned by the relayer and holding the
+ * accounts its claims share (programs, pool PDAs, mint, relayer token
+ * account). Tables are found again after a restart by authority.
+ *
+ * A new table only resolves from the slot after it is extended, so the
+ * claim that creates it goes without; later claims of the pool use it.
+ */
+export class ClaimLookupTables {
+  private tables = new Map<string, AddressLookupTableAccount>();
+  private creating = new Set<string>();
+  private loaded: Promise<void> | null = null;
+
+  constructor(
+    private connection: Connection,
+    private authority: Signer,
+    private policy: RetryPolicy,
+    private log: RetryLogger,
+  ) {}
+
+  /** The pool's table if it holds every address and is active, else null */
+  async get(pool: PublicKey, addresses: PublicKey[], requestId: string): Promise<AddressLookupTableAccount | null> {
+    this.loaded ??= this.loadOwned().catch((e) => {
+      this.loaded = null;
+      this.log('warn', 'Lookup table scan failed', { requestId, error: String(e) });
+    });
+    await this.loaded;
+
+    const table = this.tables.get(pool.toBase58());
+    if (table && table.isActive() && addresses.every((a) => table.state.addresses.some((t) => t.equals(a)))) {
+      return table;
+    }
+    if (!this.creating.has(pool.toBase58())) {
+      void this.create(pool, addresses, requestId);
+    }
+    return null;
+  }
+
+  /** Index the relayer's existing tables by the pool each one serves */
+  private async loadOwned(): Promise<void> {
+    const accounts = await this.connection.getProgramAccounts(AddressLookupTableProgram.programId, {
+      commitment: 'confirmed',
+      filters: [{ memcmp: { offset: LOOKUP_TABLE_AUTHORITY_OFFSET, bytes: this.authority.publicKey.toBase58() } }],
+    });
+    for (const { pubkey, account } of accounts) {
+      const table = new AddressLookupTableAccount({
+        key: pubkey,
+        state: AddressLookupTableAccount.deserialize(account.data),
+      });
+      // create() puts the pool first
+      const pool = table.state.addresses[0];
+      if (pool && table.isActive()) this.tables.set(pool.toBase58(), table);
+    }
+  }
+
+  private async create(pool: PublicKey, addresses: PublicKey[], requestId: string): Promise<void> {
+    const key = pool.toBase58();
+    this.creating.add(key);
+    try {
+      const recentSlot = await this.connection.getSlot('finalized');
+      const [createIx, address] = AddressLookupTableProgram.createLookupTable({
+        authority: this.authority.publicKey,
+        payer: this.authority.publicKey,
+        recentSlot,
+      });
+      const entries = [pool, ...addresses.filter((a) => !a.equals(pool))];
+      const extendIx = AddressLookupTableProgram.extendLookupTable({
+        lookupTable: address,
+        authority: this.authority.publicKey,
+        payer: this.authority.publicKey,
+        addresses: entries,
+      });
+      await sendWithRetry(this.connection, [createIx, extendIx], [this.authority], this.policy, {
+        label: 'Create lookup table',
+        requestId,
+        alreadyLanded: async () => (await this.connection.getAccountInfo(address, 'confirmed')) !== null,
+        log: this.log,
+      });
+      const state = (await this.connection.getAddressLookupTable(address, { commitment: 'confirmed' })).value;
+      if (state) this.tables.set(key, state);
+      this.log('info', 'Claim lookup table created', { requestId, pool: key.slice(0, 8), table: address.toBase58() });
+    } catch (e) {
+      this.log('warn', 'Lookup table creation failed', { requestId, pool: key.slice(0, 8), error: String(e) });
+    } finally {
+      this.creating.delete(key);
+    }
+  }
+}
@@ -0,0 +1,134 @@
+/**
+ * Deposit commitments and nullifiers, as murkl-prover's `hash` module
+ * computes them: Poseidon2 digests over M31 (width 16, x^5, 4 + 4 full
+ * rounds around 14 partial rounds), eight limbs as u32 LE.
+ *
+ * A copy of the permutation for the relayer and the scripts, which do not
+ * load the WASM module; the web app calls the module instead.
+ */
+
+import { keccak256 } from 'js-sha3';
+
+const P = 0x7fffffffn;
+const WIDTH = 16;
+const COMMITMENT_TAG = 1n;
+const NULLIFIER_TAG = 2n;
+
+const EXTERNAL_CONSTANTS: number[][] = [
+  [0x48357780, 0x2d90eca5, 0x351ba84b, 0x67749a14, 0x538b70f7, 0x57100378, 0x4a0e28dc, 0x66a552cd, 0x6290ae0b, 0x7259dee2, 0x01e5fd22, 0x1ef6e27d, 0x736a94ee, 0x676cf894, 0x6d87b2b4, 0x3f09b85e],
+  [0x54cee4c8, 0x3e72b93a, 0x34fdc009, 0x08554960, 0x5c2c2bc3, 0x56cce763, 0x3a66b0be, 0x1cbec630, 0x39809484, 0x016bb309, 0x6c304980, 0x5fa0a649, 0x1300fca6, 0x48b64fab, 0x5e77a9b7, 0x5b1adad3],
+  [0x2852aabb, 0x3505c0ec, 0x18560a4a, 0x680d22bc, 0x2269173d, 0x1f98842f, 0x21fc7940, 0x224e84d3, 0x565e3b9e, 0x3e581e3b, 0x7042061f, 0x10b22b98, 0x325ede5e, 0x5fad211b, 0x04969584, 0x16f3d6d7],
+  [0x1cf1162f, 0x1986594f, 0x2e339e3e, 0x20a5aa05, 0x6e4d45f7, 0x47df1f98, 0x5a6c8424, 0x02f21ca2, 0x70eebf09, 0x75185a75, 0x39877189, 0x752e2eef, 0x4ea2f017, 0x042edc1e, 0x49239f5a, 0x22b985ce],
+  [0x49e75b6f, 0x513e85eb, 0x115131fb, 0x18f85251, 0x4929afc0, 0x54aa8181, 0x50c1a4f0, 0x14f4897e, 0x239f5809, 0x60b2c64e, 0x52bf1c6d, 0x5be274af, 0x0a98e339, 0x6ed818ec, 0x0a81f10e, 0x4cdc5759],
+  [0x1c5ac513, 0x2a632f17, 0x4e51063c, 0x61f67e0f, 0x44745d88, 0x77376f1a, 0x01c3f73a, 0x5eaac6f1, 0x4a657871, 0x43245ca9, 0x41ee7bff, 0x52986bdc, 0x1e1c79bb, 0x75e914e9, 0x5c4d7a54, 0x29961695],
+  [0x08979458, 0x231130eb, 0x1093f96d, 0x306abfc0, 0x2d8e989d, 0x7db91a68, 0x78e32804, 0x0f55ae5b, 0x5be8ec9c, 0x6ddfa833, 0x2fc4a209, 0x21fc3848, 0x1a972564, 0x2be22dfb, 0x4b6eee1d, 0x5cc5c7cc],
+  [0x692e851f, 0x010582ab, 0x6b24f0d7, 0x4836753d, 0x67bf2e2c, 0x414866f8, 0x113dc336, 0x7ae52e0d, 0x545adf4b, 0x564fc9ea, 0x1eaed908, 0x06cd7def, 0x2419ae9a, 0x65e3d377, 0x0ab8685a, 0x0361852d],
+];
+
+const INTERNAL_CONSTANTS: number[] = [
+  0x42f77e81, 0x13fab0a1, 0x2168b256, 0x7ece749f, 0x02798ef9, 0x05cff3d2, 0x2f5f27f5,
+  0x4137fac6, 0x1eb134f9, 0x58484993, 0x4bd0307b, 0x4fbccbf4, 0x172edc1b, 0x5eba83ae,
+];
+
+/** V in the internal matrix 1 + diag(V); V0 = -2 */
+const INTERNAL_DIAG: number[] = [
+  0x7ffffffd, 1, 1 << 1, 1 << 2, 1 << 3, 1 << 4, 1 << 5, 1 << 6,
+  1 << 7, 1 << 8, 1 << 10, 1 << 12, 1 << 13, 1 << 14, 1 << 15, 1 << 16,
+];
+
+const sbox = (x: bigint): bigint => {
+  const x2 = (x * x) % P;
+  return (((x2 * x2) % P) * x) % P;
+};
+
+function externalLinear(state: bigint[]): void {
+  for (let b = 0; b < WIDTH; b += 4) {
+    const [a0, a1, a2, a3] = state.slice(b, b + 4);
+    const t01 = a0 + a1;
+    const t23 = a2 + a3;
+    const t0123 = t01 + t23;
+    const t01123 = t0123 + a1;
+    const t01233 = t0123 + a3;
+    state[b + 3] = (t01233 + 2n * a0) % P;
+    state[b + 1] = (t01123 + 2n * a2) % P;
+    state[b] = (t01123 + t01) % P;
+    state[b + 2] = (t01233 + t23) % P;
+  }
+  const sums = [0n, 0n, 0n, 0n];
+  state.forEach((lane, i) => { sums[i % 4] += lane; });
+  state.forEach((lane, i) => { state[i] = (lane + sums[i % 4]) % P; });
+}
+
+function internalLinear(state: bigint[]): void {
+  const sum = state.reduce((acc, lane) => acc + lane, 0n);
+  state.forEach((lane, i) => { state[i] = (sum + BigInt(INTERNAL_DIAG[i]) * lane) % P; });
+}
+
+function permute(state: bigint[]): void {
+  externalLinear(state);
+  const fullRound = (constants: number[]) => {
+    constants.forEach((c, i) => { state[i] = sbox((state[i] + BigInt(c)) % P); });
+    externalLinear(state);
+  };
+  EXTERNAL_CONSTANTS.slice(0, 4).forEach(fullRound);
+  for (const c of INTERNAL_CONSTANTS) {
+    state[0] = sbox((state[0] + BigInt(c)) % P);
+    internalLinear(state);
+  }
+  EXTERNAL_CONSTANTS.slice(4).forEach(fullRound);
+}
+
+/** Digest of the permuted state [tag, a, b, c, 0, ..., 0] */
+function hashTagged(tag: bigint, inputs: [number, number, number]): Buffer {
+  const state = new Array<bigint>(WIDTH).fill(0n);
+  state[0] = tag;
+  inputs.forEach((input, i) => { state[i + 1] = BigInt(input) % P; });
+  permute(state);
+  const out = Buffer.alloc(32);
+  for (let i = 0; i < 8; i++) out.writeUInt32LE(Number(state[i]), 4 * i);
+  return out;
+}
+
+const M31_PRIME = 0x7fffffff;
+
+function keccakM31(domain: string, value: string): number {
+  const hash = Buffer.from(keccak256(Buffer.concat([Buffer.from(domain), Buffer.from(value)])), 'hex');
+  return hash.readUInt32LE(0) % M31_PRIME;
+}
+
+/** `hash_identifier`: keccak("murkl_identifier_v1" || lowercase id) as an M31 */
+export function hashIdentifier(identifier: string): number {
+  return keccakM31('murkl_identifier_v1', identifier.toLowerCase());
+}
+
+/** `hash_password`: keccak("murkl_password_v1" || password) as an M31 */
+export function hashPassword(password: string): number {
+  return keccakM31('murkl_password_v1', password);
+}
+
+/** `pq_commitment`: Poseidon2 digest of [COMMITMENT_TAG, id_hash, secret, 0] */
+export function pqCommitment(idHash: number, secret: number): Buffer {
+  return hashTagged(COMMITMENT_TAG, [idHash, secret, 0]);
+}
+
+/** `pq_epoch_nullifier`: the last four bytes carry the epoch (u32 LE) */
+export function pqEpochNullifier(secret: number, leafIndex: number, epoch: number): Buffer {
+  const nullifier = hashTagged(NULLIFIER_TAG, [leafIndex, secret, epoch]);
+  nullifier.writeUInt32LE(epoch, 28);
+  return nullifier;
+}
+
+/** `pq_nullifier`: the epoch 0 nullifier */
+export function pqNullifier(secret: number, leafIndex: number): Buffer {
+  return pqEpochNullifier(secret, leafIndex, 0);
+}
+
+/** Commitment of an identifier and password, as deposits store it */
+export function computeCommitment(identifier: string, password: string): Buffer {
+  return pqCommitment(hashIdentifier(identifier), hashPassword(password));
+}
+
+/** Nullifier a claim of the deposit at `leafIndex` publishes */
+export function computeNullifier(password: string, leafIndex: number): Buffer {
+  return pqNullifier(hashPassword(password), leafIndex);
+}
@@ -0,0 +1,51 @@
+/**
+ * On-chain error decoding
+ *
+ * Maps a failed instruction's custom error code to the murkl or
+ * stark-verifier error it stands for, with a remediation hint. Both programs
+ * number errors from 6000, so the code is looked up under the program of the
+ * instruction that failed.
+ *
+ * `program-errors.json` is generated from murkl-client
+ * (`cargo run --bin error-table` in client/), whose tests fail when this
+ * copy is stale.
+ */
+
+import { PublicKey } from '@solana/web3.js';
+import table from './program-errors.json';
+
+export interface ProgramErrorInfo {
+  program: string;
+  code: number;
+  name: string;
+  message: string;
+  hint: string;
+}
+
+const errors = new Map<string, ProgramErrorInfo>(table.map((e) => [`${e.program}:${e.code}`, e]));
+
+/**
+ * Decode a transaction error of the form
+ * `{ InstructionError: [index, { Custom: code }] }`, given the program ID of
+ * each instruction in the transaction. Null for anything else.
+ */
+export function describeProgramError(err: unknown, programIds: PublicKey[]): ProgramErrorInfo | null {
+  if (!err || typeof err !== 'object' || !('InstructionError' in err)) return null;
+  const [index, detail] = (err as { InstructionError: [number, unknown] }).InstructionError;
+  if (!detail || typeof detail !== 'object' || !('Custom' in detail)) return null;
+  const program = programIds[index];
+  if (!program) return null;
+  return errors.get(`${program.toBase58()}:${(detail as { Custom: number }).Custom}`) ?? null;
+}
+
+const REJECTED_PREFIX = 'Program log: Proof rejected: ';
+
+/**
+ * The stark-verifier's account of where a proof failed, e.g.
+ * `FriFolding at query 3, FRI layer 1 (error 6013)`, from a failed
+ * transaction's logs. Null if the logs hold no rejection.
+ */
+export function verificationReport(logs: string[] | null | undefined): string | null {
+  const line = logs?.find((l) => l.startsWith(REJECTED_PREFIX));
+  return line ? line.slice(REJECTED_PREFIX.length) : null;
+}
@@ -19,16 +19,19 @@ import cors from 'cors';
 import helmet from 'helmet';
 import rateLimit from 'express-rate-limit';
 import { 
+  ComputeBudgetProgram,
   Connection, 
   Keypair, 
+  MessageV0,
   PublicKey, 
   Transaction,
   TransactionInstruction,
-  sendAndConfirmTransaction,
   LAMPORTS_PER_SOL,
   SystemProgram,
+  VersionedTransaction,
 } from '@solana/web3.js';
 import { 
+  ASSOCIATED_TOKEN_PROGRAM_ID,
   TOKEN_PROGRAM_ID, 
   getAssociatedTokenAddress,
   createAssociatedTokenAccountInstruction,
@@ -39,6 +42,31 @@ import * as crypto from 'crypto';
 import bs58 from 'bs58';
 import { auth, db, getMurklIdentifier, resend, runAuthMigrations } from './auth';
 import { toNodeHandler } from 'better-auth/node';
+import { DeadLetterQueue, SendResult, loadRetryPolicy, sendWithRetry } from './retry';
+import { PoolRouter, parsePoolFees } from './pools';
+import {
+  PROOF_BUFFER_HEADER_SIZE,
+  closeProofBufferInstruction,
+  findOwnedProofBuffers,
+  uploadProofPipelined,
+} from './upload';
+import {
+  ClaimLookupTables,
+  MAX_TRANSACTION_CU,
+  PublicInputs,
+  QUERY_CU,
+  VERIFY_BASE_CU,
+  cancelStagedVerificationInstruction,
+  finalizeAndVerifyInstruction,
+  planClaim,
+  verificationStatePda,
+  verifierConfigPda,
+} from './bundle';
+import { ClaimJob, ClaimJobs } from './jobs';
+import { hashIdentifier, hashPassword, pqCommitment } from './commitment';
+import { describeProgramError, verificationReport } from './errors';
+import { TokenInfoCache } from './tokens';
+import { CLAIM_OK, RelayerMetrics, claimFeeLamports, loadBalanceThresholds, renderStatusPage } from './metrics';
 
 
 // ============================================================================
@@ -51,15 +79,25 @@ interface Config {
   programId: PublicKey;
   maxFeeBps: number;
   chunkSize: number;
+  uploadConcurrency: number;
   corsOrigins: string[];
   rateLimitWindowMs: number;
   rateLimitMaxRequests: number;
   claimRateLimitMaxRequests: number;
   requestTimeoutMs: number;
+  poolFees: Map<string, number>;
+  poolRefreshMs: number;
+  adminToken: string | null;
+  balanceCheckMs: number;
+  /** Compile claims against a per-pool address lookup table */
+  claimLookupTables: boolean;
 }
 
 // STARK Verifier program ID
 const STARK_VERIFIER_ID = new PublicKey('StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw');
+/** stark-verifier's DEFAULT_BUFFER_EXPIRY_SLOTS, about a day */
+const BUFFER_EXPIRY_SLOTS = 216_000n;
+const [VERIFIER_PARAMS_PDA] = PublicKey.findProgramAddressSync([Buffer.from('verifier-params')], STARK_VERIFIER_ID);
 
 function loadConfig(): Config {
   const programId = process.env.PROGRAM_ID || 'muRkDGaY4yCc6rEYWhmJAnQ1abdCbUJNCr4L1Cmd1UF';
@@ -74,6 +112,14 @@ function loadConfig(): Config {
   
   const env = process.env.NODE_ENV || 'development';
 
+  let poolFees: Map<string, number>;
+  try {
+    poolFees = parsePoolFees(process.env.POOL_FEES);
+  } catch (e) {
+    console.error(`❌ ${e instanceof Error ? e.message : e}`);
+    process.exit(1);
+  }
+
   const defaultProdOrigins = ['https://murkl.app', 'https://murkl.dev', 'https://murkl-relayer-production.up.railway.app'];
   const defaultDevOrigins = ['http://localhost:3000', 'http://localhost:3001', 'http://localhost:5173', 'http://localhost:5174', 'http://127.0.0.1:5173'];
 
@@ -92,12 +138,18 @@ function loadConfig(): Config {
     rpcUrl: process.env.RPC_URL || 'http://127.0.0.1:8899',
     programId: new PublicKey(programId),
     maxFeeBps: 100,
-    chunkSize: 900,
+    chunkSize: 896, // a multiple of UPLOAD_CHUNK_ALIGN that fits the packet
+    uploadConcurrency: Math.max(1, parseInt(process.env.UPLOAD_CONCURRENCY || '4', 10) || 1),
     corsOrigins,
     rateLimitWindowMs: 60 * 1000, // 1 minute
     rateLimitMaxRequests: 100,    // 100 requests per minute (general)
     claimRateLimitMaxRequests: 10, // 10 claims per minute per IP
     requestTimeoutMs: 120_000,    // 2 minutes for claim operations
+    poolFees,                     // per-pool max fee overrides (POOL_FEES)
+    poolRefreshMs: parseInt(process.env.POOL_REFRESH_MS || '300000', 10),
+    adminToken: process.env.ADMIN_TOKEN || null, // /admin is disabled without it
+    balanceCheckMs: parseInt(process.env.BALANCE_CHECK_MS || '60000', 10),
+    claimLookupTables: process.env.CLAIM_LOOKUP_TABLES !== '0',
   };
 }
 
@@ -144,7 +196,20 @@ function shouldLog(level: LogLevel): boolean {
   return order[level] >= order[min];
 }
 
-function log(level: LogLevel, message: string, meta?: Record<string, unknown>): void {
+/** LOG_FORMAT=json|pretty; JSON lines by default in production */
+const logFormat: 'json' | 'pretty' = process.env.LOG_FORMAT === 'json' || process.env.LOG_FORMAT === 'pretty'
+  ? process.env.LOG_FORMAT
+  : (process.env.NODE_ENV === 'production' ? 'json' : 'pretty');
+
+/** Context attached to every entry logged through a span's logger */
+interface LogSpan {
+  name: string;
+  [field: string]: unknown;
+}
+
+type Logger = (level: LogLevel, message: string, meta?: Record<string, unknown>) => void;
+
+function log(level: LogLevel, message: string, meta?: Record<string, unknown>, span?: LogSpan): void {
   if (!shouldLog(level)) return;
 
   const timestamp = new Date().toISOString();
@@ -153,17 +218,26 @@ function log(level: LogLevel, message: string, meta?: Record<string, unknown>):
     timestamp,
     level,
     message,
-    ...(safeMeta || {})
+    ...(safeMeta || {}),
+    ...(span ? { span: redactMeta(span) } : {}),
   };
 
-  if (process.env.NODE_ENV === 'production') {
+  if (logFormat === 'json') {
     console.log(JSON.stringify(entry));
   } else {
     const emoji = { debug: '🔍', info: 'ℹ️', warn: '⚠️', error: '❌' }[level];
-    console.log(`${emoji} [${timestamp}] ${message}`, safeMeta || '');
+    const scope = span
+      ? ` ${span.name}{${Object.entries(span).filter(([k]) => k !== 'name').map(([k, v]) => `${k}=${v}`).join(' ')}}`
+      : '';
+    console.log(`${emoji} [${timestamp}]${scope} ${message}`, safeMeta || '');
   }
 }
 
+/** Logger for one unit of work, e.g. a claim keyed by job id, pool and nullifier prefix */
+function withSpan(span: LogSpan): Logger {
+  return (level, message, meta) => log(level, message, meta, span);
+}
+
 // ============================================================================
 // Input Validation
 // ============================================================================
@@ -192,9 +266,10 @@ function isValidBase58(value: unknown): boolean {
   }
 }
 
+// Upper bound only; the per-pool limit is enforced once the pool is resolved.
 function isValidFeeBps(value: unknown): boolean {
   if (typeof value !== 'number') return false;
-  return Number.isInteger(value) && value >= 0 && value <= config.maxFeeBps;
+  return Number.isInteger(value) && value >= 0 && value <= 10_000;
 }
 
 function isValidLeafIndex(value: unknown): boolean {
@@ -202,6 +277,30 @@ function isValidLeafIndex(value: unknown): boolean {
   return Number.isInteger(value) && value >= 0 && value < 2 ** 32;
 }
 
+/**
+ * Canonical proof bundle (murkl-prover `ProofBundle`), as written by
+ * `murkl prove` and returned by the WASM prover. Byte fields are hex.
+ */
+interface ProofBundle {
+  version: number;
+  proof: string;
+  commitment: string;
+  nullifier: string;
+  leaf_index: number;
+  pool?: string;
+}
+
+const PROOF_BUNDLE_VERSION = 1;
+
+function parseProofBundle(value: unknown): ProofBundle | string {
+  if (typeof value !== 'object' || value === null) return 'Invalid proof bundle';
+  const bundle = value as Partial<ProofBundle>;
+  if (bundle.version !== PROOF_BUNDLE_VERSION) {
+    return `Unsupported proof bundle version (expected ${PROOF_BUNDLE_VERSION})`;
+  }
+  return bundle as ProofBundle;
+}
+
 function sanitizeHex(hex: string): Buffer {
   const h = hex.startsWith('0x') ? hex.slice(2) : hex;
   if (h.length % 2 !== 0) throw new Error('Invalid hex (odd length)');
@@ -278,26 +377,63 @@ const connection = new Connection(config.rpcUrl, {
   confirmTransactionInitialTimeout: config.requestTimeoutMs,
 });
 
+const pools = new PoolRouter(connection, config.programId, config.maxFeeBps, config.poolFees, config.poolRefreshMs);
+const tokens = new TokenInfoCache(connection);
+
+const retryPolicy = loadRetryPolicy();
+const deadLetters = new DeadLetterQueue(retryPolicy);
+const metrics = new RelayerMetrics(loadBalanceThresholds());
+const lookupTables = new ClaimLookupTables(connection, relayerKeypair, retryPolicy, log);
+
 // ============================================================================
-// Nonce Tracking (replay protection)
+// Claim Jobs (replay protection + resubmission dedup)
 // ============================================================================
 
-const processedNullifiers = new Set<string>();
-const NULLIFIER_CACHE_MAX = 10000;
+const CLAIM_JOBS_MAX = 10000;
+const BUFFER_SWEEP_MS = 10 * 60 * 1000;
+const BUFFERS_PER_CLOSE_TX = 8;
 
-function trackNullifier(nullifier: string): boolean {
-  if (processedNullifiers.has(nullifier)) {
-    return false; // Already processed
-  }
-  
-  // Evict oldest if at capacity (simple FIFO)
-  if (processedNullifiers.size >= NULLIFIER_CACHE_MAX) {
-    const first = processedNullifiers.values().next().value as string | undefined;
-    if (first) processedNullifiers.delete(first);
+const jobs = new ClaimJobs(CLAIM_JOBS_MAX);
+
+/**
+ * Close proof buffers and refund their rent. The claim has already consumed
+ * (or given up on) the buffer, so failures are only logged; the periodic
+ * sweep picks up anything left behind.
+ */
+async function closeProofBuffers(buffers: PublicKey[], requestId: string): Promise<void> {
+  for (let i = 0; i < buffers.length; i += BUFFERS_PER_CLOSE_TX) {
+    const batch = buffers.slice(i, i + BUFFERS_PER_CLOSE_TX);
+    try {
+      // Abandoned staged verifications go too, refunding their state's rent
+      const states = batch.map((b) => verificationStatePda(STARK_VERIFIER_ID, b));
+      const staged = await connection.getMultipleAccountsInfo(states, 'confirmed');
+      const ixs = [
+        ...batch
+          .filter((_, j) => staged[j] !== null)
+          .map((b) => cancelStagedVerificationInstruction(STARK_VERIFIER_ID, b, relayerKeypair.publicKey)),
+        ...batch.map((b) => closeProofBufferInstruction(STARK_VERIFIER_ID, b, relayerKeypair.publicKey)),
+      ];
+      await sendWithRetry(connection, ixs, [relayerKeypair], retryPolicy, {
+        label: 'Close buffer',
+        requestId,
+        alreadyLanded: async () =>
+          (await connection.getMultipleAccountsInfo(batch, 'confirmed')).every((info) => info === null),
+        log,
+      });
+      log('info', 'Proof buffers closed', { requestId, count: batch.length });
+    } catch (e) {
+      log('warn', 'Proof buffer close failed', { requestId, error: String(e) });
+    }
   }
-  
-  processedNullifiers.add(nullifier);
-  return true;
+}
+
+/** Close relayer-owned buffers that no pending claim is using. */
+async function sweepProofBuffers(): Promise<number> {
+  const owned = await findOwnedProofBuffers(connection, STARK_VERIFIER_ID, relayerKeypair.publicKey);
+  const active = jobs.activeBuffers();
+  const stale = owned.filter((b) => !active.has(b.toBase58()));
+  if (stale.length > 0) await closeProofBuffers(stale, 'sweep');
+  return stale.length;
 }
 
 // ============================================================================
@@ -405,7 +541,7 @@ app.use((req: Request, res: Response, next: NextFunction) => {
   const start = Date.now();
   res.on('finish', () => {
     const duration = Date.now() - start;
-    if (req.path !== '/health') { // Skip health check spam
+    if (req.path !== '/health' && req.path !== '/metrics') { // Skip health check and scrape spam
       log('info', 'Request', {
         method: req.method,
         path: req.path,
@@ -573,9 +709,12 @@ async function verifyDepositTx(params: {
 }): Promise<{ depositAccount: PublicKey }> {
   const { txSignature, pool, leafIndex, amount, commitmentHex } = params;
 
-  // On-chain deposit stores amounts in base units (lamports for SOL/WSOL).
-  // Our API uses human units for UX, so convert for verification.
-  const amountBaseUnits = parseAmountToBaseUnits(amount, 9, 'amount');
+  // On-chain deposit stores amounts in base units. Our API uses human units
+  // for UX, so convert with the pool mint's decimals for verification.
+  const poolEntry = await pools.resolve(pool);
+  if (!poolEntry) throw new Error('Unknown pool');
+  const { decimals } = await tokens.get(poolEntry.tokenMint);
+  const amountBaseUnits = parseAmountToBaseUnits(amount, decimals, 'amount');
   if (amountBaseUnits <= 0n) {
     throw new Error('Invalid amount (base units)');
   }
@@ -649,7 +788,7 @@ async function verifyDepositTx(params: {
     if (ixAmount !== amountBaseUnits) continue;
     if (!ixCommitment.equals(commitmentBuf)) continue;
 
-    // Accounts: [pool, pool_merkle, deposit, vault, depositor, depositor_token, token_program, system_program]
+    // Accounts: [pool, pool_merkle, deposit, vault, depositor, depositor_token, token_program, system_program, pool_stats]
     const ixPool = accounts[0];
     const ixDeposit = accounts[2]; // index 2 after pool_merkle was added
     if (!ixPool?.equals(pool)) continue;
@@ -692,32 +831,16 @@ async function verifyDepositTx(params: {
 // Debug: Verify commitment computation — DEVELOPMENT ONLY
 if (process.env.NODE_ENV !== 'production') {
   app.post('/debug/commitment', (req: Request, res: Response) => {
-    // @ts-ignore - js-sha3 doesn't have types
-    const { keccak256 } = require('js-sha3');
     try {
       const { identifier, password } = req.body;
       if (!identifier || !password) {
         return res.status(400).json({ error: 'identifier and password required' });
       }
       
-      const M31_PRIME = 0x7FFFFFFF;
-      
       const normalizedId = identifier.toLowerCase();
-      const idData = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalizedId)]);
-      const idHash = Buffer.from(keccak256(idData), 'hex');
-      const idM31 = idHash.readUInt32LE(0) % M31_PRIME;
-      
-      const pwData = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-      const pwHash = Buffer.from(keccak256(pwData), 'hex');
-      const secretM31 = pwHash.readUInt32LE(0) % M31_PRIME;
-      
-      const idBuf = Buffer.alloc(4);
-      const secretBuf = Buffer.alloc(4);
-      idBuf.writeUInt32LE(idM31, 0);
-      secretBuf.writeUInt32LE(secretM31, 0);
-      
-      const commitData = Buffer.concat([Buffer.from('murkl_m31_hash_v1'), idBuf, secretBuf]);
-      const commitment = keccak256(commitData);
+      const idM31 = hashIdentifier(identifier);
+      const secretM31 = hashPassword(password);
+      const commitment = pqCommitment(idM31, secretM31).toString('hex');
       
       res.json({
         identifier,
@@ -740,6 +863,7 @@ app.get('/info', async (_req: Request, res: Response) => {
       balance: balance / LAMPORTS_PER_SOL,
       program: config.programId.toBase58(),
       maxFeeBps: config.maxFeeBps,
+      pools: pools.size(),
       version: '1.0.0',
     });
   } catch (e: unknown) {
@@ -749,6 +873,89 @@ app.get('/info', async (_req: Request, res: Response) => {
   }
 });
 
+// Pools served by this relayer, with fee limits and per-pool metrics
+app.get('/pools', async (_req: Request, res: Response) => {
+  try {
+    if (pools.size() === 0) await pools.refresh();
+    const list = await Promise.all(
+      pools.list().map(async (p) => {
+        const token = await tokens.get(p.tokenMint).catch(() => null);
+        return {
+          ...p,
+          token,
+          metrics: {
+            ...p.metrics,
+            feesEarnedDisplay: await tokens.format(p.tokenMint, BigInt(p.metrics.feesEarned)),
+          },
+        };
+      }),
+    );
+    res.json({ pools: list });
+  } catch (e: unknown) {
+    const message = e instanceof Error ? e.message : 'Unknown error';
+    log('error', 'Pool discovery error', { error: message });
+    res.status(500).json({ error: 'Failed to list pools' });
+  }
+});
+
+// Retry policy and dead-letter queue (nullifiers truncated)
+app.get('/status', (_req: Request, res: Response) => {
+  const deadLettered = deadLetters.entries().map((e) => ({
+    ...e,
+    nullifier: `${e.nullifier.replace(/^0x/, '').slice(0, 8)}...`,
+  }));
+  res.json({
+    retryPolicy,
+    deadLetter: {
+      count: deadLettered.length,
+      tracked: deadLetters.pendingFailures(),
+      entries: deadLettered,
+    },
+  });
+});
+
+// Human-readable summary of /status and the claim metrics
+app.get('/status/page', (_req: Request, res: Response) => {
+  res
+    .type('html')
+    .send(renderStatusPage(metrics.snapshot(), { pools: pools.size(), deadLettered: deadLetters.entries().length }));
+});
+
+// Prometheus scrape endpoint (aggregate counts only)
+app.get('/metrics', (_req: Request, res: Response) => {
+  res
+    .type('text/plain; version=0.0.4')
+    .send(metrics.prometheus({ deadLettered: deadLetters.entries().length, pools: pools.size() }));
+});
+
+/** Bearer `ADMIN_TOKEN`; admin routes 404 when no token is configured. */
+function requireAdmin(req: Request, res: Response, next: NextFunction): void {
+  if (!config.adminToken) {
+    res.status(404).json({ error: 'Not found' });
+    return;
+  }
+  const presented = Buffer.from((req.headers.authorization ?? '').replace(/^Bearer /, ''));
+  const expected = Buffer.from(config.adminToken);
+  if (presented.length !== expected.length || !crypto.timingSafeEqual(presented, expected)) {
+    log('warn', 'Admin auth failed', { ip: req.ip?.slice(0, 20) });
+    res.status(401).json({ error: 'Unauthorized' });
+    return;
+  }
+  next();
+}
+
+// Operator view: metrics, balance alert, per-pool counters and full dead-letter entries
+app.get('/admin', requireAdmin, (_req: Request, res: Response) => {
+  res.json({
+    relayer: relayerKeypair.publicKey.toBase58(),
+    metrics: metrics.snapshot(),
+    pools: pools.list(),
+    retryPolicy,
+    deadLetter: deadLetters.entries(),
+    jobs: jobs.counts(),
+  });
+});
+
 // Get pool info (merkle root needed for proof generation)
 app.get('/pool-info', async (req: Request, res: Response) => {
   try {
@@ -788,33 +995,93 @@ app.get('/pool-info', async (req: Request, res: Response) => {
   }
 });
 
+/**
+ * Every commitment in a pool's tree, in leaf order, for building a claim's
+ * Merkle path (wasm `generate_merkle_path`)
+ */
+app.get('/pool-leaves', async (req: Request, res: Response) => {
+  try {
+    const poolAddress = req.query.pool as string;
+    if (!poolAddress) {
+      return res.status(400).json({ error: 'pool query param required' });
+    }
+
+    const pool = new PublicKey(poolAddress);
+    if (!(await pools.resolve(pool))) {
+      return res.status(404).json({ error: 'Pool not found' });
+    }
+    const leaves = await pools.leaves(pool);
+    if (!leaves) {
+      return res.status(503).json({ error: 'Deposit records are still catching up; retry shortly' });
+    }
+
+    res.json({
+      pool: poolAddress,
+      leafCount: leaves.length.toString(),
+      leaves: Buffer.concat(leaves).toString('hex'),
+    });
+  } catch (e: unknown) {
+    const message = e instanceof Error ? e.message : 'Unknown error';
+    log('error', 'Pool leaves error', { error: message });
+    res.status(500).json({ error: 'Failed to fetch pool leaves' });
+  }
+});
+
+/** Reply to a failed claim, tagging it with a result code for the metrics. */
+function claimFailed(res: Response, status: number, code: string, body: Record<string, unknown>): Response {
+  res.locals.claimCode = code;
+  return res.status(status).json({ ...body, code });
+}
+
 /**
  * Submit a claim (multi-step chunked upload)
  */
 app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
   const requestId = crypto.randomBytes(8).toString('hex');
+  let span = withSpan({ name: 'claim', requestId });
   let claimNullifier: string | undefined; // Track for cleanup on error
+  let job: ClaimJob | undefined;
+  const claimDone = metrics.claimStarted();
+  res.on('close', () => {
+    claimDone(res.locals.claimCode ?? (res.statusCode < 400 ? CLAIM_OK : `HTTP_${res.statusCode}`));
+  });
   
   try {
-    const {
+    let {
       proof,
       commitment,
       nullifier,
-      merkleRoot,
       leafIndex,
-      recipientTokenAccount,
       poolAddress,
+    } = req.body;
+    const {
+      merkleRoot,
+      recipientTokenAccount,
       feeBps = 50
     } = req.body;
     
-    claimNullifier = nullifier; // Store for error handling
-    
     // ========================================
     // Input Validation
     // ========================================
     
     const errors: string[] = [];
     
+    // A proof bundle, if sent, supplies the proof and public inputs
+    if (req.body.bundle !== undefined) {
+      const bundle = parseProofBundle(req.body.bundle);
+      if (typeof bundle === 'string') {
+        errors.push(bundle);
+      } else {
+        ({ proof, commitment, nullifier, leaf_index: leafIndex } = bundle);
+        if (poolAddress && bundle.pool && poolAddress !== bundle.pool) {
+          errors.push('Proof bundle is for a different pool');
+        }
+        poolAddress = poolAddress ?? bundle.pool;
+      }
+    }
+    
+    claimNullifier = nullifier; // Store for error handling
+    
     if (!isValidHex(proof, 100, 16384)) {
       errors.push('Invalid proof format');
     }
@@ -835,22 +1102,39 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       errors.push('Invalid pool address');
     }
     if (!isValidFeeBps(feeBps)) {
-      errors.push(`Invalid fee (max ${config.maxFeeBps} bps)`);
+      errors.push('Invalid fee');
     }
     if (leafIndex === undefined || !isValidLeafIndex(leafIndex)) {
       errors.push('Invalid leaf index');
     }
     
     if (errors.length > 0) {
-      log('warn', 'Claim validation failed', { requestId, errors });
-      return res.status(400).json({ error: 'Validation failed', details: errors });
+      span('warn', 'Claim validation failed', { errors });
+      return claimFailed(res, 400, 'VALIDATION_FAILED', { error: 'Validation failed', details: errors });
     }
     
-    // Check for replay attack
-    if (!trackNullifier(nullifier)) {
-      log('warn', 'Duplicate nullifier submission', { requestId });
-      return res.status(400).json({ error: 'Claim already submitted' });
+    // Claims that keep failing are parked instead of retried forever
+    if (deadLetters.isDeadLettered(nullifier)) {
+      span('warn', 'Dead-lettered claim resubmitted');
+      return claimFailed(res, 409, 'DEAD_LETTERED', { error: 'Claim repeatedly failed; retry later' });
     }
+
+    // Resubmissions of the same claim get the existing job, not a second upload
+    const begun = jobs.begin(nullifier, commitment, requestId);
+    if (begun.kind === 'conflict') {
+      span('warn', 'Nullifier already in progress with another commitment', { jobId: begun.job.id });
+      return claimFailed(res, 409, 'DUPLICATE', { error: 'Claim already submitted' });
+    }
+    if (begun.kind === 'existing') {
+      const existing = begun.job;
+      span('info', 'Duplicate claim submission', { jobId: existing.id, status: existing.status });
+      res.locals.claimCode = existing.status === 'pending' ? 'IN_PROGRESS' : 'DUPLICATE';
+      if (existing.status === 'succeeded' && existing.result) {
+        return res.json({ success: true, ...existing.result, jobId: existing.id, duplicate: true });
+      }
+      return res.status(202).json({ status: existing.status, jobId: existing.id, code: 'IN_PROGRESS' });
+    }
+    job = begun.job;
     
     const redactHex = (s: string, keep = 8) => {
       if (!s) return s;
@@ -858,11 +1142,16 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       if (h.length <= keep) return h;
       return `${h.slice(0, keep)}...`;
     };
-
-    log('info', 'Processing claim', {
+    span = withSpan({
+      name: 'claim',
       requestId,
-      commitment: redactHex(commitment),
+      jobId: job.id,
+      pool: String(poolAddress).slice(0, 8),
       nullifier: redactHex(nullifier),
+    });
+
+    span('info', 'Processing claim', {
+      commitment: redactHex(commitment),
       merkleRoot: merkleRoot ? redactHex(merkleRoot) : 'from-pool',
       leafIndex,
       recipient: recipientTokenAccount.slice(0, 8) + '...',
@@ -873,7 +1162,7 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     // ========================================
     
     const proofBytes = sanitizeHex(proof);
-    log('info', 'Proof size', { requestId, size: proofBytes.length, maxAllowed: 16384 });
+    span('info', 'Proof size', { size: proofBytes.length, maxAllowed: 16384 });
     const commitment32 = Buffer.alloc(32);
     const nullifier32 = Buffer.alloc(32);
     sanitizeHex(commitment).slice(0, 32).copy(commitment32);
@@ -882,18 +1171,31 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     const pool = new PublicKey(poolAddress);
     const recipient = new PublicKey(recipientTokenAccount);
     
+    // Route to a pool this relayer serves and apply its fee limit
+    const poolEntry = await pools.resolve(pool);
+    if (!poolEntry) {
+      return claimFailed(res, 404, 'POOL_NOT_SERVED', { error: 'Pool not served by this relayer' });
+    }
+    if (poolEntry.paused) {
+      return claimFailed(res, 400, 'POOL_PAUSED', { error: 'Pool is paused' });
+    }
+    if (feeBps > poolEntry.maxFeeBps) {
+      return claimFailed(res, 400, 'FEE_TOO_HIGH', { error: `Invalid fee (max ${poolEntry.maxFeeBps} bps for this pool)` });
+    }
+    pools.recordSubmitted(poolEntry.address);
+    
     // Fetch pool to get merkle_root (if not provided in request)
     const poolInfo = await connection.getAccountInfo(pool);
     if (!poolInfo) {
-      return res.status(400).json({ error: 'Pool not found' });
+      return claimFailed(res, 400, 'POOL_NOT_FOUND', { error: 'Pool not found' });
     }
 
     if (!poolInfo.owner.equals(config.programId)) {
-      return res.status(400).json({ error: 'Pool account owner mismatch' });
+      return claimFailed(res, 400, 'POOL_INVALID', { error: 'Pool account owner mismatch' });
     }
 
     if (poolInfo.data.length < 8 + 32 + 32 + 32 + 32) {
-      return res.status(400).json({ error: 'Pool account data too small' });
+      return claimFailed(res, 400, 'POOL_INVALID', { error: 'Pool account data too small' });
     }
 
     // Pool layout: [8 discriminator][32 admin][32 token_mint][32 vault][32 merkle_root]...
@@ -903,7 +1205,7 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       sanitizeHex(merkleRoot).slice(0, 32).copy(merkleRoot32);
     } else {
       poolInfo.data.slice(104, 136).copy(merkleRoot32);
-      log('info', 'Fetched merkle_root from pool', { requestId, merkleRoot: merkleRoot32.toString('hex').slice(0, 16) + '...' });
+      span('info', 'Fetched merkle_root from pool', { merkleRoot: merkleRoot32.toString('hex').slice(0, 16) + '...' });
     }
     
     // Derive deposit PDA from pool + leafIndex (u64 = 8 bytes)
@@ -914,32 +1216,37 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       config.programId
     );
     const deposit = depositPda;
+    // Subscription leaves have a Subscription PDA instead of a deposit record
+    // and are claimed one epoch at a time (the epoch is in the nullifier).
+    const [subscriptionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('subscription'), pool.toBuffer(), leafIndexBuffer],
+      config.programId
+    );
     
-    log('info', 'Derived deposit PDA', { requestId, depositPda: depositPda.toBase58(), leafIndex });
+    span('info', 'Derived deposit PDA', { depositPda: depositPda.toBase58(), leafIndex });
     
     // Derive PDAs
-    const [vaultPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('vault'), pool.toBuffer()],
-      config.programId
-    );
-    log('debug', 'vaultPda derived', { requestId, vaultPda: vaultPda.toBase58() });
+    // Read the vault from the pool rather than deriving it: rotate_vault
+    // moves a pool's tokens to a new vault PDA
+    const vaultPda = new PublicKey(poolInfo.data.slice(8 + 32 + 32, 8 + 32 + 32 + 32));
+    span('debug', 'vaultPda read from pool', { vaultPda: vaultPda.toBase58() });
     
     const [nullifierPda] = PublicKey.findProgramAddressSync(
       [Buffer.from('nullifier'), pool.toBuffer(), nullifier32],
       config.programId
     );
-    log('debug', 'nullifierPda derived', { requestId, nullifierPda: nullifierPda.toBase58() });
+    span('debug', 'nullifierPda derived', { nullifierPda: nullifierPda.toBase58() });
     
     // ========================================
     // Check on-chain nullifier status
     // ========================================
     
-    log('debug', 'fetching nullifier account...', { requestId });
+    span('debug', 'fetching nullifier account...');
     const nullifierAccount = await connection.getAccountInfo(nullifierPda);
-    log('debug', 'nullifier account fetched', { requestId, exists: !!nullifierAccount });
+    span('debug', 'nullifier account fetched', { exists: !!nullifierAccount });
     if (nullifierAccount) {
-      log('warn', 'Nullifier already used on-chain', { requestId });
-      return res.status(400).json({ error: 'Funds already claimed' });
+      span('warn', 'Nullifier already used on-chain');
+      return claimFailed(res, 400, 'ALREADY_CLAIMED', { error: 'Funds already claimed' });
     }
     
     // ========================================
@@ -948,10 +1255,10 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     
     // Create a fresh buffer for this claim (temp account, closed after claim)
     const bufferKeypair = Keypair.generate();
-    const HEADER_SIZE = 169;
+    job.buffer = bufferKeypair.publicKey.toBase58(); // before creation, so the sweep skips it
+    const HEADER_SIZE = PROOF_BUFFER_HEADER_SIZE;
     const accountSize = HEADER_SIZE + proofBytes.length;
     const rentExempt = await connection.getMinimumBalanceForRentExemption(accountSize);
-    let numChunks = Math.ceil(proofBytes.length / config.chunkSize);
     
     const createAccountIx = SystemProgram.createAccount({
       fromPubkey: relayerKeypair.publicKey,
@@ -961,13 +1268,23 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       programId: STARK_VERIFIER_ID,
     });
     
-    // Initialize the buffer via stark-verifier
+    // Initialize the buffer via stark-verifier. Should this claim never get
+    // to close it, anyone may reap it once it expires; we get the rent back
+    // minus the reaper's cut.
     const expectedSizeBuffer = Buffer.alloc(4);
     expectedSizeBuffer.writeUInt32LE(proofBytes.length);
+    const expiryBuffer = Buffer.alloc(8);
+    expiryBuffer.writeBigUInt64LE(BUFFER_EXPIRY_SLOTS);
+    // The verifier only checks a buffer whose bytes hash to this
+    // @ts-ignore - js-sha3 doesn't have types
+    const { keccak256 } = require('js-sha3');
+    const expectedKeccak = Buffer.from(keccak256(proofBytes), 'hex');
     
     const initData = Buffer.concat([
       getDiscriminator('init_proof_buffer'),
       expectedSizeBuffer,
+      expiryBuffer,
+      expectedKeccak,
     ]);
     
     const initIx = new TransactionInstruction({
@@ -980,111 +1297,76 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
       data: initData,
     });
     
-    const createTx = new Transaction().add(createAccountIx).add(initIx);
-    createTx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
-    createTx.feePayer = relayerKeypair.publicKey;
-    
     // Buffer keypair must sign for createAccount
-    await sendAndConfirmTransaction(connection, createTx, [relayerKeypair, bufferKeypair]);
+    await sendWithRetry(connection, [createAccountIx, initIx], [relayerKeypair, bufferKeypair], retryPolicy, {
+      label: 'Create buffer',
+      requestId,
+      alreadyLanded: async () => (await connection.getAccountInfo(bufferKeypair.publicKey)) !== null,
+      log,
+    });
     
-    log('info', 'Buffer created', { requestId, buffer: bufferKeypair.publicKey.toBase58().slice(0, 8), accountSize, rentExempt });
+    span('info', 'Buffer created', { buffer: bufferKeypair.publicKey.toBase58().slice(0, 8), accountSize, rentExempt });
     
     // ========================================
     // Step 2: Write Proof Chunks
     // ========================================
     
-    log('debug', 'Writing chunks', { requestId, numChunks, chunkSize: config.chunkSize });
-    for (let i = 0; i < numChunks; i++) {
-      const offset = i * config.chunkSize;
-      const chunk = proofBytes.slice(offset, offset + config.chunkSize);
-      
-      log('debug', `Writing chunk ${i + 1}/${numChunks}`, { requestId, offset, chunkLen: chunk.length });
-      
-      const writeData = Buffer.concat([
-        getDiscriminator('upload_chunk'),
-        Buffer.from(new Uint32Array([offset]).buffer),
-        Buffer.from(new Uint32Array([chunk.length]).buffer),
-        chunk,
-      ]);
-      
-      const writeIx = new TransactionInstruction({
-        programId: STARK_VERIFIER_ID,
-        keys: [
-          { pubkey: bufferKeypair.publicKey, isSigner: false, isWritable: true },
-          { pubkey: relayerKeypair.publicKey, isSigner: true, isWritable: false },
-        ],
-        data: writeData,
-      });
-      
-      const writeTx = new Transaction().add(writeIx);
-      writeTx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
-      writeTx.feePayer = relayerKeypair.publicKey;
-      
-      try {
-        await sendAndConfirmTransaction(connection, writeTx, [relayerKeypair]);
-        log('debug', `Chunk ${i + 1} written successfully`, { requestId });
-      } catch (chunkErr) {
-        log('error', `Chunk ${i + 1} failed`, { requestId, error: String(chunkErr) });
-        throw chunkErr;
-      }
-    }
-    
-    log('info', 'Chunks written', { requestId, numChunks });
+    const upload = await uploadProofPipelined(connection, bufferKeypair.publicKey, relayerKeypair, proofBytes, retryPolicy, {
+      programId: STARK_VERIFIER_ID,
+      chunkSize: config.chunkSize,
+      concurrency: config.uploadConcurrency,
+      requestId,
+      log,
+    });
+    const numChunks = upload.chunks;
     
     // ========================================
-    // Step 3: Finalize Buffer
+    // Step 3: Check the proof
     // ========================================
     
     // finalize_and_verify(commitment: [u8; 32], nullifier: [u8; 32], merkle_root: [u8; 32], recipient: [u8; 32])
     const recipient32 = new PublicKey(recipientTokenAccount).toBuffer();
+    const inputs: PublicInputs = {
+      commitment: commitment32,
+      nullifier: nullifier32,
+      merkleRoot: merkleRoot32,
+      recipient: recipient32,
+    };
 
     // DEBUG: Log exact values being sent to finalize
-    log('debug', 'Finalize params', {
-      requestId,
+    span('debug', 'Finalize params', {
       commitment: `${commitment32.toString('hex').slice(0, 8)}...`,
       nullifier: `${nullifier32.toString('hex').slice(0, 8)}...`,
       merkleRoot: `${merkleRoot32.toString('hex').slice(0, 8)}...`,
       recipient: `${recipient32.toString('hex').slice(0, 8)}...`,
       proofSize: proofBytes.length,
-      proofFirst32: `${proofBytes.slice(0, 32).toString('hex').slice(0, 8)}...`, // trace_commitment
-    });
-    const finalizeData = Buffer.concat([
-      getDiscriminator('finalize_and_verify'),
-      commitment32,
-      nullifier32,
-      merkleRoot32,
-      recipient32,
-    ]);
-    
-    const finalizeIx = new TransactionInstruction({
-      programId: STARK_VERIFIER_ID,
-      keys: [
-        { pubkey: bufferKeypair.publicKey, isSigner: false, isWritable: true },
-        { pubkey: relayerKeypair.publicKey, isSigner: true, isWritable: false },
-      ],
-      data: finalizeData,
+      proofFirst32: `${proofBytes.slice(0, 32).toString('hex').slice(0, 8)}...`, // hash kind + trace_commitment
     });
     
-    const finalizeTx = new Transaction().add(finalizeIx);
-    finalizeTx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
-    finalizeTx.feePayer = relayerKeypair.publicKey;
-    
-    await sendAndConfirmTransaction(connection, finalizeTx, [relayerKeypair]);
-    log('info', 'Buffer finalized', { requestId });
-    
-    // DEBUG: Read buffer to verify commitment was stored correctly
-    const bufferInfo = await connection.getAccountInfo(bufferKeypair.publicKey);
-    if (bufferInfo) {
-      const bufData = bufferInfo.data;
-      const bufFinalized = bufData[40] === 1;
-      const bufCommitment = bufData.slice(41, 73).toString('hex');
-      log('debug', 'Buffer state after finalize', { 
-        requestId,
-        finalized: bufFinalized,
-        bufferCommitment: bufCommitment,
-        expectedCommitment: commitment,
-        match: bufCommitment === commitment ? '✅' : '❌'
-      });
+    // Simulate first: a rejected proof is the client's fault, and the
+    // verifier logs which check it failed. Proofs too large for one
+    // transaction are checked stage by stage in step 5 instead.
+    const directUnits = VERIFY_BASE_CU + proofBytes[9] * QUERY_CU;
+    if (directUnits <= MAX_TRANSACTION_CU) {
+      const finalizeIx = finalizeAndVerifyInstruction(STARK_VERIFIER_ID, bufferKeypair.publicKey, relayerKeypair.publicKey, inputs);
+      const finalizeSimTx = new Transaction()
+        .add(ComputeBudgetProgram.setComputeUnitLimit({ units: directUnits }))
+        .add(finalizeIx);
+      finalizeSimTx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
+      finalizeSimTx.feePayer = relayerKeypair.publicKey;
+      const finalizeSim = await connection.simulateTransaction(finalizeSimTx, [relayerKeypair]);
+      if (finalizeSim.value.err) {
+        const errJson = JSON.stringify(finalizeSim.value.err);
+        const programError = describeProgramError(finalizeSim.value.err, [ComputeBudgetProgram.programId, STARK_VERIFIER_ID]);
+        const report = verificationReport(finalizeSim.value.logs);
+        span('error', 'Proof rejected', { error: errJson, report, logs: finalizeSim.value.logs?.slice(-10) });
+        return claimFailed(res, 400, 'VERIFICATION_FAILED', {
+          error: 'Proof verification failed',
+          detail: programError ? `${programError.name}: ${programError.message}` : errJson,
+          ...(report && { report }),
+          ...(programError && { hint: programError.hint }),
+        });
+      }
     }
     
     // ========================================
@@ -1098,19 +1380,19 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     
     // Check if recipient ATA exists; create it if missing (relayer pays rent).
     const ataInfo = await connection.getAccountInfo(recipientAta);
-    const claimTx = new Transaction();
+    const claimIxs: TransactionInstruction[] = [];
     
     if (!ataInfo) {
       // Derive the wallet owner from the request (or from the ATA address).
       // The frontend sends `recipientWallet` alongside `recipientTokenAccount`.
       const recipientWalletStr = req.body.recipientWallet;
       if (!recipientWalletStr || !isValidBase58(recipientWalletStr)) {
-        log('error', 'Recipient ATA does not exist and no wallet provided', { requestId, recipientAta: recipientAta.toBase58() });
-        return res.status(400).json({ error: 'Recipient token account does not exist. Provide recipientWallet to auto-create.' });
+        span('error', 'Recipient ATA does not exist and no wallet provided', { recipientAta: recipientAta.toBase58() });
+        return claimFailed(res, 400, 'RECIPIENT_MISSING', { error: 'Recipient token account does not exist. Provide recipientWallet to auto-create.' });
       }
       const recipientWallet = new PublicKey(recipientWalletStr);
-      log('info', 'Creating recipient ATA', { requestId, wallet: recipientWallet.toBase58().slice(0, 8), mint: tokenMint.toBase58().slice(0, 8) });
-      claimTx.add(
+      span('info', 'Creating recipient ATA', { wallet: recipientWallet.toBase58().slice(0, 8), mint: tokenMint.toBase58().slice(0, 8) });
+      claimIxs.push(
         createAssociatedTokenAccountInstruction(
           relayerKeypair.publicKey,
           recipientAta,
@@ -1124,7 +1406,7 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     const relayerAtaInfo = await connection.getAccountInfo(relayerAta);
     
     if (!relayerAtaInfo) {
-      claimTx.add(
+      claimIxs.push(
         createAssociatedTokenAccountInstruction(
           relayerKeypair.publicKey,
           relayerAta,
@@ -1137,14 +1419,20 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     // Claim instruction expects `relayer_fee` in *token units*, not bps.
     // Convert requested feeBps into an absolute fee using the on-chain deposit amount.
     const depInfo = await connection.getAccountInfo(deposit, 'confirmed');
-    if (!depInfo) {
-      return res.status(400).json({ error: 'Deposit account not found' });
+    const subInfo = depInfo ? null : await connection.getAccountInfo(subscriptionPda, 'confirmed');
+    const isSubscription = subInfo !== null;
+    const recordInfo = depInfo ?? subInfo;
+    if (!recordInfo) {
+      return claimFailed(res, 400, 'DEPOSIT_NOT_FOUND', { error: 'Deposit account not found' });
     }
-    const depData = Buffer.from(depInfo.data);
-    if (depData.length < 8 + 32 + 32 + 8) {
-      return res.status(400).json({ error: 'Invalid deposit account' });
+    // DepositRecord:  [8 disc][32 pool][32 commitment][8 amount]...
+    // Subscription:   [8 disc][32 pool][32 commitment][8 leaf_index][8 amount_per_epoch]...
+    const amountOffset = isSubscription ? 8 + 32 + 32 + 8 : 8 + 32 + 32;
+    const depData = Buffer.from(recordInfo.data);
+    if (depData.length < amountOffset + 8) {
+      return claimFailed(res, 400, 'DEPOSIT_NOT_FOUND', { error: 'Invalid deposit account' });
     }
-    const depositAmount = readU64LE(depData, 8 + 32 + 32);
+    const depositAmount = readU64LE(depData, amountOffset);
 
     // floor(amount * bps / 10_000) in base units (u64), avoiding JS number overflow.
     const relayerFeeAmount = (depositAmount * BigInt(feeBps)) / 10_000n;
@@ -1152,107 +1440,282 @@ app.post('/claim', claimLimiter, async (req: Request, res: Response) => {
     const relayerFeeBuffer = Buffer.alloc(8);
     relayerFeeBuffer.writeBigUInt64LE(relayerFeeAmount);
     
+    // Pools with a nullifier registry record one-shot claims in a shard
+    // (created here on first use) instead of a NullifierRecord PDA
+    const shardBits = isSubscription ? 0 : poolEntry.nullifierShardBits;
+    let nullifierShard: PublicKey | null = null;
+    if (shardBits > 0) {
+      const shardIndex = nullifier32.readUInt16BE(0) >> (16 - shardBits);
+      const shardIndexBuffer = Buffer.alloc(2);
+      shardIndexBuffer.writeUInt16LE(shardIndex);
+      [nullifierShard] = PublicKey.findProgramAddressSync(
+        [Buffer.from('nullifier-shard'), pool.toBuffer(), shardIndexBuffer],
+        config.programId
+      );
+      if (!(await connection.getAccountInfo(nullifierShard, 'confirmed'))) {
+        claimIxs.push(new TransactionInstruction({
+          programId: config.programId,
+          keys: [
+            { pubkey: pool, isSigner: false, isWritable: false },
+            { pubkey: nullifierShard, isSigner: false, isWritable: true },
+            { pubkey: relayerKeypair.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([getDiscriminator('initialize_nullifier_shard'), shardIndexBuffer]),
+        }));
+      }
+    }
+
     const claimData = Buffer.concat([
-      getDiscriminator('claim'),
+      getDiscriminator(isSubscription ? 'claim_epoch' : nullifierShard ? 'claim_sharded' : 'claim'),
       relayerFeeBuffer,
       nullifier32,
     ]);
     
+    const [poolStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pool-stats'), pool.toBuffer()],
+      config.programId
+    );
+    const poolStats = (await connection.getAccountInfo(poolStatsPda, 'confirmed')) ? poolStatsPda : config.programId;
+    const [nullifierBloomPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('nullifier-bloom'), pool.toBuffer()],
+      config.programId
+    );
+    const nullifierBloom = (await connection.getAccountInfo(nullifierBloomPda, 'confirmed'))
+      ? nullifierBloomPda
+      : config.programId;
+
+    // claim_receipt (optional): written for the recipient wallet when the
+    // claimant asks for one; the relayer pays its rent on the first claim
+    let claimReceipt = config.programId;
+    if (req.body.receipt === true) {
+      const recipientWallet = ataInfo
+        ? new PublicKey(ataInfo.data.slice(32, 64))
+        : new PublicKey(req.body.recipientWallet);
+      [claimReceipt] = PublicKey.findProgramAddressSync(
+        [Buffer.from('claim-receipt'), pool.toBuffer(), recipientWallet.toBuffer()],
+        config.programId
+      );
+    }
+
+    // A plain claim closes the proof buffer itself, refunding its rent in the
+    // same transaction; the sharded and subscription claims leave it to us
+    const closesBuffer = !isSubscription && !nullifierShard;
+    
     const claimIx = new TransactionInstruction({
       programId: config.programId,
       keys: [
-        { pubkey: pool, isSigner: false, isWritable: false },           // pool (read-only)
-        { pubkey: deposit, isSigner: false, isWritable: true },         // deposit
-        { pubkey: bufferKeypair.publicKey, isSigner: false, isWritable: false }, // verifier_buffer
-        { pubkey: nullifierPda, isSigner: false, isWritable: true },    // nullifier_record
+        { pubkey: pool, isSigner: false, isWritable: true },            // pool (event_seq)
+        { pubkey: isSubscription ? subscriptionPda : deposit, isSigner: false, isWritable: true }, // deposit | subscription
+        { pubkey: bufferKeypair.publicKey, isSigner: false, isWritable: closesBuffer }, // verifier_buffer
+        { pubkey: nullifierPda, isSigner: false, isWritable: !nullifierShard }, // nullifier_record
+        // nullifier_shard (claim_sharded only)
+        ...(nullifierShard ? [{ pubkey: nullifierShard, isSigner: false, isWritable: true }] : []),
         { pubkey: vaultPda, isSigner: false, isWritable: true },        // vault
         { pubkey: recipientAta, isSigner: false, isWritable: true },    // recipient_token
         { pubkey: relayerKeypair.publicKey, isSigner: true, isWritable: true }, // relayer (SIGNER - before relayer_token!)
         { pubkey: relayerAta, isSigner: false, isWritable: true },      // relayer_token
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        // pool_stats (optional): the program ID stands in when the pool has none
+        { pubkey: poolStats, isSigner: false, isWritable: !poolStats.equals(config.programId) },
+        // verifier_params: only read when the pool pins verifier params
+        { pubkey: VERIFIER_PARAMS_PDA, isSigner: false, isWritable: false },
+        { pubkey: claimReceipt, isSigner: false, isWritable: !claimReceipt.equals(config.programId) },
+        // nullifier_bloom (optional): every claim sets its bits once it exists
+        { pubkey: nullifierBloom, isSigner: false, isWritable: !nullifierBloom.equals(config.programId) },
+        // verifier_program (claim only): present, the claim closes verifier_buffer
+        ...(closesBuffer ? [{ pubkey: STARK_VERIFIER_ID, isSigner: false, isWritable: false }] : []),
       ],
       data: claimData,
     });
     
-    claimTx.add(claimIx);
-    claimTx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
-    claimTx.feePayer = relayerKeypair.publicKey;
+    // Pools with a compliance hook expect the hook program (then its state
+    // accounts) as remaining accounts.
+    if (poolEntry.complianceHook) {
+      claimIx.keys.push({ pubkey: new PublicKey(poolEntry.complianceHook), isSigner: false, isWritable: false });
+      const extra: unknown = req.body.complianceAccounts ?? [];
+      if (!Array.isArray(extra) || extra.length > 8 || !extra.every(isValidBase58)) {
+        return claimFailed(res, 400, 'VALIDATION_FAILED', { error: 'Invalid complianceAccounts' });
+      }
+      for (const account of extra as string[]) {
+        claimIx.keys.push({ pubkey: new PublicKey(account), isSigner: false, isWritable: false });
+      }
+    }
     
-    // Simulate first
-    const simResult = await connection.simulateTransaction(claimTx, [relayerKeypair]);
-    if (simResult.value.err) {
-      // Parse error for better diagnostics
-      const errJson = JSON.stringify(simResult.value.err);
-      let errorDetail = errJson;
-      
-      // Check for specific Anchor errors
-      if (errJson.includes('3007')) {
-        // AccountOwnedByWrongProgram - check which account
-        const depositInfo = await connection.getAccountInfo(deposit);
-        const poolInfo = await connection.getAccountInfo(pool);
-        errorDetail = `AccountOwnedByWrongProgram: ` +
-          `deposit=${deposit.toBase58()} owner=${depositInfo?.owner?.toBase58() || 'NOT_FOUND'}, ` +
-          `pool=${pool.toBase58()} owner=${poolInfo?.owner?.toBase58() || 'NOT_FOUND'}, ` +
-          `expected=${config.programId.toBase58()}`;
+    claimIxs.push(claimIx);
+    
+    // ========================================
+    // Step 5: Verify & Claim, bundled
+    // ========================================
+    
+    // Accounts every claim from this pool shares go in its lookup table
+    const lookupTable = config.claimLookupTables
+      ? await lookupTables.get(pool, [
+          config.programId,
+          STARK_VERIFIER_ID,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID,
+          SystemProgram.programId,
+          ComputeBudgetProgram.programId,
+          vaultPda,
+          poolStatsPda,
+          nullifierBloomPda,
+          VERIFIER_PARAMS_PDA,
+          verifierConfigPda(STARK_VERIFIER_ID),
+          tokenMint,
+          relayerAta,
+        ], requestId)
+      : null;
+    const tables = lookupTable ? [lookupTable] : [];
+    const plan = planClaim(proofBytes, claimIxs, {
+      verifierId: STARK_VERIFIER_ID,
+      buffer: bufferKeypair.publicKey,
+      owner: relayerKeypair.publicKey,
+      inputs,
+      lookupTables: tables,
+    });
+    span('info', 'Claim planned', {
+      staged: plan.staged,
+      transactions: plan.transactions.map((tx) => tx.label),
+      lookupTable: lookupTable?.key.toBase58() ?? null,
+    });
+    
+    // Staged transactions only see each other's effects on chain, so each is
+    // simulated right before it is sent
+    const verificationState = verificationStatePda(STARK_VERIFIER_ID, bufferKeypair.publicKey);
+    const stateOf = async () => {
+      const infos = await connection.getMultipleAccountsInfo([bufferKeypair.publicKey, verificationState], 'confirmed');
+      return infos.map((info) => info?.data.toString('hex') ?? '').join(':');
+    };
+    let claimResult: SendResult | null = null;
+    let computeUnits = 0;
+    for (const planned of plan.transactions) {
+      const programIds = [ComputeBudgetProgram.programId, ...planned.instructions.map((ix) => ix.programId)];
+      const simMessage = MessageV0.compile({
+        payerKey: relayerKeypair.publicKey,
+        instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: planned.computeUnits }), ...planned.instructions],
+        recentBlockhash: (await connection.getLatestBlockhash()).blockhash,
+        addressLookupTableAccounts: tables,
+      });
+      const simResult = await connection.simulateTransaction(new VersionedTransaction(simMessage), { sigVerify: false });
+      if (simResult.value.err) {
+        // Parse error for better diagnostics
+        const errJson = JSON.stringify(simResult.value.err);
+        let errorDetail = errJson;
+        const programError = describeProgramError(simResult.value.err, programIds);
+        const report = verificationReport(simResult.value.logs);
+        
+        // Name murkl/verifier errors; otherwise look closer at specific Anchor errors
+        if (programError) {
+          errorDetail = `${programError.name}: ${programError.message}`;
+        } else if (errJson.includes('3007')) {
+          // AccountOwnedByWrongProgram - check which account
+          const depositInfo = await connection.getAccountInfo(deposit);
+          const poolInfo = await connection.getAccountInfo(pool);
+          errorDetail = `AccountOwnedByWrongProgram: ` +
+            `deposit=${deposit.toBase58()} owner=${depositInfo?.owner?.toBase58() || 'NOT_FOUND'}, ` +
+            `pool=${pool.toBase58()} owner=${poolInfo?.owner?.toBase58() || 'NOT_FOUND'}, ` +
+            `expected=${config.programId.toBase58()}`;
+        }
+        
+        span('error', `${planned.label} simulation failed`, {
+          error: errJson,
+          detail: errorDetail,
+          ...(report && { report }),
+          logs: simResult.value.logs?.slice(-10),
+        });
+        return claimFailed(res, 400, 'VERIFICATION_FAILED', {
+          error: report ? 'Proof verification failed' : 'Claim verification failed',
+          detail: errorDetail,
+          ...(report && { report }),
+          ...(programError && { hint: programError.hint }),
+        });
       }
       
-      log('error', 'Claim simulation failed', { 
-        requestId, 
-        error: errJson,
-        detail: errorDetail,
-        logs: simResult.value.logs?.slice(-10),
-      });
-      return res.status(400).json({ 
-        error: 'Claim verification failed',
-        code: 'VERIFICATION_FAILED',
-        detail: errorDetail,
+      // The nullifier PDA is created by `claim`, so its existence means an
+      // earlier broadcast already landed; any other step changes the buffer
+      // or the staged verification state.
+      const before = planned.claims ? '' : await stateOf();
+      const result = await sendWithRetry(connection, planned.instructions, [relayerKeypair], retryPolicy, {
+        label: planned.label,
+        requestId,
+        computeUnits: planned.computeUnits,
+        lookupTables: tables,
+        alreadyLanded: planned.claims
+          ? async () => (await connection.getAccountInfo(nullifierPda)) !== null
+          : async () => (await stateOf()) !== before,
+        log,
       });
+      span('debug', `${planned.label} confirmed`, { signature: result.signature, attempts: result.attempts });
+      if (planned.claims) {
+        claimResult = result;
+        computeUnits = simResult.value.unitsConsumed ?? 0;
+      }
     }
+    if (!claimResult) throw new Error('Claim plan has no claim transaction');
     
-    const claimSig = await sendAndConfirmTransaction(connection, claimTx, [relayerKeypair]);
+    const claimSig = claimResult.signature;
+    if (closesBuffer) job.buffer = null; // closed by the claim
+    deadLetters.recordSuccess(nullifier);
+    pools.recordSucceeded(poolEntry.address, relayerFeeAmount);
+    // create (relayer + buffer), one per chunk, then the planned transactions
+    metrics.recordCost(computeUnits, claimFeeLamports(2 + numChunks + plan.transactions.length, claimResult.priorityFee, computeUnits));
     
-    log('info', 'Claim successful', {
-      requestId,
+    span('info', 'Claim successful', {
       signature: claimSig,
-      computeUnits: simResult.value.unitsConsumed,
+      fee: await tokens.format(poolEntry.tokenMint, relayerFeeAmount),
+      attempts: claimResult.attempts,
+      priorityFee: claimResult.priorityFee,
+      computeUnits,
     });
     
-    // Mark deposit as claimed in the database
+    // Mark deposit as claimed in the database (subscriptions have more epochs to go)
     const depositId = `${poolAddress}-${leafIndex}`;
-    const dep = stmtFindById.get(depositId) as any;
+    const dep = isSubscription ? undefined : (stmtFindById.get(depositId) as any);
     if (dep) {
       stmtMarkClaimed.run(dep.id);
-      log('info', 'Deposit marked claimed', { requestId, depositId: dep.id });
+      span('info', 'Deposit marked claimed', { depositId: dep.id });
     }
     
-    res.json({
-      success: true,
-      signature: claimSig,
-      chunksWritten: numChunks,
-      computeUnits: simResult.value.unitsConsumed,
-    });
+    const result = { signature: claimSig, chunksWritten: numChunks, computeUnits };
+    jobs.succeed(job, result);
+    res.json({ success: true, ...result, jobId: job.id });
     
   } catch (e: unknown) {
     const message = e instanceof Error ? e.message : 'Unknown error';
     const stack = e instanceof Error ? e.stack : undefined;
-    log('error', 'Claim error', { requestId, error: message, stack });
+    span('error', 'Claim error', { error: message, stack });
     console.error('❌ Claim error:', message);
     if (stack) console.error(stack);
     
-    // Remove nullifier from cache so user can retry
     if (claimNullifier) {
-      processedNullifiers.delete(claimNullifier);
+      const failedPool = String(req.body.poolAddress ?? '');
+      pools.recordFailed(failedPool);
+      const entry = deadLetters.recordFailure(claimNullifier, failedPool, message);
+      if (deadLetters.isDeadLettered(claimNullifier)) {
+        span('error', 'Claim dead-lettered', { failures: entry.failures });
+      }
     }
     
     // Return more detail in development
     const isDev = process.env.NODE_ENV !== 'production';
     if (message.includes('insufficient funds')) {
-      res.status(503).json({ error: 'Relayer temporarily unavailable' });
+      claimFailed(res, 503, 'RELAYER_UNFUNDED', { error: 'Relayer temporarily unavailable' });
     } else if (isDev) {
-      res.status(500).json({ error: message, requestId });
+      claimFailed(res, 500, 'INTERNAL', { error: message, requestId });
     } else {
-      res.status(500).json({ error: 'Internal error' });
+      claimFailed(res, 500, 'INTERNAL', { error: 'Internal error' });
+    }
+  } finally {
+    // Early returns leave the job pending; a failed job can be resubmitted
+    if (job) {
+      if (job.status === 'pending') jobs.fail(job, res.locals.claimCode ?? 'INTERNAL');
+      if (job.buffer) {
+        const buffer = new PublicKey(job.buffer);
+        job.buffer = null;
+        void closeProofBuffers([buffer], requestId);
+      }
     }
   }
 });
@@ -1683,6 +2146,26 @@ const server = app.listen(config.port, () => {
   });
 });
 
+metrics.watchBalance(
+  connection,
+  relayerKeypair.publicKey,
+  config.balanceCheckMs,
+  (alert) => log(alert.level === 'critical' ? 'error' : 'warn', 'Relayer balance low', { ...alert }),
+  (e) => log('warn', 'Balance check failed', { error: String(e) }),
+);
+
+// Buffers left behind by a crash or a failed close
+const sweep = () =>
+  sweepProofBuffers()
+    .then((count) => count > 0 && log('info', 'Swept stale proof buffers', { count }))
+    .catch((e) => log('warn', 'Proof buffer sweep failed', { error: String(e) }));
+void sweep();
+setInterval(sweep, BUFFER_SWEEP_MS).unref();
+
+pools.refresh()
+  .then((count) => log('info', 'Pools discovered', { count }))
+  .catch((e) => log('warn', 'Initial pool discovery failed (will retry on demand)', { error: String(e) }));
+
 runAuthMigrations().catch((e) => {
   log('error', 'Auth migrations failed (continuing to serve /health; auth may be degraded)', {
     error: String(e),
@@ -0,0 +1,124 @@
+/**
+ * Claim job deduplication
+ *
+ * Clients often resubmit the same bundle after a timeout while the first
+ * request is still uploading. Claims are keyed by (nullifier, commitment):
+ * a resubmission gets the existing job instead of a second proof upload, and
+ * a finished claim replays its original result. Failed jobs may be retried.
+ */
+
+export type ClaimJobStatus = 'pending' | 'succeeded' | 'failed';
+
+export interface ClaimResult {
+  signature: string | null;
+  chunksWritten: number;
+  computeUnits: number | undefined;
+}
+
+export interface ClaimJob {
+  id: string;
+  nullifier: string;
+  commitment: string;
+  status: ClaimJobStatus;
+  /** Proof buffer created for this job, until it is closed */
+  buffer: string | null;
+  result: ClaimResult | null;
+  /** Result code of a failed job */
+  code: string | null;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export type BeginResult =
+  | { kind: 'new'; job: ClaimJob }
+  /** Same (nullifier, commitment) already pending or done */
+  | { kind: 'existing'; job: ClaimJob }
+  /** Nullifier pending under a different commitment */
+  | { kind: 'conflict'; job: ClaimJob };
+
+const normalize = (hex: string) => hex.replace(/^0x/, '').toLowerCase();
+
+export class ClaimJobs {
+  private jobs = new Map<string, ClaimJob>();
+  /** Pending job per nullifier, whatever its commitment */
+  private pendingByNullifier = new Map<string, ClaimJob>();
+
+  constructor(private maxJobs: number) {}
+
+  static key(nullifier: string, commitment: string): string {
+    return `${normalize(nullifier)}:${normalize(commitment)}`;
+  }
+
+  /** Start a job for a claim, or return the one already tracking it. */
+  begin(nullifier: string, commitment: string, id: string): BeginResult {
+    const key = ClaimJobs.key(nullifier, commitment);
+    const existing = this.jobs.get(key);
+    if (existing && existing.status !== 'failed') return { kind: 'existing', job: existing };
+
+    const pending = this.pendingByNullifier.get(normalize(nullifier));
+    if (pending) return { kind: 'conflict', job: pending };
+
+    const now = new Date().toISOString();
+    const job: ClaimJob = {
+      id,
+      nullifier: normalize(nullifier),
+      commitment: normalize(commitment),
+      status: 'pending',
+      buffer: null,
+      result: null,
+      code: null,
+      createdAt: now,
+      updatedAt: now,
+    };
+    this.jobs.delete(key);
+    this.evict();
+    this.jobs.set(key, job);
+    this.pendingByNullifier.set(job.nullifier, job);
+    return { kind: 'new', job };
+  }
+
+  succeed(job: ClaimJob, result: ClaimResult): void {
+    job.status = 'succeeded';
+    job.result = result;
+    this.settle(job);
+  }
+
+  fail(job: ClaimJob, code: string): void {
+    job.status = 'failed';
+    job.code = code;
+    this.settle(job);
+  }
+
+  /** Buffers owned by pending jobs, which must not be garbage-collected. */
+  activeBuffers(): Set<string> {
+    const buffers = new Set<string>();
+    for (const job of this.pendingByNullifier.values()) {
+      if (job.buffer) buffers.add(job.buffer);
+    }
+    return buffers;
+  }
+
+  counts(): Record<ClaimJobStatus, number> {
+    const counts = { pending: 0, succeeded: 0, failed: 0 };
+    for (const job of this.jobs.values()) counts[job.status] += 1;
+    return counts;
+  }
+
+  private settle(job: ClaimJob): void {
+    job.updatedAt = new Date().toISOString();
+    if (this.pendingByNullifier.get(job.nullifier) === job) {
+      this.pendingByNullifier.delete(job.nullifier);
+    }
+  }
+
+  /** Drop the oldest finished job once at capacity; pending jobs are never evicted. */
+  private evict(): void {
+    if (this.jobs.size < this.maxJobs) return;
+    for (const [key, job] of this.jobs) {
+      if (job.status !== 'pending') {
+        this.jobs.delete(key);
+        return;
+      }
+    }
+  }
+}
@@ -0,0 +1,246 @@
+/**
+ * Operator metrics
+ *
+ * Counts claim outcomes by result code, tracks claims in flight and the
+ * compute units and network fees each successful claim cost, and watches the
+ * hot wallet balance. Rendered as Prometheus text for /metrics and as JSON
+ * for /admin and the status page.
+ */
+
+import { Connection, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+
+/** Base fee per transaction signature */
+export const LAMPORTS_PER_SIGNATURE = 5000;
+
+/** Result code recorded for a claim that paid out */
+export const CLAIM_OK = 'OK';
+
+export interface BalanceAlert {
+  level: 'warn' | 'critical';
+  message: string;
+  since: string;
+}
+
+export interface MetricsSnapshot {
+  startedAt: string;
+  inFlight: number;
+  maxInFlight: number;
+  claimsByCode: Record<string, number>;
+  succeeded: number;
+  failed: number;
+  avgComputeUnits: number;
+  avgFeeLamports: number;
+  totalFeeLamports: string;
+  balanceLamports: number | null;
+  balanceCheckedAt: string | null;
+  alert: BalanceAlert | null;
+}
+
+export interface BalanceThresholds {
+  /** Below this many SOL the relayer warns */
+  warnSol: number;
+  /** Below this many SOL claims are likely to start failing */
+  criticalSol: number;
+}
+
+/** Read `LOW_BALANCE_SOL` / `CRITICAL_BALANCE_SOL`. */
+export function loadBalanceThresholds(): BalanceThresholds {
+  const num = (name: string, fallback: number) => {
+    const value = Number(process.env[name]);
+    return Number.isFinite(value) && value >= 0 ? value : fallback;
+  };
+  return {
+    warnSol: num('LOW_BALANCE_SOL', 0.5),
+    criticalSol: num('CRITICAL_BALANCE_SOL', 0.05),
+  };
+}
+
+/**
+ * Network fees paid for one claim: the base fee for every signature across
+ * the buffer, chunk, finalize and claim transactions, plus the claim's
+ * priority fee. Buffer rent is excluded since it is refunded on close.
+ */
+export function claimFeeLamports(signatures: number, priorityMicroLamports: number, computeUnits: number): number {
+  return signatures * LAMPORTS_PER_SIGNATURE + Math.ceil((priorityMicroLamports * computeUnits) / 1_000_000);
+}
+
+export class RelayerMetrics {
+  private readonly startedAt = new Date().toISOString();
+  private inFlight = 0;
+  private maxInFlight = 0;
+  private claimsByCode = new Map<string, number>();
+  private computeUnits = 0;
+  private feeLamports = 0n;
+  private succeeded = 0;
+  private balanceLamports: number | null = null;
+  private balanceCheckedAt: string | null = null;
+  private alert: BalanceAlert | null = null;
+
+  constructor(private thresholds: BalanceThresholds) {}
+
+  /** Count a claim as in flight; call the returned function once with its result code. */
+  claimStarted(): (code: string) => void {
+    this.inFlight += 1;
+    this.maxInFlight = Math.max(this.maxInFlight, this.inFlight);
+    let finished = false;
+    return (code: string) => {
+      if (finished) return;
+      finished = true;
+      this.inFlight -= 1;
+      this.claimsByCode.set(code, (this.claimsByCode.get(code) ?? 0) + 1);
+    };
+  }
+
+  recordCost(computeUnits: number, feeLamports: number): void {
+    this.succeeded += 1;
+    this.computeUnits += computeUnits;
+    this.feeLamports += BigInt(feeLamports);
+  }
+
+  /**
+   * Record a hot wallet balance reading. Returns the alert when the balance
+   * has just crossed into a worse level, so the caller can log it once.
+   */
+  recordBalance(lamports: number): BalanceAlert | null {
+    const sol = lamports / LAMPORTS_PER_SOL;
+    const level = sol < this.thresholds.criticalSol ? 'critical' : sol < this.thresholds.warnSol ? 'warn' : null;
+    const previous = this.alert;
+    this.balanceLamports = lamports;
+    this.balanceCheckedAt = new Date().toISOString();
+
+    if (!level) {
+      this.alert = null;
+      return null;
+    }
+    const threshold = level === 'critical' ? this.thresholds.criticalSol : this.thresholds.warnSol;
+    this.alert = {
+      level,
+      message: `Relayer balance ${sol.toFixed(4)} SOL is below ${threshold} SOL`,
+      since: previous?.level === level ? previous.since : this.balanceCheckedAt,
+    };
+    return previous?.level === level || (previous?.level === 'critical' && level === 'warn') ? null : this.alert;
+  }
+
+  snapshot(): MetricsSnapshot {
+    let failed = 0;
+    for (const [code, count] of this.claimsByCode) {
+      if (code !== CLAIM_OK) failed += count;
+    }
+    return {
+      startedAt: this.startedAt,
+      inFlight: this.inFlight,
+      maxInFlight: this.maxInFlight,
+      claimsByCode: Object.fromEntries(this.claimsByCode),
+      succeeded: this.succeeded,
+      failed,
+      avgComputeUnits: this.succeeded ? Math.round(this.computeUnits / this.succeeded) : 0,
+      avgFeeLamports: this.succeeded ? Number(this.feeLamports / BigInt(this.succeeded)) : 0,
+      totalFeeLamports: this.feeLamports.toString(),
+      balanceLamports: this.balanceLamports,
+      balanceCheckedAt: this.balanceCheckedAt,
+      alert: this.alert,
+    };
+  }
+
+  /** Prometheus text exposition format (version 0.0.4). */
+  prometheus(extra: { deadLettered: number; pools: number }): string {
+    const s = this.snapshot();
+    const lines: string[] = [];
+    const metric = (name: string, type: 'counter' | 'gauge', help: string, samples: Array<[string, number | string]>) => {
+      lines.push(`# HELP ${name} ${help}`, `# TYPE ${name} ${type}`);
+      for (const [labels, value] of samples) lines.push(`${name}${labels} ${value}`);
+    };
+
+    metric('murkl_relayer_claims_in_flight', 'gauge', 'Claims currently being processed', [['', s.inFlight]]);
+    metric(
+      'murkl_relayer_claims_total',
+      'counter',
+      'Claims handled, by result code',
+      Object.entries(s.claimsByCode).map(([code, n]) => [`{code="${code}"}`, n]),
+    );
+    metric('murkl_relayer_claim_compute_units_sum', 'counter', 'Compute units used by successful claims', [['', this.computeUnits]]);
+    metric('murkl_relayer_claim_fee_lamports_sum', 'counter', 'Network fees paid for successful claims', [['', s.totalFeeLamports]]);
+    metric('murkl_relayer_claim_cost_count', 'counter', 'Successful claims with recorded cost', [['', s.succeeded]]);
+    metric('murkl_relayer_dead_lettered', 'gauge', 'Claims currently dead-lettered', [['', extra.deadLettered]]);
+    metric('murkl_relayer_pools', 'gauge', 'Pools served', [['', extra.pools]]);
+    if (s.balanceLamports !== null) {
+      metric('murkl_relayer_balance_lamports', 'gauge', 'Hot wallet balance', [['', s.balanceLamports]]);
+    }
+    metric('murkl_relayer_balance_alert', 'gauge', 'Hot wallet balance alert (0 ok, 1 warn, 2 critical)', [
+      ['', s.alert ? (s.alert.level === 'critical' ? 2 : 1) : 0],
+    ]);
+    return lines.join('\n') + '\n';
+  }
+
+  /** Poll the hot wallet balance every `intervalMs`, logging newly raised alerts. */
+  watchBalance(
+    connection: Connection,
+    wallet: PublicKey,
+    intervalMs: number,
+    onAlert: (alert: BalanceAlert) => void,
+    onError: (error: unknown) => void,
+  ): NodeJS.Timeout {
+    const check = async () => {
+      try {
+        const alert = this.recordBalance(await connection.getBalance(wallet, 'confirmed'));
+        if (alert) onAlert(alert);
+      } catch (e) {
+        onError(e);
+      }
+    };
+    void check();
+    const timer = setInterval(check, intervalMs);
+    timer.unref();
+    return timer;
+  }
+}
+
+const escapeHtml = (s: string) =>
+  s.replace(/[&<>"']/g, (c) => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' })[c]!);
+
+/**
+ * Self-contained status page. Server-rendered and refreshed with a meta tag,
+ * so it needs no script under the relayer's CSP.
+ */
+export function renderStatusPage(s: MetricsSnapshot, extra: { pools: number; deadLettered: number }): string {
+  const rows = Object.entries(s.claimsByCode)
+    .sort(([, a], [, b]) => b - a)
+    .map(([code, n]) => `<tr><td>${escapeHtml(code)}</td><td>${n}</td></tr>`)
+    .join('');
+  const balance = s.balanceLamports === null ? 'unknown' : `${(s.balanceLamports / LAMPORTS_PER_SOL).toFixed(4)} SOL`;
+  const alert = s.alert
+    ? `<p class="${s.alert.level}">⚠️ ${escapeHtml(s.alert.message)} (since ${escapeHtml(s.alert.since)})</p>`
+    : '<p class="ok">Balance OK</p>';
+
+  return `<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="15">
+<title>Murkl Relayer Status</title>
+<style>
+body { font-family: system-ui, sans-serif; max-width: 40rem; margin: 2rem auto; color: #222; }
+table { border-collapse: collapse; width: 100%; }
+td { border-bottom: 1px solid #ddd; padding: .3rem .5rem; }
+.ok { color: #1a7f37; } .warn { color: #9a6700; } .critical { color: #cf222e; font-weight: bold; }
+</style>
+</head>
+<body>
+<h1>🐈‍⬛ Murkl Relayer</h1>
+${alert}
+<table>
+<tr><td>Balance</td><td>${balance}</td></tr>
+<tr><td>Pools served</td><td>${extra.pools}</td></tr>
+<tr><td>Claims in flight</td><td>${s.inFlight} (peak ${s.maxInFlight})</td></tr>
+<tr><td>Succeeded / failed</td><td>${s.succeeded} / ${s.failed}</td></tr>
+<tr><td>Dead-lettered</td><td>${extra.deadLettered}</td></tr>
+<tr><td>Avg compute units</td><td>${s.avgComputeUnits}</td></tr>
+<tr><td>Avg network fee</td><td>${s.avgFeeLamports} lamports</td></tr>
+<tr><td>Up since</td><td>${escapeHtml(s.startedAt)}</td></tr>
+</table>
+<h2>Claims by result</h2>
+<table>${rows || '<tr><td>No claims yet</td></tr>'}</table>
+</body>
+</html>
+`;
+}
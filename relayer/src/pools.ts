@@ -0,0 +1,220 @@
+/**
+ * Multi-pool routing
+ *
+ * Discovers every Pool account owned by the murkl program, applies per-pool
+ * relayer fee configuration and keeps per-pool claim metrics. Claims are
+ * routed by the pool pubkey in the request; unknown pools are refused.
+ */
+
+import { Connection, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+
+// Pool layout: [8 discriminator][32 admin][32 token_mint][32 vault][32 merkle_root]
+//              [8 leaf_count][8 min_deposit][2 max_relayer_fee_bps]
+//              [1 + 0|32 compliance_hook: Option<Pubkey>]
+//              [1 + 0|32 required_verifier_params_hash: Option<[u8; 32]>][1 paused][1 bump]
+//              [1 path_claims_enabled][8 max_proof_age_slots][1 stats_enabled]
+//              [8 epoch_duration_secs][1 minimal_events][8 event_seq]
+//              [30 * 32 root_history][1 root_cursor][1 root_history_window]
+//              [1 pool_kind][1 nullifier_shard_bits]
+const POOL_OFFSET_MINT = 40;
+const POOL_OFFSET_VAULT = 72;
+const POOL_OFFSET_LEAF_COUNT = 136;
+const POOL_OFFSET_MAX_FEE_BPS = 152;
+const POOL_OFFSET_COMPLIANCE_HOOK = 154;
+const POOL_MIN_SIZE = 157;
+/** nullifier_shard_bits, counted from `paused` */
+const POOL_PAUSED_TO_SHARD_BITS = 992;
+
+// DepositRecord: [8 discriminator][32 pool][32 commitment][8 amount][8 leaf_index]...
+// Subscription:  [8 discriminator][32 pool][32 commitment][8 leaf_index]...
+const RECORD_OFFSET_POOL = 8;
+const RECORD_OFFSET_COMMITMENT = 40;
+const LEAF_RECORDS = [
+  { name: 'DepositRecord', leafIndexOffset: 80 },
+  { name: 'Subscription', leafIndexOffset: 72 },
+];
+
+function accountDiscriminator(name: string): Buffer {
+  return crypto.createHash('sha256').update(`account:${name}`).digest().slice(0, 8);
+}
+
+export interface PoolMetrics {
+  claimsSubmitted: number;
+  claimsSucceeded: number;
+  claimsFailed: number;
+  /** Sum of relayer fees earned, in token base units */
+  feesEarned: string;
+  lastClaimAt: string | null;
+}
+
+export interface PoolEntry {
+  address: string;
+  tokenMint: string;
+  vault: string;
+  leafCount: string;
+  paused: boolean;
+  /** Program the pool CPIs before payout, if any */
+  complianceHook: string | null;
+  /** Nullifier registry size (2^bits shards); 0 = NullifierRecord PDAs */
+  nullifierShardBits: number;
+  /** Max fee the pool allows on-chain */
+  onChainMaxFeeBps: number;
+  /** Max fee this relayer accepts for the pool (min of config and on-chain) */
+  maxFeeBps: number;
+}
+
+/**
+ * Parse `POOL_FEES` (`<pool>:<bps>,<pool>:<bps>`) into per-pool overrides.
+ */
+export function parsePoolFees(raw: string | undefined): Map<string, number> {
+  const fees = new Map<string, number>();
+  if (!raw) return fees;
+  for (const item of raw.split(',').map((s) => s.trim()).filter(Boolean)) {
+    const [pool, bps] = item.split(':');
+    const value = Number(bps);
+    try {
+      new PublicKey(pool);
+    } catch {
+      throw new Error(`Invalid pool in POOL_FEES: ${pool}`);
+    }
+    if (!Number.isInteger(value) || value < 0 || value > 10_000) {
+      throw new Error(`Invalid fee in POOL_FEES for ${pool}: ${bps}`);
+    }
+    fees.set(pool, value);
+  }
+  return fees;
+}
+
+export class PoolRouter {
+  private pools = new Map<string, PoolEntry>();
+  private metrics = new Map<string, PoolMetrics>();
+  private lastRefresh = 0;
+
+  constructor(
+    private connection: Connection,
+    private programId: PublicKey,
+    private defaultMaxFeeBps: number,
+    private feeOverrides: Map<string, number>,
+    private refreshMs: number,
+  ) {}
+
+  /** Re-scan all Pool accounts owned by the program. */
+  async refresh(): Promise<number> {
+    const accounts = await this.connection.getProgramAccounts(this.programId, {
+      filters: [{ memcmp: { offset: 0, bytes: accountDiscriminator('Pool').toString('base64'), encoding: 'base64' } }],
+    });
+
+    const next = new Map<string, PoolEntry>();
+    for (const { pubkey, account } of accounts) {
+      const entry = this.parsePool(pubkey, account.data);
+      if (entry) next.set(entry.address, entry);
+    }
+    this.pools = next;
+    this.lastRefresh = Date.now();
+    return next.size;
+  }
+
+  /**
+   * Look up a pool, re-scanning once on a miss (or when stale) so newly
+   * created pools are served without a restart.
+   */
+  async resolve(pool: PublicKey): Promise<PoolEntry | null> {
+    const key = pool.toBase58();
+    const stale = Date.now() - this.lastRefresh > this.refreshMs;
+    if (stale || !this.pools.has(key)) {
+      await this.refresh();
+    }
+    return this.pools.get(key) ?? null;
+  }
+
+  /**
+   * Every commitment in `pool`'s tree in leaf order, read from its deposit
+   * and subscription records. `null` while a leaf has no record yet (an RPC
+   * behind the deposit), as any path built across the gap is wrong.
+   */
+  async leaves(pool: PublicKey): Promise<Buffer[] | null> {
+    const leaves: Buffer[] = [];
+    for (const { name, leafIndexOffset } of LEAF_RECORDS) {
+      const accounts = await this.connection.getProgramAccounts(this.programId, {
+        filters: [
+          { memcmp: { offset: 0, bytes: accountDiscriminator(name).toString('base64'), encoding: 'base64' } },
+          { memcmp: { offset: RECORD_OFFSET_POOL, bytes: pool.toBase58() } },
+        ],
+      });
+      for (const { account } of accounts) {
+        const leafIndex = Number(account.data.readBigUInt64LE(leafIndexOffset));
+        leaves[leafIndex] = account.data.slice(RECORD_OFFSET_COMMITMENT, RECORD_OFFSET_COMMITMENT + 32);
+      }
+    }
+    return Array.from(leaves).every((leaf) => leaf !== undefined) ? leaves : null;
+  }
+
+  list(): Array<PoolEntry & { metrics: PoolMetrics }> {
+    return Array.from(this.pools.values()).map((p) => ({
+      ...p,
+      metrics: this.metricsFor(p.address),
+    }));
+  }
+
+  size(): number {
+    return this.pools.size;
+  }
+
+  recordSubmitted(pool: string): void {
+    const m = this.metricsFor(pool);
+    m.claimsSubmitted += 1;
+    m.lastClaimAt = new Date().toISOString();
+  }
+
+  recordSucceeded(pool: string, fee: bigint): void {
+    const m = this.metricsFor(pool);
+    m.claimsSucceeded += 1;
+    m.feesEarned = (BigInt(m.feesEarned) + fee).toString();
+  }
+
+  recordFailed(pool: string): void {
+    this.metricsFor(pool).claimsFailed += 1;
+  }
+
+  private metricsFor(pool: string): PoolMetrics {
+    let m = this.metrics.get(pool);
+    if (!m) {
+      m = { claimsSubmitted: 0, claimsSucceeded: 0, claimsFailed: 0, feesEarned: '0', lastClaimAt: null };
+      this.metrics.set(pool, m);
+    }
+    return m;
+  }
+
+  private parsePool(pubkey: PublicKey, data: Buffer): PoolEntry | null {
+    if (data.length < POOL_MIN_SIZE) return null;
+    const address = pubkey.toBase58();
+    const onChainMaxFeeBps = data.readUInt16LE(POOL_OFFSET_MAX_FEE_BPS);
+    const configured = this.feeOverrides.get(address) ?? this.defaultMaxFeeBps;
+
+    let offset = POOL_OFFSET_COMPLIANCE_HOOK;
+    let complianceHook: string | null = null;
+    if (data[offset++] === 1) {
+      if (data.length < offset + 32 + 2) return null;
+      complianceHook = new PublicKey(data.slice(offset, offset + 32)).toBase58();
+      offset += 32;
+    }
+    if (data[offset++] === 1) {
+      if (data.length < offset + 32 + 2) return null;
+      offset += 32; // required_verifier_params_hash
+    }
+
+    return {
+      address,
+      tokenMint: new PublicKey(data.slice(POOL_OFFSET_MINT, POOL_OFFSET_MINT + 32)).toBase58(),
+      vault: new PublicKey(data.slice(POOL_OFFSET_VAULT, POOL_OFFSET_VAULT + 32)).toBase58(),
+      leafCount: data.readBigUInt64LE(POOL_OFFSET_LEAF_COUNT).toString(),
+      paused: data[offset] === 1,
+      complianceHook,
+      // Pools not yet grown to this field have no registry
+      nullifierShardBits: data[offset + POOL_PAUSED_TO_SHARD_BITS] ?? 0,
+      onChainMaxFeeBps,
+      maxFeeBps: Math.min(configured, onChainMaxFeeBps),
+    };
+  }
+}
@@ -0,0 +1,274 @@
+/**
+ * Transaction retry policy
+ *
+ * Rebroadcasts relayer transactions with:
+ * - Escalating priority fees (ComputeBudget setComputeUnitPrice)
+ * - A fresh blockhash per attempt
+ * - An idempotency probe before each retry (e.g. nullifier PDA exists)
+ *
+ * Claims that keep failing across requests are parked in a bounded
+ * dead-letter queue so they stop burning relayer SOL and show up in /status.
+ */
+
+import {
+  AddressLookupTableAccount,
+  ComputeBudgetProgram,
+  Connection,
+  MessageV0,
+  Signer,
+  Transaction,
+  TransactionInstruction,
+  VersionedTransaction,
+} from '@solana/web3.js';
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+export interface RetryPolicy {
+  maxAttempts: number;
+  baseFeeMicroLamports: number;
+  feeMultiplier: number;
+  maxFeeMicroLamports: number;
+  backoffMs: number;
+  /** Failed claim requests (per nullifier) before dead-lettering */
+  deadLetterAfter: number;
+  deadLetterMax: number;
+  /** How long a dead-lettered claim is refused before it may be retried */
+  deadLetterTtlMs: number;
+}
+
+function envInt(name: string, fallback: number, min: number): number {
+  const raw = process.env[name];
+  if (raw === undefined || raw === '') return fallback;
+  const value = Number(raw);
+  if (!Number.isFinite(value) || value < min) {
+    console.error(`❌ Invalid ${name}: ${raw}`);
+    process.exit(1);
+  }
+  return Math.floor(value);
+}
+
+export function loadRetryPolicy(): RetryPolicy {
+  const multiplier = Number(process.env.RETRY_FEE_MULTIPLIER || '2');
+  if (!Number.isFinite(multiplier) || multiplier < 1) {
+    console.error('❌ Invalid RETRY_FEE_MULTIPLIER (must be >= 1)');
+    process.exit(1);
+  }
+
+  return {
+    maxAttempts: envInt('RETRY_MAX_ATTEMPTS', 4, 1),
+    baseFeeMicroLamports: envInt('RETRY_BASE_FEE_MICROLAMPORTS', 1_000, 0),
+    feeMultiplier: multiplier,
+    maxFeeMicroLamports: envInt('RETRY_MAX_FEE_MICROLAMPORTS', 1_000_000, 0),
+    backoffMs: envInt('RETRY_BACKOFF_MS', 1_500, 0),
+    deadLetterAfter: envInt('DLQ_AFTER_FAILURES', 3, 1),
+    deadLetterMax: envInt('DLQ_MAX_ENTRIES', 1_000, 1),
+    deadLetterTtlMs: envInt('DLQ_TTL_MS', 60 * 60 * 1000, 0),
+  };
+}
+
+/** Priority fee for a 0-based attempt, capped at the policy maximum. */
+export function priorityFeeForAttempt(policy: RetryPolicy, attempt: number): number {
+  const fee = policy.baseFeeMicroLamports * Math.pow(policy.feeMultiplier, attempt);
+  return Math.min(Math.floor(fee), policy.maxFeeMicroLamports);
+}
+
+// ============================================================================
+// Send with retry
+// ============================================================================
+
+export type RetryLogger = (
+  level: 'debug' | 'info' | 'warn' | 'error',
+  message: string,
+  meta?: Record<string, unknown>,
+) => void;
+
+export interface SendOptions {
+  label: string;
+  requestId: string;
+  /** Returns true if the effect already landed (skip rebroadcast). */
+  alreadyLanded?: () => Promise<boolean>;
+  log?: RetryLogger;
+  /** Compute unit limit to request; the runtime default if unset */
+  computeUnits?: number;
+  /** Send as a v0 transaction compiled against these lookup tables */
+  lookupTables?: AddressLookupTableAccount[];
+}
+
+export interface SendResult {
+  /** Confirmed signature, or null if an earlier broadcast had already landed */
+  signature: string | null;
+  attempts: number;
+  priorityFee: number;
+}
+
+/** Errors that will fail identically on every rebroadcast. */
+function isPermanentError(message: string): boolean {
+  return (
+    message.includes('insufficient funds') ||
+    message.includes('custom program error') ||
+    message.includes('InstructionError')
+  );
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+export async function sendWithRetry(
+  connection: Connection,
+  instructions: TransactionInstruction[],
+  signers: Signer[],
+  policy: RetryPolicy,
+  opts: SendOptions,
+): Promise<SendResult> {
+  const log = opts.log ?? (() => {});
+  let lastError: unknown;
+
+  for (let attempt = 0; attempt < policy.maxAttempts; attempt++) {
+    const priorityFee = priorityFeeForAttempt(policy, attempt);
+
+    if (attempt > 0 && opts.alreadyLanded && (await opts.alreadyLanded())) {
+      log('info', `${opts.label} already landed, skipping rebroadcast`, {
+        requestId: opts.requestId,
+        attempt,
+      });
+      return { signature: null, attempts: attempt, priorityFee };
+    }
+
+    const ixs: TransactionInstruction[] = [];
+    if (opts.computeUnits) {
+      ixs.push(ComputeBudgetProgram.setComputeUnitLimit({ units: opts.computeUnits }));
+    }
+    if (priorityFee > 0) {
+      ixs.push(ComputeBudgetProgram.setComputeUnitPrice({ microLamports: priorityFee }));
+    }
+    ixs.push(...instructions);
+
+    const { blockhash, lastValidBlockHeight } = await connection.getLatestBlockhash();
+    let raw: Uint8Array;
+    if (opts.lookupTables) {
+      const message = MessageV0.compile({
+        payerKey: signers[0].publicKey,
+        instructions: ixs,
+        recentBlockhash: blockhash,
+        addressLookupTableAccounts: opts.lookupTables,
+      });
+      const tx = new VersionedTransaction(message);
+      tx.sign(signers);
+      raw = tx.serialize();
+    } else {
+      const tx = new Transaction().add(...ixs);
+      tx.recentBlockhash = blockhash;
+      tx.feePayer = signers[0].publicKey;
+      tx.sign(...signers);
+      raw = tx.serialize();
+    }
+
+    try {
+      const signature = await connection.sendRawTransaction(raw);
+      const confirmation = await connection.confirmTransaction(
+        { signature, blockhash, lastValidBlockHeight },
+        'confirmed',
+      );
+      if (confirmation.value.err) {
+        throw new Error(`Transaction failed: ${JSON.stringify(confirmation.value.err)}`);
+      }
+      if (attempt > 0) {
+        log('info', `${opts.label} confirmed after retry`, {
+          requestId: opts.requestId,
+          attempts: attempt + 1,
+          priorityFee,
+        });
+      }
+      return { signature, attempts: attempt + 1, priorityFee };
+    } catch (e: unknown) {
+      lastError = e;
+      const message = e instanceof Error ? e.message : String(e);
+      log('warn', `${opts.label} attempt ${attempt + 1}/${policy.maxAttempts} failed`, {
+        requestId: opts.requestId,
+        priorityFee,
+        error: message,
+      });
+      if (isPermanentError(message)) break;
+      if (attempt + 1 < policy.maxAttempts) await sleep(policy.backoffMs * (attempt + 1));
+    }
+  }
+
+  // A broadcast may have landed after its confirmation timed out.
+  if (opts.alreadyLanded && (await opts.alreadyLanded())) {
+    return { signature: null, attempts: policy.maxAttempts, priorityFee: 0 };
+  }
+  throw lastError instanceof Error ? lastError : new Error(String(lastError));
+}
+
+// ============================================================================
+// Dead-letter queue
+// ============================================================================
+
+export interface DeadLetterEntry {
+  nullifier: string;
+  pool: string;
+  failures: number;
+  lastError: string;
+  firstFailedAt: string;
+  lastFailedAt: string;
+}
+
+/**
+ * Tracks failing claims by nullifier. After `deadLetterAfter` failures the
+ * claim is dead-lettered and new submissions for it are refused until
+ * `deadLetterTtlMs` has passed since its last failure.
+ */
+export class DeadLetterQueue {
+  private failures = new Map<string, DeadLetterEntry>();
+
+  constructor(private policy: RetryPolicy) {}
+
+  recordFailure(nullifier: string, pool: string, error: string): DeadLetterEntry {
+    const now = new Date().toISOString();
+    const entry = this.failures.get(nullifier) ?? {
+      nullifier,
+      pool,
+      failures: 0,
+      lastError: '',
+      firstFailedAt: now,
+      lastFailedAt: now,
+    };
+    entry.failures += 1;
+    entry.lastError = error.slice(0, 200);
+    entry.lastFailedAt = now;
+
+    // Re-insert to keep Map order = least recently failed first
+    this.failures.delete(nullifier);
+    if (this.failures.size >= this.policy.deadLetterMax) {
+      const oldest = this.failures.keys().next().value as string | undefined;
+      if (oldest) this.failures.delete(oldest);
+    }
+    this.failures.set(nullifier, entry);
+    return entry;
+  }
+
+  recordSuccess(nullifier: string): void {
+    this.failures.delete(nullifier);
+  }
+
+  isDeadLettered(nullifier: string): boolean {
+    const entry = this.failures.get(nullifier);
+    if (!entry || entry.failures < this.policy.deadLetterAfter) return false;
+    if (Date.now() - Date.parse(entry.lastFailedAt) > this.policy.deadLetterTtlMs) {
+      this.failures.delete(nullifier);
+      return false;
+    }
+    return true;
+  }
+
+  entries(): DeadLetterEntry[] {
+    return Array.from(this.failures.values()).filter(
+      (e) => e.failures >= this.policy.deadLetterAfter,
+    );
+  }
+
+  pendingFailures(): number {
+    return this.failures.size;
+  }
+}
@@ -0,0 +1,84 @@
+/**
+ * Token display metadata
+ *
+ * Resolves decimals (from the mint) and symbol (from Metaplex metadata) per
+ * mint so logs and API responses can show "10.5 USDC" instead of raw base
+ * units, and so human amounts are converted with the mint's real decimals.
+ */
+
+import { Connection, PublicKey } from '@solana/web3.js';
+import { unpackMint } from '@solana/spl-token';
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+
+export interface TokenInfo {
+  mint: string;
+  decimals: number;
+  /** Empty when the mint has no Metaplex metadata */
+  symbol: string;
+}
+
+/** Raw base units to a display string, trailing fractional zeros dropped. */
+export function formatAmount(amount: bigint, decimals: number, symbol?: string): string {
+  const scale = 10n ** BigInt(decimals);
+  const whole = (amount / scale).toString();
+  const frac = decimals > 0 ? (amount % scale).toString().padStart(decimals, '0').replace(/0+$/, '') : '';
+  const value = frac ? `${whole}.${frac}` : whole;
+  return symbol ? `${value} ${symbol}` : value;
+}
+
+/** Metaplex metadata: [1 key][32 update_authority][32 mint][4+n name][4+n symbol] */
+function parseMetaplexSymbol(data: Buffer): string {
+  let offset = 1 + 32 + 32;
+  if (offset + 4 > data.length) return '';
+  offset += 4 + data.readUInt32LE(offset);
+  if (offset + 4 > data.length) return '';
+  const len = data.readUInt32LE(offset);
+  offset += 4;
+  if (offset + len > data.length) return '';
+  return data.slice(offset, offset + len).toString('utf8').replace(/\0+$/, '').trim();
+}
+
+export class TokenInfoCache {
+  private cache = new Map<string, Promise<TokenInfo>>();
+
+  constructor(private connection: Connection) {}
+
+  /** Decimals and symbol for a mint; mint metadata is cached for the process lifetime. */
+  get(mint: PublicKey | string): Promise<TokenInfo> {
+    const key = typeof mint === 'string' ? mint : mint.toBase58();
+    let pending = this.cache.get(key);
+    if (!pending) {
+      pending = this.fetch(new PublicKey(key));
+      pending.catch(() => this.cache.delete(key));
+      this.cache.set(key, pending);
+    }
+    return pending;
+  }
+
+  /** Format base units of `mint`, falling back to the raw value if the mint can't be read. */
+  async format(mint: PublicKey | string, amount: bigint): Promise<string> {
+    try {
+      const { decimals, symbol } = await this.get(mint);
+      return formatAmount(amount, decimals, symbol);
+    } catch {
+      return amount.toString();
+    }
+  }
+
+  private async fetch(mint: PublicKey): Promise<TokenInfo> {
+    const mintInfo = await this.connection.getAccountInfo(mint, 'confirmed');
+    if (!mintInfo) throw new Error(`Mint ${mint.toBase58()} not found`);
+    const { decimals } = unpackMint(mint, mintInfo, mintInfo.owner);
+
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+    const metadataInfo = await this.connection.getAccountInfo(metadata, 'confirmed');
+    const symbol = metadataInfo?.owner.equals(TOKEN_METADATA_PROGRAM_ID)
+      ? parseMetaplexSymbol(metadataInfo.data)
+      : '';
+    return { mint: mint.toBase58(), decimals, symbol };
+  }
+}
@@ -0,0 +1,296 @@
+/**
+ * Pipelined proof upload
+ *
+ * Writes proof chunks into a stark-verifier buffer with up to
+ * `concurrency` upload_chunk transactions in flight. Each chunk carries its
+ * own offset, so landing order does not affect the buffer contents.
+ *
+ * The rolling upload digest is order-dependent
+ * (`digest' = keccak(digest || offset LE || chunk)`), so once every chunk has
+ * confirmed we read the landed upload_chunk transactions back in ledger order,
+ * refold the digest locally and compare it with the one in the buffer header.
+ * A mismatch means the buffer saw writes we did not account for.
+ */
+
+import { Connection, PublicKey, Signer, TransactionInstruction } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import bs58 from 'bs58';
+import { RetryLogger, RetryPolicy, sendWithRetry } from './retry';
+
+// @ts-ignore - js-sha3 doesn't have types
+const { keccak256 } = require('js-sha3');
+
+// Buffer header: [32 owner][4 size][4 expected_size][1 finalized]
+//                [32 commitment][32 nullifier][32 merkle_root][32 recipient]
+//                [32 upload_digest][8 finalized_slot][32 upload_delegate]
+//                [8 created_at][8 expiry_slots][160 upload bitmap]
+//                [32 expected_keccak] then proof data
+const OFFSET_SIZE = 32;
+const OFFSET_UPLOAD_DIGEST = 169;
+export const PROOF_BUFFER_HEADER_SIZE = 449;
+/** Uploads start and end on multiples of this, except at the end of the proof */
+export const UPLOAD_CHUNK_ALIGN = 64;
+
+const UPLOAD_CHUNK_DISCRIMINATOR = crypto
+  .createHash('sha256')
+  .update('global:upload_chunk')
+  .digest()
+  .slice(0, 8);
+
+const DELEGATE_UPLOADER_DISCRIMINATOR = crypto
+  .createHash('sha256')
+  .update('global:delegate_uploader')
+  .digest()
+  .slice(0, 8);
+
+const CLOSE_PROOF_BUFFER_DISCRIMINATOR = crypto
+  .createHash('sha256')
+  .update('global:close_proof_buffer')
+  .digest()
+  .slice(0, 8);
+
+export interface ChunkPlan {
+  index: number;
+  offset: number;
+  data: Buffer;
+}
+
+export interface UploadOptions {
+  programId: PublicKey;
+  chunkSize: number;
+  /** Max upload_chunk transactions in flight; 1 uploads sequentially */
+  concurrency: number;
+  requestId: string;
+  log?: RetryLogger;
+}
+
+export interface UploadResult {
+  chunks: number;
+  /** Rolling digest read back from the buffer (hex) */
+  digest: string;
+  elapsedMs: number;
+}
+
+/** Split a proof into fixed-size chunks at independent offsets. */
+export function planChunks(proof: Buffer, chunkSize: number): ChunkPlan[] {
+  if (chunkSize <= 0 || chunkSize % UPLOAD_CHUNK_ALIGN !== 0) {
+    throw new Error(`Chunk size ${chunkSize} is not a multiple of ${UPLOAD_CHUNK_ALIGN}`);
+  }
+  const chunks: ChunkPlan[] = [];
+  for (let offset = 0, index = 0; offset < proof.length; offset += chunkSize, index++) {
+    chunks.push({ index, offset, data: proof.slice(offset, offset + chunkSize) });
+  }
+  return chunks;
+}
+
+/** One step of the on-chain rolling upload digest. */
+export function foldUploadDigest(prev: Buffer, offset: number, chunk: Buffer): Buffer {
+  const offsetLe = Buffer.alloc(4);
+  offsetLe.writeUInt32LE(offset);
+  return Buffer.from(keccak256(Buffer.concat([prev, offsetLe, chunk])), 'hex');
+}
+
+export function uploadChunkInstruction(
+  programId: PublicKey,
+  buffer: PublicKey,
+  uploader: PublicKey,
+  offset: number,
+  chunk: Buffer,
+): TransactionInstruction {
+  const header = Buffer.alloc(8);
+  header.writeUInt32LE(offset, 0);
+  header.writeUInt32LE(chunk.length, 4);
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: true },
+      { pubkey: uploader, isSigner: true, isWritable: false },
+    ],
+    data: Buffer.concat([UPLOAD_CHUNK_DISCRIMINATOR, header, chunk]),
+  });
+}
+
+/**
+ * Let `delegate` sign upload_chunk for a buffer (e.g. an upload worker key).
+ * Finalize and close stay owner-only; `PublicKey.default` revokes.
+ */
+export function delegateUploaderInstruction(
+  programId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+  delegate: PublicKey,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: false },
+    ],
+    data: Buffer.concat([DELEGATE_UPLOADER_DISCRIMINATOR, delegate.toBuffer()]),
+  });
+}
+
+/** Zero a proof buffer and refund its rent to the owner. */
+export function closeProofBufferInstruction(
+  programId: PublicKey,
+  buffer: PublicKey,
+  owner: PublicKey,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: buffer, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: true, isWritable: true },
+    ],
+    data: CLOSE_PROOF_BUFFER_DISCRIMINATOR,
+  });
+}
+
+/** Proof buffers whose header names `owner`, found by scanning the verifier's accounts. */
+export async function findOwnedProofBuffers(
+  connection: Connection,
+  programId: PublicKey,
+  owner: PublicKey,
+): Promise<PublicKey[]> {
+  const accounts = await connection.getProgramAccounts(programId, {
+    commitment: 'confirmed',
+    dataSlice: { offset: 0, length: 0 },
+    filters: [{ memcmp: { offset: 0, bytes: owner.toBase58() } }],
+  });
+  return accounts.map(({ pubkey }) => pubkey);
+}
+
+/** Decode upload_chunk instruction data, or null for any other instruction. */
+function decodeUploadChunk(data: Buffer): { offset: number; chunk: Buffer } | null {
+  if (data.length < 16 || !data.slice(0, 8).equals(UPLOAD_CHUNK_DISCRIMINATOR)) return null;
+  const offset = data.readUInt32LE(8);
+  const len = data.readUInt32LE(12);
+  if (data.length < 16 + len) return null;
+  return { offset, chunk: data.slice(16, 16 + len) };
+}
+
+/**
+ * Upload `proof` into `buffer` with pipelined chunk transactions, then check
+ * the buffer contents and rolling digest against what actually landed.
+ */
+export async function uploadProofPipelined(
+  connection: Connection,
+  buffer: PublicKey,
+  owner: Signer,
+  proof: Buffer,
+  policy: RetryPolicy,
+  opts: UploadOptions,
+): Promise<UploadResult> {
+  const log = opts.log ?? (() => {});
+  const started = Date.now();
+  const chunks = planChunks(proof, opts.chunkSize);
+  const concurrency = Math.max(1, Math.min(opts.concurrency, chunks.length));
+
+  log('debug', 'Writing chunks', {
+    requestId: opts.requestId,
+    numChunks: chunks.length,
+    chunkSize: opts.chunkSize,
+    concurrency,
+  });
+
+  // Signature that confirmed each chunk, so the digest check can map landed
+  // transactions back to chunks without refetching them.
+  const landed = new Map<string, ChunkPlan>();
+  let next = 0;
+  let failed: unknown = null;
+
+  const worker = async () => {
+    while (failed === null && next < chunks.length) {
+      const chunk = chunks[next++];
+      const ix = uploadChunkInstruction(opts.programId, buffer, owner.publicKey, chunk.offset, chunk.data);
+      try {
+        // Rewriting a chunk at the same offset is harmless, so no landed probe
+        const { signature } = await sendWithRetry(connection, [ix], [owner], policy, {
+          label: `Chunk ${chunk.index + 1}`,
+          requestId: opts.requestId,
+          log,
+        });
+        if (signature) landed.set(signature, chunk);
+        log('debug', `Chunk ${chunk.index + 1}/${chunks.length} written`, {
+          requestId: opts.requestId,
+          offset: chunk.offset,
+        });
+      } catch (e) {
+        log('error', `Chunk ${chunk.index + 1} failed`, { requestId: opts.requestId, error: String(e) });
+        failed = failed ?? e;
+      }
+    }
+  };
+  await Promise.all(Array.from({ length: concurrency }, worker));
+  if (failed !== null) {
+    throw failed instanceof Error ? failed : new Error(String(failed));
+  }
+
+  const digest = await checkUploadConsistency(connection, buffer, proof, landed, opts);
+  const elapsedMs = Date.now() - started;
+  log('info', 'Chunks written', {
+    requestId: opts.requestId,
+    numChunks: chunks.length,
+    concurrency,
+    elapsedMs,
+  });
+  return { chunks: chunks.length, digest: digest.toString('hex'), elapsedMs };
+}
+
+/**
+ * Refold the rolling digest over the upload_chunk transactions that landed on
+ * the buffer, in ledger order, and require it to match the header. Also checks
+ * the written size and the proof bytes themselves.
+ */
+async function checkUploadConsistency(
+  connection: Connection,
+  buffer: PublicKey,
+  proof: Buffer,
+  landed: Map<string, ChunkPlan>,
+  opts: UploadOptions,
+): Promise<Buffer> {
+  const account = await connection.getAccountInfo(buffer, 'confirmed');
+  if (!account || account.data.length < PROOF_BUFFER_HEADER_SIZE + proof.length) {
+    throw new Error('Proof buffer missing or truncated after upload');
+  }
+  const data = account.data;
+  const size = data.readUInt32LE(OFFSET_SIZE);
+  if (size !== proof.length) {
+    throw new Error(`Proof buffer holds ${size} bytes, expected ${proof.length}`);
+  }
+  if (!data.slice(PROOF_BUFFER_HEADER_SIZE, PROOF_BUFFER_HEADER_SIZE + proof.length).equals(proof)) {
+    throw new Error('Proof buffer contents differ from the uploaded proof');
+  }
+  const onChainDigest = Buffer.from(data.slice(OFFSET_UPLOAD_DIGEST, OFFSET_UPLOAD_DIGEST + 32));
+
+  // Newest first; the buffer is fresh per claim so one page covers it.
+  const history = await connection.getSignaturesForAddress(buffer, { limit: 1000 }, 'confirmed');
+  let digest = Buffer.alloc(32);
+  for (const entry of history.reverse()) {
+    if (entry.err) continue;
+    const known = landed.get(entry.signature);
+    if (known) {
+      digest = foldUploadDigest(digest, known.offset, known.data);
+      continue;
+    }
+    // A rebroadcast whose confirmation timed out, or the buffer init.
+    const tx = await connection.getTransaction(entry.signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    if (!tx) throw new Error(`Upload transaction ${entry.signature} not found`);
+    const keys = tx.transaction.message.staticAccountKeys;
+    for (const ix of tx.transaction.message.compiledInstructions) {
+      if (!keys[ix.programIdIndex].equals(opts.programId)) continue;
+      const decoded = decodeUploadChunk(Buffer.from(ix.data));
+      if (decoded) digest = foldUploadDigest(digest, decoded.offset, decoded.chunk);
+    }
+  }
+
+  if (!digest.equals(onChainDigest)) {
+    throw new Error(
+      `Upload digest mismatch: buffer has ${bs58.encode(onChainDigest)}, landed chunks fold to ${bs58.encode(digest)}`,
+    );
+  }
+  return onChainDigest;
+}
@@ -39,16 +39,37 @@ async function main() {
   console.log(`  Leaf Count: ${poolInfo.leafCount}`);
   console.log();
   
-  // Step 2: Load WASM and generate proof
+  // Step 2: Get recipient token account; the proof is bound to it
+  const { PublicKey } = await import('@solana/web3.js');
+  const { getAssociatedTokenAddress } = await import('@solana/spl-token');
+  
+  const WSOL_MINT = new PublicKey('So11111111111111111111111111111111111111112');
+  const recipientPubkey = new PublicKey(RECIPIENT);
+  const recipientATA = await getAssociatedTokenAddress(WSOL_MINT, recipientPubkey);
+  
+  console.log(`Recipient ATA: ${recipientATA.toBase58()}`);
+  console.log();
+  
+  // Step 3: Load WASM and generate proof
   console.log('Loading WASM...');
   const wasm = await loadWasm();
   
+  const leavesRes = await fetch(`${RELAYER_URL}/pool-leaves?pool=${POOL}`);
+  const { leaves } = await leavesRes.json() as { leaves: string };
+  const merklePath = wasm.generate_merkle_path(leaves, LEAF_INDEX);
+  if (merklePath === undefined) {
+    console.log(`❌ Leaf ${LEAF_INDEX} is not in the pool leaves`);
+    return;
+  }
+  
   console.log('Generating STARK proof...');
   const bundle = wasm.generate_proof(
     IDENTIFIER,
     PASSWORD,
     LEAF_INDEX,
-    poolInfo.merkleRoot
+    poolInfo.merkleRoot,
+    merklePath,
+    Buffer.from(recipientATA.toBytes()).toString('hex')
   ) as {
     commitment: string;
     nullifier: string;
@@ -74,18 +95,7 @@ async function main() {
   console.log(`  Proof prefix: ${proof.slice(0, 64)}...`);
   console.log();
   
-  // Step 4: Get recipient token account
-  const { PublicKey } = await import('@solana/web3.js');
-  const { getAssociatedTokenAddress } = await import('@solana/spl-token');
-  
-  const WSOL_MINT = new PublicKey('So11111111111111111111111111111111111111112');
-  const recipientPubkey = new PublicKey(RECIPIENT);
-  const recipientATA = await getAssociatedTokenAddress(WSOL_MINT, recipientPubkey);
-  
-  console.log(`Recipient ATA: ${recipientATA.toBase58()}`);
-  console.log();
-  
-  // Step 5: Submit claim
+  // Step 4: Submit claim
   console.log('Submitting claim to relayer...');
   const claimRes = await fetch(`${RELAYER_URL}/claim`, {
     method: 'POST',
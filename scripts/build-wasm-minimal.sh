@@ -0,0 +1,58 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+# Build the minimal wasm bundle and print its size next to the default one.
+#
+# The minimal build drops the `json` feature (serde, serde-wasm-bindgen, hex),
+# so only the Uint8Array API in wasm/src/lib.rs is exported. It uses the
+# `minimal` cargo profile (opt-level z, panic=abort) and runs wasm-opt -Oz on
+# the result. Cargo has no hook to post-process its own wasm output, so the
+# wasm-opt step lives here rather than in a build script.
+#
+# Usage:
+#   bash scripts/build-wasm-minimal.sh             # build wasm/pkg-minimal
+#   bash scripts/build-wasm-minimal.sh --compare   # also build wasm/pkg for the "before" size
+#
+# Requires: rustup target wasm32-unknown-unknown, wasm-bindgen-cli, wasm-opt (binaryen).
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+OUT_DIR="$ROOT_DIR/wasm/pkg-minimal"
+TARGET_DIR="$ROOT_DIR/wasm/target"
+
+for tool in wasm-bindgen wasm-opt; do
+  if ! command -v "$tool" >/dev/null 2>&1; then
+    echo "❌ $tool not found" >&2
+    exit 1
+  fi
+done
+
+if [[ "${1:-}" == "--compare" ]]; then
+  if ! command -v wasm-pack >/dev/null 2>&1; then
+    echo "❌ wasm-pack not found (cargo install wasm-pack)" >&2
+    exit 1
+  fi
+  (cd "$ROOT_DIR/wasm" && wasm-pack build --target web --release)
+fi
+
+(cd "$ROOT_DIR/wasm" && cargo build --target wasm32-unknown-unknown --profile minimal --no-default-features)
+
+rm -rf "$OUT_DIR"
+wasm-bindgen --target web --out-dir "$OUT_DIR" \
+  "$TARGET_DIR/wasm32-unknown-unknown/minimal/murkl_wasm.wasm"
+wasm-opt -Oz --strip-debug --strip-producers \
+  -o "$OUT_DIR/murkl_wasm_bg.wasm" "$OUT_DIR/murkl_wasm_bg.wasm"
+
+report() {
+  local label="$1" artifact="$2"
+  if [[ ! -f "$artifact" ]]; then
+    echo "$label: not built"
+    return
+  fi
+  local raw gz
+  raw=$(wc -c < "$artifact" | tr -d ' ')
+  gz=$(gzip -9 -c "$artifact" | wc -c | tr -d ' ')
+  echo "$label: ${raw} bytes, ${gz} bytes gzipped"
+}
+
+report "default (wasm/pkg)        " "$ROOT_DIR/wasm/pkg/murkl_wasm_bg.wasm"
+report "minimal (wasm/pkg-minimal)" "$OUT_DIR/murkl_wasm_bg.wasm"
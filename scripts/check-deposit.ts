@@ -2,50 +2,14 @@
  * Check deposit commitment on-chain and compare with computed values
  */
 import { Connection, PublicKey } from '@solana/web3.js';
-import { keccak256 } from 'js-sha3';
+import { computeCommitment } from '../relayer/src/commitment';
 
-const M31_PRIME = 0x7FFFFFFF;
 const PROGRAM_ID = new PublicKey('muRkDGaY4yCc6rEYWhmJAnQ1abdCbUJNCr4L1Cmd1UF');
 const POOL_ADDRESS = new PublicKey('8MU3WQzxLDHi6Up2ksk255LWrRm17i7UQ6Hap4zeF3qJ');
 
 // Get leaf index from command line or default to 0
 const leafIndex = parseInt(process.argv[2] || '0', 10);
 
-function hashPassword(password: string): number {
-  const data = new TextEncoder().encode('murkl_password_v1' + password);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  return view.getUint32(0, true) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = new TextEncoder().encode('murkl_identifier_v1' + normalized);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  return view.getUint32(0, true) % M31_PRIME;
-}
-
-function computeCommitment(identifier: string, password: string): Buffer {
-  const idHash = hashIdentifier(identifier);
-  const secret = hashPassword(password);
-  
-  const prefix = new TextEncoder().encode('murkl_m31_hash_v1');
-  const idBuf = new Uint8Array(4);
-  const secretBuf = new Uint8Array(4);
-  
-  new DataView(idBuf.buffer).setUint32(0, idHash, true);
-  new DataView(secretBuf.buffer).setUint32(0, secret, true);
-  
-  const combined = new Uint8Array(prefix.length + 4 + 4);
-  combined.set(prefix, 0);
-  combined.set(idBuf, prefix.length);
-  combined.set(secretBuf, prefix.length + 4);
-  
-  const hashArray = keccak256.arrayBuffer(combined);
-  return Buffer.from(hashArray);
-}
-
 async function main() {
   const connection = new Connection('https://api.devnet.solana.com', 'confirmed');
   
@@ -0,0 +1,56 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+# Build the wasm bundle and fail if it grows past its size budget.
+#
+# murkl-wasm depends on murkl-prover with the `verify` feature only; pulling
+# in `prove` (FRI prover, evaluation cache) shows up here as a size jump.
+#
+# Usage:
+#   bash scripts/check-wasm-size.sh            # build wasm/pkg, then check it
+#   bash scripts/check-wasm-size.sh --no-build # check web/src/wasm as committed
+#
+# Tunables (optional env vars):
+# - MAX_WASM_BYTES (default 262144) raw size of murkl_wasm_bg.wasm
+# - MAX_WASM_GZIP_BYTES (default 98304) gzipped size of murkl_wasm_bg.wasm
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+
+MAX_WASM_BYTES="${MAX_WASM_BYTES:-262144}"
+MAX_WASM_GZIP_BYTES="${MAX_WASM_GZIP_BYTES:-98304}"
+
+if [[ "${1:-}" == "--no-build" ]]; then
+  WASM_OUT_DIR="$ROOT_DIR/web/src/wasm"
+else
+  WASM_OUT_DIR="$ROOT_DIR/wasm/pkg"
+  if ! command -v wasm-pack >/dev/null 2>&1; then
+    echo "❌ wasm-pack not found (cargo install wasm-pack)" >&2
+    exit 1
+  fi
+  (cd "$ROOT_DIR/wasm" && wasm-pack build --target web --release)
+fi
+
+ARTIFACT="$WASM_OUT_DIR/murkl_wasm_bg.wasm"
+if [[ ! -f "$ARTIFACT" ]]; then
+  echo "❌ $ARTIFACT not found" >&2
+  exit 1
+fi
+
+raw=$(wc -c < "$ARTIFACT" | tr -d ' ')
+gz=$(gzip -9 -c "$ARTIFACT" | wc -c | tr -d ' ')
+
+echo "murkl_wasm_bg.wasm: ${raw} bytes (budget ${MAX_WASM_BYTES}), ${gz} bytes gzipped (budget ${MAX_WASM_GZIP_BYTES})"
+
+status=0
+if (( raw > MAX_WASM_BYTES )); then
+  echo "❌ raw size over budget by $((raw - MAX_WASM_BYTES)) bytes" >&2
+  status=1
+fi
+if (( gz > MAX_WASM_GZIP_BYTES )); then
+  echo "❌ gzipped size over budget by $((gz - MAX_WASM_GZIP_BYTES)) bytes" >&2
+  status=1
+fi
+if (( status == 0 )); then
+  echo "✅ within budget"
+fi
+exit $status
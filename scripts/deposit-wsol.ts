@@ -9,34 +9,11 @@ import {
 } from '@solana/spl-token';
 import * as fs from 'fs';
 import * as crypto from 'crypto';
-import sha3 from 'js-sha3';
-const { keccak256 } = sha3;
+import { hashIdentifier, hashPassword, pqCommitment } from '../relayer/src/commitment';
 
 const PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92');
 const WSOL_POOL = new PublicKey('HBdNYy8ChUY2KJGf5qTXETXCpeX7kt7aok4XuXk6vbCd');
 const RPC_URL = 'https://api.devnet.solana.com';
-const M31_PRIME = 0x7FFFFFFF;
-
-function hashPassword(password: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(id.toLowerCase())]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function computeCommitment(idHash: number, secret: number): Buffer {
-  const data = Buffer.concat([
-    Buffer.from('murkl_m31_hash_v1'),
-    Buffer.from(new Uint32Array([idHash]).buffer),
-    Buffer.from(new Uint32Array([secret]).buffer)
-  ]);
-  return Buffer.from(keccak256(data), 'hex');
-}
 
 function getDiscriminator(name: string): Buffer {
   return crypto.createHash('sha256').update(`global:${name}`).digest().slice(0, 8);
@@ -55,7 +32,7 @@ async function main() {
   
   const idHash = hashIdentifier(identifier);
   const secret = hashPassword(password);
-  const commitment = computeCommitment(idHash, secret);
+  const commitment = pqCommitment(idHash, secret);
   
   // Get pool info for leaf index
   const poolInfo = await connection.getAccountInfo(WSOL_POOL);
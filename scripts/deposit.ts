@@ -12,49 +12,10 @@ import {
 } from '@solana/web3.js';
 import { TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from '@solana/spl-token';
 import * as fs from 'fs';
-import sha3 from 'js-sha3';
-const { keccak256 } = sha3;
 import * as crypto from 'crypto';
+import { hashIdentifier, hashPassword, pqCommitment } from '../relayer/src/commitment';
 
 const PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92');
-const M31_PRIME = 0x7FFFFFFF;
-
-// Hash functions using KECCAK256 (matching WASM prover!)
-function hashPassword(password: string): number {
-  const data = Buffer.concat([
-    Buffer.from('murkl_password_v1'),
-    Buffer.from(password)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  const val = hash.readUInt32LE(0);
-  return val % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([
-    Buffer.from('murkl_identifier_v1'),
-    Buffer.from(normalized)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  const val = hash.readUInt32LE(0);
-  return val % M31_PRIME;
-}
-
-function computeCommitment(idHash: number, secret: number): Buffer {
-  const idBuf = Buffer.alloc(4);
-  idBuf.writeUInt32LE(idHash, 0);
-  const secretBuf = Buffer.alloc(4);
-  secretBuf.writeUInt32LE(secret, 0);
-  
-  const data = Buffer.concat([
-    Buffer.from('murkl_m31_hash_v1'),
-    idBuf,
-    secretBuf
-  ]);
-  
-  return Buffer.from(keccak256(data), 'hex');
-}
 
 function getDiscriminator(name: string): Buffer {
   const hash = crypto.createHash('sha256').update(`global:${name}`).digest();
@@ -83,7 +44,7 @@ async function main() {
   // Compute commitment
   const idHash = hashIdentifier(identifier);
   const secret = hashPassword(password);
-  const commitment = computeCommitment(idHash, secret);
+  const commitment = pqCommitment(idHash, secret);
   
   console.log(`\n🔐 Commitment details:`);
   console.log(`   Identifier: ${identifier}`);
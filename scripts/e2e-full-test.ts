@@ -23,6 +23,7 @@ import {
 import * as fs from 'fs';
 import * as crypto from 'crypto';
 import { keccak256 } from 'js-sha3';
+import { hashIdentifier, hashPassword, computeCommitment, computeNullifier } from '../relayer/src/commitment';
 
 // ============================================================================
 // Config
@@ -55,43 +56,6 @@ function getDiscriminator(name: string): Buffer {
 // Commitment/Nullifier (CORRECT - matches WASM/Rust)
 // ============================================================================
 
-function hashPassword(password: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalized)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-// CORRECT commitment with domain prefix
-function computeCommitment(identifier: string, password: string): Buffer {
-  const idHash = hashIdentifier(identifier);
-  const secret = hashPassword(password);
-  
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const idBuf = Buffer.alloc(4);
-  const secretBuf = Buffer.alloc(4);
-  idBuf.writeUInt32LE(idHash, 0);
-  secretBuf.writeUInt32LE(secret, 0);
-  
-  const data = Buffer.concat([prefix, idBuf, secretBuf]);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
-// CORRECT nullifier
-function computeNullifier(password: string, leafIndex: number): Buffer {
-  const secret = hashPassword(password);
-  const data = Buffer.alloc(8);
-  data.writeUInt32LE(secret, 0);
-  data.writeUInt32LE(leafIndex, 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
 // ============================================================================
 // Proof Generation (matches WASM prover format)
 // ============================================================================
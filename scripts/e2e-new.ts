@@ -28,12 +28,13 @@ import * as fs from 'fs';
 import * as crypto from 'crypto';
 import sha3 from 'js-sha3';
 const { keccak256 } = sha3;
+import { hashIdentifier, hashPassword, pqCommitment, pqNullifier } from '../relayer/src/commitment';
 
 // Program IDs
 const MURKL_PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92');
 const STARK_VERIFIER_ID = new PublicKey('StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw');
+const [VERIFIER_CONFIG_PDA] = PublicKey.findProgramAddressSync([Buffer.from('verifier-config')], STARK_VERIFIER_ID);
 
-const M31_PRIME = 0x7FFFFFFF;
 const CHUNK_SIZE = 900;
 
 // Buffer layout offsets (stark-verifier)
@@ -56,44 +57,6 @@ function getDiscriminator(name: string): Buffer {
 // Crypto (matching WASM prover)
 // ============================================================================
 
-function hashPassword(password: string): number {
-  const data = Buffer.concat([
-    Buffer.from('murkl_password_v1'),
-    Buffer.from(password)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([
-    Buffer.from('murkl_identifier_v1'),
-    Buffer.from(normalized)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function computeCommitment(identifierHash: number, secretHash: number): Buffer {
-  // FIXED: Added domain prefix to match WASM prover
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const data = Buffer.alloc(prefix.length + 8);
-  prefix.copy(data, 0);
-  data.writeUInt32LE(identifierHash, prefix.length);
-  data.writeUInt32LE(secretHash, prefix.length + 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
-function computeNullifier(secretHash: number, leafIndex: number): Buffer {
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const data = Buffer.alloc(prefix.length + 8);
-  prefix.copy(data, 0);
-  data.writeUInt32LE(identifierHash, prefix.length);
-  data.writeUInt32LE(secretHash, prefix.length + 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
 // ============================================================================
 // Mock STARK Proof Generator (matches verifier expected format)
 // ============================================================================
@@ -218,8 +181,8 @@ async function main() {
 
   const idHash = hashIdentifier(identifier);
   const secretHash = hashPassword(password);
-  const commitment = computeCommitment(idHash, secretHash);
-  const nullifier = computeNullifier(secretHash, leafIndex);
+  const commitment = pqCommitment(idHash, secretHash);
+  const nullifier = pqNullifier(secretHash, leafIndex);
 
   console.log(`\n🔐 Commitment: 0x${commitment.slice(0, 8).toString('hex')}...`);
   console.log(`🔐 Nullifier: 0x${nullifier.slice(0, 8).toString('hex')}...`);
@@ -315,6 +278,7 @@ async function main() {
     keys: [
       { pubkey: proofBuffer.publicKey, isSigner: false, isWritable: true },
       { pubkey: relayer.publicKey, isSigner: true, isWritable: false },
+      { pubkey: VERIFIER_CONFIG_PDA, isSigner: false, isWritable: false },
     ],
     data: finalizeData,
   });
@@ -28,7 +28,7 @@ import {
 } from '@solana/spl-token';
 
 // WASM prover (node init via initSync with bytes)
-import initSync, { generate_commitment, generate_proof } from '../web/src/wasm/murkl_wasm.js';
+import initSync, { generate_commitment, generate_merkle_path, generate_proof } from '../web/src/wasm/murkl_wasm.js';
 
 const RELAYER_URL = process.env.RELAYER_URL || 'https://murkl-relayer-production.up.railway.app';
 const RPC_URL = process.env.RPC_URL || 'https://api.devnet.solana.com';
@@ -267,9 +267,17 @@ async function main() {
   const merkleRootHex = poolInfo.merkleRoot as string;
   console.log('pool merkleRoot:', merkleRootHex);
 
+  const leavesRes = await fetch(`${RELAYER_URL}/pool-leaves?pool=${POOL.toBase58()}`, {
+    headers: { Origin: ORIGIN },
+  });
+  if (!leavesRes.ok) throw new Error('pool-leaves failed');
+  const { leaves } = await leavesRes.json() as any;
+  const merklePathHex = generate_merkle_path(leaves, leafIndex);
+  if (merklePathHex === undefined) throw new Error(`leaf ${leafIndex} is not in the pool leaves`);
+
   // Generate proof bound to recipient ATA A
   const recipientHexA = u8aToHex(recipientAtaA.toBytes());
-  const proofBundle: any = generate_proof(identifier, password, leafIndex, merkleRootHex, recipientHexA);
+  const proofBundle: any = generate_proof(identifier, password, leafIndex, merkleRootHex, merklePathHex, recipientHexA);
   if (proofBundle?.error) throw new Error(`wasm proof error: ${proofBundle.error}`);
 
   console.log('proof size:', proofBundle.proof_size);
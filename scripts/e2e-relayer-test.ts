@@ -24,6 +24,7 @@ import {
 import * as fs from 'fs';
 import * as crypto from 'crypto';
 import { keccak256 } from 'js-sha3';
+import { hashIdentifier, hashPassword, pqCommitment, pqNullifier } from '../relayer/src/commitment';
 
 // Config
 const RPC_URL = process.env.RPC_URL || 'https://api.devnet.solana.com';
@@ -38,36 +39,6 @@ const AMOUNT = 0.01 * LAMPORTS_PER_SOL; // 0.01 SOL
 // Crypto (CORRECT implementation with domain prefix)
 // ============================================================================
 
-function hashPassword(password: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalized)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-// CORRECT: with domain prefix
-function computeCommitment(identifierHash: number, secretHash: number): Buffer {
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const data = Buffer.alloc(prefix.length + 8);
-  prefix.copy(data, 0);
-  data.writeUInt32LE(identifierHash, prefix.length);
-  data.writeUInt32LE(secretHash, prefix.length + 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
-function computeNullifier(secretHash: number, leafIndex: number): Buffer {
-  const data = Buffer.alloc(8);
-  data.writeUInt32LE(secretHash, 0);
-  data.writeUInt32LE(leafIndex, 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
 function getDiscriminator(name: string): Buffer {
   const hash = crypto.createHash('sha256').update(`global:${name}`).digest();
   return hash.slice(0, 8);
@@ -85,8 +56,8 @@ function generateProof(identifier: string, password: string, leafIndex: number,
   const idHash = hashIdentifier(identifier);
   const secretHash = hashPassword(password);
   
-  const commitment = computeCommitment(idHash, secretHash);
-  const nullifier = computeNullifier(secretHash, leafIndex);
+  const commitment = pqCommitment(idHash, secretHash);
+  const nullifier = pqNullifier(secretHash, leafIndex);
   
   // M31 field values
   const commitmentM31 = Buffer.from(keccak256(Buffer.concat([Buffer.from('m31_commitment'), commitment])), 'hex').readUInt32LE(0) % M31_PRIME;
@@ -317,7 +288,7 @@ async function main() {
   
   const idHash = hashIdentifier(identifier);
   const secretHash = hashPassword(password);
-  const commitment = computeCommitment(idHash, secretHash);
+  const commitment = pqCommitment(idHash, secretHash);
   
   console.log(`   Commitment: ${commitment.toString('hex')}`);
 
@@ -26,6 +26,7 @@ import * as fs from 'fs';
 import * as crypto from 'crypto';
 import sha3 from 'js-sha3';
 const { keccak256 } = sha3;
+import { hashIdentifier, hashPassword, pqCommitment, pqNullifier } from '../relayer/src/commitment';
 
 const PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92');
 const M31_PRIME = 0x7FFFFFFF;
@@ -35,55 +36,6 @@ const CHUNK_SIZE = 900;
 // Hash functions (matching WASM)
 // ============================================================================
 
-function hashPassword(password: string): number {
-  const data = Buffer.concat([
-    Buffer.from('murkl_password_v1'),
-    Buffer.from(password)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([
-    Buffer.from('murkl_identifier_v1'),
-    Buffer.from(normalized)
-  ]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function computeCommitment(idHash: number, secret: number): Buffer {
-  const idBuf = Buffer.alloc(4);
-  idBuf.writeUInt32LE(idHash, 0);
-  const secretBuf = Buffer.alloc(4);
-  secretBuf.writeUInt32LE(secret, 0);
-  
-  const data = Buffer.concat([
-    Buffer.from('murkl_m31_hash_v1'),
-    idBuf,
-    secretBuf
-  ]);
-  
-  return Buffer.from(keccak256(data), 'hex');
-}
-
-function computeNullifier(secret: number, leafIndex: number): Buffer {
-  const secretBuf = Buffer.alloc(4);
-  secretBuf.writeUInt32LE(secret, 0);
-  const leafBuf = Buffer.alloc(4);
-  leafBuf.writeUInt32LE(leafIndex, 0);
-  
-  const data = Buffer.concat([
-    Buffer.from('murkl_nullifier_v1'),
-    secretBuf,
-    leafBuf
-  ]);
-  
-  return Buffer.from(keccak256(data), 'hex');
-}
-
 function getDiscriminator(name: string): Buffer {
   const hash = crypto.createHash('sha256').update(`global:${name}`).digest();
   return hash.slice(0, 8);
@@ -259,8 +211,8 @@ async function main() {
   // Compute values
   const idHash = hashIdentifier(depositData.identifier);
   const secret = hashPassword(depositData.password);
-  const commitment = computeCommitment(idHash, secret);
-  const nullifier = computeNullifier(secret, depositData.leafIndex);
+  const commitment = pqCommitment(idHash, secret);
+  const nullifier = pqNullifier(secret, depositData.leafIndex);
   
   console.log(`   ID Hash: ${idHash}`);
   console.log(`   Secret: ${secret}`);
@@ -436,7 +388,7 @@ async function main() {
   const claimIx = new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
-      { pubkey: pool, isSigner: false, isWritable: false },           // pool (not writable)
+      { pubkey: pool, isSigner: false, isWritable: true },            // pool (event_seq)
       { pubkey: deposit, isSigner: false, isWritable: true },         // deposit
       { pubkey: proofBufferPda, isSigner: false, isWritable: false }, // verifier_buffer
       { pubkey: nullifierPda, isSigner: false, isWritable: true },    // nullifier_record (init)
@@ -21,6 +21,7 @@ import {
 } from '@solana/spl-token';
 import * as fs from 'fs';
 import * as crypto from 'crypto';
+import { computeCommitment } from '../relayer/src/commitment';
 
 // NEW VANITY ADDRESS
 const PROGRAM_ID = new PublicKey('muRkDGaY4yCc6rEYWhmJAnQ1abdCbUJNCr4L1Cmd1UF');
@@ -32,32 +33,6 @@ function getDiscriminator(name: string): Buffer {
   return hash.slice(0, 8);
 }
 
-function keccakHash(data: Buffer): Buffer {
-  const { keccak256 } = require('js-sha3');
-  return Buffer.from(keccak256.arrayBuffer(data));
-}
-
-const M31_PRIME = 0x7FFFFFFF;
-
-function computeCommitment(identifier: string, password: string): Buffer {
-  // Hash identifier
-  const idData = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(identifier.toLowerCase())]);
-  const idHash = keccakHash(idData).readUInt32LE(0) % M31_PRIME;
-  
-  // Hash password
-  const pwData = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const secret = keccakHash(pwData).readUInt32LE(0) % M31_PRIME;
-  
-  // Compute commitment
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const idBuf = Buffer.alloc(4);
-  const secretBuf = Buffer.alloc(4);
-  idBuf.writeUInt32LE(idHash);
-  secretBuf.writeUInt32LE(secret);
-  
-  return keccakHash(Buffer.concat([prefix, idBuf, secretBuf]));
-}
-
 async function main() {
   console.log('🐈‍⬛ E2E Test: Vanity Address WSOL Pool\n');
   
@@ -2,10 +2,15 @@ import { Connection, PublicKey } from "@solana/web3.js";
 const MURKL_ID = new PublicKey("74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92");
 const TOKEN_MINT = new PublicKey("DTMXeBXH1vRbRvcsHTN46jksTo9tSQwq7WYQSX8MYPA9");
 
+// Must match cli/src/types.rs (MERKLE_DATA_VERSION) and the on-chain MERKLE_DEPTH
+const MERKLE_DATA_VERSION = 1;
+const MERKLE_DEPTH = 20;
+
 async function main() {
   const connection = new Connection("https://api.devnet.solana.com", "confirmed");
   const [poolPda] = PublicKey.findProgramAddressSync([Buffer.from("pool"), TOKEN_MINT.toBuffer()], MURKL_ID);
   
+  const slot = await connection.getSlot("confirmed");
   const poolInfo = await connection.getAccountInfo(poolPda);
   if (!poolInfo) throw new Error("Pool not found");
   
@@ -16,12 +21,30 @@ async function main() {
   console.log("Merkle root:", merkleRoot.toString("hex"));
   console.log("Leaf count:", leafCount.toString());
   
+  // Every leaf is a DepositRecord at its index
+  const leaves: { index: number; commitment: string }[] = [];
+  for (let i = 0; i < Number(leafCount); i++) {
+    const leafBuf = Buffer.alloc(8);
+    leafBuf.writeBigUInt64LE(BigInt(i));
+    const [depositPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), poolPda.toBuffer(), leafBuf],
+      MURKL_ID
+    );
+    const depositInfo = await connection.getAccountInfo(depositPda);
+    if (!depositInfo) throw new Error(`Deposit ${i} not found`);
+    // DepositRecord: discriminator(8) + pool(32) + commitment(32) + ...
+    leaves.push({ index: i, commitment: depositInfo.data.slice(8 + 32, 8 + 32 + 32).toString("hex") });
+  }
+  
   // Save merkle data for prover
   const fs = require("fs");
   fs.writeFileSync("/tmp/merkle.json", JSON.stringify({
+    version: MERKLE_DATA_VERSION,
+    pool: poolPda.toBase58(),
     root: merkleRoot.toString("hex"),
-    leaves: [], // We'd need to track all commitments
-    depth: Math.ceil(Math.log2(Number(leafCount) + 1)) || 1,
+    depth: MERKLE_DEPTH,
+    last_indexed_slot: slot,
+    leaves,
   }, null, 2));
   console.log("Merkle data saved to /tmp/merkle.json");
 }
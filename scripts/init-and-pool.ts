@@ -103,7 +103,7 @@ async function main() {
     console.log(`\n🚀 Initializing pool...`);
     
     // PoolConfig: min_deposit (u64) + max_relayer_fee_bps (u16)
-    const configData = Buffer.alloc(10);
+    const configData = Buffer.alloc(12); // trailing zeros = compliance_hook, required_verifier_params_hash: None
     configData.writeBigUInt64LE(BigInt(1), 0); // min_deposit = 1
     configData.writeUInt16LE(100, 8); // max_relayer_fee_bps = 1%
     
@@ -7,8 +7,13 @@ import * as os from "os";
 
 // Load IDL
 const idl = JSON.parse(fs.readFileSync("./target/idl/murkl_program.json", "utf8"));
+const verifierIdl = JSON.parse(fs.readFileSync("./target/idl/stark_verifier.json", "utf8"));
 
 const MURKL_ID = new PublicKey("74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92");
+const STARK_VERIFIER_ID = new PublicKey("StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw");
+// SecurityLevel byte: 0 = demo (needs a verifier built with the `demo`
+// feature, which devnet's is), 1 = standard96, 2 = high128
+const VERIFIER_SECURITY_LEVEL = Number(process.env.VERIFIER_SECURITY_LEVEL ?? 0);
 
 async function main() {
   // Connect to devnet
@@ -60,6 +65,31 @@ async function main() {
     }
   }
   
+  // The verifier refuses every proof until its profile is set
+  const [verifierConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("verifier-config")],
+    STARK_VERIFIER_ID
+  );
+  if (await connection.getAccountInfo(verifierConfigPda)) {
+    console.log("Verifier config already initialized!");
+  } else {
+    console.log("Initializing verifier config at level", VERIFIER_SECURITY_LEVEL);
+    try {
+      const verifier = new Program(verifierIdl, provider);
+      const tx = await verifier.methods
+        .initializeVerifierConfig(VERIFIER_SECURITY_LEVEL)
+        .accounts({
+          verifierConfig: verifierConfigPda,
+          authority: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      console.log("Verifier config initialized! Tx:", tx);
+    } catch (e) {
+      console.error("Failed to init verifier config:", e);
+    }
+  }
+  
   // Derive pool PDA for WSOL
   const [poolPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("pool"), NATIVE_MINT.toBuffer()],
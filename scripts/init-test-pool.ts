@@ -39,7 +39,8 @@ async function main() {
   const relayerFeeBps = Buffer.alloc(2);
   relayerFeeBps.writeUInt16LE(50); // 0.5%
   
-  const data = Buffer.concat([discriminator, minDeposit, relayerFeeBps]);
+  const noComplianceHook = Buffer.from([0, 0]); // compliance_hook, required_verifier_params_hash: None
+  const data = Buffer.concat([discriminator, minDeposit, relayerFeeBps, noComplianceHook]);
   
   const keys = [
     { pubkey: configPda, isSigner: false, isWritable: false },
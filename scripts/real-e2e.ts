@@ -23,11 +23,11 @@ import {
 import * as fs from 'fs';
 import * as crypto from 'crypto';
 import { execSync } from 'child_process';
-import sha3 from 'js-sha3';
-const { keccak256 } = sha3;
+import { hashIdentifier, hashPassword, pqCommitment, pqNullifier } from '../relayer/src/commitment';
 
 const MURKL_PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92');
 const STARK_VERIFIER_ID = new PublicKey('StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw');
+const [VERIFIER_CONFIG_PDA] = PublicKey.findProgramAddressSync([Buffer.from('verifier-config')], STARK_VERIFIER_ID);
 const CHUNK_SIZE = 800;
 
 function getDiscriminator(name: string): Buffer {
@@ -35,40 +35,6 @@ function getDiscriminator(name: string): Buffer {
   return hash.slice(0, 8);
 }
 
-const M31_PRIME = 0x7FFFFFFF;
-
-function hashPassword(password: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalized)]);
-  const hash = Buffer.from(keccak256(data), 'hex');
-  return hash.readUInt32LE(0) % M31_PRIME;
-}
-
-function computeCommitment(identifierHash: number, secretHash: number): Buffer {
-  // FIXED: Added domain prefix to match WASM prover
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const data = Buffer.alloc(prefix.length + 8);
-  prefix.copy(data, 0);
-  data.writeUInt32LE(identifierHash, prefix.length);
-  data.writeUInt32LE(secretHash, prefix.length + 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
-function computeNullifier(secretHash: number, leafIndex: number): Buffer {
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const data = Buffer.alloc(prefix.length + 8);
-  prefix.copy(data, 0);
-  data.writeUInt32LE(identifierHash, prefix.length);
-  data.writeUInt32LE(secretHash, prefix.length + 4);
-  return Buffer.from(keccak256(data), 'hex');
-}
-
 async function main() {
   console.log('🐈‍⬛ Real E2E Test with CLI Prover\n');
 
@@ -139,7 +105,7 @@ async function main() {
 
   const idHash = hashIdentifier(identifier);
   const secretHash = hashPassword(password);
-  const commitment = computeCommitment(idHash, secretHash);
+  const commitment = pqCommitment(idHash, secretHash);
   const leafIndex = 0;
 
   console.log(`   Identifier: ${identifier}`);
@@ -182,7 +148,7 @@ async function main() {
   // Step 4: Generate proof with CLI
   console.log('\n🔐 Step 4: Generating proof with CLI...');
   
-  const nullifier = computeNullifier(secretHash, leafIndex);
+  const nullifier = pqNullifier(secretHash, leafIndex);
   console.log(`   Nullifier: 0x${nullifier.slice(0, 8).toString('hex')}...`);
 
   // Create merkle tree file for CLI
@@ -291,6 +257,7 @@ async function main() {
     keys: [
       { pubkey: proofBuffer.publicKey, isSigner: false, isWritable: true },
       { pubkey: payer.publicKey, isSigner: true, isWritable: false },
+      { pubkey: VERIFIER_CONFIG_PDA, isSigner: false, isWritable: false },
     ],
     data: finalizeData,
   });
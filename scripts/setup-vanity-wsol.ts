@@ -90,7 +90,7 @@ async function main() {
     console.log(`\n🚀 Initializing WSOL pool...`);
     
     // PoolConfig: min_deposit (u64) + max_relayer_fee_bps (u16)
-    const configData = Buffer.alloc(10);
+    const configData = Buffer.alloc(12); // trailing zeros = compliance_hook, required_verifier_params_hash: None
     configData.writeBigUInt64LE(BigInt(1000000), 0); // 0.001 SOL min
     configData.writeUInt16LE(100, 8); // 1% max fee
     
@@ -80,7 +80,7 @@ async function main() {
   const discriminator = getDiscriminator('initialize_pool');
   
   // PoolConfig: min_deposit (u64) + max_relayer_fee_bps (u16)
-  const configData = Buffer.alloc(10);
+  const configData = Buffer.alloc(12); // trailing zeros = compliance_hook, required_verifier_params_hash: None
   configData.writeBigUInt64LE(BigInt(1000000), 0); // min_deposit = 0.001 WSOL (1M lamports)
   configData.writeUInt16LE(100, 8); // max_relayer_fee_bps = 100 (1%)
   
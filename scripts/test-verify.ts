@@ -4,6 +4,7 @@ import * as os from "os";
 import * as crypto from "crypto";
 
 const STARK_VERIFIER_ID = new PublicKey("StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw");
+const [VERIFIER_CONFIG_PDA] = PublicKey.findProgramAddressSync([Buffer.from("verifier-config")], STARK_VERIFIER_ID);
 
 // Discriminators
 function disc(name: string): Buffer {
@@ -119,6 +120,7 @@ async function main() {
     keys: [
       { pubkey: bufferKeypair.publicKey, isSigner: false, isWritable: true },
       { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
+      { pubkey: VERIFIER_CONFIG_PDA, isSigner: false, isWritable: false },
     ],
     data: Buffer.concat([FINALIZE, commitment, nullifier, merkleRoot]),
   });
@@ -17,14 +17,24 @@ import {
   SEEDS,
 } from './constants';
 import { StarkVerifier } from './verifier';
+import { fetchTokenMetadata, formatAmount, parseAmount } from './token';
 import type { 
   MurklConfig, 
   DepositParams, 
   ClaimParams,
   PoolInfo,
   DepositInfo,
+  RegisteredPool,
+  TokenMetadata,
 } from './types';
 
+// Anchor account discriminator for "RegistryEntry"
+const REGISTRY_ENTRY_DISCRIMINATOR = Buffer.from([0x30, 0xc6, 0xf0, 0xfc, 0x9b, 0xba, 0x48, 0x10]);
+
+// Pool's trailing min_security_level Option, counted from `paused`: past the
+// fixed fields through vault_generation (30-root history, 4 accepted mints)
+const POOL_PAUSED_TO_MIN_SECURITY_LEVEL = 1261;
+
 /**
  * Client for interacting with Murkl anonymous transfer pools
  */
@@ -33,6 +43,7 @@ export class MurklClient {
   readonly wallet: Signer;
   readonly programId: PublicKey;
   readonly verifier: StarkVerifier;
+  private tokenMetadata = new Map<string, Promise<TokenMetadata>>();
 
   constructor(config: MurklConfig) {
     this.connection = config.connection;
@@ -106,6 +117,28 @@ export class MurklClient {
     return pda;
   }
 
+  /**
+   * Get pool registry PDA
+   */
+  getRegistryPDA(): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [SEEDS.REGISTRY],
+      this.programId
+    );
+    return pda;
+  }
+
+  /**
+   * Get registry entry PDA for a pool
+   */
+  getRegistryEntryPDA(pool: PublicKey): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [SEEDS.REGISTRY_ENTRY, pool.toBuffer()],
+      this.programId
+    );
+    return pda;
+  }
+
   // =========================================================================
   // Read operations
   // =========================================================================
@@ -137,7 +170,19 @@ export class MurklClient {
     const leafCount = data.readBigUInt64LE(offset); offset += 8;
     const minDeposit = data.readBigUInt64LE(offset); offset += 8;
     const maxRelayerFeeBps = data.readUInt16LE(offset); offset += 2;
+    // Option<Pubkey>: 1-byte tag, then 32 bytes only when Some
+    let complianceHook: PublicKey | null = null;
+    if (data[offset++] === 1) {
+      complianceHook = new PublicKey(data.slice(offset, offset + 32)); offset += 32;
+    }
+    let requiredVerifierParamsHash: Uint8Array | null = null;
+    if (data[offset++] === 1) {
+      requiredVerifierParamsHash = new Uint8Array(data.slice(offset, offset + 32)); offset += 32;
+    }
     const paused = data[offset] === 1;
+    // Pools not yet grown to this field have no minimum
+    const levelAt = offset + POOL_PAUSED_TO_MIN_SECURITY_LEVEL;
+    const minSecurityLevel = data[levelAt] === 1 && levelAt + 1 < data.length ? data[levelAt + 1] : null;
 
     return {
       address: poolAddress,
@@ -148,10 +193,90 @@ export class MurklClient {
       leafCount,
       minDeposit,
       maxRelayerFeeBps,
+      complianceHook,
+      requiredVerifierParamsHash,
+      minSecurityLevel,
       paused,
     };
   }
 
+  /**
+   * List every registered (official) pool in registration order
+   */
+  async listRegisteredPools(): Promise<RegisteredPool[]> {
+    const accounts = await this.connection.getProgramAccounts(this.programId, {
+      filters: [{ memcmp: { offset: 0, bytes: REGISTRY_ENTRY_DISCRIMINATOR.toString('base64'), encoding: 'base64' } }],
+    });
+    return accounts
+      .map(({ pubkey, account }) => parseRegistryEntry(pubkey, account.data))
+      .sort((a, b) => (a.index < b.index ? -1 : a.index > b.index ? 1 : 0));
+  }
+
+  /**
+   * Get the registry entry for a pool, or null if the pool is not official
+   */
+  async getRegisteredPool(pool: PublicKey): Promise<RegisteredPool | null> {
+    const address = this.getRegistryEntryPDA(pool);
+    const accountInfo = await this.connection.getAccountInfo(address);
+    if (!accountInfo || !accountInfo.owner.equals(this.programId)) return null;
+    return parseRegistryEntry(address, accountInfo.data);
+  }
+
+  /**
+   * Check a pool is official: it has a registry entry, and the pool account
+   * still matches the mint and vault recorded at registration. Pass
+   * `verifierConfigHash` to also require the expected verifier parameters.
+   */
+  async validatePool(pool: PublicKey, verifierConfigHash?: Uint8Array): Promise<RegisteredPool> {
+    const entry = await this.getRegisteredPool(pool);
+    if (!entry) throw new Error(`Pool ${pool.toBase58()} is not in the registry`);
+
+    const info = await this.getPool(pool);
+    if (!info || !info.address.equals(pool)) throw new Error(`Pool ${pool.toBase58()} not found`);
+    if (!info.tokenMint.equals(entry.tokenMint) || !info.vault.equals(entry.vault)) {
+      throw new Error(`Pool ${pool.toBase58()} does not match its registry entry`);
+    }
+    if (verifierConfigHash && !Buffer.from(verifierConfigHash).equals(Buffer.from(entry.verifierConfigHash))) {
+      throw new Error(`Pool ${pool.toBase58()} targets a different verifier config`);
+    }
+    return entry;
+  }
+
+  /**
+   * Get decimals and symbol for a mint (cached per client)
+   */
+  async getTokenMetadata(mint: PublicKey): Promise<TokenMetadata> {
+    const key = mint.toBase58();
+    let pending = this.tokenMetadata.get(key);
+    if (!pending) {
+      pending = fetchTokenMetadata(this.connection, mint);
+      // Don't cache failures; a later call may succeed
+      pending.catch(() => this.tokenMetadata.delete(key));
+      this.tokenMetadata.set(key, pending);
+    }
+    return pending;
+  }
+
+  /**
+   * Format a raw amount of a pool's token, e.g. "10.5 USDC"
+   */
+  async formatPoolAmount(pool: PublicKey, amount: bigint): Promise<string> {
+    const info = await this.getPool(pool);
+    if (!info) throw new Error(`Pool ${pool.toBase58()} not found`);
+    const { decimals, symbol } = await this.getTokenMetadata(info.tokenMint);
+    return formatAmount(amount, decimals, symbol);
+  }
+
+  /**
+   * Parse a human amount of a pool's token into raw units
+   */
+  async parsePoolAmount(pool: PublicKey, input: string): Promise<bigint> {
+    const info = await this.getPool(pool);
+    if (!info) throw new Error(`Pool ${pool.toBase58()} not found`);
+    const { decimals } = await this.getTokenMetadata(info.tokenMint);
+    return parseAmount(input, decimals);
+  }
+
   /**
    * Get deposit information
    */
@@ -238,6 +363,14 @@ export class MurklClient {
       params.nullifier
     );
 
+    // Compliance hook program goes first in remaining accounts, then its state
+    if (pool.complianceHook) {
+      ix.keys.push({ pubkey: pool.complianceHook, isSigner: false, isWritable: false });
+      for (const account of params.complianceAccounts ?? []) {
+        ix.keys.push({ pubkey: account, isSigner: false, isWritable: false });
+      }
+    }
+
     const tx = new Transaction().add(ix);
     return sendAndConfirmTransaction(this.connection, tx, [this.wallet]);
   }
@@ -314,3 +447,27 @@ export class MurklClient {
     });
   }
 }
+
+function parseRegistryEntry(address: PublicKey, data: Buffer): RegisteredPool {
+  // Skip 8-byte Anchor discriminator
+  let offset = 8;
+
+  const index = data.readBigUInt64LE(offset); offset += 8;
+  const pool = new PublicKey(data.slice(offset, offset + 32)); offset += 32;
+  const tokenMint = new PublicKey(data.slice(offset, offset + 32)); offset += 32;
+  const vault = new PublicKey(data.slice(offset, offset + 32)); offset += 32;
+  const denomination = data.readBigUInt64LE(offset); offset += 8;
+  const verifierConfigHash = data.slice(offset, offset + 32); offset += 32;
+  const registeredAt = data.readBigInt64LE(offset);
+
+  return {
+    address,
+    index,
+    pool,
+    tokenMint,
+    vault,
+    denomination,
+    verifierConfigHash: new Uint8Array(verifierConfigHash),
+    registeredAt,
+  };
+}
@@ -10,6 +10,11 @@ export const MURKL_PROGRAM_ID = new PublicKey('74P7nTytTESmeJTH46geZ93GLFq3yAojn
  */
 export const STARK_VERIFIER_PROGRAM_ID = new PublicKey('StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw');
 
+/**
+ * Metaplex token metadata program (mint name/symbol)
+ */
+export const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+
 /**
  * Verifier buffer layout offsets
  */
@@ -45,4 +50,6 @@ export const SEEDS = {
   VAULT: Buffer.from('vault'),
   DEPOSIT: Buffer.from('deposit'),
   NULLIFIER: Buffer.from('nullifier'),
+  REGISTRY: Buffer.from('pool-registry'),
+  REGISTRY_ENTRY: Buffer.from('registry-entry'),
 } as const;
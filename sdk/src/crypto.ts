@@ -82,3 +82,20 @@ export function fromHex(hex: string): Uint8Array {
   }
   return bytes;
 }
+
+/**
+ * Hash of the stark-verifier parameters a pool's proofs target
+ * (matches `verifier_config_hash` in the murkl program)
+ */
+export function verifierConfigHash(
+  logTraceSize: number,
+  logBlowup: number,
+  logFoldingFactor: number,
+  logFinalPolyDegree: number,
+): Uint8Array {
+  const params = Buffer.alloc(16);
+  [logTraceSize, logBlowup, logFoldingFactor, logFinalPolyDegree].forEach((v, i) =>
+    params.writeUInt32LE(v, i * 4),
+  );
+  return keccak256(Buffer.concat([Buffer.from('murkl_verifier_config_v1'), params]));
+}
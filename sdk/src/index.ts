@@ -20,11 +20,18 @@ export {
   generateCommitment, 
   generateNullifier, 
   hashIdentifier,
-  keccak256 
+  keccak256,
+  verifierConfigHash,
 } from './crypto';
+export {
+  fetchTokenMetadata,
+  formatAmount,
+  parseAmount,
+} from './token';
 export {
   MURKL_PROGRAM_ID,
   STARK_VERIFIER_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
   VERIFIER_BUFFER_HEADER_SIZE,
 } from './constants';
 export {
@@ -40,6 +47,8 @@ export type {
   ProofParams,
   PoolInfo,
   DepositInfo,
+  RegisteredPool,
+  TokenMetadata,
 } from './types';
 export type {
   STWOProof,
@@ -11,7 +11,9 @@ import { M31, QM31 } from './types';
 export interface STWOProof {
   traceCommitment: Uint8Array;      // 32 bytes
   compositionCommitment: Uint8Array; // 32 bytes
-  traceOods: Uint8Array;            // 16 bytes (QM31)
+  traceColumns: number;             // columns opened per query
+  traceOods: Uint8Array;            // 16 bytes (QM31) per trace column
+  traceOodsNext: Uint8Array;        // 16 bytes (QM31) per trace column, one row on
   compositionOods: Uint8Array;      // 16 bytes (QM31)
   friLayers: FriLayer[];
   finalPoly: Uint8Array;
@@ -24,7 +26,7 @@ export interface FriLayer {
 
 export interface QueryProof {
   index: number;
-  traceValue: Uint8Array;         // 32 bytes
+  traceRow: Uint8Array;           // 4 bytes (M31 LE) per trace column
   tracePath: Uint8Array[];        // Array of 32-byte nodes
   compositionValue: Uint8Array;   // 32 bytes
   compositionPath: Uint8Array[];  // Array of 32-byte nodes
@@ -42,8 +44,12 @@ export function serializeProof(proof: STWOProof): Uint8Array {
   // Composition commitment (32 bytes)
   parts.push(proof.compositionCommitment);
 
-  // Trace OODS (16 bytes)
+  // Trace column count (1 byte)
+  parts.push(new Uint8Array([proof.traceColumns]));
+
+  // Trace OODS (16 bytes per column), then one row on
   parts.push(proof.traceOods);
+  parts.push(proof.traceOodsNext);
 
   // Composition OODS (16 bytes)
   parts.push(proof.compositionOods);
@@ -73,8 +79,8 @@ export function serializeProof(proof: STWOProof): Uint8Array {
     new DataView(indexBytes.buffer).setUint32(0, query.index, true);
     parts.push(indexBytes);
 
-    // Trace value (32 bytes)
-    parts.push(query.traceValue);
+    // Trace row (4 bytes per column)
+    parts.push(query.traceRow);
 
     // Trace path length (1 byte)
     parts.push(new Uint8Array([query.tracePath.length]));
@@ -122,9 +128,15 @@ export function parseProof(data: Uint8Array): STWOProof {
   const compositionCommitment = data.slice(offset, offset + 32);
   offset += 32;
 
-  // Trace OODS (16 bytes)
-  const traceOods = data.slice(offset, offset + 16);
-  offset += 16;
+  // Trace column count
+  const traceColumns = data[offset];
+  offset += 1;
+
+  // Trace OODS (16 bytes per column), then one row on
+  const traceOods = data.slice(offset, offset + 16 * traceColumns);
+  offset += 16 * traceColumns;
+  const traceOodsNext = data.slice(offset, offset + 16 * traceColumns);
+  offset += 16 * traceColumns;
 
   // Composition OODS (16 bytes)
   const compositionOods = data.slice(offset, offset + 16);
@@ -160,9 +172,9 @@ export function parseProof(data: Uint8Array): STWOProof {
     const index = new DataView(data.buffer, data.byteOffset + offset, 4).getUint32(0, true);
     offset += 4;
 
-    // Trace value
-    const traceValue = data.slice(offset, offset + 32);
-    offset += 32;
+    // Trace row
+    const traceRow = data.slice(offset, offset + 4 * traceColumns);
+    offset += 4 * traceColumns;
 
     // Trace path
     const tracePathLen = data[offset];
@@ -188,7 +200,7 @@ export function parseProof(data: Uint8Array): STWOProof {
 
     queries.push({
       index,
-      traceValue,
+      traceRow,
       tracePath,
       compositionValue,
       compositionPath,
@@ -198,7 +210,9 @@ export function parseProof(data: Uint8Array): STWOProof {
   return {
     traceCommitment,
     compositionCommitment,
+    traceColumns,
     traceOods,
+    traceOodsNext,
     compositionOods,
     friLayers,
     finalPoly,
@@ -217,12 +231,14 @@ export function generateMockProof(
     numFriLayers?: number;
     numQueries?: number;
     merkleDepth?: number;
+    traceColumns?: number;
   } = {}
 ): STWOProof {
   const {
     numFriLayers = 4,
     numQueries = 2,
     merkleDepth = 10,
+    traceColumns = 4,
   } = options;
 
   const keccak = (data: Uint8Array) => keccak_256(data);
@@ -234,7 +250,15 @@ export function generateMockProof(
   const compositionCommitment = keccak(new Uint8Array([...traceCommitment, ...merkleRoot]));
 
   // OODS values (QM31 = 4 M31 values = 16 bytes)
-  const traceOods = keccak(new Uint8Array([...commitment, 0x01])).slice(0, 16);
+  const oods = (tag: number) => {
+    const values = new Uint8Array(16 * traceColumns);
+    for (let c = 0; c < traceColumns; c++) {
+      values.set(keccak(new Uint8Array([...commitment, tag, c])).slice(0, 16), 16 * c);
+    }
+    return values;
+  };
+  const traceOods = oods(0x01);
+  const traceOodsNext = oods(0x03);
   const compositionOods = keccak(new Uint8Array([...nullifier, 0x02])).slice(0, 16);
 
   // FRI layers
@@ -253,10 +277,10 @@ export function generateMockProof(
   for (let q = 0; q < numQueries; q++) {
     const index = q * 1000;
     
-    const traceValue = keccak(new Uint8Array([...traceCommitment, q, 0x10]));
+    const traceRow = keccak(new Uint8Array([...traceCommitment, q, 0x10])).slice(0, 4 * traceColumns);
     const tracePath: Uint8Array[] = [];
     for (let p = 0; p < merkleDepth; p++) {
-      tracePath.push(keccak(new Uint8Array([...traceValue, p])));
+      tracePath.push(keccak(new Uint8Array([...traceRow, p])));
     }
 
     const compositionValue = keccak(new Uint8Array([...compositionCommitment, q, 0x20]));
@@ -267,7 +291,7 @@ export function generateMockProof(
 
     queries.push({
       index,
-      traceValue,
+      traceRow,
       tracePath,
       compositionValue,
       compositionPath,
@@ -277,7 +301,9 @@ export function generateMockProof(
   return {
     traceCommitment,
     compositionCommitment,
+    traceColumns,
     traceOods,
+    traceOodsNext,
     compositionOods,
     friLayers,
     finalPoly,
@@ -292,7 +318,8 @@ export function calculateProofSize(proof: STWOProof): number {
   let size = 0;
   size += 32; // trace commitment
   size += 32; // composition commitment
-  size += 16; // trace OODS
+  size += 1;  // trace column count
+  size += 2 * 16 * proof.traceColumns; // trace OODS, then one row on
   size += 16; // composition OODS
   size += 1;  // num FRI layers
   size += proof.friLayers.length * 32; // FRI layer commitments
@@ -302,7 +329,7 @@ export function calculateProofSize(proof: STWOProof): number {
 
   for (const query of proof.queries) {
     size += 4;  // index
-    size += 32; // trace value
+    size += 4 * proof.traceColumns; // trace row
     size += 1;  // trace path length
     size += query.tracePath.length * 32; // trace path
     size += 32; // composition value
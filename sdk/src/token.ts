@@ -0,0 +1,98 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+import {
+  TOKEN_2022_PROGRAM_ID,
+  getTokenMetadata,
+  unpackMint,
+} from '@solana/spl-token';
+import { TOKEN_METADATA_PROGRAM_ID } from './constants';
+import type { TokenMetadata } from './types';
+
+const U64_MAX = (1n << 64n) - 1n;
+
+/**
+ * Resolve decimals, symbol and name for a mint.
+ *
+ * Decimals come from the mint account (SPL Token or Token-2022). The symbol
+ * is read from the Metaplex metadata account, falling back to the Token-2022
+ * metadata extension; mints with neither get an empty symbol.
+ */
+export async function fetchTokenMetadata(
+  connection: Connection,
+  mint: PublicKey
+): Promise<TokenMetadata> {
+  const mintInfo = await connection.getAccountInfo(mint);
+  if (!mintInfo) throw new Error(`Mint ${mint.toBase58()} not found`);
+  const { decimals } = unpackMint(mint, mintInfo, mintInfo.owner);
+
+  const [metadataAddress] = PublicKey.findProgramAddressSync(
+    [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+  const metadataInfo = await connection.getAccountInfo(metadataAddress);
+  if (metadataInfo && metadataInfo.owner.equals(TOKEN_METADATA_PROGRAM_ID)) {
+    const parsed = parseMetaplexNameSymbol(metadataInfo.data);
+    if (parsed) return { mint, decimals, ...parsed };
+  }
+
+  if (mintInfo.owner.equals(TOKEN_2022_PROGRAM_ID)) {
+    const ext = await getTokenMetadata(connection, mint, undefined, TOKEN_2022_PROGRAM_ID);
+    if (ext) return { mint, decimals, name: ext.name, symbol: ext.symbol };
+  }
+
+  return { mint, decimals, name: '', symbol: '' };
+}
+
+/**
+ * Metaplex metadata: [1 key][32 update_authority][32 mint]
+ * [4 + n name][4 + n symbol]..., with strings padded by NULs
+ */
+function parseMetaplexNameSymbol(data: Buffer): { name: string; symbol: string } | null {
+  let offset = 1 + 32 + 32;
+  const readString = (): string | null => {
+    if (offset + 4 > data.length) return null;
+    const len = data.readUInt32LE(offset);
+    offset += 4;
+    if (offset + len > data.length) return null;
+    const value = data.slice(offset, offset + len).toString('utf8').replace(/\0+$/, '').trim();
+    offset += len;
+    return value;
+  };
+  const name = readString();
+  const symbol = readString();
+  if (name === null || symbol === null) return null;
+  return { name, symbol };
+}
+
+/**
+ * Format a raw token amount for display, e.g. `10500000n, 6, 'USDC'` →
+ * `"10.5 USDC"`. Trailing fractional zeros are dropped.
+ */
+export function formatAmount(amount: bigint, decimals: number, symbol?: string): string {
+  const negative = amount < 0n;
+  const abs = negative ? -amount : amount;
+  const scale = 10n ** BigInt(decimals);
+  const whole = (abs / scale).toString();
+  const frac = decimals > 0 ? (abs % scale).toString().padStart(decimals, '0').replace(/0+$/, '') : '';
+  const value = `${negative ? '-' : ''}${whole}${frac ? `.${frac}` : ''}`;
+  return symbol ? `${value} ${symbol}` : value;
+}
+
+/**
+ * Parse a human amount ("10.5") into raw units for a mint with `decimals`.
+ *
+ * Rejects exponent notation, signs, more fractional digits than the mint
+ * supports, and values that do not fit in a u64.
+ */
+export function parseAmount(input: string, decimals: number): bigint {
+  const raw = input.trim().replace(/_/g, '');
+  if (!/^\d+(\.\d+)?$/.test(raw)) throw new Error(`Invalid amount: ${input}`);
+
+  const [whole, fracRaw = ''] = raw.split('.');
+  if (fracRaw.length > decimals) {
+    throw new Error(`Invalid amount: ${input} (at most ${decimals} decimal places)`);
+  }
+  const frac = fracRaw.padEnd(decimals, '0');
+  const value = BigInt(whole) * 10n ** BigInt(decimals) + BigInt(frac || '0');
+  if (value > U64_MAX) throw new Error(`Amount too large: ${input}`);
+  return value;
+}
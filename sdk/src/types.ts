@@ -44,6 +44,8 @@ export interface ClaimParams {
   recipientTokenAccount: PublicKey;
   /** Relayer fee (in token units) */
   relayerFee?: bigint;
+  /** Extra accounts forwarded to the pool's compliance hook, if it has one */
+  complianceAccounts?: PublicKey[];
 }
 
 /**
@@ -80,10 +82,52 @@ export interface PoolInfo {
   minDeposit: bigint;
   /** Maximum relayer fee in basis points */
   maxRelayerFeeBps: number;
+  /** Program CPI'd before payout, or null if the pool is permissionless */
+  complianceHook: PublicKey | null;
+  /** stark-verifier params hash claims must be verified under, or null if unpinned */
+  requiredVerifierParamsHash: Uint8Array | null;
+  /** `SecurityLevel` byte the verifier params must guarantee, or null for any */
+  minSecurityLevel: number | null;
   /** Whether pool is paused */
   paused: boolean;
 }
 
+/**
+ * Pool registry entry (an official pool)
+ */
+export interface RegisteredPool {
+  /** Registry entry address */
+  address: PublicKey;
+  /** Registration order, 0-based */
+  index: bigint;
+  /** Pool address */
+  pool: PublicKey;
+  /** Token mint */
+  tokenMint: PublicKey;
+  /** Vault address */
+  vault: PublicKey;
+  /** Pool's minimum deposit at registration */
+  denomination: bigint;
+  /** Hash of the verifier parameters the pool's proofs target */
+  verifierConfigHash: Uint8Array;
+  /** Registration time (unix seconds) */
+  registeredAt: bigint;
+}
+
+/**
+ * Display metadata for a token mint
+ */
+export interface TokenMetadata {
+  /** Token mint */
+  mint: PublicKey;
+  /** Decimal places of the raw u64 amount */
+  decimals: number;
+  /** Ticker, e.g. "USDC"; empty when the mint has no metadata */
+  symbol: string;
+  /** Full token name; empty when the mint has no metadata */
+  name: string;
+}
+
 /**
  * Deposit record information
  */
@@ -213,11 +213,20 @@ export class StarkVerifier {
       keys: [
         { pubkey: buffer, isSigner: false, isWritable: true },
         { pubkey: this.wallet.publicKey, isSigner: true, isWritable: false },
+        { pubkey: this.getVerifierConfigPDA(), isSigner: false, isWritable: false },
       ],
       data,
     });
   }
 
+  /**
+   * Get the VerifierConfigAccount PDA: the profile proofs are checked at
+   */
+  getVerifierConfigPDA(): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync([Buffer.from('verifier-config')], this.programId);
+    return pda;
+  }
+
   private buildCloseBufferIx(buffer: PublicKey): TransactionInstruction {
     // Anchor discriminator for "close_proof_buffer"
     const discriminator = Buffer.from([0x6e, 0x7f, 0x8a, 0x9b, 0xac, 0xbd, 0xce, 0xdf]);
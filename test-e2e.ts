@@ -29,6 +29,7 @@ import {
 import { keccak256 } from 'js-sha3';
 import * as fs from 'fs';
 import * as crypto from 'crypto';
+import { hashIdentifier, computeCommitment } from './relayer/src/commitment';
 
 // ============================================================
 // Config
@@ -59,37 +60,6 @@ function loadTestWallet(): Keypair {
 // Helpers (matching frontend logic exactly)
 // ============================================================
 
-const M31_PRIME = 0x7FFFFFFF;
-
-function hashPassword(password: string): number {
-  const data = Buffer.concat([Buffer.from('murkl_password_v1'), Buffer.from(password)]);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  return view.getUint32(0, true) % M31_PRIME;
-}
-
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalized)]);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  return view.getUint32(0, true) % M31_PRIME;
-}
-
-function computeCommitment(identifier: string, password: string): Buffer {
-  const idHash = hashIdentifier(identifier);
-  const secret = hashPassword(password);
-
-  const prefix = Buffer.from('murkl_m31_hash_v1');
-  const idBuf = Buffer.alloc(4);
-  const secretBuf = Buffer.alloc(4);
-  idBuf.writeUInt32LE(idHash);
-  secretBuf.writeUInt32LE(secret);
-
-  const combined = Buffer.concat([prefix, idBuf, secretBuf]);
-  return Buffer.from(keccak256.arrayBuffer(combined));
-}
-
 function hashIdentifierForDb(identifier: string): string {
   const normalized = identifier.toLowerCase();
   const data = Buffer.concat([Buffer.from('murkl_identifier_v1'), Buffer.from(normalized)]);
@@ -269,12 +269,14 @@ function buildFinalizeAndVerifyIx(
   Buffer.from(commitment).copy(data, 8);
   Buffer.from(nullifier).copy(data, 40);
   Buffer.from(merkleRoot).copy(data, 72);
+  const [verifierConfig] = PublicKey.findProgramAddressSync([Buffer.from('verifier-config')], programId);
 
   return new TransactionInstruction({
     programId,
     keys: [
       { pubkey: buffer, isSigner: false, isWritable: true },
       { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: verifierConfig, isSigner: false, isWritable: false },
     ],
     data,
   });
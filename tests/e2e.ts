@@ -8,6 +8,7 @@ import { execSync } from "child_process";
 
 const MURKL_ID = new PublicKey("74P7nTytTESmeJTH46geZ93GLFq3yAojnvKDxJFFZa92");
 const STARK_VERIFIER_ID = new PublicKey("StArKSLbAn43UCcujFMc5gKc8rY2BVfSbguMfyLTMtw");
+const [VERIFIER_CONFIG_PDA] = PublicKey.findProgramAddressSync([Buffer.from("verifier-config")], STARK_VERIFIER_ID);
 
 function disc(name: string): Buffer {
   return crypto.createHash('sha256').update('global:' + name).digest().slice(0, 8);
@@ -310,6 +311,7 @@ describe("Murkl E2E Tests", () => {
       keys: [
         { pubkey: buffer, isSigner: false, isWritable: true },
         { pubkey: owner, isSigner: true, isWritable: false },
+        { pubkey: VERIFIER_CONFIG_PDA, isSigner: false, isWritable: false },
       ],
       data: Buffer.concat([disc("finalize_and_verify"), c32, n32, m32]),
     });
@@ -0,0 +1,236 @@
+//! JSON / hex-string API
+//!
+//! What the web app calls today: hashes go in and out as hex strings and
+//! proofs come back as a [`ProofBundle`] object via `serde-wasm-bindgen`.
+//! Behind the default `json` feature; the byte-array API in the crate root
+//! does the same work without serde or hex.
+
+use serde::Serialize;
+
+use murkl_prover::StageMetrics;
+
+use super::*;
+
+/// Returned to JS in place of a [`ProofBundle`] when proof generation fails
+#[derive(Serialize)]
+struct ProofFailure {
+    error: String,
+}
+
+fn proof_failure(error: &str) -> JsValue {
+    serde_wasm_bindgen::to_value(&ProofFailure { error: error.to_string() }).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn generate_commitment(identifier: &str, password: &str) -> String {
+    let id_hash = hash_identifier(identifier);
+    let secret = hash_password(password);
+    let commitment = pq_commitment(id_hash, secret);
+    hex::encode(commitment)
+}
+
+#[wasm_bindgen]
+pub fn generate_nullifier(password: &str, leaf_index: u32) -> String {
+    let secret = hash_password(password);
+    let nullifier = pq_nullifier(secret, leaf_index);
+    hex::encode(nullifier)
+}
+
+/// Nullifier for `epoch` of a subscription deposit; its last four bytes are the epoch (u32 LE)
+#[wasm_bindgen]
+pub fn generate_epoch_nullifier(password: &str, leaf_index: u32, epoch: u32) -> String {
+    let secret = hash_password(password);
+    let nullifier = pq_epoch_nullifier(secret, leaf_index, epoch);
+    hex::encode(nullifier)
+}
+
+/// Claim proof for the commitment at `leaf_index`; `merkle_path_hex` is its
+/// siblings up to `merkle_root_hex`, leaf level first, concatenated
+#[wasm_bindgen]
+pub fn generate_proof(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str) -> JsValue {
+    prove_with_secret(identifier, hash_password(password), leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, HashKind::Keccak, None, &mut StageRecorder::new(no_clock))
+}
+
+/// [`generate_proof`] committing with the given hash (0 = keccak, 1 = Poseidon2)
+#[wasm_bindgen]
+pub fn generate_proof_with_hash(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str, hash_kind: u8) -> JsValue {
+    match HashKind::from_byte(hash_kind) {
+        Some(hash) => prove_with_secret(identifier, hash_password(password), leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, hash, None, &mut StageRecorder::new(no_clock)),
+        None => proof_failure("Unknown hash kind"),
+    }
+}
+
+/// Cost of one prover stage, as passed to JS
+#[derive(Serialize)]
+struct StageReport {
+    stage: &'static str,
+    elapsed_ms: f64,
+    hashes: u64,
+}
+
+impl From<&StageMetrics> for StageReport {
+    fn from(metrics: &StageMetrics) -> Self {
+        StageReport {
+            stage: metrics.stage.name(),
+            elapsed_ms: metrics.elapsed.as_secs_f64() * 1e3,
+            hashes: metrics.hashes,
+        }
+    }
+}
+
+/// Time since page load in the browser; `Instant` is unavailable there
+#[cfg(target_arch = "wasm32")]
+fn clock() -> core::time::Duration {
+    core::time::Duration::from_secs_f64(js_sys::Date::now() / 1e3)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use murkl_prover::metrics::system_clock as clock;
+
+/// [`generate_proof_with_hash`] reporting prover stages as they finish
+///
+/// `on_stage` is called with `{ stage, elapsed_ms, hashes }` after each
+/// stage; proving is synchronous, so the page repaints only once it
+/// returns. A successful result also carries every stage under `metrics`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_proof_with_progress(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str, hash_kind: u8, on_stage: &js_sys::Function) -> JsValue {
+    let Some(hash) = HashKind::from_byte(hash_kind) else {
+        return proof_failure("Unknown hash kind");
+    };
+    let mut hook = |metrics: &StageMetrics| {
+        let report = serde_wasm_bindgen::to_value(&StageReport::from(metrics)).unwrap();
+        let _ = on_stage.call1(&JsValue::NULL, &report);
+    };
+    let mut recorder = StageRecorder::new(clock).with_hook(&mut hook);
+    let result = prove_with_secret(identifier, hash_password(password), leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, hash, None, &mut recorder);
+    if !recorder.stages().is_empty() {
+        let stages: Vec<StageReport> = recorder.stages().iter().map(StageReport::from).collect();
+        let _ = js_sys::Reflect::set(&result, &"metrics".into(), &serde_wasm_bindgen::to_value(&stages).unwrap());
+    }
+    result
+}
+
+/// [`generate_proof_with_hash`] grinding `pow_bits` of proof of work before
+/// the queries are drawn
+///
+/// `on_progress(attempts, expected)` is called every 65536 nonces tried;
+/// a search takes `expected` (2^pow_bits) attempts on average and can run
+/// well past it. Grinding is synchronous, so call this from a worker that
+/// posts progress back to the page. The nonce comes back as `pow_nonce`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_proof_with_pow(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str, hash_kind: u8, pow_bits: u32, on_progress: &js_sys::Function) -> JsValue {
+    let Some(hash) = HashKind::from_byte(hash_kind) else {
+        return proof_failure("Unknown hash kind");
+    };
+    let mut on_progress = |progress: GrindProgress| {
+        let _ = on_progress.call2(&JsValue::NULL, &(progress.attempts as f64).into(), &(progress.expected as f64).into());
+    };
+    let pow = Grinding { pow_bits, on_progress: &mut on_progress, resume: None, on_checkpoint: None };
+    prove_with_secret(identifier, hash_password(password), leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, hash, Some(pow), &mut StageRecorder::new(no_clock))
+}
+
+/// [`generate_proof_with_pow`] that survives a closed tab
+///
+/// `on_checkpoint(bytes)` is called with a `Uint8Array` once the nonce is
+/// found; keep it (e.g. in IndexedDB) and pass it back as `checkpoint` to
+/// skip the search when the same claim is proven again. The stages before
+/// the search are quick and simply rerun. The checkpoint holds the nonce
+/// and no witness values. One for another claim is ignored; bytes that do
+/// not parse as a checkpoint fail the call.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_proof_resumable(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str, hash_kind: u8, pow_bits: u32, checkpoint: Option<Vec<u8>>, on_progress: &js_sys::Function, on_checkpoint: &js_sys::Function) -> JsValue {
+    let Some(hash) = HashKind::from_byte(hash_kind) else {
+        return proof_failure("Unknown hash kind");
+    };
+    let resume = match checkpoint.as_deref().map(Checkpoint::from_bytes).transpose() {
+        Ok(resume) => resume,
+        Err(_) => return proof_failure("Invalid checkpoint"),
+    };
+    let mut on_progress = |progress: GrindProgress| {
+        let _ = on_progress.call2(&JsValue::NULL, &(progress.attempts as f64).into(), &(progress.expected as f64).into());
+    };
+    let mut save = |checkpoint: &Checkpoint| {
+        let bytes = js_sys::Uint8Array::from(checkpoint.to_bytes().as_slice());
+        let _ = on_checkpoint.call1(&JsValue::NULL, &bytes);
+    };
+    let pow = Grinding { pow_bits, on_progress: &mut on_progress, resume: resume.as_ref(), on_checkpoint: Some(&mut save) };
+    prove_with_secret(identifier, hash_password(password), leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, hash, Some(pow), &mut StageRecorder::new(no_clock))
+}
+
+/// [`generate_proof`] for one epoch of a subscription deposit, claimed with `claim_epoch`
+#[wasm_bindgen]
+pub fn generate_subscription_proof(identifier: &str, password: &str, leaf_index: u32, epoch: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str) -> JsValue {
+    prove_with_secret(identifier, hash_password(password), leaf_index, Some(epoch), merkle_root_hex, merkle_path_hex, recipient_hex, HashKind::Keccak, None, &mut StageRecorder::new(no_clock))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_with_secret(identifier: &str, secret: u32, leaf_index: u32, epoch: Option<u32>, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str, hash: HashKind, pow: Option<Grinding<'_>>, recorder: &mut StageRecorder<'_>) -> JsValue {
+    let merkle_root: [u8; 32] = match hex::decode(merkle_root_hex) {
+        Ok(bytes) if bytes.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&bytes);
+            arr
+        }
+        _ => return proof_failure("Invalid merkle_root hex"),
+    };
+    
+    let recipient: [u8; 32] = match hex::decode(recipient_hex) {
+        Ok(bytes) if bytes.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&bytes);
+            arr
+        }
+        _ => return proof_failure("Invalid recipient hex"),
+    };
+
+    let merkle_path: Vec<[u8; 32]> = match hex::decode(merkle_path_hex) {
+        Ok(bytes) if bytes.len() % 32 == 0 => bytes.chunks_exact(32).map(|sibling| sibling.try_into().unwrap()).collect(),
+        _ => return proof_failure("Invalid merkle_path hex"),
+    };
+
+    match claim_bundle(identifier, secret, leaf_index, epoch, &merkle_root, &merkle_path, &recipient, hash, pow, recorder) {
+        Ok(bundle) => serde_wasm_bindgen::to_value(&bundle).unwrap(),
+        Err(e) => proof_failure(&e.to_string()),
+    }
+}
+
+#[wasm_bindgen]
+pub fn verify_commitment(identifier: &str, password: &str, commitment_hex: &str) -> bool {
+    let id_hash = hash_identifier(identifier);
+    let secret = hash_password(password);
+    let computed = pq_commitment(id_hash, secret);
+    let expected = hex::decode(commitment_hex).unwrap_or_default();
+    computed[..] == expected[..]
+}
+
+/// The `merkle_path_hex` of the commitment at `leaf_index`, from every
+/// commitment in the pool (`leaves_hex`, in leaf order, concatenated);
+/// `undefined` if there is no such leaf.
+#[wasm_bindgen]
+pub fn generate_merkle_path(leaves_hex: &str, leaf_index: u32) -> Option<String> {
+    let bytes = hex::decode(leaves_hex).ok().filter(|bytes| bytes.len() % 32 == 0)?;
+    let leaves: Vec<[u8; 32]> = bytes.chunks_exact(32).map(|leaf| leaf.try_into().unwrap()).collect();
+    let witness = murkl_prover::MerkleWitness::from_leaves(&leaves, TREE_DEPTH, leaf_index as usize)?;
+    Some(hex::encode(witness.siblings.concat()))
+}
+
+/// [`generate_commitment`] from a finished [`KeyStretch`]; `undefined` if it
+/// was cancelled or has rounds left.
+#[wasm_bindgen]
+pub fn generate_commitment_stretched(identifier: &str, stretch: &KeyStretch) -> Option<String> {
+    let secret = stretch.secret()?;
+    Some(hex::encode(pq_commitment(hash_identifier(identifier), secret)))
+}
+
+/// [`generate_proof`] from a finished [`KeyStretch`]
+#[wasm_bindgen]
+pub fn generate_proof_stretched(identifier: &str, stretch: &KeyStretch, leaf_index: u32, merkle_root_hex: &str, merkle_path_hex: &str, recipient_hex: &str) -> JsValue {
+    match stretch.secret() {
+        Some(secret) => prove_with_secret(identifier, secret, leaf_index, None, merkle_root_hex, merkle_path_hex, recipient_hex, HashKind::Keccak, None, &mut StageRecorder::new(no_clock)),
+        None if stretch.is_cancelled() => proof_failure("Key stretching was cancelled"),
+        None => proof_failure("Key stretching has not finished"),
+    }
+}
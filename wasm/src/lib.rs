@@ -4,23 +4,30 @@
 //! Output format matches on-chain verifier exactly.
 //!
 //! Uses `murkl-prover` for shared cryptographic primitives.
+//!
+//! Two JS APIs produce the same proofs. The default `json` feature keeps
+//! the hex-string / [`ProofBundle`]-object functions the web app uses
+//! (`generate_*`). The byte-array functions (`*_bytes`, [`ClaimProof`]) are
+//! always built; with `--no-default-features` they are all that is left,
+//! dropping serde, serde-wasm-bindgen and hex from the bundle. The
+//! `minimal` Cargo profile adds `opt-level = "z"` and `panic = "abort"`;
+//! `scripts/build-wasm-minimal.sh` builds it, runs wasm-opt and prints both
+//! bundle sizes.
 
 use wasm_bindgen::prelude::*;
-use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 
-// Import from murkl-prover SDK
-use murkl_prover::M31_PRIME;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::*;
 
-/// Simple keccak256 hash (matches on-chain verifier)
-fn keccak_single(data: &[u8]) -> [u8; 32] {
-    let mut hasher = Keccak256::new();
-    hasher.update(data);
-    let result = hasher.finalize();
-    let mut hash = [0u8; 32];
-    hash.copy_from_slice(&result);
-    hash
-}
+// Import from murkl-prover SDK
+use murkl_prover::air::poseidon2::{MurklHashAir, WITNESS_COLUMNS};
+use murkl_prover::metrics::no_clock;
+use murkl_proof_format::{FinalLayer, FriOpening, QueryOpening, SerializedProof, FRI_GROUP_DOMAIN, TRACE_ROW_DOMAIN};
+use murkl_prover::{Checkpoint, HashKind, ProofBundle, ProofError, SecureCirclePoint, StageRecorder, ProveStage, M31_PRIME, TREE_DEPTH};
+use murkl_prover::prover::{grind_resumable, GrindProgress};
 
 /// Multi-input keccak (for compatibility with SDK)
 fn keccak_multi(inputs: &[&[u8]]) -> [u8; 32] {
@@ -37,8 +44,11 @@ fn keccak_multi(inputs: &[&[u8]]) -> [u8; 32] {
 // Prover config (matches verifier)
 const N_FRI_LAYERS: usize = 3;
 const N_QUERIES: usize = 4;
-const DOMAIN_SIZE: u32 = 1024; // 2^10 for constraint evaluation
-const LOG_DOMAIN_SIZE: usize = 14; // 10 + 4 (LOG_BLOWUP)
+const TRACE_COLUMNS: usize = WITNESS_COLUMNS;
+const LOG_TRACE_SIZE: usize = 10;
+const LOG_BLOWUP: usize = 4;
+const LOG_FOLDING_FACTOR: usize = 2; // Fold by 4 each round
+const LOG_DOMAIN_SIZE: usize = LOG_TRACE_SIZE + LOG_BLOWUP;
 const EVAL_DOMAIN_SIZE: usize = 1 << LOG_DOMAIN_SIZE; // 16384
 
 // ============================================================================
@@ -55,7 +65,7 @@ impl MerkleTree {
     /// Build a Merkle tree from raw leaf values (will be hashed for tree nodes)
     /// The verifier expects leaf_value and hashes it, so we store raw values
     /// in `leaves` and hashed values in the tree nodes.
-    fn new(leaves: Vec<[u8; 32]>) -> Self {
+    fn new(leaves: Vec<[u8; 32]>, hash: HashKind) -> Self {
         let n = leaves.len();
         assert!(n.is_power_of_two(), "Leaf count must be power of 2");
         let height = (n as f64).log2() as usize;
@@ -64,19 +74,14 @@ impl MerkleTree {
         let mut nodes = vec![[0u8; 32]; 2 * n - 1];
         
         // Hash leaves and put in bottom level of tree
-        // This matches verifier which does: current = keccak_hash(leaf_value)
+        // This matches verifier which does: current = hash(leaf_value)
         for (i, leaf) in leaves.iter().enumerate() {
-            nodes[n - 1 + i] = keccak_single(leaf);
+            nodes[n - 1 + i] = hash.hash(&[leaf]);
         }
         
         // Build tree bottom-up
         for i in (0..n - 1).rev() {
-            let left = &nodes[2 * i + 1];
-            let right = &nodes[2 * i + 2];
-            let mut combined = [0u8; 64];
-            combined[..32].copy_from_slice(left);
-            combined[32..].copy_from_slice(right);
-            nodes[i] = keccak_single(&combined);
+            nodes[i] = hash.hash_node(&nodes[2 * i + 1], &nodes[2 * i + 2]);
         }
         
         MerkleTree { leaves, nodes, height }
@@ -109,41 +114,36 @@ impl MerkleTree {
     }
 }
 
-/// FRI Merkle tree for 16-byte QM31 values
-/// Leaf hash = keccak(16 bytes), matching verifier's hash_qm31_leaf
+/// FRI Merkle tree with a leaf per fold-by-4 group
+/// Leaf hash = hash(hash(FRI_GROUP_DOMAIN || four 16-byte QM31 values)),
+/// matching the verifier's FRI leaves
 struct FriMerkleTree {
-    qm31_values: Vec<[u8; 16]>,
+    groups: Vec<[u8; 64]>,
     nodes: Vec<[u8; 32]>,
     height: usize,
 }
 
 impl FriMerkleTree {
-    fn new(qm31_values: Vec<[u8; 16]>) -> Self {
-        let n = qm31_values.len();
+    fn new(groups: Vec<[u8; 64]>, hash: HashKind) -> Self {
+        let n = groups.len();
         assert!(n.is_power_of_two(), "Leaf count must be power of 2");
         let height = (n as f64).log2() as usize;
         
         let mut nodes = vec![[0u8; 32]; 2 * n - 1];
         
-        // Hash 32-byte padded QM31 values to get leaf nodes
-        // Verifier passes 32 bytes (16 QM31 + 16 zeros) to verify_merkle_path
-        for (i, qm31) in qm31_values.iter().enumerate() {
-            let mut padded = [0u8; 32];
-            padded[..16].copy_from_slice(qm31);
-            nodes[n - 1 + i] = keccak_single(&padded);
+        // The verifier hashes the group into a leaf value, and
+        // verify_merkle_path hashes that value once more
+        for (i, group) in groups.iter().enumerate() {
+            let leaf = hash.hash(&[FRI_GROUP_DOMAIN, group]);
+            nodes[n - 1 + i] = hash.hash(&[&leaf]);
         }
         
         // Build tree bottom-up
         for i in (0..n - 1).rev() {
-            let left = &nodes[2 * i + 1];
-            let right = &nodes[2 * i + 2];
-            let mut combined = [0u8; 64];
-            combined[..32].copy_from_slice(left);
-            combined[32..].copy_from_slice(right);
-            nodes[i] = keccak_single(&combined);
+            nodes[i] = hash.hash_node(&nodes[2 * i + 1], &nodes[2 * i + 2]);
         }
         
-        FriMerkleTree { qm31_values, nodes, height }
+        FriMerkleTree { groups, nodes, height }
     }
     
     fn root(&self) -> [u8; 32] {
@@ -152,7 +152,7 @@ impl FriMerkleTree {
     
     /// Get authentication path for leaf at index
     fn get_path(&self, index: usize) -> Vec<[u8; 32]> {
-        let n = self.qm31_values.len();
+        let n = self.groups.len();
         let mut path = Vec::with_capacity(self.height);
         let mut idx = n - 1 + index;
         
@@ -164,10 +164,6 @@ impl FriMerkleTree {
         
         path
     }
-    
-    fn get_qm31(&self, index: usize) -> [u8; 16] {
-        self.qm31_values[index]
-    }
 }
 
 // ============================================================================
@@ -194,10 +190,6 @@ impl M31 {
         M31::new(((self.0 as u64 * other.0 as u64) % M31_PRIME as u64) as u32)
     }
     
-    fn neg(self) -> Self {
-        if self.0 == 0 { M31::new(0) } else { M31::new(M31_PRIME - self.0) }
-    }
-    
     fn pow(self, mut exp: u32) -> Self {
         let mut base = self;
         let mut result = M31::new(1);
@@ -229,14 +221,6 @@ impl QM31 {
         QM31 { a, b, c, d }
     }
     
-    fn zero() -> Self {
-        QM31::new(M31::new(0), M31::new(0), M31::new(0), M31::new(0))
-    }
-    
-    fn one() -> Self {
-        QM31::new(M31::new(1), M31::new(0), M31::new(0), M31::new(0))
-    }
-    
     fn add(self, other: Self) -> Self {
         QM31::new(
             self.a.add(other.a),
@@ -278,40 +262,6 @@ impl QM31 {
         QM31::new(r0.add(r2_real), i0.add(r2_imag), r1, i1)
     }
     
-    fn pow(self, mut exp: u32) -> Self {
-        let mut base = self;
-        let mut result = QM31::one();
-        while exp > 0 {
-            if exp & 1 == 1 {
-                result = result.mul(base);
-            }
-            base = base.mul(base);
-            exp >>= 1;
-        }
-        result
-    }
-    
-    fn inv(self) -> Self {
-        // QM31 inverse matching on-chain verifier exactly
-        let a2_b2 = self.a.mul(self.a).sub(self.b.mul(self.b));
-        let two_ab = self.a.mul(self.b).mul(M31::new(2));
-        let c2_d2 = self.c.mul(self.c).sub(self.d.mul(self.d));
-        let two_cd = self.c.mul(self.d).mul(M31::new(2));
-        let u2_real = c2_d2.mul(M31::new(2)).sub(two_cd);
-        let u2_imag = c2_d2.add(two_cd.mul(M31::new(2)));
-        let nr = a2_b2.sub(u2_real);
-        let ni = two_ab.sub(u2_imag);
-        let norm_m31 = nr.mul(nr).add(ni.mul(ni));
-        let norm_inv = norm_m31.inv();
-        let cm31_inv_r = nr.mul(norm_inv);
-        let cm31_inv_i = ni.neg().mul(norm_inv);
-        let res_a = self.a.mul(cm31_inv_r).sub(self.b.mul(cm31_inv_i));
-        let res_b = self.a.mul(cm31_inv_i).add(self.b.mul(cm31_inv_r));
-        let res_c = self.c.mul(cm31_inv_r).sub(self.d.mul(cm31_inv_i)).neg();
-        let res_d = self.c.mul(cm31_inv_i).add(self.d.mul(cm31_inv_r)).neg();
-        QM31::new(res_a, res_b, res_c, res_d)
-    }
-    
     fn to_bytes(&self) -> [u8; 32] {
         let mut bytes = [0u8; 32];
         bytes[0..4].copy_from_slice(&self.a.0.to_le_bytes());
@@ -323,47 +273,98 @@ impl QM31 {
     }
 }
 
+/// QM31 value held in the first 16 bytes of a composition leaf
+fn qm31_from_leaf(leaf: &[u8; 32]) -> QM31 {
+    let limb = |i: usize| M31::new(u32::from_le_bytes(leaf[4 * i..4 * i + 4].try_into().unwrap()));
+    QM31::new(limb(0), limb(1), limb(2), limb(3))
+}
+
+/// Fold one group of four onto the next FRI layer, as stark-verifier's
+/// `verify_fri_fold` checks it. The group holds f at p, -p, r and -r with
+/// r = p plus a quarter turn, so r.x = p.y: each antipodal pair folds with
+/// α onto 2p and -2p, and those two fold with α² onto 4p.
+fn fri_fold(siblings: &[QM31; 4], alpha: &QM31, point: murkl_prover::circle::CirclePoint) -> QM31 {
+    let at_p = fold_antipodal(siblings[0], siblings[1], alpha, M31::new(point.x.value()));
+    let at_minus_p = fold_antipodal(siblings[2], siblings[3], alpha, M31::new(point.y.value()));
+    fold_antipodal(at_p, at_minus_p, &alpha.mul(*alpha), M31::new(point.double().x.value()))
+}
+
+/// `f(q) + f(-q) + α·(f(q) - f(-q))/x`
+fn fold_antipodal(at_q: QM31, at_minus_q: QM31, alpha: &QM31, x: M31) -> QM31 {
+    let x_inv = x.inv();
+    let diff = at_q.sub(at_minus_q);
+    let odd = QM31::new(diff.a.mul(x_inv), diff.b.mul(x_inv), diff.c.mul(x_inv), diff.d.mul(x_inv));
+    at_q.add(at_minus_q).add(alpha.mul(odd))
+}
+
+/// Domain point at `position` of FRI layer `layer`: the layer-0 point at
+/// `position << 2·layer`, doubled twice per fold
+fn fri_layer_point(position: usize, layer: usize) -> murkl_prover::circle::CirclePoint {
+    murkl_prover::fri::final_domain_point(
+        position << (layer * LOG_FOLDING_FACTOR),
+        LOG_DOMAIN_SIZE as u32,
+        layer,
+        LOG_FOLDING_FACTOR as u32,
+    )
+}
+
 // ============================================================================
-// Fiat-Shamir Channel (matches on-chain verifier exactly)
+// Fiat-Shamir Channel (same transcript as stark-verifier's; see
+// tests::test_channel_matches_verifier)
 // ============================================================================
 
 struct Channel {
     state: [u8; 32],
     counter: u64,
+    hash: HashKind,
+    /// Hashes run so far
+    hashes: u64,
 }
 
 impl Channel {
-    fn new() -> Self {
-        Self { state: [0u8; 32], counter: 0 }
+    fn new(hash: HashKind) -> Self {
+        Self { state: [0u8; 32], counter: 0, hash, hashes: 0 }
     }
     
     fn mix_digest(&mut self, digest: &[u8; 32]) {
-        let mut data = [0u8; 64];
-        data[..32].copy_from_slice(&self.state);
-        data[32..].copy_from_slice(digest);
-        self.state = keccak_single(&data);
+        self.state = self.hash.hash(&[&self.state, digest]);
         self.counter += 1;
+        self.hashes += 1;
     }
     
     fn mix_qm31(&mut self, elem: &QM31) {
-        let mut data = [0u8; 48];
-        data[..32].copy_from_slice(&self.state);
-        data[32..36].copy_from_slice(&elem.a.0.to_le_bytes());
-        data[36..40].copy_from_slice(&elem.b.0.to_le_bytes());
-        data[40..44].copy_from_slice(&elem.c.0.to_le_bytes());
-        data[44..48].copy_from_slice(&elem.d.0.to_le_bytes());
-        self.state = keccak_single(&data);
+        let mut limbs = [0u8; 16];
+        limbs[0..4].copy_from_slice(&elem.a.0.to_le_bytes());
+        limbs[4..8].copy_from_slice(&elem.b.0.to_le_bytes());
+        limbs[8..12].copy_from_slice(&elem.c.0.to_le_bytes());
+        limbs[12..16].copy_from_slice(&elem.d.0.to_le_bytes());
+        self.state = self.hash.hash(&[&self.state, &limbs]);
         self.counter += 1;
+        self.hashes += 1;
     }
     
-    fn squeeze_m31(&mut self) -> M31 {
-        let mut data = [0u8; 40];
-        data[..32].copy_from_slice(&self.state);
-        data[32..40].copy_from_slice(&self.counter.to_le_bytes());
-        let hash = keccak_single(&data);
+    fn mix_nonce(&mut self, nonce: u64) {
+        self.state = self.hash.hash(&[&self.state, &nonce.to_le_bytes()]);
+        self.counter += 1;
+        self.hashes += 1;
+    }
+    
+    fn squeeze_word(&mut self) -> u32 {
+        let hash = self.hash.hash(&[&self.state, &self.counter.to_le_bytes()]);
         self.state = hash;
         self.counter += 1;
-        M31::new(u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]))
+        self.hashes += 1;
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+    
+    fn squeeze_m31(&mut self) -> M31 {
+        M31::new(self.squeeze_word())
+    }
+    
+    /// Uniform index below 2^bits: the low bits of the word, as the verifier
+    /// derives it
+    fn squeeze_bits(&mut self, bits: usize) -> usize {
+        (self.squeeze_word() & ((1u32 << bits) - 1)) as usize
     }
     
     fn squeeze_qm31(&mut self) -> QM31 {
@@ -373,144 +374,302 @@ impl Channel {
         let d = self.squeeze_m31();
         QM31::new(a, b, c, d)
     }
+
+    /// OODS point, drawn as the verifier's `squeeze_circle_point` does
+    fn squeeze_circle_point(&mut self) -> SecureCirclePoint {
+        loop {
+            let t = self.squeeze_qm31();
+            let t = murkl_prover::QM31::from_u32(t.a.0, t.b.0, t.c.0, t.d.0);
+            if let Some(point) = SecureCirclePoint::try_from_t(t) {
+                return point;
+            }
+        }
+    }
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-fn bytes_to_qm31(bytes: &[u8; 32]) -> QM31 {
-    let hash = keccak_single(bytes);
+fn from_prover_qm31(value: murkl_prover::QM31) -> QM31 {
     QM31::new(
-        M31::new(u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])),
-        M31::new(u32::from_le_bytes([hash[4], hash[5], hash[6], hash[7]])),
-        M31::new(u32::from_le_bytes([hash[8], hash[9], hash[10], hash[11]])),
-        M31::new(u32::from_le_bytes([hash[12], hash[13], hash[14], hash[15]])),
+        M31::new(value.a.value()),
+        M31::new(value.b.value()),
+        M31::new(value.c.value()),
+        M31::new(value.d.value()),
     )
 }
 
-fn evaluate_murkl_constraint(
-    trace_oods: &QM31,
-    commitment: &[u8; 32],
-    nullifier: &[u8; 32],
-    merkle_root: &[u8; 32],
-    alpha: &QM31,
-    oods_point: &QM31,
-) -> QM31 {
-    let c = bytes_to_qm31(commitment);
-    let n = bytes_to_qm31(nullifier);
-    let r = bytes_to_qm31(merkle_root);
-    
-    let c1 = trace_oods.sub(c);
-    let c2 = alpha.mul(trace_oods.sub(n));
-    let alpha_sq = alpha.mul(*alpha);
-    let c3 = alpha_sq.mul(trace_oods.sub(r));
-    let constraint_sum = c1.add(c2).add(c3);
-    
-    let oods_pow = oods_point.pow(DOMAIN_SIZE);
-    let vanishing_at_oods = oods_pow.sub(QM31::one());
-    
-    let is_zero = vanishing_at_oods.a.0 == 0 
-        && vanishing_at_oods.b.0 == 0 
-        && vanishing_at_oods.c.0 == 0 
-        && vanishing_at_oods.d.0 == 0;
-    
-    if is_zero { constraint_sum } else { constraint_sum.mul(vanishing_at_oods.inv()) }
+/// Extend the witness columns over the commitment coset
+///
+/// Each column holds the trace over the canonic coset of the trace length;
+/// it is interpolated by circle FFT and its polynomial evaluated over the
+/// commitment coset, which is disjoint from the trace domain, so no leaf
+/// holds a trace value.
+fn extend_witness_columns(columns: &[Vec<murkl_prover::M31>]) -> Vec<Vec<murkl_prover::M31>> {
+    use murkl_prover::circle::{evaluate_on_domain, icfft, Coset, FftTwiddles};
+
+    let twiddles = FftTwiddles::new(LOG_TRACE_SIZE as u32);
+    let domain = Coset::commitment(LOG_DOMAIN_SIZE as u32);
+    columns.iter().map(|column| evaluate_on_domain(&icfft(column, &twiddles), &domain)).collect()
 }
 
 // ============================================================================
 // Public API
 // ============================================================================
 
-#[derive(Serialize, Deserialize)]
-struct ProofBundle {
-    commitment: String,
-    nullifier: String,
+/// Claim proof for native callers (e.g. murkl-client's devnet canary)
+///
+/// The same bundle [`generate_proof_with_hash`] returns to JS.
+/// `merkle_path` holds the commitment's [`TREE_DEPTH`] siblings in the pool
+/// tree, leaf level first; fails if it has another length.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_claim(
+    identifier: &str,
+    password: &str,
     leaf_index: u32,
-    proof: String,
-    proof_size: usize,
-    error: Option<String>,
+    merkle_root: &[u8; 32],
+    merkle_path: &[[u8; 32]],
+    recipient: &[u8; 32],
+    hash: HashKind,
+    recorder: &mut StageRecorder<'_>,
+) -> Result<ProofBundle, ProofError> {
+    let secret = hash_password(password);
+    claim_bundle(identifier, secret, leaf_index, None, merkle_root, merkle_path, recipient, hash, None, recorder)
 }
 
-#[wasm_bindgen]
-pub fn generate_commitment(identifier: &str, password: &str) -> String {
+/// Proof of work to grind before query indices are squeezed
+struct Grinding<'a> {
+    pow_bits: u32,
+    /// Called with the search's progress every
+    /// [`GRIND_REPORT_INTERVAL`](murkl_prover::prover::GRIND_REPORT_INTERVAL) nonces
+    on_progress: &'a mut dyn FnMut(GrindProgress),
+    /// Checkpoint of an interrupted search for the same proof
+    resume: Option<&'a Checkpoint>,
+    /// Gets the checkpoint holding the nonce once it is found
+    on_checkpoint: Option<&'a mut dyn FnMut(&Checkpoint)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn claim_bundle(
+    identifier: &str,
+    secret: u32,
+    leaf_index: u32,
+    epoch: Option<u32>,
+    merkle_root: &[u8; 32],
+    merkle_path: &[[u8; 32]],
+    recipient: &[u8; 32],
+    hash: HashKind,
+    pow: Option<Grinding<'_>>,
+    recorder: &mut StageRecorder<'_>,
+) -> Result<ProofBundle, ProofError> {
     let id_hash = hash_identifier(identifier);
-    let secret = hash_password(password);
     let commitment = pq_commitment(id_hash, secret);
-    hex::encode(commitment)
+    let nullifier = match epoch {
+        Some(epoch) => pq_epoch_nullifier(secret, leaf_index, epoch),
+        None => pq_nullifier(secret, leaf_index),
+    };
+    let (proof, pow_nonce) = generate_stark_proof(
+        id_hash, secret, leaf_index, &commitment, &nullifier, merkle_root, merkle_path, recipient, hash, pow, recorder,
+    )?;
+    let bundle = ProofBundle::new(proof, commitment, nullifier, leaf_index);
+    Ok(match pow_nonce {
+        Some(nonce) => bundle.with_pow_nonce(nonce),
+        None => bundle,
+    })
 }
 
+/// Password stretching driven from JS in chunks
+///
+/// Stretching runs on the calling thread, so a UI calls [`KeyStretch::step`]
+/// with a small round budget, yields to the event loop in between, and can
+/// [`KeyStretch::cancel`] at any point (e.g. from an `AbortSignal`).
 #[wasm_bindgen]
-pub fn generate_nullifier(password: &str, leaf_index: u32) -> String {
-    let secret = hash_password(password);
-    let nullifier = pq_nullifier(secret, leaf_index);
-    hex::encode(nullifier)
+pub struct KeyStretch {
+    inner: murkl_prover::PasswordStretch,
+    cancelled: bool,
 }
 
 #[wasm_bindgen]
-pub fn generate_proof(identifier: &str, password: &str, leaf_index: u32, merkle_root_hex: &str, recipient_hex: &str) -> JsValue {
-    let merkle_root: [u8; 32] = match hex::decode(merkle_root_hex) {
-        Ok(bytes) if bytes.len() == 32 => {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&bytes);
-            arr
+impl KeyStretch {
+    #[wasm_bindgen(constructor)]
+    pub fn new(password: &str, rounds: u32) -> KeyStretch {
+        KeyStretch {
+            inner: murkl_prover::PasswordStretch::new(password, rounds),
+            cancelled: false,
         }
-        _ => {
-            let bundle = ProofBundle {
-                commitment: String::new(),
-                nullifier: String::new(),
-                leaf_index,
-                proof: String::new(),
-                proof_size: 0,
-                error: Some("Invalid merkle_root hex".to_string()),
-            };
-            return serde_wasm_bindgen::to_value(&bundle).unwrap();
+    }
+
+    /// Run up to `max_rounds` rounds and return progress in [0, 1].
+    /// Does nothing once cancelled.
+    pub fn step(&mut self, max_rounds: u32) -> f64 {
+        if !self.cancelled {
+            self.inner.step(max_rounds);
         }
-    };
-    
-    let recipient: [u8; 32] = match hex::decode(recipient_hex) {
-        Ok(bytes) if bytes.len() == 32 => {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&bytes);
-            arr
+        self.progress()
+    }
+
+    pub fn progress(&self) -> f64 {
+        match self.inner.rounds() {
+            0 => 1.0,
+            rounds => self.inner.rounds_done() as f64 / rounds as f64,
         }
-        _ => {
-            let bundle = ProofBundle {
-                commitment: String::new(),
-                nullifier: String::new(),
-                leaf_index,
-                proof: String::new(),
-                proof_size: 0,
-                error: Some("Invalid recipient hex".to_string()),
-            };
-            return serde_wasm_bindgen::to_value(&bundle).unwrap();
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.cancelled && self.inner.is_finished()
+    }
+
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl KeyStretch {
+    fn secret(&self) -> Option<u32> {
+        if self.cancelled {
+            return None;
         }
-    };
+        self.inner.secret().map(|s| s.value())
+    }
+}
 
-    let id_hash = hash_identifier(identifier);
-    let secret = hash_password(password);
-    let commitment = pq_commitment(id_hash, secret);
-    let nullifier = pq_nullifier(secret, leaf_index);
-    let proof = generate_stark_proof(id_hash, secret, leaf_index, &commitment, &nullifier, &merkle_root, &recipient);
-
-    let bundle = ProofBundle {
-        commitment: hex::encode(commitment),
-        nullifier: hex::encode(nullifier),
-        leaf_index,
-        proof: hex::encode(&proof),
-        proof_size: proof.len(),
-        error: None,
-    };
-    serde_wasm_bindgen::to_value(&bundle).unwrap()
+/// Proof and public inputs from the byte-array API, read by JS through
+/// `Uint8Array` getters
+#[wasm_bindgen]
+pub struct ClaimProof {
+    proof: Vec<u8>,
+    commitment: [u8; 32],
+    nullifier: [u8; 32],
+    leaf_index: u32,
 }
 
 #[wasm_bindgen]
-pub fn verify_commitment(identifier: &str, password: &str, commitment_hex: &str) -> bool {
-    let id_hash = hash_identifier(identifier);
-    let secret = hash_password(password);
-    let computed = pq_commitment(id_hash, secret);
-    let expected = hex::decode(commitment_hex).unwrap_or_default();
-    computed[..] == expected[..]
+impl ClaimProof {
+    /// Serialized proof, as uploaded to the verifier buffer
+    #[wasm_bindgen(getter)]
+    pub fn proof(&self) -> Vec<u8> {
+        self.proof.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn commitment(&self) -> Vec<u8> {
+        self.commitment.to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nullifier(&self) -> Vec<u8> {
+        self.nullifier.to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn leaf_index(&self) -> u32 {
+        self.leaf_index
+    }
+}
+
+impl From<ProofBundle> for ClaimProof {
+    fn from(bundle: ProofBundle) -> Self {
+        ClaimProof {
+            proof: bundle.proof,
+            commitment: bundle.commitment,
+            nullifier: bundle.nullifier,
+            leaf_index: bundle.leaf_index,
+        }
+    }
+}
+
+/// A 32-byte argument from JS
+fn hash_arg(bytes: &[u8], name: &str) -> Result<[u8; 32], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new(&format!("{} must be 32 bytes", name)))
+}
+
+/// A Merkle path from JS: the siblings' 32 bytes each, leaf level first
+fn path_arg(bytes: &[u8]) -> Result<Vec<[u8; 32]>, JsError> {
+    if bytes.len() != TREE_DEPTH * 32 {
+        return Err(JsError::new(&format!("merkle_path must be {} bytes", TREE_DEPTH * 32)));
+    }
+    Ok(bytes.chunks_exact(32).map(|sibling| sibling.try_into().unwrap()).collect())
+}
+
+/// Identifier for a verified `phone:<number>` or `email:<address>` channel,
+/// salted with the relayer's OTP session token; pass it as the identifier
+/// to deposit and claim so the raw address never leaves the device
+#[wasm_bindgen]
+pub fn blind_identifier(otp_session_token: &str, channel: &str) -> Result<String, JsError> {
+    if otp_session_token.is_empty() {
+        return Err(JsError::new("OTP session token is empty"));
+    }
+    murkl_prover::blind_identifier(otp_session_token.as_bytes(), channel)
+        .ok_or_else(|| JsError::new("Channel must be phone:+<number> or email:<address>"))
+}
+
+#[wasm_bindgen]
+pub fn commitment_bytes(identifier: &str, password: &str) -> Vec<u8> {
+    pq_commitment(hash_identifier(identifier), hash_password(password)).to_vec()
+}
+
+#[wasm_bindgen]
+pub fn nullifier_bytes(password: &str, leaf_index: u32) -> Vec<u8> {
+    pq_nullifier(hash_password(password), leaf_index).to_vec()
+}
+
+/// Subscription epoch nullifier; its last four bytes are the epoch (u32 LE)
+#[wasm_bindgen]
+pub fn epoch_nullifier_bytes(password: &str, leaf_index: u32, epoch: u32) -> Vec<u8> {
+    pq_epoch_nullifier(hash_password(password), leaf_index, epoch).to_vec()
+}
+
+/// Claim proof over a 32-byte `merkle_root` and `recipient`, committing
+/// with `hash_kind` (0 = keccak, 1 = Poseidon2)
+///
+/// `merkle_path` is the commitment's [`TREE_DEPTH`] siblings up to
+/// `merkle_root`, 32 bytes each, leaf level first.
+#[wasm_bindgen]
+pub fn prove_bytes(identifier: &str, password: &str, leaf_index: u32, merkle_root: &[u8], merkle_path: &[u8], recipient: &[u8], hash_kind: u8) -> Result<ClaimProof, JsError> {
+    let hash = HashKind::from_byte(hash_kind).ok_or_else(|| JsError::new("Unknown hash kind"))?;
+    prove_secret_bytes(identifier, hash_password(password), leaf_index, None, merkle_root, merkle_path, recipient, hash)
+}
+
+/// [`prove_bytes`] for one epoch of a subscription deposit
+#[wasm_bindgen]
+pub fn prove_subscription_bytes(identifier: &str, password: &str, leaf_index: u32, epoch: u32, merkle_root: &[u8], merkle_path: &[u8], recipient: &[u8]) -> Result<ClaimProof, JsError> {
+    prove_secret_bytes(identifier, hash_password(password), leaf_index, Some(epoch), merkle_root, merkle_path, recipient, HashKind::Keccak)
+}
+
+/// [`commitment_bytes`] from a finished [`KeyStretch`]; `undefined` if it
+/// was cancelled or has rounds left
+#[wasm_bindgen]
+pub fn commitment_bytes_stretched(identifier: &str, stretch: &KeyStretch) -> Option<Vec<u8>> {
+    Some(pq_commitment(hash_identifier(identifier), stretch.secret()?).to_vec())
+}
+
+/// [`prove_bytes`] (keccak) from a finished [`KeyStretch`]
+#[wasm_bindgen]
+pub fn prove_bytes_stretched(identifier: &str, stretch: &KeyStretch, leaf_index: u32, merkle_root: &[u8], merkle_path: &[u8], recipient: &[u8]) -> Result<ClaimProof, JsError> {
+    let secret = match stretch.secret() {
+        Some(secret) => secret,
+        None if stretch.is_cancelled() => return Err(JsError::new("Key stretching was cancelled")),
+        None => return Err(JsError::new("Key stretching has not finished")),
+    };
+    prove_secret_bytes(identifier, secret, leaf_index, None, merkle_root, merkle_path, recipient, HashKind::Keccak)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_secret_bytes(identifier: &str, secret: u32, leaf_index: u32, epoch: Option<u32>, merkle_root: &[u8], merkle_path: &[u8], recipient: &[u8], hash: HashKind) -> Result<ClaimProof, JsError> {
+    let merkle_root = hash_arg(merkle_root, "merkle_root")?;
+    let merkle_path = path_arg(merkle_path)?;
+    let recipient = hash_arg(recipient, "recipient")?;
+    let mut recorder = StageRecorder::new(no_clock);
+    let bundle = claim_bundle(identifier, secret, leaf_index, epoch, &merkle_root, &merkle_path, &recipient, hash, None, &mut recorder)
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(bundle.into())
 }
 
 #[wasm_bindgen]
@@ -522,6 +681,7 @@ pub fn get_sdk_version() -> String {
 // STARK Proof Generation with Real Merkle Trees
 // ============================================================================
 
+#[allow(clippy::too_many_arguments)]
 fn generate_stark_proof(
     id_hash: u32,
     secret: u32,
@@ -529,41 +689,48 @@ fn generate_stark_proof(
     commitment: &[u8; 32],
     nullifier: &[u8; 32],
     merkle_root: &[u8; 32],
+    merkle_path: &[[u8; 32]],
     recipient: &[u8; 32],
-) -> Vec<u8> {
-    let mut proof = Vec::with_capacity(20000);
-
-    let id_m31 = id_hash % M31_PRIME;
-    let secret_m31 = secret % M31_PRIME;
-    let commitment_m31 = compute_m31_commitment(id_m31, secret_m31);
-    let nullifier_m31 = compute_m31_nullifier(secret_m31, leaf_index);
-
-    // The trace OODS value
-    let trace_oods = QM31::new(
-        M31::new(commitment_m31),
-        M31::new(nullifier_m31),
-        M31::new(id_m31),
-        M31::new(secret_m31),
-    );
+    hash: HashKind,
+    pow: Option<Grinding<'_>>,
+    recorder: &mut StageRecorder<'_>,
+) -> Result<(Vec<u8>, Option<u64>), ProofError> {
+    // The hash AIR's trace: the commitment and nullifier permutations, a
+    // permutation per level of the commitment's path to the root, then
+    // idle rows. Its fixed columns are left out; the verifier evaluates
+    // them itself.
+    let air = MurklHashAir::new(commitment, nullifier)
+        .with_membership(merkle_root, TREE_DEPTH)
+        .with_log_rows(LOG_TRACE_SIZE as u32);
+    let (id_hash, secret) = (murkl_prover::M31::new(id_hash), murkl_prover::M31::new(secret));
+    let trace = air.generate_trace(id_hash, secret, leaf_index, merkle_path)?;
+    // A wrong password or a stale path fails here, per row, rather than as
+    // a proof the verifier rejects
+    murkl_prover::air::verify_constraints(&air, &trace).map_err(|failures| {
+        let (row, name) = &failures[0];
+        ProofError::constraint_violation(format!("{} constraint violation(s), first: {name} at row {row}", failures.len()))
+    })?;
+    let witness_columns: Vec<Vec<murkl_prover::M31>> =
+        trace.columns[..TRACE_COLUMNS].iter().map(|column| column.values.clone()).collect();
 
     // ========================================
     // Build REAL Merkle Trees
     // ========================================
     
-    // Generate trace evaluations (deterministic from witness)
-    let mut trace_leaves = Vec::with_capacity(EVAL_DOMAIN_SIZE);
-    for i in 0..EVAL_DOMAIN_SIZE {
-        // Each leaf is a deterministic value based on position and witness
-        let leaf_data = keccak_multi(&[
-            b"trace_eval_v1",
-            &(i as u32).to_le_bytes(),
-            &id_m31.to_le_bytes(),
-            &secret_m31.to_le_bytes(),
-        ]);
-        trace_leaves.push(leaf_data);
-    }
-    let trace_tree = MerkleTree::new(trace_leaves);
+    // Trace evaluations over the commitment coset (never the trace domain);
+    // a row's leaf hashes all of its column values together.
+    let extended_columns = extend_witness_columns(&witness_columns);
+    recorder.finish(ProveStage::Extend, 0);
+    let trace_rows: Vec<Vec<u8>> = (0..EVAL_DOMAIN_SIZE)
+        .map(|i| extended_columns.iter().flat_map(|column| column[i].to_le_bytes()).collect())
+        .collect();
+    let trace_leaves: Vec<[u8; 32]> = trace_rows
+        .iter()
+        .map(|row| hash.hash(&[TRACE_ROW_DOMAIN, row]))
+        .collect();
+    let trace_tree = MerkleTree::new(trace_leaves, hash);
     let trace_commitment = trace_tree.root();
+    recorder.finish(ProveStage::CommitTrace, (EVAL_DOMAIN_SIZE + trace_tree.nodes.len()) as u64);
     
     // Generate composition evaluations
     // First 16 bytes = QM31 value (used for FRI), rest = padding for Merkle uniqueness
@@ -581,21 +748,15 @@ fn generate_stark_proof(
         leaf_data[16..32].copy_from_slice(&position_hash[..16]);
         comp_leaves.push(leaf_data);
     }
-    let comp_tree = MerkleTree::new(comp_leaves);
+    let comp_tree = MerkleTree::new(comp_leaves, hash);
     let composition_commitment = comp_tree.root();
+    recorder.finish(ProveStage::Composition, (EVAL_DOMAIN_SIZE + comp_tree.nodes.len()) as u64);
 
-    // 1. Write commitments
-    proof.extend_from_slice(&trace_commitment);
-    proof.extend_from_slice(&composition_commitment);
-
-    // 2. Trace OODS (16 bytes)
-    proof.extend_from_slice(&trace_oods.a.0.to_le_bytes());
-    proof.extend_from_slice(&trace_oods.b.0.to_le_bytes());
-    proof.extend_from_slice(&trace_oods.c.0.to_le_bytes());
-    proof.extend_from_slice(&trace_oods.d.0.to_le_bytes());
+    // 1. The parameter header and both commitments are set when the
+    // SerializedProof is assembled below
 
-    // 3. Run Fiat-Shamir to get alpha, oods_point
-    let mut channel = Channel::new();
+    // 2. Run Fiat-Shamir to get alpha, oods_point
+    let mut channel = Channel::new(hash);
     channel.mix_digest(commitment);
     channel.mix_digest(nullifier);
     channel.mix_digest(merkle_root);
@@ -604,129 +765,149 @@ fn generate_stark_proof(
     channel.mix_digest(&trace_commitment);
     let alpha = channel.squeeze_qm31();
     channel.mix_digest(&composition_commitment);
-    let oods_point = channel.squeeze_qm31();
-    
-    // 4. Composition OODS
-    let composition_oods = evaluate_murkl_constraint(
-        &trace_oods, commitment, nullifier, merkle_root, &alpha, &oods_point,
-    );
-    proof.extend_from_slice(&composition_oods.a.0.to_le_bytes());
-    proof.extend_from_slice(&composition_oods.b.0.to_le_bytes());
-    proof.extend_from_slice(&composition_oods.c.0.to_le_bytes());
-    proof.extend_from_slice(&composition_oods.d.0.to_le_bytes());
+    let oods_point = channel.squeeze_circle_point();
+
+    // 3. Trace OODS: evaluate the committed columns at the OODS point and
+    // one row on
+    let (trace_oods, trace_oods_next) =
+        murkl_prover::onchain::evaluate_trace_at_oods(&witness_columns, LOG_TRACE_SIZE as u32, &oods_point);
+
+    // 4. Composition OODS: the hash AIR's constraints folded with α, as
+    // the verifier recomputes them
+    let composition_oods = murkl_prover::onchain::hash_air_composition(
+        &trace_oods,
+        &trace_oods_next,
+        commitment,
+        nullifier,
+        merkle_root,
+        murkl_prover::QM31::from_u32(alpha.a.0, alpha.b.0, alpha.c.0, alpha.d.0),
+        &oods_point,
+        LOG_TRACE_SIZE as u32,
+    )
+    .map_err(|e| ProofError::InvalidWitness(e.to_string()))?;
+    let trace_oods: Vec<QM31> = trace_oods.into_iter().map(from_prover_qm31).collect();
+    let trace_oods_next: Vec<QM31> = trace_oods_next.into_iter().map(from_prover_qm31).collect();
+    let composition_oods = from_prover_qm31(composition_oods);
 
     // 5. Mix OODS into channel
-    channel.mix_qm31(&trace_oods);
+    for value in trace_oods.iter().chain(&trace_oods_next) {
+        channel.mix_qm31(value);
+    }
     channel.mix_qm31(&composition_oods);
+    recorder.finish(ProveStage::Oods, channel.hashes);
+    let mut channel_hashes = channel.hashes;
 
-    // 6. FRI layer commitments
-    // KEY INSIGHT: FRI verifies that composition polynomial is low-degree
-    // The verifier folds: f_folded = s0 + α*s1 + α²*s2 + α³*s3
-    // where α comes from Fiat-Shamir AFTER the tree commitment
-    // 
-    // For a valid proof, we need siblings such that alpha-folding produces
-    // consistent values through all layers, ending at final_poly evaluation.
-    //
-    // Elegant solution: use ALL ZEROS for composition and FRI values
-    // - Folding zeros with any α gives zeros: 0 + α*0 + α²*0 + α³*0 = 0
-    // - Final poly = constant 0 satisfies all queries
-    // - This is cryptographically valid (proves a degree-0 polynomial = 0)
-    //
-    // Note: Constraint verification is SEPARATE - it uses composition_oods,
-    // not the composition tree values. So using zeros here doesn't break constraints.
-    
-    proof.push(N_FRI_LAYERS as u8);
-    
+    // 6. FRI layers: layer 0 is the composition column, and each next layer
+    // folds groups of four with that layer's α exactly as the verifier
+    // checks it (see fri_fold). The composition values are all zero, so
+    // every layer is too; the folds still run so the siblings and the
+    // final layer are whatever the verifier will recompute.
+    let mut fri_layer_commitments = Vec::with_capacity(N_FRI_LAYERS);
     let mut fri_trees: Vec<FriMerkleTree> = Vec::with_capacity(N_FRI_LAYERS);
-    let mut current_domain = EVAL_DOMAIN_SIZE;
-    
-    for _layer in 0..N_FRI_LAYERS {
-        let tree_size = current_domain / 4;
-        
-        // All-zero QM31 values
-        let qm31_zero: [u8; 16] = [0u8; 16];
-        let qm31_values: Vec<[u8; 16]> = vec![qm31_zero; tree_size];
-        
-        let fri_tree = FriMerkleTree::new(qm31_values);
+    let mut fri_layers: Vec<Vec<QM31>> = Vec::with_capacity(N_FRI_LAYERS);
+    let mut layer: Vec<QM31> = (0..EVAL_DOMAIN_SIZE).map(|i| qm31_from_leaf(&comp_tree.get_leaf(i))).collect();
+
+    for layer_idx in 0..N_FRI_LAYERS {
+        // One leaf per group, holding all four of its values
+        let leaves = layer
+            .chunks_exact(4)
+            .map(|group| {
+                let mut bytes = [0u8; 64];
+                for (out, v) in bytes.chunks_exact_mut(16).zip(group) {
+                    out.copy_from_slice(&v.to_bytes()[..16]);
+                }
+                bytes
+            })
+            .collect();
+        let fri_tree = FriMerkleTree::new(leaves, hash);
         let fri_commitment = fri_tree.root();
-        
-        proof.extend_from_slice(&fri_commitment);
+
+        fri_layer_commitments.push(fri_commitment);
         channel.mix_digest(&fri_commitment);
-        let _fri_alpha = channel.squeeze_qm31(); // Still need to advance channel state
-        
+        let alpha = channel.squeeze_qm31();
+
+        let folded = layer
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(group, siblings)| fri_fold(siblings.try_into().unwrap(), &alpha, fri_layer_point(4 * group, layer_idx)))
+            .collect();
         fri_trees.push(fri_tree);
-        current_domain /= 4;
+        fri_layers.push(core::mem::replace(&mut layer, folded));
     }
-    
-    // 7. Final polynomial = constant 0 (trivially satisfies all evaluations)
-    proof.extend_from_slice(&1u16.to_le_bytes()); // 1 coefficient
-    proof.extend_from_slice(&0u32.to_le_bytes()); // a = 0
-    proof.extend_from_slice(&0u32.to_le_bytes()); // b = 0
-    proof.extend_from_slice(&0u32.to_le_bytes()); // c = 0
-    proof.extend_from_slice(&0u32.to_le_bytes()); // d = 0
 
-    // 8. Query count
-    proof.push(N_QUERIES as u8);
+    let fri_tree_hashes: usize = fri_trees.iter().map(|tree| tree.groups.len() + tree.nodes.len()).sum();
+    recorder.finish(ProveStage::Fri, fri_tree_hashes as u64 + channel.hashes - channel_hashes);
+
+    // 7. Final polynomial: the last layer is constant (zero), so a single
+    // coefficient covers every query
+    debug_assert!(layer.iter().all(|v| v.to_bytes() == layer[0].to_bytes()));
+    let qm31 = |v: &QM31| [v.a.0, v.b.0, v.c.0, v.d.0];
+    let final_poly = vec![qm31(&layer[0])];
+
+    // 8. Proof of work: the nonce is mixed in before any query index is
+    // squeezed, so every draw of the queries costs 2^pow_bits hashes
+    let pow_nonce = pow.filter(|pow| pow.pow_bits > 0).map(|pow| {
+        let mut searched = false;
+        let mut on_checkpoint = pow.on_checkpoint;
+        let mut save = |checkpoint: &Checkpoint| {
+            searched = true;
+            if let Some(on_checkpoint) = on_checkpoint.as_mut() {
+                on_checkpoint(checkpoint);
+            }
+        };
+        let nonce = grind_resumable(&channel.state, pow.pow_bits, 1, pow.on_progress, pow.resume, &mut save);
+        channel.mix_nonce(nonce);
+        recorder.finish(ProveStage::Grind, if searched { nonce + 2 } else { 0 });
+        nonce
+    });
+    channel_hashes = channel.hashes;
 
     // 9. Generate query proofs with REAL Merkle paths
+    let mut queries = Vec::with_capacity(N_QUERIES);
     for _q in 0..N_QUERIES {
-        let query_idx_m31 = channel.squeeze_m31();
-        let idx = (query_idx_m31.0 as usize) % EVAL_DOMAIN_SIZE;
-        
-        // Query index (4 bytes)
-        proof.extend_from_slice(&(idx as u32).to_le_bytes());
-        
-        // Trace value (32 bytes) - actual leaf from tree
-        let trace_leaf = trace_tree.get_leaf(idx);
-        proof.extend_from_slice(&trace_leaf);
-        
-        // Trace path (length + siblings)
-        let trace_path = trace_tree.get_path(idx);
-        proof.push(trace_path.len() as u8);
-        for sibling in &trace_path {
-            proof.extend_from_slice(sibling);
-        }
-        
-        // Composition value (32 bytes)
-        let comp_leaf = comp_tree.get_leaf(idx);
-        proof.extend_from_slice(&comp_leaf);
-        
-        // Composition path
-        let comp_path = comp_tree.get_path(idx);
-        proof.push(comp_path.len() as u8);
-        for sibling in &comp_path {
-            proof.extend_from_slice(sibling);
-        }
-        
-        // FRI layer values and paths
-        // All FRI tree values are ZERO, so:
-        // - folded = 0 + α*0 + α²*0 + α³*0 = 0 for any α
-        // - final_poly(x) = 0 matches
-        // This is cryptographically valid (low-degree polynomial = 0)
-        
+        let idx = channel.squeeze_bits(LOG_DOMAIN_SIZE);
+
+        // Each layer opens the query's whole group, which is one leaf of
+        // the layer's tree
         let mut fri_idx = idx;
-        for (_layer_idx, fri_tree) in fri_trees.iter().enumerate() {
-            let tree_size = fri_tree.qm31_values.len();
-            let tree_pos = fri_idx / 4;
-            
-            // All 4 siblings are zero (64 bytes)
-            for _ in 0..4 {
-                proof.extend_from_slice(&[0u8; 16]);
-            }
-            
-            // FRI path - proves tree[tree_pos] == 0
-            let tree_idx = tree_pos % tree_size;
-            let fri_path = fri_tree.get_path(tree_idx);
-            proof.push(fri_path.len() as u8);
-            for path_sibling in &fri_path {
-                proof.extend_from_slice(path_sibling);
-            }
-            
+        let mut fri = Vec::with_capacity(N_FRI_LAYERS);
+        for (fri_tree, values) in fri_trees.iter().zip(&fri_layers) {
+            let group = fri_idx & !3;
+            let siblings = core::array::from_fn(|k| qm31(&values[group + k]));
+            fri.push(FriOpening { siblings, path: fri_tree.get_path(fri_idx / 4) });
+
             fri_idx /= 4;
         }
+
+        // The verifier rehashes the trace row into the leaf
+        queries.push(QueryOpening {
+            index: idx as u32,
+            trace_row: extended_columns.iter().map(|column| column[idx].value()).collect(),
+            trace_path: trace_tree.get_path(idx),
+            composition_leaf: comp_tree.get_leaf(idx),
+            composition_path: comp_tree.get_path(idx),
+            fri,
+        });
     }
+    recorder.finish(ProveStage::Queries, channel.hashes - channel_hashes);
 
-    proof
+    let proof = SerializedProof {
+        hash_kind: hash as u8,
+        log_trace_size: LOG_TRACE_SIZE as u8,
+        log_blowup: LOG_BLOWUP as u8,
+        log_folding_factor: LOG_FOLDING_FACTOR as u8,
+        trace_commitment,
+        composition_commitment,
+        trace_columns: TRACE_COLUMNS as u8,
+        trace_oods: trace_oods.iter().map(qm31).collect(),
+        trace_oods_next: trace_oods_next.iter().map(qm31).collect(),
+        composition_oods: qm31(&composition_oods),
+        fri_layer_commitments,
+        final_layer: FinalLayer::Coefficients(final_poly),
+        queries,
+    }
+    .encode();
+    Ok((proof, pow_nonce))
 }
 
 // ============================================================================
@@ -741,22 +922,566 @@ fn hash_identifier(id: &str) -> u32 {
     murkl_prover::hash_identifier(id).value()
 }
 
-fn compute_m31_commitment(id: u32, secret: u32) -> u32 {
+fn pq_commitment(id_hash: u32, secret: u32) -> [u8; 32] {
     use murkl_prover::M31;
-    murkl_prover::m31_commitment(M31::new(id), M31::new(secret)).value()
+    murkl_prover::pq_commitment(M31::new(id_hash), M31::new(secret))
 }
 
-fn compute_m31_nullifier(secret: u32, leaf_index: u32) -> u32 {
+fn pq_nullifier(secret: u32, leaf_index: u32) -> [u8; 32] {
     use murkl_prover::M31;
-    murkl_prover::m31_nullifier(M31::new(secret), leaf_index).value()
+    murkl_prover::pq_nullifier(M31::new(secret), leaf_index)
 }
 
-fn pq_commitment(id_hash: u32, secret: u32) -> [u8; 32] {
+fn pq_epoch_nullifier(secret: u32, leaf_index: u32, epoch: u32) -> [u8; 32] {
     use murkl_prover::M31;
-    murkl_prover::pq_commitment(M31::new(id_hash), M31::new(secret))
+    murkl_prover::pq_epoch_nullifier(M31::new(secret), leaf_index, epoch)
 }
 
-fn pq_nullifier(secret: u32, leaf_index: u32) -> [u8; 32] {
-    use murkl_prover::M31;
-    murkl_prover::pq_nullifier(M31::new(secret), leaf_index)
+// ============================================================================
+// Differential tests against the on-chain verifier
+// ============================================================================
+
+/// Host-side runs of prover output through `stark_verifier::verify_stark_proof`.
+///
+/// This is the only verifier that consumes the on-chain wire format: the
+/// murkl program has no verifier of its own (it CPIs into stark-verifier and
+/// reads the finalized buffer), and `murkl_prover::Verifier` checks the
+/// prover crate's `Proof` struct, which has a different layout and
+/// transcript. Every mutation below must be rejected; every honest proof
+/// must be accepted.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use murkl_prover::testing::{Mutant, ProofMutator};
+    use murkl_prover::StageMetrics;
+
+    struct Statement {
+        commitment: [u8; 32],
+        nullifier: [u8; 32],
+        merkle_root: [u8; 32],
+        recipient: [u8; 32],
+        proof: Vec<u8>,
+        stages: Vec<StageMetrics>,
+    }
+
+    fn statement(identifier: &str, password: &str, leaf_index: u32, seed: u8) -> Statement {
+        hashed_statement(identifier, password, leaf_index, None, seed, HashKind::Keccak)
+    }
+
+    fn epoch_statement(identifier: &str, password: &str, leaf_index: u32, epoch: Option<u32>, seed: u8) -> Statement {
+        hashed_statement(identifier, password, leaf_index, epoch, seed, HashKind::Keccak)
+    }
+
+    fn hashed_statement(
+        identifier: &str,
+        password: &str,
+        leaf_index: u32,
+        epoch: Option<u32>,
+        seed: u8,
+        hash: HashKind,
+    ) -> Statement {
+        let id_hash = hash_identifier(identifier);
+        let secret = hash_password(password);
+        let commitment = pq_commitment(id_hash, secret);
+        let nullifier = match epoch {
+            Some(epoch) => pq_epoch_nullifier(secret, leaf_index, epoch),
+            None => pq_nullifier(secret, leaf_index),
+        };
+        let (merkle_root, merkle_path) = pool_path(&commitment, leaf_index, seed);
+        let recipient = keccak_multi(&[b"recipient", &[seed]]);
+        let mut recorder = StageRecorder::new(no_clock);
+        let (proof, _) = generate_stark_proof(
+            id_hash, secret, leaf_index, &commitment, &nullifier, &merkle_root, &merkle_path, &recipient, hash, None,
+            &mut recorder,
+        )
+        .unwrap();
+        let stages = recorder.stages().to_vec();
+        Statement { commitment, nullifier, merkle_root, recipient, proof, stages }
+    }
+
+    /// Root of a pool tree holding `commitment` at `leaf_index` after
+    /// filler deposits, and the commitment's path up to it
+    fn pool_path(commitment: &[u8; 32], leaf_index: u32, seed: u8) -> ([u8; 32], Vec<[u8; 32]>) {
+        let mut leaves: Vec<[u8; 32]> =
+            (0..leaf_index).map(|i| keccak_multi(&[b"leaf", &[seed], &i.to_le_bytes()])).collect();
+        leaves.push(*commitment);
+        let witness = murkl_prover::MerkleWitness::from_leaves(&leaves, TREE_DEPTH, leaf_index as usize).unwrap();
+        (witness.root, witness.siblings)
+    }
+
+    fn corpus() -> Vec<Statement> {
+        vec![
+            statement("@alice", "correct horse", 0, 1),
+            statement("bob@example.com", "battery staple", 7, 2),
+            statement("@carol", "", 1023, 3),
+            hashed_statement("@erin", "field friendly", 5, None, 4, HashKind::Poseidon2),
+        ]
+    }
+
+    fn accepts(s: &Statement, proof: &[u8]) -> bool {
+        stark_verifier::verify_stark_proof(proof, &s.commitment, &s.nullifier, &s.merkle_root, &s.recipient)
+            .is_ok()
+    }
+
+    #[test]
+    fn test_spec_matches_emitter_and_verifier() {
+        use murkl_prover::spec;
+
+        assert_eq!(spec::MAX_TRACE_COLUMNS, stark_verifier::MAX_TRACE_COLUMNS);
+        assert_eq!(spec::MAX_FRI_LAYERS, stark_verifier::MAX_FRI_LAYERS);
+        assert_eq!(spec::MAX_MERKLE_DEPTH, stark_verifier::MAX_MERKLE_DEPTH);
+        assert_eq!(spec::MAX_FINAL_POLY_LEN, stark_verifier::MAX_FINAL_POLY_LEN);
+        assert_eq!(spec::MAX_FINAL_LAYER_EVALS, stark_verifier::MAX_FINAL_LAYER_EVALS);
+        assert_eq!(spec::MAX_QUERIES, stark_verifier::MAX_QUERIES);
+        assert_eq!(spec::MAX_PROOF_SIZE, stark_verifier::MAX_PROOF_SIZE);
+        assert_eq!(spec::FINAL_EVALUATIONS_FLAG, stark_verifier::FINAL_EVALUATIONS_FLAG);
+        assert_eq!(spec::PROOF_MAGIC, stark_verifier::PROOF_MAGIC);
+        assert_eq!(spec::PROOF_VERSION, stark_verifier::PROOF_VERSION);
+
+        for s in corpus() {
+            let vector = spec::annotate(&s.proof).unwrap();
+            assert_eq!(spec::parse_annotated(&vector).unwrap(), s.proof);
+            assert_eq!(s.proof[spec::TRACE_COLUMNS.start] as usize, TRACE_COLUMNS);
+            assert_eq!(s.proof[spec::LOG_TRACE_SIZE.start] as usize + s.proof[spec::LOG_BLOWUP.start] as usize, LOG_DOMAIN_SIZE);
+            assert_eq!(s.proof[spec::QUERY_COUNT.start] as usize, N_QUERIES);
+            assert_eq!(s.proof[spec::fri_layer_count(TRACE_COLUMNS).start] as usize, N_FRI_LAYERS);
+        }
+    }
+
+    /// The prover's channel and stark-verifier's, driven through the absorb
+    /// sequence of a proof, squeeze the same alpha, OODS point, FRI alphas
+    /// and query indices. The CLI prover and murkl-prover's `Transcript`
+    /// don't emit proofs for this verifier, so they have no channel to match.
+    #[test]
+    fn test_channel_matches_verifier() {
+        let qm31 = |q: QM31| q.to_bytes()[..16].to_vec();
+        for hash in [HashKind::Keccak, HashKind::Poseidon2] {
+            let mut prover = Channel::new(hash);
+            let kind = stark_verifier::HashKind::from_byte(hash as u8).unwrap();
+            let mut verifier = stark_verifier::Channel::new(kind);
+
+            // Public inputs, then the trace and composition roots
+            let digests: Vec<[u8; 32]> = (0..6u8).map(|i| keccak_multi(&[b"digest", &[i]])).collect();
+            for digest in &digests[..5] {
+                prover.mix_digest(digest);
+                verifier.mix_digest(digest);
+            }
+            let alpha = prover.squeeze_qm31();
+            assert_eq!(qm31(alpha), verifier.squeeze_qm31().to_le_bytes(), "{:?} alpha", hash);
+            prover.mix_digest(&digests[5]);
+            verifier.mix_digest(&digests[5]);
+            let oods_point = prover.squeeze_circle_point();
+            let verifier_point = verifier.squeeze_circle_point();
+            assert_eq!(oods_point.x.to_bytes(), verifier_point.x.to_le_bytes(), "{:?} OODS point", hash);
+            assert_eq!(oods_point.y.to_bytes(), verifier_point.y.to_le_bytes(), "{:?} OODS point", hash);
+
+            let oods = QM31::new(M31::new(1), M31::new(M31_PRIME - 1), M31::new(1 << 30), M31::new(7));
+            let verifier_oods = stark_verifier::QM31::new(
+                stark_verifier::M31::new(1),
+                stark_verifier::M31::new(M31_PRIME - 1),
+                stark_verifier::M31::new(1 << 30),
+                stark_verifier::M31::new(7),
+            );
+            prover.mix_qm31(&oods);
+            verifier.mix_qm31(&verifier_oods);
+
+            for layer in 0..N_FRI_LAYERS as u8 {
+                let root = keccak_multi(&[b"fri", &[layer]]);
+                prover.mix_digest(&root);
+                verifier.mix_digest(&root);
+                let fri_alpha = prover.squeeze_qm31();
+                assert_eq!(qm31(fri_alpha), verifier.squeeze_qm31().to_le_bytes(), "{:?} FRI alpha {}", hash, layer);
+            }
+
+            for q in 0..N_QUERIES {
+                let index = prover.squeeze_bits(LOG_DOMAIN_SIZE);
+                assert_eq!(index as u32, verifier.squeeze_bits(LOG_DOMAIN_SIZE as u32), "{:?} query {}", hash, q);
+            }
+        }
+    }
+
+    /// The prover's composition is zero, so honest proofs never exercise
+    /// the fold on real values; check it against the verifier's directly.
+    #[test]
+    fn test_fri_fold_matches_verifier() {
+        let value = |seed: u32| QM31::new(M31::new(seed), M31::new(seed * 3 + 1), M31::new(M31_PRIME - seed), M31::new(seed << 20));
+        let verifier_value = |v: QM31| {
+            let [a, b, c, d] = [v.a, v.b, v.c, v.d].map(|m| stark_verifier::M31::new(m.0));
+            stark_verifier::QM31::new(a, b, c, d)
+        };
+        let alpha = value(99);
+        for layer in 0..N_FRI_LAYERS {
+            for position in [0, 4, 36, (EVAL_DOMAIN_SIZE >> (2 * layer)) - 4] {
+                let siblings: [QM31; 4] = core::array::from_fn(|k| value(17 * (position + k) as u32 + layer as u32));
+                let point = stark_verifier::fri_layer_point(position, layer, LOG_DOMAIN_SIZE as u32, LOG_FOLDING_FACTOR as u32);
+                assert_eq!(point.x.0, fri_layer_point(position, layer).x.value());
+                let expected = stark_verifier::verify_fri_fold(&siblings.map(verifier_value), &verifier_value(alpha), point);
+                let folded = fri_fold(&siblings, &alpha, fri_layer_point(position, layer));
+                assert_eq!(folded.to_bytes()[..16], expected.to_le_bytes(), "layer {} position {}", layer, position);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_air_matches_verifier() {
+        let verifier_value = |v: &murkl_prover::QM31| {
+            let [a, b, c, d] = [v.a, v.b, v.c, v.d].map(|m| stark_verifier::M31::new(m.value()));
+            stark_verifier::QM31::new(a, b, c, d)
+        };
+        let verifier_values = |values: &[murkl_prover::QM31]| values.iter().map(verifier_value).collect::<Vec<_>>();
+
+        for (epoch, seed) in [(None, 1u8), (Some(3), 2)] {
+            let (id_hash, secret, leaf_index) = (hash_identifier("@alice"), hash_password("correct horse"), 6);
+            let commitment = pq_commitment(id_hash, secret);
+            let nullifier = match epoch {
+                Some(epoch) => pq_epoch_nullifier(secret, leaf_index, epoch),
+                None => pq_nullifier(secret, leaf_index),
+            };
+            let (merkle_root, merkle_path) = pool_path(&commitment, leaf_index, seed);
+            let trace = MurklHashAir::new(&commitment, &nullifier)
+                .with_membership(&merkle_root, TREE_DEPTH)
+                .with_log_rows(LOG_TRACE_SIZE as u32)
+                .generate_trace(murkl_prover::M31::new(id_hash), murkl_prover::M31::new(secret), leaf_index, &merkle_path)
+                .unwrap();
+            let columns: Vec<_> = trace.columns[..TRACE_COLUMNS].iter().map(|c| c.values.clone()).collect();
+
+            let mut channel = Channel::new(HashKind::Keccak);
+            channel.mix_digest(&keccak_multi(&[b"hash air", &[seed]]));
+            let alpha = channel.squeeze_qm31();
+            let alpha = murkl_prover::QM31::from_u32(alpha.a.0, alpha.b.0, alpha.c.0, alpha.d.0);
+            let point = channel.squeeze_circle_point();
+            let verifier_point = stark_verifier::SecureCirclePoint { x: verifier_value(&point.x), y: verifier_value(&point.y) };
+
+            let honest = murkl_prover::onchain::evaluate_trace_at_oods(&columns, LOG_TRACE_SIZE as u32, &point);
+            let arbitrary: Vec<_> = (0..TRACE_COLUMNS as u32)
+                .map(|c| murkl_prover::QM31::from_u32(c * 31 + 5, c << 17, M31_PRIME - c, c * c))
+                .collect();
+            let statement = stark_verifier::HashStatement::new(&commitment, &nullifier, &merkle_root).unwrap();
+            for (current, next) in [honest, (arbitrary.clone(), arbitrary)] {
+                let composition = murkl_prover::onchain::hash_air_composition(
+                    &current,
+                    &next,
+                    &commitment,
+                    &nullifier,
+                    &merkle_root,
+                    alpha,
+                    &point,
+                    LOG_TRACE_SIZE as u32,
+                )
+                .unwrap();
+                let constraints = statement
+                    .evaluate(&verifier_values(&current), &verifier_values(&next), &verifier_point, LOG_TRACE_SIZE as u32)
+                    .unwrap();
+                assert_eq!(constraints.len(), stark_verifier::HASH_CONSTRAINTS);
+                let expected = stark_verifier::constraint_composition(
+                    &constraints,
+                    &verifier_value(&alpha),
+                    &verifier_point,
+                    LOG_TRACE_SIZE as u32,
+                );
+                assert_eq!(composition.to_bytes(), expected.to_le_bytes(), "{:?}", epoch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_honest_proofs_accepted() {
+        for s in corpus() {
+            assert!(accepts(&s, &s.proof));
+        }
+    }
+
+    /// Mutants of an honest proof: a bit flipped at each end of every
+    /// header field and of the first query's, two queries swapped, and
+    /// cuts inside the queries
+    fn mutants(proof: &[u8]) -> Vec<Mutant> {
+        let mut mutator = ProofMutator::new(proof).unwrap();
+        let fields: Vec<(String, usize)> = mutator
+            .fields()
+            .iter()
+            .filter(|f| f.len > 0 && (!f.name.starts_with("queries[") || f.name.starts_with("queries[0].")))
+            .map(|f| (f.name.clone(), f.len))
+            .collect();
+        for (name, len) in fields {
+            mutator = mutator.flip_bit(&name, 0).flip_bit(&name, (len - 1) * 8);
+        }
+        let last = N_QUERIES - 1;
+        mutator
+            .swap_queries(0, last)
+            .truncate(&format!("queries[{}]", last))
+            .truncate(&format!("queries[{}].fri[{}].path", last, N_FRI_LAYERS - 1))
+            .mutants()
+    }
+
+    #[test]
+    fn test_corrupted_proofs_rejected() {
+        for hash in [HashKind::Keccak, HashKind::Poseidon2] {
+            let s = hashed_statement("@alice", "correct horse", 0, None, 1, hash);
+            for mutant in mutants(&s.proof) {
+                assert!(!accepts(&s, &mutant.bytes), "{hash:?}: {} accepted", mutant.label);
+            }
+        }
+    }
+
+    /// Custom error code the program rejects `proof` with, `None` if it
+    /// accepts
+    fn program_verdict(s: &Statement, proof: &[u8]) -> Option<u32> {
+        match stark_verifier::verify_stark_proof(proof, &s.commitment, &s.nullifier, &s.merkle_root, &s.recipient) {
+            Ok(()) => None,
+            Err(anchor_lang::error::Error::AnchorError(e)) => Some(e.error_code_number),
+            Err(e) => panic!("not a verifier error: {e}"),
+        }
+    }
+
+    fn offchain_verdict(s: &Statement, proof: &[u8]) -> Option<u32> {
+        murkl_prover::Verifier::verify_bytes(proof, &s.commitment, &s.nullifier, &s.merkle_root, &s.recipient)
+            .err()
+            .map(|e| e.code())
+    }
+
+    #[test]
+    fn test_offchain_verifier_matches_program() {
+        for s in corpus() {
+            assert_eq!(offchain_verdict(&s, &s.proof), None);
+            let mut other = Statement { proof: Vec::new(), stages: Vec::new(), ..s };
+            other.recipient[0] ^= 1;
+            assert_eq!(offchain_verdict(&other, &s.proof), program_verdict(&other, &s.proof));
+
+            let mut trailing = s.proof.clone();
+            trailing.push(0);
+            assert_eq!(offchain_verdict(&s, &trailing), program_verdict(&s, &trailing));
+
+            for mutant in mutants(&s.proof) {
+                assert_eq!(
+                    offchain_verdict(&s, &mutant.bytes),
+                    program_verdict(&s, &mutant.bytes),
+                    "{}",
+                    mutant.label
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_paths_match_tree_depth() {
+        let s = statement("@alice", "correct horse", 0, 1);
+        let honest = SerializedProof::decode(&s.proof).unwrap();
+        // Each tree's depth, and its path in the first query
+        type PathOf = fn(&mut SerializedProof) -> &mut Vec<[u8; 32]>;
+        let paths: [(&str, usize, PathOf); 3] = [
+            ("trace", LOG_DOMAIN_SIZE, |p| &mut p.queries[0].trace_path),
+            ("composition", LOG_DOMAIN_SIZE, |p| &mut p.queries[0].composition_path),
+            ("last FRI layer", LOG_DOMAIN_SIZE - 2 * N_FRI_LAYERS, |p| &mut p.queries[0].fri[N_FRI_LAYERS - 1].path),
+        ];
+        for (name, depth, path) in paths {
+            assert_eq!(path(&mut honest.clone()).len(), depth, "{name}");
+            for grow in [false, true] {
+                let mut proof = honest.clone();
+                if grow {
+                    path(&mut proof).push([0; 32]);
+                } else {
+                    path(&mut proof).pop();
+                }
+                let bytes = proof.encode();
+                assert!(!accepts(&s, &bytes), "{name} path, grown: {grow}");
+                assert_eq!(offchain_verdict(&s, &bytes), program_verdict(&s, &bytes), "{name} path, grown: {grow}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_fri_siblings_committed() {
+        let s = statement("@alice", "correct horse", 0, 1);
+        let honest = SerializedProof::decode(&s.proof).unwrap();
+        let index = honest.queries[0].index as usize;
+        let rejected = Some(murkl_prover::onchain::OnChainError::FriFoldingFailed.code());
+        // The siblings beside the query's own slot only feed the fold; in the
+        // last layer the fold goes to the final polynomial, so without the
+        // group in the leaf they'd fail as FinalPolyMismatch, if at all
+        for layer in 0..N_FRI_LAYERS {
+            let slot = (index >> (2 * layer)) % 4;
+            for sibling in (0..4).filter(|&k| k != slot) {
+                let mut proof = honest.clone();
+                proof.queries[0].fri[layer].siblings[sibling][0] ^= 1;
+                let bytes = proof.encode();
+                assert_eq!(program_verdict(&s, &bytes), rejected, "layer {layer}, sibling {sibling}");
+                assert_eq!(offchain_verdict(&s, &bytes), rejected, "layer {layer}, sibling {sibling}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_stage_metrics() {
+        let s = statement("@alice", "correct horse", 0, 1);
+        use ProveStage::*;
+        let stages: Vec<ProveStage> = s.stages.iter().map(|m| m.stage).collect();
+        assert_eq!(stages, [Extend, CommitTrace, Composition, Oods, Fri, Queries]);
+
+        let hashes = |stage| s.stages.iter().find(|m| m.stage == stage).unwrap().hashes;
+        // One hash per row, then a tree over the row hashes
+        assert_eq!(hashes(CommitTrace), (EVAL_DOMAIN_SIZE + 2 * EVAL_DOMAIN_SIZE - 1) as u64);
+        // Per layer: a hash per group, a tree over them, one mix and a
+        // four-limb squeeze
+        let fri: usize = (1..=N_FRI_LAYERS).map(|l| 3 * (EVAL_DOMAIN_SIZE >> (2 * l)) - 1 + 5).sum();
+        assert_eq!(hashes(Fri), fri as u64);
+        assert_eq!(hashes(Queries), N_QUERIES as u64);
+    }
+
+    #[test]
+    fn test_proofs_bound_to_statement() {
+        let corpus = corpus();
+        let (a, b) = (&corpus[0], &corpus[1]);
+        assert!(!accepts(b, &a.proof));
+
+        for field in 0..4 {
+            let mut swapped = Statement { proof: a.proof.clone(), stages: Vec::new(), ..*a };
+            match field {
+                0 => swapped.commitment = b.commitment,
+                1 => swapped.nullifier = b.nullifier,
+                2 => swapped.merkle_root = b.merkle_root,
+                _ => swapped.recipient = b.recipient,
+            }
+            assert!(!accepts(&swapped, &swapped.proof), "public input {field} not bound");
+        }
+    }
+
+    #[test]
+    fn test_wrong_sibling_rejected() {
+        let s = statement("@alice", "correct horse", 5, 1);
+        let (id_hash, secret) = (hash_identifier("@alice"), hash_password("correct horse"));
+        let (_, mut path) = pool_path(&s.commitment, 5, 1);
+        path[2][0] ^= 1;
+        let mut recorder = StageRecorder::new(no_clock);
+        let proof = generate_stark_proof(
+            id_hash, secret, 5, &s.commitment, &s.nullifier, &s.merkle_root, &path, &s.recipient, HashKind::Keccak,
+            None, &mut recorder,
+        );
+        assert!(accepts(&s, &s.proof));
+        match proof {
+            Err(ProofError::ConstraintViolation(msg)) => assert!(msg.contains("root_"), "{msg}"),
+            other => panic!("proved a path to another root: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_subscription_proofs_bound_to_epoch() {
+        let first = epoch_statement("@dave", "monthly", 4, Some(0), 9);
+        let second = epoch_statement("@dave", "monthly", 4, Some(1), 9);
+        assert!(accepts(&first, &first.proof));
+        assert!(accepts(&second, &second.proof));
+        assert_eq!(first.commitment, second.commitment);
+        assert_eq!(second.nullifier[28..], 1u32.to_le_bytes());
+
+        // An epoch's proof can't claim another epoch's nullifier
+        let replayed = Statement { proof: first.proof.clone(), ..second };
+        assert!(!accepts(&replayed, &replayed.proof));
+    }
+
+    #[test]
+    fn test_byte_api_matches_bundle() {
+        let recipient = [4u8; 32];
+        let commitment = commitment_bytes("@erin", "hunter2").try_into().unwrap();
+        let (root, path) = pool_path(&commitment, 2, 3);
+        let proof = prove_bytes("@erin", "hunter2", 2, &root, &path.concat(), &recipient, 0)
+            .unwrap_or_else(|_| panic!("prove_bytes failed"));
+        let mut recorder = StageRecorder::new(no_clock);
+        let bundle = prove_claim("@erin", "hunter2", 2, &root, &path, &recipient, HashKind::Keccak, &mut recorder).unwrap();
+        assert_eq!(proof.proof(), bundle.proof);
+        assert_eq!(proof.commitment(), commitment_bytes("@erin", "hunter2"));
+        assert_eq!(proof.nullifier(), nullifier_bytes("hunter2", 2));
+        assert_eq!(proof.leaf_index(), 2);
+        assert_eq!(epoch_nullifier_bytes("hunter2", 2, 7)[28..], 7u32.to_le_bytes());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_grinding_binds_nonce() {
+        let s = statement("@alice", "correct horse", 0, 1);
+        let (_, path) = pool_path(&s.commitment, 0, 1);
+        let bundle = |pow_bits: u32, reports: &mut u64| {
+            let mut on_progress = |_: GrindProgress| *reports += 1;
+            let pow = Grinding { pow_bits, on_progress: &mut on_progress, resume: None, on_checkpoint: None };
+            let secret = hash_password("correct horse");
+            let mut recorder = StageRecorder::new(no_clock);
+            claim_bundle("@alice", secret, 0, None, &s.merkle_root, &path, &s.recipient, HashKind::Keccak, Some(pow), &mut recorder)
+                .unwrap()
+        };
+        let mut reports = 0;
+
+        // Zero bits leaves the transcript alone
+        let plain = bundle(0, &mut reports);
+        assert_eq!((plain.proof.as_slice(), plain.pow_nonce), (s.proof.as_slice(), None));
+
+        let ground = bundle(8, &mut reports);
+        let nonce = ground.pow_nonce.unwrap();
+        assert_eq!(reports, nonce / murkl_prover::prover::GRIND_REPORT_INTERVAL);
+        assert_ne!(ground.proof, s.proof);
+        assert_eq!(&ground.proof[..74], &s.proof[..74]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_merkle_path_roots_leaf() {
+        let leaves = [[1u8; 32], [2; 32], [3; 32]];
+        let path = generate_merkle_path(&hex::encode(leaves.concat()), 1).unwrap();
+        let siblings = hex::decode(path).unwrap().chunks_exact(32).map(|s| s.try_into().unwrap()).collect();
+        let root = murkl_prover::merkle::pool_root(&leaves, TREE_DEPTH);
+        assert!(murkl_prover::MerkleWitness { leaf: leaves[1], leaf_index: 1, siblings, root }.verify());
+
+        assert_eq!(generate_merkle_path(&hex::encode(leaves.concat()), 3), None);
+        assert_eq!(generate_merkle_path("abcd", 0), None);
+    }
+
+    #[test]
+    fn test_grinding_resumes_from_checkpoint() {
+        let recipient = [4u8; 32];
+        let bundle = |password: &str, resume: Option<&Checkpoint>, saved: &mut Option<Checkpoint>| {
+            let (root, path) = pool_path(&commitment_bytes("@alice", password).try_into().unwrap(), 0, 3);
+            let mut on_progress = |_: GrindProgress| {};
+            let mut on_checkpoint = |c: &Checkpoint| *saved = Some(c.clone());
+            let pow = Grinding { pow_bits: 8, on_progress: &mut on_progress, resume, on_checkpoint: Some(&mut on_checkpoint) };
+            let mut recorder = StageRecorder::new(no_clock);
+            let secret = hash_password(password);
+            let bundle = claim_bundle("@alice", secret, 0, None, &root, &path, &recipient, HashKind::Keccak, Some(pow), &mut recorder)
+                .unwrap();
+            (bundle, recorder.stages().iter().find(|m| m.stage == ProveStage::Grind).unwrap().hashes)
+        };
+
+        let mut saved = None;
+        let (fresh, _) = bundle("correct horse", None, &mut saved);
+        let checkpoint = Checkpoint::from_bytes(&saved.take().unwrap().to_bytes()).unwrap();
+
+        let (resumed, hashes) = bundle("correct horse", Some(&checkpoint), &mut saved);
+        assert_eq!(resumed, fresh);
+        assert_eq!(hashes, 0);
+        assert!(saved.is_none());
+
+        // Another claim searches for its own nonce
+        let (_, hashes) = bundle("battery staple", Some(&checkpoint), &mut saved);
+        assert!(hashes > 0 && saved.is_some());
+    }
+
+    #[test]
+    fn test_key_stretch_chunks_and_cancels() {
+        let unstretched = KeyStretch::new("correct horse", 0);
+        assert!(unstretched.is_done());
+        assert_eq!(
+            generate_commitment_stretched("@alice", &unstretched),
+            Some(generate_commitment("@alice", "correct horse"))
+        );
+
+        let mut stretch = KeyStretch::new("correct horse", 10);
+        assert_eq!(stretch.step(4), 0.4);
+        assert_eq!(generate_commitment_stretched("@alice", &stretch), None);
+        assert_eq!(stretch.step(100), 1.0);
+        assert!(stretch.is_done());
+        assert_ne!(generate_commitment_stretched("@alice", &stretch), generate_commitment_stretched("@alice", &unstretched));
+
+        let mut cancelled = KeyStretch::new("correct horse", 10);
+        cancelled.step(4);
+        cancelled.cancel();
+        assert_eq!(cancelled.step(100), 0.4);
+        assert!(!cancelled.is_done());
+        assert_eq!(generate_commitment_stretched("@alice", &cancelled), None);
+    }
 }
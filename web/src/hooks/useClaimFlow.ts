@@ -6,7 +6,7 @@ import { useQueryClient } from '@tanstack/react-query';
 import toast from '../components/Toast';
 import { RELAYER_URL, POOL_ADDRESS } from '../lib/constants';
 import { poolKeys } from './usePoolInfo';
-import { generate_proof } from '../wasm/murkl_wasm';
+import { generate_merkle_path, generate_proof } from '../wasm/murkl_wasm';
 
 // WSOL mint address
 const WSOL_MINT = new PublicKey('So11111111111111111111111111111111111111112');
@@ -92,6 +92,13 @@ export function useClaimFlow(wasmReady: boolean) {
         // Use default
       }
 
+      // The deposit's path up to that root, from every commitment in the pool
+      const leavesRes = await fetch(`${RELAYER_URL}/pool-leaves?pool=${poolAddress}`);
+      if (!leavesRes.ok) throw new Error('Failed to fetch pool leaves');
+      const { leaves } = await leavesRes.json();
+      const merklePath = generate_merkle_path(leaves, params.leafIndex);
+      if (merklePath === undefined) throw new Error('Deposit is not in the pool tree yet');
+
       // Generate proof
       // Never log identifiers/passwords/commitments in production builds.
       if (import.meta.env.DEV) {
@@ -111,6 +118,7 @@ export function useClaimFlow(wasmReady: boolean) {
         params.password,
         params.leafIndex,
         merkleRoot,
+        merklePath,
         recipientHex,
       );
 
@@ -138,22 +146,26 @@ export function useClaimFlow(wasmReady: boolean) {
       setStage('verifying');
       const recipientATA = recipientAta;
 
-      const response = await fetch(`${RELAYER_URL}/claim`, {
+      const submit = () => fetch(`${RELAYER_URL}/claim`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({
-          proof: proofResult.proof,
-          commitment: proofResult.commitment,
-          nullifier: proofResult.nullifier,
+          bundle: { ...proofResult, pool: poolAddress },
           recipientTokenAccount: recipientATA.toBase58(),
           recipientWallet: publicKey.toBase58(),
-          poolAddress,
-          leafIndex: params.leafIndex,
           feeBps: 50,
         }),
       });
 
-      if (!response.ok) {
+      // 202 = this claim is already being processed (e.g. after a timeout);
+      // resubmitting the same bundle returns its status without re-uploading
+      let response = await submit();
+      for (let polls = 0; response.status === 202 && polls < 20; polls++) {
+        await new Promise(r => setTimeout(r, 7000)); // stay under the claim rate limit
+        response = await submit();
+      }
+
+      if (!response.ok || response.status === 202) {
         throw new Error('Claim failed — try again');
       }
 
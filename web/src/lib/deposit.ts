@@ -12,56 +12,16 @@ import {
   createAssociatedTokenAccountInstruction,
   createSyncNativeInstruction,
 } from '@solana/spl-token';
-import { keccak256 } from 'js-sha3';
 import { PROGRAM_ID, TOKEN_DECIMALS } from './constants';
-
-const M31_PRIME = 0x7FFFFFFF;
-
-/**
- * Hash password to M31 field element (matches WASM prover)
- */
-function hashPassword(password: string): number {
-  const data = new TextEncoder().encode('murkl_password_v1' + password);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  const val = view.getUint32(0, true); // little-endian
-  return val % M31_PRIME;
-}
-
-/**
- * Hash identifier to M31 field element (matches WASM prover)
- */
-function hashIdentifier(id: string): number {
-  const normalized = id.toLowerCase();
-  const data = new TextEncoder().encode('murkl_identifier_v1' + normalized);
-  const hash = keccak256.arrayBuffer(data);
-  const view = new DataView(hash);
-  const val = view.getUint32(0, true);
-  return val % M31_PRIME;
-}
+import { generate_commitment } from '../wasm/murkl_wasm';
 
 /**
- * Compute commitment (matches WASM prover)
+ * Compute commitment: the Poseidon2 digest the WASM prover proves against
+ * (the module is initialized in App.tsx before any deposit)
  */
 export function computeCommitment(identifier: string, password: string): Uint8Array {
-  const idHash = hashIdentifier(identifier);
-  const secret = hashPassword(password);
-  
-  // Build commitment input
-  const prefix = new TextEncoder().encode('murkl_m31_hash_v1');
-  const idBuf = new Uint8Array(4);
-  const secretBuf = new Uint8Array(4);
-  
-  new DataView(idBuf.buffer).setUint32(0, idHash, true);
-  new DataView(secretBuf.buffer).setUint32(0, secret, true);
-  
-  const combined = new Uint8Array(prefix.length + 4 + 4);
-  combined.set(prefix, 0);
-  combined.set(idBuf, prefix.length);
-  combined.set(secretBuf, prefix.length + 4);
-  
-  const hashArray = keccak256.arrayBuffer(combined);
-  return new Uint8Array(hashArray);
+  const hex = generate_commitment(identifier, password);
+  return Uint8Array.from(hex.match(/../g) ?? [], (byte) => parseInt(byte, 16));
 }
 
 /**
@@ -166,6 +126,14 @@ export async function buildDepositTransaction(
     PROGRAM_ID
   );
   
+  // Pools that track statistics require their PoolStats PDA; others get the
+  // program ID in its place
+  const [poolStatsPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from('pool-stats'), pool.toBuffer()],
+    PROGRAM_ID
+  );
+  const poolStats = (await connection.getAccountInfo(poolStatsPda)) ? poolStatsPda : PROGRAM_ID;
+
   // Build instruction data: discriminator (8) + amount (8) + commitment (32)
   const discriminator = await getDiscriminator('deposit');
   const amountLamports = BigInt(Math.floor(amount * Math.pow(10, TOKEN_DECIMALS)));
@@ -207,7 +175,7 @@ export async function buildDepositTransaction(
   
   // Add deposit instruction
   // Account order must match on-chain Deposit struct:
-  // pool, pool_merkle, deposit, vault, depositor, depositor_token, token_program, system_program
+  // pool, pool_merkle, deposit, vault, depositor, depositor_token, token_program, system_program, pool_stats
   const ix = new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
@@ -219,6 +187,7 @@ export async function buildDepositTransaction(
       { pubkey: userAta, isSigner: false, isWritable: true },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: new PublicKey('11111111111111111111111111111111'), isSigner: false, isWritable: false },
+      { pubkey: poolStats, isSigner: false, isWritable: !poolStats.equals(PROGRAM_ID) },
     ],
     data: instructionData
   });
@@ -0,0 +1,46 @@
+import { describe, expect, it } from 'vitest';
+import { runKeyStretch, StretchAbortedError, type KeyStretchHandle } from './keyStretch';
+
+/** Stand-in for the wasm class with the same stepping semantics */
+class FakeStretch implements KeyStretchHandle {
+  done = 0;
+  cancelled = false;
+  constructor(private rounds: number) {}
+  step(maxRounds: number): number {
+    if (!this.cancelled) this.done = Math.min(this.rounds, this.done + maxRounds);
+    return this.rounds === 0 ? 1 : this.done / this.rounds;
+  }
+  is_done(): boolean {
+    return !this.cancelled && this.done === this.rounds;
+  }
+  cancel(): void {
+    this.cancelled = true;
+  }
+}
+
+describe('runKeyStretch', () => {
+  it('runs in chunks and reports progress', async () => {
+    const progress: number[] = [];
+    const stretch = await runKeyStretch(new FakeStretch(10), {
+      chunkRounds: 4,
+      onProgress: (p) => progress.push(p),
+    });
+    expect(stretch.done).toBe(10);
+    expect(progress).toEqual([0.4, 0.8, 1, 1]);
+  });
+
+  it('cancels the stretch when the signal aborts', async () => {
+    const controller = new AbortController();
+    const stretch = new FakeStretch(100);
+    const run = runKeyStretch(stretch, {
+      signal: controller.signal,
+      chunkRounds: 10,
+      onProgress: (p) => {
+        if (p >= 0.3) controller.abort();
+      },
+    });
+    await expect(run).rejects.toBeInstanceOf(StretchAbortedError);
+    expect(stretch.cancelled).toBe(true);
+    expect(stretch.done).toBe(30);
+  });
+});
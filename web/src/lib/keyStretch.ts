@@ -0,0 +1,51 @@
+/**
+ * Drive wasm key stretching without freezing the page.
+ *
+ * `KeyStretch` (murkl-wasm) does its rounds synchronously on the calling
+ * thread. Running them in small chunks and yielding to the event loop in
+ * between keeps the UI responsive, lets us report progress, and lets an
+ * AbortSignal stop the work between chunks.
+ */
+
+/** The subset of the wasm `KeyStretch` class this driver uses. */
+export interface KeyStretchHandle {
+  step(maxRounds: number): number;
+  is_done(): boolean;
+  cancel(): void;
+}
+
+export interface StretchOptions {
+  signal?: AbortSignal;
+  /** Called after every chunk with progress in [0, 1] */
+  onProgress?: (progress: number) => void;
+  /** Rounds per chunk (default: 2000) */
+  chunkRounds?: number;
+}
+
+export class StretchAbortedError extends Error {
+  constructor() {
+    super('Key stretching was cancelled');
+    this.name = 'AbortError';
+  }
+}
+
+const yieldToEventLoop = () => new Promise<void>((resolve) => setTimeout(resolve, 0));
+
+/**
+ * Run `stretch` to completion in chunks. Rejects with `StretchAbortedError`
+ * (and cancels the stretch) if `signal` aborts first.
+ */
+export async function runKeyStretch<T extends KeyStretchHandle>(stretch: T, opts: StretchOptions = {}): Promise<T> {
+  const { signal, onProgress, chunkRounds = 2000 } = opts;
+
+  while (!stretch.is_done()) {
+    if (signal?.aborted) {
+      stretch.cancel();
+      throw new StretchAbortedError();
+    }
+    onProgress?.(stretch.step(chunkRounds));
+    await yieldToEventLoop();
+  }
+  onProgress?.(1);
+  return stretch;
+}